@@ -7,7 +7,7 @@ use std::io::Read;
 use clap::Parser;
 use rustyline::error::ReadlineError;
 use rustyline::{DefaultEditor, Result};
-use v8::Interpreter;
+use v8::{HandleScope, InspectOptions, Interpreter, Local, Object, StdoutConsole, Value};
 
 use crate::utils::Args;
 
@@ -18,23 +18,38 @@ fn main() -> Result<()> {
     let args = Args::parse();
     let mut rl = DefaultEditor::new()?;
     let mut ctrl_c_once = false;
-    let mut interpreter = Interpreter::new().setup();
+    // output goes straight to the terminal instead of being collected
+    let interpreter = Interpreter::new().with_console(StdoutConsole);
+    let mut interpreter = match args.commonjs {
+        true => interpreter.with_commonjs().setup(),
+        false => interpreter.setup(),
+    };
+    if let Some(seed) = args.seed {
+        interpreter.set_random_seed(seed);
+    }
 
     // we're in file land, don't need the repl
     if args.path.is_some() {
         let path = args.path.unwrap();
         let extension = path.extension();
-        match extension {
-            Some(ext) => {
-                if ext != "js" {
-                    // we dunno what this is so just fail out
-                    std::process::exit(1)
-                }
-            }
-            None => {
+        let (is_module, is_commonjs) = match extension {
+            Some(ext) if ext == "js" => (args.module, args.commonjs),
+            Some(ext) if ext == "mjs" => (true, false),
+            Some(ext) if ext == "cjs" => (false, true),
+            _ => {
                 // we dunno what this is so just fail out
                 std::process::exit(1)
             }
+        };
+        if is_module || is_commonjs {
+            // the loader reads the entry point and everything it imports
+            let path = std::path::absolute(&path).unwrap_or(path);
+            match is_module {
+                true => interpreter.run_module(&path.to_string_lossy()),
+                false => interpreter.run_commonjs(&path.to_string_lossy()),
+            };
+            interpreter.run_event_loop();
+            std::process::exit(exit_code(&interpreter));
         }
         let mut file =
             File::open(&path).unwrap_or_else(|_| panic!("Cannot find module {:?}", &path));
@@ -44,15 +59,14 @@ fn main() -> Result<()> {
             // we dunno what this is so just fail out
             std::process::exit(1);
         }
-        // we have a valid js file that's been read into a string
-        let (out, err) = interpreter.interpret(&source).unwrap();
-        if out.len() > 0 {
-            println!("{out}");
-        }
-        if err.len() > 0 {
-            eprintln!("{err}");
+        // we have a valid js file that's been read into a string, the errors in it have been
+        // reported by the time this fails
+        if interpreter.interpret(&source).is_err() {
+            std::process::exit(1);
         }
-        std::process::exit(0);
+        // keep going until every timer has fired
+        interpreter.run_event_loop();
+        std::process::exit(exit_code(&interpreter));
     }
     println!("Welcome to v8 0.0.1");
 
@@ -62,6 +76,8 @@ fn main() -> Result<()> {
     } else {
         'repl: loop {
             let readline = rl.readline("> ");
+            // timers that came due while waiting for input go first, like they would in node
+            interpreter.run_until_idle();
             match readline {
                 Ok(line) => {
                     if line == ".exit" || line == "exit()" {
@@ -72,13 +88,21 @@ fn main() -> Result<()> {
                     } else {
                         line
                     };
-                    let (out, err) = interpreter.interpret(&line).unwrap();
-                    if out.len() > 0 {
-                        print!("{out}");
-                    }
-                    if err.len() > 0 {
-                        eprint!("{err}");
+                    // results are echoed the way node's repl shows them
+                    let mut scope = HandleScope::new(&mut interpreter);
+                    let options = InspectOptions {
+                        colors: true,
+                        ..InspectOptions::default()
+                    };
+                    match scope.eval(&line) {
+                        Ok(value) => println!("{}", scope.inspect(&value, &options)),
+                        Err(error) => {
+                            eprintln!("Uncaught {}", describe_error(&mut scope, &error, &options))
+                        }
                     }
+                    drop(scope);
+                    // timers the line set up that are due already, the rest run on a later line
+                    interpreter.run_until_idle();
                 }
                 Err(ReadlineError::Interrupted) => {
                     if ctrl_c_once {
@@ -100,3 +124,35 @@ fn main() -> Result<()> {
     }
     Ok(())
 }
+
+/// Like node, a program that left an error uncaught fails.
+fn exit_code(interpreter: &Interpreter) -> i32 {
+    match interpreter.uncaught_errors() {
+        0 => 0,
+        _ => 1,
+    }
+}
+
+/// `name: message` for errors, anything else thrown inspected.
+fn describe_error<'i>(
+    scope: &mut HandleScope<'i>,
+    error: &Local<'i, Value>,
+    options: &InspectOptions,
+) -> String {
+    if let Some(object) = error.clone().cast::<Object>() {
+        let name = scope
+            .get(&object, "name")
+            .ok()
+            .filter(|name| !name.is_undefined());
+        let message = scope
+            .get(&object, "message")
+            .ok()
+            .filter(|message| !message.is_undefined());
+        if let (Some(name), Some(message)) = (name, message) {
+            let name = scope.to_rust_string(&name).unwrap_or_default();
+            let message = scope.to_rust_string(&message).unwrap_or_default();
+            return format!("{name}: {message}");
+        }
+    }
+    scope.inspect(error, options)
+}
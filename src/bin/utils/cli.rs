@@ -10,6 +10,18 @@ pub struct Args {
     #[arg(long)]
     pub debugger: bool,
 
+    /// run the file as an ES module (implied for .mjs files)
+    #[arg(long)]
+    pub module: bool,
+
+    /// run the file as CommonJS with require and module.exports (implied for .cjs files)
+    #[arg(long)]
+    pub commonjs: bool,
+
+    /// seed for Math.random, to make runs reproducible
+    #[arg(long)]
+    pub seed: Option<u64>,
+
     /// path to file we're running
     pub path: Option<PathBuf>,
 }
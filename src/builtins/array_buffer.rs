@@ -0,0 +1,485 @@
+//! The `ArrayBuffer` constructor and `ArrayBuffer.prototype`. A buffer owns its bytes; typed
+//! arrays and DataViews only keep the buffer's id and read and write through it.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        collection::{prototype_from_new_target, this_collection},
+        object::{add_getter, add_methods, add_to_string_tag},
+        relative_index, species_constructor, to_index,
+    },
+    constants::ARRAY_BUFFER_NAME,
+    debug_value,
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectId, ObjectKind, Property,
+        PropertyKey, WellKnownSymbol, get_property_value, link_constructor_and_prototype,
+    },
+};
+
+/// The largest buffer scripts may allocate, so a bogus length throws instead of taking the
+/// process down.
+const MAX_BYTE_LENGTH: usize = 1 << 32;
+
+/// [[ArrayBufferData]] and [[ArrayBufferMaxByteLength]].
+#[derive(Clone, Debug)]
+pub struct ArrayBufferState {
+    /// The bytes, `None` once the buffer is detached.
+    data: Option<Vec<u8>>,
+    /// How far `resize` may grow the buffer, `None` for fixed-length buffers.
+    max_byte_length: Option<usize>,
+}
+
+impl ArrayBufferState {
+    pub fn new(data: Vec<u8>, max_byte_length: Option<usize>) -> Self {
+        Self {
+            data: Some(data),
+            max_byte_length,
+        }
+    }
+
+    /// The current length, 0 for a detached buffer.
+    pub fn byte_length(&self) -> usize {
+        self.data.as_ref().map_or(0, Vec::len)
+    }
+
+    pub fn is_detached(&self) -> bool {
+        self.data.is_none()
+    }
+
+    pub fn is_resizable(&self) -> bool {
+        self.max_byte_length.is_some()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.data.as_deref().unwrap_or_default()
+    }
+
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        self.data.as_deref_mut().unwrap_or_default()
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-detacharraybuffer
+    /// Takes the bytes out, leaving the buffer detached.
+    pub fn detach(&mut self) -> Option<Vec<u8>> {
+        self.data.take()
+    }
+}
+
+/// Builds the global `ArrayBuffer` with its prototype.
+pub fn create_array_buffer_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id = JSObject::new_native_function(
+        ARRAY_BUFFER_NAME,
+        1,
+        array_buffer_call,
+        Some(array_buffer_construct),
+        interpreter,
+    );
+    let statics: Vec<(&str, usize, NativeFunction)> = vec![("isView", 1, array_buffer_is_view)];
+    add_methods(constructor_id, statics, interpreter);
+    let species = PropertyKey::well_known(WellKnownSymbol::Species);
+    add_getter(constructor_id, species, array_buffer_species, interpreter);
+
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("resize", 1, array_buffer_resize),
+            ("slice", 2, array_buffer_slice),
+            ("transfer", 0, array_buffer_transfer),
+            (
+                "transferToFixedLength",
+                0,
+                array_buffer_transfer_to_fixed_length,
+            ),
+        ],
+        interpreter,
+    );
+    let getters: Vec<(&str, NativeFunction)> = vec![
+        ("byteLength", array_buffer_byte_length),
+        ("detached", array_buffer_detached),
+        ("maxByteLength", array_buffer_max_byte_length),
+        ("resizable", array_buffer_resizable),
+    ];
+    for (name, getter) in getters {
+        add_getter(
+            prototype_id,
+            get_or_intern_string(name),
+            getter,
+            interpreter,
+        );
+    }
+    add_to_string_tag(prototype_id, ARRAY_BUFFER_NAME, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.array_buffer_proto_id = prototype_id;
+    interpreter.intrinsics.array_buffer_constructor_id = constructor_id;
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(ARRAY_BUFFER_NAME), value)
+}
+
+/// Wraps `data` in a fixed-length ArrayBuffer without copying it.
+pub fn new_array_buffer(interpreter: &mut Interpreter, data: Vec<u8>) -> ObjectId {
+    let state = ArrayBufferState::new(data, None);
+    let prototype = interpreter.intrinsics.array_buffer_proto_id;
+    JSObject::new_object_with_data(
+        ObjectData::ArrayBuffer(Box::new(state)),
+        Some(prototype),
+        interpreter,
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-allocatearraybuffer
+/// `byte_length` zeroed bytes, or the RangeError engines give when that is too much.
+pub fn allocate_bytes(byte_length: usize) -> JSResult<Vec<u8>> {
+    if byte_length > MAX_BYTE_LENGTH {
+        return Err(JSError::new_range_error("Array buffer allocation failed"));
+    }
+    Ok(vec![0; byte_length])
+}
+
+/// The state of the ArrayBuffer `object_id`, `None` for any other object.
+pub fn array_buffer_state(
+    interpreter: &Interpreter,
+    object_id: ObjectId,
+) -> Option<&ArrayBufferState> {
+    match interpreter.get_object(object_id).ok()?.get_data() {
+        Some(ObjectData::ArrayBuffer(state)) => Some(state),
+        _ => None,
+    }
+}
+
+pub fn array_buffer_state_mut(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+) -> Option<&mut ArrayBufferState> {
+    match interpreter.get_object_mut(object_id).ok()?.get_data_mut() {
+        Some(ObjectData::ArrayBuffer(state)) => Some(state),
+        _ => None,
+    }
+}
+
+/// The id of the ArrayBuffer `value` is, if it is one.
+pub fn as_array_buffer(interpreter: &Interpreter, value: &JSValue) -> Option<ObjectId> {
+    match value {
+        JSValue::Object { object_id, .. } => {
+            array_buffer_state(interpreter, *object_id).map(|_| *object_id)
+        }
+        _ => None,
+    }
+}
+
+fn array_buffer_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error(
+        "Constructor ArrayBuffer requires 'new'",
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-arraybuffer-length
+fn array_buffer_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let message = "Invalid array buffer length";
+    let byte_length = to_index(interpreter, &argument(args, 0), message)? as usize;
+    // https://262.ecma-international.org/15.0/index.html#sec-getarraybuffermaxbytelengthoption
+    let options = argument(args, 1);
+    let max_byte_length = match options.is_object() {
+        true => {
+            let key = get_or_intern_string("maxByteLength");
+            match get_property_value(interpreter, &options, key)? {
+                JSValue::Undefined => None,
+                max => Some(to_index(
+                    interpreter,
+                    &max,
+                    "Invalid array buffer max length",
+                )?),
+            }
+        }
+        false => None,
+    };
+    let max_byte_length = max_byte_length.map(|max| max as usize);
+    if max_byte_length.is_some_and(|max| byte_length > max || max > MAX_BYTE_LENGTH) {
+        return Err(JSError::new_range_error("Invalid array buffer max length"));
+    }
+    let fallback = interpreter.intrinsics.array_buffer_proto_id;
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    let state = ArrayBufferState::new(allocate_bytes(byte_length)?, max_byte_length);
+    let object_id = JSObject::new_object_with_data(
+        ObjectData::ArrayBuffer(Box::new(state)),
+        Some(prototype),
+        interpreter,
+    );
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-arraybuffer.isview
+fn array_buffer_is_view(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let is_view = match argument(args, 0) {
+        JSValue::Object { object_id, .. } => matches!(
+            interpreter.get_object(object_id)?.get_data(),
+            Some(ObjectData::TypedArray(_) | ObjectData::DataView(_))
+        ),
+        _ => false,
+    };
+    Ok(JSValue::new_boolean(is_view))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-arraybuffer-@@species
+fn array_buffer_species(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this.clone())
+}
+
+/// The id of the ArrayBuffer `this` is, or the receiver error of `method`.
+fn this_array_buffer(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<ObjectId> {
+    this_collection(
+        interpreter,
+        this,
+        &format!("ArrayBuffer.prototype.{method}"),
+        |data| matches!(data, ObjectData::ArrayBuffer(_)),
+    )
+}
+
+/// The state of a buffer `this_array_buffer` has vouched for.
+fn buffer_state(
+    interpreter: &mut Interpreter,
+    buffer_id: ObjectId,
+) -> JSResult<&mut ArrayBufferState> {
+    array_buffer_state_mut(interpreter, buffer_id)
+        .ok_or_else(|| JSError::new_type_error("object is not an ArrayBuffer"))
+}
+
+fn detached_error(method: &str) -> JSError {
+    JSError::new_type_error(&format!(
+        "Cannot perform ArrayBuffer.prototype.{method} on a detached ArrayBuffer"
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-arraybuffer.prototype.bytelength
+fn array_buffer_byte_length(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let buffer_id = this_array_buffer(interpreter, this, "byteLength")?;
+    let byte_length = buffer_state(interpreter, buffer_id)?.byte_length();
+    Ok(JSValue::new_number(&(byte_length as f64)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-arraybuffer.prototype.detached
+fn array_buffer_detached(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let buffer_id = this_array_buffer(interpreter, this, "detached")?;
+    let detached = buffer_state(interpreter, buffer_id)?.is_detached();
+    Ok(JSValue::new_boolean(detached))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-arraybuffer.prototype.maxbytelength
+fn array_buffer_max_byte_length(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let buffer_id = this_array_buffer(interpreter, this, "maxByteLength")?;
+    let state = buffer_state(interpreter, buffer_id)?;
+    let max_byte_length = match state.is_detached() {
+        true => 0,
+        false => state.max_byte_length.unwrap_or(state.byte_length()),
+    };
+    Ok(JSValue::new_number(&(max_byte_length as f64)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-arraybuffer.prototype.resizable
+fn array_buffer_resizable(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let buffer_id = this_array_buffer(interpreter, this, "resizable")?;
+    let resizable = buffer_state(interpreter, buffer_id)?.is_resizable();
+    Ok(JSValue::new_boolean(resizable))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-arraybuffer.prototype.resize
+/// Grows or shrinks a resizable buffer in place. New bytes are zero, and views over it see
+/// the new length on their next access.
+fn array_buffer_resize(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let buffer_id = this_array_buffer(interpreter, this, "resize")?;
+    if !buffer_state(interpreter, buffer_id)?.is_resizable() {
+        let receiver = debug_value(interpreter, this);
+        return Err(JSError::new_type_error(&format!(
+            "Method ArrayBuffer.prototype.resize called on incompatible receiver {receiver}"
+        )));
+    }
+    let message = "ArrayBuffer.prototype.resize: Invalid length parameter";
+    let new_length = to_index(interpreter, &argument(args, 0), message)? as usize;
+    let state = buffer_state(interpreter, buffer_id)?;
+    let (Some(data), Some(max)) = (&mut state.data, state.max_byte_length) else {
+        return Err(detached_error("resize"));
+    };
+    if new_length > max {
+        return Err(JSError::new_range_error(message));
+    }
+    data.resize(new_length, 0);
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-arraybuffer.prototype.slice
+/// Copies `start..end` into a new buffer made by the species constructor.
+fn array_buffer_slice(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let buffer_id = this_array_buffer(interpreter, this, "slice")?;
+    if buffer_state(interpreter, buffer_id)?.is_detached() {
+        return Err(detached_error("slice"));
+    }
+    let length = buffer_state(interpreter, buffer_id)?.byte_length();
+    let start = argument(args, 0)
+        .to_integer_or_infinity(interpreter)?
+        .get_number();
+    let first = relative_index(start, length);
+    let last = match argument(args, 1) {
+        JSValue::Undefined => length,
+        end => relative_index(
+            end.to_integer_or_infinity(interpreter)?.get_number(),
+            length,
+        ),
+    };
+    let new_length = last.saturating_sub(first);
+
+    let default = JSValue::Object {
+        object_id: interpreter.intrinsics.array_buffer_constructor_id,
+        kind: ObjectKind::Function,
+    };
+    let constructor = species_constructor(interpreter, this, default)?;
+    let new_length_value = JSValue::new_number(&(new_length as f64));
+    let new = interpreter.construct(&constructor, &[new_length_value])?;
+    let Some(new_id) = as_array_buffer(interpreter, &new) else {
+        return Err(JSError::new_type_error(
+            "ArrayBuffer subclass returned this from species constructor",
+        ));
+    };
+    if new_id == buffer_id {
+        return Err(JSError::new_type_error(
+            "ArrayBuffer subclass returned this from species constructor",
+        ));
+    }
+    let new_state = buffer_state(interpreter, new_id)?;
+    if new_state.is_detached() || new_state.byte_length() < new_length {
+        return Err(JSError::new_type_error(
+            "Species constructor returned a too small or detached ArrayBuffer",
+        ));
+    }
+    // the species constructor may have detached or shrunk this buffer
+    let state = buffer_state(interpreter, buffer_id)?;
+    if state.is_detached() {
+        return Err(detached_error("slice"));
+    }
+    let current_length = state.byte_length();
+    let bytes = state
+        .bytes()
+        .get(first.min(current_length)..last.min(current_length))
+        .unwrap_or_default()
+        .to_vec();
+    buffer_state(interpreter, new_id)?.bytes_mut()[..bytes.len()].copy_from_slice(&bytes);
+    Ok(new)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-arraybuffer.prototype.transfer
+fn array_buffer_transfer(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    transfer(interpreter, this, args, "transfer", true)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-arraybuffer.prototype.transfertofixedlength
+fn array_buffer_transfer_to_fixed_length(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    transfer(interpreter, this, args, "transferToFixedLength", false)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-arraybuffercopyanddetach
+/// Moves the bytes into a new buffer of the requested length and detaches this one. The
+/// bytes aren't copied, only grown or truncated.
+fn transfer(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+    method: &str,
+    preserve_resizability: bool,
+) -> JSResult<JSValue> {
+    let buffer_id = this_array_buffer(interpreter, this, method)?;
+    let new_length = match argument(args, 0) {
+        JSValue::Undefined => buffer_state(interpreter, buffer_id)?.byte_length(),
+        length => to_index(interpreter, &length, "Invalid array buffer length")? as usize,
+    };
+    let state = buffer_state(interpreter, buffer_id)?;
+    let max_byte_length = match preserve_resizability {
+        true => state.max_byte_length,
+        false => None,
+    };
+    if max_byte_length.is_some_and(|max| new_length > max) {
+        return Err(JSError::new_range_error(&format!(
+            "ArrayBuffer.prototype.{method}: Invalid length parameter"
+        )));
+    }
+    if new_length > MAX_BYTE_LENGTH {
+        return Err(JSError::new_range_error("Array buffer allocation failed"));
+    }
+    let Some(mut data) = state.detach() else {
+        return Err(detached_error(method));
+    };
+    data.resize(new_length, 0);
+    let prototype = interpreter.intrinsics.array_buffer_proto_id;
+    let state = ArrayBufferState::new(data, max_byte_length);
+    let object_id = JSObject::new_object_with_data(
+        ObjectData::ArrayBuffer(Box::new(state)),
+        Some(prototype),
+        interpreter,
+    );
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
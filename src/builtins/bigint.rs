@@ -0,0 +1,154 @@
+//! The `BigInt` function, its `asIntN`/`asUintN` statics and `BigInt.prototype`.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        object::{add_methods, add_to_string_tag},
+        to_index,
+    },
+    constants::BIGINT_NAME,
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectKind, PreferredType,
+        Property,
+        bigint::{self, as_n_bits, number_to_big_int},
+        link_constructor_and_prototype,
+    },
+};
+
+/// Builds the global `BigInt` function. It converts, but unlike `Number` it can't be called
+/// with `new`.
+pub fn create_bigint_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id =
+        JSObject::new_native_function(BIGINT_NAME, 1, bigint_call, None, interpreter);
+    let prototype = JSValue::new_object(vec![], interpreter);
+    let prototype_id = prototype.get_object_id().unwrap_or_default();
+    add_methods(
+        prototype_id,
+        vec![
+            ("toLocaleString", 0, bigint_to_locale_string),
+            ("toString", 0, bigint_to_string),
+            ("valueOf", 0, bigint_value_of),
+        ],
+        interpreter,
+    );
+    add_to_string_tag(prototype_id, BIGINT_NAME, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.bigint_proto_id = prototype_id;
+
+    let statics: Vec<(&str, usize, NativeFunction)> = vec![
+        ("asIntN", 2, bigint_as_int_n),
+        ("asUintN", 2, bigint_as_uint_n),
+    ];
+    add_methods(constructor_id, statics, interpreter);
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(BIGINT_NAME), value)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-bigint-constructor-number-value
+fn bigint_call(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let value = argument(args, 0);
+    let primitive = value.to_primitive(Some(PreferredType::Number), interpreter)?;
+    let result = match primitive {
+        JSValue::Number { data } => number_to_big_int(data)?,
+        _ => primitive.to_big_int(interpreter)?,
+    };
+    Ok(JSValue::new_big_int(result))
+}
+
+fn as_n(interpreter: &mut Interpreter, args: &[JSValue], signed: bool) -> JSResult<JSValue> {
+    let bits = to_index(
+        interpreter,
+        &argument(args, 0),
+        "Invalid value: not (convertible to) a safe integer",
+    )?;
+    let value = argument(args, 1).to_big_int(interpreter)?;
+    Ok(JSValue::new_big_int(as_n_bits(&value, bits, signed)?))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-bigint.asintn
+fn bigint_as_int_n(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    as_n(interpreter, args, true)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-bigint.asuintn
+fn bigint_as_uint_n(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    as_n(interpreter, args, false)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-thisbigintvalue
+fn this_bigint_value(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<num_bigint::BigInt> {
+    match this {
+        JSValue::BigInt { data } => Ok(*data.clone()),
+        JSValue::Object { object_id, .. }
+            if let Some(ObjectData::BigInt(data)) =
+                interpreter.get_object(*object_id)?.get_data() =>
+        {
+            Ok(*data.clone())
+        }
+        _ => Err(JSError::new_type_error(&format!(
+            "BigInt.prototype.{method} requires that 'this' be a BigInt"
+        ))),
+    }
+}
+
+fn bigint_to_locale_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_bigint_value(interpreter, this, "toLocaleString")?;
+    let string = bigint::to_string(&x, 10);
+    Ok(JSValue::new_string(&get_or_intern_string(&string)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-bigint.prototype.tostring
+fn bigint_to_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_bigint_value(interpreter, this, "toString")?;
+    let radix = match argument(args, 0) {
+        JSValue::Undefined => 10.0,
+        radix => radix.to_integer_or_infinity(interpreter)?.get_number(),
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(JSError::new_range_error(
+            "toString() radix argument must be between 2 and 36",
+        ));
+    }
+    let string = bigint::to_string(&x, radix as u8);
+    Ok(JSValue::new_string(&get_or_intern_string(&string)))
+}
+
+fn bigint_value_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_bigint_value(interpreter, this, "valueOf")?;
+    Ok(JSValue::new_big_int(x))
+}
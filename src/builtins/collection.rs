@@ -0,0 +1,518 @@
+//! What the keyed collections share: the insertion-ordered table behind `Map` and `Set`, the
+//! weakly keyed table behind `WeakMap` and `WeakSet`, the iterators `Map` and `Set` return and
+//! the loop their constructors fill themselves from an iterable with.
+
+use std::{collections::HashMap, ops::Range};
+
+use num_bigint::BigInt;
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument, create_array,
+        iterator::{create_iter_result, get_iterator, iterator_close, iterator_step},
+    },
+    constants::PROTOTYPE_NAME,
+    debug_value,
+    errors::JSError,
+    global::get_or_intern_string,
+    heap::{Slots, Trace, Tracer, WriteLog},
+    values::{JSObject, JSResult, JSValue, ObjectData, ObjectId, ObjectKind, get_property_value},
+};
+
+/// A key as SameValueZero sees it, so equal keys hash alike: NaN equals NaN and -0 equals +0.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum TableKey {
+    Undefined,
+    Null,
+    Boolean(bool),
+    String(SymbolU32),
+    Symbol(usize),
+    Number(u64),
+    BigInt(BigInt),
+    Object(ObjectId),
+}
+
+impl TableKey {
+    fn new(value: &JSValue) -> Self {
+        match value {
+            JSValue::Undefined => TableKey::Undefined,
+            JSValue::Null => TableKey::Null,
+            JSValue::Boolean { data } => TableKey::Boolean(*data),
+            JSValue::String { data } => TableKey::String(*data),
+            JSValue::Symbol { id, .. } => TableKey::Symbol(*id),
+            JSValue::Number { data } if data.is_nan() => TableKey::Number(f64::NAN.to_bits()),
+            JSValue::Number { data } if *data == 0.0 => TableKey::Number(0.0f64.to_bits()),
+            JSValue::Number { data } => TableKey::Number(data.to_bits()),
+            JSValue::BigInt { data } => TableKey::BigInt((**data).clone()),
+            JSValue::Object { object_id, .. } => TableKey::Object(*object_id),
+        }
+    }
+}
+
+/// [[MapData]] and [[SetData]]: entries in insertion order with SameValueZero keys.
+///
+/// Deleting leaves a hole rather than shifting the entries after it, because iterators and
+/// `forEach` loops walk the table by position and have to carry on where they were. The holes
+/// are compacted away once no walk is in progress.
+#[derive(Clone, Debug)]
+pub struct OrderedTable<V> {
+    entries: Vec<Option<(JSValue, V)>>,
+    positions: HashMap<TableKey, usize>,
+    /// Iterators and loops currently walking `entries`.
+    cursors: usize,
+    write_log: WriteLog,
+}
+
+impl<V: Clone> OrderedTable<V> {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            positions: HashMap::new(),
+            cursors: 0,
+            write_log: WriteLog::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn get(&self, key: &JSValue) -> Option<&V> {
+        let position = *self.positions.get(&TableKey::new(key))?;
+        self.entries[position].as_ref().map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &JSValue) -> Option<&mut V> {
+        let position = *self.positions.get(&TableKey::new(key))?;
+        self.write_log.record(position);
+        self.entries[position].as_mut().map(|(_, value)| value)
+    }
+
+    pub fn contains(&self, key: &JSValue) -> bool {
+        self.positions.contains_key(&TableKey::new(key))
+    }
+
+    /// Replaces the value of an existing key in place, otherwise appends the entry. A -0 key
+    /// is stored as +0.
+    pub fn insert(&mut self, key: JSValue, value: V) {
+        let table_key = TableKey::new(&key);
+        if let Some(&position) = self.positions.get(&table_key) {
+            if let Some((_, existing)) = &mut self.entries[position] {
+                *existing = value;
+            }
+            self.write_log.record(position);
+            return;
+        }
+        let key = match key {
+            JSValue::Number { data: 0.0 } => JSValue::new_number(&0.0),
+            key => key,
+        };
+        self.positions.insert(table_key, self.entries.len());
+        self.write_log.record(self.entries.len());
+        self.entries.push(Some((key, value)));
+    }
+
+    pub fn remove(&mut self, key: &JSValue) -> bool {
+        let Some(position) = self.positions.remove(&TableKey::new(key)) else {
+            return false;
+        };
+        self.entries[position] = None;
+        self.compact();
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.compact();
+    }
+
+    /// The first entry at or after `position` and where it was found.
+    pub fn entry_from(&self, position: usize) -> Option<(usize, JSValue, V)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .skip(position)
+            .find_map(|(index, entry)| {
+                let (key, value) = entry.as_ref()?;
+                Some((index, key.clone(), value.clone()))
+            })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&JSValue, &V)> {
+        self.entries
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key, value))
+    }
+
+    pub fn open_cursor(&mut self) {
+        self.cursors += 1;
+    }
+
+    pub fn close_cursor(&mut self) {
+        self.cursors = self.cursors.saturating_sub(1);
+        self.compact();
+    }
+
+    /// Drops the holes once they make up half the table, unless a walk would lose its place.
+    fn compact(&mut self) {
+        let holes = self.entries.len() - self.positions.len();
+        if self.cursors > 0 || holes == 0 || holes < self.positions.len() {
+            return;
+        }
+        self.entries.retain(Option::is_some);
+        self.write_log.record_from(0, self.entries.len());
+        for (position, entry) in self.entries.iter().enumerate() {
+            if let Some((key, _)) = entry {
+                self.positions.insert(TableKey::new(key), position);
+            }
+        }
+    }
+}
+
+impl<V: Trace> Trace for OrderedTable<V> {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.trace_slots(0..self.entries.len(), tracer);
+    }
+}
+
+impl<V: Trace> Slots for OrderedTable<V> {
+    fn slot_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn trace_slots(&self, slots: Range<usize>, tracer: &mut Tracer) {
+        for (key, value) in self.entries[slots].iter().flatten() {
+            key.trace(tracer);
+            value.trace(tracer);
+        }
+    }
+
+    fn write_log(&mut self) -> &mut WriteLog {
+        &mut self.write_log
+    }
+}
+
+/// What a `WeakMap` or `WeakSet` may be keyed by: objects and symbols that aren't in the
+/// `Symbol.for` registry, i.e. values that can be unreachable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WeakKey {
+    Object(ObjectId),
+    Symbol(usize),
+}
+
+impl WeakKey {
+    // https://262.ecma-international.org/15.0/index.html#sec-canbeheldweakly
+    pub fn new(interpreter: &Interpreter, value: &JSValue) -> Option<Self> {
+        match value {
+            JSValue::Object { object_id, .. } => Some(WeakKey::Object(*object_id)),
+            JSValue::Symbol { id, .. } => {
+                let registered = interpreter.symbol_registry.values().any(|s| s == value);
+                (!registered).then_some(WeakKey::Symbol(*id))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// [[WeakMapData]] and [[WeakSetData]]. The table doesn't keep its keys alive: once a key
+/// object is unreachable the collector drops its entry. Heap ids aren't reused, so an entry the
+/// collector hasn't got to yet can't be found through a newer object either.
+#[derive(Clone, Debug)]
+pub struct WeakTable<V> {
+    entries: HashMap<WeakKey, V>,
+}
+
+impl<V> WeakTable<V> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, key: &WeakKey) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    pub fn contains(&self, key: &WeakKey) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: WeakKey, value: V) {
+        self.entries.insert(key, value);
+    }
+
+    pub fn remove(&mut self, key: &WeakKey) -> bool {
+        self.entries.remove(key).is_some()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&WeakKey, &V)> {
+        self.entries.iter()
+    }
+
+    /// Drops the entries whose key object the collector found unreachable.
+    pub fn retain_live(&mut self, is_live: impl Fn(ObjectId) -> bool) {
+        self.entries.retain(|key, _| match key {
+            WeakKey::Object(object_id) => is_live(*object_id),
+            WeakKey::Symbol(_) => true,
+        });
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IterationKind {
+    Keys,
+    Values,
+    Entries,
+}
+
+/// The state of a Map or Set iterator: the collection, or `None` once it is exhausted, and
+/// the position of the next entry.
+#[derive(Clone, Debug)]
+pub struct CollectionIteratorState {
+    collection: Option<ObjectId>,
+    position: usize,
+    kind: IterationKind,
+}
+
+impl Trace for CollectionIteratorState {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(collection) = self.collection {
+            tracer.mark(collection);
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-createmapiterator
+/// An iterator over the Map or Set `collection_id`, the caller has checked it is one.
+pub fn create_collection_iterator(
+    interpreter: &mut Interpreter,
+    collection_id: ObjectId,
+    kind: IterationKind,
+) -> JSResult<JSValue> {
+    let (prototype, data) = {
+        let state = Box::new(CollectionIteratorState {
+            collection: Some(collection_id),
+            position: 0,
+            kind,
+        });
+        match interpreter.get_object_mut(collection_id)?.get_data_mut() {
+            Some(ObjectData::Map(table)) => {
+                table.open_cursor();
+                (
+                    interpreter.intrinsics.map_iterator_proto_id,
+                    ObjectData::MapIterator(state),
+                )
+            }
+            Some(ObjectData::Set(table)) => {
+                table.open_cursor();
+                (
+                    interpreter.intrinsics.set_iterator_proto_id,
+                    ObjectData::SetIterator(state),
+                )
+            }
+            _ => return Err(JSError::new_type_error("object is not a Map or Set")),
+        }
+    };
+    let object_id = JSObject::new_object_with_data(data, Some(prototype), interpreter);
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+/// The entry of a Map or Set at or after `position`, a Set entry's value doubling as its key.
+/// Closes the collection's cursor once there is none.
+pub fn next_entry(
+    interpreter: &mut Interpreter,
+    collection_id: ObjectId,
+    position: usize,
+) -> JSResult<Option<(usize, JSValue, JSValue)>> {
+    let entry = match interpreter.get_object_mut(collection_id)?.get_data_mut() {
+        Some(ObjectData::Map(table)) => {
+            let entry = table.entry_from(position);
+            if entry.is_none() {
+                table.close_cursor();
+            }
+            entry
+        }
+        Some(ObjectData::Set(table)) => {
+            let entry = table.entry_from(position);
+            if entry.is_none() {
+                table.close_cursor();
+            }
+            entry.map(|(position, value, _)| (position, value.clone(), value))
+        }
+        _ => None,
+    };
+    Ok(entry)
+}
+
+/// Stops a walk over a Map or Set before its end, e.g. when a `forEach` callback throws.
+pub fn close_cursor(interpreter: &mut Interpreter, collection_id: ObjectId) -> JSResult<()> {
+    match interpreter.get_object_mut(collection_id)?.get_data_mut() {
+        Some(ObjectData::Map(table)) => table.close_cursor(),
+        Some(ObjectData::Set(table)) => table.close_cursor(),
+        _ => {}
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%mapiteratorprototype%.next
+/// `next` of both `%MapIteratorPrototype%` and `%SetIteratorPrototype%`, `name` tells them
+/// apart in the receiver error.
+pub fn collection_iterator_next(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    name: &str,
+) -> JSResult<JSValue> {
+    let state = match this {
+        JSValue::Object { object_id, .. } => match interpreter.get_object(*object_id)?.get_data() {
+            Some(ObjectData::MapIterator(state)) if name == "Map" => {
+                Some((*object_id, (**state).clone()))
+            }
+            Some(ObjectData::SetIterator(state)) if name == "Set" => {
+                Some((*object_id, (**state).clone()))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some((object_id, state)) = state else {
+        let receiver = debug_value(interpreter, this);
+        return Err(JSError::new_type_error(&format!(
+            "Method {name} Iterator.prototype.next called on incompatible receiver {receiver}"
+        )));
+    };
+    let Some(collection_id) = state.collection else {
+        return Ok(create_iter_result(interpreter, JSValue::Undefined, true));
+    };
+    let entry = next_entry(interpreter, collection_id, state.position)?;
+    let (collection, position) = match &entry {
+        Some((position, _, _)) => (Some(collection_id), position + 1),
+        None => (None, state.position),
+    };
+    if let Some(ObjectData::MapIterator(state) | ObjectData::SetIterator(state)) =
+        interpreter.get_object_mut(object_id)?.get_data_mut()
+    {
+        state.collection = collection;
+        state.position = position;
+    }
+    let Some((_, key, value)) = entry else {
+        return Ok(create_iter_result(interpreter, JSValue::Undefined, true));
+    };
+    let result = match state.kind {
+        IterationKind::Keys => key,
+        IterationKind::Values => value,
+        IterationKind::Entries => create_array(vec![key, value], interpreter),
+    };
+    Ok(create_iter_result(interpreter, result, false))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-add-entries-from-iterable
+/// Feeds an iterable to a freshly made collection through its own `adder` method, as the
+/// constructors do. Map-like collections take `[key, value]` entries, set-like ones values.
+pub fn add_from_iterable(
+    interpreter: &mut Interpreter,
+    target: &JSValue,
+    iterable: &JSValue,
+    adder_name: &str,
+    entries: bool,
+) -> JSResult<()> {
+    if iterable.is_undefined() || iterable.is_null() {
+        return Ok(());
+    }
+    let adder = get_property_value(interpreter, target, get_or_intern_string(adder_name))?;
+    if !interpreter.is_callable(&adder) {
+        let adder = debug_value(interpreter, &adder);
+        let target = debug_value(interpreter, target);
+        return Err(JSError::new_type_error(&format!(
+            "'{adder}' returned for property '{adder_name}' of object '{target}' is not a function"
+        )));
+    }
+    let mut record = get_iterator(interpreter, iterable)?;
+    while let Some(item) = iterator_step(interpreter, &mut record)? {
+        let args = if entries {
+            if !item.is_object() {
+                iterator_close(interpreter, &record)?;
+                let item = debug_value(interpreter, &item);
+                return Err(JSError::new_type_error(&format!(
+                    "Iterator value {item} is not an entry object"
+                )));
+            }
+            let key = get_property_value(interpreter, &item, get_or_intern_string("0"));
+            let value = key.and_then(|key| {
+                let value = get_property_value(interpreter, &item, get_or_intern_string("1"))?;
+                Ok(vec![key, value])
+            });
+            match value {
+                Ok(args) => args,
+                Err(error) => {
+                    iterator_close(interpreter, &record)?;
+                    return Err(error);
+                }
+            }
+        } else {
+            vec![item]
+        };
+        if let Err(error) = interpreter.call_function(&adder, target, &args) {
+            iterator_close(interpreter, &record)?;
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// The id of `this` when `is_kind` accepts its internal slots, otherwise the receiver error of
+/// `method`, e.g. `Map.prototype.get`.
+pub fn this_collection(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+    is_kind: fn(&ObjectData) -> bool,
+) -> JSResult<ObjectId> {
+    if let JSValue::Object { object_id, .. } = this
+        && interpreter
+            .get_object(*object_id)?
+            .get_data()
+            .is_some_and(is_kind)
+    {
+        return Ok(*object_id);
+    }
+    let receiver = debug_value(interpreter, this);
+    Err(JSError::new_type_error(&format!(
+        "Method {method} called on incompatible receiver {receiver}"
+    )))
+}
+
+/// The prototype `new C()` gives its object: `newTarget.prototype`, or `fallback` when that
+/// isn't an object.
+pub fn prototype_from_new_target(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    fallback: ObjectId,
+) -> ObjectId {
+    let prototype_key = get_or_intern_string(PROTOTYPE_NAME);
+    match get_property_value(interpreter, new_target, prototype_key) {
+        Ok(JSValue::Object { object_id, .. }) => object_id,
+        _ => fallback,
+    }
+}
+
+/// The callback and `thisArg` of a `forEach`.
+pub fn for_each_callback(
+    interpreter: &mut Interpreter,
+    args: &[JSValue],
+) -> JSResult<(JSValue, JSValue)> {
+    let callback = argument(args, 0);
+    if !interpreter.is_callable(&callback) {
+        let callback = debug_value(interpreter, &callback);
+        return Err(JSError::new_type_error(&format!(
+            "{callback} is not a function"
+        )));
+    }
+    Ok((callback, argument(args, 1)))
+}
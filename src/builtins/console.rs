@@ -0,0 +1,563 @@
+//! The `console` namespace. Every method turns its arguments into the text Node would print
+//! and hands both to the host's `Console`, see `crate::console`.
+
+use std::collections::HashMap;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        object::{add_methods, add_to_string_tag, own_keys, string_keys},
+    },
+    console::{ConsoleMessage, LogLevel},
+    constants::CONSOLE_NAME,
+    global::{get_or_intern_string, get_string_from_pool},
+    handles::{HandleScope, Local, Value},
+    inspect::{InspectOptions, inspect},
+    values::{self, JSObject, JSResult, JSValue, ObjectKind, Property, get_property_value},
+};
+
+/// What the console methods remember between calls.
+#[derive(Debug, Default)]
+pub struct ConsoleState {
+    /// `console.count` calls by label.
+    counts: HashMap<String, usize>,
+    /// When `console.time` was called, by label, in milliseconds of the event loop's clock.
+    timers: HashMap<String, f64>,
+    group_depth: usize,
+}
+
+pub fn create_console_object(interpreter: &mut Interpreter) -> Property {
+    let proto_id = interpreter.get_object_proto_id();
+    let object_id = JSObject::new_ordinary_object(vec![], true, Some(proto_id), interpreter);
+    add_methods(
+        object_id,
+        vec![
+            ("log", 0, console_log),
+            ("info", 0, console_info),
+            ("warn", 0, console_warn),
+            ("error", 0, console_error),
+            ("debug", 0, console_debug),
+            ("trace", 0, console_trace),
+            ("assert", 0, console_assert),
+            ("count", 0, console_count),
+            ("countReset", 0, console_count_reset),
+            ("time", 0, console_time),
+            ("timeLog", 0, console_time_log),
+            ("timeEnd", 0, console_time_end),
+            ("group", 0, console_group),
+            ("groupCollapsed", 0, console_group),
+            ("groupEnd", 0, console_group_end),
+            ("table", 1, console_table),
+            ("dir", 0, console_dir),
+        ],
+        interpreter,
+    );
+    add_to_string_tag(object_id, CONSOLE_NAME, interpreter);
+    let value = JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    };
+    (get_or_intern_string(CONSOLE_NAME), value)
+}
+
+/// Hands a message to the host's console. `arguments` are what the script passed, `text`
+/// what the default consoles print.
+pub fn write_message(
+    interpreter: &mut Interpreter,
+    level: LogLevel,
+    arguments: &[JSValue],
+    text: &str,
+) {
+    // the console is only missing while it's busy with another message
+    let Some(mut console) = interpreter.console.take() else {
+        return;
+    };
+    let group_depth = interpreter.console_state.group_depth;
+    {
+        let mut scope = HandleScope::new(interpreter);
+        let arguments: Vec<Local<Value>> = arguments
+            .iter()
+            .map(|argument| scope.local(argument.clone()))
+            .collect();
+        let message = ConsoleMessage {
+            level,
+            arguments: &arguments,
+            text,
+            group_depth,
+        };
+        console.message(&mut scope, &message);
+    }
+    interpreter.console = Some(console);
+}
+
+/// A message the interpreter makes up itself, with `text` as its only argument.
+pub fn write_text(interpreter: &mut Interpreter, level: LogLevel, text: &str) {
+    let argument = JSValue::new_string(&get_or_intern_string(text));
+    write_message(interpreter, level, &[argument], text);
+}
+
+fn log(interpreter: &mut Interpreter, level: LogLevel, args: &[JSValue]) -> JSResult<JSValue> {
+    let text = format_arguments(interpreter, args)?;
+    write_message(interpreter, level, args, &text);
+    Ok(JSValue::Undefined)
+}
+
+fn console_log(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    log(interpreter, LogLevel::Log, args)
+}
+
+fn console_info(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    log(interpreter, LogLevel::Info, args)
+}
+
+fn console_warn(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    log(interpreter, LogLevel::Warn, args)
+}
+
+fn console_error(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    log(interpreter, LogLevel::Error, args)
+}
+
+fn console_debug(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    log(interpreter, LogLevel::Debug, args)
+}
+
+fn console_trace(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let text = match format_arguments(interpreter, args)? {
+        text if text.is_empty() => "Trace".to_string(),
+        text => format!("Trace: {text}"),
+    };
+    write_message(interpreter, LogLevel::Trace, args, &text);
+    Ok(JSValue::Undefined)
+}
+
+fn console_assert(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if argument(args, 0).to_boolean() {
+        return Ok(JSValue::Undefined);
+    }
+    let data = args.get(1..).unwrap_or_default();
+    let text = match format_arguments(interpreter, data)? {
+        text if text.is_empty() => "Assertion failed".to_string(),
+        text => format!("Assertion failed: {text}"),
+    };
+    write_message(interpreter, LogLevel::Error, data, &text);
+    Ok(JSValue::Undefined)
+}
+
+/// The label of `count` and `time` and their relatives, "default" when there is none.
+fn label(interpreter: &mut Interpreter, args: &[JSValue]) -> JSResult<String> {
+    match argument(args, 0) {
+        JSValue::Undefined => Ok("default".to_string()),
+        label => {
+            let label = label.to_string(interpreter)?;
+            Ok(get_string_from_pool(&label).unwrap_or_default())
+        }
+    }
+}
+
+/// Node reports misuse of labels as process warnings rather than through the console, but
+/// hosts have nowhere else to see them.
+fn warn(interpreter: &mut Interpreter, text: &str) {
+    write_text(interpreter, LogLevel::Warn, &format!("Warning: {text}"));
+}
+
+fn console_count(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let label = label(interpreter, args)?;
+    let count = interpreter
+        .console_state
+        .counts
+        .entry(label.clone())
+        .or_default();
+    *count += 1;
+    let text = format!("{label}: {count}");
+    write_text(interpreter, LogLevel::Info, &text);
+    Ok(JSValue::Undefined)
+}
+
+fn console_count_reset(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let label = label(interpreter, args)?;
+    match interpreter.console_state.counts.get_mut(&label) {
+        Some(count) => *count = 0,
+        None => warn(interpreter, &format!("Count for '{label}' does not exist")),
+    }
+    Ok(JSValue::Undefined)
+}
+
+fn console_time(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let label = label(interpreter, args)?;
+    if interpreter.console_state.timers.contains_key(&label) {
+        warn(
+            interpreter,
+            &format!("Label '{label}' already exists for console.time()"),
+        );
+        return Ok(JSValue::Undefined);
+    }
+    let now = interpreter.now();
+    interpreter.console_state.timers.insert(label, now);
+    Ok(JSValue::Undefined)
+}
+
+fn console_time_log(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    log_time(interpreter, args, "console.timeLog()", false)
+}
+
+fn console_time_end(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    log_time(interpreter, args, "console.timeEnd()", true)
+}
+
+/// Logs how long ago `console.time` was called for the label, followed by the rest of the
+/// arguments.
+fn log_time(
+    interpreter: &mut Interpreter,
+    args: &[JSValue],
+    method: &str,
+    end: bool,
+) -> JSResult<JSValue> {
+    let label = label(interpreter, args)?;
+    let Some(start) = interpreter.console_state.timers.get(&label).copied() else {
+        warn(
+            interpreter,
+            &format!("No such label '{label}' for {method}"),
+        );
+        return Ok(JSValue::Undefined);
+    };
+    if end {
+        interpreter.console_state.timers.remove(&label);
+    }
+    let data = args.get(1..).unwrap_or_default();
+    let mut text = format!("{label}: {}", format_duration(interpreter.now() - start));
+    for value in data {
+        text.push(' ');
+        text.push_str(&display_value(interpreter, value));
+    }
+    write_message(interpreter, LogLevel::Info, data, &text);
+    Ok(JSValue::Undefined)
+}
+
+/// Milliseconds with up to three decimals, or seconds from one second on.
+fn format_duration(ms: f64) -> String {
+    let round = |value: f64| values::to_string((value * 1000.0).round() / 1000.0, 10);
+    match ms >= 1000.0 {
+        true => format!("{}s", round(ms / 1000.0)),
+        false => format!("{}ms", round(ms)),
+    }
+}
+
+fn console_group(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if !args.is_empty() {
+        log(interpreter, LogLevel::Log, args)?;
+    }
+    interpreter.console_state.group_depth += 1;
+    Ok(JSValue::Undefined)
+}
+
+fn console_group_end(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let depth = &mut interpreter.console_state.group_depth;
+    *depth = depth.saturating_sub(1);
+    Ok(JSValue::Undefined)
+}
+
+fn console_dir(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let value = argument(args, 0);
+    let options = dir_options(interpreter, &argument(args, 1))?;
+    let text = inspect(interpreter, &value, &options);
+    write_message(interpreter, LogLevel::Log, &[value], &text);
+    Ok(JSValue::Undefined)
+}
+
+/// The `depth` of the options `dir` takes, a number or `null` for no limit.
+fn dir_options(interpreter: &mut Interpreter, options: &JSValue) -> JSResult<InspectOptions> {
+    let mut inspect_options = InspectOptions::default();
+    if !options.is_object() {
+        return Ok(inspect_options);
+    }
+    match get_property_value(interpreter, options, get_or_intern_string("depth"))? {
+        JSValue::Null => inspect_options.depth = None,
+        JSValue::Number { data } if data == f64::INFINITY => inspect_options.depth = None,
+        JSValue::Number { data } if data >= 0.0 => inspect_options.depth = Some(data as usize),
+        _ => {}
+    }
+    Ok(inspect_options)
+}
+
+/// Lays the properties of `data` out as rows and those of the rows as columns. Rows that
+/// aren't objects go in a "Values" column; anything but an object is logged as it is.
+fn console_table(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let data = argument(args, 0);
+    if !data.is_object() {
+        return log(interpreter, LogLevel::Log, args);
+    }
+    let filter = match argument(args, 1) {
+        columns if columns.is_object() => Some(column_names(interpreter, &columns)?),
+        _ => None,
+    };
+
+    let mut columns: Vec<String> = vec![];
+    let mut rows: Vec<(String, HashMap<String, String>, Option<String>)> = vec![];
+    for key in string_keys(own_keys(interpreter, &data, true)?) {
+        let index = get_string_from_pool(&key).unwrap_or_default();
+        let row = get_property_value(interpreter, &data, key)?;
+        let is_row_object = row.is_object() && !interpreter.is_callable(&row);
+        if !is_row_object {
+            let value = table_cell(interpreter, &row);
+            rows.push((index, HashMap::new(), Some(value)));
+            continue;
+        }
+        let mut cells = HashMap::new();
+        for key in string_keys(own_keys(interpreter, &row, true)?) {
+            let column = get_string_from_pool(&key).unwrap_or_default();
+            if filter
+                .as_ref()
+                .is_some_and(|filter| !filter.contains(&column))
+            {
+                continue;
+            }
+            let value = get_property_value(interpreter, &row, key)?;
+            let value = table_cell(interpreter, &value);
+            if !columns.contains(&column) {
+                columns.push(column.clone());
+            }
+            cells.insert(column, value);
+        }
+        rows.push((index, cells, None));
+    }
+    if let Some(filter) = filter {
+        columns = filter;
+    }
+
+    let has_values = rows.iter().any(|(_, _, value)| value.is_some());
+    let mut header = vec!["(index)".to_string()];
+    header.extend(columns.iter().cloned());
+    if has_values {
+        header.push("Values".to_string());
+    }
+    let body: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|(index, mut cells, value)| {
+            let mut line = vec![index];
+            line.extend(
+                columns
+                    .iter()
+                    .map(|column| cells.remove(column).unwrap_or_default()),
+            );
+            if has_values {
+                line.push(value.unwrap_or_default());
+            }
+            line
+        })
+        .collect();
+
+    let text = render_table(&header, &body);
+    write_message(interpreter, LogLevel::Log, &[data], &text);
+    Ok(JSValue::Undefined)
+}
+
+fn column_names(interpreter: &mut Interpreter, columns: &JSValue) -> JSResult<Vec<String>> {
+    let mut names = vec![];
+    for key in string_keys(own_keys(interpreter, columns, true)?) {
+        let name = get_property_value(interpreter, columns, key)?;
+        let name = name.to_string(interpreter)?;
+        names.push(get_string_from_pool(&name).unwrap_or_default());
+    }
+    Ok(names)
+}
+
+/// Draws the rows with box-drawing characters, every column as wide as its widest cell plus
+/// a space on either side.
+fn render_table(header: &[String], body: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            body.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].chars().count()])
+                .max()
+                .unwrap_or(0)
+                + 2
+        })
+        .collect();
+    let divider = |left: &str, middle: &str, right: &str| {
+        let lines: Vec<String> = widths.iter().map(|width| "─".repeat(*width)).collect();
+        format!("{left}{}{right}", lines.join(middle))
+    };
+    let row = |cells: &[String]| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| {
+                let padding = width - 1 - cell.chars().count();
+                format!(" {cell}{}", " ".repeat(padding))
+            })
+            .collect();
+        format!("│{}│", cells.join("│"))
+    };
+    let mut lines = vec![divider("┌", "┬", "┐"), row(header), divider("├", "┼", "┤")];
+    lines.extend(body.iter().map(|cells| row(cells)));
+    lines.push(divider("└", "┴", "┘"));
+    lines.join("\n")
+}
+
+/// Node's `util.format`: a string first argument may contain `%s`, `%d`, `%i`, `%f`, `%o`,
+/// `%O` and `%c`, each of which takes the next argument. Whatever is left over is appended,
+/// separated by spaces.
+pub fn format_arguments(interpreter: &mut Interpreter, args: &[JSValue]) -> JSResult<String> {
+    let mut text = String::new();
+    let mut rest = args;
+    if let Some(JSValue::String { data }) = args.first() {
+        let format = get_string_from_pool(data).unwrap_or_default();
+        rest = &args[1..];
+        let mut chars = format.chars().peekable();
+        while let Some(c) = chars.next() {
+            let Some(&specifier) = chars.peek().filter(|_| c == '%') else {
+                text.push(c);
+                continue;
+            };
+            if specifier == '%' {
+                chars.next();
+                text.push('%');
+                continue;
+            }
+            let Some((value, remaining)) = rest.split_first() else {
+                text.push(c);
+                continue;
+            };
+            let formatted = match specifier {
+                's' => string_value(interpreter, value),
+                'd' | 'i' | 'f' => format_number(interpreter, value, specifier == 'i')?,
+                'o' => {
+                    let options = InspectOptions {
+                        depth: Some(4),
+                        ..InspectOptions::default()
+                    };
+                    inspect(interpreter, value, &options)
+                }
+                'O' => inspect(interpreter, value, &InspectOptions::default()),
+                'c' => String::new(),
+                _ => {
+                    text.push(c);
+                    continue;
+                }
+            };
+            chars.next();
+            rest = remaining;
+            text.push_str(&formatted);
+        }
+    }
+    for (index, value) in rest.iter().enumerate() {
+        if index > 0 || rest.len() < args.len() {
+            text.push(' ');
+        }
+        text.push_str(&display_value(interpreter, value));
+    }
+    Ok(text)
+}
+
+/// `%d`, `%i` and `%f`: the argument as a number, BigInts with their suffix.
+fn format_number(
+    interpreter: &mut Interpreter,
+    value: &JSValue,
+    integer: bool,
+) -> JSResult<String> {
+    let number = match value {
+        JSValue::BigInt { .. } => return Ok(display_value(interpreter, value)),
+        JSValue::Symbol { .. } => f64::NAN,
+        value => value.to_number(interpreter)?.get_number(),
+    };
+    let number = if integer { number.trunc() } else { number };
+    Ok(values::to_string(number, 10))
+}
+
+/// How a value is printed on its own: strings as they are, everything else inspected.
+fn display_value(interpreter: &mut Interpreter, value: &JSValue) -> String {
+    match value {
+        JSValue::String { data } => get_string_from_pool(data).unwrap_or_default(),
+        value => inspect(interpreter, value, &InspectOptions::default()),
+    }
+}
+
+/// `%s`: like any other argument, but without looking inside nested objects.
+fn string_value(interpreter: &mut Interpreter, value: &JSValue) -> String {
+    let options = InspectOptions {
+        depth: Some(0),
+        ..InspectOptions::default()
+    };
+    match value {
+        JSValue::Object { .. } => inspect(interpreter, value, &options),
+        value => display_value(interpreter, value),
+    }
+}
+
+/// A cell of `console.table`: on one line, and only the first few elements of arrays.
+fn table_cell(interpreter: &mut Interpreter, value: &JSValue) -> String {
+    let options = InspectOptions {
+        depth: Some(0),
+        break_length: usize::MAX,
+        max_array_length: 3,
+        ..InspectOptions::default()
+    };
+    inspect(interpreter, value, &options)
+}
@@ -0,0 +1,547 @@
+//! The `DataView` constructor and `DataView.prototype`: reads and writes of any element type
+//! at any byte offset of an ArrayBuffer, in either byte order.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        array_buffer::{array_buffer_state, as_array_buffer},
+        collection::{prototype_from_new_target, this_collection},
+        object::{add_getter, add_methods, add_to_string_tag},
+        to_index,
+        typed_array::{TypedArrayKind, get_value_from_buffer, set_value_in_buffer},
+    },
+    constants::DATA_VIEW_NAME,
+    errors::JSError,
+    global::get_or_intern_string,
+    heap::{Trace, Tracer},
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectId, ObjectKind, Property,
+        link_constructor_and_prototype,
+    },
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct DataViewState {
+    buffer: ObjectId,
+    byte_offset: usize,
+    /// `None` for views that track the length of a resizable buffer.
+    byte_length: Option<usize>,
+}
+
+impl Trace for DataViewState {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.mark(self.buffer);
+    }
+}
+
+/// Builds the global `DataView` with its prototype.
+pub fn create_data_view_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id = JSObject::new_native_function(
+        DATA_VIEW_NAME,
+        1,
+        data_view_call,
+        Some(data_view_construct),
+        interpreter,
+    );
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("getBigInt64", 1, data_view_get_big_int64),
+            ("getBigUint64", 1, data_view_get_big_uint64),
+            ("getFloat32", 1, data_view_get_float32),
+            ("getFloat64", 1, data_view_get_float64),
+            ("getInt8", 1, data_view_get_int8),
+            ("getInt16", 1, data_view_get_int16),
+            ("getInt32", 1, data_view_get_int32),
+            ("getUint8", 1, data_view_get_uint8),
+            ("getUint16", 1, data_view_get_uint16),
+            ("getUint32", 1, data_view_get_uint32),
+            ("setBigInt64", 2, data_view_set_big_int64),
+            ("setBigUint64", 2, data_view_set_big_uint64),
+            ("setFloat32", 2, data_view_set_float32),
+            ("setFloat64", 2, data_view_set_float64),
+            ("setInt8", 2, data_view_set_int8),
+            ("setInt16", 2, data_view_set_int16),
+            ("setInt32", 2, data_view_set_int32),
+            ("setUint8", 2, data_view_set_uint8),
+            ("setUint16", 2, data_view_set_uint16),
+            ("setUint32", 2, data_view_set_uint32),
+        ],
+        interpreter,
+    );
+    let getters: Vec<(&str, NativeFunction)> = vec![
+        ("buffer", data_view_buffer),
+        ("byteLength", data_view_byte_length),
+        ("byteOffset", data_view_byte_offset),
+    ];
+    for (name, getter) in getters {
+        add_getter(
+            prototype_id,
+            get_or_intern_string(name),
+            getter,
+            interpreter,
+        );
+    }
+    add_to_string_tag(prototype_id, DATA_VIEW_NAME, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.data_view_proto_id = prototype_id;
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(DATA_VIEW_NAME), value)
+}
+
+fn data_view_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error(
+        "Constructor DataView requires 'new'",
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview-buffer-byteoffset-bytelength
+fn data_view_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let Some(buffer) = as_array_buffer(interpreter, &argument(args, 0)) else {
+        return Err(JSError::new_type_error(
+            "First argument to DataView constructor must be an ArrayBuffer",
+        ));
+    };
+    let message = "Start offset is outside the bounds of the buffer";
+    let byte_offset = to_index(interpreter, &argument(args, 1), message)? as usize;
+    let byte_length = match argument(args, 2) {
+        JSValue::Undefined => None,
+        length => {
+            let message = "Invalid DataView length";
+            Some(to_index(interpreter, &length, message)? as usize)
+        }
+    };
+    // getting the prototype runs script code, which may detach or shrink the buffer, so the
+    // bounds are checked after it
+    let fallback = interpreter.intrinsics.data_view_proto_id;
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    let Some(buffer_state) = array_buffer_state(interpreter, buffer) else {
+        return Err(JSError::new_type_error("object is not an ArrayBuffer"));
+    };
+    if buffer_state.is_detached() {
+        return Err(JSError::new_type_error(
+            "Cannot perform DataView constructor on a detached ArrayBuffer",
+        ));
+    }
+    let buffer_byte_length = buffer_state.byte_length();
+    if byte_offset > buffer_byte_length {
+        return Err(JSError::new_range_error(&format!(
+            "Start offset {byte_offset} is outside the bounds of the buffer"
+        )));
+    }
+    let byte_length = match byte_length {
+        None if buffer_state.is_resizable() => None,
+        None => Some(buffer_byte_length - byte_offset),
+        Some(length) if byte_offset + length > buffer_byte_length => {
+            return Err(JSError::new_range_error(&format!(
+                "Invalid DataView length {length}"
+            )));
+        }
+        Some(length) => Some(length),
+    };
+    let state = DataViewState {
+        buffer,
+        byte_offset,
+        byte_length,
+    };
+    let object_id = JSObject::new_object_with_data(
+        ObjectData::DataView(Box::new(state)),
+        Some(prototype),
+        interpreter,
+    );
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+/// The state of the DataView `this` is, or the receiver error of `method`.
+fn this_data_view(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<DataViewState> {
+    let object_id = this_collection(
+        interpreter,
+        this,
+        &format!("DataView.prototype.{method}"),
+        |data| matches!(data, ObjectData::DataView(_)),
+    )?;
+    match interpreter.get_object(object_id)?.get_data() {
+        Some(ObjectData::DataView(state)) => Ok(**state),
+        _ => Err(JSError::new_type_error("object is not a DataView")),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getviewbytelength
+/// The number of bytes the view covers right now, `None` once its buffer is detached or
+/// has shrunk past the view.
+fn view_byte_length(interpreter: &Interpreter, state: &DataViewState) -> Option<usize> {
+    let buffer = array_buffer_state(interpreter, state.buffer)?;
+    if buffer.is_detached() {
+        return None;
+    }
+    let buffer_byte_length = buffer.byte_length();
+    match state.byte_length {
+        Some(length) => (state.byte_offset + length <= buffer_byte_length).then_some(length),
+        None => (state.byte_offset <= buffer_byte_length)
+            .then(|| buffer_byte_length - state.byte_offset),
+    }
+}
+
+fn out_of_bounds_error(method: &str) -> JSError {
+    JSError::new_type_error(&format!(
+        "Cannot perform DataView.prototype.{method} on a detached ArrayBuffer"
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-dataview.prototype.buffer
+fn data_view_buffer(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_data_view(interpreter, this, "buffer")?;
+    Ok(JSValue::Object {
+        object_id: state.buffer,
+        kind: ObjectKind::Object,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-dataview.prototype.bytelength
+fn data_view_byte_length(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_data_view(interpreter, this, "byteLength")?;
+    let Some(byte_length) = view_byte_length(interpreter, &state) else {
+        return Err(out_of_bounds_error("byteLength"));
+    };
+    Ok(JSValue::new_number(&(byte_length as f64)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-dataview.prototype.byteoffset
+fn data_view_byte_offset(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_data_view(interpreter, this, "byteOffset")?;
+    if view_byte_length(interpreter, &state).is_none() {
+        return Err(out_of_bounds_error("byteOffset"));
+    }
+    Ok(JSValue::new_number(&(state.byte_offset as f64)))
+}
+
+/// Where in the buffer an access of `kind` at `request_index` lands, or the error for an
+/// access outside the view.
+fn view_byte_index(
+    interpreter: &Interpreter,
+    state: &DataViewState,
+    request_index: usize,
+    kind: TypedArrayKind,
+    method: &str,
+) -> JSResult<usize> {
+    let Some(view_size) = view_byte_length(interpreter, state) else {
+        return Err(out_of_bounds_error(method));
+    };
+    if request_index
+        .checked_add(kind.element_size())
+        .is_none_or(|end| end > view_size)
+    {
+        return Err(JSError::new_range_error(
+            "Offset is outside the bounds of the DataView",
+        ));
+    }
+    Ok(state.byte_offset + request_index)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getviewvalue
+/// `get<Type>(byteOffset, littleEndian)`. Without a second argument the value is read big
+/// endian.
+fn get_view_value(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+    kind: TypedArrayKind,
+    method: &str,
+) -> JSResult<JSValue> {
+    let state = this_data_view(interpreter, this, method)?;
+    let message = "Offset is outside the bounds of the DataView";
+    let request_index = to_index(interpreter, &argument(args, 0), message)? as usize;
+    let little_endian = argument(args, 1).to_boolean();
+    let byte_index = view_byte_index(interpreter, &state, request_index, kind, method)?;
+    Ok(get_value_from_buffer(
+        interpreter,
+        state.buffer,
+        byte_index,
+        kind,
+        little_endian,
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-setviewvalue
+/// `set<Type>(byteOffset, value, littleEndian)`, converting `value` before the bounds are
+/// checked.
+fn set_view_value(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+    kind: TypedArrayKind,
+    method: &str,
+) -> JSResult<JSValue> {
+    let state = this_data_view(interpreter, this, method)?;
+    let message = "Offset is outside the bounds of the DataView";
+    let request_index = to_index(interpreter, &argument(args, 0), message)? as usize;
+    let value = kind.to_element(interpreter, &argument(args, 1))?;
+    let little_endian = argument(args, 2).to_boolean();
+    let byte_index = view_byte_index(interpreter, &state, request_index, kind, method)?;
+    let bytes = kind.to_raw_bytes(interpreter, &value, little_endian)?;
+    set_value_in_buffer(interpreter, state.buffer, byte_index, &bytes);
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getbigint64
+fn data_view_get_big_int64(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::BigInt64,
+        "getBigInt64",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getbiguint64
+fn data_view_get_big_uint64(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::BigUint64,
+        "getBigUint64",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getfloat32
+fn data_view_get_float32(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::Float32,
+        "getFloat32",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getfloat64
+fn data_view_get_float64(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::Float64,
+        "getFloat64",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getint8
+fn data_view_get_int8(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(interpreter, this, args, TypedArrayKind::Int8, "getInt8")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getint16
+fn data_view_get_int16(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(interpreter, this, args, TypedArrayKind::Int16, "getInt16")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getint32
+fn data_view_get_int32(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(interpreter, this, args, TypedArrayKind::Int32, "getInt32")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getuint8
+fn data_view_get_uint8(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(interpreter, this, args, TypedArrayKind::Uint8, "getUint8")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getuint16
+fn data_view_get_uint16(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(interpreter, this, args, TypedArrayKind::Uint16, "getUint16")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.getuint32
+fn data_view_get_uint32(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_view_value(interpreter, this, args, TypedArrayKind::Uint32, "getUint32")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setbigint64
+fn data_view_set_big_int64(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::BigInt64,
+        "setBigInt64",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setbiguint64
+fn data_view_set_big_uint64(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::BigUint64,
+        "setBigUint64",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setfloat32
+fn data_view_set_float32(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::Float32,
+        "setFloat32",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setfloat64
+fn data_view_set_float64(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(
+        interpreter,
+        this,
+        args,
+        TypedArrayKind::Float64,
+        "setFloat64",
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setint8
+fn data_view_set_int8(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(interpreter, this, args, TypedArrayKind::Int8, "setInt8")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setint16
+fn data_view_set_int16(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(interpreter, this, args, TypedArrayKind::Int16, "setInt16")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setint32
+fn data_view_set_int32(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(interpreter, this, args, TypedArrayKind::Int32, "setInt32")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setuint8
+fn data_view_set_uint8(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(interpreter, this, args, TypedArrayKind::Uint8, "setUint8")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setuint16
+fn data_view_set_uint16(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(interpreter, this, args, TypedArrayKind::Uint16, "setUint16")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-dataview.prototype.setuint32
+fn data_view_set_uint32(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_view_value(interpreter, this, args, TypedArrayKind::Uint32, "setUint32")
+}
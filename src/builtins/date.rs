@@ -0,0 +1,855 @@
+//! The `Date` constructor and `Date.prototype`. The calendar arithmetic and string formats are
+//! in `crate::date`; this file turns them into methods, converting between UTC and the local
+//! time of the interpreter's time zone.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        collection::prototype_from_new_target,
+        object::{add_method_alias, add_methods},
+    },
+    constants::DATE_NAME,
+    date::{
+        DateFields, MS_PER_DAY, MS_PER_MINUTE, date_string, iso_string, locale_date_string,
+        locale_time_string, make_date, make_day, make_time, parse_date, time_clip, time_string,
+        time_zone_string, utc_string,
+    },
+    debug_value,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectKind, ObjectPropertyBuilder,
+        PreferredType, Property, PropertyKey, WellKnownSymbol, get_property_value,
+        link_constructor_and_prototype, ordinary_to_primitive,
+    },
+};
+
+const INVALID_DATE: &str = "Invalid Date";
+
+/// Builds the global `Date` with its prototype.
+pub fn create_date_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id =
+        JSObject::new_native_function(DATE_NAME, 7, date_call, Some(date_construct), interpreter);
+    let statics: Vec<(&str, usize, NativeFunction)> = vec![
+        ("UTC", 7, date_utc),
+        ("now", 0, date_now),
+        ("parse", 1, date_parse),
+    ];
+    add_methods(constructor_id, statics, interpreter);
+
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("getDate", 0, date_get_date),
+            ("getDay", 0, date_get_day),
+            ("getFullYear", 0, date_get_full_year),
+            ("getHours", 0, date_get_hours),
+            ("getMilliseconds", 0, date_get_milliseconds),
+            ("getMinutes", 0, date_get_minutes),
+            ("getMonth", 0, date_get_month),
+            ("getSeconds", 0, date_get_seconds),
+            ("getTime", 0, date_value_of),
+            ("getTimezoneOffset", 0, date_get_timezone_offset),
+            ("getUTCDate", 0, date_get_utc_date),
+            ("getUTCDay", 0, date_get_utc_day),
+            ("getUTCFullYear", 0, date_get_utc_full_year),
+            ("getUTCHours", 0, date_get_utc_hours),
+            ("getUTCMilliseconds", 0, date_get_utc_milliseconds),
+            ("getUTCMinutes", 0, date_get_utc_minutes),
+            ("getUTCMonth", 0, date_get_utc_month),
+            ("getUTCSeconds", 0, date_get_utc_seconds),
+            ("getYear", 0, date_get_year),
+            ("setDate", 1, date_set_date),
+            ("setFullYear", 3, date_set_full_year),
+            ("setHours", 4, date_set_hours),
+            ("setMilliseconds", 1, date_set_milliseconds),
+            ("setMinutes", 3, date_set_minutes),
+            ("setMonth", 2, date_set_month),
+            ("setSeconds", 2, date_set_seconds),
+            ("setTime", 1, date_set_time),
+            ("setUTCDate", 1, date_set_utc_date),
+            ("setUTCFullYear", 3, date_set_utc_full_year),
+            ("setUTCHours", 4, date_set_utc_hours),
+            ("setUTCMilliseconds", 1, date_set_utc_milliseconds),
+            ("setUTCMinutes", 3, date_set_utc_minutes),
+            ("setUTCMonth", 2, date_set_utc_month),
+            ("setUTCSeconds", 2, date_set_utc_seconds),
+            ("setYear", 1, date_set_year),
+            ("toDateString", 0, date_to_date_string),
+            ("toISOString", 0, date_to_iso_string),
+            ("toJSON", 1, date_to_json),
+            ("toLocaleDateString", 0, date_to_locale_date_string),
+            ("toLocaleString", 0, date_to_locale_string),
+            ("toLocaleTimeString", 0, date_to_locale_time_string),
+            ("toString", 0, date_to_string),
+            ("toTimeString", 0, date_to_time_string),
+            ("toUTCString", 0, date_to_utc_string),
+            ("valueOf", 0, date_value_of),
+        ],
+        interpreter,
+    );
+    // https://262.ecma-international.org/15.0/index.html#sec-date.prototype.togmtstring
+    add_method_alias(
+        prototype_id,
+        "toUTCString",
+        get_or_intern_string("toGMTString"),
+        interpreter,
+    );
+    // read-only, like Symbol.prototype[@@toPrimitive]
+    let key = PropertyKey::well_known(WellKnownSymbol::ToPrimitive);
+    let (_, to_primitive) =
+        JSObject::new_native_fn(&key.function_name(), 1, date_to_primitive, interpreter);
+    let prop = ObjectPropertyBuilder::new(to_primitive)
+        .configurable(true)
+        .build();
+    if let Ok(prototype) = interpreter.get_object_mut(prototype_id) {
+        prototype.define_property(key, prop);
+    }
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.date_proto_id = prototype_id;
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(DATE_NAME), value)
+}
+
+/// The current time value: the host's time origin plus how far the event loop's clock has
+/// moved, so a virtual clock makes `Date.now` advance with `advance_time`.
+fn now(interpreter: &Interpreter) -> f64 {
+    time_clip((interpreter.time_origin + interpreter.timers.clock().now()).floor())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-localtime
+fn local_time(interpreter: &Interpreter, time: f64) -> f64 {
+    time + interpreter.time_zone.offset(time)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-utc-t
+fn utc(interpreter: &Interpreter, local: f64) -> f64 {
+    if !local.is_finite() {
+        return f64::NAN;
+    }
+    local - interpreter.time_zone.offset_from_local(local)
+}
+
+/// `toString` of a valid time value, e.g. `Tue Nov 14 2023 23:13:20 GMT+0100 (CET)`.
+fn to_date_string(interpreter: &Interpreter, time: f64) -> String {
+    let local = local_time(interpreter, time);
+    format!(
+        "{} {} {}",
+        date_string(local),
+        time_string(local),
+        zone_string(interpreter, time)
+    )
+}
+
+fn zone_string(interpreter: &Interpreter, time: f64) -> String {
+    let offset = interpreter.time_zone.offset(time);
+    time_zone_string(offset, &interpreter.time_zone.name(time))
+}
+
+fn new_string(string: &str) -> JSValue {
+    JSValue::new_string(&get_or_intern_string(string))
+}
+
+/// `ToNumber` of each argument in turn.
+fn numbers(interpreter: &mut Interpreter, args: &[JSValue]) -> JSResult<Vec<f64>> {
+    args.iter()
+        .map(|arg| Ok(arg.to_number(interpreter)?.get_number()))
+        .collect()
+}
+
+/// A year of 0 to 99 given to the constructor, `Date.UTC` or `setYear` means 1900 to 1999.
+fn full_year(year: f64) -> f64 {
+    match year.trunc() {
+        short if (0.0..=99.0).contains(&short) => 1900.0 + short,
+        _ => year,
+    }
+}
+
+/// The time value of year, month and the optional date, hours, minutes, seconds and
+/// milliseconds the constructor and `Date.UTC` take, without converting from local time.
+fn time_from_components(components: &[f64]) -> f64 {
+    let component = |index: usize, default: f64| components.get(index).copied().unwrap_or(default);
+    let year = full_year(component(0, f64::NAN));
+    make_date(
+        make_day(year, component(1, 0.0), component(2, 1.0)),
+        make_time(
+            component(3, 0.0),
+            component(4, 0.0),
+            component(5, 0.0),
+            component(6, 0.0),
+        ),
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date-constructor
+/// Without `new`, `Date` ignores its arguments and returns the current time as a string.
+fn date_call(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = now(interpreter);
+    Ok(new_string(&to_date_string(interpreter, time)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date
+fn date_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = match args {
+        [] => now(interpreter),
+        [value] => {
+            let date_value = match value {
+                JSValue::Object { object_id, .. } => {
+                    match interpreter.get_object(*object_id)?.get_data() {
+                        Some(ObjectData::Date(time)) => Some(*time),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            let time = match date_value {
+                Some(time) => time,
+                None => match value.to_primitive(None, interpreter)? {
+                    JSValue::String { data } => {
+                        let string = get_string_from_pool(&data).unwrap_or_default();
+                        parse_date(&string, &interpreter.time_zone)
+                    }
+                    primitive => primitive.to_number(interpreter)?.get_number(),
+                },
+            };
+            time_clip(time)
+        }
+        _ => {
+            let components = numbers(interpreter, &args[..args.len().min(7)])?;
+            let local = time_from_components(&components);
+            time_clip(utc(interpreter, local))
+        }
+    };
+    let fallback = interpreter.intrinsics.date_proto_id;
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    let object_id =
+        JSObject::new_object_with_data(ObjectData::Date(time), Some(prototype), interpreter);
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.now
+fn date_now(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(JSValue::new_number(&now(interpreter)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.parse
+fn date_parse(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let string = argument(args, 0).to_string(interpreter)?;
+    let string = get_string_from_pool(&string).unwrap_or_default();
+    let time = parse_date(&string, &interpreter.time_zone);
+    Ok(JSValue::new_number(&time))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.utc
+fn date_utc(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let components = numbers(interpreter, &args[..args.len().min(7)])?;
+    let time = time_clip(time_from_components(&components));
+    Ok(JSValue::new_number(&time))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-thistimevalue
+fn this_time_value(interpreter: &mut Interpreter, this: &JSValue) -> JSResult<f64> {
+    if let JSValue::Object { object_id, .. } = this
+        && let Some(ObjectData::Date(time)) = interpreter.get_object(*object_id)?.get_data()
+    {
+        return Ok(*time);
+    }
+    Err(JSError::new_type_error("this is not a Date object."))
+}
+
+fn set_time_value(interpreter: &mut Interpreter, this: &JSValue, time: f64) -> JSResult<JSValue> {
+    if let JSValue::Object { object_id, .. } = this
+        && let Some(ObjectData::Date(value)) =
+            interpreter.get_object_mut(*object_id)?.get_data_mut()
+    {
+        *value = time;
+    }
+    Ok(JSValue::new_number(&time))
+}
+
+/// A calendar field of `this`, in local time or UTC, NaN for an invalid date.
+fn get_field(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    local: bool,
+    field: fn(&DateFields) -> f64,
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(JSValue::new_number(&f64::NAN));
+    }
+    let time = if local {
+        local_time(interpreter, time)
+    } else {
+        time
+    };
+    Ok(JSValue::new_number(&field(&DateFields::from_time(time))))
+}
+
+fn date_get_date(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.date)
+}
+
+fn date_get_day(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.week_day)
+}
+
+fn date_get_full_year(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.year)
+}
+
+fn date_get_hours(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.hours)
+}
+
+fn date_get_milliseconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.milliseconds)
+}
+
+fn date_get_minutes(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.minutes)
+}
+
+fn date_get_month(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.month)
+}
+
+fn date_get_seconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.seconds)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.gettimezoneoffset
+/// Minutes from local time to UTC, so positive west of Greenwich.
+fn date_get_timezone_offset(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    let offset = (time - local_time(interpreter, time)) / MS_PER_MINUTE;
+    Ok(JSValue::new_number(&(offset + 0.0)))
+}
+
+fn date_get_utc_date(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.date)
+}
+
+fn date_get_utc_day(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.week_day)
+}
+
+fn date_get_utc_full_year(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.year)
+}
+
+fn date_get_utc_hours(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.hours)
+}
+
+fn date_get_utc_milliseconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.milliseconds)
+}
+
+fn date_get_utc_minutes(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.minutes)
+}
+
+fn date_get_utc_month(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.month)
+}
+
+fn date_get_utc_seconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, false, |fields| fields.seconds)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.getyear
+fn date_get_year(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_field(interpreter, this, true, |fields| fields.year - 1900.0)
+}
+
+/// Where each setter's first argument goes among year, month, date, hours, minutes, seconds
+/// and milliseconds.
+#[derive(Clone, Copy)]
+enum Field {
+    Year = 0,
+    Month = 1,
+    Date = 2,
+    Hours = 3,
+    Minutes = 4,
+    Seconds = 5,
+    Milliseconds = 6,
+}
+
+/// The shared steps of the setters: replaces up to `count` fields starting at `first` with
+/// the arguments, in local time or UTC, and stores the resulting time value. Only
+/// `setFullYear` turns an invalid date into a valid one, starting from +0.
+fn set_fields(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+    local: bool,
+    first: Field,
+    count: usize,
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    let values = numbers(interpreter, &args[..args.len().min(count)])?;
+    let time = match (first, time.is_nan()) {
+        (Field::Year, true) => 0.0,
+        (_, true) => return Ok(JSValue::new_number(&f64::NAN)),
+        (_, false) if local => local_time(interpreter, time),
+        (_, false) => time,
+    };
+    let fields = DateFields::from_time(time);
+    let mut components = [
+        fields.year,
+        fields.month,
+        fields.date,
+        fields.hours,
+        fields.minutes,
+        fields.seconds,
+        fields.milliseconds,
+    ];
+    // a setter called without arguments sets its field to NaN
+    let values = if values.is_empty() {
+        vec![f64::NAN]
+    } else {
+        values
+    };
+    for (offset, value) in values.into_iter().enumerate() {
+        components[first as usize + offset] = value;
+    }
+    let [year, month, date, hours, minutes, seconds, milliseconds] = components;
+    let new_time = make_date(
+        make_day(year, month, date),
+        make_time(hours, minutes, seconds, milliseconds),
+    );
+    let new_time = if local {
+        utc(interpreter, new_time)
+    } else {
+        new_time
+    };
+    set_time_value(interpreter, this, time_clip(new_time))
+}
+
+fn date_set_date(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, true, Field::Date, 1)
+}
+
+fn date_set_full_year(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, true, Field::Year, 3)
+}
+
+fn date_set_hours(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, true, Field::Hours, 4)
+}
+
+fn date_set_milliseconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, true, Field::Milliseconds, 1)
+}
+
+fn date_set_minutes(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, true, Field::Minutes, 3)
+}
+
+fn date_set_month(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, true, Field::Month, 2)
+}
+
+fn date_set_seconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, true, Field::Seconds, 2)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.settime
+fn date_set_time(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    this_time_value(interpreter, this)?;
+    let time = argument(args, 0).to_number(interpreter)?.get_number();
+    set_time_value(interpreter, this, time_clip(time))
+}
+
+fn date_set_utc_date(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, false, Field::Date, 1)
+}
+
+fn date_set_utc_full_year(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, false, Field::Year, 3)
+}
+
+fn date_set_utc_hours(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, false, Field::Hours, 4)
+}
+
+fn date_set_utc_milliseconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, false, Field::Milliseconds, 1)
+}
+
+fn date_set_utc_minutes(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, false, Field::Minutes, 3)
+}
+
+fn date_set_utc_month(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, false, Field::Month, 2)
+}
+
+fn date_set_utc_seconds(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    set_fields(interpreter, this, args, false, Field::Seconds, 2)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.setyear
+fn date_set_year(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    let year = argument(args, 0).to_number(interpreter)?.get_number();
+    let local = match time.is_nan() {
+        true => 0.0,
+        false => local_time(interpreter, time),
+    };
+    if year.is_nan() {
+        return set_time_value(interpreter, this, f64::NAN);
+    }
+    let fields = DateFields::from_time(local);
+    let day = make_day(full_year(year), fields.month, fields.date);
+    let new_time = make_date(day, local.rem_euclid(MS_PER_DAY));
+    let new_time = utc(interpreter, new_time);
+    set_time_value(interpreter, this, time_clip(new_time))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.todatestring
+fn date_to_date_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(new_string(INVALID_DATE));
+    }
+    Ok(new_string(&date_string(local_time(interpreter, time))))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.toisostring
+fn date_to_iso_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Err(JSError::new_range_error("Invalid time value"));
+    }
+    Ok(new_string(&iso_string(time)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.tojson
+/// Generic: works on any object with a `toISOString`, and gives `null` for objects whose
+/// number value isn't finite.
+fn date_to_json(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    if this.is_undefined() || this.is_null() {
+        return Err(JSError::new_type_error(
+            "Date.prototype.toJSON called on null or undefined",
+        ));
+    }
+    let time_value = this.to_primitive(Some(PreferredType::Number), interpreter)?;
+    if let JSValue::Number { data } = time_value
+        && !data.is_finite()
+    {
+        return Ok(JSValue::new_null());
+    }
+    let to_iso_string = get_property_value(interpreter, this, get_or_intern_string("toISOString"))?;
+    if !interpreter.is_callable(&to_iso_string) {
+        return Err(JSError::new_type_error("toISOString is not a function"));
+    }
+    interpreter.call_function(&to_iso_string, this, &[])
+}
+
+fn date_to_locale_date_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(new_string(INVALID_DATE));
+    }
+    Ok(new_string(&locale_date_string(local_time(
+        interpreter,
+        time,
+    ))))
+}
+
+/// English formatting, without `Intl` to ask for anything else.
+fn date_to_locale_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(new_string(INVALID_DATE));
+    }
+    let local = local_time(interpreter, time);
+    let string = format!(
+        "{}, {}",
+        locale_date_string(local),
+        locale_time_string(local)
+    );
+    Ok(new_string(&string))
+}
+
+fn date_to_locale_time_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(new_string(INVALID_DATE));
+    }
+    Ok(new_string(&locale_time_string(local_time(
+        interpreter,
+        time,
+    ))))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.tostring
+fn date_to_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(new_string(INVALID_DATE));
+    }
+    Ok(new_string(&to_date_string(interpreter, time)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.totimestring
+fn date_to_time_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(new_string(INVALID_DATE));
+    }
+    let string = format!(
+        "{} {}",
+        time_string(local_time(interpreter, time)),
+        zone_string(interpreter, time)
+    );
+    Ok(new_string(&string))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.toutcstring
+fn date_to_utc_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let time = this_time_value(interpreter, this)?;
+    if time.is_nan() {
+        return Ok(new_string(INVALID_DATE));
+    }
+    Ok(new_string(&utc_string(time)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.valueof
+fn date_value_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(JSValue::new_number(&this_time_value(interpreter, this)?))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype-@@toprimitive
+/// Unlike other objects, dates prefer strings when the hint is "default", which is why
+/// `date + 1` concatenates.
+fn date_to_primitive(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if !this.is_object() {
+        return Err(JSError::new_type_error(
+            "Date.prototype [ @@toPrimitive ] called on non-object",
+        ));
+    }
+    let hint = argument(args, 0);
+    let hint = match &hint {
+        JSValue::String { data } => get_string_from_pool(data).unwrap_or_default(),
+        _ => String::new(),
+    };
+    let hint = match hint.as_str() {
+        "string" | "default" => PreferredType::String,
+        "number" => PreferredType::Number,
+        _ => {
+            let hint = debug_value(interpreter, &argument(args, 0));
+            return Err(JSError::new_type_error(&format!("Invalid hint: {hint}")));
+        }
+    };
+    ordinary_to_primitive(interpreter, this, hint)
+}
@@ -0,0 +1,218 @@
+//! Generator objects and %GeneratorPrototype%. The body of a generator is resumed with the
+//! records its last `yield` left behind, see `resume`.
+
+use std::mem;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        iterator::create_iter_result,
+        object::{add_methods, add_to_string_tag},
+    },
+    errors::{ErrorKind, JSError},
+    heap::{Trace, Tracer},
+    resume::{ResumeAction, ResumeRecord},
+    stmt::Stmt,
+    values::{JSObject, JSResult, JSValue, ObjectData, ObjectKind},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GeneratorStatus {
+    SuspendedStart,
+    SuspendedYield,
+    Executing,
+    Completed,
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-properties-of-generator-instances
+#[derive(Clone, Debug)]
+pub struct GeneratorState {
+    status: GeneratorStatus,
+    /// The function scope holding `this` and the parameters.
+    environment: usize,
+    body: Box<Stmt>,
+    /// Where the body is suspended, innermost record first.
+    records: Vec<ResumeRecord>,
+}
+
+impl Trace for GeneratorState {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.mark(self.environment);
+        self.records.trace(tracer);
+    }
+}
+
+pub fn create_generator(
+    environment: usize,
+    body: Box<Stmt>,
+    prototype: Option<usize>,
+    interpreter: &mut Interpreter,
+) -> JSValue {
+    let state = GeneratorState {
+        status: GeneratorStatus::SuspendedStart,
+        environment,
+        body,
+        records: vec![],
+    };
+    let prototype = prototype.unwrap_or(interpreter.intrinsics.generator_proto_id);
+    let data = ObjectData::Generator(Box::new(state));
+    let object_id = JSObject::new_object_with_data(data, Some(prototype), interpreter);
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-properties-of-generator-prototype
+pub fn create_generator_prototype(interpreter: &mut Interpreter) -> usize {
+    let iterator_proto_id = interpreter.intrinsics.iterator_proto_id;
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(iterator_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("next", 1, generator_next),
+            ("return", 1, generator_return),
+            ("throw", 1, generator_throw),
+        ],
+        interpreter,
+    );
+    add_to_string_tag(prototype_id, "Generator", interpreter);
+    prototype_id
+}
+
+fn generator_next(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let action = ResumeAction::Next(argument(args, 0));
+    generator_resume(interpreter, this, action, "next")
+}
+
+fn generator_return(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let action = ResumeAction::Return(argument(args, 0));
+    generator_resume(interpreter, this, action, "return")
+}
+
+fn generator_throw(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let action = ResumeAction::Throw(argument(args, 0));
+    generator_resume(interpreter, this, action, "throw")
+}
+
+fn generator_state<'a>(
+    interpreter: &'a mut Interpreter,
+    generator: &JSValue,
+    method: &str,
+) -> JSResult<&'a mut GeneratorState> {
+    let data = match generator {
+        JSValue::Object { object_id, .. } => interpreter.get_object_mut(*object_id)?.get_data_mut(),
+        _ => None,
+    };
+    match data {
+        Some(ObjectData::Generator(state)) => Ok(state),
+        _ => Err(JSError::new_type_error(&format!(
+            "{method} method called on incompatible receiver"
+        ))),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-generatorresume
+fn generator_resume(
+    interpreter: &mut Interpreter,
+    generator: &JSValue,
+    action: ResumeAction,
+    method: &str,
+) -> JSResult<JSValue> {
+    let (value, done) = resume_generator(interpreter, generator, action, method)?;
+    Ok(create_iter_result(interpreter, value, done))
+}
+
+/// Runs the body until it yields or completes. Returns the yielded or returned value and
+/// whether the generator is done. Async functions drive their body through this as well.
+pub fn resume_generator(
+    interpreter: &mut Interpreter,
+    generator: &JSValue,
+    action: ResumeAction,
+    method: &str,
+) -> JSResult<(JSValue, bool)> {
+    let state = generator_state(interpreter, generator, method)?;
+    match (state.status, action) {
+        (GeneratorStatus::Executing, _) => {
+            Err(JSError::new_type_error("Generator is already running"))
+        }
+        (GeneratorStatus::Completed, ResumeAction::Next(_)) => Ok((JSValue::Undefined, true)),
+        // a generator that never started has no try blocks to run, so it completes immediately
+        (
+            GeneratorStatus::SuspendedStart | GeneratorStatus::Completed,
+            ResumeAction::Return(value),
+        ) => {
+            state.status = GeneratorStatus::Completed;
+            Ok((value, true))
+        }
+        (
+            GeneratorStatus::SuspendedStart | GeneratorStatus::Completed,
+            ResumeAction::Throw(value),
+        ) => {
+            state.status = GeneratorStatus::Completed;
+            Err(interpreter.throw_value(value))
+        }
+        (_, action) => run_generator(interpreter, generator, action, method),
+    }
+}
+
+fn run_generator(
+    interpreter: &mut Interpreter,
+    generator: &JSValue,
+    action: ResumeAction,
+    method: &str,
+) -> JSResult<(JSValue, bool)> {
+    let state = generator_state(interpreter, generator, method)?;
+    let mut records = mem::take(&mut state.records);
+    if let Some(record) = records.first_mut() {
+        record.set_action(action);
+    }
+    // the body is moved out while it runs and put back afterwards
+    let body = mem::replace(&mut state.body, Box::new(Stmt::Break));
+    let environment = state.environment;
+    state.status = GeneratorStatus::Executing;
+
+    let outer_records = interpreter.take_resume_stack();
+    interpreter.restore_resume_stack(records);
+    let depth = interpreter.environment_stack.len();
+    interpreter.enter_scope(Some(environment));
+    let result = body.evaluate(interpreter);
+    interpreter.environment_stack.truncate(depth);
+    let records = interpreter.take_resume_stack();
+    interpreter.restore_resume_stack(outer_records);
+
+    let state = generator_state(interpreter, generator, method)?;
+    state.body = body;
+    state.status = GeneratorStatus::Completed;
+    let error = match result {
+        Ok(_) => return Ok((JSValue::Undefined, true)),
+        Err(error) => error,
+    };
+    match error.kind {
+        ErrorKind::Yield(id) => {
+            state.status = GeneratorStatus::SuspendedYield;
+            state.records = records;
+            let value = interpreter.get_value(id)?.clone();
+            Ok((value, false))
+        }
+        ErrorKind::Return(id) => {
+            let value = interpreter.get_value(id)?.clone();
+            Ok((value, true))
+        }
+        _ => Err(error),
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    Interpreter,
+    builtins::index_key,
+    builtins::object::add_symbol_methods,
+    constants::LENGTH_NAME,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    heap::{Trace, Tracer},
+    values::{
+        JSObject, JSResult, JSValue, ObjectKind, PropertyKey, WellKnownSymbol, get_property_value,
+    },
+};
+
+// https://262.ecma-international.org/15.0/index.html#sec-%iteratorprototype%-object
+pub fn create_iterator_prototype(interpreter: &mut Interpreter) -> usize {
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_symbol_methods(
+        prototype_id,
+        vec![(WellKnownSymbol::Iterator, 0, iterator_prototype_iterator)],
+        interpreter,
+    );
+    prototype_id
+}
+
+/// `%IteratorPrototype%[@@iterator]` returns the iterator itself, which makes every iterator
+/// iterable.
+fn iterator_prototype_iterator(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-iterator-records
+#[derive(Clone, Debug)]
+pub enum IteratorRecord {
+    /// Arrays and strings are walked by index, re-reading `length` on every step.
+    ArrayLike { object: JSValue, index: usize },
+    /// Anything exposing `@@iterator`, e.g. generator objects.
+    Protocol {
+        iterator: JSValue,
+        next_method: JSValue,
+        done: bool,
+    },
+}
+
+impl Trace for IteratorRecord {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            IteratorRecord::ArrayLike { object, .. } => object.trace(tracer),
+            IteratorRecord::Protocol {
+                iterator,
+                next_method,
+                ..
+            } => {
+                iterator.trace(tracer);
+                next_method.trace(tracer);
+            }
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getiterator
+pub fn get_iterator(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<IteratorRecord> {
+    match value {
+        JSValue::Object { .. } | JSValue::String { .. } => {
+            let key = PropertyKey::well_known(WellKnownSymbol::Iterator);
+            let method = get_property_value(interpreter, value, key)?;
+            if interpreter.is_callable(&method) {
+                let iterator = interpreter.call_function(&method, value, &[])?;
+                if !iterator.is_object() {
+                    return Err(JSError::new_type_error(
+                        "Result of the Symbol.iterator method is not an object",
+                    ));
+                }
+                let next_method =
+                    get_property_value(interpreter, &iterator, get_or_intern_string("next"))?;
+                return Ok(IteratorRecord::Protocol {
+                    iterator,
+                    next_method,
+                    done: false,
+                });
+            }
+            match value {
+                JSValue::Object {
+                    kind: ObjectKind::Array,
+                    ..
+                } => Ok(IteratorRecord::ArrayLike {
+                    object: value.clone(),
+                    index: 0,
+                }),
+                JSValue::String { .. } => Err(JSError::new_type_error("string is not iterable")),
+                _ => Err(JSError::new_type_error("object is not iterable")),
+            }
+        }
+        other => {
+            let string = other.to_string(interpreter)?;
+            let name = get_string_from_pool(&string).unwrap_or_default();
+            Err(JSError::new_type_error(&format!("{name} is not iterable")))
+        }
+    }
+}
+
+/// Calls `next(value)` and returns the raw iterator result object.
+pub fn iterator_next(
+    interpreter: &mut Interpreter,
+    record: &mut IteratorRecord,
+    value: JSValue,
+) -> JSResult<JSValue> {
+    match record {
+        IteratorRecord::ArrayLike { object, index } => {
+            let length = get_property_value(interpreter, object, get_or_intern_string(LENGTH_NAME))?
+                .to_length(interpreter)?
+                .get_number() as usize;
+            if *index >= length {
+                return Ok(create_iter_result(interpreter, JSValue::Undefined, true));
+            }
+            let object = object.clone();
+            let element = get_property_value(interpreter, &object, index_key(*index))?;
+            *index += 1;
+            Ok(create_iter_result(interpreter, element, false))
+        }
+        IteratorRecord::Protocol {
+            iterator,
+            next_method,
+            done,
+        } => {
+            let iterator = iterator.clone();
+            let result = interpreter.call_function(next_method, &iterator, &[value]);
+            let result = match result {
+                Ok(result) if result.is_object() => result,
+                Ok(_) => {
+                    *done = true;
+                    return Err(JSError::new_type_error("Iterator result is not an object"));
+                }
+                Err(e) => {
+                    *done = true;
+                    return Err(e);
+                }
+            };
+            if iter_result_done(interpreter, &result)? {
+                *done = true;
+            }
+            Ok(result)
+        }
+    }
+}
+
+pub fn iter_result_done(interpreter: &mut Interpreter, result: &JSValue) -> JSResult<bool> {
+    let done = get_property_value(interpreter, result, get_or_intern_string("done"))?;
+    Ok(done.to_boolean())
+}
+
+pub fn iter_result_value(interpreter: &mut Interpreter, result: &JSValue) -> JSResult<JSValue> {
+    get_property_value(interpreter, result, get_or_intern_string("value"))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-iteratorstep
+/// Advances the iterator, returning `None` once it is exhausted.
+pub fn iterator_step(
+    interpreter: &mut Interpreter,
+    record: &mut IteratorRecord,
+) -> JSResult<Option<JSValue>> {
+    let result = iterator_next(interpreter, record, JSValue::Undefined)?;
+    if iter_result_done(interpreter, &result)? {
+        return Ok(None);
+    }
+    Ok(Some(iter_result_value(interpreter, &result)?))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-iteratorclose
+/// Lets the iterator clean up when a loop exits early, e.g. runs a generator's `finally` blocks.
+pub fn iterator_close(interpreter: &mut Interpreter, record: &IteratorRecord) -> JSResult<()> {
+    let IteratorRecord::Protocol { iterator, done, .. } = record else {
+        return Ok(());
+    };
+    if *done {
+        return Ok(());
+    }
+    let return_method = get_property_value(interpreter, iterator, get_or_intern_string("return"))?;
+    if interpreter.is_callable(&return_method) {
+        let result = interpreter.call_function(&return_method, iterator, &[])?;
+        if !result.is_object() {
+            return Err(JSError::new_type_error("Iterator result is not an object"));
+        }
+    }
+    Ok(())
+}
+
+/// Drains an iterable, as spread and the iterable-taking built-ins do.
+pub fn iterate_to_list(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<Vec<JSValue>> {
+    let mut record = get_iterator(interpreter, value)?;
+    let mut values = vec![];
+    while let Some(value) = iterator_step(interpreter, &mut record)? {
+        values.push(value);
+    }
+    Ok(values)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-createiterresultobject
+pub fn create_iter_result(interpreter: &mut Interpreter, value: JSValue, done: bool) -> JSValue {
+    let properties = vec![
+        (get_or_intern_string("value"), value),
+        (get_or_intern_string("done"), JSValue::new_boolean(done)),
+    ];
+    JSValue::new_object(properties, interpreter)
+}
@@ -0,0 +1,606 @@
+//! The `JSON` namespace. Parsing builds objects and arrays straight on the heap and
+//! stringifying reads them back, so hosts can move data in and out as JSON text without
+//! evaluating any script.
+
+use indexmap::IndexMap;
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument, create_array, index_key, object::add_methods, object::add_to_string_tag,
+        object::own_keys, object::string_keys,
+    },
+    constants::LENGTH_NAME,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    values::{
+        JSObject, JSResult, JSValue, ObjectData, ObjectKind, Property, get_property_value, internal,
+    },
+};
+
+/// `space` is cut down to this many characters, or spaces for a number.
+const MAX_GAP: usize = 10;
+
+pub fn create_json_object(interpreter: &mut Interpreter) -> Property {
+    let proto_id = interpreter.get_object_proto_id();
+    let object_id = JSObject::new_ordinary_object(vec![], true, Some(proto_id), interpreter);
+    add_methods(
+        object_id,
+        vec![("parse", 2, json_parse), ("stringify", 3, json_stringify)],
+        interpreter,
+    );
+    add_to_string_tag(object_id, "JSON", interpreter);
+    let value = JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    };
+    (get_or_intern_string("JSON"), value)
+}
+
+fn is_array(value: &JSValue) -> bool {
+    matches!(
+        value,
+        JSValue::Object {
+            kind: ObjectKind::Array,
+            ..
+        }
+    )
+}
+
+/// Own enumerable string keys, symbol keys are never serialized.
+fn enumerable_keys(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<Vec<SymbolU32>> {
+    Ok(string_keys(own_keys(interpreter, value, true)?))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-json.parse
+fn json_parse(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let text = argument(args, 0).to_string(interpreter)?;
+    let text = get_string_from_pool(&text).unwrap_or_default();
+    let value = parse_json(interpreter, &text)?;
+    let reviver = argument(args, 1);
+    if !interpreter.is_callable(&reviver) {
+        return Ok(value);
+    }
+    let empty = get_or_intern_string("");
+    let root = JSValue::new_object(vec![(empty, value)], interpreter);
+    internalize_property(interpreter, &root, empty, &reviver)
+}
+
+/// Parses JSON text into values on the heap. Errors are SyntaxErrors naming the position, in
+/// characters, where the text stopped being valid JSON.
+pub fn parse_json(interpreter: &mut Interpreter, text: &str) -> JSResult<JSValue> {
+    let mut parser = JsonParser {
+        chars: text.chars().collect(),
+        position: 0,
+    };
+    parser.skip_whitespace();
+    let value = parser.parse_value(interpreter)?;
+    parser.skip_whitespace();
+    if parser.position < parser.chars.len() {
+        return Err(JSError::new_syntax_error(&format!(
+            "Unexpected non-whitespace character after JSON at position {}",
+            parser.position
+        )));
+    }
+    Ok(value)
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl JsonParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn error(&self, message: &str) -> JSError {
+        if self.position >= self.chars.len() {
+            return JSError::new_syntax_error("Unexpected end of JSON input");
+        }
+        JSError::new_syntax_error(&format!("{message} in JSON at position {}", self.position))
+    }
+
+    fn unexpected(&self) -> JSError {
+        match self.peek() {
+            Some(c) => self.error(&format!("Unexpected token '{c}'")),
+            None => self.error(""),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn parse_literal(&mut self, word: &str, value: JSValue) -> JSResult<JSValue> {
+        for expected in word.chars() {
+            if !self.consume(expected) {
+                return Err(self.unexpected());
+            }
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
+        match self.peek() {
+            Some('{') => self.parse_object(interpreter),
+            Some('[') => self.parse_array(interpreter),
+            Some('"') => {
+                let string = self.parse_string()?;
+                Ok(JSValue::new_string(&get_or_intern_string(&string)))
+            }
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some('t') => self.parse_literal("true", JSValue::new_boolean(true)),
+            Some('f') => self.parse_literal("false", JSValue::new_boolean(false)),
+            Some('n') => self.parse_literal("null", JSValue::Null),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_object(&mut self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
+        self.position += 1;
+        // a repeated key keeps its first position and its last value
+        let mut properties: IndexMap<SymbolU32, JSValue> = IndexMap::new();
+        self.skip_whitespace();
+        if !self.consume('}') {
+            loop {
+                if self.peek() != Some('"') {
+                    return Err(self.error("Expected property name or '}'"));
+                }
+                let key = get_or_intern_string(&self.parse_string()?);
+                self.skip_whitespace();
+                if !self.consume(':') {
+                    return Err(self.error("Expected ':' after property name"));
+                }
+                self.skip_whitespace();
+                let value = self.parse_value(interpreter)?;
+                properties.insert(key, value);
+                self.skip_whitespace();
+                if self.consume('}') {
+                    break;
+                }
+                if !self.consume(',') {
+                    return Err(self.error("Expected ',' or '}' after property value"));
+                }
+                self.skip_whitespace();
+            }
+        }
+        Ok(JSValue::new_object(
+            properties.into_iter().collect(),
+            interpreter,
+        ))
+    }
+
+    fn parse_array(&mut self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
+        self.position += 1;
+        let mut elements = vec![];
+        self.skip_whitespace();
+        if !self.consume(']') {
+            loop {
+                elements.push(self.parse_value(interpreter)?);
+                self.skip_whitespace();
+                if self.consume(']') {
+                    break;
+                }
+                if !self.consume(',') {
+                    return Err(self.error("Expected ',' or ']' after array element"));
+                }
+                self.skip_whitespace();
+            }
+        }
+        Ok(create_array(elements, interpreter))
+    }
+
+    fn parse_digits(&mut self) -> usize {
+        let start = self.position;
+        while let Some('0'..='9') = self.peek() {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn parse_number(&mut self) -> JSResult<JSValue> {
+        let start = self.position;
+        if self.consume('-') && !matches!(self.peek(), Some('0'..='9')) {
+            return Err(self.error("No number after minus sign"));
+        }
+        // no leading zeros: a 0 can only be followed by a fraction or exponent
+        if !self.consume('0') {
+            self.parse_digits();
+        }
+        if self.consume('.') && self.parse_digits() == 0 {
+            return Err(self.error("Unterminated fractional number"));
+        }
+        if self.consume('e') || self.consume('E') {
+            if !self.consume('+') {
+                self.consume('-');
+            }
+            if self.parse_digits() == 0 {
+                return Err(self.error("Exponent part is missing a number"));
+            }
+        }
+        let text: String = self.chars[start..self.position].iter().collect();
+        let number = text.parse::<f64>().map_err(|_| self.unexpected())?;
+        Ok(JSValue::new_number(&number))
+    }
+
+    fn parse_hex_escape(&mut self) -> JSResult<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.peek().and_then(|c| c.to_digit(16));
+            match digit {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.error("Bad Unicode escape")),
+            }
+            self.position += 1;
+        }
+        Ok(code)
+    }
+
+    fn parse_string(&mut self) -> JSResult<String> {
+        self.position += 1;
+        let mut string = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(JSError::new_syntax_error(&format!(
+                    "Unterminated string in JSON at position {}",
+                    self.position
+                )));
+            };
+            match c {
+                '"' => {
+                    self.position += 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    self.position += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.position += 1;
+                            string.push(self.parse_unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("Bad escaped character")),
+                    };
+                    self.position += 1;
+                    string.push(escaped);
+                }
+                c if c < ' ' => {
+                    return Err(self.error("Bad control character in string literal"));
+                }
+                c => {
+                    self.position += 1;
+                    string.push(c);
+                }
+            }
+        }
+    }
+
+    /// The `XXXX` of a `\uXXXX` escape, combined with a following low surrogate escape when it
+    /// starts a pair. Unpaired surrogates can't be stored and become U+FFFD.
+    fn parse_unicode_escape(&mut self) -> JSResult<char> {
+        let high = self.parse_hex_escape()?;
+        if (0xD800..0xDC00).contains(&high)
+            && self.chars.get(self.position) == Some(&'\\')
+            && self.chars.get(self.position + 1) == Some(&'u')
+        {
+            let resume = self.position;
+            self.position += 2;
+            let low = self.parse_hex_escape()?;
+            if (0xDC00..0xE000).contains(&low) {
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                return Ok(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            self.position = resume;
+        }
+        Ok(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-internalizejsonproperty
+/// Walks the parsed value bottom up, replacing every property with what the reviver returns
+/// for it and deleting the ones it returns `undefined` for.
+fn internalize_property(
+    interpreter: &mut Interpreter,
+    holder: &JSValue,
+    name: SymbolU32,
+    reviver: &JSValue,
+) -> JSResult<JSValue> {
+    let value = get_property_value(interpreter, holder, name)?;
+    if value.is_object() {
+        let keys = match is_array(&value) {
+            true => {
+                let length_key = get_or_intern_string(LENGTH_NAME);
+                let length = get_property_value(interpreter, &value, length_key)?
+                    .to_length(interpreter)?
+                    .get_number() as usize;
+                (0..length).map(index_key).collect()
+            }
+            false => enumerable_keys(interpreter, &value)?,
+        };
+        let object_id = value.get_object_id()?;
+        for key in keys {
+            let element = internalize_property(interpreter, &value, key, reviver)?;
+            match element {
+                JSValue::Undefined => internal::delete(interpreter, object_id, &key.into())?,
+                element => internal::create_data_property(interpreter, object_id, key, element)?,
+            };
+        }
+    }
+    let name = JSValue::new_string(&name);
+    interpreter.call_function(reviver, holder, &[name, value])
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-json.stringify
+fn json_stringify(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let replacer = argument(args, 1);
+    let space = argument(args, 2);
+    let result = stringify_json(interpreter, &argument(args, 0), &replacer, &space)?;
+    Ok(match result {
+        Some(json) => JSValue::new_string(&get_or_intern_string(&json)),
+        None => JSValue::Undefined,
+    })
+}
+
+/// Serializes `value` like `JSON.stringify(value, replacer, space)`. `None` when there is
+/// nothing to serialize, e.g. for `undefined` or a function.
+pub fn stringify_json(
+    interpreter: &mut Interpreter,
+    value: &JSValue,
+    replacer: &JSValue,
+    space: &JSValue,
+) -> JSResult<Option<String>> {
+    let mut serializer = Serializer {
+        replacer: None,
+        property_list: None,
+        gap: to_gap(interpreter, space)?,
+        indent: String::new(),
+        stack: vec![],
+    };
+    if interpreter.is_callable(replacer) {
+        serializer.replacer = Some(replacer.clone());
+    } else if is_array(replacer) {
+        serializer.property_list = Some(to_property_list(interpreter, replacer)?);
+    }
+    let empty = get_or_intern_string("");
+    let wrapper = JSValue::new_object(vec![(empty, value.clone())], interpreter);
+    serializer.serialize_property(interpreter, &wrapper, empty)
+}
+
+/// Only strings and numbers in a replacer array name properties, each of them once.
+fn to_property_list(interpreter: &mut Interpreter, replacer: &JSValue) -> JSResult<Vec<SymbolU32>> {
+    let length_key = get_or_intern_string(LENGTH_NAME);
+    let length = get_property_value(interpreter, replacer, length_key)?
+        .to_length(interpreter)?
+        .get_number() as usize;
+    let mut list = vec![];
+    for index in 0..length {
+        let element = get_property_value(interpreter, replacer, index_key(index))?;
+        let key = match element {
+            JSValue::String { data } => data,
+            JSValue::Number { .. } => element.to_string(interpreter)?,
+            _ => continue,
+        };
+        if !list.contains(&key) {
+            list.push(key);
+        }
+    }
+    Ok(list)
+}
+
+fn to_gap(interpreter: &mut Interpreter, space: &JSValue) -> JSResult<String> {
+    match space {
+        JSValue::Number { .. } => {
+            let count = space.to_integer_or_infinity(interpreter)?.get_number();
+            Ok(" ".repeat(count.clamp(0.0, MAX_GAP as f64) as usize))
+        }
+        JSValue::String { data } => {
+            let space = get_string_from_pool(data).unwrap_or_default();
+            Ok(space.chars().take(MAX_GAP).collect())
+        }
+        _ => Ok(String::new()),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-quotejsonstring
+fn quote(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('"');
+    for c in string.chars() {
+        match c {
+            '\u{8}' => quoted.push_str("\\b"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\r' => quoted.push_str("\\r"),
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Serializer {
+    replacer: Option<JSValue>,
+    property_list: Option<Vec<SymbolU32>>,
+    gap: String,
+    indent: String,
+    /// Objects being serialized, to detect cycles.
+    stack: Vec<usize>,
+}
+
+impl Serializer {
+    // https://262.ecma-international.org/15.0/index.html#sec-serializejsonproperty
+    fn serialize_property(
+        &mut self,
+        interpreter: &mut Interpreter,
+        holder: &JSValue,
+        key: SymbolU32,
+    ) -> JSResult<Option<String>> {
+        let mut value = get_property_value(interpreter, holder, key)?;
+        if value.is_object() {
+            let to_json = get_property_value(interpreter, &value, get_or_intern_string("toJSON"))?;
+            if interpreter.is_callable(&to_json) {
+                value =
+                    interpreter.call_function(&to_json, &value, &[JSValue::new_string(&key)])?;
+            }
+        }
+        if let Some(replacer) = &self.replacer {
+            let arguments = [JSValue::new_string(&key), value];
+            value = interpreter.call_function(replacer, holder, &arguments)?;
+        }
+        // Number, String, Boolean and BigInt objects serialize as the primitives they hold
+        if let JSValue::Object { object_id, .. } = value {
+            match interpreter.get_object(object_id)?.get_data() {
+                Some(ObjectData::Number(_)) => value = value.to_number(interpreter)?,
+                Some(ObjectData::String(_)) => {
+                    value = JSValue::new_string(&value.to_string(interpreter)?);
+                }
+                Some(ObjectData::Boolean(data)) => value = JSValue::Boolean { data: *data },
+                Some(ObjectData::BigInt(data)) => value = JSValue::BigInt { data: data.clone() },
+                _ => (),
+            }
+        }
+        let json = match &value {
+            JSValue::Null => "null".to_string(),
+            JSValue::Boolean { data } => data.to_string(),
+            JSValue::String { data } => quote(&get_string_from_pool(data).unwrap_or_default()),
+            JSValue::Number { data } if data.is_finite() => {
+                let string = value.to_string(interpreter)?;
+                get_string_from_pool(&string).unwrap_or_default()
+            }
+            JSValue::Number { .. } => "null".to_string(),
+            JSValue::BigInt { .. } => {
+                return Err(JSError::new_type_error(
+                    "Do not know how to serialize a BigInt",
+                ));
+            }
+            JSValue::Object { .. } if !interpreter.is_callable(&value) => match is_array(&value) {
+                true => self.serialize_array(interpreter, &value)?,
+                false => self.serialize_object(interpreter, &value)?,
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(json))
+    }
+
+    fn enter(&mut self, value: &JSValue) -> JSResult<String> {
+        let object_id = value.get_object_id()?;
+        if self.stack.contains(&object_id) {
+            return Err(JSError::new_type_error(
+                "Converting circular structure to JSON",
+            ));
+        }
+        self.stack.push(object_id);
+        let stepback = self.indent.clone();
+        self.indent.push_str(&self.gap);
+        Ok(stepback)
+    }
+
+    fn leave(&mut self, stepback: String) {
+        self.stack.pop();
+        self.indent = stepback;
+    }
+
+    /// Joins members between `open` and `close`, one per line when there is a gap.
+    fn wrap(&self, members: Vec<String>, open: char, close: char, stepback: &str) -> String {
+        if members.is_empty() {
+            return format!("{open}{close}");
+        }
+        if self.gap.is_empty() {
+            return format!("{open}{}{close}", members.join(","));
+        }
+        let separator = format!(",\n{}", self.indent);
+        format!(
+            "{open}\n{}{}\n{stepback}{close}",
+            self.indent,
+            members.join(&separator)
+        )
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-serializejsonobject
+    fn serialize_object(
+        &mut self,
+        interpreter: &mut Interpreter,
+        value: &JSValue,
+    ) -> JSResult<String> {
+        let stepback = self.enter(value)?;
+        let keys = match &self.property_list {
+            Some(list) => list.clone(),
+            None => enumerable_keys(interpreter, value)?,
+        };
+        let mut members = vec![];
+        for key in keys {
+            let result = self.serialize_property(interpreter, value, key);
+            let json = match result {
+                Ok(json) => json,
+                Err(e) => {
+                    self.leave(stepback);
+                    return Err(e);
+                }
+            };
+            if let Some(json) = json {
+                let name = quote(&get_string_from_pool(&key).unwrap_or_default());
+                let colon = if self.gap.is_empty() { ":" } else { ": " };
+                members.push(format!("{name}{colon}{json}"));
+            }
+        }
+        let json = self.wrap(members, '{', '}', &stepback);
+        self.leave(stepback);
+        Ok(json)
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-serializejsonarray
+    fn serialize_array(
+        &mut self,
+        interpreter: &mut Interpreter,
+        value: &JSValue,
+    ) -> JSResult<String> {
+        let stepback = self.enter(value)?;
+        let length_key = get_or_intern_string(LENGTH_NAME);
+        let result = get_property_value(interpreter, value, length_key)
+            .and_then(|length| length.to_length(interpreter))
+            .and_then(|length| {
+                (0..length.get_number() as usize)
+                    .map(|index| {
+                        let json = self.serialize_property(interpreter, value, index_key(index))?;
+                        Ok(json.unwrap_or_else(|| "null".to_string()))
+                    })
+                    .collect::<JSResult<Vec<String>>>()
+            });
+        let json = result.map(|elements| self.wrap(elements, '[', ']', &stepback));
+        self.leave(stepback);
+        json
+    }
+}
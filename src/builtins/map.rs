@@ -0,0 +1,295 @@
+//! The `Map` constructor, `Map.prototype` and the prototype of the iterators its `keys`,
+//! `values` and `entries` return.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        collection::{
+            IterationKind, OrderedTable, add_from_iterable, close_cursor, collection_iterator_next,
+            create_collection_iterator, for_each_callback, next_entry, prototype_from_new_target,
+            this_collection,
+        },
+        create_array,
+        iterator::iterate_to_list,
+        object::{add_getter, add_method_alias, add_methods, add_to_string_tag},
+    },
+    constants::MAP_NAME,
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{
+        JSObject, JSResult, JSValue, ObjectData, ObjectId, ObjectKind, Property, PropertyKey,
+        WellKnownSymbol, link_constructor_and_prototype,
+    },
+};
+
+/// Builds the global `Map` with its prototype, and the prototype of Map iterators.
+pub fn create_map_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id =
+        JSObject::new_native_function(MAP_NAME, 0, map_call, Some(map_construct), interpreter);
+    add_methods(
+        constructor_id,
+        vec![("groupBy", 2, map_group_by)],
+        interpreter,
+    );
+    let species = PropertyKey::well_known(WellKnownSymbol::Species);
+    add_getter(constructor_id, species, map_species, interpreter);
+
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("clear", 0, map_clear),
+            ("delete", 1, map_delete),
+            ("entries", 0, map_entries),
+            ("forEach", 1, map_for_each),
+            ("get", 1, map_get),
+            ("has", 1, map_has),
+            ("keys", 0, map_keys),
+            ("set", 2, map_set),
+            ("values", 0, map_values),
+        ],
+        interpreter,
+    );
+    add_getter(
+        prototype_id,
+        get_or_intern_string("size"),
+        map_size,
+        interpreter,
+    );
+    let iterator = PropertyKey::well_known(WellKnownSymbol::Iterator);
+    add_method_alias(prototype_id, "entries", iterator, interpreter);
+    add_to_string_tag(prototype_id, MAP_NAME, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.map_proto_id = prototype_id;
+
+    // https://262.ecma-international.org/15.0/index.html#sec-%mapiteratorprototype%-object
+    let iterator_proto_id = interpreter.intrinsics.iterator_proto_id;
+    let map_iterator_id =
+        JSObject::new_ordinary_object(vec![], true, Some(iterator_proto_id), interpreter);
+    add_methods(
+        map_iterator_id,
+        vec![("next", 0, map_iterator_next)],
+        interpreter,
+    );
+    add_to_string_tag(map_iterator_id, "Map Iterator", interpreter);
+    interpreter.intrinsics.map_iterator_proto_id = map_iterator_id;
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(MAP_NAME), value)
+}
+
+/// A new, empty Map inheriting from `prototype`.
+fn create_map(interpreter: &mut Interpreter, prototype: ObjectId) -> ObjectId {
+    let data = ObjectData::Map(Box::new(OrderedTable::new()));
+    JSObject::new_object_with_data(data, Some(prototype), interpreter)
+}
+
+fn map_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error("Constructor Map requires 'new'"))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map-iterable
+fn map_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let fallback = interpreter.intrinsics.map_proto_id;
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    let map = JSValue::Object {
+        object_id: create_map(interpreter, prototype),
+        kind: ObjectKind::Object,
+    };
+    add_from_iterable(interpreter, &map, &argument(args, 0), "set", true)?;
+    Ok(map)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.groupby
+fn map_group_by(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let items = argument(args, 0);
+    let callback = argument(args, 1);
+    if items.is_null() || items.is_undefined() {
+        return Err(JSError::new_type_error(
+            "Cannot convert undefined or null to object",
+        ));
+    }
+    if !interpreter.is_callable(&callback) {
+        return Err(JSError::new_type_error("callback is not a function"));
+    }
+    let mut groups: OrderedTable<Vec<JSValue>> = OrderedTable::new();
+    for (index, value) in iterate_to_list(interpreter, &items)?
+        .into_iter()
+        .enumerate()
+    {
+        let index = JSValue::new_number(&(index as f64));
+        let key =
+            interpreter.call_function(&callback, &JSValue::Undefined, &[value.clone(), index])?;
+        match groups.get_mut(&key) {
+            Some(group) => group.push(value),
+            None => groups.insert(key, vec![value]),
+        }
+    }
+    let map_id = create_map(interpreter, interpreter.intrinsics.map_proto_id);
+    for (key, values) in groups.iter() {
+        let group = create_array(values.clone(), interpreter);
+        map_table(interpreter, map_id)?.insert(key.clone(), group);
+    }
+    Ok(JSValue::Object {
+        object_id: map_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-map-@@species
+fn map_species(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this.clone())
+}
+
+/// The id of the Map `this` is, or the receiver error of `method`.
+fn this_map(interpreter: &mut Interpreter, this: &JSValue, method: &str) -> JSResult<ObjectId> {
+    this_collection(
+        interpreter,
+        this,
+        &format!("Map.prototype.{method}"),
+        |data| matches!(data, ObjectData::Map(_)),
+    )
+}
+
+/// The entries of a Map `this_map` has vouched for.
+fn map_table(
+    interpreter: &mut Interpreter,
+    map_id: ObjectId,
+) -> JSResult<&mut OrderedTable<JSValue>> {
+    match interpreter.get_object_mut(map_id)?.get_data_mut() {
+        Some(ObjectData::Map(table)) => Ok(table),
+        _ => Err(JSError::new_type_error("object is not a Map")),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.clear
+fn map_clear(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "clear")?;
+    map_table(interpreter, map_id)?.clear();
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.delete
+fn map_delete(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "delete")?;
+    let deleted = map_table(interpreter, map_id)?.remove(&argument(args, 0));
+    Ok(JSValue::new_boolean(deleted))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.entries
+fn map_entries(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "entries")?;
+    create_collection_iterator(interpreter, map_id, IterationKind::Entries)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.foreach
+/// Entries added during the loop are visited too, deleted ones that weren't reached yet aren't.
+fn map_for_each(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "forEach")?;
+    let (callback, this_arg) = for_each_callback(interpreter, args)?;
+    map_table(interpreter, map_id)?.open_cursor();
+    let mut position = 0;
+    while let Some((found, key, value)) = next_entry(interpreter, map_id, position)? {
+        position = found + 1;
+        let args = [value, key, this.clone()];
+        if let Err(error) = interpreter.call_function(&callback, &this_arg, &args) {
+            close_cursor(interpreter, map_id)?;
+            return Err(error);
+        }
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.get
+fn map_get(interpreter: &mut Interpreter, this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "get")?;
+    let value = map_table(interpreter, map_id)?
+        .get(&argument(args, 0))
+        .cloned();
+    Ok(value.unwrap_or(JSValue::Undefined))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.has
+fn map_has(interpreter: &mut Interpreter, this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "has")?;
+    let has = map_table(interpreter, map_id)?.contains(&argument(args, 0));
+    Ok(JSValue::new_boolean(has))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.keys
+fn map_keys(interpreter: &mut Interpreter, this: &JSValue, _args: &[JSValue]) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "keys")?;
+    create_collection_iterator(interpreter, map_id, IterationKind::Keys)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.set
+fn map_set(interpreter: &mut Interpreter, this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "set")?;
+    map_table(interpreter, map_id)?.insert(argument(args, 0), argument(args, 1));
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-map.prototype.size
+fn map_size(interpreter: &mut Interpreter, this: &JSValue, _args: &[JSValue]) -> JSResult<JSValue> {
+    let map_id = this_collection(interpreter, this, "get Map.prototype.size", |data| {
+        matches!(data, ObjectData::Map(_))
+    })?;
+    let size = map_table(interpreter, map_id)?.len();
+    Ok(JSValue::new_number(&(size as f64)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-map.prototype.values
+fn map_values(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let map_id = this_map(interpreter, this, "values")?;
+    create_collection_iterator(interpreter, map_id, IterationKind::Values)
+}
+
+fn map_iterator_next(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    collection_iterator_next(interpreter, this, MAP_NAME)
+}
@@ -0,0 +1,411 @@
+//! The `Math` namespace: constants, the ES2023 functions and a `random` the host can seed.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        object::{add_methods, add_to_string_tag},
+    },
+    global::get_or_intern_string,
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectKind, ObjectPropertyBuilder, Property,
+        exponentiate,
+    },
+};
+
+const CONSTANTS: [(&str, f64); 8] = [
+    ("E", std::f64::consts::E),
+    ("LN10", std::f64::consts::LN_10),
+    ("LN2", std::f64::consts::LN_2),
+    ("LOG10E", std::f64::consts::LOG10_E),
+    ("LOG2E", std::f64::consts::LOG2_E),
+    ("PI", std::f64::consts::PI),
+    ("SQRT1_2", std::f64::consts::FRAC_1_SQRT_2),
+    ("SQRT2", std::f64::consts::SQRT_2),
+];
+
+pub fn create_math_object(interpreter: &mut Interpreter) -> Property {
+    let proto_id = interpreter.get_object_proto_id();
+    let object_id = JSObject::new_ordinary_object(vec![], true, Some(proto_id), interpreter);
+    let methods: Vec<(&str, usize, NativeFunction)> = vec![
+        ("abs", 1, math_abs),
+        ("acos", 1, math_acos),
+        ("acosh", 1, math_acosh),
+        ("asin", 1, math_asin),
+        ("asinh", 1, math_asinh),
+        ("atan", 1, math_atan),
+        ("atan2", 2, math_atan2),
+        ("atanh", 1, math_atanh),
+        ("cbrt", 1, math_cbrt),
+        ("ceil", 1, math_ceil),
+        ("clz32", 1, math_clz32),
+        ("cos", 1, math_cos),
+        ("cosh", 1, math_cosh),
+        ("exp", 1, math_exp),
+        ("expm1", 1, math_expm1),
+        ("floor", 1, math_floor),
+        ("fround", 1, math_fround),
+        ("hypot", 2, math_hypot),
+        ("imul", 2, math_imul),
+        ("log", 1, math_log),
+        ("log10", 1, math_log10),
+        ("log1p", 1, math_log1p),
+        ("log2", 1, math_log2),
+        ("max", 2, math_max),
+        ("min", 2, math_min),
+        ("pow", 2, math_pow),
+        ("random", 0, math_random),
+        ("round", 1, math_round),
+        ("sign", 1, math_sign),
+        ("sin", 1, math_sin),
+        ("sinh", 1, math_sinh),
+        ("sqrt", 1, math_sqrt),
+        ("tan", 1, math_tan),
+        ("tanh", 1, math_tanh),
+        ("trunc", 1, math_trunc),
+    ];
+    add_methods(object_id, methods, interpreter);
+    add_to_string_tag(object_id, "Math", interpreter);
+    if let Ok(math) = interpreter.get_object_mut(object_id) {
+        // constants are neither writable, enumerable nor configurable
+        for (name, value) in CONSTANTS {
+            let prop = ObjectPropertyBuilder::new(JSValue::new_number(&value)).build();
+            math.define_property(get_or_intern_string(name), prop);
+        }
+    }
+    let value = JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    };
+    (get_or_intern_string("Math"), value)
+}
+
+fn number_argument(interpreter: &mut Interpreter, args: &[JSValue], index: usize) -> JSResult<f64> {
+    Ok(argument(args, index).to_number(interpreter)?.get_number())
+}
+
+fn unary(interpreter: &mut Interpreter, args: &[JSValue], op: fn(f64) -> f64) -> JSResult<JSValue> {
+    let x = number_argument(interpreter, args, 0)?;
+    Ok(JSValue::new_number(&op(x)))
+}
+
+/// Every argument converted to a number, in order, even once the result is known.
+fn number_arguments(interpreter: &mut Interpreter, args: &[JSValue]) -> JSResult<Vec<f64>> {
+    args.iter()
+        .map(|arg| Ok(arg.to_number(interpreter)?.get_number()))
+        .collect()
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-math.round
+/// Rounds half up, towards +Infinity, and keeps the sign of zero results.
+fn round(x: f64) -> f64 {
+    if !x.is_finite() || x == 0.0 {
+        return x;
+    }
+    if (-0.5..0.0).contains(&x) {
+        return -0.0;
+    }
+    // subtracting the floor is exact, unlike adding 0.5 first
+    let floor = x.floor();
+    match x - floor >= 0.5 {
+        true => floor + 1.0,
+        false => floor,
+    }
+}
+
+fn sign(x: f64) -> f64 {
+    if x.is_nan() || x == 0.0 {
+        return x;
+    }
+    x.signum()
+}
+
+fn fround(x: f64) -> f64 {
+    x as f32 as f64
+}
+
+fn math_abs(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::abs)
+}
+
+fn math_acos(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::acos)
+}
+
+fn math_acosh(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::acosh)
+}
+
+fn math_asin(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::asin)
+}
+
+fn math_asinh(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::asinh)
+}
+
+fn math_atan(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::atan)
+}
+
+fn math_atanh(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::atanh)
+}
+
+fn math_cbrt(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::cbrt)
+}
+
+fn math_ceil(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::ceil)
+}
+
+fn math_cos(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::cos)
+}
+
+fn math_cosh(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::cosh)
+}
+
+fn math_exp(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::exp)
+}
+
+fn math_expm1(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::exp_m1)
+}
+
+fn math_floor(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::floor)
+}
+
+fn math_fround(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, fround)
+}
+
+fn math_log(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::ln)
+}
+
+fn math_log1p(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::ln_1p)
+}
+
+fn math_log10(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::log10)
+}
+
+fn math_log2(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::log2)
+}
+
+fn math_round(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, round)
+}
+
+fn math_sign(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, sign)
+}
+
+fn math_sin(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::sin)
+}
+
+fn math_sinh(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::sinh)
+}
+
+fn math_sqrt(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::sqrt)
+}
+
+fn math_tan(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::tan)
+}
+
+fn math_tanh(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::tanh)
+}
+
+fn math_trunc(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    unary(interpreter, args, f64::trunc)
+}
+
+fn math_atan2(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let y = number_argument(interpreter, args, 0)?;
+    let x = number_argument(interpreter, args, 1)?;
+    Ok(JSValue::new_number(&y.atan2(x)))
+}
+
+fn math_clz32(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let n = argument(args, 0).to_uint_32(interpreter)?;
+    Ok(JSValue::new_number(&(n.leading_zeros() as f64)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-math.hypot
+fn math_hypot(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let numbers = number_arguments(interpreter, args)?;
+    // an infinity wins over NaN
+    let result = if numbers.iter().any(|n| n.is_infinite()) {
+        f64::INFINITY
+    } else if numbers.iter().any(|n| n.is_nan()) {
+        f64::NAN
+    } else {
+        numbers.iter().fold(0.0_f64, |sum, n| sum.hypot(*n))
+    };
+    Ok(JSValue::new_number(&result))
+}
+
+fn math_imul(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let a = argument(args, 0).to_int_32(interpreter)?;
+    let b = argument(args, 1).to_int_32(interpreter)?;
+    Ok(JSValue::new_number(&(a.wrapping_mul(b) as f64)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-math.max
+/// Any NaN makes the result NaN, and +0 counts as larger than -0.
+fn math_max(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let numbers = number_arguments(interpreter, args)?;
+    let mut result = f64::NEG_INFINITY;
+    for n in numbers {
+        if n.is_nan() {
+            return Ok(JSValue::new_number(&f64::NAN));
+        }
+        if n > result || (n == 0.0 && result == 0.0 && n.is_sign_positive()) {
+            result = n;
+        }
+    }
+    Ok(JSValue::new_number(&result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-math.min
+/// Any NaN makes the result NaN, and -0 counts as smaller than +0.
+fn math_min(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let numbers = number_arguments(interpreter, args)?;
+    let mut result = f64::INFINITY;
+    for n in numbers {
+        if n.is_nan() {
+            return Ok(JSValue::new_number(&f64::NAN));
+        }
+        if n < result || (n == 0.0 && result == 0.0 && n.is_sign_negative()) {
+            result = n;
+        }
+    }
+    Ok(JSValue::new_number(&result))
+}
+
+fn math_pow(interpreter: &mut Interpreter, _this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let base = number_argument(interpreter, args, 0)?;
+    let exponent = number_argument(interpreter, args, 1)?;
+    Ok(JSValue::new_number(&exponentiate(base, exponent)))
+}
+
+/// Draws from the interpreter's generator, see `Interpreter::with_random_seed`.
+fn math_random(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(JSValue::new_number(&interpreter.random.next_f64()))
+}
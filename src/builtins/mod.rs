@@ -0,0 +1,124 @@
+//! Built-in constructors and namespaces that are implemented directly in Rust.
+
+pub mod array_buffer;
+pub mod bigint;
+pub mod collection;
+pub mod console;
+pub mod data_view;
+pub mod date;
+pub mod generator;
+pub mod iterator;
+pub mod json;
+pub mod map;
+pub mod math;
+pub mod number;
+pub mod object;
+pub mod promise;
+pub mod proxy;
+pub mod reflect;
+pub mod regexp;
+pub mod set;
+pub mod string;
+pub mod symbol;
+pub mod timers;
+pub mod typed_array;
+pub mod weak;
+
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    constants::{CONSTRUCTOR_NAME, LENGTH_NAME},
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{JSResult, JSValue, PropertyKey, WellKnownSymbol, get_property_value},
+};
+
+/// Returns the argument at `index`, or `undefined` when the caller passed fewer arguments.
+pub fn argument(args: &[JSValue], index: usize) -> JSValue {
+    args.get(index).cloned().unwrap_or(JSValue::Undefined)
+}
+
+pub fn create_array(values: Vec<JSValue>, interpreter: &mut Interpreter) -> JSValue {
+    let properties = values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| (index_key(index), value))
+        .collect();
+    JSValue::new_array(properties, interpreter)
+}
+
+pub fn index_key(index: usize) -> SymbolU32 {
+    get_or_intern_string(&index.to_string())
+}
+
+/// Reads the elements of an array-like value (anything with a `length`) in index order.
+pub fn list_from_array_like(
+    interpreter: &mut Interpreter,
+    value: &JSValue,
+) -> JSResult<Vec<JSValue>> {
+    if !value.is_object() {
+        return Err(JSError::new_type_error("object is not iterable"));
+    }
+    let length_key = get_or_intern_string(LENGTH_NAME);
+    let length = get_property_value(interpreter, value, length_key)?
+        .to_length(interpreter)?
+        .get_number() as usize;
+    (0..length)
+        .map(|index| get_property_value(interpreter, value, index_key(index)))
+        .collect()
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-speciesconstructor
+/// The constructor for objects derived from `object`: its constructor's `@@species`, or
+/// `default` when either of them is missing.
+pub fn species_constructor(
+    interpreter: &mut Interpreter,
+    object: &JSValue,
+    default: JSValue,
+) -> JSResult<JSValue> {
+    let constructor_key = get_or_intern_string(CONSTRUCTOR_NAME);
+    let constructor = get_property_value(interpreter, object, constructor_key)?;
+    if constructor.is_undefined() {
+        return Ok(default);
+    }
+    if !constructor.is_object() {
+        return Err(JSError::new_type_error(
+            "The .constructor property is not an object",
+        ));
+    }
+    let species_key = PropertyKey::well_known(WellKnownSymbol::Species);
+    let species = get_property_value(interpreter, &constructor, species_key)?;
+    if species.is_undefined() || species.is_null() {
+        return Ok(default);
+    }
+    let is_constructor = species
+        .get_object(interpreter)
+        .is_ok_and(|object| object.is_constructor());
+    if !is_constructor {
+        return Err(JSError::new_type_error(
+            "object.constructor[Symbol.species] is not a constructor",
+        ));
+    }
+    Ok(species)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-toindex
+/// A length or offset argument: an integer from 0 to 2^53 - 1, or a RangeError with
+/// `message`.
+pub fn to_index(interpreter: &mut Interpreter, value: &JSValue, message: &str) -> JSResult<u64> {
+    let integer = value.to_integer_or_infinity(interpreter)?.get_number();
+    if !(0.0..=9007199254740991.0).contains(&integer) {
+        return Err(JSError::new_range_error(message));
+    }
+    Ok(integer as u64)
+}
+
+/// The position a relative index argument like `slice`'s stands for in a sequence of `length`
+/// elements: negative values count from the end, and the result is clamped to 0..=length.
+pub fn relative_index(relative: f64, length: usize) -> usize {
+    match relative {
+        relative if relative < 0.0 => (length as f64 + relative).max(0.0) as usize,
+        relative => relative.min(length as f64) as usize,
+    }
+}
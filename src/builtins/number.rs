@@ -0,0 +1,516 @@
+//! The `Number` constructor, `Number.prototype` and the global `parseInt`, `parseFloat`,
+//! `isNaN` and `isFinite`, which are the same functions as their `Number.*` counterparts.
+
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::{argument, object::add_methods},
+    constants::{IS_FINITE_NAME, NUMBER_NAME, PROTOTYPE_NAME},
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectKind, ObjectPropertyBuilder,
+        Property, bigint, decimal_prefix_length, exact_digits, get_property_value,
+        is_js_whitespace, link_constructor_and_prototype, parse_decimal, shortest_digits,
+        to_string,
+    },
+};
+
+/// 2^53 - 1, the largest integer every smaller one of is exactly representable.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+const CONSTANTS: [(&str, f64); 8] = [
+    ("EPSILON", f64::EPSILON),
+    ("MAX_SAFE_INTEGER", MAX_SAFE_INTEGER),
+    ("MAX_VALUE", f64::MAX),
+    ("MIN_SAFE_INTEGER", -MAX_SAFE_INTEGER),
+    // the smallest subnormal, not f64::MIN_POSITIVE
+    ("MIN_VALUE", 5e-324),
+    ("NaN", f64::NAN),
+    ("NEGATIVE_INFINITY", f64::NEG_INFINITY),
+    ("POSITIVE_INFINITY", f64::INFINITY),
+];
+
+/// `toFixed`, `toExponential` and `toPrecision` allow at most this many digits.
+const MAX_FRACTION_DIGITS: f64 = 100.0;
+
+/// Builds the global `Number` constructor with its prototype, statics and constants, and
+/// returns the globals that `Number` shares its functions with.
+pub fn create_number_constructor(interpreter: &mut Interpreter) -> Vec<Property> {
+    let constructor_id = JSObject::new_native_function(
+        NUMBER_NAME,
+        1,
+        number_call,
+        Some(number_construct),
+        interpreter,
+    );
+    // Number.prototype is itself a Number object, for +0
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_object_with_data(ObjectData::Number(0.0), Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("toExponential", 1, number_to_exponential),
+            ("toFixed", 1, number_to_fixed),
+            ("toLocaleString", 0, number_to_locale_string),
+            ("toPrecision", 1, number_to_precision),
+            ("toString", 1, number_to_string),
+            ("valueOf", 0, number_value_of),
+        ],
+        interpreter,
+    );
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.number_proto_id = prototype_id;
+
+    let statics: Vec<(&str, usize, NativeFunction)> = vec![
+        ("isFinite", 1, number_is_finite),
+        ("isInteger", 1, number_is_integer),
+        ("isNaN", 1, number_is_nan),
+        ("isSafeInteger", 1, number_is_safe_integer),
+    ];
+    add_methods(constructor_id, statics, interpreter);
+
+    // Number.parseFloat === parseFloat, the same goes for parseInt
+    let shared: Vec<(&str, usize, NativeFunction)> =
+        vec![("parseFloat", 1, parse_float), ("parseInt", 2, parse_int)];
+    add_methods(constructor_id, shared, interpreter);
+    let mut globals = vec![];
+    if let Ok(constructor) = interpreter.get_object_mut(constructor_id) {
+        for name in ["parseFloat", "parseInt"] {
+            let key = get_or_intern_string(name);
+            if let Some(Ok(function)) = constructor
+                .get_property(&key.into())
+                .map(|prop| prop.get_value())
+            {
+                globals.push((key, function.clone()));
+            }
+        }
+        // constants are neither writable, enumerable nor configurable
+        for (name, value) in CONSTANTS {
+            let prop = ObjectPropertyBuilder::new(JSValue::new_number(&value)).build();
+            constructor.define_property(get_or_intern_string(name), prop);
+        }
+    }
+    let global_functions: Vec<(&str, usize, NativeFunction)> = vec![
+        (IS_FINITE_NAME, 1, global_is_finite),
+        ("isNaN", 1, global_is_nan),
+    ];
+    for (name, length, native) in global_functions {
+        globals.push(JSObject::new_native_fn(name, length, native, interpreter));
+    }
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    globals.insert(0, (get_or_intern_string(NUMBER_NAME), value));
+    globals
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-number-constructor-number-value
+fn to_number_argument(interpreter: &mut Interpreter, args: &[JSValue]) -> JSResult<f64> {
+    match args.first() {
+        Some(value) => match value.to_numeric(interpreter)? {
+            JSValue::BigInt { data } => Ok(bigint::to_number(&data)),
+            numeric => Ok(numeric.get_number()),
+        },
+        None => Ok(0.0),
+    }
+}
+
+fn number_call(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let number = to_number_argument(interpreter, args)?;
+    Ok(JSValue::new_number(&number))
+}
+
+fn number_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let number = to_number_argument(interpreter, args)?;
+    let prototype_key = get_or_intern_string(PROTOTYPE_NAME);
+    let prototype = match get_property_value(interpreter, new_target, prototype_key) {
+        Ok(JSValue::Object { object_id, .. }) => object_id,
+        _ => interpreter.intrinsics.number_proto_id,
+    };
+    let object_id =
+        JSObject::new_object_with_data(ObjectData::Number(number), Some(prototype), interpreter);
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-thisnumbervalue
+fn this_number_value(interpreter: &mut Interpreter, this: &JSValue, method: &str) -> JSResult<f64> {
+    match this {
+        JSValue::Number { data } => return Ok(*data),
+        JSValue::Object { object_id, .. } => {
+            if let Some(ObjectData::Number(number)) = interpreter.get_object(*object_id)?.get_data()
+            {
+                return Ok(*number);
+            }
+        }
+        _ => {}
+    }
+    Err(JSError::new_type_error(&format!(
+        "Number.prototype.{method} requires that 'this' be a Number"
+    )))
+}
+
+fn new_string(string: &str) -> JSValue {
+    JSValue::new_string(&get_or_intern_string(string))
+}
+
+/// The first `count` of `digits`, rounded half up on what follows. A carry out of the first
+/// digit makes the result one digit longer.
+fn round_digits(digits: &[u8], count: i32) -> Vec<u8> {
+    if count < 0 {
+        return vec![];
+    }
+    let count = count as usize;
+    let mut rounded: Vec<u8> = (0..count)
+        .map(|index| digits.get(index).copied().unwrap_or(0))
+        .collect();
+    if digits.get(count).is_some_and(|digit| *digit >= 5) {
+        let mut index = count;
+        loop {
+            if index == 0 {
+                rounded.insert(0, 1);
+                break;
+            }
+            index -= 1;
+            if rounded[index] < 9 {
+                rounded[index] += 1;
+                break;
+            }
+            rounded[index] = 0;
+        }
+    }
+    rounded
+}
+
+fn digits_to_string(digits: &[u8]) -> String {
+    digits.iter().map(|digit| (b'0' + digit) as char).collect()
+}
+
+/// `digits` as `d.ddde+x`, one digit before the point.
+fn exponential_notation(digits: &str, exponent: i32) -> String {
+    let sign = if exponent < 0 { '-' } else { '+' };
+    let (first, rest) = digits.split_at(1);
+    match rest.is_empty() {
+        true => format!("{first}e{sign}{}", exponent.abs()),
+        false => format!("{first}.{rest}e{sign}{}", exponent.abs()),
+    }
+}
+
+/// Significant digits of `x` rounded to `count`, with the exponent of the first one.
+fn significant_digits(x: f64, count: usize) -> (String, i32) {
+    if x == 0.0 {
+        return ("0".repeat(count), 0);
+    }
+    let (digits, point) = exact_digits(x);
+    let mut rounded = round_digits(&digits, count as i32);
+    let mut exponent = point - 1;
+    if rounded.len() > count {
+        rounded.truncate(count);
+        exponent += 1;
+    }
+    (digits_to_string(&rounded), exponent)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-number.prototype.toexponential
+fn number_to_exponential(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_number_value(interpreter, this, "toExponential")?;
+    let fraction_digits = argument(args, 0);
+    let f = fraction_digits
+        .to_integer_or_infinity(interpreter)?
+        .get_number();
+    if !x.is_finite() {
+        return Ok(new_string(&to_string(x, 10)));
+    }
+    if !(0.0..=MAX_FRACTION_DIGITS).contains(&f) {
+        return Err(JSError::new_range_error(
+            "toExponential() argument must be between 0 and 100",
+        ));
+    }
+    let sign = if x < 0.0 { "-" } else { "" };
+    let x = x.abs();
+    // without a digit count, as many digits as it takes to tell x apart
+    let (digits, exponent) = match fraction_digits.is_undefined() {
+        true if x != 0.0 => shortest_digits(x),
+        true => ("0".to_string(), 0),
+        false => significant_digits(x, f as usize + 1),
+    };
+    Ok(new_string(&format!(
+        "{sign}{}",
+        exponential_notation(&digits, exponent)
+    )))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-number.prototype.tofixed
+fn number_to_fixed(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_number_value(interpreter, this, "toFixed")?;
+    let f = argument(args, 0)
+        .to_integer_or_infinity(interpreter)?
+        .get_number();
+    if !(0.0..=MAX_FRACTION_DIGITS).contains(&f) {
+        return Err(JSError::new_range_error(
+            "toFixed() digits argument must be between 0 and 100",
+        ));
+    }
+    let f = f as usize;
+    if !x.is_finite() || x.abs() >= 1e21 {
+        return Ok(new_string(&to_string(x, 10)));
+    }
+    let sign = if x < 0.0 { "-" } else { "" };
+    // n is the integer closest to x * 10^f, ties going to the larger one
+    let n = match x == 0.0 {
+        true => vec![],
+        false => {
+            let (digits, point) = exact_digits(x.abs());
+            round_digits(&digits, point + f as i32)
+        }
+    };
+    let mut n = digits_to_string(&n);
+    if n.len() <= f {
+        n = format!("{}{n}", "0".repeat(f + 1 - n.len()));
+    }
+    let (integer, fraction) = n.split_at(n.len() - f);
+    let fixed = match f {
+        0 => integer.to_string(),
+        _ => format!("{integer}.{fraction}"),
+    };
+    Ok(new_string(&format!("{sign}{fixed}")))
+}
+
+fn number_to_locale_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_number_value(interpreter, this, "toLocaleString")?;
+    Ok(new_string(&to_string(x, 10)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-number.prototype.toprecision
+fn number_to_precision(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_number_value(interpreter, this, "toPrecision")?;
+    let precision = argument(args, 0);
+    if precision.is_undefined() {
+        return Ok(new_string(&to_string(x, 10)));
+    }
+    let p = precision.to_integer_or_infinity(interpreter)?.get_number();
+    if !x.is_finite() {
+        return Ok(new_string(&to_string(x, 10)));
+    }
+    if !(1.0..=MAX_FRACTION_DIGITS).contains(&p) {
+        return Err(JSError::new_range_error(
+            "toPrecision() argument must be between 1 and 100",
+        ));
+    }
+    let p = p as usize;
+    let sign = if x < 0.0 { "-" } else { "" };
+    let (digits, e) = significant_digits(x.abs(), p);
+    let string = if e < -6 || e >= p as i32 {
+        exponential_notation(&digits, e)
+    } else if e >= 0 {
+        let (integer, fraction) = digits.split_at(e as usize + 1);
+        match fraction.is_empty() {
+            true => integer.to_string(),
+            false => format!("{integer}.{fraction}"),
+        }
+    } else {
+        format!("0.{}{digits}", "0".repeat((-(e + 1)) as usize))
+    };
+    Ok(new_string(&format!("{sign}{string}")))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-number.prototype.tostring
+fn number_to_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_number_value(interpreter, this, "toString")?;
+    let radix = match argument(args, 0) {
+        JSValue::Undefined => 10.0,
+        radix => radix.to_integer_or_infinity(interpreter)?.get_number(),
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(JSError::new_range_error(
+            "toString() radix argument must be between 2 and 36",
+        ));
+    }
+    Ok(new_string(&to_string(x, radix as u8)))
+}
+
+fn number_value_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let x = this_number_value(interpreter, this, "valueOf")?;
+    Ok(JSValue::new_number(&x))
+}
+
+/// The argument when it is a number, `Number.is*` don't convert anything else.
+fn number_argument(args: &[JSValue]) -> Option<f64> {
+    match args.first() {
+        Some(JSValue::Number { data }) => Some(*data),
+        _ => None,
+    }
+}
+
+fn is_integral_number(x: f64) -> bool {
+    x.is_finite() && x.trunc() == x
+}
+
+fn number_is_finite(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let result = number_argument(args).is_some_and(f64::is_finite);
+    Ok(JSValue::new_boolean(result))
+}
+
+fn number_is_integer(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let result = number_argument(args).is_some_and(is_integral_number);
+    Ok(JSValue::new_boolean(result))
+}
+
+fn number_is_nan(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let result = number_argument(args).is_some_and(f64::is_nan);
+    Ok(JSValue::new_boolean(result))
+}
+
+fn number_is_safe_integer(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let result =
+        number_argument(args).is_some_and(|x| is_integral_number(x) && x.abs() <= MAX_SAFE_INTEGER);
+    Ok(JSValue::new_boolean(result))
+}
+
+/// The global `isFinite`, which unlike `Number.isFinite` converts its argument first.
+fn global_is_finite(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let number = argument(args, 0).to_number(interpreter)?.get_number();
+    Ok(JSValue::new_boolean(number.is_finite()))
+}
+
+/// The global `isNaN`, which unlike `Number.isNaN` converts its argument first.
+fn global_is_nan(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let number = argument(args, 0).to_number(interpreter)?.get_number();
+    Ok(JSValue::new_boolean(number.is_nan()))
+}
+
+fn string_argument(interpreter: &mut Interpreter, args: &[JSValue]) -> JSResult<String> {
+    let string: SymbolU32 = argument(args, 0).to_string(interpreter)?;
+    Ok(get_string_from_pool(&string).unwrap_or_default())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-parsefloat-string
+/// Reads the longest prefix that is a decimal literal, ignoring whatever follows it.
+fn parse_float(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let string = string_argument(interpreter, args)?;
+    let trimmed = string.trim_start_matches(is_js_whitespace);
+    let number = match decimal_prefix_length(trimmed) {
+        0 => f64::NAN,
+        length => parse_decimal(&trimmed[..length]),
+    };
+    Ok(JSValue::new_number(&number))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-parseint-string-radix
+/// Reads the longest prefix of digits in `radix`, which defaults to 16 after `0x` and to 10
+/// otherwise.
+fn parse_int(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let string = string_argument(interpreter, args)?;
+    let mut radix = argument(args, 1).to_int_32(interpreter)?;
+    let mut string = string.trim_start_matches(is_js_whitespace);
+    let sign = match string.strip_prefix('-') {
+        Some(rest) => {
+            string = rest;
+            -1.0
+        }
+        None => {
+            string = string.strip_prefix('+').unwrap_or(string);
+            1.0
+        }
+    };
+    let mut strip_prefix = true;
+    if radix != 0 {
+        if !(2..=36).contains(&radix) {
+            return Ok(JSValue::new_number(&f64::NAN));
+        }
+        strip_prefix = radix == 16;
+    } else {
+        radix = 10;
+    }
+    if strip_prefix && let Some(rest) = string.strip_prefix("0x").or(string.strip_prefix("0X")) {
+        string = rest;
+        radix = 16;
+    }
+    let radix = radix as u32;
+    let end = string
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(string.len());
+    let digits = &string[..end];
+    if digits.is_empty() {
+        return Ok(JSValue::new_number(&f64::NAN));
+    }
+    // decimal digits go through the exact parser, other radixes can only be approximated
+    let number = match radix {
+        10 => digits.parse().unwrap_or(f64::NAN),
+        _ => digits.chars().fold(0.0, |number, c| {
+            number * radix as f64 + c.to_digit(radix).unwrap_or_default() as f64
+        }),
+    };
+    Ok(JSValue::new_number(&(sign * number)))
+}
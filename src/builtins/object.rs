@@ -0,0 +1,851 @@
+use indexmap::IndexMap;
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument, create_array, index_key,
+        iterator::iterate_to_list,
+        string::{from_utf16, to_utf16},
+        typed_array::typed_array_own_keys,
+    },
+    constants::{LENGTH_NAME, OBJECT_NAME},
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectId, ObjectKind,
+        ObjectProperty, ObjectPropertyBuilder, Property, PropertyDescriptor, PropertyKey,
+        WellKnownSymbol, define_own_property, get_property_value, internal, is_same_value,
+        link_constructor_and_prototype, set_property_value,
+    },
+};
+
+/// Builds the global `Object` constructor together with its static methods.
+pub fn create_object_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id = JSObject::new_native_function(
+        OBJECT_NAME,
+        1,
+        object_call,
+        Some(object_construct),
+        interpreter,
+    );
+    let proto_id = interpreter.get_object_proto_id();
+    link_constructor_and_prototype(constructor_id, proto_id, interpreter);
+    add_methods(
+        proto_id,
+        vec![
+            ("toString", 0, object_prototype_to_string),
+            ("valueOf", 0, object_prototype_value_of),
+        ],
+        interpreter,
+    );
+
+    let statics: Vec<(&str, usize, NativeFunction)> = vec![
+        ("defineProperty", 3, object_define_property),
+        ("defineProperties", 2, object_define_properties),
+        (
+            "getOwnPropertyDescriptor",
+            2,
+            object_get_own_property_descriptor,
+        ),
+        (
+            "getOwnPropertyDescriptors",
+            1,
+            object_get_own_property_descriptors,
+        ),
+        ("freeze", 1, object_freeze),
+        ("seal", 1, object_seal),
+        ("preventExtensions", 1, object_prevent_extensions),
+        ("isFrozen", 1, object_is_frozen),
+        ("isSealed", 1, object_is_sealed),
+        ("isExtensible", 1, object_is_extensible),
+        ("keys", 1, object_keys),
+        ("values", 1, object_values),
+        ("entries", 1, object_entries),
+        ("assign", 2, object_assign),
+        ("fromEntries", 1, object_from_entries),
+        ("getOwnPropertyNames", 1, object_get_own_property_names),
+        ("getOwnPropertySymbols", 1, object_get_own_property_symbols),
+        ("is", 2, object_is),
+        ("groupBy", 2, object_group_by),
+    ];
+    add_methods(constructor_id, statics, interpreter);
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(OBJECT_NAME), value)
+}
+
+/// Installs native methods as non-enumerable properties, the way built-in methods are defined.
+pub fn add_methods(
+    object_id: ObjectId,
+    methods: Vec<(&str, usize, NativeFunction)>,
+    interpreter: &mut Interpreter,
+) {
+    for (name, length, native) in methods {
+        let (key, value) = JSObject::new_native_fn(name, length, native, interpreter);
+        let prop = ObjectPropertyBuilder::new(value)
+            .writable(true)
+            .configurable(true)
+            .build();
+        if let Ok(object) = interpreter.get_object_mut(object_id) {
+            object.define_property(key, prop);
+        }
+    }
+}
+
+/// Like `add_methods`, for methods keyed by a well-known symbol. Their names are the symbol's
+/// description in brackets, e.g. `[Symbol.iterator]`.
+pub fn add_symbol_methods(
+    object_id: ObjectId,
+    methods: Vec<(WellKnownSymbol, usize, NativeFunction)>,
+    interpreter: &mut Interpreter,
+) {
+    for (symbol, length, native) in methods {
+        let key = PropertyKey::well_known(symbol);
+        let (_, value) = JSObject::new_native_fn(&key.function_name(), length, native, interpreter);
+        let prop = ObjectPropertyBuilder::new(value)
+            .writable(true)
+            .configurable(true)
+            .build();
+        if let Ok(object) = interpreter.get_object_mut(object_id) {
+            object.define_property(key, prop);
+        }
+    }
+}
+
+/// Installs the method already at `name` under a second key too, the way e.g.
+/// `Map.prototype[@@iterator]` is the same function as `Map.prototype.entries`.
+pub fn add_method_alias(
+    object_id: ObjectId,
+    name: &str,
+    alias: impl Into<PropertyKey>,
+    interpreter: &mut Interpreter,
+) {
+    let key = PropertyKey::from_name(name);
+    let Ok(object) = interpreter.get_object_mut(object_id) else {
+        return;
+    };
+    if let Some(prop) = object.get_property(&key).cloned() {
+        object.define_property(alias, prop);
+    }
+}
+
+/// Adds a configurable, non-enumerable accessor with only a getter, named `get <key>`.
+pub fn add_getter(
+    object_id: ObjectId,
+    key: impl Into<PropertyKey>,
+    getter: NativeFunction,
+    interpreter: &mut Interpreter,
+) {
+    let key = key.into();
+    let name = format!("get {}", key.function_name());
+    let (_, getter) = JSObject::new_native_fn(&name, 0, getter, interpreter);
+    let prop = ObjectProperty::Accessor {
+        get: Some(getter),
+        set: None,
+        enumerable: false,
+        configurable: true,
+    };
+    if let Ok(object) = interpreter.get_object_mut(object_id) {
+        object.define_property(key, prop);
+    }
+}
+
+/// Sets the `@@toStringTag` that `Object.prototype.toString` reports, e.g. `[object Math]`.
+pub fn add_to_string_tag(object_id: ObjectId, tag: &str, interpreter: &mut Interpreter) {
+    let tag = JSValue::new_string(&get_or_intern_string(tag));
+    let prop = ObjectPropertyBuilder::new(tag).configurable(true).build();
+    if let Ok(object) = interpreter.get_object_mut(object_id) {
+        object.define_property(PropertyKey::well_known(WellKnownSymbol::ToStringTag), prop);
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-object-value
+fn object_call(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    match argument(args, 0) {
+        JSValue::Null | JSValue::Undefined => Ok(JSValue::new_object(vec![], interpreter)),
+        value => to_object(interpreter, &value),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-toobject
+/// Wraps a primitive in an object holding it, objects are returned as they are.
+pub fn to_object(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<JSValue> {
+    let intrinsics = &interpreter.intrinsics;
+    let (data, prototype) = match value {
+        JSValue::Null | JSValue::Undefined => {
+            return Err(JSError::new_type_error(
+                "Cannot convert undefined or null to object",
+            ));
+        }
+        JSValue::Object { .. } => return Ok(value.clone()),
+        JSValue::Boolean { data } => (ObjectData::Boolean(*data), intrinsics.object_proto_id),
+        JSValue::Number { data } => (ObjectData::Number(*data), intrinsics.number_proto_id),
+        JSValue::String { data } => (ObjectData::String(*data), intrinsics.string_proto_id),
+        JSValue::Symbol { id, description } => {
+            let symbol = PropertyKey::Symbol {
+                id: *id,
+                description: *description,
+            };
+            (ObjectData::Symbol(symbol), intrinsics.symbol_proto_id)
+        }
+        JSValue::BigInt { data } => (ObjectData::BigInt(data.clone()), intrinsics.bigint_proto_id),
+    };
+    let object_id = JSObject::new_object_with_data(data, Some(prototype), interpreter);
+    // a String object's indices and length are fixed, so they're defined up front
+    if let JSValue::String { data } = value {
+        let units = to_utf16(*data);
+        let object = interpreter.get_object_mut(object_id)?;
+        for (index, unit) in units.iter().enumerate() {
+            let prop = ObjectPropertyBuilder::new(from_utf16(&[*unit]))
+                .enumerable(true)
+                .build();
+            object.define_property(index_key(index), prop);
+        }
+        let length = JSValue::new_number(&(units.len() as f64));
+        object.define_property(
+            get_or_intern_string(LENGTH_NAME),
+            ObjectPropertyBuilder::new(length).build(),
+        );
+    }
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+fn object_construct(
+    interpreter: &mut Interpreter,
+    _new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    object_call(interpreter, &JSValue::Undefined, args)
+}
+
+fn require_object(value: &JSValue, caller: &str) -> JSResult<ObjectId> {
+    match value {
+        JSValue::Object { object_id, .. } => Ok(*object_id),
+        _ => Err(JSError::new_type_error(&format!(
+            "{caller} called on non-object"
+        ))),
+    }
+}
+
+fn key_name(key: &SymbolU32) -> String {
+    get_string_from_pool(key).unwrap_or_default()
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-topropertydescriptor
+pub fn to_property_descriptor(
+    interpreter: &mut Interpreter,
+    value: &JSValue,
+) -> JSResult<PropertyDescriptor> {
+    let JSValue::Object { object_id, .. } = value else {
+        let string = value.to_string(interpreter)?;
+        return Err(JSError::new_type_error(&format!(
+            "Property description must be an object: {}",
+            key_name(&string)
+        )));
+    };
+    let field = |name: &str, interpreter: &mut Interpreter| -> JSResult<Option<JSValue>> {
+        let key = get_or_intern_string(name);
+        if !internal::has_property(interpreter, *object_id, &key.into())? {
+            return Ok(None);
+        }
+        Ok(Some(get_property_value(interpreter, value, key)?))
+    };
+    let desc = PropertyDescriptor {
+        enumerable: field("enumerable", interpreter)?.map(|v| v.to_boolean()),
+        configurable: field("configurable", interpreter)?.map(|v| v.to_boolean()),
+        value: field("value", interpreter)?,
+        writable: field("writable", interpreter)?.map(|v| v.to_boolean()),
+        get: field("get", interpreter)?,
+        set: field("set", interpreter)?,
+    };
+    for (name, accessor) in [("Getter", &desc.get), ("Setter", &desc.set)] {
+        if let Some(accessor) = accessor
+            && !accessor.is_undefined()
+            && !interpreter.is_callable(accessor)
+        {
+            let string = accessor.to_string(interpreter)?;
+            return Err(JSError::new_type_error(&format!(
+                "{name} must be a function: {}",
+                key_name(&string)
+            )));
+        }
+    }
+    if desc.is_accessor_descriptor() && desc.is_data_descriptor() {
+        return Err(JSError::new_type_error(
+            "Invalid property descriptor. Cannot both specify accessors and a value or writable attribute",
+        ));
+    }
+    Ok(desc)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-frompropertydescriptor
+pub fn from_property(interpreter: &mut Interpreter, prop: &ObjectProperty) -> JSValue {
+    let bool_value = JSValue::new_boolean;
+    let properties = match prop {
+        ObjectProperty::Data {
+            value,
+            writable,
+            enumerable,
+            configurable,
+        } => vec![
+            (get_or_intern_string("value"), value.clone()),
+            (get_or_intern_string("writable"), bool_value(*writable)),
+            (get_or_intern_string("enumerable"), bool_value(*enumerable)),
+            (
+                get_or_intern_string("configurable"),
+                bool_value(*configurable),
+            ),
+        ],
+        ObjectProperty::Accessor {
+            get,
+            set,
+            enumerable,
+            configurable,
+        } => vec![
+            (get_or_intern_string("get"), get.clone().unwrap_or_default()),
+            (get_or_intern_string("set"), set.clone().unwrap_or_default()),
+            (get_or_intern_string("enumerable"), bool_value(*enumerable)),
+            (
+                get_or_intern_string("configurable"),
+                bool_value(*configurable),
+            ),
+        ],
+    };
+    JSValue::new_object(properties, interpreter)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-frompropertydescriptor
+/// Like `from_property`, for a descriptor that may leave some of its fields out.
+pub fn from_property_descriptor(
+    interpreter: &mut Interpreter,
+    desc: &PropertyDescriptor,
+) -> JSValue {
+    let bool_value = JSValue::new_boolean;
+    let fields = [
+        ("value", desc.value.clone()),
+        ("writable", desc.writable.map(bool_value)),
+        ("get", desc.get.clone()),
+        ("set", desc.set.clone()),
+        ("enumerable", desc.enumerable.map(bool_value)),
+        ("configurable", desc.configurable.map(bool_value)),
+    ];
+    let properties = fields
+        .into_iter()
+        .filter_map(|(name, value)| Some((get_or_intern_string(name), value?)))
+        .collect();
+    JSValue::new_object(properties, interpreter)
+}
+
+fn define_or_throw(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: PropertyKey,
+    desc: &PropertyDescriptor,
+) -> JSResult<()> {
+    if define_own_property(interpreter, object_id, key, desc)? {
+        return Ok(());
+    }
+    let message = if internal::get_own_property(interpreter, object_id, &key)?.is_none() {
+        format!("Cannot define property {key}, object is not extensible")
+    } else {
+        format!("Cannot redefine property: {key}")
+    };
+    Err(JSError::new_type_error(&message))
+}
+
+fn object_define_property(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let object_id = require_object(&target, "Object.defineProperty")?;
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    let desc = to_property_descriptor(interpreter, &argument(args, 2))?;
+    define_or_throw(interpreter, object_id, key, &desc)?;
+    Ok(target)
+}
+
+fn object_define_properties(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let object_id = require_object(&target, "Object.defineProperties")?;
+    let props = argument(args, 1);
+    let props_id = require_object(&props, "Object.defineProperties")?;
+    // every descriptor is validated before any of them is applied
+    let mut descriptors = vec![];
+    for key in internal::own_property_keys(interpreter, props_id)? {
+        let is_enumerable = internal::get_own_property(interpreter, props_id, &key)?
+            .is_some_and(|p| p.is_enumerable());
+        if !is_enumerable {
+            continue;
+        }
+        let desc_value = get_property_value(interpreter, &props, key)?;
+        descriptors.push((key, to_property_descriptor(interpreter, &desc_value)?));
+    }
+    for (key, desc) in descriptors {
+        define_or_throw(interpreter, object_id, key, &desc)?;
+    }
+    Ok(target)
+}
+
+fn object_get_own_property_descriptor(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    let JSValue::Object { object_id, .. } = target else {
+        return Ok(JSValue::Undefined);
+    };
+    match internal::get_own_property(interpreter, object_id, &key)? {
+        Some(prop) => Ok(from_property(interpreter, &prop)),
+        None => Ok(JSValue::Undefined),
+    }
+}
+
+fn object_get_own_property_descriptors(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let JSValue::Object { object_id, .. } = target else {
+        return Ok(JSValue::new_object(vec![], interpreter));
+    };
+    let descriptors = JSValue::new_object(vec![], interpreter);
+    let descriptors_id = descriptors.get_object_id()?;
+    for key in own_keys(interpreter, &target, false)? {
+        let Some(prop) = internal::get_own_property(interpreter, object_id, &key)? else {
+            continue;
+        };
+        let descriptor = from_property(interpreter, &prop);
+        interpreter
+            .get_object_mut(descriptors_id)?
+            .add_property(key, descriptor);
+    }
+    Ok(descriptors)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-setintegritylevel
+fn set_integrity_level(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    frozen: bool,
+) -> JSResult<()> {
+    let level = if frozen { "freeze" } else { "seal" };
+    // the elements of a typed array can be neither frozen nor sealed
+    if !typed_array_own_keys(interpreter, object_id).is_empty() {
+        return Err(JSError::new_type_error(&format!(
+            "Cannot {level} array buffer views with elements"
+        )));
+    }
+    // only a proxy can refuse
+    if !internal::prevent_extensions(interpreter, object_id)? {
+        return Err(JSError::new_type_error(
+            "'preventExtensions' on proxy: trap returned falsish",
+        ));
+    }
+    for key in internal::own_property_keys(interpreter, object_id)? {
+        let Some(prop) = internal::get_own_property(interpreter, object_id, &key)? else {
+            continue;
+        };
+        let desc = PropertyDescriptor {
+            configurable: Some(false),
+            writable: (frozen && !prop.is_accessor()).then_some(false),
+            ..Default::default()
+        };
+        define_or_throw(interpreter, object_id, key, &desc)?;
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-testintegritylevel
+fn test_integrity_level(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    frozen: bool,
+) -> JSResult<bool> {
+    if internal::is_extensible(interpreter, object_id)? {
+        return Ok(false);
+    }
+    for key in internal::own_property_keys(interpreter, object_id)? {
+        let Some(prop) = internal::get_own_property(interpreter, object_id, &key)? else {
+            continue;
+        };
+        if prop.is_configurable() || (frozen && !prop.is_accessor() && prop.is_writable()) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn object_freeze(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    if let JSValue::Object { object_id, .. } = target {
+        set_integrity_level(interpreter, object_id, true)?;
+    }
+    Ok(target)
+}
+
+fn object_seal(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    if let JSValue::Object { object_id, .. } = target {
+        set_integrity_level(interpreter, object_id, false)?;
+    }
+    Ok(target)
+}
+
+fn object_prevent_extensions(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    if let JSValue::Object { object_id, .. } = target
+        && !internal::prevent_extensions(interpreter, object_id)?
+    {
+        return Err(JSError::new_type_error(
+            "'preventExtensions' on proxy: trap returned falsish",
+        ));
+    }
+    Ok(target)
+}
+
+fn object_is_frozen(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    match argument(args, 0) {
+        JSValue::Object { object_id, .. } => Ok(JSValue::new_boolean(test_integrity_level(
+            interpreter,
+            object_id,
+            true,
+        )?)),
+        _ => Ok(JSValue::new_boolean(true)),
+    }
+}
+
+fn object_is_sealed(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    match argument(args, 0) {
+        JSValue::Object { object_id, .. } => Ok(JSValue::new_boolean(test_integrity_level(
+            interpreter,
+            object_id,
+            false,
+        )?)),
+        _ => Ok(JSValue::new_boolean(true)),
+    }
+}
+
+fn object_is_extensible(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    match argument(args, 0) {
+        JSValue::Object { object_id, .. } => Ok(JSValue::new_boolean(internal::is_extensible(
+            interpreter,
+            object_id,
+        )?)),
+        _ => Ok(JSValue::new_boolean(false)),
+    }
+}
+
+/// Own keys of `value` after ToObject, symbols included. Primitives other than null and undefined have no own
+/// properties we model yet, so they produce an empty list.
+pub fn own_keys(
+    interpreter: &mut Interpreter,
+    value: &JSValue,
+    enumerable_only: bool,
+) -> JSResult<Vec<PropertyKey>> {
+    match value {
+        JSValue::Null | JSValue::Undefined => Err(JSError::new_type_error(
+            "Cannot convert undefined or null to object",
+        )),
+        JSValue::Object { object_id, .. } => {
+            let keys = internal::own_property_keys(interpreter, *object_id)?;
+            if !enumerable_only {
+                return Ok(keys);
+            }
+            let mut enumerable = vec![];
+            for key in keys {
+                if internal::get_own_property(interpreter, *object_id, &key)?
+                    .is_some_and(|prop| prop.is_enumerable())
+                {
+                    enumerable.push(key);
+                }
+            }
+            Ok(enumerable)
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+/// The keys of `keys` that are strings, in order.
+pub fn string_keys(keys: Vec<PropertyKey>) -> Vec<SymbolU32> {
+    keys.iter().filter_map(PropertyKey::as_string).collect()
+}
+
+fn object_keys(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let keys = string_keys(own_keys(interpreter, &argument(args, 0), true)?)
+        .iter()
+        .map(JSValue::new_string)
+        .collect();
+    Ok(create_array(keys, interpreter))
+}
+
+fn object_values(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let mut values = vec![];
+    for key in string_keys(own_keys(interpreter, &target, true)?) {
+        values.push(get_property_value(interpreter, &target, key)?);
+    }
+    Ok(create_array(values, interpreter))
+}
+
+fn object_entries(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let mut entries = vec![];
+    for key in string_keys(own_keys(interpreter, &target, true)?) {
+        let value = get_property_value(interpreter, &target, key)?;
+        entries.push(create_array(
+            vec![JSValue::new_string(&key), value],
+            interpreter,
+        ));
+    }
+    Ok(create_array(entries, interpreter))
+}
+
+fn object_assign(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let target = match target {
+        JSValue::Null | JSValue::Undefined => {
+            return Err(JSError::new_type_error(
+                "Cannot convert undefined or null to object",
+            ));
+        }
+        JSValue::Object { .. } => target,
+        primitive => object_call(interpreter, &JSValue::Undefined, &[primitive])?,
+    };
+    for source in args.iter().skip(1) {
+        if source.is_null() || source.is_undefined() {
+            continue;
+        }
+        for key in own_keys(interpreter, source, true)? {
+            let value = get_property_value(interpreter, source, key)?;
+            if !set_property_value(interpreter, &target, key, value)? {
+                return Err(JSError::new_type_error(&format!(
+                    "Cannot assign to read only property '{key}' of object"
+                )));
+            }
+        }
+    }
+    Ok(target)
+}
+
+fn object_from_entries(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let entries = iterate_to_list(interpreter, &argument(args, 0))?;
+    let object = JSValue::new_object(vec![], interpreter);
+    let object_id = object.get_object_id()?;
+    for entry in entries {
+        if !entry.is_object() {
+            let string = entry.to_string(interpreter)?;
+            return Err(JSError::new_type_error(&format!(
+                "Iterator value {} is not an entry object",
+                key_name(&string)
+            )));
+        }
+        let key = get_property_value(interpreter, &entry, get_or_intern_string("0"))?
+            .to_property_key(interpreter)?;
+        let value = get_property_value(interpreter, &entry, get_or_intern_string("1"))?;
+        let desc = PropertyDescriptor {
+            value: Some(value),
+            writable: Some(true),
+            enumerable: Some(true),
+            configurable: Some(true),
+            ..Default::default()
+        };
+        define_own_property(interpreter, object_id, key, &desc)?;
+    }
+    Ok(object)
+}
+
+fn object_get_own_property_names(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let keys = string_keys(own_keys(interpreter, &argument(args, 0), false)?)
+        .iter()
+        .map(JSValue::new_string)
+        .collect();
+    Ok(create_array(keys, interpreter))
+}
+
+fn object_get_own_property_symbols(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let symbols = own_keys(interpreter, &argument(args, 0), false)?
+        .iter()
+        .filter(|key| key.is_symbol())
+        .map(|key| key.to_value())
+        .collect();
+    Ok(create_array(symbols, interpreter))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-object.prototype.tostring
+fn object_prototype_to_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let builtin_tag = match this {
+        JSValue::Undefined => "Undefined",
+        JSValue::Null => "Null",
+        JSValue::Boolean { .. } => "Boolean",
+        JSValue::String { .. } => "String",
+        JSValue::Number { .. } => "Number",
+        JSValue::Object {
+            kind: ObjectKind::Array,
+            ..
+        } => "Array",
+        JSValue::Object { object_id, .. } => {
+            let object = interpreter.get_object(*object_id)?;
+            match (object.is_function(), object.get_data()) {
+                (true, _) => "Function",
+                (false, Some(ObjectData::Number(_))) => "Number",
+                (false, Some(ObjectData::Boolean(_))) => "Boolean",
+                (false, Some(ObjectData::String(_))) => "String",
+                _ => "Object",
+            }
+        }
+        JSValue::Symbol { .. } | JSValue::BigInt { .. } => "Object",
+    };
+    let tag = match this {
+        JSValue::Undefined | JSValue::Null => JSValue::Undefined,
+        _ => {
+            let key = PropertyKey::well_known(WellKnownSymbol::ToStringTag);
+            get_property_value(interpreter, this, key)?
+        }
+    };
+    let tag = match tag {
+        JSValue::String { data } => get_string_from_pool(&data).unwrap_or_default(),
+        _ => builtin_tag.to_string(),
+    };
+    let string = format!("[object {tag}]");
+    Ok(JSValue::new_string(&get_or_intern_string(&string)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-object.prototype.valueof
+/// Primitives come back as they are rather than boxed in a wrapper object.
+fn object_prototype_value_of(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    if this.is_undefined() || this.is_null() {
+        return Err(JSError::new_type_error(
+            "Cannot convert undefined or null to object",
+        ));
+    }
+    Ok(this.clone())
+}
+
+fn object_is(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let result = is_same_value(&argument(args, 0), &argument(args, 1));
+    Ok(JSValue::new_boolean(result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-object.groupby
+fn object_group_by(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let items = argument(args, 0);
+    let callback = argument(args, 1);
+    if items.is_null() || items.is_undefined() {
+        return Err(JSError::new_type_error(
+            "Cannot convert undefined or null to object",
+        ));
+    }
+    if !interpreter.is_callable(&callback) {
+        return Err(JSError::new_type_error("callback is not a function"));
+    }
+    let mut groups: IndexMap<SymbolU32, Vec<JSValue>> = IndexMap::new();
+    for (index, value) in iterate_to_list(interpreter, &items)?
+        .into_iter()
+        .enumerate()
+    {
+        let index = JSValue::new_number(&(index as f64));
+        let key = interpreter
+            .call_function(&callback, &JSValue::Undefined, &[value.clone(), index])?
+            .to_string(interpreter)?;
+        groups.entry(key).or_default().push(value);
+    }
+    let object_id = JSObject::new_ordinary_object(vec![], true, None, interpreter);
+    for (key, values) in groups {
+        let group = create_array(values, interpreter);
+        interpreter
+            .get_object_mut(object_id)?
+            .add_property(key, group);
+    }
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
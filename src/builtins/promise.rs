@@ -0,0 +1,1073 @@
+//! Promises, their combinators and the driver behind async functions. Reactions never run
+//! synchronously, they are queued as microtasks on the interpreter's job queue, see `jobs`.
+
+use std::mem;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument, create_array,
+        generator::resume_generator,
+        index_key,
+        iterator::iterate_to_list,
+        object::{add_getter, add_methods, add_to_string_tag},
+        species_constructor,
+    },
+    constants::{PROMISE_NAME, PROTOTYPE_NAME},
+    errors::JSError,
+    global::get_or_intern_string,
+    heap::{Trace, Tracer},
+    jobs::Job,
+    resume::ResumeAction,
+    values::{
+        JSObject, JSResult, JSValue, NativeClosure, NativeFunction, ObjectData, ObjectKind,
+        Property, PropertyKey, WellKnownSymbol, get_property_value, link_constructor_and_prototype,
+        set_property_value,
+    },
+};
+
+#[derive(Clone, Debug)]
+enum PromiseStatus {
+    Pending,
+    Fulfilled(JSValue),
+    Rejected(JSValue),
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-properties-of-promise-instances
+#[derive(Clone, Debug)]
+pub struct PromiseState {
+    status: PromiseStatus,
+    fulfill_reactions: Vec<PromiseReaction>,
+    reject_reactions: Vec<PromiseReaction>,
+    /// Whether a handler was ever attached, used to report unhandled rejections.
+    is_handled: bool,
+}
+
+impl PromiseState {
+    /// The value the promise was fulfilled with or the reason it was rejected with, `None`
+    /// while it is pending.
+    pub fn result(&self) -> Option<Result<&JSValue, &JSValue>> {
+        match &self.status {
+            PromiseStatus::Pending => None,
+            PromiseStatus::Fulfilled(value) => Some(Ok(value)),
+            PromiseStatus::Rejected(reason) => Some(Err(reason)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReactionKind {
+    Fulfill,
+    Reject,
+}
+
+#[derive(Clone, Debug)]
+enum ReactionHandler {
+    /// No callable handler was passed, the value or reason is handed on unchanged.
+    Identity,
+    Function(JSValue),
+    /// Resumes an async function suspended at `await` and settles `promise` once it finishes.
+    Await {
+        coroutine: JSValue,
+        promise: JSValue,
+    },
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promisecapability-records
+/// A promise together with the functions that settle it. The promise may come from a
+/// `Symbol.species` constructor, so it's only ever settled through these.
+#[derive(Clone, Debug)]
+pub struct PromiseCapability {
+    promise: JSValue,
+    resolve: JSValue,
+    reject: JSValue,
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promisereaction-records
+#[derive(Clone, Debug)]
+pub struct PromiseReaction {
+    /// The promise returned by `then`, settled with the outcome of the handler.
+    derived: Option<PromiseCapability>,
+    kind: ReactionKind,
+    handler: ReactionHandler,
+}
+
+/// Builds the global `Promise` constructor together with its prototype and static methods.
+impl Trace for PromiseState {
+    fn trace(&self, tracer: &mut Tracer) {
+        match &self.status {
+            PromiseStatus::Fulfilled(value) | PromiseStatus::Rejected(value) => value.trace(tracer),
+            PromiseStatus::Pending => (),
+        }
+        self.fulfill_reactions.trace(tracer);
+        self.reject_reactions.trace(tracer);
+    }
+}
+
+impl Trace for PromiseCapability {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.promise.trace(tracer);
+        self.resolve.trace(tracer);
+        self.reject.trace(tracer);
+    }
+}
+
+impl Trace for PromiseReaction {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.derived.trace(tracer);
+        match &self.handler {
+            ReactionHandler::Identity => (),
+            ReactionHandler::Function(handler) => handler.trace(tracer),
+            ReactionHandler::Await { coroutine, promise } => {
+                coroutine.trace(tracer);
+                promise.trace(tracer);
+            }
+        }
+    }
+}
+
+pub fn create_promise_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id = JSObject::new_native_function(
+        PROMISE_NAME,
+        1,
+        promise_call,
+        Some(promise_construct),
+        interpreter,
+    );
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("then", 2, promise_then),
+            ("catch", 1, promise_catch),
+            ("finally", 1, promise_finally),
+        ],
+        interpreter,
+    );
+    add_to_string_tag(prototype_id, PROMISE_NAME, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.promise_proto_id = prototype_id;
+    interpreter.intrinsics.promise_constructor_id = constructor_id;
+
+    let statics: Vec<(&str, usize, NativeFunction)> = vec![
+        ("resolve", 1, promise_resolve_static),
+        ("reject", 1, promise_reject_static),
+        ("all", 1, promise_all),
+        ("allSettled", 1, promise_all_settled),
+        ("race", 1, promise_race),
+        ("any", 1, promise_any),
+    ];
+    add_methods(constructor_id, statics, interpreter);
+    let species = PropertyKey::well_known(WellKnownSymbol::Species);
+    add_getter(constructor_id, species, promise_species, interpreter);
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(PROMISE_NAME), value)
+}
+
+/// The global `queueMicrotask`.
+pub fn queue_microtask(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let callback = argument(args, 0);
+    if !interpreter.is_callable(&callback) {
+        return Err(JSError::new_type_error(
+            "The \"callback\" argument must be of type function",
+        ));
+    }
+    interpreter.enqueue_job(Job::Callback(callback));
+    Ok(JSValue::Undefined)
+}
+
+pub fn create_promise(interpreter: &mut Interpreter, prototype: Option<usize>) -> JSValue {
+    let state = PromiseState {
+        status: PromiseStatus::Pending,
+        fulfill_reactions: vec![],
+        reject_reactions: vec![],
+        is_handled: false,
+    };
+    let prototype = prototype.unwrap_or(interpreter.intrinsics.promise_proto_id);
+    let data = ObjectData::Promise(Box::new(state));
+    let object_id = JSObject::new_object_with_data(data, Some(prototype), interpreter);
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    }
+}
+
+fn promise_state<'a>(
+    interpreter: &'a mut Interpreter,
+    promise: &JSValue,
+) -> Option<&'a mut PromiseState> {
+    let JSValue::Object { object_id, .. } = promise else {
+        return None;
+    };
+    match interpreter.get_object_mut(*object_id).ok()?.get_data_mut() {
+        Some(ObjectData::Promise(state)) => Some(state),
+        _ => None,
+    }
+}
+
+fn is_promise(interpreter: &mut Interpreter, value: &JSValue) -> bool {
+    promise_state(interpreter, value).is_some()
+}
+
+fn require_promise(interpreter: &mut Interpreter, value: &JSValue, method: &str) -> JSResult<()> {
+    if is_promise(interpreter, value) {
+        return Ok(());
+    }
+    Err(JSError::new_type_error(&format!(
+        "Method Promise.prototype.{method} called on incompatible receiver"
+    )))
+}
+
+/// The rejection reason for an abrupt completion. Anything that isn't an exception, like a
+/// `return` unwinding, keeps propagating.
+fn abrupt_reason(interpreter: &mut Interpreter, error: JSError) -> JSResult<JSValue> {
+    if error.is_exception() {
+        return interpreter.error_value(&error);
+    }
+    Err(error)
+}
+
+fn new_closure(
+    interpreter: &mut Interpreter,
+    length: usize,
+    closure: NativeClosure,
+    captures: Vec<JSValue>,
+) -> JSValue {
+    let object_id = JSObject::new_native_closure("", length, closure, captures, interpreter);
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Function,
+    }
+}
+
+/// State shared between the closures of one call, kept in an object scripts never see.
+fn new_record(interpreter: &mut Interpreter, fields: Vec<(&str, JSValue)>) -> JSValue {
+    let properties = fields
+        .into_iter()
+        .map(|(name, value)| (get_or_intern_string(name), value))
+        .collect();
+    let object_id = JSObject::new_ordinary_object(properties, true, None, interpreter);
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    }
+}
+
+/// Sets a flag of a record, returning whether it was already set.
+fn test_and_set(interpreter: &mut Interpreter, record: &JSValue, field: &str) -> JSResult<bool> {
+    let key = get_or_intern_string(field);
+    let was_set = get_property_value(interpreter, record, key)?.to_boolean();
+    set_property_value(interpreter, record, key, JSValue::new_boolean(true))?;
+    Ok(was_set)
+}
+
+/// Counts down a record's `remaining` field, returning whether it reached zero.
+fn count_down(interpreter: &mut Interpreter, record: &JSValue) -> JSResult<bool> {
+    let key = get_or_intern_string("remaining");
+    let remaining = get_property_value(interpreter, record, key)?.get_number() - 1.0;
+    set_property_value(interpreter, record, key, JSValue::new_number(&remaining))?;
+    Ok(remaining == 0.0)
+}
+
+fn promise_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error(
+        "Promise constructor cannot be invoked without 'new'",
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise-executor
+fn promise_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let executor = argument(args, 0);
+    if !interpreter.is_callable(&executor) {
+        return Err(JSError::new_type_error(
+            "Promise resolver is not a function",
+        ));
+    }
+    let prototype_key = get_or_intern_string(PROTOTYPE_NAME);
+    let prototype = match get_property_value(interpreter, new_target, prototype_key) {
+        Ok(JSValue::Object { object_id, .. }) => Some(object_id),
+        _ => None,
+    };
+    let promise = create_promise(interpreter, prototype);
+    let (resolve, reject) = create_resolving_functions(interpreter, &promise);
+    let result =
+        interpreter.call_function(&executor, &JSValue::Undefined, &[resolve, reject.clone()]);
+    if let Err(error) = result {
+        let reason = abrupt_reason(interpreter, error)?;
+        interpreter.call_function(&reject, &JSValue::Undefined, &[reason])?;
+    }
+    Ok(promise)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-promise-@@species
+fn promise_species(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-newpromisecapability
+fn new_promise_capability(
+    interpreter: &mut Interpreter,
+    constructor: &JSValue,
+) -> JSResult<PromiseCapability> {
+    let record = new_record(
+        interpreter,
+        vec![
+            ("resolve", JSValue::Undefined),
+            ("reject", JSValue::Undefined),
+        ],
+    );
+    let executor = new_closure(interpreter, 2, capability_executor, vec![record.clone()]);
+    let promise = interpreter.construct(constructor, &[executor])?;
+    let resolve = get_property_value(interpreter, &record, get_or_intern_string("resolve"))?;
+    let reject = get_property_value(interpreter, &record, get_or_intern_string("reject"))?;
+    if !interpreter.is_callable(&resolve) || !interpreter.is_callable(&reject) {
+        return Err(JSError::new_type_error(
+            "Promise resolve or reject function is not callable",
+        ));
+    }
+    Ok(PromiseCapability {
+        promise,
+        resolve,
+        reject,
+    })
+}
+
+/// The executor `new_promise_capability` hands to the constructor, it keeps the resolving
+/// functions it is called with.
+fn capability_executor(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let record = &captures[0];
+    for name in ["resolve", "reject"] {
+        let key = get_or_intern_string(name);
+        if !get_property_value(interpreter, record, key)?.is_undefined() {
+            return Err(JSError::new_type_error(
+                "Promise executor has already been invoked with non-undefined arguments",
+            ));
+        }
+    }
+    for (index, name) in ["resolve", "reject"].into_iter().enumerate() {
+        let key = get_or_intern_string(name);
+        set_property_value(interpreter, record, key, argument(args, index))?;
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-createresolvingfunctions
+/// The `resolve`/`reject` pair handed to an executor or a thenable. Only the first call of
+/// either one has any effect.
+fn create_resolving_functions(
+    interpreter: &mut Interpreter,
+    promise: &JSValue,
+) -> (JSValue, JSValue) {
+    let record = new_record(interpreter, vec![("resolved", JSValue::new_boolean(false))]);
+    let captures = vec![promise.clone(), record];
+    let resolve = new_closure(interpreter, 1, resolve_function, captures.clone());
+    let reject = new_closure(interpreter, 1, reject_function, captures);
+    (resolve, reject)
+}
+
+fn resolve_function(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if !test_and_set(interpreter, &captures[1], "resolved")? {
+        resolve_promise(interpreter, &captures[0], argument(args, 0))?;
+    }
+    Ok(JSValue::Undefined)
+}
+
+fn reject_function(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if !test_and_set(interpreter, &captures[1], "resolved")? {
+        reject_promise(interpreter, &captures[0], argument(args, 0))?;
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise-resolve-functions
+/// Resolving with a thenable adopts its state, anything else fulfills the promise.
+pub fn resolve_promise(
+    interpreter: &mut Interpreter,
+    promise: &JSValue,
+    resolution: JSValue,
+) -> JSResult<()> {
+    if resolution.is_object() && resolution.get_object_id().ok() == promise.get_object_id().ok() {
+        let error = JSError::new_type_error("Chaining cycle detected for promise #<Promise>");
+        let reason = interpreter.error_value(&error)?;
+        return reject_promise(interpreter, promise, reason);
+    }
+    if !resolution.is_object() {
+        return settle_promise(interpreter, promise, PromiseStatus::Fulfilled(resolution));
+    }
+    let then = match get_property_value(interpreter, &resolution, get_or_intern_string("then")) {
+        Ok(then) => then,
+        Err(error) => {
+            let reason = abrupt_reason(interpreter, error)?;
+            return reject_promise(interpreter, promise, reason);
+        }
+    };
+    if !interpreter.is_callable(&then) {
+        return settle_promise(interpreter, promise, PromiseStatus::Fulfilled(resolution));
+    }
+    interpreter.enqueue_job(Job::ResolveThenable {
+        promise: promise.clone(),
+        thenable: resolution,
+        then,
+    });
+    Ok(())
+}
+
+pub fn reject_promise(
+    interpreter: &mut Interpreter,
+    promise: &JSValue,
+    reason: JSValue,
+) -> JSResult<()> {
+    settle_promise(interpreter, promise, PromiseStatus::Rejected(reason))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-fulfillpromise
+// https://262.ecma-international.org/15.0/index.html#sec-rejectpromise
+fn settle_promise(
+    interpreter: &mut Interpreter,
+    promise: &JSValue,
+    status: PromiseStatus,
+) -> JSResult<()> {
+    let Some(state) = promise_state(interpreter, promise) else {
+        return Ok(());
+    };
+    if !matches!(state.status, PromiseStatus::Pending) {
+        return Ok(());
+    }
+    let fulfill_reactions = mem::take(&mut state.fulfill_reactions);
+    let reject_reactions = mem::take(&mut state.reject_reactions);
+    state.status = status.clone();
+    let is_handled = state.is_handled;
+    let (reactions, argument) = match status {
+        PromiseStatus::Fulfilled(value) => (fulfill_reactions, value),
+        PromiseStatus::Rejected(reason) => {
+            if !is_handled {
+                interpreter.pending_rejections.push(promise.clone());
+            }
+            (reject_reactions, reason)
+        }
+        PromiseStatus::Pending => return Ok(()),
+    };
+    for reaction in reactions {
+        interpreter.enqueue_job(Job::PromiseReaction {
+            reaction,
+            argument: argument.clone(),
+        });
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-performpromisethen
+fn perform_promise_then(
+    interpreter: &mut Interpreter,
+    promise: &JSValue,
+    on_fulfilled: ReactionHandler,
+    on_rejected: ReactionHandler,
+    derived: Option<PromiseCapability>,
+) -> JSResult<()> {
+    let fulfill_reaction = PromiseReaction {
+        derived: derived.clone(),
+        kind: ReactionKind::Fulfill,
+        handler: on_fulfilled,
+    };
+    let reject_reaction = PromiseReaction {
+        derived,
+        kind: ReactionKind::Reject,
+        handler: on_rejected,
+    };
+    let Some(state) = promise_state(interpreter, promise) else {
+        return Err(JSError::new_type_error("value is not a promise"));
+    };
+    let was_handled = mem::replace(&mut state.is_handled, true);
+    let job = match state.status.clone() {
+        PromiseStatus::Pending => {
+            state.fulfill_reactions.push(fulfill_reaction);
+            state.reject_reactions.push(reject_reaction);
+            return Ok(());
+        }
+        PromiseStatus::Fulfilled(value) => Job::PromiseReaction {
+            reaction: fulfill_reaction,
+            argument: value,
+        },
+        PromiseStatus::Rejected(reason) => {
+            if !was_handled {
+                let id = promise.get_object_id().ok();
+                interpreter
+                    .pending_rejections
+                    .retain(|pending| pending.get_object_id().ok() != id);
+            }
+            Job::PromiseReaction {
+                reaction: reject_reaction,
+                argument: reason,
+            }
+        }
+    };
+    interpreter.enqueue_job(job);
+    Ok(())
+}
+
+pub fn promise_reaction_job(
+    interpreter: &mut Interpreter,
+    reaction: PromiseReaction,
+    argument: JSValue,
+) -> JSResult<()> {
+    let result = match (reaction.handler, reaction.kind) {
+        (ReactionHandler::Await { coroutine, promise }, kind) => {
+            let action = match kind {
+                ReactionKind::Fulfill => ResumeAction::Next(argument),
+                ReactionKind::Reject => ResumeAction::Throw(argument),
+            };
+            return async_step(interpreter, &coroutine, &promise, action);
+        }
+        (ReactionHandler::Identity, ReactionKind::Fulfill) => Ok(argument),
+        (ReactionHandler::Identity, ReactionKind::Reject) => {
+            if let Some(derived) = &reaction.derived {
+                interpreter.call_function(&derived.reject, &JSValue::Undefined, &[argument])?;
+            }
+            return Ok(());
+        }
+        (ReactionHandler::Function(handler), _) => {
+            interpreter.call_function(&handler, &JSValue::Undefined, &[argument])
+        }
+    };
+    let Some(derived) = reaction.derived else {
+        return result.map(|_| ());
+    };
+    let (settle, value) = match result {
+        Ok(value) => (derived.resolve, value),
+        Err(error) => (derived.reject, abrupt_reason(interpreter, error)?),
+    };
+    interpreter.call_function(&settle, &JSValue::Undefined, &[value])?;
+    Ok(())
+}
+
+pub fn resolve_thenable_job(
+    interpreter: &mut Interpreter,
+    promise: JSValue,
+    thenable: JSValue,
+    then: JSValue,
+) -> JSResult<()> {
+    let (resolve, reject) = create_resolving_functions(interpreter, &promise);
+    if let Err(error) = interpreter.call_function(&then, &thenable, &[resolve, reject.clone()]) {
+        let reason = abrupt_reason(interpreter, error)?;
+        interpreter.call_function(&reject, &JSValue::Undefined, &[reason])?;
+    }
+    Ok(())
+}
+
+/// Reports every promise that was rejected without anyone handling it. Runs once the job
+/// queue is empty, so handlers attached by later jobs still count.
+pub fn report_unhandled_rejections(interpreter: &mut Interpreter) {
+    for promise in mem::take(&mut interpreter.pending_rejections) {
+        let reason = match promise_state(interpreter, &promise) {
+            Some(PromiseState {
+                status: PromiseStatus::Rejected(reason),
+                is_handled: false,
+                ..
+            }) => reason.clone(),
+            _ => continue,
+        };
+        let description = interpreter.describe_thrown(&reason);
+        interpreter.report_error(&format!("Uncaught (in promise) {description}"));
+    }
+}
+
+fn reaction_handler(interpreter: &mut Interpreter, value: JSValue) -> ReactionHandler {
+    if interpreter.is_callable(&value) {
+        return ReactionHandler::Function(value);
+    }
+    ReactionHandler::Identity
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise.prototype.then
+fn promise_then(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_promise(interpreter, this, "then")?;
+    let default = JSValue::Object {
+        object_id: interpreter.intrinsics.promise_constructor_id,
+        kind: ObjectKind::Function,
+    };
+    let constructor = species_constructor(interpreter, this, default)?;
+    let derived = new_promise_capability(interpreter, &constructor)?;
+    let on_fulfilled = reaction_handler(interpreter, argument(args, 0));
+    let on_rejected = reaction_handler(interpreter, argument(args, 1));
+    let promise = derived.promise.clone();
+    perform_promise_then(interpreter, this, on_fulfilled, on_rejected, Some(derived))?;
+    Ok(promise)
+}
+
+/// Calls the `then` method of a value, which may be any thenable.
+fn invoke_then(
+    interpreter: &mut Interpreter,
+    promise: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let then = get_property_value(interpreter, promise, get_or_intern_string("then"))?;
+    interpreter.call_function(&then, promise, args)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise.prototype.catch
+fn promise_catch(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    invoke_then(interpreter, this, &[JSValue::Undefined, argument(args, 0)])
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise.prototype.finally
+fn promise_finally(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let on_finally = argument(args, 0);
+    if !interpreter.is_callable(&on_finally) {
+        return invoke_then(interpreter, this, &[on_finally.clone(), on_finally]);
+    }
+    let then_finally = new_closure(interpreter, 1, then_finally, vec![on_finally.clone()]);
+    let catch_finally = new_closure(interpreter, 1, catch_finally, vec![on_finally]);
+    invoke_then(interpreter, this, &[then_finally, catch_finally])
+}
+
+/// Runs the `finally` callback, then passes the original value on once its result settles.
+fn then_finally(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let result = interpreter.call_function(&captures[0], &JSValue::Undefined, &[])?;
+    let promise = promise_resolve(interpreter, result)?;
+    let value_thunk = new_closure(interpreter, 0, return_captured, vec![argument(args, 0)]);
+    invoke_then(interpreter, &promise, &[value_thunk])
+}
+
+/// Like `then_finally`, but rethrows the original reason.
+fn catch_finally(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let result = interpreter.call_function(&captures[0], &JSValue::Undefined, &[])?;
+    let promise = promise_resolve(interpreter, result)?;
+    let thrower = new_closure(interpreter, 0, throw_captured, vec![argument(args, 0)]);
+    invoke_then(interpreter, &promise, &[thrower])
+}
+
+fn return_captured(
+    _interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(captures[0].clone())
+}
+
+fn throw_captured(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(interpreter.throw_value(captures[0].clone()))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise-resolve
+/// Promises are returned as is, anything else is wrapped in a promise resolved with it.
+pub fn promise_resolve(interpreter: &mut Interpreter, value: JSValue) -> JSResult<JSValue> {
+    if is_promise(interpreter, &value) {
+        return Ok(value);
+    }
+    let promise = create_promise(interpreter, None);
+    resolve_promise(interpreter, &promise, value)?;
+    Ok(promise)
+}
+
+fn promise_resolve_static(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    promise_resolve(interpreter, argument(args, 0))
+}
+
+fn promise_reject_static(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let promise = create_promise(interpreter, None);
+    reject_promise(interpreter, &promise, argument(args, 0))?;
+    Ok(promise)
+}
+
+/// Sets up one of the combinators over the elements of an iterable. Any exception along the
+/// way rejects the returned promise instead of being thrown.
+fn combine(
+    interpreter: &mut Interpreter,
+    iterable: &JSValue,
+    setup: fn(&mut Interpreter, Vec<JSValue>, &JSValue, &JSValue) -> JSResult<()>,
+) -> JSResult<JSValue> {
+    let promise = create_promise(interpreter, None);
+    let (resolve, reject) = create_resolving_functions(interpreter, &promise);
+    let result = iterate_to_list(interpreter, iterable)
+        .and_then(|items| setup(interpreter, items, &resolve, &reject));
+    if let Err(error) = result {
+        let reason = abrupt_reason(interpreter, error)?;
+        interpreter.call_function(&reject, &JSValue::Undefined, &[reason])?;
+    }
+    Ok(promise)
+}
+
+/// A per-element callback of a combinator. Captures the shared values, the element's index
+/// and a flag so that only its first call counts.
+fn element_closure(
+    interpreter: &mut Interpreter,
+    closure: NativeClosure,
+    shared: &[JSValue],
+    index: usize,
+    already_called: &JSValue,
+) -> JSValue {
+    let mut captures = shared.to_vec();
+    captures.push(JSValue::new_number(&(index as f64)));
+    captures.push(already_called.clone());
+    new_closure(interpreter, 1, closure, captures)
+}
+
+fn new_called_flag(interpreter: &mut Interpreter) -> JSValue {
+    new_record(interpreter, vec![("called", JSValue::new_boolean(false))])
+}
+
+fn new_countdown(interpreter: &mut Interpreter, count: usize) -> JSValue {
+    // starts one higher so it can't reach zero before every element is subscribed
+    let remaining = JSValue::new_number(&(count as f64 + 1.0));
+    new_record(interpreter, vec![("remaining", remaining)])
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise.all
+fn promise_all(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    combine(interpreter, &argument(args, 0), all_setup)
+}
+
+fn all_setup(
+    interpreter: &mut Interpreter,
+    items: Vec<JSValue>,
+    resolve: &JSValue,
+    reject: &JSValue,
+) -> JSResult<()> {
+    let values = create_array(vec![JSValue::Undefined; items.len()], interpreter);
+    let countdown = new_countdown(interpreter, items.len());
+    let shared = [values.clone(), countdown.clone(), resolve.clone()];
+    for (index, item) in items.into_iter().enumerate() {
+        let next = promise_resolve(interpreter, item)?;
+        let called = new_called_flag(interpreter);
+        let on_fulfilled =
+            element_closure(interpreter, all_resolve_element, &shared, index, &called);
+        invoke_then(interpreter, &next, &[on_fulfilled, reject.clone()])?;
+    }
+    if count_down(interpreter, &countdown)? {
+        interpreter.call_function(resolve, &JSValue::Undefined, &[values])?;
+    }
+    Ok(())
+}
+
+/// Captures `[values, countdown, resolve, index, called]`.
+fn all_resolve_element(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if test_and_set(interpreter, &captures[4], "called")? {
+        return Ok(JSValue::Undefined);
+    }
+    let index = index_key(captures[3].get_number() as usize);
+    set_property_value(interpreter, &captures[0], index, argument(args, 0))?;
+    if count_down(interpreter, &captures[1])? {
+        interpreter.call_function(&captures[2], &JSValue::Undefined, &[captures[0].clone()])?;
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise.allsettled
+fn promise_all_settled(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    combine(interpreter, &argument(args, 0), all_settled_setup)
+}
+
+fn all_settled_setup(
+    interpreter: &mut Interpreter,
+    items: Vec<JSValue>,
+    resolve: &JSValue,
+    _reject: &JSValue,
+) -> JSResult<()> {
+    let values = create_array(vec![JSValue::Undefined; items.len()], interpreter);
+    let countdown = new_countdown(interpreter, items.len());
+    let shared = [values.clone(), countdown.clone(), resolve.clone()];
+    for (index, item) in items.into_iter().enumerate() {
+        let next = promise_resolve(interpreter, item)?;
+        // both callbacks of an element share one flag
+        let called = new_called_flag(interpreter);
+        let on_fulfilled = element_closure(
+            interpreter,
+            settled_fulfill_element,
+            &shared,
+            index,
+            &called,
+        );
+        let on_rejected =
+            element_closure(interpreter, settled_reject_element, &shared, index, &called);
+        invoke_then(interpreter, &next, &[on_fulfilled, on_rejected])?;
+    }
+    if count_down(interpreter, &countdown)? {
+        interpreter.call_function(resolve, &JSValue::Undefined, &[values])?;
+    }
+    Ok(())
+}
+
+fn settled_fulfill_element(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    settled_element(
+        interpreter,
+        captures,
+        "fulfilled",
+        "value",
+        argument(args, 0),
+    )
+}
+
+fn settled_reject_element(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    settled_element(
+        interpreter,
+        captures,
+        "rejected",
+        "reason",
+        argument(args, 0),
+    )
+}
+
+/// Captures `[values, countdown, resolve, index, called]`.
+fn settled_element(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    status: &str,
+    field: &str,
+    value: JSValue,
+) -> JSResult<JSValue> {
+    if test_and_set(interpreter, &captures[4], "called")? {
+        return Ok(JSValue::Undefined);
+    }
+    let properties = vec![
+        (
+            get_or_intern_string("status"),
+            JSValue::new_string(&get_or_intern_string(status)),
+        ),
+        (get_or_intern_string(field), value),
+    ];
+    let outcome = JSValue::new_object(properties, interpreter);
+    let index = index_key(captures[3].get_number() as usize);
+    set_property_value(interpreter, &captures[0], index, outcome)?;
+    if count_down(interpreter, &captures[1])? {
+        interpreter.call_function(&captures[2], &JSValue::Undefined, &[captures[0].clone()])?;
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise.race
+fn promise_race(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    combine(interpreter, &argument(args, 0), race_setup)
+}
+
+fn race_setup(
+    interpreter: &mut Interpreter,
+    items: Vec<JSValue>,
+    resolve: &JSValue,
+    reject: &JSValue,
+) -> JSResult<()> {
+    for item in items {
+        let next = promise_resolve(interpreter, item)?;
+        invoke_then(interpreter, &next, &[resolve.clone(), reject.clone()])?;
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-promise.any
+fn promise_any(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    combine(interpreter, &argument(args, 0), any_setup)
+}
+
+fn any_setup(
+    interpreter: &mut Interpreter,
+    items: Vec<JSValue>,
+    resolve: &JSValue,
+    reject: &JSValue,
+) -> JSResult<()> {
+    let errors = create_array(vec![JSValue::Undefined; items.len()], interpreter);
+    let countdown = new_countdown(interpreter, items.len());
+    let shared = [errors.clone(), countdown.clone(), reject.clone()];
+    for (index, item) in items.into_iter().enumerate() {
+        let next = promise_resolve(interpreter, item)?;
+        let called = new_called_flag(interpreter);
+        let on_rejected = element_closure(interpreter, any_reject_element, &shared, index, &called);
+        invoke_then(interpreter, &next, &[resolve.clone(), on_rejected])?;
+    }
+    if count_down(interpreter, &countdown)? {
+        let error = aggregate_error(interpreter, errors);
+        interpreter.call_function(reject, &JSValue::Undefined, &[error])?;
+    }
+    Ok(())
+}
+
+/// Captures `[errors, countdown, reject, index, called]`.
+fn any_reject_element(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if test_and_set(interpreter, &captures[4], "called")? {
+        return Ok(JSValue::Undefined);
+    }
+    let index = index_key(captures[3].get_number() as usize);
+    set_property_value(interpreter, &captures[0], index, argument(args, 0))?;
+    if count_down(interpreter, &captures[1])? {
+        let error = aggregate_error(interpreter, captures[0].clone());
+        interpreter.call_function(&captures[2], &JSValue::Undefined, &[error])?;
+    }
+    Ok(JSValue::Undefined)
+}
+
+fn aggregate_error(interpreter: &mut Interpreter, errors: JSValue) -> JSValue {
+    let properties = vec![
+        (
+            get_or_intern_string("name"),
+            JSValue::new_string(&get_or_intern_string("AggregateError")),
+        ),
+        (
+            get_or_intern_string("message"),
+            JSValue::new_string(&get_or_intern_string("All promises were rejected")),
+        ),
+        (get_or_intern_string("errors"), errors),
+    ];
+    JSValue::new_object(properties, interpreter)
+}
+
+/// Runs the body of an async function up to its first `await` and returns the promise for
+/// its result. `coroutine` is a generator object wrapping the body.
+pub fn start_async_function(
+    interpreter: &mut Interpreter,
+    coroutine: JSValue,
+) -> JSResult<JSValue> {
+    let promise = create_promise(interpreter, None);
+    async_step(
+        interpreter,
+        &coroutine,
+        &promise,
+        ResumeAction::Next(JSValue::Undefined),
+    )?;
+    Ok(promise)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-asyncblockstart
+/// Resumes the body until the next `await`, or settles `promise` once the body completes.
+fn async_step(
+    interpreter: &mut Interpreter,
+    coroutine: &JSValue,
+    promise: &JSValue,
+    action: ResumeAction,
+) -> JSResult<()> {
+    match resume_generator(interpreter, coroutine, action, "await") {
+        Ok((value, true)) => resolve_promise(interpreter, promise, value),
+        Ok((value, false)) => await_value(interpreter, coroutine, promise, value),
+        Err(error) => {
+            let reason = abrupt_reason(interpreter, error)?;
+            reject_promise(interpreter, promise, reason)
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#await
+fn await_value(
+    interpreter: &mut Interpreter,
+    coroutine: &JSValue,
+    promise: &JSValue,
+    value: JSValue,
+) -> JSResult<()> {
+    let awaited = match promise_resolve(interpreter, value) {
+        Ok(awaited) => awaited,
+        Err(error) => {
+            let reason = abrupt_reason(interpreter, error)?;
+            return async_step(interpreter, coroutine, promise, ResumeAction::Throw(reason));
+        }
+    };
+    let handler = ReactionHandler::Await {
+        coroutine: coroutine.clone(),
+        promise: promise.clone(),
+    };
+    perform_promise_then(interpreter, &awaited, handler.clone(), handler, None)
+}
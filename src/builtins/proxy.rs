@@ -0,0 +1,116 @@
+//! The `Proxy` constructor and `Proxy.revocable`. The proxies themselves live with the other
+//! object kinds, see `ProxyObject`.
+
+use crate::{
+    Interpreter,
+    builtins::{argument, object::add_methods},
+    constants::PROXY_NAME,
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{JSObject, JSResult, JSValue, ObjectKind, Property, ProxyObject},
+};
+
+/// Builds the global `Proxy`. It has no `prototype`, proxies take everything from their target.
+pub fn create_proxy_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id = JSObject::new_native_function(
+        PROXY_NAME,
+        2,
+        proxy_call,
+        Some(proxy_construct),
+        interpreter,
+    );
+    add_methods(
+        constructor_id,
+        vec![("revocable", 2, proxy_revocable)],
+        interpreter,
+    );
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(PROXY_NAME), value)
+}
+
+fn proxy_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error("Constructor Proxy requires 'new'"))
+}
+
+fn proxy_construct(
+    interpreter: &mut Interpreter,
+    _new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    proxy_create(interpreter, &argument(args, 0), &argument(args, 1))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxycreate
+fn proxy_create(
+    interpreter: &mut Interpreter,
+    target: &JSValue,
+    handler: &JSValue,
+) -> JSResult<JSValue> {
+    let (
+        JSValue::Object {
+            object_id: target_id,
+            ..
+        },
+        JSValue::Object {
+            object_id: handler_id,
+            ..
+        },
+    ) = (target, handler)
+    else {
+        return Err(JSError::new_type_error(
+            "Cannot create proxy with a non-object as target or handler",
+        ));
+    };
+    let target_object = interpreter.get_object(*target_id)?;
+    let kind = target_object.kind();
+    let proxy = ProxyObject::new(
+        *target_id,
+        *handler_id,
+        kind,
+        target_object.is_constructor(),
+    );
+    let object_id = JSObject::new_proxy_object(proxy, interpreter);
+    Ok(JSValue::Object { object_id, kind })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy.revocable
+fn proxy_revocable(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let proxy = proxy_create(interpreter, &argument(args, 0), &argument(args, 1))?;
+    let revoke_id =
+        JSObject::new_native_closure("", 0, revoke_proxy, vec![proxy.clone()], interpreter);
+    let revoke = JSValue::Object {
+        object_id: revoke_id,
+        kind: ObjectKind::Function,
+    };
+    let properties = vec![
+        (get_or_intern_string("proxy"), proxy),
+        (get_or_intern_string("revoke"), revoke),
+    ];
+    Ok(JSValue::new_object(properties, interpreter))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-revocation-functions
+/// Revoking twice does nothing more.
+fn revoke_proxy(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let proxy_id = argument(captures, 0).get_object_id()?;
+    if let JSObject::Proxy(proxy) = interpreter.get_object_mut(proxy_id)? {
+        proxy.revoke();
+    }
+    Ok(JSValue::Undefined)
+}
@@ -0,0 +1,254 @@
+//! The `Reflect` namespace: one function per internal method, so a proxy handler can forward
+//! whatever it doesn't want to change.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument, create_array, list_from_array_like,
+        object::{add_methods, add_to_string_tag, from_property, to_property_descriptor},
+    },
+    constants::REFLECT_NAME,
+    debug_value,
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectId, ObjectKind, Property, internal,
+    },
+};
+
+pub fn create_reflect_object(interpreter: &mut Interpreter) -> Property {
+    let proto_id = interpreter.get_object_proto_id();
+    let object_id = JSObject::new_ordinary_object(vec![], true, Some(proto_id), interpreter);
+    let methods: Vec<(&str, usize, NativeFunction)> = vec![
+        ("apply", 3, reflect_apply),
+        ("construct", 2, reflect_construct),
+        ("defineProperty", 3, reflect_define_property),
+        ("deleteProperty", 2, reflect_delete_property),
+        ("get", 2, reflect_get),
+        (
+            "getOwnPropertyDescriptor",
+            2,
+            reflect_get_own_property_descriptor,
+        ),
+        ("getPrototypeOf", 1, reflect_get_prototype_of),
+        ("has", 2, reflect_has),
+        ("isExtensible", 1, reflect_is_extensible),
+        ("ownKeys", 1, reflect_own_keys),
+        ("preventExtensions", 1, reflect_prevent_extensions),
+        ("set", 3, reflect_set),
+        ("setPrototypeOf", 2, reflect_set_prototype_of),
+    ];
+    add_methods(object_id, methods, interpreter);
+    add_to_string_tag(object_id, REFLECT_NAME, interpreter);
+    let value = JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    };
+    (get_or_intern_string(REFLECT_NAME), value)
+}
+
+/// The object the first argument must be, or the TypeError every `Reflect` function throws.
+fn target_object(args: &[JSValue], caller: &str) -> JSResult<ObjectId> {
+    match argument(args, 0) {
+        JSValue::Object { object_id, .. } => Ok(object_id),
+        _ => Err(JSError::new_type_error(&format!(
+            "Reflect.{caller} called on non-object"
+        ))),
+    }
+}
+
+/// The arguments list `apply` and `construct` take, which has to be an array-like object.
+fn arguments_list(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<Vec<JSValue>> {
+    if !value.is_object() {
+        return Err(JSError::new_type_error(
+            "CreateListFromArrayLike called on non-object",
+        ));
+    }
+    list_from_array_like(interpreter, value)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.apply
+fn reflect_apply(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    if !interpreter.is_callable(&target) {
+        let target = debug_value(interpreter, &target);
+        return Err(JSError::new_type_error(&format!(
+            "Reflect.apply target {target} is not a function"
+        )));
+    }
+    let arguments = arguments_list(interpreter, &argument(args, 2))?;
+    interpreter.call_function(&target, &argument(args, 1), &arguments)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.construct
+fn reflect_construct(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let target = argument(args, 0);
+    let new_target = args.get(2).cloned().unwrap_or_else(|| target.clone());
+    for value in [&target, &new_target] {
+        let is_constructor = value
+            .get_object(interpreter)
+            .is_ok_and(|object| object.is_constructor());
+        if !is_constructor {
+            let value = debug_value(interpreter, value);
+            return Err(JSError::new_type_error(&format!(
+                "{value} is not a constructor"
+            )));
+        }
+    }
+    let arguments = arguments_list(interpreter, &argument(args, 1))?;
+    interpreter.construct_with_new_target(&target, &arguments, &new_target)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.defineproperty
+fn reflect_define_property(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "defineProperty")?;
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    let desc = to_property_descriptor(interpreter, &argument(args, 2))?;
+    let result = internal::define_own_property(interpreter, object_id, key, &desc)?;
+    Ok(JSValue::new_boolean(result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.deleteproperty
+fn reflect_delete_property(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "deleteProperty")?;
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    let result = internal::delete(interpreter, object_id, &key)?;
+    Ok(JSValue::new_boolean(result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.get
+fn reflect_get(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "get")?;
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    let receiver = args.get(2).cloned().unwrap_or_else(|| argument(args, 0));
+    internal::get(interpreter, object_id, &key, &receiver)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.getownpropertydescriptor
+fn reflect_get_own_property_descriptor(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "getOwnPropertyDescriptor")?;
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    match internal::get_own_property(interpreter, object_id, &key)? {
+        Some(prop) => Ok(from_property(interpreter, &prop)),
+        None => Ok(JSValue::Undefined),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.getprototypeof
+fn reflect_get_prototype_of(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "getPrototypeOf")?;
+    match internal::get_prototype_of(interpreter, object_id)? {
+        Some(prototype_id) => internal::object_value(interpreter, prototype_id),
+        None => Ok(JSValue::Null),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.has
+fn reflect_has(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "has")?;
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    let result = internal::has_property(interpreter, object_id, &key)?;
+    Ok(JSValue::new_boolean(result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.isextensible
+fn reflect_is_extensible(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "isExtensible")?;
+    let result = internal::is_extensible(interpreter, object_id)?;
+    Ok(JSValue::new_boolean(result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.ownkeys
+fn reflect_own_keys(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "ownKeys")?;
+    let keys = internal::own_property_keys(interpreter, object_id)?
+        .into_iter()
+        .map(|key| key.to_value())
+        .collect();
+    Ok(create_array(keys, interpreter))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.preventextensions
+fn reflect_prevent_extensions(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "preventExtensions")?;
+    let result = internal::prevent_extensions(interpreter, object_id)?;
+    Ok(JSValue::new_boolean(result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.set
+fn reflect_set(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "set")?;
+    let key = argument(args, 1).to_property_key(interpreter)?;
+    let receiver = args.get(3).cloned().unwrap_or_else(|| argument(args, 0));
+    let result = internal::set(interpreter, object_id, &key, argument(args, 2), &receiver)?;
+    Ok(JSValue::new_boolean(result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-reflect.setprototypeof
+fn reflect_set_prototype_of(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let object_id = target_object(args, "setPrototypeOf")?;
+    let prototype = match argument(args, 1) {
+        JSValue::Object { object_id, .. } => Some(object_id),
+        JSValue::Null => None,
+        prototype => {
+            let prototype = debug_value(interpreter, &prototype);
+            return Err(JSError::new_type_error(&format!(
+                "Object prototype may only be an Object or null: {prototype}"
+            )));
+        }
+    };
+    let result = internal::set_prototype_of(interpreter, object_id, prototype)?;
+    Ok(JSValue::new_boolean(result))
+}
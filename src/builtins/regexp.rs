@@ -0,0 +1,1145 @@
+//! The `RegExp` constructor, `RegExp.prototype` with the symbol methods the `String.prototype`
+//! methods delegate to, and the iterator `matchAll` returns. Matching itself is done by the
+//! engine in `crate::regexp`.
+
+use std::sync::Arc;
+
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument, create_array, index_key,
+        iterator::create_iter_result,
+        object::{add_getter, add_methods, add_symbol_methods, add_to_string_tag},
+        species_constructor,
+        string::{from_utf16, to_utf16},
+    },
+    constants::{LAST_INDEX_NAME, LENGTH_NAME, PROTOTYPE_NAME, REGEXP_NAME},
+    debug_value,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    heap::{Trace, Tracer},
+    regexp::{Captures, Flags, Regex, advance_string_index},
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectKind, ObjectPropertyBuilder,
+        Property, PropertyKey, WellKnownSymbol, get_property_value, is_same_value,
+        link_constructor_and_prototype, set_property_value,
+    },
+};
+
+/// [[OriginalSource]], [[OriginalFlags]] and [[RegExpMatcher]] of a RegExp object.
+#[derive(Clone, Debug)]
+pub struct RegExpState {
+    source: String,
+    flags: String,
+    regex: Arc<Regex>,
+}
+
+impl RegExpState {
+    /// `/source/flags`, what `RegExp.prototype.toString` returns for an unmodified RegExp.
+    pub fn literal(&self) -> String {
+        format!("/{}/{}", escape_regexp_pattern(&self.source), self.flags)
+    }
+}
+
+/// The state of the iterator `matchAll` returns.
+#[derive(Clone, Debug)]
+pub struct RegExpStringIteratorState {
+    matcher: JSValue,
+    string: SymbolU32,
+    global: bool,
+    full_unicode: bool,
+    done: bool,
+}
+
+impl Trace for RegExpStringIteratorState {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.matcher.trace(tracer);
+    }
+}
+
+/// Builds the global `RegExp` with its prototype, and the prototype of the iterators `matchAll`
+/// returns.
+pub fn create_regexp_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id = JSObject::new_native_function(
+        REGEXP_NAME,
+        2,
+        regexp_call,
+        Some(regexp_construct),
+        interpreter,
+    );
+    let species = PropertyKey::well_known(WellKnownSymbol::Species);
+    add_getter(constructor_id, species, regexp_species, interpreter);
+
+    // unlike the other prototypes, RegExp.prototype is not a RegExp itself
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("exec", 1, regexp_exec_method),
+            ("test", 1, regexp_test),
+            ("toString", 0, regexp_to_string),
+        ],
+        interpreter,
+    );
+    let getters: Vec<(&str, NativeFunction)> = vec![
+        ("dotAll", regexp_dot_all),
+        ("flags", regexp_flags),
+        ("global", regexp_global),
+        ("hasIndices", regexp_has_indices),
+        ("ignoreCase", regexp_ignore_case),
+        ("multiline", regexp_multiline),
+        ("source", regexp_source),
+        ("sticky", regexp_sticky),
+        ("unicode", regexp_unicode),
+    ];
+    for (name, getter) in getters {
+        add_getter(
+            prototype_id,
+            get_or_intern_string(name),
+            getter,
+            interpreter,
+        );
+    }
+    add_symbol_methods(
+        prototype_id,
+        vec![
+            (WellKnownSymbol::Match, 1, regexp_symbol_match),
+            (WellKnownSymbol::MatchAll, 1, regexp_symbol_match_all),
+            (WellKnownSymbol::Replace, 2, regexp_symbol_replace),
+            (WellKnownSymbol::Search, 1, regexp_symbol_search),
+            (WellKnownSymbol::Split, 2, regexp_symbol_split),
+        ],
+        interpreter,
+    );
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.regexp_proto_id = prototype_id;
+    interpreter.intrinsics.regexp_constructor_id = constructor_id;
+
+    // https://262.ecma-international.org/15.0/index.html#sec-%regexpstringiteratorprototype%-object
+    let iterator_proto_id = interpreter.intrinsics.iterator_proto_id;
+    let string_iterator_id =
+        JSObject::new_ordinary_object(vec![], true, Some(iterator_proto_id), interpreter);
+    add_methods(
+        string_iterator_id,
+        vec![("next", 0, regexp_string_iterator_next)],
+        interpreter,
+    );
+    add_to_string_tag(string_iterator_id, "RegExp String Iterator", interpreter);
+    interpreter.intrinsics.regexp_string_iterator_proto_id = string_iterator_id;
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(REGEXP_NAME), value)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexpcreate
+/// A new RegExp from a pattern and flags, what literals and the `String.prototype` methods
+/// create.
+pub fn regexp_create(
+    interpreter: &mut Interpreter,
+    pattern: &str,
+    flags: &str,
+) -> JSResult<JSValue> {
+    let prototype = interpreter.intrinsics.regexp_proto_id;
+    regexp_initialize(interpreter, pattern, flags, prototype)
+}
+
+/// Compiles a literal's pattern ahead of time, so invalid ones are early errors.
+pub fn validate_regexp_literal(pattern: &str, flags: &str) -> JSResult<()> {
+    let parsed = Flags::parse(flags)
+        .ok_or_else(|| JSError::new_syntax_error("Invalid regular expression flags"))?;
+    Regex::new(pattern, parsed).map_err(|message| {
+        JSError::new_syntax_error(&format!(
+            "Invalid regular expression: /{pattern}/{flags}: {message}"
+        ))
+    })?;
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexpinitialize
+fn regexp_initialize(
+    interpreter: &mut Interpreter,
+    pattern: &str,
+    flags: &str,
+    prototype: usize,
+) -> JSResult<JSValue> {
+    let parsed = Flags::parse(flags).ok_or_else(|| {
+        JSError::new_syntax_error(&format!(
+            "Invalid flags supplied to RegExp constructor '{flags}'"
+        ))
+    })?;
+    let regex = Regex::new(pattern, parsed).map_err(|message| {
+        JSError::new_syntax_error(&format!(
+            "Invalid regular expression: /{pattern}/{flags}: {message}"
+        ))
+    })?;
+    let state = RegExpState {
+        source: pattern.to_string(),
+        flags: flags.to_string(),
+        regex: Arc::new(regex),
+    };
+    let data = ObjectData::RegExp(Box::new(state));
+    let object_id = JSObject::new_object_with_data(data, Some(prototype), interpreter);
+    // lastIndex is the one own property of a RegExp, writable but neither enumerable nor
+    // configurable
+    let last_index = ObjectPropertyBuilder::new(JSValue::new_number(&0.0))
+        .writable(true)
+        .build();
+    interpreter
+        .get_object_mut(object_id)?
+        .define_property(get_or_intern_string(LAST_INDEX_NAME), last_index);
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+fn regexp_state(interpreter: &mut Interpreter, value: &JSValue) -> Option<RegExpState> {
+    let JSValue::Object { object_id, .. } = value else {
+        return None;
+    };
+    match interpreter.get_object(*object_id).ok()?.get_data() {
+        Some(ObjectData::RegExp(state)) => Some((**state).clone()),
+        _ => None,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-isregexp
+pub fn is_regexp(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<bool> {
+    if !value.is_object() {
+        return Ok(false);
+    }
+    let key = PropertyKey::well_known(WellKnownSymbol::Match);
+    let matcher = get_property_value(interpreter, value, key)?;
+    if !matcher.is_undefined() {
+        return Ok(matcher.to_boolean());
+    }
+    Ok(regexp_state(interpreter, value).is_some())
+}
+
+fn regexp_call(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let pattern = argument(args, 0);
+    let flags = argument(args, 1);
+    // RegExp(re) hands back re itself, unless it would come out different
+    if is_regexp(interpreter, &pattern)? && flags.is_undefined() {
+        let constructor_key = get_or_intern_string("constructor");
+        let constructor = get_property_value(interpreter, &pattern, constructor_key)?;
+        if let JSValue::Object { object_id, .. } = constructor
+            && object_id == interpreter.intrinsics.regexp_constructor_id
+        {
+            return Ok(pattern);
+        }
+    }
+    let prototype = interpreter.intrinsics.regexp_proto_id;
+    construct_from(interpreter, &pattern, &flags, prototype)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp-pattern-flags
+fn regexp_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let prototype_key = get_or_intern_string(PROTOTYPE_NAME);
+    let prototype = match get_property_value(interpreter, new_target, prototype_key) {
+        Ok(JSValue::Object { object_id, .. }) => object_id,
+        _ => interpreter.intrinsics.regexp_proto_id,
+    };
+    construct_from(
+        interpreter,
+        &argument(args, 0),
+        &argument(args, 1),
+        prototype,
+    )
+}
+
+/// The part of the constructor both `RegExp(...)` and `new RegExp(...)` share. A RegExp or
+/// RegExp-like pattern lends its source, and its flags when none are given.
+fn construct_from(
+    interpreter: &mut Interpreter,
+    pattern: &JSValue,
+    flags: &JSValue,
+    prototype: usize,
+) -> JSResult<JSValue> {
+    let (source, flags) = if let Some(state) = regexp_state(interpreter, pattern) {
+        let flags = match flags {
+            JSValue::Undefined => state.flags,
+            flags => to_rust_string(interpreter, flags)?,
+        };
+        (state.source, flags)
+    } else if is_regexp(interpreter, pattern)? {
+        let source = get_property_value(interpreter, pattern, get_or_intern_string("source"))?;
+        let flags = match flags {
+            JSValue::Undefined => {
+                get_property_value(interpreter, pattern, get_or_intern_string("flags"))?
+            }
+            flags => flags.clone(),
+        };
+        let source = match source {
+            JSValue::Undefined => String::new(),
+            source => to_rust_string(interpreter, &source)?,
+        };
+        let flags = match flags {
+            JSValue::Undefined => String::new(),
+            flags => to_rust_string(interpreter, &flags)?,
+        };
+        (source, flags)
+    } else {
+        let source = match pattern {
+            JSValue::Undefined => String::new(),
+            pattern => to_rust_string(interpreter, pattern)?,
+        };
+        let flags = match flags {
+            JSValue::Undefined => String::new(),
+            flags => to_rust_string(interpreter, flags)?,
+        };
+        (source, flags)
+    };
+    regexp_initialize(interpreter, &source, &flags, prototype)
+}
+
+fn to_rust_string(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<String> {
+    let string = value.to_string(interpreter)?;
+    Ok(get_string_from_pool(&string).unwrap_or_default())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-regexp-@@species
+fn regexp_species(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this.clone())
+}
+
+fn require_object(interpreter: &mut Interpreter, this: &JSValue, method: &str) -> JSResult<()> {
+    if this.is_object() {
+        return Ok(());
+    }
+    let receiver = debug_value(interpreter, this);
+    Err(JSError::new_type_error(&format!(
+        "Method RegExp.prototype.{method} called on incompatible receiver {receiver}"
+    )))
+}
+
+fn this_regexp(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<RegExpState> {
+    match regexp_state(interpreter, this) {
+        Some(state) => Ok(state),
+        None => {
+            let receiver = debug_value(interpreter, this);
+            Err(JSError::new_type_error(&format!(
+                "Method RegExp.prototype.{method} called on incompatible receiver {receiver}"
+            )))
+        }
+    }
+}
+
+fn get_last_index(interpreter: &mut Interpreter, regexp: &JSValue) -> JSResult<usize> {
+    let key = get_or_intern_string(LAST_INDEX_NAME);
+    let last_index = get_property_value(interpreter, regexp, key)?;
+    Ok(last_index.to_length(interpreter)?.get_number() as usize)
+}
+
+/// Writes `lastIndex` the way the spec's `Set(R, "lastIndex", v, true)` does, failing on a
+/// frozen RegExp.
+fn set_last_index(interpreter: &mut Interpreter, regexp: &JSValue, value: usize) -> JSResult<()> {
+    let key = get_or_intern_string(LAST_INDEX_NAME);
+    let value = JSValue::new_number(&(value as f64));
+    if !set_property_value(interpreter, regexp, key, value)? {
+        return Err(JSError::new_type_error(
+            "Cannot assign to read only property 'lastIndex' of object",
+        ));
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexpexec
+/// Runs a user-defined `exec` if there is one, the built-in one otherwise.
+fn regexp_exec(
+    interpreter: &mut Interpreter,
+    regexp: &JSValue,
+    string: SymbolU32,
+) -> JSResult<JSValue> {
+    let exec = get_property_value(interpreter, regexp, get_or_intern_string("exec"))?;
+    if interpreter.is_callable(&exec) {
+        let result = interpreter.call_function(&exec, regexp, &[JSValue::new_string(&string)])?;
+        if !result.is_object() && !result.is_null() {
+            return Err(JSError::new_type_error(
+                "object returned by exec is neither an object nor null",
+            ));
+        }
+        return Ok(result);
+    }
+    let state = this_regexp(interpreter, regexp, "exec")?;
+    regexp_builtin_exec(interpreter, regexp, &state, string)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexpbuiltinexec
+fn regexp_builtin_exec(
+    interpreter: &mut Interpreter,
+    regexp: &JSValue,
+    state: &RegExpState,
+    string: SymbolU32,
+) -> JSResult<JSValue> {
+    let input = to_utf16(string);
+    let flags = state.regex.flags;
+    let mut last_index = get_last_index(interpreter, regexp)?;
+    if !flags.global && !flags.sticky {
+        last_index = 0;
+    }
+    if last_index > input.len() {
+        if flags.global || flags.sticky {
+            set_last_index(interpreter, regexp, 0)?;
+        }
+        return Ok(JSValue::Null);
+    }
+    let found = state
+        .regex
+        .find(&input, last_index)
+        .map_err(|_| JSError::new_range_error("Maximum call stack size exceeded"))?;
+    let Some(captures) = found else {
+        if flags.global || flags.sticky {
+            set_last_index(interpreter, regexp, 0)?;
+        }
+        return Ok(JSValue::Null);
+    };
+    let (start, end) = captures[0].unwrap_or_default();
+    if flags.global || flags.sticky {
+        set_last_index(interpreter, regexp, end)?;
+    }
+    Ok(match_result(
+        interpreter,
+        &state.regex,
+        &input,
+        string,
+        &captures,
+        start,
+    ))
+}
+
+/// The array `exec` returns: the match and its captures, plus `index`, `input`, `groups` and,
+/// with the `d` flag, `indices`.
+fn match_result(
+    interpreter: &mut Interpreter,
+    regex: &Regex,
+    input: &[u16],
+    string: SymbolU32,
+    captures: &Captures,
+    start: usize,
+) -> JSValue {
+    let values = captures
+        .iter()
+        .map(|capture| match capture {
+            Some((start, end)) => from_utf16(&input[*start..*end]),
+            None => JSValue::Undefined,
+        })
+        .collect::<Vec<_>>();
+    let groups = named_groups(interpreter, regex, &values);
+    let result = create_array(values, interpreter);
+    let mut properties = vec![
+        ("index", JSValue::new_number(&(start as f64))),
+        ("input", JSValue::new_string(&string)),
+        ("groups", groups),
+    ];
+    if regex.flags.has_indices {
+        let pairs = captures
+            .iter()
+            .map(|capture| match capture {
+                Some((start, end)) => {
+                    let pair = vec![
+                        JSValue::new_number(&(*start as f64)),
+                        JSValue::new_number(&(*end as f64)),
+                    ];
+                    create_array(pair, interpreter)
+                }
+                None => JSValue::Undefined,
+            })
+            .collect::<Vec<_>>();
+        let index_groups = named_groups(interpreter, regex, &pairs);
+        let indices = create_array(pairs, interpreter);
+        if let Ok(object) = indices.to_object_mut(interpreter) {
+            object.add_property(get_or_intern_string("groups"), index_groups);
+        }
+        properties.push(("indices", indices));
+    }
+    if let Ok(object) = result.to_object_mut(interpreter) {
+        for (name, value) in properties {
+            object.add_property(get_or_intern_string(name), value);
+        }
+    }
+    result
+}
+
+/// The `groups` object of a match, without a prototype, or `undefined` for patterns without
+/// named groups.
+fn named_groups(interpreter: &mut Interpreter, regex: &Regex, values: &[JSValue]) -> JSValue {
+    if regex.group_names().is_empty() {
+        return JSValue::Undefined;
+    }
+    let properties = regex
+        .group_names()
+        .iter()
+        .map(|(name, index)| (get_or_intern_string(name), values[*index].clone()))
+        .collect();
+    let object_id = JSObject::new_ordinary_object(properties, true, None, interpreter);
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp.prototype.exec
+fn regexp_exec_method(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_regexp(interpreter, this, "exec")?;
+    let string = argument(args, 0).to_string(interpreter)?;
+    regexp_builtin_exec(interpreter, this, &state, string)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp.prototype.test
+fn regexp_test(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object(interpreter, this, "test")?;
+    let string = argument(args, 0).to_string(interpreter)?;
+    let result = regexp_exec(interpreter, this, string)?;
+    Ok(JSValue::new_boolean(!result.is_null()))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp.prototype.tostring
+fn regexp_to_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object(interpreter, this, "toString")?;
+    let source = get_property_value(interpreter, this, get_or_intern_string("source"))?;
+    let source = to_rust_string(interpreter, &source)?;
+    let flags = get_property_value(interpreter, this, get_or_intern_string("flags"))?;
+    let flags = to_rust_string(interpreter, &flags)?;
+    Ok(JSValue::new_string(&get_or_intern_string(&format!(
+        "/{source}/{flags}"
+    ))))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-regexp.prototype.flags
+fn regexp_flags(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    if !this.is_object() {
+        let receiver = debug_value(interpreter, this);
+        return Err(JSError::new_type_error(&format!(
+            "RegExp.prototype.flags getter called on non-object {receiver}"
+        )));
+    }
+    let mut flags = String::new();
+    let names = [
+        ("hasIndices", 'd'),
+        ("global", 'g'),
+        ("ignoreCase", 'i'),
+        ("multiline", 'm'),
+        ("dotAll", 's'),
+        ("unicode", 'u'),
+        ("sticky", 'y'),
+    ];
+    for (name, flag) in names {
+        if get_property_value(interpreter, this, get_or_intern_string(name))?.to_boolean() {
+            flags.push(flag);
+        }
+    }
+    Ok(JSValue::new_string(&get_or_intern_string(&flags)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexphasflag
+/// The flag getters answer `undefined` for `RegExp.prototype` itself and throw for anything
+/// else that isn't a RegExp.
+fn regexp_has_flag(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    name: &str,
+    flag: fn(&Flags) -> bool,
+) -> JSResult<JSValue> {
+    if let Some(state) = regexp_state(interpreter, this) {
+        return Ok(JSValue::new_boolean(flag(&state.regex.flags)));
+    }
+    if let JSValue::Object { object_id, .. } = this
+        && *object_id == interpreter.intrinsics.regexp_proto_id
+    {
+        return Ok(JSValue::Undefined);
+    }
+    let receiver = debug_value(interpreter, this);
+    Err(JSError::new_type_error(&format!(
+        "RegExp.prototype.{name} getter called on non-RegExp object {receiver}"
+    )))
+}
+
+fn regexp_dot_all(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    regexp_has_flag(interpreter, this, "dotAll", |flags| flags.dot_all)
+}
+
+fn regexp_global(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    regexp_has_flag(interpreter, this, "global", |flags| flags.global)
+}
+
+fn regexp_has_indices(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    regexp_has_flag(interpreter, this, "hasIndices", |flags| flags.has_indices)
+}
+
+fn regexp_ignore_case(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    regexp_has_flag(interpreter, this, "ignoreCase", |flags| flags.ignore_case)
+}
+
+fn regexp_multiline(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    regexp_has_flag(interpreter, this, "multiline", |flags| flags.multiline)
+}
+
+fn regexp_sticky(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    regexp_has_flag(interpreter, this, "sticky", |flags| flags.sticky)
+}
+
+fn regexp_unicode(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    regexp_has_flag(interpreter, this, "unicode", |flags| flags.unicode)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-regexp.prototype.source
+fn regexp_source(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let source = match regexp_state(interpreter, this) {
+        Some(state) => escape_regexp_pattern(&state.source),
+        None => match this {
+            JSValue::Object { object_id, .. }
+                if *object_id == interpreter.intrinsics.regexp_proto_id =>
+            {
+                "(?:)".to_string()
+            }
+            _ => {
+                let receiver = debug_value(interpreter, this);
+                return Err(JSError::new_type_error(&format!(
+                    "RegExp.prototype.source getter called on non-RegExp object {receiver}"
+                )));
+            }
+        },
+    };
+    Ok(JSValue::new_string(&get_or_intern_string(&source)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-escaperegexppattern
+/// The source as it would have to be written in a literal: slashes escaped, line breaks spelled
+/// out and `(?:)` for the empty pattern.
+fn escape_regexp_pattern(source: &str) -> String {
+    if source.is_empty() {
+        return "(?:)".to_string();
+    }
+    let mut escaped = String::with_capacity(source.len());
+    let mut in_class = false;
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                escaped.push(c);
+                if let Some(next) = chars.next() {
+                    escaped.push(next);
+                }
+                continue;
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => {
+                escaped.push_str("\\/");
+                continue;
+            }
+            '\n' => {
+                escaped.push_str("\\n");
+                continue;
+            }
+            '\r' => {
+                escaped.push_str("\\r");
+                continue;
+            }
+            '\u{2028}' => {
+                escaped.push_str("\\u2028");
+                continue;
+            }
+            '\u{2029}' => {
+                escaped.push_str("\\u2029");
+                continue;
+            }
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn flags_of(interpreter: &mut Interpreter, regexp: &JSValue) -> JSResult<String> {
+    let flags = get_property_value(interpreter, regexp, get_or_intern_string("flags"))?;
+    to_rust_string(interpreter, &flags)
+}
+
+/// Moves `lastIndex` past an empty match, so global matching can't get stuck on it.
+fn skip_empty_match(
+    interpreter: &mut Interpreter,
+    regexp: &JSValue,
+    result: &JSValue,
+    input: &[u16],
+    full_unicode: bool,
+) -> JSResult<()> {
+    let matched = get_property_value(interpreter, result, index_key(0))?;
+    let matched = to_rust_string(interpreter, &matched)?;
+    if matched.is_empty() {
+        let this_index = get_last_index(interpreter, regexp)?;
+        let next_index = advance_string_index(input, this_index, full_unicode);
+        set_last_index(interpreter, regexp, next_index)?;
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp.prototype-@@match
+fn regexp_symbol_match(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object(interpreter, this, "@@match")?;
+    let string = argument(args, 0).to_string(interpreter)?;
+    let flags = flags_of(interpreter, this)?;
+    if !flags.contains('g') {
+        return regexp_exec(interpreter, this, string);
+    }
+    let full_unicode = flags.contains('u');
+    set_last_index(interpreter, this, 0)?;
+    let input = to_utf16(string);
+    let mut matches = vec![];
+    loop {
+        let result = regexp_exec(interpreter, this, string)?;
+        if result.is_null() {
+            return match matches.is_empty() {
+                true => Ok(JSValue::Null),
+                false => Ok(create_array(matches, interpreter)),
+            };
+        }
+        let matched = get_property_value(interpreter, &result, index_key(0))?;
+        matches.push(JSValue::new_string(&matched.to_string(interpreter)?));
+        skip_empty_match(interpreter, this, &result, &input, full_unicode)?;
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp-prototype-matchall
+fn regexp_symbol_match_all(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object(interpreter, this, "@@matchAll")?;
+    let string = argument(args, 0).to_string(interpreter)?;
+    let default = JSValue::Object {
+        object_id: interpreter.intrinsics.regexp_constructor_id,
+        kind: ObjectKind::Function,
+    };
+    let constructor = species_constructor(interpreter, this, default)?;
+    let flags = flags_of(interpreter, this)?;
+    let flags_value = JSValue::new_string(&get_or_intern_string(&flags));
+    let matcher = interpreter.construct(&constructor, &[this.clone(), flags_value])?;
+    let last_index = get_last_index(interpreter, this)?;
+    set_last_index(interpreter, &matcher, last_index)?;
+    let state = RegExpStringIteratorState {
+        matcher,
+        string,
+        global: flags.contains('g'),
+        full_unicode: flags.contains('u'),
+        done: false,
+    };
+    let data = ObjectData::RegExpStringIterator(Box::new(state));
+    let prototype = interpreter.intrinsics.regexp_string_iterator_proto_id;
+    let object_id = JSObject::new_object_with_data(data, Some(prototype), interpreter);
+    Ok(JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%regexpstringiteratorprototype%.next
+fn regexp_string_iterator_next(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = match this {
+        JSValue::Object { object_id, .. } => match interpreter.get_object(*object_id)?.get_data() {
+            Some(ObjectData::RegExpStringIterator(state)) => Some((*object_id, (**state).clone())),
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some((object_id, state)) = state else {
+        let receiver = debug_value(interpreter, this);
+        return Err(JSError::new_type_error(&format!(
+            "Method %RegExpStringIterator%.prototype.next called on incompatible receiver {receiver}"
+        )));
+    };
+    if state.done {
+        return Ok(create_iter_result(interpreter, JSValue::Undefined, true));
+    }
+    let result = regexp_exec(interpreter, &state.matcher, state.string)?;
+    let finished = result.is_null() || !state.global;
+    if finished
+        && let Some(ObjectData::RegExpStringIterator(state)) =
+            interpreter.get_object_mut(object_id)?.get_data_mut()
+    {
+        state.done = true;
+    }
+    if result.is_null() {
+        return Ok(create_iter_result(interpreter, JSValue::Undefined, true));
+    }
+    if state.global {
+        let input = to_utf16(state.string);
+        skip_empty_match(
+            interpreter,
+            &state.matcher,
+            &result,
+            &input,
+            state.full_unicode,
+        )?;
+    }
+    Ok(create_iter_result(interpreter, result, false))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp.prototype-@@replace
+fn regexp_symbol_replace(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object(interpreter, this, "@@replace")?;
+    let string = argument(args, 0).to_string(interpreter)?;
+    let input = to_utf16(string);
+    let mut replace_value = argument(args, 1);
+    let functional = interpreter.is_callable(&replace_value);
+    if !functional {
+        replace_value = JSValue::new_string(&replace_value.to_string(interpreter)?);
+    }
+    let flags = flags_of(interpreter, this)?;
+    let global = flags.contains('g');
+    if global {
+        set_last_index(interpreter, this, 0)?;
+    }
+    let mut results = vec![];
+    loop {
+        let result = regexp_exec(interpreter, this, string)?;
+        if result.is_null() {
+            break;
+        }
+        results.push(result.clone());
+        if !global {
+            break;
+        }
+        skip_empty_match(interpreter, this, &result, &input, flags.contains('u'))?;
+    }
+
+    let mut accumulated: Vec<u16> = vec![];
+    let mut next_source_position = 0;
+    for result in results {
+        let length_key = get_or_intern_string(LENGTH_NAME);
+        let length = get_property_value(interpreter, &result, length_key)?
+            .to_length(interpreter)?
+            .get_number() as usize;
+        let matched = get_property_value(interpreter, &result, index_key(0))?;
+        let matched = matched.to_string(interpreter)?;
+        let match_length = to_utf16(matched).len();
+        let position = get_property_value(interpreter, &result, get_or_intern_string("index"))?;
+        let position = position.to_integer_or_infinity(interpreter)?.get_number();
+        let position = position.clamp(0.0, input.len() as f64) as usize;
+        let mut captures = vec![];
+        for index in 1..length.max(1) {
+            let capture = get_property_value(interpreter, &result, index_key(index))?;
+            captures.push(match capture {
+                JSValue::Undefined => capture,
+                capture => JSValue::new_string(&capture.to_string(interpreter)?),
+            });
+        }
+        let named_captures =
+            get_property_value(interpreter, &result, get_or_intern_string("groups"))?;
+        let replacement = if functional {
+            let mut replacer_args = vec![JSValue::new_string(&matched)];
+            replacer_args.extend(captures);
+            replacer_args.push(JSValue::new_number(&(position as f64)));
+            replacer_args.push(JSValue::new_string(&string));
+            if !named_captures.is_undefined() {
+                replacer_args.push(named_captures);
+            }
+            let value =
+                interpreter.call_function(&replace_value, &JSValue::Undefined, &replacer_args)?;
+            to_utf16(value.to_string(interpreter)?)
+        } else {
+            let template = to_utf16(replace_value.to_string(interpreter)?);
+            let substitution = Substitution {
+                matched: &to_utf16(matched),
+                string: &input,
+                position,
+                captures: &captures,
+                named_captures: &named_captures,
+            };
+            substitution.apply(interpreter, &template)?
+        };
+        if position >= next_source_position {
+            accumulated.extend_from_slice(&input[next_source_position..position]);
+            accumulated.extend(replacement);
+            next_source_position = position + match_length;
+        }
+    }
+    if next_source_position < input.len() {
+        accumulated.extend_from_slice(&input[next_source_position..]);
+    }
+    Ok(from_utf16(&accumulated))
+}
+
+/// What `$`-patterns in a replacement string refer to.
+// https://262.ecma-international.org/15.0/index.html#sec-getsubstitution
+pub struct Substitution<'a> {
+    pub matched: &'a [u16],
+    pub string: &'a [u16],
+    pub position: usize,
+    /// Strings, or `undefined` for groups that didn't participate.
+    pub captures: &'a [JSValue],
+    /// The `groups` object, or `undefined` when there are no named groups.
+    pub named_captures: &'a JSValue,
+}
+
+impl Substitution<'_> {
+    /// Expands `$$`, `$&`, `` $` ``, `$'`, `$n`, `$nn` and `$<name>` in `template`.
+    pub fn apply(&self, interpreter: &mut Interpreter, template: &[u16]) -> JSResult<Vec<u16>> {
+        let dollar = u16::from(b'$');
+        let digit = |unit: Option<&u16>| {
+            unit.and_then(|unit| char::from_u32(u32::from(*unit)))
+                .and_then(|c| c.to_digit(10))
+                .map(|digit| digit as usize)
+        };
+        let mut result = vec![];
+        let mut index = 0;
+        while index < template.len() {
+            let unit = template[index];
+            let next = template.get(index + 1).copied();
+            if unit != dollar || next.is_none() {
+                result.push(unit);
+                index += 1;
+                continue;
+            }
+            let next = next.unwrap_or_default();
+            match char::from_u32(u32::from(next)) {
+                Some('$') => {
+                    result.push(dollar);
+                    index += 2;
+                }
+                Some('&') => {
+                    result.extend_from_slice(self.matched);
+                    index += 2;
+                }
+                Some('`') => {
+                    result.extend_from_slice(&self.string[..self.position]);
+                    index += 2;
+                }
+                Some('\'') => {
+                    let end = (self.position + self.matched.len()).min(self.string.len());
+                    result.extend_from_slice(&self.string[end..]);
+                    index += 2;
+                }
+                Some('0'..='9') => {
+                    let count = self.captures.len();
+                    let first = digit(template.get(index + 1)).unwrap_or_default();
+                    let two_digits = digit(template.get(index + 2))
+                        .map(|second| first * 10 + second)
+                        .filter(|number| (1..=count).contains(number));
+                    let (number, length) = match two_digits {
+                        Some(number) => (number, 3),
+                        None => (first, 2),
+                    };
+                    if !(1..=count).contains(&number) {
+                        result.push(dollar);
+                        index += 1;
+                        continue;
+                    }
+                    if let JSValue::String { data } = &self.captures[number - 1] {
+                        result.extend(to_utf16(*data));
+                    }
+                    index += length;
+                }
+                Some('<') => {
+                    let close = template[index + 2..]
+                        .iter()
+                        .position(|unit| *unit == u16::from(b'>'));
+                    let (false, Some(close)) = (self.named_captures.is_undefined(), close) else {
+                        result.push(dollar);
+                        index += 1;
+                        continue;
+                    };
+                    let name = String::from_utf16_lossy(&template[index + 2..index + 2 + close]);
+                    let capture = get_property_value(
+                        interpreter,
+                        self.named_captures,
+                        get_or_intern_string(&name),
+                    )?;
+                    if !capture.is_undefined() {
+                        result.extend(to_utf16(capture.to_string(interpreter)?));
+                    }
+                    index += close + 3;
+                }
+                _ => {
+                    result.push(dollar);
+                    index += 1;
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp.prototype-@@search
+fn regexp_symbol_search(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object(interpreter, this, "@@search")?;
+    let string = argument(args, 0).to_string(interpreter)?;
+    let key = get_or_intern_string(LAST_INDEX_NAME);
+    let previous = get_property_value(interpreter, this, key)?;
+    let zero = JSValue::new_number(&0.0);
+    if !is_same_value(&previous, &zero) {
+        set_property_value(interpreter, this, key, zero)?;
+    }
+    let result = regexp_exec(interpreter, this, string)?;
+    let current = get_property_value(interpreter, this, key)?;
+    if !is_same_value(&current, &previous) {
+        set_property_value(interpreter, this, key, previous)?;
+    }
+    if result.is_null() {
+        return Ok(JSValue::new_number(&-1.0));
+    }
+    get_property_value(interpreter, &result, get_or_intern_string("index"))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-regexp.prototype-@@split
+fn regexp_symbol_split(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object(interpreter, this, "@@split")?;
+    let string = argument(args, 0).to_string(interpreter)?;
+    let input = to_utf16(string);
+    let default = JSValue::Object {
+        object_id: interpreter.intrinsics.regexp_constructor_id,
+        kind: ObjectKind::Function,
+    };
+    let constructor = species_constructor(interpreter, this, default)?;
+    let flags = flags_of(interpreter, this)?;
+    let unicode_matching = flags.contains('u');
+    // the splitter is sticky, it tries a match at every position in turn
+    let new_flags = match flags.contains('y') {
+        true => flags,
+        false => format!("{flags}y"),
+    };
+    let new_flags = JSValue::new_string(&get_or_intern_string(&new_flags));
+    let splitter = interpreter.construct(&constructor, &[this.clone(), new_flags])?;
+    let limit = match argument(args, 1) {
+        JSValue::Undefined => u32::MAX,
+        limit => limit.to_uint_32(interpreter)?,
+    } as usize;
+    let mut parts = vec![];
+    if limit == 0 {
+        return Ok(create_array(parts, interpreter));
+    }
+    if input.is_empty() {
+        let result = regexp_exec(interpreter, &splitter, string)?;
+        if result.is_null() {
+            parts.push(JSValue::new_string(&string));
+        }
+        return Ok(create_array(parts, interpreter));
+    }
+    let size = input.len();
+    let mut p = 0;
+    let mut q = p;
+    while q < size {
+        set_last_index(interpreter, &splitter, q)?;
+        let result = regexp_exec(interpreter, &splitter, string)?;
+        if result.is_null() {
+            q = advance_string_index(&input, q, unicode_matching);
+            continue;
+        }
+        let e = get_last_index(interpreter, &splitter)?.min(size);
+        if e == p {
+            q = advance_string_index(&input, q, unicode_matching);
+            continue;
+        }
+        parts.push(from_utf16(&input[p..q]));
+        if parts.len() == limit {
+            return Ok(create_array(parts, interpreter));
+        }
+        p = e;
+        let length_key = get_or_intern_string(LENGTH_NAME);
+        let length = get_property_value(interpreter, &result, length_key)?
+            .to_length(interpreter)?
+            .get_number() as usize;
+        for index in 1..length.max(1) {
+            parts.push(get_property_value(interpreter, &result, index_key(index))?);
+            if parts.len() == limit {
+                return Ok(create_array(parts, interpreter));
+            }
+        }
+        q = p;
+    }
+    parts.push(from_utf16(&input[p..size]));
+    Ok(create_array(parts, interpreter))
+}
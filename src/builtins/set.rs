@@ -0,0 +1,230 @@
+//! The `Set` constructor, `Set.prototype` and the prototype of the iterators its `values` and
+//! `entries` return.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        collection::{
+            IterationKind, OrderedTable, add_from_iterable, close_cursor, collection_iterator_next,
+            create_collection_iterator, for_each_callback, next_entry, prototype_from_new_target,
+            this_collection,
+        },
+        object::{add_getter, add_method_alias, add_methods, add_to_string_tag},
+    },
+    constants::SET_NAME,
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{
+        JSObject, JSResult, JSValue, ObjectData, ObjectId, ObjectKind, Property, PropertyKey,
+        WellKnownSymbol, link_constructor_and_prototype,
+    },
+};
+
+/// Builds the global `Set` with its prototype, and the prototype of Set iterators.
+pub fn create_set_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id =
+        JSObject::new_native_function(SET_NAME, 0, set_call, Some(set_construct), interpreter);
+    let species = PropertyKey::well_known(WellKnownSymbol::Species);
+    add_getter(constructor_id, species, set_species, interpreter);
+
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("add", 1, set_add),
+            ("clear", 0, set_clear),
+            ("delete", 1, set_delete),
+            ("entries", 0, set_entries),
+            ("forEach", 1, set_for_each),
+            ("has", 1, set_has),
+            ("values", 0, set_values),
+        ],
+        interpreter,
+    );
+    add_getter(
+        prototype_id,
+        get_or_intern_string("size"),
+        set_size,
+        interpreter,
+    );
+    // keys and @@iterator are the very same function as values
+    add_method_alias(
+        prototype_id,
+        "values",
+        get_or_intern_string("keys"),
+        interpreter,
+    );
+    let iterator = PropertyKey::well_known(WellKnownSymbol::Iterator);
+    add_method_alias(prototype_id, "values", iterator, interpreter);
+    add_to_string_tag(prototype_id, SET_NAME, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.set_proto_id = prototype_id;
+
+    // https://262.ecma-international.org/15.0/index.html#sec-%setiteratorprototype%-object
+    let iterator_proto_id = interpreter.intrinsics.iterator_proto_id;
+    let set_iterator_id =
+        JSObject::new_ordinary_object(vec![], true, Some(iterator_proto_id), interpreter);
+    add_methods(
+        set_iterator_id,
+        vec![("next", 0, set_iterator_next)],
+        interpreter,
+    );
+    add_to_string_tag(set_iterator_id, "Set Iterator", interpreter);
+    interpreter.intrinsics.set_iterator_proto_id = set_iterator_id;
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(SET_NAME), value)
+}
+
+fn set_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error("Constructor Set requires 'new'"))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set-iterable
+fn set_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let fallback = interpreter.intrinsics.set_proto_id;
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    let data = ObjectData::Set(Box::new(OrderedTable::new()));
+    let set = JSValue::Object {
+        object_id: JSObject::new_object_with_data(data, Some(prototype), interpreter),
+        kind: ObjectKind::Object,
+    };
+    add_from_iterable(interpreter, &set, &argument(args, 0), "add", false)?;
+    Ok(set)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-set-@@species
+fn set_species(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this.clone())
+}
+
+/// The id of the Set `this` is, or the receiver error of `method`.
+fn this_set(interpreter: &mut Interpreter, this: &JSValue, method: &str) -> JSResult<ObjectId> {
+    this_collection(
+        interpreter,
+        this,
+        &format!("Set.prototype.{method}"),
+        |data| matches!(data, ObjectData::Set(_)),
+    )
+}
+
+/// The values of a Set `this_set` has vouched for.
+fn set_table(interpreter: &mut Interpreter, set_id: ObjectId) -> JSResult<&mut OrderedTable<()>> {
+    match interpreter.get_object_mut(set_id)?.get_data_mut() {
+        Some(ObjectData::Set(table)) => Ok(table),
+        _ => Err(JSError::new_type_error("object is not a Set")),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set.prototype.add
+fn set_add(interpreter: &mut Interpreter, this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let set_id = this_set(interpreter, this, "add")?;
+    set_table(interpreter, set_id)?.insert(argument(args, 0), ());
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set.prototype.clear
+fn set_clear(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let set_id = this_set(interpreter, this, "clear")?;
+    set_table(interpreter, set_id)?.clear();
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set.prototype.delete
+fn set_delete(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let set_id = this_set(interpreter, this, "delete")?;
+    let deleted = set_table(interpreter, set_id)?.remove(&argument(args, 0));
+    Ok(JSValue::new_boolean(deleted))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set.prototype.entries
+fn set_entries(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let set_id = this_set(interpreter, this, "entries")?;
+    create_collection_iterator(interpreter, set_id, IterationKind::Entries)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set.prototype.foreach
+/// Like `Map.prototype.forEach`, with each value passed as its own key.
+fn set_for_each(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let set_id = this_set(interpreter, this, "forEach")?;
+    let (callback, this_arg) = for_each_callback(interpreter, args)?;
+    set_table(interpreter, set_id)?.open_cursor();
+    let mut position = 0;
+    while let Some((found, key, value)) = next_entry(interpreter, set_id, position)? {
+        position = found + 1;
+        let args = [value, key, this.clone()];
+        if let Err(error) = interpreter.call_function(&callback, &this_arg, &args) {
+            close_cursor(interpreter, set_id)?;
+            return Err(error);
+        }
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set.prototype.has
+fn set_has(interpreter: &mut Interpreter, this: &JSValue, args: &[JSValue]) -> JSResult<JSValue> {
+    let set_id = this_set(interpreter, this, "has")?;
+    let has = set_table(interpreter, set_id)?.contains(&argument(args, 0));
+    Ok(JSValue::new_boolean(has))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-set.prototype.size
+fn set_size(interpreter: &mut Interpreter, this: &JSValue, _args: &[JSValue]) -> JSResult<JSValue> {
+    let set_id = this_collection(interpreter, this, "get Set.prototype.size", |data| {
+        matches!(data, ObjectData::Set(_))
+    })?;
+    let size = set_table(interpreter, set_id)?.len();
+    Ok(JSValue::new_number(&(size as f64)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set.prototype.values
+fn set_values(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let set_id = this_set(interpreter, this, "values")?;
+    create_collection_iterator(interpreter, set_id, IterationKind::Values)
+}
+
+fn set_iterator_next(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    collection_iterator_next(interpreter, this, SET_NAME)
+}
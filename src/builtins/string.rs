@@ -0,0 +1,423 @@
+//! The `String` function and the `String.prototype` methods built on regular expressions. String
+//! wrapper objects only come from `Object(string)`, so `String` converts but can't be used with
+//! `new`.
+
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        collection::IterationKind,
+        create_array,
+        object::{add_methods, add_symbol_methods},
+        regexp::{Substitution, is_regexp, regexp_create},
+        typed_array::new_array_iterator,
+    },
+    constants::STRING_NAME,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    values::{
+        JSObject, JSResult, JSValue, ObjectData, ObjectKind, Property, PropertyKey,
+        WellKnownSymbol, get_property_value, link_constructor_and_prototype,
+    },
+};
+
+/// Builds the global `String` and the prototype string primitives look their methods up on.
+pub fn create_string_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id =
+        JSObject::new_native_function(STRING_NAME, 1, string_call, None, interpreter);
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("match", 1, string_match),
+            ("matchAll", 1, string_match_all),
+            ("replace", 2, string_replace),
+            ("search", 1, string_search),
+            ("split", 2, string_split),
+            ("toString", 0, string_to_string),
+            ("valueOf", 0, string_value_of),
+        ],
+        interpreter,
+    );
+    add_symbol_methods(
+        prototype_id,
+        vec![(WellKnownSymbol::Iterator, 0, string_iterator)],
+        interpreter,
+    );
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.string_proto_id = prototype_id;
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(STRING_NAME), value)
+}
+
+/// The UTF-16 code units of an interned string, which is what string indices count.
+pub fn to_utf16(string: SymbolU32) -> Vec<u16> {
+    get_string_from_pool(&string)
+        .unwrap_or_default()
+        .encode_utf16()
+        .collect()
+}
+
+/// Interns code units as a string value. Lone surrogates can't be interned and become U+FFFD.
+pub fn from_utf16(units: &[u16]) -> JSValue {
+    JSValue::new_string(&get_or_intern_string(&String::from_utf16_lossy(units)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string-constructor-string-value
+fn string_call(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let string = match args.first() {
+        None => get_or_intern_string(""),
+        // the one conversion of a symbol to a string that doesn't throw
+        Some(JSValue::Symbol { id, description }) => {
+            let symbol = PropertyKey::Symbol {
+                id: *id,
+                description: *description,
+            };
+            get_or_intern_string(&symbol.to_string())
+        }
+        Some(value) => value.to_string(interpreter)?,
+    };
+    Ok(JSValue::new_string(&string))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype-@@iterator
+/// Iterates the string's code points. Strings can't change, so this is an array iterator over
+/// the code points as they are now.
+fn string_iterator(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    if matches!(this, JSValue::Null | JSValue::Undefined) {
+        return Err(JSError::new_type_error(
+            "String.prototype[Symbol.iterator] called on null or undefined",
+        ));
+    }
+    let string = get_string_from_pool(&this.to_string(interpreter)?).unwrap_or_default();
+    let code_points = string
+        .chars()
+        .map(|c| JSValue::new_string(&get_or_intern_string(&c.to_string())))
+        .collect();
+    let array = create_array(code_points, interpreter);
+    Ok(new_array_iterator(
+        interpreter,
+        array.get_object_id()?,
+        IterationKind::Values,
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-thisstringvalue
+fn this_string_value(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<SymbolU32> {
+    match this {
+        JSValue::String { data } => Ok(*data),
+        JSValue::Object { object_id, .. }
+            if let Some(ObjectData::String(data)) =
+                interpreter.get_object(*object_id)?.get_data() =>
+        {
+            Ok(*data)
+        }
+        _ => Err(JSError::new_type_error(&format!(
+            "String.prototype.{method} requires that 'this' be a String"
+        ))),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype.tostring
+fn string_to_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(JSValue::new_string(&this_string_value(
+        interpreter,
+        this,
+        "toString",
+    )?))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype.valueof
+fn string_value_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(JSValue::new_string(&this_string_value(
+        interpreter,
+        this,
+        "valueOf",
+    )?))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-requireobjectcoercible
+fn require_object_coercible(this: &JSValue, method: &str) -> JSResult<()> {
+    if this.is_null() || this.is_undefined() {
+        return Err(JSError::new_type_error(&format!(
+            "String.prototype.{method} called on null or undefined"
+        )));
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getmethod
+fn get_method(
+    interpreter: &mut Interpreter,
+    value: &JSValue,
+    symbol: WellKnownSymbol,
+) -> JSResult<Option<JSValue>> {
+    let key = PropertyKey::well_known(symbol);
+    let method = get_property_value(interpreter, value, key)?;
+    if method.is_undefined() || method.is_null() {
+        return Ok(None);
+    }
+    if !interpreter.is_callable(&method) {
+        return Err(JSError::new_type_error(&format!("{key} is not a function")));
+    }
+    Ok(Some(method))
+}
+
+/// Hands the call to the argument's symbol method, e.g. `RegExp.prototype[@@match]` for
+/// `match`. `None` when the argument doesn't have one.
+fn delegate(
+    interpreter: &mut Interpreter,
+    target: &JSValue,
+    symbol: WellKnownSymbol,
+    args: &[JSValue],
+) -> JSResult<Option<JSValue>> {
+    if target.is_undefined() || target.is_null() {
+        return Ok(None);
+    }
+    match get_method(interpreter, target, symbol)? {
+        Some(method) => Ok(Some(interpreter.call_function(&method, target, args)?)),
+        None => Ok(None),
+    }
+}
+
+/// Creates a RegExp from the argument and calls its symbol method on the string.
+fn invoke_on_new_regexp(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    pattern: &JSValue,
+    flags: &str,
+    symbol: WellKnownSymbol,
+) -> JSResult<JSValue> {
+    let string = JSValue::new_string(&this.to_string(interpreter)?);
+    let pattern = match pattern {
+        JSValue::Undefined => String::new(),
+        pattern => get_string_from_pool(&pattern.to_string(interpreter)?).unwrap_or_default(),
+    };
+    let regexp = regexp_create(interpreter, &pattern, flags)?;
+    let method = get_property_value(interpreter, &regexp, PropertyKey::well_known(symbol))?;
+    interpreter.call_function(&method, &regexp, &[string])
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype.match
+fn string_match(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object_coercible(this, "match")?;
+    let regexp = argument(args, 0);
+    if let Some(result) = delegate(
+        interpreter,
+        &regexp,
+        WellKnownSymbol::Match,
+        std::slice::from_ref(this),
+    )? {
+        return Ok(result);
+    }
+    invoke_on_new_regexp(interpreter, this, &regexp, "", WellKnownSymbol::Match)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype.matchall
+fn string_match_all(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object_coercible(this, "matchAll")?;
+    let regexp = argument(args, 0);
+    if is_regexp(interpreter, &regexp)? {
+        let flags = get_property_value(interpreter, &regexp, get_or_intern_string("flags"))?;
+        require_object_coercible(&flags, "matchAll")?;
+        let flags = flags.to_string(interpreter)?;
+        if !get_string_from_pool(&flags)
+            .unwrap_or_default()
+            .contains('g')
+        {
+            return Err(JSError::new_type_error(
+                "String.prototype.matchAll called with a non-global RegExp argument",
+            ));
+        }
+    }
+    let args = [this.clone()];
+    if let Some(result) = delegate(interpreter, &regexp, WellKnownSymbol::MatchAll, &args)? {
+        return Ok(result);
+    }
+    invoke_on_new_regexp(interpreter, this, &regexp, "g", WellKnownSymbol::MatchAll)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype.replace
+fn string_replace(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object_coercible(this, "replace")?;
+    let search_value = argument(args, 0);
+    let mut replace_value = argument(args, 1);
+    let delegated_args = [this.clone(), replace_value.clone()];
+    let replaced = delegate(
+        interpreter,
+        &search_value,
+        WellKnownSymbol::Replace,
+        &delegated_args,
+    )?;
+    if let Some(result) = replaced {
+        return Ok(result);
+    }
+    let string = this.to_string(interpreter)?;
+    let search_string = search_value.to_string(interpreter)?;
+    let functional = interpreter.is_callable(&replace_value);
+    if !functional {
+        replace_value = JSValue::new_string(&replace_value.to_string(interpreter)?);
+    }
+    let units = to_utf16(string);
+    let search = to_utf16(search_string);
+    let Some(position) = string_index_of(&units, &search, 0) else {
+        return Ok(JSValue::new_string(&string));
+    };
+    let replacement = if functional {
+        let replacer_args = [
+            JSValue::new_string(&search_string),
+            JSValue::new_number(&(position as f64)),
+            JSValue::new_string(&string),
+        ];
+        let value =
+            interpreter.call_function(&replace_value, &JSValue::Undefined, &replacer_args)?;
+        to_utf16(value.to_string(interpreter)?)
+    } else {
+        let template = to_utf16(replace_value.to_string(interpreter)?);
+        let substitution = Substitution {
+            matched: &search,
+            string: &units,
+            position,
+            captures: &[],
+            named_captures: &JSValue::Undefined,
+        };
+        substitution.apply(interpreter, &template)?
+    };
+    let mut result = units[..position].to_vec();
+    result.extend(replacement);
+    result.extend_from_slice(&units[position + search.len()..]);
+    Ok(from_utf16(&result))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype.search
+fn string_search(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object_coercible(this, "search")?;
+    let regexp = argument(args, 0);
+    if let Some(result) = delegate(
+        interpreter,
+        &regexp,
+        WellKnownSymbol::Search,
+        std::slice::from_ref(this),
+    )? {
+        return Ok(result);
+    }
+    invoke_on_new_regexp(interpreter, this, &regexp, "", WellKnownSymbol::Search)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string.prototype.split
+fn string_split(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_object_coercible(this, "split")?;
+    let separator = argument(args, 0);
+    let limit = argument(args, 1);
+    let delegated_args = [this.clone(), limit.clone()];
+    if let Some(result) = delegate(
+        interpreter,
+        &separator,
+        WellKnownSymbol::Split,
+        &delegated_args,
+    )? {
+        return Ok(result);
+    }
+    let string = this.to_string(interpreter)?;
+    let limit = match limit {
+        JSValue::Undefined => u32::MAX,
+        limit => limit.to_uint_32(interpreter)?,
+    } as usize;
+    let separator_string = separator.to_string(interpreter)?;
+    if limit == 0 {
+        return Ok(create_array(vec![], interpreter));
+    }
+    if separator.is_undefined() {
+        return Ok(create_array(
+            vec![JSValue::new_string(&string)],
+            interpreter,
+        ));
+    }
+    let units = to_utf16(string);
+    let separator = to_utf16(separator_string);
+    // an empty separator splits into code units
+    if separator.is_empty() {
+        let parts = units
+            .iter()
+            .take(limit)
+            .map(|unit| from_utf16(std::slice::from_ref(unit)))
+            .collect();
+        return Ok(create_array(parts, interpreter));
+    }
+    if units.is_empty() {
+        return Ok(create_array(
+            vec![JSValue::new_string(&string)],
+            interpreter,
+        ));
+    }
+    let mut parts = vec![];
+    let mut start = 0;
+    while let Some(end) = string_index_of(&units, &separator, start) {
+        parts.push(from_utf16(&units[start..end]));
+        if parts.len() == limit {
+            return Ok(create_array(parts, interpreter));
+        }
+        start = end + separator.len();
+    }
+    parts.push(from_utf16(&units[start..]));
+    Ok(create_array(parts, interpreter))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-stringindexof
+fn string_index_of(string: &[u16], search: &[u16], from: usize) -> Option<usize> {
+    if search.is_empty() {
+        return (from <= string.len()).then_some(from);
+    }
+    if search.len() > string.len() {
+        return None;
+    }
+    (from..=string.len() - search.len()).find(|&index| string[index..].starts_with(search))
+}
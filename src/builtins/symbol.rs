@@ -0,0 +1,216 @@
+//! The `Symbol` function, the global symbol registry, the well-known symbols and
+//! `Symbol.prototype`.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        object::{add_getter, add_methods, add_to_string_tag},
+    },
+    constants::SYMBOL_NAME,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectKind, ObjectProperty,
+        ObjectPropertyBuilder, Property, PropertyKey, WellKnownSymbol,
+        link_constructor_and_prototype,
+    },
+};
+
+/// Builds the global `Symbol` function. Every call makes a new symbol, so it can't be used with
+/// `new`.
+pub fn create_symbol_constructor(interpreter: &mut Interpreter) -> Property {
+    let constructor_id =
+        JSObject::new_native_function(SYMBOL_NAME, 0, symbol_call, None, interpreter);
+    let prototype = JSValue::new_object(vec![], interpreter);
+    let prototype_id = prototype.get_object_id().unwrap_or_default();
+    add_methods(
+        prototype_id,
+        vec![
+            ("toString", 0, symbol_to_string),
+            ("valueOf", 0, symbol_value_of),
+        ],
+        interpreter,
+    );
+    let description = get_or_intern_string("description");
+    add_getter(prototype_id, description, symbol_description, interpreter);
+    // unlike other symbol-keyed methods, this one is read-only
+    let key = PropertyKey::well_known(WellKnownSymbol::ToPrimitive);
+    let (_, to_primitive) =
+        JSObject::new_native_fn(&key.function_name(), 1, symbol_value_of, interpreter);
+    let prop = ObjectPropertyBuilder::new(to_primitive)
+        .configurable(true)
+        .build();
+    define(prototype_id, key, prop, interpreter);
+    add_to_string_tag(prototype_id, SYMBOL_NAME, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.symbol_proto_id = prototype_id;
+
+    let statics: Vec<(&str, usize, NativeFunction)> =
+        vec![("for", 1, symbol_for), ("keyFor", 1, symbol_key_for)];
+    add_methods(constructor_id, statics, interpreter);
+    for symbol in WellKnownSymbol::ALL {
+        let prop = ObjectPropertyBuilder::new(symbol.to_value()).build();
+        define(
+            constructor_id,
+            get_or_intern_string(symbol.name()),
+            prop,
+            interpreter,
+        );
+    }
+
+    // what `instanceof` does for every function that doesn't bring its own `@@hasInstance`
+    let key = PropertyKey::well_known(WellKnownSymbol::HasInstance);
+    let (_, has_instance) = JSObject::new_native_fn(
+        &key.function_name(),
+        1,
+        function_prototype_has_instance,
+        interpreter,
+    );
+    let prop = ObjectPropertyBuilder::new(has_instance).build();
+    define(
+        interpreter.intrinsics.function_proto_id,
+        key,
+        prop,
+        interpreter,
+    );
+
+    let value = JSValue::Object {
+        object_id: constructor_id,
+        kind: ObjectKind::Function,
+    };
+    (get_or_intern_string(SYMBOL_NAME), value)
+}
+
+fn define(
+    object_id: usize,
+    key: impl Into<PropertyKey>,
+    prop: ObjectProperty,
+    interpreter: &mut Interpreter,
+) {
+    if let Ok(object) = interpreter.get_object_mut(object_id) {
+        object.define_property(key, prop);
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-function.prototype-@@hasinstance
+fn function_prototype_has_instance(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    interpreter.ordinary_has_instance(this, &argument(args, 0))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-symbol-description
+fn symbol_call(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let description = match argument(args, 0) {
+        JSValue::Undefined => None,
+        description => Some(description.to_string(interpreter)?),
+    };
+    Ok(JSValue::new_symbol(description))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-symbol.for
+/// Returns the registry's symbol for the key, creating it on first use. Registered symbols are
+/// shared by every script run on this interpreter.
+fn symbol_for(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let key = argument(args, 0).to_string(interpreter)?;
+    let symbol = interpreter
+        .symbol_registry
+        .entry(key)
+        .or_insert_with(|| JSValue::new_symbol(Some(key)));
+    Ok(symbol.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-symbol.keyfor
+fn symbol_key_for(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let symbol = argument(args, 0);
+    if !symbol.is_symbol() {
+        let string = symbol.to_string(interpreter)?;
+        let string = get_string_from_pool(&string).unwrap_or_default();
+        return Err(JSError::new_type_error(&format!(
+            "{string} is not a symbol"
+        )));
+    }
+    let key = interpreter
+        .symbol_registry
+        .iter()
+        .find(|(_, registered)| **registered == symbol)
+        .map(|(key, _)| *key);
+    match key {
+        Some(key) => Ok(JSValue::new_string(&key)),
+        None => Ok(JSValue::Undefined),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-thissymbolvalue
+fn this_symbol_value(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<PropertyKey> {
+    match this {
+        JSValue::Symbol { id, description } => Ok(PropertyKey::Symbol {
+            id: *id,
+            description: *description,
+        }),
+        JSValue::Object { object_id, .. }
+            if let Some(ObjectData::Symbol(symbol)) =
+                interpreter.get_object(*object_id)?.get_data() =>
+        {
+            Ok(*symbol)
+        }
+        _ => Err(JSError::new_type_error(&format!(
+            "Symbol.prototype.{method} requires that 'this' be a Symbol"
+        ))),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-symbol.prototype.tostring
+fn symbol_to_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let symbol = this_symbol_value(interpreter, this, "toString")?;
+    Ok(JSValue::new_string(&get_or_intern_string(
+        &symbol.to_string(),
+    )))
+}
+
+// also `Symbol.prototype[@@toPrimitive]`, which ignores its hint
+fn symbol_value_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this_symbol_value(interpreter, this, "valueOf")?.to_value())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-symbol.prototype.description
+fn symbol_description(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    match this_symbol_value(interpreter, this, "description")? {
+        PropertyKey::Symbol {
+            description: Some(description),
+            ..
+        } => Ok(JSValue::new_string(&description)),
+        _ => Ok(JSValue::Undefined),
+    }
+}
@@ -0,0 +1,105 @@
+//! `setTimeout`, `setInterval`, `setImmediate` and their `clear*` counterparts.
+
+use crate::{
+    Interpreter,
+    builtins::argument,
+    errors::JSError,
+    values::{JSObject, JSResult, JSValue, NativeFunction, Property},
+};
+
+/// Delays above this overflow a 32-bit signed integer and, like in browsers and Node, run
+/// almost immediately instead.
+const MAX_DELAY: f64 = 2147483647.0;
+
+pub fn create_timer_functions(interpreter: &mut Interpreter) -> Vec<Property> {
+    let functions: Vec<(&str, usize, NativeFunction)> = vec![
+        ("setTimeout", 2, set_timeout),
+        ("setInterval", 2, set_interval),
+        ("setImmediate", 1, set_immediate),
+        ("clearTimeout", 1, clear_timer),
+        ("clearInterval", 1, clear_timer),
+        ("clearImmediate", 1, clear_timer),
+    ];
+    functions
+        .into_iter()
+        .map(|(name, length, native)| JSObject::new_native_fn(name, length, native, interpreter))
+        .collect()
+}
+
+fn require_callback(interpreter: &mut Interpreter, callback: &JSValue) -> JSResult<()> {
+    if interpreter.is_callable(callback) {
+        return Ok(());
+    }
+    Err(JSError::new_type_error(
+        "The \"callback\" argument must be of type function",
+    ))
+}
+
+/// Timers wait at least a millisecond, so a zero delay still lets pending immediates run first.
+fn to_delay(interpreter: &mut Interpreter, value: &JSValue) -> JSResult<f64> {
+    let delay = match value {
+        JSValue::Undefined => 0.0,
+        value => value.to_number(interpreter)?.get_number(),
+    };
+    if !(1.0..=MAX_DELAY).contains(&delay) {
+        return Ok(1.0);
+    }
+    Ok(delay.trunc())
+}
+
+fn schedule_timer(
+    interpreter: &mut Interpreter,
+    args: &[JSValue],
+    repeat: bool,
+) -> JSResult<JSValue> {
+    let callback = argument(args, 0);
+    require_callback(interpreter, &callback)?;
+    let delay = to_delay(interpreter, &argument(args, 1))?;
+    let arguments = args.iter().skip(2).cloned().collect();
+    let interval = repeat.then_some(delay);
+    let id = interpreter
+        .timers
+        .schedule(callback, arguments, delay, interval);
+    Ok(JSValue::new_number(&(id as f64)))
+}
+
+fn set_timeout(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    schedule_timer(interpreter, args, false)
+}
+
+fn set_interval(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    schedule_timer(interpreter, args, true)
+}
+
+/// Runs the callback as soon as the current script and its microtasks are done.
+fn set_immediate(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let callback = argument(args, 0);
+    require_callback(interpreter, &callback)?;
+    let arguments = args.iter().skip(1).cloned().collect();
+    let id = interpreter.timers.schedule(callback, arguments, 0.0, None);
+    Ok(JSValue::new_number(&(id as f64)))
+}
+
+/// Timers share one id space, so every `clear*` function accepts any id.
+fn clear_timer(
+    interpreter: &mut Interpreter,
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    if let JSValue::Number { data } = argument(args, 0) {
+        interpreter.timers.clear(data as u32);
+    }
+    Ok(JSValue::Undefined)
+}
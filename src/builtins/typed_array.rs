@@ -0,0 +1,2045 @@
+//! `%TypedArray%`, the eleven typed array constructors built on it and
+//! `%ArrayIteratorPrototype%`. A typed array keeps no elements of its own: its indices read and
+//! write the bytes of an ArrayBuffer, see the hooks in `get_property_value` and friends.
+
+use num_bigint::BigInt;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        array_buffer::{
+            allocate_bytes, array_buffer_state, array_buffer_state_mut, as_array_buffer,
+            new_array_buffer,
+        },
+        collection::{IterationKind, for_each_callback, prototype_from_new_target},
+        create_array, index_key,
+        iterator::{create_iter_result, iterate_to_list},
+        list_from_array_like,
+        object::{add_getter, add_method_alias, add_methods, add_to_string_tag},
+        relative_index, species_constructor, to_index,
+    },
+    constants::{LENGTH_NAME, TYPED_ARRAY_NAME},
+    debug_value,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    heap::{Trace, Tracer},
+    values::{
+        JSObject, JSResult, JSValue, NativeFunction, ObjectData, ObjectId, ObjectKind,
+        ObjectPropertyBuilder, Property, PropertyKey, WellKnownSymbol, get_property_value,
+        less_than, link_constructor_and_prototype, same_value_zero, string_to_number, to_string,
+    },
+};
+
+/// The element type of a typed array, which decides its constructor, its element size and
+/// how values are converted on the way in and out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypedArrayKind {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+    BigInt64,
+    BigUint64,
+}
+
+impl TypedArrayKind {
+    pub const ALL: [TypedArrayKind; 11] = [
+        TypedArrayKind::Int8,
+        TypedArrayKind::Uint8,
+        TypedArrayKind::Uint8Clamped,
+        TypedArrayKind::Int16,
+        TypedArrayKind::Uint16,
+        TypedArrayKind::Int32,
+        TypedArrayKind::Uint32,
+        TypedArrayKind::Float32,
+        TypedArrayKind::Float64,
+        TypedArrayKind::BigInt64,
+        TypedArrayKind::BigUint64,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TypedArrayKind::Int8 => "Int8Array",
+            TypedArrayKind::Uint8 => "Uint8Array",
+            TypedArrayKind::Uint8Clamped => "Uint8ClampedArray",
+            TypedArrayKind::Int16 => "Int16Array",
+            TypedArrayKind::Uint16 => "Uint16Array",
+            TypedArrayKind::Int32 => "Int32Array",
+            TypedArrayKind::Uint32 => "Uint32Array",
+            TypedArrayKind::Float32 => "Float32Array",
+            TypedArrayKind::Float64 => "Float64Array",
+            TypedArrayKind::BigInt64 => "BigInt64Array",
+            TypedArrayKind::BigUint64 => "BigUint64Array",
+        }
+    }
+
+    pub fn element_size(self) -> usize {
+        match self {
+            TypedArrayKind::Int8 | TypedArrayKind::Uint8 | TypedArrayKind::Uint8Clamped => 1,
+            TypedArrayKind::Int16 | TypedArrayKind::Uint16 => 2,
+            TypedArrayKind::Int32 | TypedArrayKind::Uint32 | TypedArrayKind::Float32 => 4,
+            TypedArrayKind::Float64 | TypedArrayKind::BigInt64 | TypedArrayKind::BigUint64 => 8,
+        }
+    }
+
+    /// Whether elements are BigInts rather than Numbers, the [[ContentType]] of the spec.
+    pub fn is_big_int(self) -> bool {
+        matches!(self, TypedArrayKind::BigInt64 | TypedArrayKind::BigUint64)
+    }
+
+    /// `value` as the kind of primitive elements hold: ToBigInt or ToNumber. Stores convert
+    /// first, so a `valueOf` that detaches the buffer still runs before the write is dropped.
+    pub fn to_element(self, interpreter: &mut Interpreter, value: &JSValue) -> JSResult<JSValue> {
+        match self.is_big_int() {
+            true => Ok(JSValue::new_big_int(value.to_big_int(interpreter)?)),
+            false => value.to_number(interpreter),
+        }
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-numerictorawbytes
+    /// The bytes that store `value`, wrapped around or clamped to the element type.
+    pub fn to_raw_bytes(
+        self,
+        interpreter: &mut Interpreter,
+        value: &JSValue,
+        little_endian: bool,
+    ) -> JSResult<Vec<u8>> {
+        let mut bytes = match self {
+            TypedArrayKind::Int8 => value.to_int_8(interpreter)?.to_le_bytes().to_vec(),
+            TypedArrayKind::Uint8 => value.to_uint_8(interpreter)?.to_le_bytes().to_vec(),
+            TypedArrayKind::Uint8Clamped => {
+                value.to_uint_8_clamped(interpreter)?.to_le_bytes().to_vec()
+            }
+            TypedArrayKind::Int16 => value.to_int_16(interpreter)?.to_le_bytes().to_vec(),
+            TypedArrayKind::Uint16 => value.to_uint_16(interpreter)?.to_le_bytes().to_vec(),
+            TypedArrayKind::Int32 => value.to_int_32(interpreter)?.to_le_bytes().to_vec(),
+            TypedArrayKind::Uint32 => value.to_uint_32(interpreter)?.to_le_bytes().to_vec(),
+            TypedArrayKind::Float32 => {
+                let number = value.to_number(interpreter)?.get_number();
+                (number as f32).to_le_bytes().to_vec()
+            }
+            TypedArrayKind::Float64 => {
+                let number = value.to_number(interpreter)?.get_number();
+                number.to_le_bytes().to_vec()
+            }
+            TypedArrayKind::BigInt64 => value.to_big_int_64(interpreter)?.to_le_bytes().to_vec(),
+            TypedArrayKind::BigUint64 => value.to_big_uint_64(interpreter)?.to_le_bytes().to_vec(),
+        };
+        if !little_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-rawbytestonumeric
+    /// The Number or BigInt stored in `bytes`, which hold exactly one element.
+    pub fn raw_bytes_to_numeric(self, bytes: &[u8], little_endian: bool) -> JSValue {
+        let mut raw = [0; 8];
+        raw[..bytes.len()].copy_from_slice(bytes);
+        if !little_endian {
+            raw[..bytes.len()].reverse();
+        }
+        let [b0, b1, b2, b3, ..] = raw;
+        let number = match self {
+            TypedArrayKind::Int8 => b0 as i8 as f64,
+            TypedArrayKind::Uint8 | TypedArrayKind::Uint8Clamped => b0 as f64,
+            TypedArrayKind::Int16 => i16::from_le_bytes([b0, b1]) as f64,
+            TypedArrayKind::Uint16 => u16::from_le_bytes([b0, b1]) as f64,
+            TypedArrayKind::Int32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            TypedArrayKind::Uint32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            TypedArrayKind::Float32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            TypedArrayKind::Float64 => f64::from_le_bytes(raw),
+            TypedArrayKind::BigInt64 => {
+                return JSValue::new_big_int(BigInt::from(i64::from_le_bytes(raw)));
+            }
+            TypedArrayKind::BigUint64 => {
+                return JSValue::new_big_int(BigInt::from(u64::from_le_bytes(raw)));
+            }
+        };
+        JSValue::new_number(&number)
+    }
+}
+
+/// [[TypedArrayName]], [[ViewedArrayBuffer]], [[ByteOffset]] and [[ArrayLength]].
+#[derive(Clone, Copy, Debug)]
+pub struct TypedArrayState {
+    pub kind: TypedArrayKind,
+    pub buffer: ObjectId,
+    pub byte_offset: usize,
+    /// `None` for arrays that track the length of a resizable buffer.
+    pub array_length: Option<usize>,
+}
+
+/// The state of an `entries`, `keys` or `values` iterator: the typed array or other array-like
+/// object, or `None` once it is exhausted, and the next index.
+#[derive(Clone, Debug)]
+pub struct ArrayIteratorState {
+    array: Option<ObjectId>,
+    index: usize,
+    kind: IterationKind,
+}
+
+impl Trace for TypedArrayState {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.mark(self.buffer);
+    }
+}
+
+impl Trace for ArrayIteratorState {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(array) = self.array {
+            tracer.mark(array);
+        }
+    }
+}
+
+/// Builds `%TypedArray%` and the eleven constructors inheriting from it, with their
+/// prototypes and `%ArrayIteratorPrototype%`. Only the eleven become globals.
+pub fn create_typed_array_constructors(interpreter: &mut Interpreter) -> Vec<Property> {
+    // https://262.ecma-international.org/15.0/index.html#sec-%arrayiteratorprototype%-object
+    let iterator_proto_id = interpreter.intrinsics.iterator_proto_id;
+    let array_iterator_id =
+        JSObject::new_ordinary_object(vec![], true, Some(iterator_proto_id), interpreter);
+    add_methods(
+        array_iterator_id,
+        vec![("next", 0, array_iterator_next)],
+        interpreter,
+    );
+    add_to_string_tag(array_iterator_id, "Array Iterator", interpreter);
+    interpreter.intrinsics.array_iterator_proto_id = array_iterator_id;
+
+    // https://262.ecma-international.org/15.0/index.html#sec-%typedarray%-intrinsic-object
+    let constructor_id = JSObject::new_native_function(
+        TYPED_ARRAY_NAME,
+        0,
+        typed_array_abstract,
+        Some(typed_array_abstract_construct),
+        interpreter,
+    );
+    add_methods(
+        constructor_id,
+        vec![("from", 1, typed_array_from), ("of", 0, typed_array_of)],
+        interpreter,
+    );
+    let species = PropertyKey::well_known(WellKnownSymbol::Species);
+    add_getter(constructor_id, species, typed_array_species, interpreter);
+
+    let object_proto_id = interpreter.get_object_proto_id();
+    let prototype_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        prototype_id,
+        vec![
+            ("at", 1, typed_array_at),
+            ("copyWithin", 2, typed_array_copy_within),
+            ("entries", 0, typed_array_entries),
+            ("every", 1, typed_array_every),
+            ("fill", 1, typed_array_fill),
+            ("filter", 1, typed_array_filter),
+            ("find", 1, typed_array_find),
+            ("findIndex", 1, typed_array_find_index),
+            ("findLast", 1, typed_array_find_last),
+            ("findLastIndex", 1, typed_array_find_last_index),
+            ("forEach", 1, typed_array_for_each),
+            ("includes", 1, typed_array_includes),
+            ("indexOf", 1, typed_array_index_of),
+            ("join", 1, typed_array_join),
+            ("keys", 0, typed_array_keys),
+            ("lastIndexOf", 1, typed_array_last_index_of),
+            ("map", 1, typed_array_map),
+            ("reduce", 1, typed_array_reduce),
+            ("reduceRight", 1, typed_array_reduce_right),
+            ("reverse", 0, typed_array_reverse),
+            ("set", 1, typed_array_set),
+            ("slice", 2, typed_array_slice),
+            ("some", 1, typed_array_some),
+            ("sort", 1, typed_array_sort),
+            ("subarray", 2, typed_array_subarray),
+            ("toLocaleString", 0, typed_array_to_locale_string),
+            ("toReversed", 0, typed_array_to_reversed),
+            ("toSorted", 1, typed_array_to_sorted),
+            ("toString", 0, typed_array_join),
+            ("values", 0, typed_array_values),
+            ("with", 2, typed_array_with),
+        ],
+        interpreter,
+    );
+    let getters: Vec<(&str, NativeFunction)> = vec![
+        ("buffer", typed_array_buffer),
+        ("byteLength", typed_array_byte_length),
+        ("byteOffset", typed_array_byte_offset),
+        ("length", typed_array_length_getter),
+    ];
+    for (name, getter) in getters {
+        add_getter(
+            prototype_id,
+            get_or_intern_string(name),
+            getter,
+            interpreter,
+        );
+    }
+    let to_string_tag = PropertyKey::well_known(WellKnownSymbol::ToStringTag);
+    add_getter(
+        prototype_id,
+        to_string_tag,
+        typed_array_to_string_tag,
+        interpreter,
+    );
+    let iterator = PropertyKey::well_known(WellKnownSymbol::Iterator);
+    add_method_alias(prototype_id, "values", iterator, interpreter);
+    link_constructor_and_prototype(constructor_id, prototype_id, interpreter);
+    interpreter.intrinsics.typed_array_proto_id = prototype_id;
+
+    let constructors: [(TypedArrayKind, NativeFunction, NativeFunction); 11] = [
+        (TypedArrayKind::Int8, int8_array_call, int8_array_construct),
+        (
+            TypedArrayKind::Uint8,
+            uint8_array_call,
+            uint8_array_construct,
+        ),
+        (
+            TypedArrayKind::Uint8Clamped,
+            uint8_clamped_array_call,
+            uint8_clamped_array_construct,
+        ),
+        (
+            TypedArrayKind::Int16,
+            int16_array_call,
+            int16_array_construct,
+        ),
+        (
+            TypedArrayKind::Uint16,
+            uint16_array_call,
+            uint16_array_construct,
+        ),
+        (
+            TypedArrayKind::Int32,
+            int32_array_call,
+            int32_array_construct,
+        ),
+        (
+            TypedArrayKind::Uint32,
+            uint32_array_call,
+            uint32_array_construct,
+        ),
+        (
+            TypedArrayKind::Float32,
+            float32_array_call,
+            float32_array_construct,
+        ),
+        (
+            TypedArrayKind::Float64,
+            float64_array_call,
+            float64_array_construct,
+        ),
+        (
+            TypedArrayKind::BigInt64,
+            big_int64_array_call,
+            big_int64_array_construct,
+        ),
+        (
+            TypedArrayKind::BigUint64,
+            big_uint64_array_call,
+            big_uint64_array_construct,
+        ),
+    ];
+    let mut properties = vec![];
+    for (kind, call, construct) in constructors {
+        let concrete_id =
+            JSObject::new_native_function(kind.name(), 3, call, Some(construct), interpreter);
+        if let Ok(object) = interpreter.get_object_mut(concrete_id) {
+            object.set_prototype(Some(constructor_id));
+        }
+        let concrete_proto_id =
+            JSObject::new_ordinary_object(vec![], true, Some(prototype_id), interpreter);
+        // https://262.ecma-international.org/15.0/index.html#sec-typedarray.bytes_per_element
+        let bytes_per_element = JSValue::new_number(&(kind.element_size() as f64));
+        for object_id in [concrete_id, concrete_proto_id] {
+            let prop = ObjectPropertyBuilder::new(bytes_per_element.clone()).build();
+            if let Ok(object) = interpreter.get_object_mut(object_id) {
+                object.define_property(get_or_intern_string("BYTES_PER_ELEMENT"), prop);
+            }
+        }
+        link_constructor_and_prototype(concrete_id, concrete_proto_id, interpreter);
+        interpreter.intrinsics.typed_array_constructor_ids[kind as usize] = concrete_id;
+        interpreter.intrinsics.typed_array_proto_ids[kind as usize] = concrete_proto_id;
+
+        let value = JSValue::Object {
+            object_id: concrete_id,
+            kind: ObjectKind::Function,
+        };
+        properties.push((get_or_intern_string(kind.name()), value));
+    }
+    properties
+}
+
+fn create_typed_array_object(
+    interpreter: &mut Interpreter,
+    state: TypedArrayState,
+    prototype: ObjectId,
+) -> JSValue {
+    let object_id = JSObject::new_object_with_data(
+        ObjectData::TypedArray(Box::new(state)),
+        Some(prototype),
+        interpreter,
+    );
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-allocatetypedarray
+/// A typed array of `length` zeroed elements with a fresh buffer of its own.
+fn allocate_typed_array(
+    interpreter: &mut Interpreter,
+    kind: TypedArrayKind,
+    prototype: ObjectId,
+    length: usize,
+) -> JSResult<(JSValue, TypedArrayState)> {
+    let byte_length = length.checked_mul(kind.element_size()).ok_or_else(|| {
+        JSError::new_range_error(&format!("Invalid typed array length: {length}"))
+    })?;
+    let bytes = allocate_bytes(byte_length)?;
+    let state = TypedArrayState {
+        kind,
+        buffer: new_array_buffer(interpreter, bytes),
+        byte_offset: 0,
+        array_length: Some(length),
+    };
+    Ok((
+        create_typed_array_object(interpreter, state, prototype),
+        state,
+    ))
+}
+
+/// The state of the typed array `object_id`, `None` for any other object.
+pub fn typed_array_state(
+    interpreter: &Interpreter,
+    object_id: ObjectId,
+) -> Option<TypedArrayState> {
+    match interpreter.get_object(object_id).ok()?.get_data() {
+        Some(ObjectData::TypedArray(state)) => Some(**state),
+        _ => None,
+    }
+}
+
+pub fn as_typed_array(interpreter: &Interpreter, value: &JSValue) -> Option<TypedArrayState> {
+    match value {
+        JSValue::Object { object_id, .. } => typed_array_state(interpreter, *object_id),
+        _ => None,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-istypedarrayoutofbounds
+/// The current length of a typed array, `None` once its buffer is detached or has shrunk
+/// past the elements it views.
+pub fn typed_array_length(interpreter: &Interpreter, state: &TypedArrayState) -> Option<usize> {
+    let buffer = array_buffer_state(interpreter, state.buffer)?;
+    if buffer.is_detached() {
+        return None;
+    }
+    let byte_length = buffer.byte_length();
+    let size = state.kind.element_size();
+    match state.array_length {
+        Some(length) => (state.byte_offset + length * size <= byte_length).then_some(length),
+        None => {
+            (state.byte_offset <= byte_length).then(|| (byte_length - state.byte_offset) / size)
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-validatetypedarray
+/// The typed array `value` is and its length, or the TypeError of `method` when it isn't one
+/// or is out of bounds.
+pub fn validate_typed_array(
+    interpreter: &Interpreter,
+    value: &JSValue,
+    method: &str,
+) -> JSResult<(TypedArrayState, usize)> {
+    let Some(state) = as_typed_array(interpreter, value) else {
+        return Err(JSError::new_type_error("this is not a typed array."));
+    };
+    match typed_array_length(interpreter, &state) {
+        Some(length) => Ok((state, length)),
+        None => Err(JSError::new_type_error(&format!(
+            "Cannot perform {method} on a detached ArrayBuffer"
+        ))),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getvaluefrombuffer
+/// The element of `kind` stored at `byte_index` of `buffer`. The caller has checked the
+/// index is in bounds, anything else reads as `undefined`.
+pub fn get_value_from_buffer(
+    interpreter: &Interpreter,
+    buffer: ObjectId,
+    byte_index: usize,
+    kind: TypedArrayKind,
+    little_endian: bool,
+) -> JSValue {
+    let bytes = array_buffer_state(interpreter, buffer).and_then(|state| {
+        state
+            .bytes()
+            .get(byte_index..byte_index + kind.element_size())
+    });
+    match bytes {
+        Some(bytes) => kind.raw_bytes_to_numeric(bytes, little_endian),
+        None => JSValue::Undefined,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-setvalueinbuffer
+/// Writes `bytes` at `byte_index` of `buffer`, dropping writes that no longer fit.
+pub fn set_value_in_buffer(
+    interpreter: &mut Interpreter,
+    buffer: ObjectId,
+    byte_index: usize,
+    bytes: &[u8],
+) {
+    let target = array_buffer_state_mut(interpreter, buffer).and_then(|state| {
+        state
+            .bytes_mut()
+            .get_mut(byte_index..byte_index + bytes.len())
+    });
+    if let Some(target) = target {
+        target.copy_from_slice(bytes);
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-canonicalnumericindexstring
+/// The number a string key stands for when it is the canonical way to write it, like `"1"`
+/// or `"-0"` but not `"01"`. Such keys never reach a typed array's ordinary properties.
+fn canonical_numeric_index(key: &PropertyKey) -> Option<f64> {
+    let name = get_string_from_pool(&key.as_string()?)?;
+    if name == "-0" {
+        return Some(-0.0);
+    }
+    let number = string_to_number(&name);
+    (to_string(number, 10) == name).then_some(number)
+}
+
+/// The typed array `object_id` is and the numeric index `key` stands for, when both are the
+/// case and the property access is the typed array's business rather than the ordinary one.
+pub fn typed_array_index(
+    interpreter: &Interpreter,
+    object_id: ObjectId,
+    key: &PropertyKey,
+) -> Option<(TypedArrayState, f64)> {
+    let state = typed_array_state(interpreter, object_id)?;
+    Some((state, canonical_numeric_index(key)?))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-isvalidintegerindex
+fn valid_index(interpreter: &Interpreter, state: &TypedArrayState, index: f64) -> Option<usize> {
+    if index.fract() != 0.0 || (index == 0.0 && index.is_sign_negative()) || index < 0.0 {
+        return None;
+    }
+    let length = typed_array_length(interpreter, state)?;
+    (index < length as f64).then_some(index as usize)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-typedarraygetelement
+pub fn typed_array_get_element(
+    interpreter: &Interpreter,
+    state: &TypedArrayState,
+    index: f64,
+) -> JSValue {
+    match valid_index(interpreter, state, index) {
+        Some(index) => {
+            let byte_index = state.byte_offset + index * state.kind.element_size();
+            get_value_from_buffer(interpreter, state.buffer, byte_index, state.kind, true)
+        }
+        None => JSValue::Undefined,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-typedarraysetelement
+/// Converts `value` and stores it, unless the index turns out invalid after the conversion.
+pub fn typed_array_set_element(
+    interpreter: &mut Interpreter,
+    state: &TypedArrayState,
+    index: f64,
+    value: &JSValue,
+) -> JSResult<()> {
+    let value = state.kind.to_element(interpreter, value)?;
+    if let Some(index) = valid_index(interpreter, state, index) {
+        let bytes = state.kind.to_raw_bytes(interpreter, &value, true)?;
+        let byte_index = state.byte_offset + index * state.kind.element_size();
+        set_value_in_buffer(interpreter, state.buffer, byte_index, &bytes);
+    }
+    Ok(())
+}
+
+/// Whether `index` is an element of the typed array right now; the elements are its only
+/// own properties with numeric keys.
+pub fn is_valid_integer_index(
+    interpreter: &Interpreter,
+    state: &TypedArrayState,
+    index: f64,
+) -> bool {
+    valid_index(interpreter, state, index).is_some()
+}
+
+/// The keys of the elements of the typed array `object_id`, which come before its other
+/// own keys. Empty for anything else.
+pub fn typed_array_own_keys(interpreter: &Interpreter, object_id: ObjectId) -> Vec<PropertyKey> {
+    let length = typed_array_state(interpreter, object_id)
+        .and_then(|state| typed_array_length(interpreter, &state))
+        .unwrap_or(0);
+    (0..length)
+        .map(|index| PropertyKey::from(index_key(index)))
+        .collect()
+}
+
+fn get_index(interpreter: &Interpreter, state: &TypedArrayState, index: usize) -> JSValue {
+    typed_array_get_element(interpreter, state, index as f64)
+}
+
+fn intrinsic_constructor(interpreter: &Interpreter, kind: TypedArrayKind) -> JSValue {
+    JSValue::Object {
+        object_id: interpreter.intrinsics.typed_array_constructor_ids[kind as usize],
+        kind: ObjectKind::Function,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-typedarraycreatefromconstructor
+fn create_from_constructor(
+    interpreter: &mut Interpreter,
+    constructor: &JSValue,
+    args: &[JSValue],
+) -> JSResult<(JSValue, TypedArrayState)> {
+    let array = interpreter.construct(constructor, args)?;
+    let (state, length) = validate_typed_array(interpreter, &array, "Construct")?;
+    if let [JSValue::Number { data }] = args
+        && (length as f64) < *data
+    {
+        return Err(JSError::new_type_error(
+            "Derived TypedArray constructor created an array which was too small",
+        ));
+    }
+    Ok((array, state))
+}
+
+// https://262.ecma-international.org/15.0/index.html#typedarray-species-create
+/// A typed array made by the species constructor of `exemplar`, which has to hold the same
+/// type of content, Numbers or BigInts.
+fn species_create(
+    interpreter: &mut Interpreter,
+    exemplar: &JSValue,
+    kind: TypedArrayKind,
+    args: &[JSValue],
+) -> JSResult<(JSValue, TypedArrayState)> {
+    let default = intrinsic_constructor(interpreter, kind);
+    let constructor = species_constructor(interpreter, exemplar, default)?;
+    let (array, state) = create_from_constructor(interpreter, &constructor, args)?;
+    if state.kind.is_big_int() != kind.is_big_int() {
+        return Err(JSError::new_type_error(
+            "Content type of the species constructor's array doesn't match",
+        ));
+    }
+    Ok((array, state))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-typedarray-create-same-type
+fn create_same_type(
+    interpreter: &mut Interpreter,
+    kind: TypedArrayKind,
+    length: usize,
+) -> JSResult<(JSValue, TypedArrayState)> {
+    let prototype = interpreter.intrinsics.typed_array_proto_ids[kind as usize];
+    allocate_typed_array(interpreter, kind, prototype, length)
+}
+
+fn length_value(length: usize) -> JSValue {
+    JSValue::new_number(&(length as f64))
+}
+
+fn typed_array_abstract(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error(
+        "Abstract class TypedArray not directly constructable",
+    ))
+}
+
+fn typed_array_abstract_construct(
+    _interpreter: &mut Interpreter,
+    _new_target: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error(
+        "Abstract class TypedArray not directly constructable",
+    ))
+}
+
+fn int8_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Int8))
+}
+
+fn uint8_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Uint8))
+}
+
+fn uint8_clamped_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Uint8Clamped))
+}
+
+fn int16_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Int16))
+}
+
+fn uint16_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Uint16))
+}
+
+fn int32_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Int32))
+}
+
+fn uint32_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Uint32))
+}
+
+fn float32_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Float32))
+}
+
+fn float64_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::Float64))
+}
+
+fn big_int64_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::BigInt64))
+}
+
+fn big_uint64_array_call(_: &mut Interpreter, _: &JSValue, _: &[JSValue]) -> JSResult<JSValue> {
+    Err(requires_new(TypedArrayKind::BigUint64))
+}
+
+fn requires_new(kind: TypedArrayKind) -> JSError {
+    JSError::new_type_error(&format!("Constructor {} requires 'new'", kind.name()))
+}
+
+fn int8_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Int8)
+}
+
+fn uint8_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Uint8)
+}
+
+fn uint8_clamped_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Uint8Clamped)
+}
+
+fn int16_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Int16)
+}
+
+fn uint16_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Uint16)
+}
+
+fn int32_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Int32)
+}
+
+fn uint32_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Uint32)
+}
+
+fn float32_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Float32)
+}
+
+fn float64_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::Float64)
+}
+
+fn big_int64_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::BigInt64)
+}
+
+fn big_uint64_array_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    construct_typed_array(interpreter, new_target, args, TypedArrayKind::BigUint64)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-typedarray
+/// `new Int8Array(...)` and the others: from a length, another typed array, a window on an
+/// ArrayBuffer, an iterable or an array-like.
+fn construct_typed_array(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+    kind: TypedArrayKind,
+) -> JSResult<JSValue> {
+    let fallback = interpreter.intrinsics.typed_array_proto_ids[kind as usize];
+    let first = argument(args, 0);
+    if !first.is_object() {
+        let message = format!(
+            "Invalid typed array length: {}",
+            debug_value(interpreter, &first)
+        );
+        let length = to_index(interpreter, &first, &message)? as usize;
+        let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+        return Ok(allocate_typed_array(interpreter, kind, prototype, length)?.0);
+    }
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    if let Some(source) = as_typed_array(interpreter, &first) {
+        return initialize_from_typed_array(interpreter, kind, prototype, &source);
+    }
+    if let Some(buffer) = as_array_buffer(interpreter, &first) {
+        return initialize_from_array_buffer(interpreter, kind, prototype, buffer, args);
+    }
+    let iterator = PropertyKey::well_known(WellKnownSymbol::Iterator);
+    let values = match get_property_value(interpreter, &first, iterator)? {
+        JSValue::Undefined | JSValue::Null => list_from_array_like(interpreter, &first)?,
+        _ => iterate_to_list(interpreter, &first)?,
+    };
+    let (array, state) = allocate_typed_array(interpreter, kind, prototype, values.len())?;
+    for (index, value) in values.iter().enumerate() {
+        typed_array_set_element(interpreter, &state, index as f64, value)?;
+    }
+    Ok(array)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-initializetypedarrayfromtypedarray
+/// A copy of `source` with a buffer of its own, converting each element unless the kinds
+/// match.
+fn initialize_from_typed_array(
+    interpreter: &mut Interpreter,
+    kind: TypedArrayKind,
+    prototype: ObjectId,
+    source: &TypedArrayState,
+) -> JSResult<JSValue> {
+    let Some(length) = typed_array_length(interpreter, source) else {
+        return Err(JSError::new_type_error(
+            "Cannot perform Construct on a detached ArrayBuffer",
+        ));
+    };
+    if source.kind.is_big_int() != kind.is_big_int() {
+        return Err(JSError::new_type_error(
+            "Cannot mix BigInt and other types, use explicit conversions",
+        ));
+    }
+    let (array, state) = allocate_typed_array(interpreter, kind, prototype, length)?;
+    if source.kind == kind {
+        let start = source.byte_offset;
+        let end = start + length * kind.element_size();
+        let bytes = array_buffer_state(interpreter, source.buffer)
+            .map(|buffer| buffer.bytes()[start..end].to_vec())
+            .unwrap_or_default();
+        set_value_in_buffer(interpreter, state.buffer, 0, &bytes);
+        return Ok(array);
+    }
+    for index in 0..length {
+        let value = get_index(interpreter, source, index);
+        typed_array_set_element(interpreter, &state, index as f64, &value)?;
+    }
+    Ok(array)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-initializetypedarrayfromarraybuffer
+/// A view on `buffer` from `args[1]` for `args[2]` elements. Without a length the view runs
+/// to the end of the buffer, following it as it grows when the buffer is resizable.
+fn initialize_from_array_buffer(
+    interpreter: &mut Interpreter,
+    kind: TypedArrayKind,
+    prototype: ObjectId,
+    buffer: ObjectId,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let size = kind.element_size();
+    let name = kind.name();
+    let message = "Start offset is outside the bounds of the buffer";
+    let byte_offset = to_index(interpreter, &argument(args, 1), message)? as usize;
+    if !byte_offset.is_multiple_of(size) {
+        return Err(JSError::new_range_error(&format!(
+            "start offset of {name} should be a multiple of {size}"
+        )));
+    }
+    let length = match argument(args, 2) {
+        JSValue::Undefined => None,
+        length => {
+            let message = format!(
+                "Invalid typed array length: {}",
+                debug_value(interpreter, &length)
+            );
+            Some(to_index(interpreter, &length, &message)? as usize)
+        }
+    };
+    let Some(buffer_state) = array_buffer_state(interpreter, buffer) else {
+        return Err(JSError::new_type_error("object is not an ArrayBuffer"));
+    };
+    if buffer_state.is_detached() {
+        return Err(JSError::new_type_error(
+            "Cannot perform Construct on a detached ArrayBuffer",
+        ));
+    }
+    let buffer_byte_length = buffer_state.byte_length();
+    let out_of_bounds = || {
+        JSError::new_range_error(&format!(
+            "Start offset {byte_offset} is outside the bounds of the buffer"
+        ))
+    };
+    let array_length = match length {
+        None if buffer_state.is_resizable() => {
+            if byte_offset > buffer_byte_length {
+                return Err(out_of_bounds());
+            }
+            None
+        }
+        None => {
+            if !buffer_byte_length.is_multiple_of(size) {
+                return Err(JSError::new_range_error(&format!(
+                    "byte length of {name} should be a multiple of {size}"
+                )));
+            }
+            if byte_offset > buffer_byte_length {
+                return Err(out_of_bounds());
+            }
+            Some((buffer_byte_length - byte_offset) / size)
+        }
+        Some(length) => {
+            let fits = length
+                .checked_mul(size)
+                .and_then(|bytes| bytes.checked_add(byte_offset))
+                .is_some_and(|end| end <= buffer_byte_length);
+            if !fits {
+                return Err(JSError::new_range_error(&format!(
+                    "Invalid typed array length: {length}"
+                )));
+            }
+            Some(length)
+        }
+    };
+    let state = TypedArrayState {
+        kind,
+        buffer,
+        byte_offset,
+        array_length,
+    };
+    Ok(create_typed_array_object(interpreter, state, prototype))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-%typedarray%-@@species
+fn typed_array_species(
+    _interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Ok(this.clone())
+}
+
+fn require_constructor(interpreter: &mut Interpreter, this: &JSValue) -> JSResult<()> {
+    let is_constructor = this
+        .get_object(interpreter)
+        .is_ok_and(|object| object.is_constructor());
+    if !is_constructor {
+        let this = debug_value(interpreter, this);
+        return Err(JSError::new_type_error(&format!(
+            "{this} is not a constructor"
+        )));
+    }
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.from
+fn typed_array_from(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_constructor(interpreter, this)?;
+    let source = argument(args, 0);
+    let map_fn = argument(args, 1);
+    let this_arg = argument(args, 2);
+    if !map_fn.is_undefined() && !interpreter.is_callable(&map_fn) {
+        let map_fn = debug_value(interpreter, &map_fn);
+        return Err(JSError::new_type_error(&format!(
+            "{map_fn} is not a function"
+        )));
+    }
+    if source.is_null() || source.is_undefined() {
+        return Err(JSError::new_type_error(
+            "Cannot convert undefined or null to object",
+        ));
+    }
+    let iterator = PropertyKey::well_known(WellKnownSymbol::Iterator);
+    let values = match source {
+        JSValue::String { .. } => iterate_to_list(interpreter, &source)?,
+        JSValue::Object { .. } => match get_property_value(interpreter, &source, iterator)? {
+            JSValue::Undefined | JSValue::Null => list_from_array_like(interpreter, &source)?,
+            _ => iterate_to_list(interpreter, &source)?,
+        },
+        // other primitives have no elements
+        _ => vec![],
+    };
+    let (array, state) = create_from_constructor(interpreter, this, &[length_value(values.len())])?;
+    for (index, value) in values.into_iter().enumerate() {
+        let value = match map_fn.is_undefined() {
+            true => value,
+            false => {
+                let index = JSValue::new_number(&(index as f64));
+                interpreter.call_function(&map_fn, &this_arg, &[value, index])?
+            }
+        };
+        typed_array_set_element(interpreter, &state, index as f64, &value)?;
+    }
+    Ok(array)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.of
+fn typed_array_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    require_constructor(interpreter, this)?;
+    let (array, state) = create_from_constructor(interpreter, this, &[length_value(args.len())])?;
+    for (index, value) in args.iter().enumerate() {
+        typed_array_set_element(interpreter, &state, index as f64, value)?;
+    }
+    Ok(array)
+}
+
+/// The state of `this` for the accessors, which don't mind out of bounds arrays.
+fn this_typed_array(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    getter: &str,
+) -> JSResult<TypedArrayState> {
+    match as_typed_array(interpreter, this) {
+        Some(state) => Ok(state),
+        None => {
+            let receiver = debug_value(interpreter, this);
+            Err(JSError::new_type_error(&format!(
+                "Method get TypedArray.prototype.{getter} called on incompatible receiver {receiver}"
+            )))
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-%typedarray%.prototype.buffer
+fn typed_array_buffer(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_typed_array(interpreter, this, "buffer")?;
+    Ok(JSValue::Object {
+        object_id: state.buffer,
+        kind: ObjectKind::Object,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-%typedarray%.prototype.bytelength
+fn typed_array_byte_length(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_typed_array(interpreter, this, "byteLength")?;
+    let length = typed_array_length(interpreter, &state).unwrap_or(0);
+    Ok(length_value(length * state.kind.element_size()))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-%typedarray%.prototype.byteoffset
+fn typed_array_byte_offset(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_typed_array(interpreter, this, "byteOffset")?;
+    let byte_offset = match typed_array_length(interpreter, &state) {
+        Some(_) => state.byte_offset,
+        None => 0,
+    };
+    Ok(length_value(byte_offset))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-%typedarray%.prototype.length
+fn typed_array_length_getter(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let state = this_typed_array(interpreter, this, "length")?;
+    Ok(length_value(
+        typed_array_length(interpreter, &state).unwrap_or(0),
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-get-%typedarray%.prototype-@@tostringtag
+fn typed_array_to_string_tag(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    match as_typed_array(interpreter, this) {
+        Some(state) => Ok(JSValue::new_string(&get_or_intern_string(
+            state.kind.name(),
+        ))),
+        None => Ok(JSValue::Undefined),
+    }
+}
+
+fn validate_this(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<(TypedArrayState, usize)> {
+    validate_typed_array(
+        interpreter,
+        this,
+        &format!("%TypedArray%.prototype.{method}"),
+    )
+}
+
+/// The integer a relative index argument stands for, or `default` when it is `undefined`.
+fn relative_argument(
+    interpreter: &mut Interpreter,
+    args: &[JSValue],
+    index: usize,
+    length: usize,
+    default: usize,
+) -> JSResult<usize> {
+    match argument(args, index) {
+        JSValue::Undefined => Ok(default),
+        value => {
+            let relative = value.to_integer_or_infinity(interpreter)?.get_number();
+            Ok(relative_index(relative, length))
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.at
+fn typed_array_at(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "at")?;
+    let relative = argument(args, 0)
+        .to_integer_or_infinity(interpreter)?
+        .get_number();
+    let index = match relative >= 0.0 {
+        true => relative,
+        false => length as f64 + relative,
+    };
+    if index < 0.0 || index >= length as f64 {
+        return Ok(JSValue::Undefined);
+    }
+    Ok(typed_array_get_element(interpreter, &state, index))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.copywithin
+fn typed_array_copy_within(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "copyWithin")?;
+    let to = relative_argument(interpreter, args, 0, length, 0)?;
+    let from = relative_argument(interpreter, args, 1, length, 0)?;
+    let end = relative_argument(interpreter, args, 2, length, length)?;
+    let count = end.saturating_sub(from).min(length - to);
+    if count == 0 {
+        return Ok(this.clone());
+    }
+    // the arguments may have shrunk the buffer
+    let (_, length) = validate_this(interpreter, this, "copyWithin")?;
+    let count = count
+        .min(length.saturating_sub(from))
+        .min(length.saturating_sub(to));
+    let size = state.kind.element_size();
+    if let Some(buffer) = array_buffer_state_mut(interpreter, state.buffer) {
+        let from = state.byte_offset + from * size;
+        let to = state.byte_offset + to * size;
+        buffer
+            .bytes_mut()
+            .copy_within(from..from + count * size, to);
+    }
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-createarrayiterator
+fn create_array_iterator(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    kind: IterationKind,
+    method: &str,
+) -> JSResult<JSValue> {
+    validate_this(interpreter, this, method)?;
+    Ok(new_array_iterator(interpreter, this.get_object_id()?, kind))
+}
+
+/// An iterator over `array`, which needn't be a typed array: anything with a `length` will do.
+pub fn new_array_iterator(
+    interpreter: &mut Interpreter,
+    array: ObjectId,
+    kind: IterationKind,
+) -> JSValue {
+    let state = ArrayIteratorState {
+        array: Some(array),
+        index: 0,
+        kind,
+    };
+    let prototype = interpreter.intrinsics.array_iterator_proto_id;
+    let object_id = JSObject::new_object_with_data(
+        ObjectData::ArrayIterator(Box::new(state)),
+        Some(prototype),
+        interpreter,
+    );
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%arrayiteratorprototype%.next
+fn array_iterator_next(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let iterator_id = this.get_object_id().ok();
+    let state = iterator_id.and_then(|id| match interpreter.get_object(id).ok()?.get_data() {
+        Some(ObjectData::ArrayIterator(state)) => Some((**state).clone()),
+        _ => None,
+    });
+    let (Some(iterator_id), Some(state)) = (iterator_id, state) else {
+        let receiver = debug_value(interpreter, this);
+        return Err(JSError::new_type_error(&format!(
+            "Method Array Iterator.prototype.next called on incompatible receiver {receiver}"
+        )));
+    };
+    let Some(array_id) = state.array else {
+        return Ok(create_iter_result(interpreter, JSValue::Undefined, true));
+    };
+    let array = JSValue::Object {
+        object_id: array_id,
+        kind: ObjectKind::Object,
+    };
+    let (array_state, length) = match interpreter.get_object(array_id)?.get_data() {
+        Some(ObjectData::TypedArray(_)) => {
+            let (array_state, length) =
+                validate_typed_array(interpreter, &array, "%ArrayIteratorPrototype%.next")?;
+            (Some(array_state), length)
+        }
+        _ => {
+            let length = get_property_value(interpreter, &array, get_or_intern_string(LENGTH_NAME))?
+                .to_length(interpreter)?
+                .get_number() as usize;
+            (None, length)
+        }
+    };
+    let index = state.index;
+    if let Some(ObjectData::ArrayIterator(state)) =
+        interpreter.get_object_mut(iterator_id)?.get_data_mut()
+    {
+        match index < length {
+            true => state.index += 1,
+            false => state.array = None,
+        }
+    }
+    if index >= length {
+        return Ok(create_iter_result(interpreter, JSValue::Undefined, true));
+    }
+    let key = length_value(index);
+    let value = match (&state.kind, &array_state) {
+        (IterationKind::Keys, _) => JSValue::Undefined,
+        (_, Some(array_state)) => get_index(interpreter, array_state, index),
+        (_, None) => get_property_value(interpreter, &array, index_key(index))?,
+    };
+    let result = match state.kind {
+        IterationKind::Keys => key,
+        IterationKind::Values => value,
+        IterationKind::Entries => create_array(vec![key, value], interpreter),
+    };
+    Ok(create_iter_result(interpreter, result, false))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.entries
+fn typed_array_entries(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    create_array_iterator(interpreter, this, IterationKind::Entries, "entries")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.keys
+fn typed_array_keys(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    create_array_iterator(interpreter, this, IterationKind::Keys, "keys")
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.values
+fn typed_array_values(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    create_array_iterator(interpreter, this, IterationKind::Values, "values")
+}
+
+/// Calls `callback(element, index, array)` for each index in `indices`, stopping at the
+/// first call whose result `stop` accepts. Returns that index and element.
+fn find_element(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+    method: &str,
+    reverse: bool,
+    stop: fn(&JSValue) -> bool,
+) -> JSResult<Option<(usize, JSValue)>> {
+    let (state, length) = validate_this(interpreter, this, method)?;
+    let (callback, this_arg) = for_each_callback(interpreter, args)?;
+    let indices: Box<dyn Iterator<Item = usize>> = match reverse {
+        true => Box::new((0..length).rev()),
+        false => Box::new(0..length),
+    };
+    for index in indices {
+        let value = get_index(interpreter, &state, index);
+        let call_args = [value.clone(), length_value(index), this.clone()];
+        let result = interpreter.call_function(&callback, &this_arg, &call_args)?;
+        if stop(&result) {
+            return Ok(Some((index, value)));
+        }
+    }
+    Ok(None)
+}
+
+fn is_truthy(value: &JSValue) -> bool {
+    value.to_boolean()
+}
+
+fn is_falsy(value: &JSValue) -> bool {
+    !value.to_boolean()
+}
+
+fn never(_value: &JSValue) -> bool {
+    false
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.every
+fn typed_array_every(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let found = find_element(interpreter, this, args, "every", false, is_falsy)?;
+    Ok(JSValue::new_boolean(found.is_none()))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.some
+fn typed_array_some(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let found = find_element(interpreter, this, args, "some", false, is_truthy)?;
+    Ok(JSValue::new_boolean(found.is_some()))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.find
+fn typed_array_find(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let found = find_element(interpreter, this, args, "find", false, is_truthy)?;
+    Ok(found.map(|(_, value)| value).unwrap_or_default())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.findindex
+fn typed_array_find_index(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let found = find_element(interpreter, this, args, "findIndex", false, is_truthy)?;
+    Ok(JSValue::new_number(
+        &found.map_or(-1.0, |(index, _)| index as f64),
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.findlast
+fn typed_array_find_last(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let found = find_element(interpreter, this, args, "findLast", true, is_truthy)?;
+    Ok(found.map(|(_, value)| value).unwrap_or_default())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.findlastindex
+fn typed_array_find_last_index(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let found = find_element(interpreter, this, args, "findLastIndex", true, is_truthy)?;
+    Ok(JSValue::new_number(
+        &found.map_or(-1.0, |(index, _)| index as f64),
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.foreach
+fn typed_array_for_each(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    find_element(interpreter, this, args, "forEach", false, never)?;
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.fill
+fn typed_array_fill(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "fill")?;
+    let value = state.kind.to_element(interpreter, &argument(args, 0))?;
+    let start = relative_argument(interpreter, args, 1, length, 0)?;
+    let end = relative_argument(interpreter, args, 2, length, length)?;
+    let (_, length) = validate_this(interpreter, this, "fill")?;
+    let bytes = state.kind.to_raw_bytes(interpreter, &value, true)?;
+    let size = state.kind.element_size();
+    for index in start..end.min(length) {
+        let byte_index = state.byte_offset + index * size;
+        set_value_in_buffer(interpreter, state.buffer, byte_index, &bytes);
+    }
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.filter
+fn typed_array_filter(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "filter")?;
+    let (callback, this_arg) = for_each_callback(interpreter, args)?;
+    let mut kept = vec![];
+    for index in 0..length {
+        let value = get_index(interpreter, &state, index);
+        let call_args = [value.clone(), length_value(index), this.clone()];
+        if interpreter
+            .call_function(&callback, &this_arg, &call_args)?
+            .to_boolean()
+        {
+            kept.push(value);
+        }
+    }
+    let (array, new_state) =
+        species_create(interpreter, this, state.kind, &[length_value(kept.len())])?;
+    for (index, value) in kept.iter().enumerate() {
+        typed_array_set_element(interpreter, &new_state, index as f64, value)?;
+    }
+    Ok(array)
+}
+
+/// The position a search starts from: `fromIndex` counted from the end when negative,
+/// `None` when that is past the end.
+fn search_start(
+    interpreter: &mut Interpreter,
+    args: &[JSValue],
+    length: usize,
+) -> JSResult<Option<usize>> {
+    let from = argument(args, 1)
+        .to_integer_or_infinity(interpreter)?
+        .get_number();
+    if from >= length as f64 {
+        return Ok(None);
+    }
+    Ok(Some(relative_index(from, length)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.includes
+fn typed_array_includes(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "includes")?;
+    if length == 0 {
+        return Ok(JSValue::new_boolean(false));
+    }
+    let Some(start) = search_start(interpreter, args, length)? else {
+        return Ok(JSValue::new_boolean(false));
+    };
+    let search = argument(args, 0);
+    let found =
+        (start..length).any(
+            |index| match (get_index(interpreter, &state, index), &search) {
+                (JSValue::Number { data: element }, JSValue::Number { data: search }) => {
+                    same_value_zero(element, *search)
+                }
+                (element, search) => element == *search,
+            },
+        );
+    Ok(JSValue::new_boolean(found))
+}
+
+/// IsStrictlyEqual for the Numbers and BigInts elements are: NaN matches nothing.
+fn strictly_equal_element(element: &JSValue, search: &JSValue) -> bool {
+    !element.is_undefined() && element == search
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.indexof
+fn typed_array_index_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "indexOf")?;
+    if length == 0 {
+        return Ok(JSValue::new_number(&-1.0));
+    }
+    let Some(start) = search_start(interpreter, args, length)? else {
+        return Ok(JSValue::new_number(&-1.0));
+    };
+    let search = argument(args, 0);
+    let found = (start..length)
+        .find(|index| strictly_equal_element(&get_index(interpreter, &state, *index), &search));
+    Ok(JSValue::new_number(
+        &found.map_or(-1.0, |index| index as f64),
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.lastindexof
+fn typed_array_last_index_of(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "lastIndexOf")?;
+    if length == 0 {
+        return Ok(JSValue::new_number(&-1.0));
+    }
+    let from = match args.get(1) {
+        Some(from) => from.to_integer_or_infinity(interpreter)?.get_number(),
+        None => length as f64 - 1.0,
+    };
+    let last = match from >= 0.0 {
+        true => from.min(length as f64 - 1.0),
+        false => length as f64 + from,
+    };
+    if last < 0.0 {
+        return Ok(JSValue::new_number(&-1.0));
+    }
+    let search = argument(args, 0);
+    let found = (0..=last as usize)
+        .rev()
+        .find(|index| strictly_equal_element(&get_index(interpreter, &state, *index), &search));
+    Ok(JSValue::new_number(
+        &found.map_or(-1.0, |index| index as f64),
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.join
+/// Also `toString`, which is `join` with the default separator.
+fn typed_array_join(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "join")?;
+    let separator = match argument(args, 0) {
+        JSValue::Undefined => ",".to_string(),
+        separator => {
+            let separator = separator.to_string(interpreter)?;
+            get_string_from_pool(&separator).unwrap_or_default()
+        }
+    };
+    let mut parts = vec![];
+    for index in 0..length {
+        let part = match get_index(interpreter, &state, index) {
+            JSValue::Undefined => String::new(),
+            value => {
+                let string = value.to_string(interpreter)?;
+                get_string_from_pool(&string).unwrap_or_default()
+            }
+        };
+        parts.push(part);
+    }
+    let joined = parts.join(&separator);
+    Ok(JSValue::new_string(&get_or_intern_string(&joined)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.tolocalestring
+fn typed_array_to_locale_string(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "toLocaleString")?;
+    let method_key = get_or_intern_string("toLocaleString");
+    let mut parts = vec![];
+    for index in 0..length {
+        let value = get_index(interpreter, &state, index);
+        if value.is_undefined() {
+            parts.push(String::new());
+            continue;
+        }
+        let method = get_property_value(interpreter, &value, method_key)?;
+        let string = interpreter
+            .call_function(&method, &value, &[])?
+            .to_string(interpreter)?;
+        parts.push(get_string_from_pool(&string).unwrap_or_default());
+    }
+    let joined = parts.join(",");
+    Ok(JSValue::new_string(&get_or_intern_string(&joined)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.map
+fn typed_array_map(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "map")?;
+    let (callback, this_arg) = for_each_callback(interpreter, args)?;
+    let (array, new_state) =
+        species_create(interpreter, this, state.kind, &[length_value(length)])?;
+    for index in 0..length {
+        let value = get_index(interpreter, &state, index);
+        let call_args = [value, length_value(index), this.clone()];
+        let mapped = interpreter.call_function(&callback, &this_arg, &call_args)?;
+        typed_array_set_element(interpreter, &new_state, index as f64, &mapped)?;
+    }
+    Ok(array)
+}
+
+fn reduce(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+    method: &str,
+    reverse: bool,
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, method)?;
+    let callback = argument(args, 0);
+    if !interpreter.is_callable(&callback) {
+        let callback = debug_value(interpreter, &callback);
+        return Err(JSError::new_type_error(&format!(
+            "{callback} is not a function"
+        )));
+    }
+    let mut indices: Box<dyn Iterator<Item = usize>> = match reverse {
+        true => Box::new((0..length).rev()),
+        false => Box::new(0..length),
+    };
+    let mut accumulator = match args.get(1) {
+        Some(initial) => initial.clone(),
+        None => match indices.next() {
+            Some(index) => get_index(interpreter, &state, index),
+            None => {
+                return Err(JSError::new_type_error(
+                    "Reduce of empty array with no initial value",
+                ));
+            }
+        },
+    };
+    for index in indices {
+        let value = get_index(interpreter, &state, index);
+        let call_args = [accumulator, value, length_value(index), this.clone()];
+        accumulator = interpreter.call_function(&callback, &JSValue::Undefined, &call_args)?;
+    }
+    Ok(accumulator)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.reduce
+fn typed_array_reduce(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    reduce(interpreter, this, args, "reduce", false)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.reduceright
+fn typed_array_reduce_right(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    reduce(interpreter, this, args, "reduceRight", true)
+}
+
+/// The bytes of all `length` elements, in index order.
+fn element_bytes(interpreter: &Interpreter, state: &TypedArrayState, length: usize) -> Vec<u8> {
+    let start = state.byte_offset;
+    let end = start + length * state.kind.element_size();
+    array_buffer_state(interpreter, state.buffer)
+        .and_then(|buffer| buffer.bytes().get(start..end))
+        .unwrap_or_default()
+        .to_vec()
+}
+
+/// `bytes` with the order of their elements reversed.
+fn reverse_elements(bytes: &[u8], size: usize) -> Vec<u8> {
+    bytes.chunks(size).rev().flatten().copied().collect()
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.reverse
+fn typed_array_reverse(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "reverse")?;
+    let bytes = element_bytes(interpreter, &state, length);
+    let reversed = reverse_elements(&bytes, state.kind.element_size());
+    set_value_in_buffer(interpreter, state.buffer, state.byte_offset, &reversed);
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.toreversed
+fn typed_array_to_reversed(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "toReversed")?;
+    let bytes = element_bytes(interpreter, &state, length);
+    let reversed = reverse_elements(&bytes, state.kind.element_size());
+    let (array, new_state) = create_same_type(interpreter, state.kind, length)?;
+    set_value_in_buffer(interpreter, new_state.buffer, 0, &reversed);
+    Ok(array)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.set
+/// Copies a typed array or array-like into this one from `offset` on.
+fn typed_array_set(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let Some(target) = as_typed_array(interpreter, this) else {
+        return Err(JSError::new_type_error("this is not a typed array."));
+    };
+    let offset = argument(args, 1)
+        .to_integer_or_infinity(interpreter)?
+        .get_number();
+    if offset < 0.0 {
+        return Err(JSError::new_range_error("offset is out of bounds"));
+    }
+    let source = argument(args, 0);
+    let (_, target_length) = validate_this(interpreter, this, "set")?;
+    // https://262.ecma-international.org/15.0/index.html#sec-settypedarrayfromtypedarray
+    if let Some(source_state) = as_typed_array(interpreter, &source) {
+        let Some(source_length) = typed_array_length(interpreter, &source_state) else {
+            return Err(JSError::new_type_error(
+                "Cannot perform %TypedArray%.prototype.set on a detached ArrayBuffer",
+            ));
+        };
+        if target.kind.is_big_int() != source_state.kind.is_big_int() {
+            return Err(JSError::new_type_error(
+                "Cannot mix BigInt and other types, use explicit conversions",
+            ));
+        }
+        if offset + source_length as f64 > target_length as f64 {
+            return Err(JSError::new_range_error("offset is out of bounds"));
+        }
+        let offset = offset as usize;
+        // reading everything first makes overlapping views on one buffer safe
+        if source_state.kind == target.kind {
+            let bytes = element_bytes(interpreter, &source_state, source_length);
+            let byte_index = target.byte_offset + offset * target.kind.element_size();
+            set_value_in_buffer(interpreter, target.buffer, byte_index, &bytes);
+            return Ok(JSValue::Undefined);
+        }
+        let values = (0..source_length)
+            .map(|index| get_index(interpreter, &source_state, index))
+            .collect::<Vec<_>>();
+        for (index, value) in values.iter().enumerate() {
+            let index = (offset + index) as f64;
+            typed_array_set_element(interpreter, &target, index, value)?;
+        }
+        return Ok(JSValue::Undefined);
+    }
+    // https://262.ecma-international.org/15.0/index.html#sec-settypedarrayfromarraylike
+    if source.is_null() || source.is_undefined() {
+        return Err(JSError::new_type_error(
+            "Cannot convert undefined or null to object",
+        ));
+    }
+    let length_key = get_or_intern_string("length");
+    let source_length = match source.is_object() {
+        true => get_property_value(interpreter, &source, length_key)?
+            .to_length(interpreter)?
+            .get_number(),
+        false => 0.0,
+    };
+    if offset + source_length > target_length as f64 {
+        return Err(JSError::new_range_error("offset is out of bounds"));
+    }
+    for index in 0..source_length as usize {
+        let value = get_property_value(interpreter, &source, index_key(index))?;
+        typed_array_set_element(interpreter, &target, offset + index as f64, &value)?;
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.slice
+fn typed_array_slice(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "slice")?;
+    let start = relative_argument(interpreter, args, 0, length, 0)?;
+    let end = relative_argument(interpreter, args, 1, length, length)?;
+    let count = end.saturating_sub(start);
+    let (array, new_state) = species_create(interpreter, this, state.kind, &[length_value(count)])?;
+    if count == 0 {
+        return Ok(array);
+    }
+    let (_, length) = validate_this(interpreter, this, "slice")?;
+    let end = end.min(length);
+    if new_state.kind == state.kind {
+        let size = state.kind.element_size();
+        let from = state.byte_offset + start * size;
+        let bytes = array_buffer_state(interpreter, state.buffer)
+            .and_then(|buffer| {
+                buffer
+                    .bytes()
+                    .get(from..from + end.saturating_sub(start) * size)
+            })
+            .unwrap_or_default()
+            .to_vec();
+        set_value_in_buffer(interpreter, new_state.buffer, new_state.byte_offset, &bytes);
+        return Ok(array);
+    }
+    for (target_index, index) in (start..end).enumerate() {
+        let value = get_index(interpreter, &state, index);
+        typed_array_set_element(interpreter, &new_state, target_index as f64, &value)?;
+    }
+    Ok(array)
+}
+
+/// The comparator argument of `sort` and `toSorted`, checked before anything else happens.
+fn comparator(interpreter: &mut Interpreter, args: &[JSValue]) -> JSResult<JSValue> {
+    let compare = argument(args, 0);
+    if !compare.is_undefined() && !interpreter.is_callable(&compare) {
+        return Err(JSError::new_type_error(
+            "The comparison function must be either a function or undefined",
+        ));
+    }
+    Ok(compare)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-comparetypedarrayelements
+/// Whether `right` goes before `left`. Without a comparator Numbers sort numerically, with
+/// -0 before +0 and NaN last.
+fn goes_before(
+    interpreter: &mut Interpreter,
+    compare: &JSValue,
+    right: &JSValue,
+    left: &JSValue,
+) -> JSResult<bool> {
+    if !compare.is_undefined() {
+        let order = interpreter
+            .call_function(compare, &JSValue::Undefined, &[right.clone(), left.clone()])?
+            .to_number(interpreter)?
+            .get_number();
+        return Ok(order < 0.0);
+    }
+    Ok(match (right, left) {
+        (JSValue::Number { data: x }, JSValue::Number { data: y }) => {
+            match (x.is_nan(), y.is_nan()) {
+                (_, true) => !x.is_nan(),
+                (true, false) => false,
+                _ if x == y => x.is_sign_negative() && y.is_sign_positive(),
+                _ => less_than(*x, *y),
+            }
+        }
+        (JSValue::BigInt { data: x }, JSValue::BigInt { data: y }) => x < y,
+        _ => false,
+    })
+}
+
+/// A stable merge sort that lets the comparator throw.
+fn sort_values(
+    interpreter: &mut Interpreter,
+    values: Vec<JSValue>,
+    compare: &JSValue,
+) -> JSResult<Vec<JSValue>> {
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+    let mut left = values;
+    let right = left.split_off(left.len() / 2);
+    let left = sort_values(interpreter, left, compare)?;
+    let right = sort_values(interpreter, right, compare)?;
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        match goes_before(interpreter, compare, r, l)? {
+            true => merged.extend(right.next()),
+            false => merged.extend(left.next()),
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.sort
+fn typed_array_sort(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let compare = comparator(interpreter, args)?;
+    let (state, length) = validate_this(interpreter, this, "sort")?;
+    let values = (0..length)
+        .map(|index| get_index(interpreter, &state, index))
+        .collect();
+    let sorted = sort_values(interpreter, values, &compare)?;
+    for (index, value) in sorted.iter().enumerate() {
+        typed_array_set_element(interpreter, &state, index as f64, value)?;
+    }
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.tosorted
+fn typed_array_to_sorted(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let compare = comparator(interpreter, args)?;
+    let (state, length) = validate_this(interpreter, this, "toSorted")?;
+    let (array, new_state) = create_same_type(interpreter, state.kind, length)?;
+    let values = (0..length)
+        .map(|index| get_index(interpreter, &state, index))
+        .collect();
+    let sorted = sort_values(interpreter, values, &compare)?;
+    for (index, value) in sorted.iter().enumerate() {
+        typed_array_set_element(interpreter, &new_state, index as f64, value)?;
+    }
+    Ok(array)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.subarray
+/// A new view on the same buffer. Subarrays of length-tracking arrays track too unless an
+/// end is given.
+fn typed_array_subarray(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let Some(state) = as_typed_array(interpreter, this) else {
+        return Err(JSError::new_type_error("this is not a typed array."));
+    };
+    let length = typed_array_length(interpreter, &state).unwrap_or(0);
+    let begin = relative_argument(interpreter, args, 0, length, 0)?;
+    let end = argument(args, 1);
+    let buffer = JSValue::Object {
+        object_id: state.buffer,
+        kind: ObjectKind::Object,
+    };
+    let begin_byte_offset = length_value(state.byte_offset + begin * state.kind.element_size());
+    let species_args = match state.array_length.is_none() && end.is_undefined() {
+        true => vec![buffer, begin_byte_offset],
+        false => {
+            let end = relative_argument(interpreter, args, 1, length, length)?;
+            let new_length = length_value(end.saturating_sub(begin));
+            vec![buffer, begin_byte_offset, new_length]
+        }
+    };
+    Ok(species_create(interpreter, this, state.kind, &species_args)?.0)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-%typedarray%.prototype.with
+fn typed_array_with(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let (state, length) = validate_this(interpreter, this, "with")?;
+    let relative = argument(args, 0)
+        .to_integer_or_infinity(interpreter)?
+        .get_number();
+    let index = match relative >= 0.0 {
+        true => relative,
+        false => length as f64 + relative,
+    };
+    let value = state.kind.to_element(interpreter, &argument(args, 1))?;
+    if !is_valid_integer_index(interpreter, &state, index) {
+        return Err(JSError::new_range_error("Invalid typed array index"));
+    }
+    let (array, new_state) = create_same_type(interpreter, state.kind, length)?;
+    for position in 0..length {
+        let element = match position as f64 == index {
+            true => value.clone(),
+            false => get_index(interpreter, &state, position),
+        };
+        typed_array_set_element(interpreter, &new_state, position as f64, &element)?;
+    }
+    Ok(array)
+}
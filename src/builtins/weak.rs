@@ -0,0 +1,283 @@
+//! The `WeakMap` and `WeakSet` constructors and their prototypes. Their keys must be values
+//! that can become unreachable, and the entries go away with them; see `WeakTable`.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        argument,
+        collection::{
+            WeakKey, WeakTable, add_from_iterable, prototype_from_new_target, this_collection,
+        },
+        object::{add_methods, add_to_string_tag},
+    },
+    constants::{WEAK_MAP_NAME, WEAK_SET_NAME},
+    errors::JSError,
+    global::get_or_intern_string,
+    values::{
+        JSObject, JSResult, JSValue, ObjectData, ObjectId, ObjectKind, Property,
+        link_constructor_and_prototype,
+    },
+};
+
+/// Builds the globals `WeakMap` and `WeakSet` with their prototypes.
+pub fn create_weak_constructors(interpreter: &mut Interpreter) -> Vec<Property> {
+    let object_proto_id = interpreter.get_object_proto_id();
+
+    let weak_map_id = JSObject::new_native_function(
+        WEAK_MAP_NAME,
+        0,
+        weak_map_call,
+        Some(weak_map_construct),
+        interpreter,
+    );
+    let weak_map_proto_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        weak_map_proto_id,
+        vec![
+            ("delete", 1, weak_map_delete),
+            ("get", 1, weak_map_get),
+            ("has", 1, weak_map_has),
+            ("set", 2, weak_map_set),
+        ],
+        interpreter,
+    );
+    add_to_string_tag(weak_map_proto_id, WEAK_MAP_NAME, interpreter);
+    link_constructor_and_prototype(weak_map_id, weak_map_proto_id, interpreter);
+    interpreter.intrinsics.weak_map_proto_id = weak_map_proto_id;
+
+    let weak_set_id = JSObject::new_native_function(
+        WEAK_SET_NAME,
+        0,
+        weak_set_call,
+        Some(weak_set_construct),
+        interpreter,
+    );
+    let weak_set_proto_id =
+        JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+    add_methods(
+        weak_set_proto_id,
+        vec![
+            ("add", 1, weak_set_add),
+            ("delete", 1, weak_set_delete),
+            ("has", 1, weak_set_has),
+        ],
+        interpreter,
+    );
+    add_to_string_tag(weak_set_proto_id, WEAK_SET_NAME, interpreter);
+    link_constructor_and_prototype(weak_set_id, weak_set_proto_id, interpreter);
+    interpreter.intrinsics.weak_set_proto_id = weak_set_proto_id;
+
+    [(WEAK_MAP_NAME, weak_map_id), (WEAK_SET_NAME, weak_set_id)]
+        .into_iter()
+        .map(|(name, object_id)| {
+            let value = JSValue::Object {
+                object_id,
+                kind: ObjectKind::Function,
+            };
+            (get_or_intern_string(name), value)
+        })
+        .collect()
+}
+
+fn weak_map_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error(
+        "Constructor WeakMap requires 'new'",
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakmap-iterable
+fn weak_map_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let fallback = interpreter.intrinsics.weak_map_proto_id;
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    let data = ObjectData::WeakMap(Box::new(WeakTable::new()));
+    let weak_map = JSValue::Object {
+        object_id: JSObject::new_object_with_data(data, Some(prototype), interpreter),
+        kind: ObjectKind::Object,
+    };
+    add_from_iterable(interpreter, &weak_map, &argument(args, 0), "set", true)?;
+    Ok(weak_map)
+}
+
+/// The id of the WeakMap `this` is, or the receiver error of `method`.
+fn this_weak_map(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<ObjectId> {
+    this_collection(
+        interpreter,
+        this,
+        &format!("WeakMap.prototype.{method}"),
+        |data| matches!(data, ObjectData::WeakMap(_)),
+    )
+}
+
+fn weak_map_table(
+    interpreter: &mut Interpreter,
+    weak_map_id: ObjectId,
+) -> JSResult<&mut WeakTable<JSValue>> {
+    match interpreter.get_object_mut(weak_map_id)?.get_data_mut() {
+        Some(ObjectData::WeakMap(table)) => Ok(table),
+        _ => Err(JSError::new_type_error("object is not a WeakMap")),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakmap.prototype.delete
+fn weak_map_delete(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let weak_map_id = this_weak_map(interpreter, this, "delete")?;
+    let Some(key) = WeakKey::new(interpreter, &argument(args, 0)) else {
+        return Ok(JSValue::new_boolean(false));
+    };
+    let deleted = weak_map_table(interpreter, weak_map_id)?.remove(&key);
+    Ok(JSValue::new_boolean(deleted))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakmap.prototype.get
+fn weak_map_get(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let weak_map_id = this_weak_map(interpreter, this, "get")?;
+    let Some(key) = WeakKey::new(interpreter, &argument(args, 0)) else {
+        return Ok(JSValue::Undefined);
+    };
+    let value = weak_map_table(interpreter, weak_map_id)?.get(&key).cloned();
+    Ok(value.unwrap_or(JSValue::Undefined))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakmap.prototype.has
+fn weak_map_has(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let weak_map_id = this_weak_map(interpreter, this, "has")?;
+    let Some(key) = WeakKey::new(interpreter, &argument(args, 0)) else {
+        return Ok(JSValue::new_boolean(false));
+    };
+    let has = weak_map_table(interpreter, weak_map_id)?.contains(&key);
+    Ok(JSValue::new_boolean(has))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakmap.prototype.set
+fn weak_map_set(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let weak_map_id = this_weak_map(interpreter, this, "set")?;
+    let Some(key) = WeakKey::new(interpreter, &argument(args, 0)) else {
+        return Err(JSError::new_type_error(
+            "Invalid value used as weak map key",
+        ));
+    };
+    weak_map_table(interpreter, weak_map_id)?.insert(key, argument(args, 1));
+    Ok(this.clone())
+}
+
+fn weak_set_call(
+    _interpreter: &mut Interpreter,
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    Err(JSError::new_type_error(
+        "Constructor WeakSet requires 'new'",
+    ))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakset-iterable
+fn weak_set_construct(
+    interpreter: &mut Interpreter,
+    new_target: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let fallback = interpreter.intrinsics.weak_set_proto_id;
+    let prototype = prototype_from_new_target(interpreter, new_target, fallback);
+    let data = ObjectData::WeakSet(Box::new(WeakTable::new()));
+    let weak_set = JSValue::Object {
+        object_id: JSObject::new_object_with_data(data, Some(prototype), interpreter),
+        kind: ObjectKind::Object,
+    };
+    add_from_iterable(interpreter, &weak_set, &argument(args, 0), "add", false)?;
+    Ok(weak_set)
+}
+
+/// The id of the WeakSet `this` is, or the receiver error of `method`.
+fn this_weak_set(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    method: &str,
+) -> JSResult<ObjectId> {
+    this_collection(
+        interpreter,
+        this,
+        &format!("WeakSet.prototype.{method}"),
+        |data| matches!(data, ObjectData::WeakSet(_)),
+    )
+}
+
+fn weak_set_table(
+    interpreter: &mut Interpreter,
+    weak_set_id: ObjectId,
+) -> JSResult<&mut WeakTable<()>> {
+    match interpreter.get_object_mut(weak_set_id)?.get_data_mut() {
+        Some(ObjectData::WeakSet(table)) => Ok(table),
+        _ => Err(JSError::new_type_error("object is not a WeakSet")),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakset.prototype.add
+fn weak_set_add(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let weak_set_id = this_weak_set(interpreter, this, "add")?;
+    let Some(value) = WeakKey::new(interpreter, &argument(args, 0)) else {
+        return Err(JSError::new_type_error("Invalid value used in weak set"));
+    };
+    weak_set_table(interpreter, weak_set_id)?.insert(value, ());
+    Ok(this.clone())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakset.prototype.delete
+fn weak_set_delete(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let weak_set_id = this_weak_set(interpreter, this, "delete")?;
+    let Some(value) = WeakKey::new(interpreter, &argument(args, 0)) else {
+        return Ok(JSValue::new_boolean(false));
+    };
+    let deleted = weak_set_table(interpreter, weak_set_id)?.remove(&value);
+    Ok(JSValue::new_boolean(deleted))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-weakset.prototype.has
+fn weak_set_has(
+    interpreter: &mut Interpreter,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let weak_set_id = this_weak_set(interpreter, this, "has")?;
+    let Some(value) = WeakKey::new(interpreter, &argument(args, 0)) else {
+        return Ok(JSValue::new_boolean(false));
+    };
+    let has = weak_set_table(interpreter, weak_set_id)?.contains(&value);
+    Ok(JSValue::new_boolean(has))
+}
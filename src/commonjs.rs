@@ -0,0 +1,208 @@
+//! CommonJS compatibility: `require`, `module.exports` and `exports`. Files are found through
+//! the interpreter's `ModuleLoader`, like ES modules, and each one runs once inside a function
+//! the way Node wraps them.
+
+use std::path::Path;
+
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::json::parse_json,
+    errors::JSError,
+    expr::{Expr, evaluate_function},
+    global::{get_or_intern_string, get_string_from_pool},
+    modules::{loader_error, parse_source},
+    stmt::Stmt,
+    values::{
+        BodyKind, FunctionKind, JSObject, JSResult, JSValue, ObjectKind, get_property_value,
+        set_property_value,
+    },
+};
+
+/// The parameters of the function every file is wrapped in, in Node's order.
+const WRAPPER_PARAMETERS: [&str; 5] = ["exports", "require", "module", "__filename", "__dirname"];
+
+/// Extensions tried, in order, when a specifier doesn't name a file as is.
+const EXTENSIONS: [&str; 2] = [".js", ".json"];
+
+// https://nodejs.org/api/modules.html#all-together
+/// Finds the key of the file `specifier` refers to: the file itself, then with each of
+/// `EXTENSIONS` added, then the `index.js` of a directory.
+fn resolve_require(
+    interpreter: &mut Interpreter,
+    specifier: &str,
+    referrer: Option<&str>,
+) -> JSResult<String> {
+    let candidates = std::iter::once(specifier.to_string())
+        .chain(EXTENSIONS.iter().map(|ext| format!("{specifier}{ext}")))
+        .chain(std::iter::once(format!(
+            "{}/index.js",
+            specifier.trim_end_matches('/')
+        )));
+    for candidate in candidates {
+        if let Ok(key) = interpreter.module_loader.resolve(&candidate, referrer) {
+            return Ok(key);
+        }
+    }
+    Err(loader_error(&format!("Cannot find module '{specifier}'")))
+}
+
+/// Loads and runs the file `specifier` refers to, once, and returns its `module.exports`. A
+/// module is cached before it runs, so a circular `require` gets whatever it exported so far.
+pub fn require(
+    interpreter: &mut Interpreter,
+    specifier: &str,
+    referrer: Option<&str>,
+) -> JSResult<JSValue> {
+    let exports_key = get_or_intern_string("exports");
+    let key = resolve_require(interpreter, specifier, referrer)?;
+    if let Some(module) = interpreter.commonjs_cache.get(&key).cloned() {
+        return get_property_value(interpreter, &module, exports_key);
+    }
+    let source = interpreter
+        .module_loader
+        .load(&key)
+        .map_err(|message| loader_error(&message))?;
+
+    let exports = JSValue::new_object(vec![], interpreter);
+    let filename = JSValue::new_string(&get_or_intern_string(&key));
+    let properties = vec![
+        (get_or_intern_string("id"), filename.clone()),
+        (get_or_intern_string("filename"), filename),
+        (get_or_intern_string("loaded"), JSValue::new_boolean(false)),
+        (exports_key, exports.clone()),
+    ];
+    let module = JSValue::new_object(properties, interpreter);
+    interpreter
+        .commonjs_cache
+        .insert(key.clone(), module.clone());
+
+    // JSON files are parsed, not run, and their value is the module's exports
+    let result = match key.ends_with(".json") {
+        true => parse_json(interpreter, &source)
+            .map_err(|e| {
+                let message = e.message.trim_start_matches("Uncaught SyntaxError: ");
+                JSError::new_syntax_error(&format!("{key}: {message}"))
+            })
+            .and_then(|value| set_property_value(interpreter, &module, exports_key, value))
+            .map(|_| ()),
+        false => run_wrapped(interpreter, &key, &source, &module, &exports).map(|_| ()),
+    };
+    if let Err(e) = result {
+        // like Node, a module that threw is loaded again by the next require
+        interpreter.commonjs_cache.remove(&key);
+        return Err(e);
+    }
+    let loaded_key = get_or_intern_string("loaded");
+    set_property_value(interpreter, &module, loaded_key, JSValue::new_boolean(true))?;
+    get_property_value(interpreter, &module, exports_key)
+}
+
+/// Runs `source` as the body of `function (exports, require, module, __filename, __dirname)`
+/// with `this` set to `exports`.
+fn run_wrapped(
+    interpreter: &mut Interpreter,
+    key: &str,
+    source: &str,
+    module: &JSValue,
+    exports: &JSValue,
+) -> JSResult<JSValue> {
+    let body = parse_source(interpreter, source, None)?;
+    let parameters: Vec<Expr> = WRAPPER_PARAMETERS
+        .iter()
+        .map(|name| Expr::new_identifier(&get_or_intern_string(name)))
+        .collect();
+    // the wrapper closes over the global scope, not whatever called require
+    let global_environment = interpreter.environment_stack[0];
+    interpreter.enter_scope(Some(global_environment));
+    let wrapper = evaluate_function(
+        None,
+        None,
+        &parameters,
+        &Stmt::new_block(body),
+        &FunctionKind::Normal,
+        BodyKind::Normal,
+        interpreter,
+    );
+    interpreter.leave_scope();
+
+    let filename = get_or_intern_string(key);
+    let dirname = Path::new(key)
+        .parent()
+        .map(|parent| parent.to_string_lossy().into_owned())
+        .filter(|parent| !parent.is_empty())
+        .unwrap_or_else(|| ".".to_string());
+    let arguments = [
+        exports.clone(),
+        create_require(interpreter, Some(filename)),
+        module.clone(),
+        JSValue::new_string(&filename),
+        JSValue::new_string(&get_or_intern_string(&dirname)),
+    ];
+    interpreter.call_function(&wrapper?, exports, &arguments)
+}
+
+/// The `require` function handed to a file, resolving specifiers relative to `referrer`. The
+/// global one, without a referrer, resolves them against the loader's root.
+pub fn create_require(interpreter: &mut Interpreter, referrer: Option<SymbolU32>) -> JSValue {
+    let captures = vec![match referrer {
+        Some(referrer) => JSValue::new_string(&referrer),
+        None => JSValue::Undefined,
+    }];
+    let require_id =
+        JSObject::new_native_closure("require", 1, require_closure, captures.clone(), interpreter);
+    let resolve_id =
+        JSObject::new_native_closure("resolve", 1, resolve_closure, captures, interpreter);
+    let resolve = JSValue::Object {
+        object_id: resolve_id,
+        kind: ObjectKind::Function,
+    };
+    if let Ok(require) = interpreter.get_object_mut(require_id) {
+        require.add_property(get_or_intern_string("resolve"), resolve);
+    }
+    JSValue::Object {
+        object_id: require_id,
+        kind: ObjectKind::Function,
+    }
+}
+
+fn captured_referrer(captures: &[JSValue]) -> Option<String> {
+    match captures.first() {
+        Some(JSValue::String { data }) => get_string_from_pool(data),
+        _ => None,
+    }
+}
+
+fn specifier_argument(args: &[JSValue]) -> JSResult<String> {
+    match args.first() {
+        Some(JSValue::String { data }) => Ok(get_string_from_pool(data).unwrap_or_default()),
+        _ => Err(JSError::new_type_error(
+            "The \"id\" argument must be of type string",
+        )),
+    }
+}
+
+fn require_closure(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let specifier = specifier_argument(args)?;
+    let referrer = captured_referrer(captures);
+    require(interpreter, &specifier, referrer.as_deref())
+}
+
+/// `require.resolve(specifier)`, the key `require` would load.
+fn resolve_closure(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let specifier = specifier_argument(args)?;
+    let referrer = captured_referrer(captures);
+    let key = resolve_require(interpreter, &specifier, referrer.as_deref())?;
+    Ok(JSValue::new_string(&get_or_intern_string(&key)))
+}
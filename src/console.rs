@@ -0,0 +1,123 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use crate::handles::{HandleScope, Local, Value};
+
+/// How serious a console message is. `Warn`, `Error` and `Trace` are the ones that go to
+/// standard error, like in Node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Log,
+    Info,
+    Warn,
+    Error,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn is_error(self) -> bool {
+        matches!(self, LogLevel::Warn | LogLevel::Error | LogLevel::Trace)
+    }
+}
+
+/// One message for the console: what a call like `console.warn('%d left', n)` or an uncaught
+/// error produced.
+pub struct ConsoleMessage<'a, 'i> {
+    pub level: LogLevel,
+    /// The values the script passed, format string included. Messages the interpreter makes
+    /// up itself, e.g. for `console.count` or an uncaught error, carry their text as a string.
+    pub arguments: &'a [Local<'i, Value>],
+    /// The arguments formatted the way Node would print them, without the indentation of
+    /// the groups the message is in.
+    pub text: &'a str,
+    /// How many `console.group` calls the message is nested in.
+    pub group_depth: usize,
+}
+
+impl ConsoleMessage<'_, '_> {
+    /// `text` indented by two spaces per group, on every line.
+    pub fn indented_text(&self) -> String {
+        let indent = "  ".repeat(self.group_depth);
+        self.text
+            .split('\n')
+            .map(|line| format!("{indent}{line}\n"))
+            .collect()
+    }
+}
+
+/// Where the output of scripts goes: everything the `console` methods print and the errors
+/// nothing caught. The arguments are handles into the interpreter, so hosts can look at the
+/// values themselves instead of the text, e.g. for structured logging. Messages logged while
+/// the console is still handling one, by calling back into the interpreter, are dropped.
+pub trait Console: Send {
+    fn message<'i>(&mut self, scope: &mut HandleScope<'i>, message: &ConsoleMessage<'_, 'i>);
+}
+
+/// Prints messages as they come, errors and warnings to standard error.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdoutConsole;
+
+impl Console for StdoutConsole {
+    fn message<'i>(&mut self, _scope: &mut HandleScope<'i>, message: &ConsoleMessage<'_, 'i>) {
+        let text = message.indented_text();
+        // there is nobody to tell when the terminal is gone
+        let _ = match message.level.is_error() {
+            true => std::io::stderr().write_all(text.as_bytes()),
+            false => std::io::stdout().write_all(text.as_bytes()),
+        };
+    }
+}
+
+/// Collects messages in memory, the messages for standard output and standard error apart.
+/// Clones share their buffers, so the host can keep one to read what the interpreter's copy
+/// collected. This is the console interpreters start out with, the one `interpret` and the
+/// event loop methods return the output of.
+#[derive(Clone, Debug, Default)]
+pub struct BufferConsole {
+    buffers: Arc<Mutex<Buffers>>,
+}
+
+#[derive(Debug, Default)]
+struct Buffers {
+    output: String,
+    errors: String,
+}
+
+impl BufferConsole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything logged to standard output so far.
+    pub fn output(&self) -> String {
+        self.buffers.lock().unwrap().output.clone()
+    }
+
+    /// Everything logged to standard error so far.
+    pub fn errors(&self) -> String {
+        self.buffers.lock().unwrap().errors.clone()
+    }
+
+    /// Empties both buffers, returning what they held.
+    pub fn take(&self) -> (String, String) {
+        let mut buffers = self.buffers.lock().unwrap();
+        (
+            std::mem::take(&mut buffers.output),
+            std::mem::take(&mut buffers.errors),
+        )
+    }
+}
+
+impl Console for BufferConsole {
+    fn message<'i>(&mut self, _scope: &mut HandleScope<'i>, message: &ConsoleMessage<'_, 'i>) {
+        let text = message.indented_text();
+        let mut buffers = self.buffers.lock().unwrap();
+        match message.level.is_error() {
+            true => buffers.errors.push_str(&text),
+            false => buffers.output.push_str(&text),
+        }
+    }
+}
@@ -4,5 +4,29 @@ pub const INFINITY_NAME: &'static str = "Infinity";
 pub const NAN_NAME: &'static str = "NaN";
 pub const UNDEFINED_NAME: &'static str = "undefined";
 pub const CONSOLE_NAME: &'static str = "console";
-pub const LOG_NAME: &'static str = "log";
-pub const ERROR_NAME: &'static str = "error";
+pub const THIS_NAME: &str = "this";
+pub const OBJECT_NAME: &str = "Object";
+pub const PROTOTYPE_NAME: &str = "prototype";
+pub const CONSTRUCTOR_NAME: &str = "constructor";
+pub const LENGTH_NAME: &str = "length";
+pub const NAME_NAME: &str = "name";
+pub const PROMISE_NAME: &str = "Promise";
+pub const NUMBER_NAME: &str = "Number";
+pub const DEFAULT_NAME: &str = "default";
+// the binding behind `export default <expression>`, not a valid identifier on purpose
+pub const DEFAULT_EXPORT_BINDING: &str = "*default*";
+pub const BIGINT_NAME: &str = "BigInt";
+pub const SYMBOL_NAME: &str = "Symbol";
+pub const REGEXP_NAME: &str = "RegExp";
+pub const STRING_NAME: &str = "String";
+pub const LAST_INDEX_NAME: &str = "lastIndex";
+pub const MAP_NAME: &str = "Map";
+pub const SET_NAME: &str = "Set";
+pub const WEAK_MAP_NAME: &str = "WeakMap";
+pub const WEAK_SET_NAME: &str = "WeakSet";
+pub const DATE_NAME: &str = "Date";
+pub const ARRAY_BUFFER_NAME: &str = "ArrayBuffer";
+pub const TYPED_ARRAY_NAME: &str = "TypedArray";
+pub const DATA_VIEW_NAME: &str = "DataView";
+pub const PROXY_NAME: &str = "Proxy";
+pub const REFLECT_NAME: &str = "Reflect";
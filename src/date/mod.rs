@@ -0,0 +1,235 @@
+//! Time values, the milliseconds since the epoch `Date` objects hold: splitting them into
+//! calendar fields and back, and the string forms `Date` prints and parses. The builtins are in
+//! `crate::builtins::date`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod parse;
+pub mod timezone;
+
+pub use parse::parse_date;
+
+pub const MS_PER_SECOND: f64 = 1000.0;
+pub const MS_PER_MINUTE: f64 = 60_000.0;
+pub const MS_PER_HOUR: f64 = 3_600_000.0;
+pub const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// The largest distance from the epoch a time value may have, 100 million days.
+const MAX_TIME: f64 = 8.64e15;
+
+const WEEK_DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Milliseconds since the epoch according to the system clock.
+pub fn system_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as f64)
+        .unwrap_or_default()
+}
+
+/// The days since 1970-01-01 of a date in the proleptic Gregorian calendar, `month` counting
+/// from 1.
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The year, month (1 to 12) and day of the month of a day counted from 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// The calendar fields of a finite time value, `month` counting from 0 like `getMonth`.
+#[derive(Clone, Copy, Debug)]
+pub struct DateFields {
+    pub year: f64,
+    pub month: f64,
+    pub date: f64,
+    pub hours: f64,
+    pub minutes: f64,
+    pub seconds: f64,
+    pub milliseconds: f64,
+    pub week_day: f64,
+}
+
+impl DateFields {
+    // https://262.ecma-international.org/15.0/index.html#sec-year-number
+    pub fn from_time(time: f64) -> Self {
+        let day = (time / MS_PER_DAY).floor();
+        let (year, month, date) = civil_from_days(day as i64);
+        // + 0.0 turns the -0 of negative whole days into +0
+        let within_day = time.rem_euclid(MS_PER_DAY) + 0.0;
+        Self {
+            year: year as f64,
+            month: (month - 1) as f64,
+            date: date as f64,
+            hours: (within_day / MS_PER_HOUR).floor(),
+            minutes: (within_day / MS_PER_MINUTE).floor() % 60.0,
+            seconds: (within_day / MS_PER_SECOND).floor() % 60.0,
+            milliseconds: within_day % MS_PER_SECOND,
+            week_day: (day + 4.0).rem_euclid(7.0),
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-maketime
+pub fn make_time(hours: f64, minutes: f64, seconds: f64, milliseconds: f64) -> f64 {
+    if ![hours, minutes, seconds, milliseconds]
+        .iter()
+        .all(|field| field.is_finite())
+    {
+        return f64::NAN;
+    }
+    hours.trunc() * MS_PER_HOUR
+        + minutes.trunc() * MS_PER_MINUTE
+        + seconds.trunc() * MS_PER_SECOND
+        + milliseconds.trunc()
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-makeday
+/// The day number of `date` in `month` of `year`, months past December or before January
+/// carrying into the year.
+pub fn make_day(year: f64, month: f64, date: f64) -> f64 {
+    if ![year, month, date].iter().all(|field| field.is_finite()) {
+        return f64::NAN;
+    }
+    let year = year.trunc() + (month.trunc() / 12.0).floor();
+    // far enough past the range of time values that the result is clipped anyway
+    if year.abs() > 400_000.0 {
+        return f64::NAN;
+    }
+    let month = month.trunc().rem_euclid(12.0);
+    days_from_civil(year as i64, month as i64 + 1, 1) as f64 + date.trunc() - 1.0
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-makedate
+pub fn make_date(day: f64, time: f64) -> f64 {
+    let date = day * MS_PER_DAY + time;
+    if date.is_finite() { date } else { f64::NAN }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-timeclip
+pub fn time_clip(time: f64) -> f64 {
+    if !time.is_finite() || time.abs() > MAX_TIME {
+        return f64::NAN;
+    }
+    time.trunc() + 0.0
+}
+
+/// Years print with at least four digits, negative ones with a sign.
+fn format_year(year: f64) -> String {
+    match year < 0.0 {
+        true => format!("-{:04}", -year),
+        false => format!("{year:04}"),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-datestring
+/// `Tue Nov 14 2023`
+pub fn date_string(local: f64) -> String {
+    let fields = DateFields::from_time(local);
+    format!(
+        "{} {} {:02} {}",
+        WEEK_DAYS[fields.week_day as usize],
+        MONTHS[fields.month as usize],
+        fields.date,
+        format_year(fields.year)
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-timestring
+/// `22:13:20`
+pub fn time_string(local: f64) -> String {
+    let fields = DateFields::from_time(local);
+    format!(
+        "{:02}:{:02}:{:02}",
+        fields.hours, fields.minutes, fields.seconds
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-timezoneestring
+/// `GMT+0100 (CET)`, from the offset in milliseconds and the name of the zone.
+pub fn time_zone_string(offset: f64, name: &str) -> String {
+    let sign = if offset < 0.0 { '-' } else { '+' };
+    let minutes = (offset.abs() / MS_PER_MINUTE).floor();
+    format!(
+        "GMT{sign}{:02}{:02} ({name})",
+        (minutes / 60.0).floor(),
+        minutes % 60.0
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.prototype.toutcstring
+/// `Tue, 14 Nov 2023 22:13:20 GMT`
+pub fn utc_string(time: f64) -> String {
+    let fields = DateFields::from_time(time);
+    format!(
+        "{}, {:02} {} {} {} GMT",
+        WEEK_DAYS[fields.week_day as usize],
+        fields.date,
+        MONTHS[fields.month as usize],
+        format_year(fields.year),
+        time_string(time)
+    )
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date-time-string-format
+/// `2023-11-14T22:13:20.000Z`, years outside 0 to 9999 with six digits and a sign.
+pub fn iso_string(time: f64) -> String {
+    let fields = DateFields::from_time(time);
+    let year = match fields.year {
+        year if (0.0..=9999.0).contains(&year) => format!("{year:04}"),
+        year if year < 0.0 => format!("-{:06}", -year),
+        year => format!("+{year:06}"),
+    };
+    format!(
+        "{year}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        fields.month + 1.0,
+        fields.date,
+        fields.hours,
+        fields.minutes,
+        fields.seconds,
+        fields.milliseconds
+    )
+}
+
+/// `11/14/2023`, what `toLocaleDateString` prints for English.
+pub fn locale_date_string(local: f64) -> String {
+    let fields = DateFields::from_time(local);
+    format!("{}/{}/{}", fields.month + 1.0, fields.date, fields.year)
+}
+
+/// `10:13:20 PM`, what `toLocaleTimeString` prints for English.
+pub fn locale_time_string(local: f64) -> String {
+    let fields = DateFields::from_time(local);
+    let period = if fields.hours < 12.0 { "AM" } else { "PM" };
+    let hours = match fields.hours % 12.0 {
+        0.0 => 12.0,
+        hours => hours,
+    };
+    format!(
+        "{hours}:{:02}:{:02} {period}",
+        fields.minutes, fields.seconds
+    )
+}
@@ -0,0 +1,315 @@
+//! `Date.parse`: the date time string format of the spec, and the looser formats browsers
+//! accept on top, like `Tue, 14 Nov 2023 22:13:20 GMT` or `November 14, 2023 10:13 PM`.
+
+use crate::date::{MS_PER_MINUTE, make_date, make_day, make_time, time_clip, timezone::TimeZone};
+
+const MONTH_NAMES: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+const WEEK_DAY_NAMES: [&str; 7] = [
+    "sunday",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+];
+
+// https://262.ecma-international.org/15.0/index.html#sec-date.parse
+/// The time value `string` stands for, NaN when it isn't a date. Strings without an offset are
+/// local time, except for the date-only forms of the spec's format, which are UTC.
+pub fn parse_date(string: &str, time_zone: &TimeZone) -> f64 {
+    let parsed = parse_iso(string).or_else(|| parse_legacy(string));
+    let Some(parsed) = parsed else {
+        return f64::NAN;
+    };
+    let time = match parsed.offset {
+        Some(offset) => parsed.local - offset,
+        None => parsed.local - time_zone.offset_from_local(parsed.local),
+    };
+    time_clip(time)
+}
+
+struct Parsed {
+    /// The date and time as written.
+    local: f64,
+    /// The offset from UTC in milliseconds, `None` for local time.
+    offset: Option<f64>,
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn eat(&mut self, expected: u8) -> bool {
+        let matches = self.peek() == Some(expected);
+        self.position += matches as usize;
+        matches
+    }
+
+    fn eat_any(&mut self, expected: &[u8]) -> Option<u8> {
+        let byte = self.peek().filter(|byte| expected.contains(byte))?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    /// Exactly `count` digits.
+    fn digits(&mut self, count: usize) -> Option<f64> {
+        let digits = self.bytes.get(self.position..self.position + count)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        self.position += count;
+        Some(digits.iter().fold(0.0, |n, d| n * 10.0 + (d - b'0') as f64))
+    }
+
+    /// A run of digits and how many there were.
+    fn number(&mut self) -> Option<(f64, usize)> {
+        let start = self.position;
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let count = self.position - start;
+        (count > 0)
+            .then(|| {
+                (self.bytes[start..self.position].iter())
+                    .fold(0.0, |n, d| n * 10.0 + (d - b'0') as f64)
+            })
+            .map(|number| (number, count))
+    }
+
+    /// A fraction of a second written after the dot, in milliseconds. Digits past the third
+    /// are dropped.
+    fn milliseconds(&mut self) -> Option<f64> {
+        let (_, count) = self.number()?;
+        let digits = &self.bytes[self.position - count..self.position];
+        let value = digits
+            .iter()
+            .chain(b"00".iter())
+            .take(3)
+            .fold(0.0, |n, d| n * 10.0 + (d - b'0') as f64);
+        Some(value)
+    }
+
+    fn at_end(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-date-time-string-format
+/// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, optionally followed by `THH:mm`, seconds, a fraction and
+/// `Z` or `±HH:mm`. Years may also be six digits with a sign. `None` when `string` isn't in
+/// this format at all, `Some(NaN)` when it is but names an impossible date.
+fn parse_iso(string: &str) -> Option<Parsed> {
+    let mut cursor = Cursor {
+        bytes: string.as_bytes(),
+        position: 0,
+    };
+    let year = match cursor.eat_any(b"+-") {
+        Some(sign) => {
+            let year = cursor.digits(6)?;
+            // -000000 would be a second way to write year 0
+            if sign == b'-' && year == 0.0 {
+                return None;
+            }
+            if sign == b'-' { -year } else { year }
+        }
+        None => cursor.digits(4)?,
+    };
+    let mut month = 1.0;
+    let mut day = 1.0;
+    if cursor.eat(b'-') {
+        month = cursor.digits(2)?;
+        if cursor.eat(b'-') {
+            day = cursor.digits(2)?;
+        }
+    }
+    let (mut hours, mut minutes, mut seconds, mut milliseconds) = (0.0, 0.0, 0.0, 0.0);
+    let mut offset = Some(0.0);
+    if cursor.eat_any(b"Tt").is_some() {
+        hours = cursor.digits(2)?;
+        if !cursor.eat(b':') {
+            return None;
+        }
+        minutes = cursor.digits(2)?;
+        if cursor.eat(b':') {
+            seconds = cursor.digits(2)?;
+            if cursor.eat(b'.') {
+                milliseconds = cursor.milliseconds()?;
+            }
+        }
+        // date-time forms without an offset are local time
+        offset = match cursor.eat_any(b"Zz+-") {
+            None => None,
+            Some(b'Z' | b'z') => Some(0.0),
+            Some(sign) => {
+                let offset_hours = cursor.digits(2)?;
+                cursor.eat(b':');
+                let offset_minutes = cursor.digits(2)?;
+                let offset = (offset_hours * 60.0 + offset_minutes) * MS_PER_MINUTE;
+                Some(if sign == b'-' { -offset } else { offset })
+            }
+        };
+    }
+    if !cursor.at_end() {
+        return None;
+    }
+    let valid = (1.0..=12.0).contains(&month)
+        && (1.0..=31.0).contains(&day)
+        && minutes < 60.0
+        && seconds < 60.0
+        && (hours < 24.0 || (hours == 24.0 && minutes + seconds + milliseconds == 0.0));
+    let local = match valid {
+        true => make_date(
+            make_day(year, month - 1.0, day),
+            make_time(hours, minutes, seconds, milliseconds),
+        ),
+        false => f64::NAN,
+    };
+    Some(Parsed { local, offset })
+}
+
+/// Any other string browsers make sense of: words for months and weekdays, dates as
+/// `M/D/Y`, `Y/M/D` or day and year around a month name, `h:mm:ss` times with AM or PM, and
+/// `GMT`, `UTC` or `+hhmm` offsets. Text in parentheses is a comment.
+fn parse_legacy(string: &str) -> Option<Parsed> {
+    let mut cursor = Cursor {
+        bytes: string.as_bytes(),
+        position: 0,
+    };
+    let mut numbers: Vec<(f64, usize)> = vec![];
+    let mut month_name = None;
+    let mut time: Option<(f64, f64, f64, f64)> = None;
+    let mut afternoon = None;
+    let mut offset = None;
+    while let Some(byte) = cursor.peek() {
+        match byte {
+            b'(' => {
+                let mut depth = 0;
+                while let Some(byte) = cursor.peek() {
+                    cursor.position += 1;
+                    match byte {
+                        b'(' => depth += 1,
+                        b')' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            b'+' | b'-' if time.is_some() || offset.is_some() => {
+                cursor.position += 1;
+                let (number, count) = cursor.number()?;
+                let (hours, minutes) = match count {
+                    1 | 2 if cursor.eat(b':') => (number, cursor.number()?.0),
+                    1 | 2 => (number, 0.0),
+                    4 => ((number / 100.0).floor(), number % 100.0),
+                    _ => return None,
+                };
+                let minutes = hours * 60.0 + minutes;
+                offset = Some(if byte == b'-' { -minutes } else { minutes } * MS_PER_MINUTE);
+            }
+            b'0'..=b'9' => {
+                let (number, count) = cursor.number()?;
+                if cursor.eat(b':') {
+                    if time.is_some() {
+                        return None;
+                    }
+                    let minutes = cursor.number()?.0;
+                    let mut seconds = 0.0;
+                    let mut milliseconds = 0.0;
+                    if cursor.eat(b':') {
+                        seconds = cursor.number()?.0;
+                        if cursor.eat(b'.') {
+                            milliseconds = cursor.milliseconds()?;
+                        }
+                    }
+                    time = Some((number, minutes, seconds, milliseconds));
+                } else {
+                    numbers.push((number, count));
+                }
+            }
+            byte if byte.is_ascii_alphabetic() => {
+                let start = cursor.position;
+                while cursor.peek().is_some_and(|byte| byte.is_ascii_alphabetic()) {
+                    cursor.position += 1;
+                }
+                let word = string[start..cursor.position].to_ascii_lowercase();
+                let is_prefix_of = |name: &&str| word.len() >= 3 && name.starts_with(&word);
+                match word.as_str() {
+                    "am" | "pm" => afternoon = Some(word == "pm"),
+                    "utc" | "gmt" | "ut" | "z" => offset = Some(0.0),
+                    _ => {
+                        if let Some(month) = MONTH_NAMES.iter().position(is_prefix_of) {
+                            if month_name.is_some() {
+                                return None;
+                            }
+                            month_name = Some(month as f64);
+                        } else if !WEEK_DAY_NAMES.iter().any(is_prefix_of) {
+                            return None;
+                        }
+                    }
+                }
+            }
+            b' ' | b',' | b'/' | b'-' | b'.' | b'\t' | b'\n' | b'\r' => cursor.position += 1,
+            _ => return None,
+        }
+    }
+
+    let is_year = |(number, count): (f64, usize)| count >= 3 || number > 31.0;
+    let (year, month, day) = match (month_name, numbers.as_slice()) {
+        (Some(month), [first, second]) if is_year(*first) => (*first, month, second.0),
+        (Some(month), [first, second]) => (*second, month, first.0),
+        (Some(month), [only]) if is_year(*only) => (*only, month, 1.0),
+        // browsers settle on 2001 when the year is missing
+        (Some(month), [only]) => ((2001.0, 4), month, only.0),
+        (None, [first, second, third]) if is_year(*first) => (*first, second.0 - 1.0, third.0),
+        (None, [first, second, third]) => (*third, first.0 - 1.0, second.0),
+        _ => return None,
+    };
+    // two-digit years are in 1950 to 2049
+    let year = match year {
+        (year, count) if count <= 2 && year < 50.0 => year + 2000.0,
+        (year, count) if count <= 2 => year + 1900.0,
+        (year, _) => year,
+    };
+    let (mut hours, minutes, seconds, milliseconds) = time.unwrap_or_default();
+    if let Some(afternoon) = afternoon {
+        if hours > 12.0 {
+            return None;
+        }
+        hours = hours % 12.0 + if afternoon { 12.0 } else { 0.0 };
+    }
+    let valid = (0.0..12.0).contains(&month)
+        && (1.0..=31.0).contains(&day)
+        && hours <= 24.0
+        && minutes < 60.0
+        && seconds < 60.0;
+    if !valid {
+        return None;
+    }
+    let local = make_date(
+        make_day(year, month, day),
+        make_time(hours, minutes, seconds, milliseconds),
+    );
+    Some(Parsed { local, offset })
+}
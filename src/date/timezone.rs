@@ -0,0 +1,555 @@
+//! The time zones `Date` converts between UTC and local time with. Besides UTC and fixed
+//! offsets, rules come from TZif files, the format of `/etc/localtime` and the zoneinfo
+//! database, and from POSIX `TZ` strings, which TZif files use for the years after their last
+//! transition.
+
+use std::path::Path;
+
+use crate::date::{MS_PER_DAY, civil_from_days, days_from_civil};
+
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
+/// The time zone `Date` converts between UTC and local time with.
+#[derive(Clone, Debug)]
+pub enum TimeZone {
+    Utc,
+    /// A constant offset from UTC in minutes, positive east of Greenwich, e.g. 330 for
+    /// UTC+05:30.
+    Fixed(i32),
+    /// Offsets that change over time, daylight saving time for instance.
+    Rules(Box<ZoneRules>),
+}
+
+impl TimeZone {
+    /// The local time zone of the system: the one `TZ` names, `/etc/localtime` when it is
+    /// unset, and UTC when neither can be read.
+    pub fn system() -> Self {
+        match std::env::var("TZ") {
+            Ok(tz) if !tz.is_empty() => {
+                let name = tz.strip_prefix(':').unwrap_or(&tz);
+                Self::named(name)
+                    .or_else(|| Self::from_posix(name))
+                    .unwrap_or(TimeZone::Utc)
+            }
+            _ => Self::from_file(Path::new("/etc/localtime")).unwrap_or(TimeZone::Utc),
+        }
+    }
+
+    /// A zone of the zoneinfo database by name, e.g. `Europe/Berlin`, or by absolute path.
+    pub fn named(name: &str) -> Option<Self> {
+        if name.split('/').any(|part| part == "..") {
+            return None;
+        }
+        match name.starts_with('/') {
+            true => Self::from_file(Path::new(name)),
+            false => Self::from_file(&Path::new(ZONEINFO_DIR).join(name)),
+        }
+    }
+
+    fn from_file(path: &Path) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        Self::from_tzif(&bytes)
+    }
+
+    /// The zone a TZif file describes, see RFC 8536.
+    pub fn from_tzif(bytes: &[u8]) -> Option<Self> {
+        let rules = ZoneRules::from_tzif(bytes)?;
+        Some(TimeZone::Rules(Box::new(rules)))
+    }
+
+    /// The zone a POSIX `TZ` string describes, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`.
+    pub fn from_posix(rule: &str) -> Option<Self> {
+        let rule = PosixRule::parse(rule)?;
+        Some(TimeZone::Rules(Box::new(ZoneRules {
+            transitions: vec![],
+            types: vec![rule.standard.clone()],
+            rule: Some(rule),
+        })))
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-getnamedtimezoneoffsetnanoseconds
+    /// The offset from UTC in milliseconds at the UTC time `time`.
+    pub fn offset(&self, time: f64) -> f64 {
+        match self {
+            TimeZone::Utc => 0.0,
+            TimeZone::Fixed(minutes) => *minutes as f64 * 60_000.0,
+            TimeZone::Rules(rules) => rules.local_type(time).offset as f64 * 1000.0,
+        }
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-getnamedtimezoneepochnanoseconds
+    /// The offset to subtract from the local time `local` to get UTC. A local time that occurs
+    /// twice when clocks go back is read as the earlier of the two, one that is skipped when
+    /// they go forward with the offset from before the change.
+    pub fn offset_from_local(&self, local: f64) -> f64 {
+        if !matches!(self, TimeZone::Rules(_)) {
+            return self.offset(local);
+        }
+        // offsets change at most once within a day, so these are the only candidates
+        let before = self.offset(local - MS_PER_DAY);
+        let after = self.offset(local + MS_PER_DAY);
+        [before, after]
+            .into_iter()
+            .filter(|offset| self.offset(local - offset) == *offset)
+            .max_by(|a, b| a.total_cmp(b))
+            .unwrap_or(before)
+    }
+
+    /// How `Date.prototype.toString` names the zone at the UTC time `time`.
+    pub fn name(&self, time: f64) -> String {
+        match self {
+            TimeZone::Utc => "Coordinated Universal Time".to_string(),
+            TimeZone::Fixed(minutes) => format_gmt_offset(*minutes as f64),
+            TimeZone::Rules(rules) => {
+                let local_type = rules.local_type(time);
+                match local_type.abbreviation.as_str() {
+                    "UTC" => "Coordinated Universal Time".to_string(),
+                    abbreviation if abbreviation.starts_with(['+', '-']) => {
+                        format_gmt_offset(local_type.offset as f64 / 60.0)
+                    }
+                    abbreviation => abbreviation.to_string(),
+                }
+            }
+        }
+    }
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+/// `GMT+05:30`, the name of a zone that only has an offset.
+fn format_gmt_offset(minutes: f64) -> String {
+    let sign = if minutes < 0.0 { '-' } else { '+' };
+    let minutes = minutes.abs() as i64;
+    format!("GMT{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct LocalTimeType {
+    /// Seconds east of UTC.
+    offset: i64,
+    abbreviation: String,
+}
+
+/// Historical transitions between local time types, and the rule that takes over after the
+/// last of them.
+#[derive(Clone, Debug)]
+pub struct ZoneRules {
+    /// When each transition happens in seconds since the epoch, and the index of the type in
+    /// effect from then on.
+    transitions: Vec<(i64, usize)>,
+    /// The first is also the one in effect before the first transition.
+    types: Vec<LocalTimeType>,
+    rule: Option<PosixRule>,
+}
+
+impl ZoneRules {
+    fn local_type(&self, time: f64) -> LocalTimeType {
+        let seconds = (time / 1000.0).floor() as i64;
+        let after_last = self
+            .transitions
+            .last()
+            .is_none_or(|(last, _)| seconds >= *last);
+        if after_last && let Some(rule) = &self.rule {
+            return rule.local_type(seconds).clone();
+        }
+        let index = self
+            .transitions
+            .partition_point(|(transition, _)| *transition <= seconds);
+        match index {
+            0 => self.types[0].clone(),
+            index => self.types[self.transitions[index - 1].1].clone(),
+        }
+    }
+
+    fn from_tzif(bytes: &[u8]) -> Option<Self> {
+        let mut reader = TzifReader { bytes, position: 0 };
+        let header = reader.header()?;
+        if header.version < b'2' {
+            return reader.data(&header, 4);
+        }
+        // version 2 files repeat everything with 64-bit times after the 32-bit data
+        reader.skip(header.data_length(4))?;
+        let header = reader.header()?;
+        let mut rules = reader.data(&header, 8)?;
+        rules.rule = reader.footer().and_then(|footer| PosixRule::parse(&footer));
+        Some(rules)
+    }
+}
+
+struct TzifHeader {
+    version: u8,
+    utc_indicators: usize,
+    standard_indicators: usize,
+    leap_seconds: usize,
+    transitions: usize,
+    types: usize,
+    abbreviation_bytes: usize,
+}
+
+impl TzifHeader {
+    fn data_length(&self, time_size: usize) -> usize {
+        self.transitions * (time_size + 1)
+            + self.types * 6
+            + self.abbreviation_bytes
+            + self.leap_seconds * (time_size + 4)
+            + self.standard_indicators
+            + self.utc_indicators
+    }
+}
+
+struct TzifReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl TzifReader<'_> {
+    fn take(&mut self, length: usize) -> Option<&[u8]> {
+        let bytes = self.bytes.get(self.position..self.position + length)?;
+        self.position += length;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> Option<()> {
+        self.take(length).map(|_| ())
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn read_time(&mut self, size: usize) -> Option<i64> {
+        match size {
+            4 => Some(i32::from_be_bytes(self.take(4)?.try_into().ok()?) as i64),
+            _ => Some(i64::from_be_bytes(self.take(8)?.try_into().ok()?)),
+        }
+    }
+
+    fn header(&mut self) -> Option<TzifHeader> {
+        if self.take(4)? != b"TZif" {
+            return None;
+        }
+        let version = self.take(1)?[0];
+        self.skip(15)?;
+        let mut count = || self.read_u32().map(|count| count as usize);
+        Some(TzifHeader {
+            version,
+            utc_indicators: count()?,
+            standard_indicators: count()?,
+            leap_seconds: count()?,
+            transitions: count()?,
+            types: count()?,
+            abbreviation_bytes: count()?,
+        })
+    }
+
+    fn data(&mut self, header: &TzifHeader, time_size: usize) -> Option<ZoneRules> {
+        let times = (0..header.transitions)
+            .map(|_| self.read_time(time_size))
+            .collect::<Option<Vec<_>>>()?;
+        let indices = self.take(header.transitions)?.to_vec();
+        let mut types = vec![];
+        for _ in 0..header.types {
+            let offset = self.read_u32()? as i32 as i64;
+            let entry = self.take(2)?;
+            types.push((offset, entry[1] as usize));
+        }
+        let abbreviations = self.take(header.abbreviation_bytes)?.to_vec();
+        self.skip(
+            header.leap_seconds * (time_size + 4)
+                + header.standard_indicators
+                + header.utc_indicators,
+        )?;
+        let types: Vec<LocalTimeType> = types
+            .into_iter()
+            .map(|(offset, start)| {
+                let abbreviation = abbreviations.get(start..).unwrap_or_default();
+                let end = abbreviation
+                    .iter()
+                    .position(|byte| *byte == 0)
+                    .unwrap_or(abbreviation.len());
+                LocalTimeType {
+                    offset,
+                    abbreviation: String::from_utf8_lossy(&abbreviation[..end]).into_owned(),
+                }
+            })
+            .collect();
+        if types.is_empty() || indices.iter().any(|index| *index as usize >= types.len()) {
+            return None;
+        }
+        let transitions = times
+            .into_iter()
+            .zip(indices.into_iter().map(usize::from))
+            .collect();
+        Some(ZoneRules {
+            transitions,
+            types,
+            rule: None,
+        })
+    }
+
+    /// The POSIX `TZ` string between newlines at the end of a version 2 file.
+    fn footer(&mut self) -> Option<String> {
+        let rest = self.bytes.get(self.position..)?;
+        let rest = rest.strip_prefix(b"\n")?;
+        let end = rest.iter().position(|byte| *byte == b'\n')?;
+        let footer = std::str::from_utf8(&rest[..end]).ok()?;
+        (!footer.is_empty()).then(|| footer.to_string())
+    }
+}
+
+/// When daylight saving time starts or ends within a year.
+#[derive(Clone, Debug)]
+enum RuleDay {
+    /// `Jn`: day 1 to 365, February 29 is never counted.
+    JulianNoLeap(i64),
+    /// `n`: day 0 to 365, counting February 29 in leap years.
+    Julian(i64),
+    /// `Mm.w.d`: weekday `d` (0 is Sunday) of week `w` of month `m`, week 5 being the last.
+    MonthWeekDay { month: i64, week: i64, weekday: i64 },
+}
+
+impl RuleDay {
+    /// The day, counted from the epoch, this rule picks in `year`.
+    fn day_in(&self, year: i64) -> i64 {
+        let january_first = days_from_civil(year, 1, 1);
+        match self {
+            RuleDay::JulianNoLeap(day) => {
+                let leap_day = is_leap_year(year) && *day >= 60;
+                january_first + day - 1 + leap_day as i64
+            }
+            RuleDay::Julian(day) => january_first + day,
+            RuleDay::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, *month, 1);
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (weekday - first_weekday).rem_euclid(7) + (week - 1) * 7;
+                let next_month = match month {
+                    12 => days_from_civil(year + 1, 1, 1),
+                    month => days_from_civil(year, month + 1, 1),
+                };
+                while day >= next_month {
+                    day -= 7;
+                }
+                day
+            }
+        }
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+#[derive(Clone, Debug)]
+struct DaylightSaving {
+    local_type: LocalTimeType,
+    start: RuleDay,
+    /// Seconds after local midnight, in standard time.
+    start_time: i64,
+    end: RuleDay,
+    /// Seconds after local midnight, in daylight saving time.
+    end_time: i64,
+}
+
+/// A POSIX `TZ` string: standard time and possibly a yearly daylight saving time.
+#[derive(Clone, Debug)]
+struct PosixRule {
+    standard: LocalTimeType,
+    daylight: Option<DaylightSaving>,
+}
+
+impl PosixRule {
+    fn parse(source: &str) -> Option<Self> {
+        let mut parser = PosixParser {
+            chars: source.chars().collect(),
+            position: 0,
+        };
+        let rule = parser.rule()?;
+        parser.at_end().then_some(rule)
+    }
+
+    fn local_type(&self, seconds: i64) -> &LocalTimeType {
+        let Some(daylight) = &self.daylight else {
+            return &self.standard;
+        };
+        let local_day = (seconds + self.standard.offset).div_euclid(86_400);
+        let (year, _, _) = civil_from_days(local_day);
+        let start =
+            daylight.start.day_in(year) * 86_400 + daylight.start_time - self.standard.offset;
+        let end =
+            daylight.end.day_in(year) * 86_400 + daylight.end_time - daylight.local_type.offset;
+        let in_daylight = match start < end {
+            true => start <= seconds && seconds < end,
+            // the southern hemisphere, where daylight saving time spans the new year
+            false => !(end <= seconds && seconds < start),
+        };
+        match in_daylight {
+            true => &daylight.local_type,
+            false => &self.standard,
+        }
+    }
+}
+
+struct PosixParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl PosixParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let matches = self.peek() == Some(expected);
+        self.position += matches as usize;
+        matches
+    }
+
+    fn at_end(&self) -> bool {
+        self.position == self.chars.len()
+    }
+
+    fn rule(&mut self) -> Option<PosixRule> {
+        let name = self.name()?;
+        // POSIX offsets count west of Greenwich, the opposite of everything else
+        let offset = -self.time()?;
+        let standard = LocalTimeType {
+            offset,
+            abbreviation: name,
+        };
+        if self.at_end() {
+            return Some(PosixRule {
+                standard,
+                daylight: None,
+            });
+        }
+        let name = self.name()?;
+        let daylight_offset = match self.peek() {
+            Some(',') | None => offset + 3600,
+            Some(_) => -self.time()?,
+        };
+        let local_type = LocalTimeType {
+            offset: daylight_offset,
+            abbreviation: name,
+        };
+        // without dates, the rule of the United States applies
+        let (start, start_time, end, end_time) = match self.eat(',') {
+            true => {
+                let (start, start_time) = self.rule_day()?;
+                if !self.eat(',') {
+                    return None;
+                }
+                let (end, end_time) = self.rule_day()?;
+                (start, start_time, end, end_time)
+            }
+            false => (
+                RuleDay::MonthWeekDay {
+                    month: 3,
+                    week: 2,
+                    weekday: 0,
+                },
+                7200,
+                RuleDay::MonthWeekDay {
+                    month: 11,
+                    week: 1,
+                    weekday: 0,
+                },
+                7200,
+            ),
+        };
+        Some(PosixRule {
+            standard,
+            daylight: Some(DaylightSaving {
+                local_type,
+                start,
+                start_time,
+                end,
+                end_time,
+            }),
+        })
+    }
+
+    /// An abbreviation: three or more letters, or anything but `>` between `<` and `>`.
+    fn name(&mut self) -> Option<String> {
+        let mut name = String::new();
+        if self.eat('<') {
+            while let Some(c) = self.peek().filter(|c| *c != '>') {
+                name.push(c);
+                self.position += 1;
+            }
+            if !self.eat('>') {
+                return None;
+            }
+        } else {
+            while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+                name.push(c);
+                self.position += 1;
+            }
+        }
+        (name.len() >= 3).then_some(name)
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        digits.parse().ok()
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds. Hours go up to 167 for the transition times of RFC 8536.
+    fn time(&mut self) -> Option<i64> {
+        let sign = match self.peek() {
+            Some('-') => -1,
+            _ => 1,
+        };
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.position += 1;
+        }
+        let mut seconds = self.number()? * 3600;
+        if self.eat(':') {
+            seconds += self.number()? * 60;
+            if self.eat(':') {
+                seconds += self.number()?;
+            }
+        }
+        Some(sign * seconds)
+    }
+
+    fn rule_day(&mut self) -> Option<(RuleDay, i64)> {
+        let day = if self.eat('J') {
+            RuleDay::JulianNoLeap(self.number().filter(|day| (1..=365).contains(day))?)
+        } else if self.eat('M') {
+            let month = self.number().filter(|month| (1..=12).contains(month))?;
+            if !self.eat('.') {
+                return None;
+            }
+            let week = self.number().filter(|week| (1..=5).contains(week))?;
+            if !self.eat('.') {
+                return None;
+            }
+            let weekday = self.number().filter(|weekday| (0..=6).contains(weekday))?;
+            RuleDay::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            }
+        } else {
+            RuleDay::Julian(self.number().filter(|day| (0..=365).contains(day))?)
+        };
+        let time = match self.eat('/') {
+            true => self.time()?,
+            false => 7200,
+        };
+        Some((day, time))
+    }
+}
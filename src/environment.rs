@@ -1,23 +1,31 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use log::trace;
 use string_interner::{Symbol, symbol::SymbolU32};
 
-use crate::{global::get_string_from_pool, variable::VariableId};
+use crate::{
+    global::get_string_from_pool,
+    heap::{HeapId, Trace, Tracer},
+    variable::VariableId,
+};
 
 type StringId = SymbolU32;
 
 #[derive(Debug, Clone)]
 pub struct Environment {
     _is_expired: bool,
+    parent: Option<HeapId>,                 // enclosing lexical environment
     handles: HashMap<StringId, VariableId>, // stringID: variableID (maps string names to variable ids)
+    imports: HashSet<StringId>,             // names bound to another module's variable
 }
 
 impl Environment {
-    pub fn new() -> Self {
+    pub fn new(parent: Option<HeapId>) -> Self {
         Self {
             _is_expired: false,
+            parent,
             handles: HashMap::new(),
+            imports: HashSet::new(),
         }
     }
 
@@ -29,8 +37,8 @@ impl Environment {
         None
     }
 
-    pub fn has_variable(&self, string_id: &StringId) -> bool {
-        self.handles.contains_key(string_id)
+    pub fn get_parent(&self) -> Option<HeapId> {
+        self.parent
     }
 
     pub fn add_variable(&mut self, string_id: StringId, variable_id: VariableId) {
@@ -38,9 +46,37 @@ impl Environment {
         trace!("{:?}", self);
     }
 
+    /// Binds `string_id` to a variable owned by another module. Reads see its current value,
+    /// assignments are rejected.
+    pub fn add_import(&mut self, string_id: StringId, variable_id: VariableId) {
+        self.handles.insert(string_id, variable_id);
+        self.imports.insert(string_id);
+    }
+
+    pub fn is_import(&self, string_id: &StringId) -> bool {
+        self.imports.contains(string_id)
+    }
+
     pub fn _expire(&mut self) {
         self._is_expired = true;
     }
+
+    /// Roughly what the bindings take up beyond the environment itself.
+    pub fn estimated_size(&self) -> usize {
+        self.handles.len() * size_of::<(StringId, VariableId)>()
+            + self.imports.len() * size_of::<StringId>()
+    }
+}
+
+impl Trace for Environment {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(parent) = self.parent {
+            tracer.mark(parent);
+        }
+        for variable_id in self.handles.values() {
+            tracer.mark(*variable_id);
+        }
+    }
 }
 
 impl std::fmt::Display for Environment {
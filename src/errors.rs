@@ -1,4 +1,4 @@
-use crate::heap::HeapId;
+use crate::heap::{HeapId, Trace, Tracer};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ErrorKind {
@@ -6,6 +6,8 @@ pub enum ErrorKind {
     Break,
     Continue,
     Return(HeapId),
+    Throw(HeapId),
+    Yield(HeapId),
 }
 
 impl Default for ErrorKind {
@@ -20,6 +22,16 @@ pub struct JSError {
     pub message: String,
 }
 
+/// The value a `return`, `throw` or `yield` completion carries is kept on the heap.
+impl Trace for JSError {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self.kind {
+            ErrorKind::Return(id) | ErrorKind::Throw(id) | ErrorKind::Yield(id) => tracer.mark(id),
+            ErrorKind::Normal | ErrorKind::Break | ErrorKind::Continue => (),
+        }
+    }
+}
+
 impl JSError {
     pub fn new(message: &str) -> Self {
         Self {
@@ -35,6 +47,43 @@ impl JSError {
         }
     }
 
+    pub fn new_type_error(message: &str) -> Self {
+        Self {
+            kind: Default::default(),
+            message: format!("Uncaught TypeError: {message}"),
+        }
+    }
+
+    pub fn new_syntax_error(message: &str) -> Self {
+        Self {
+            kind: Default::default(),
+            message: format!("Uncaught SyntaxError: {message}"),
+        }
+    }
+
+    pub fn new_range_error(message: &str) -> Self {
+        Self {
+            kind: Default::default(),
+            message: format!("Uncaught RangeError: {message}"),
+        }
+    }
+
+    pub fn new_reference_error(name: &str) -> Self {
+        Self {
+            kind: Default::default(),
+            message: format!("Uncaught ReferenceError: {name} is not defined"),
+        }
+    }
+
+    pub fn new_uninitialized_error(name: &str) -> Self {
+        Self {
+            kind: Default::default(),
+            message: format!(
+                "Uncaught ReferenceError: Cannot access '{name}' before initialization"
+            ),
+        }
+    }
+
     pub fn new_const_type_error() -> Self {
         Self {
             kind: Default::default(),
@@ -63,6 +112,31 @@ impl JSError {
         }
     }
 
+    /// A `throw` of an arbitrary script value. The message is what gets reported if nothing
+    /// catches it.
+    pub fn new_throw(id: HeapId, message: &str) -> Self {
+        Self {
+            kind: ErrorKind::Throw(id),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn new_yield(id: HeapId) -> Self {
+        Self {
+            kind: ErrorKind::Yield(id),
+            message: String::new(),
+        }
+    }
+
+    pub fn is_yield(&self) -> bool {
+        matches!(self.kind, ErrorKind::Yield(_))
+    }
+
+    /// Exceptions are the completions a `catch` block can observe.
+    pub fn is_exception(&self) -> bool {
+        matches!(self.kind, ErrorKind::Normal | ErrorKind::Throw(_))
+    }
+
     pub fn new_not_found(kind: &str, id: usize) -> Self {
         Self {
             kind: ErrorKind::Normal,
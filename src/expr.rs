@@ -1,31 +1,67 @@
 use std::fmt;
 
-use log::debug;
 use string_interner::symbol::SymbolU32;
 
 use crate::{
     Interpreter,
+    builtins::{
+        create_array,
+        iterator::{
+            IteratorRecord, get_iterator, iter_result_done, iter_result_value, iterate_to_list,
+            iterator_close, iterator_next,
+        },
+        list_from_array_like,
+        regexp::regexp_create,
+    },
+    constants::{PROTOTYPE_NAME, THIS_NAME},
     errors::JSError,
     global::{get_or_intern_string, get_string_from_pool},
+    modules::dynamic_import,
+    resume::{OperandCursor, ResumeAction, ResumeRecord},
     stmt::Stmt,
     token::{Kind, Token},
     utils::get_function_params,
     values::{
-        JSObject, JSResult, JSValue, ObjectKind, get_object_property, get_object_property_mut,
+        BodyKind, FunctionKind, JSObject, JSResult, JSValue, ObjectKind, PropertyDescriptor,
+        PropertyKey, bigint, bitwise_not, define_own_property, get_property_value,
+        set_property_value,
     },
 };
-#[derive(Clone, Debug)]
-pub enum LogKind {
-    Log,
-    Error,
-}
-
 #[derive(Clone, Debug)]
 pub enum ObjectCallKind {
     Dot,
     Square,
 }
 
+#[derive(Clone, Debug)]
+pub enum PropertyKind {
+    Value,
+    Getter,
+    Setter,
+}
+
+/// One entry of an object literal or class body. Getters and setters carry their function in
+/// `value`.
+#[derive(Clone, Debug)]
+pub struct PropertyDefinition {
+    pub kind: PropertyKind,
+    pub key: Expr,
+    pub value: Expr,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClassMember {
+    pub is_static: bool,
+    pub definition: PropertyDefinition,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClassDefinition {
+    pub identifier: Option<SymbolU32>,
+    pub constructor: Option<Expr>,
+    pub members: Vec<ClassMember>,
+}
+
 #[derive(Clone, Debug)]
 pub enum Expr {
     Assignment {
@@ -43,6 +79,11 @@ pub enum Expr {
     Literal {
         value: JSValue,
     },
+    /// `/pattern/flags`, which makes a new RegExp object every time it is evaluated.
+    RegExpLiteral {
+        pattern: SymbolU32,
+        flags: SymbolU32,
+    },
     Unary {
         operator: Token,
         right: Box<Expr>,
@@ -67,10 +108,40 @@ pub enum Expr {
         identifier: Option<Box<Expr>>,
         arguments: Vec<Expr>,
         body: Box<Stmt>,
+        kind: FunctionKind,
+        body_kind: BodyKind,
+    },
+    This,
+    New {
+        callee: Box<Expr>,
+        arguments: Vec<Expr>,
+    },
+    ObjectLiteral {
+        properties: Vec<PropertyDefinition>,
+    },
+    ArrayLiteral {
+        elements: Vec<Expr>,
+    },
+    Class {
+        definition: Box<ClassDefinition>,
     },
-    // internal only
-    PrintExpr {
-        kind: LogKind,
+    Yield {
+        argument: Option<Box<Expr>>,
+        delegate: bool,
+    },
+    /// Suspends an async function until the operand settles. Runs on the same machinery as
+    /// `yield`, the async function's driver treats the yielded value as the awaited one.
+    Await {
+        argument: Box<Expr>,
+    },
+    /// `import(specifier)`. `referrer` is the key of the module it appears in, if any.
+    Import {
+        specifier: Box<Expr>,
+        referrer: Option<SymbolU32>,
+    },
+    /// `...expr`, only valid as a call argument or array element.
+    Spread {
+        expr: Box<Expr>,
     },
 }
 
@@ -79,6 +150,10 @@ impl Expr {
         Self::Literal { value }
     }
 
+    pub fn new_regexp_literal(pattern: SymbolU32, flags: SymbolU32) -> Self {
+        Self::RegExpLiteral { pattern, flags }
+    }
+
     pub fn new_grouping(expr: Expr) -> Self {
         Self::Grouping {
             expr: Box::new(expr),
@@ -139,35 +214,113 @@ impl Expr {
         identifier: Option<Box<Expr>>,
         arguments: Vec<Expr>,
         body: Stmt,
+        kind: FunctionKind,
+        body_kind: BodyKind,
     ) -> Self {
         Self::FunctionDecl {
             identifier,
             arguments,
             body: Box::new(body),
+            kind,
+            body_kind,
+        }
+    }
+
+    pub fn new_new(callee: Expr, arguments: Vec<Expr>) -> Self {
+        Self::New {
+            callee: Box::new(callee),
+            arguments,
+        }
+    }
+
+    pub fn new_object_literal(properties: Vec<PropertyDefinition>) -> Self {
+        Self::ObjectLiteral { properties }
+    }
+
+    pub fn new_array_literal(elements: Vec<Expr>) -> Self {
+        Self::ArrayLiteral { elements }
+    }
+
+    pub fn new_class(definition: ClassDefinition) -> Self {
+        Self::Class {
+            definition: Box::new(definition),
+        }
+    }
+
+    pub fn new_yield(argument: Option<Expr>, delegate: bool) -> Self {
+        Self::Yield {
+            argument: argument.map(Box::new),
+            delegate,
         }
     }
 
-    pub fn new_print_expr(kind: LogKind) -> Self {
-        Self::PrintExpr { kind }
+    pub fn new_await(argument: Expr) -> Self {
+        Self::Await {
+            argument: Box::new(argument),
+        }
+    }
+
+    pub fn new_import(specifier: Expr, referrer: Option<SymbolU32>) -> Self {
+        Self::Import {
+            specifier: Box::new(specifier),
+            referrer,
+        }
     }
 
+    pub fn new_spread(expr: Expr) -> Self {
+        Self::Spread {
+            expr: Box::new(expr),
+        }
+    }
+
+    /// The result is rooted until the enclosing statement completes, the caller may still be
+    /// holding on to it while its other operands run.
     pub fn evaluate(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
+        let value = self.evaluate_expression(interpreter)?;
+        interpreter.root_value(&value);
+        Ok(value)
+    }
+
+    fn evaluate_expression(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
         match self {
             Self::Literal { value } => Ok(value.clone()),
+            Self::RegExpLiteral { pattern, flags } => {
+                let pattern = get_string_from_pool(pattern).unwrap_or_default();
+                let flags = get_string_from_pool(flags).unwrap_or_default();
+                regexp_create(interpreter, &pattern, &flags)
+            }
             Self::Unary { operator, right } => {
-                let right = right.evaluate(interpreter)?;
+                // typeof is the one place an unresolvable reference isn't an error
+                let right = match (operator.get_kind(), &**right) {
+                    (Kind::Typeof, Expr::Identifier { string_index }) => interpreter
+                        .get_value_from_environment(*string_index)
+                        .cloned()
+                        .unwrap_or(JSValue::Undefined),
+                    _ => right.evaluate(interpreter)?,
+                };
                 match operator.get_kind() {
                     Kind::Bang => {
                         let val_as_bool = right.to_boolean();
                         let negated = !val_as_bool;
                         Ok(JSValue::new_boolean(negated))
                     }
-                    Kind::Minus => {
-                        let val_as_number = right.to_numeric(interpreter)?.get_number();
-                        Ok(JSValue::new_number(&-val_as_number))
-                    }
+                    Kind::Minus => match right.to_numeric(interpreter)? {
+                        JSValue::BigInt { data } => {
+                            Ok(JSValue::new_big_int(bigint::unary_minus(&data)))
+                        }
+                        value => Ok(JSValue::new_number(&-value.get_number())),
+                    },
+                    Kind::BitwiseNot => match right.to_numeric(interpreter)? {
+                        JSValue::BigInt { data } => {
+                            Ok(JSValue::new_big_int(bigint::bitwise_not(&data)))
+                        }
+                        value => {
+                            let result = bitwise_not(value.get_number(), interpreter);
+                            Ok(JSValue::new_number(&(result as f64)))
+                        }
+                    },
                     Kind::Plus => {
-                        let val_as_number = right.to_numeric(interpreter)?.get_number();
+                        let val_as_number = right.to_number(interpreter)?.get_number();
                         Ok(JSValue::new_number(&val_as_number))
                     }
                     Kind::Void => Ok(JSValue::new_undefined()),
@@ -182,7 +335,7 @@ impl Expr {
                                 description: _,
                             } => "symbol",
                             JSValue::Number { data: _ } => "number",
-                            JSValue::BigInt => "bigint",
+                            JSValue::BigInt { .. } => "bigint",
                             JSValue::Object { object_id, kind: _ } => {
                                 let obj = interpreter.get_object_mut(object_id)?;
                                 match obj.is_function() {
@@ -208,11 +361,15 @@ impl Expr {
                 left,
                 right,
             } => {
-                let left = left.evaluate(interpreter)?;
-                let right = right.evaluate(interpreter)?;
+                let mut cursor = OperandCursor::new(interpreter);
+                let left = cursor.evaluate(left, interpreter)?;
+                let right = cursor.evaluate(right, interpreter)?;
                 if operator.is_equality_operator() {
                     return left.compute_equality(operator, &right, interpreter);
                 }
+                if *operator == Kind::Instanceof {
+                    return interpreter.instance_of(&left, &right);
+                }
                 if operator.is_binary_operator() {
                     return left.apply_string_or_numeric_binary_operator(
                         operator,
@@ -230,158 +387,540 @@ impl Expr {
             }
             Expr::Grouping { expr } => Ok(expr.evaluate(interpreter)?),
             Expr::Identifier { string_index } => {
-                let exists = interpreter.get_value_from_environment(*string_index);
-                match exists {
-                    Ok(val) => Ok(val.clone()),
-                    Err(_) => Ok(JSValue::new_string(string_index)),
+                match interpreter
+                    .get_value_from_environment(*string_index)
+                    .cloned()
+                {
+                    Ok(val) => Ok(val),
+                    // declared, but its declaration hasn't run yet
+                    Err(e) if interpreter.resolve_variable_id(*string_index)?.is_some() => Err(e),
+                    Err(_) => {
+                        let name = get_string_from_pool(string_index).unwrap_or_default();
+                        Err(JSError::new_reference_error(&name))
+                    }
                 }
             }
             Expr::Assignment { identifier, right } => {
-                let rhs = right.evaluate(interpreter)?;
+                let mut cursor = OperandCursor::new(interpreter);
+                let rhs = cursor.evaluate(right, interpreter)?;
                 match &**identifier {
                     Expr::ObjectCall {
                         kind: _,
                         object,
                         accessor,
                     } => {
-                        let object = object.evaluate(interpreter)?;
-                        let accessor = accessor.evaluate(interpreter)?;
-                        let key = accessor.to_string(interpreter)?;
-                        let property_res = get_object_property_mut(interpreter, &object, key);
-                        match property_res {
-                            Ok(prop) => {
-                                prop.set_value(rhs.clone());
-                                return Ok(rhs);
+                        let object = cursor.evaluate(object, interpreter)?;
+                        let key = cursor
+                            .evaluate(accessor, interpreter)?
+                            .to_property_key(interpreter)?;
+                        if object.is_null() || object.is_undefined() {
+                            let base = debug_name(&object);
+                            return Err(JSError::new_type_error(&format!(
+                                "Cannot set properties of {base} (setting '{key}')"
+                            )));
+                        }
+                        // writes to primitives and rejected writes are silently ignored in sloppy mode
+                        if object.is_object() {
+                            set_property_value(interpreter, &object, key, rhs.clone())?;
+                        }
+                        Ok(rhs)
+                    }
+                    Expr::Identifier { string_index } => {
+                        if interpreter.is_import_binding(*string_index)? {
+                            return Err(JSError::new_const_type_error());
+                        }
+                        match interpreter.get_variable_from_current_environment(*string_index) {
+                            Ok(var) if !var.is_initialized() => {
+                                let name = get_string_from_pool(string_index).unwrap_or_default();
+                                return Err(JSError::new_uninitialized_error(&name));
+                            }
+                            Ok(var) => {
+                                var.update_value(rhs.clone())?;
                             }
                             Err(_) => {
-                                // new property
-                                let object = object.to_object_mut(interpreter)?;
-                                object.add_property(key, rhs.clone());
-                                return Ok(rhs);
+                                // sloppy mode assignments to undeclared names create a global
+                                let global = interpreter.get_global_object()?;
+                                set_property_value(
+                                    interpreter,
+                                    &global,
+                                    *string_index,
+                                    rhs.clone(),
+                                )?;
                             }
                         }
+                        Ok(rhs)
                     }
-                    _ => (),
-                }
-                let ident_index = if let Expr::Identifier { string_index } = **identifier {
-                    string_index
-                } else {
-                    return Err(JSError::new("Invalid left-hand side in assignment"));
-                };
-                let exists = interpreter.get_variable_from_current_environment(ident_index);
-                match exists {
-                    Ok(var) => {
-                        if var.is_mutable() {
-                            var.update_value(rhs.clone())?;
-                            return Ok(rhs);
-                        } else {
-                            return Err(JSError::new(
-                                "Syntax error: Cannot assign to constant variable",
-                            ));
-                        }
-                    }
-                    Err(_) => Ok(JSValue::new_string(&ident_index)),
+                    _ => Err(JSError::new(
+                        "Uncaught SyntaxError: Invalid left-hand side in assignment",
+                    )),
                 }
             }
             Expr::FunctionCall {
                 identifier,
                 arguments,
             } => {
-                let value = identifier.evaluate(interpreter)?;
-                match value {
-                    JSValue::String { data: ident_index } => {
-                        let value = interpreter.get_value_from_environment(ident_index)?.clone();
-                        let object = value.get_object(interpreter)?.clone();
-                        let result = object.call(arguments, Some(&ident_index), interpreter)?;
-                        Ok(result)
-                    }
-                    JSValue::Object { object_id, kind } => {
-                        if let ObjectKind::Function = kind {
-                            let obj = interpreter.get_object(object_id)?.clone();
-                            let result = obj.call(arguments, None, interpreter)?;
-                            return Ok(result);
-                        }
-                        panic!("Attempting to call an ordinary object")
+                let mut cursor = OperandCursor::new(interpreter);
+                let (callee, this) = match &**identifier {
+                    Expr::ObjectCall {
+                        kind: _,
+                        object,
+                        accessor,
+                    } => {
+                        let object = cursor.evaluate(object, interpreter)?;
+                        let key = cursor
+                            .evaluate(accessor, interpreter)?
+                            .to_property_key(interpreter)?;
+                        let callee = cursor.compute(interpreter, |interpreter| {
+                            get_member(interpreter, &object, key)
+                        })?;
+                        (callee, object)
                     }
-                    _ => panic!("Attempting to call something that should not be called"),
+                    expr => (cursor.evaluate(expr, interpreter)?, JSValue::Undefined),
+                };
+                let arguments = evaluate_arguments(arguments, &mut cursor, interpreter)?;
+                if !interpreter.is_callable(&callee) {
+                    return Err(JSError::new_function_type_error(&callee_name(identifier)));
                 }
+                interpreter.call_function(&callee, &this, &arguments)
             }
             Expr::ObjectCall {
                 kind: _,
                 object,
                 accessor,
             } => {
-                let object = object.evaluate(interpreter)?;
-                let accessor = accessor.evaluate(interpreter)?.to_string(interpreter)?;
-                let property = get_object_property(interpreter, &object, accessor)?;
-                let value = property.get_value()?;
-                Ok(value.clone())
+                let mut cursor = OperandCursor::new(interpreter);
+                let object = cursor.evaluate(object, interpreter)?;
+                let key = cursor
+                    .evaluate(accessor, interpreter)?
+                    .to_property_key(interpreter)?;
+                get_member(interpreter, &object, key)
             }
             Expr::FunctionDecl {
                 identifier,
                 arguments,
                 body,
+                kind,
+                body_kind,
             } => {
-                let ident = if let Some(id) = identifier {
-                    id.evaluate(interpreter)?
-                } else {
-                    JSValue::Undefined
+                let name = match identifier.as_deref() {
+                    Some(Expr::Identifier { string_index }) => Some(*string_index),
+                    _ => None,
                 };
-                let ident_id = ident.to_string(interpreter)?;
-                let scope_id = interpreter.enter_scope(None);
-                let parameters = get_function_params(arguments, interpreter)?;
-                for param in &parameters {
-                    interpreter.new_variable(*param, true, JSValue::Undefined);
+                evaluate_function(name, None, arguments, body, kind, *body_kind, interpreter)
+            }
+            Expr::This => {
+                let this_id = get_or_intern_string(THIS_NAME);
+                match interpreter.resolve_variable_id(this_id)? {
+                    Some(var_id) => Ok(interpreter.get_var(var_id)?.get_value_cloned()),
+                    None => Ok(JSValue::Undefined),
                 }
-                interpreter.leave_scope();
-                let object_id =
-                    JSObject::new_function_object(body.clone(), parameters, scope_id, interpreter);
-
-                let object_val = JSValue::Object {
-                    object_id,
-                    kind: ObjectKind::Function,
+            }
+            Expr::New { callee, arguments } => {
+                let mut cursor = OperandCursor::new(interpreter);
+                let constructor = cursor.evaluate(callee, interpreter)?;
+                let arguments = evaluate_arguments(arguments, &mut cursor, interpreter)?;
+                let is_constructor = match &constructor {
+                    JSValue::Object { object_id, kind: _ } => {
+                        interpreter.get_object(*object_id)?.is_constructor()
+                    }
+                    _ => false,
                 };
-                interpreter.new_variable(ident_id, false, object_val.clone());
-
-                // function expressions return the function itself
-                Ok(object_val)
-            }
-            Expr::PrintExpr { kind } => {
-                let data = get_or_intern_string("data");
-                let variable = interpreter.get_variable_from_current_environment(data);
-                if let Ok(var) = variable {
-                    let value = var.get_value_cloned();
-                    let s = value.to_string(interpreter)?;
-                    let string_value = get_string_from_pool(&s).unwrap();
-                    add_message(&string_value, kind, interpreter);
+                if !is_constructor {
+                    let name = callee_name(callee);
+                    return Err(JSError::new_type_error(&format!(
+                        "{name} is not a constructor"
+                    )));
                 }
+                interpreter.construct(&constructor, &arguments)
+            }
+            Expr::ObjectLiteral { properties } => {
+                let mut cursor = OperandCursor::new(interpreter);
+                let object = cursor.compute(interpreter, |interpreter| {
+                    Ok(JSValue::new_object(vec![], interpreter))
+                })?;
+                let object_id = object.get_object_id()?;
+                for definition in properties {
+                    define_from_definition(object_id, definition, true, &mut cursor, interpreter)?;
+                }
+                Ok(object)
+            }
+            Expr::ArrayLiteral { elements } => {
+                let mut cursor = OperandCursor::new(interpreter);
+                let values = evaluate_arguments(elements, &mut cursor, interpreter)?;
+                Ok(create_array(values, interpreter))
+            }
+            Expr::Class { definition } => evaluate_class(definition, interpreter),
+            Expr::Yield { argument, delegate } => {
+                evaluate_yield(argument.as_deref(), *delegate, interpreter)
+            }
+            Expr::Await { argument } => evaluate_yield(Some(argument), false, interpreter),
+            Expr::Import {
+                specifier,
+                referrer,
+            } => {
+                let specifier = specifier.evaluate(interpreter)?;
+                dynamic_import(interpreter, &specifier, *referrer)
+            }
+            Expr::Spread { expr: _ } => {
+                Err(JSError::new("Uncaught SyntaxError: Unexpected token '...'"))
+            }
+        }
+    }
+}
 
-                Ok(JSValue::Undefined)
+/// Evaluates call arguments or array elements, expanding `...iterable` in place.
+fn evaluate_arguments(
+    arguments: &[Expr],
+    cursor: &mut OperandCursor,
+    interpreter: &mut Interpreter,
+) -> JSResult<Vec<JSValue>> {
+    let mut values = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        match argument {
+            Expr::Spread { expr } => {
+                // the spread values are kept as one array so a resumed generator reuses them
+                let spread = cursor.compute(interpreter, |interpreter| {
+                    let iterable = expr.evaluate(interpreter)?;
+                    let list = iterate_to_list(interpreter, &iterable)?;
+                    Ok(create_array(list, interpreter))
+                })?;
+                values.extend(list_from_array_like(interpreter, &spread)?);
             }
+            argument => values.push(cursor.evaluate(argument, interpreter)?),
         }
     }
+    Ok(values)
 }
 
-fn add_message(message: &str, kind: &LogKind, interpreter: &mut Interpreter) {
-    let quote = '\'';
-    let len = message.len();
-    let message = if message.starts_with(quote) && message.ends_with(quote) {
-        &message[1..len - 1]
-    } else {
-        &message
+// https://262.ecma-international.org/15.0/index.html#sec-yield
+/// Suspends the generator by unwinding with a yield completion. When the generator is resumed
+/// the record pushed here holds how it was resumed.
+fn evaluate_yield(
+    argument: Option<&Expr>,
+    delegate: bool,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    match interpreter.take_resume_record() {
+        Some(ResumeRecord::Yield(Some(action))) => {
+            return match action {
+                ResumeAction::Next(value) => Ok(value),
+                ResumeAction::Throw(value) => Err(interpreter.throw_value(value)),
+                ResumeAction::Return(value) => {
+                    let id = interpreter.add_value(value);
+                    Err(JSError::new_return(id))
+                }
+            };
+        }
+        Some(ResumeRecord::Delegate {
+            iterator,
+            action: Some(action),
+        }) => return delegate_yield(iterator, action, interpreter),
+        Some(record) => interpreter.save_resume_record(record),
+        None => (),
+    }
+    let mut cursor = OperandCursor::new(interpreter);
+    let value = match argument {
+        Some(argument) => cursor.evaluate(argument, interpreter)?,
+        None => JSValue::Undefined,
     };
-    let message = format!("{message}\n");
-    debug!("Adding {message} to {kind:?} output buffer");
-    match kind {
-        LogKind::Log => {
-            interpreter.output_buffer.push_str(&message);
+    if delegate {
+        let iterator = get_iterator(interpreter, &value)?;
+        return delegate_yield(
+            iterator,
+            ResumeAction::Next(JSValue::Undefined),
+            interpreter,
+        );
+    }
+    interpreter.save_resume_record(ResumeRecord::Yield(None));
+    let id = interpreter.add_value(value);
+    Err(JSError::new_yield(id))
+}
+
+/// One step of `yield*`: forwards how the generator was resumed to the inner iterator, then
+/// either finishes with the inner return value or suspends again with its next value.
+fn delegate_yield(
+    mut iterator: IteratorRecord,
+    action: ResumeAction,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let (result, is_return) = match action {
+        ResumeAction::Next(value) => (iterator_next(interpreter, &mut iterator, value)?, false),
+        ResumeAction::Throw(value) => {
+            let throw = iterator_method(&iterator, "throw", interpreter)?;
+            if !interpreter.is_callable(&throw) {
+                iterator_close(interpreter, &iterator)?;
+                return Err(JSError::new_type_error(
+                    "The iterator does not provide a 'throw' method",
+                ));
+            }
+            let inner = iterator_value(&iterator);
+            (interpreter.call_function(&throw, &inner, &[value])?, false)
         }
-        LogKind::Error => {
-            interpreter.error_buffer.push_str(&message);
+        ResumeAction::Return(value) => {
+            let method = iterator_method(&iterator, "return", interpreter)?;
+            if !interpreter.is_callable(&method) {
+                let id = interpreter.add_value(value);
+                return Err(JSError::new_return(id));
+            }
+            let inner = iterator_value(&iterator);
+            (interpreter.call_function(&method, &inner, &[value])?, true)
         }
+    };
+    if !result.is_object() {
+        return Err(JSError::new_type_error("Iterator result is not an object"));
+    }
+    if iter_result_done(interpreter, &result)? {
+        let value = iter_result_value(interpreter, &result)?;
+        if is_return {
+            let id = interpreter.add_value(value);
+            return Err(JSError::new_return(id));
+        }
+        return Ok(value);
+    }
+    let value = iter_result_value(interpreter, &result)?;
+    interpreter.save_resume_record(ResumeRecord::Delegate {
+        iterator,
+        action: None,
+    });
+    let id = interpreter.add_value(value);
+    Err(JSError::new_yield(id))
+}
+
+fn iterator_value(iterator: &IteratorRecord) -> JSValue {
+    match iterator {
+        IteratorRecord::Protocol { iterator, .. } => iterator.clone(),
+        IteratorRecord::ArrayLike { .. } => JSValue::Undefined,
     }
 }
 
+/// Looks up `throw` or `return` on the iterator. Index based iterators have neither.
+fn iterator_method(
+    iterator: &IteratorRecord,
+    name: &str,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    match iterator {
+        IteratorRecord::Protocol { iterator, .. } => {
+            get_property_value(interpreter, iterator, get_or_intern_string(name))
+        }
+        IteratorRecord::ArrayLike { .. } => Ok(JSValue::Undefined),
+    }
+}
+
+/// Property read with the TypeError real engines give for `undefined.x` and `null.x`.
+fn get_member(
+    interpreter: &mut Interpreter,
+    object: &JSValue,
+    key: PropertyKey,
+) -> JSResult<JSValue> {
+    if object.is_null() || object.is_undefined() {
+        let base = debug_name(object);
+        return Err(JSError::new_type_error(&format!(
+            "Cannot read properties of {base} (reading '{key}')"
+        )));
+    }
+    get_property_value(interpreter, object, key)
+}
+
+fn debug_name(value: &JSValue) -> &'static str {
+    match value {
+        JSValue::Null => "null",
+        _ => "undefined",
+    }
+}
+
+/// Best effort source-like name for error messages, e.g. `obj.method`.
+fn callee_name(expr: &Expr) -> String {
+    match expr {
+        Expr::Identifier { string_index } => get_string_from_pool(string_index).unwrap_or_default(),
+        Expr::This => THIS_NAME.to_string(),
+        Expr::ObjectCall {
+            kind: ObjectCallKind::Dot,
+            object,
+            accessor,
+        } => match &**accessor {
+            Expr::Literal {
+                value: JSValue::String { data },
+            } => {
+                let key = get_string_from_pool(data).unwrap_or_default();
+                format!("{}.{key}", callee_name(object))
+            }
+            _ => "expression".to_string(),
+        },
+        Expr::Grouping { expr } => callee_name(expr),
+        _ => "expression".to_string(),
+    }
+}
+
+/// Creates the function object for a declaration or expression. A named function expression
+/// gets its own scope holding the name so the body can refer to itself without leaking the
+/// binding into the enclosing scope.
+pub fn evaluate_function(
+    binding: Option<SymbolU32>,
+    name: Option<SymbolU32>,
+    arguments: &[Expr],
+    body: &Stmt,
+    kind: &FunctionKind,
+    body_kind: BodyKind,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let parameters = get_function_params(arguments)?;
+    let environment_id = match binding {
+        Some(_) => interpreter.new_scope(),
+        None => interpreter.get_current_environment_handle(),
+    };
+    let function_name = binding.or(name).unwrap_or(get_or_intern_string(""));
+    let object_id = JSObject::new_function_object(
+        Box::new(body.clone()),
+        parameters,
+        environment_id,
+        kind.clone(),
+        body_kind,
+        function_name,
+        interpreter,
+    );
+    let value = JSValue::Object {
+        object_id,
+        kind: ObjectKind::Function,
+    };
+    if let Some(binding) = binding {
+        interpreter.enter_scope(Some(environment_id));
+        interpreter.new_variable(binding, false, value.clone());
+        interpreter.leave_scope();
+    }
+    Ok(value)
+}
+
+/// Evaluates a property value, naming anonymous functions after their key.
+pub fn evaluate_named(
+    expr: &Expr,
+    name: SymbolU32,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    match expr {
+        Expr::FunctionDecl {
+            identifier: None,
+            arguments,
+            body,
+            kind,
+            body_kind,
+        } => evaluate_function(
+            None,
+            Some(name),
+            arguments,
+            body,
+            kind,
+            *body_kind,
+            interpreter,
+        ),
+        _ => expr.evaluate(interpreter),
+    }
+}
+
+/// Defines one object literal or class member. Class members are non-enumerable.
+fn define_from_definition(
+    object_id: usize,
+    definition: &PropertyDefinition,
+    enumerable: bool,
+    cursor: &mut OperandCursor,
+    interpreter: &mut Interpreter,
+) -> JSResult<()> {
+    let key = cursor
+        .evaluate(&definition.key, interpreter)?
+        .to_property_key(interpreter)?;
+    let key_string = key.function_name();
+    let descriptor = match definition.kind {
+        PropertyKind::Value => PropertyDescriptor {
+            value: Some(cursor.compute(interpreter, |interpreter| {
+                let name = get_or_intern_string(&key_string);
+                evaluate_named(&definition.value, name, interpreter)
+            })?),
+            writable: Some(true),
+            enumerable: Some(enumerable),
+            configurable: Some(true),
+            ..Default::default()
+        },
+        PropertyKind::Getter => {
+            let name = get_or_intern_string(&format!("get {key_string}"));
+            PropertyDescriptor {
+                get: Some(evaluate_named(&definition.value, name, interpreter)?),
+                enumerable: Some(enumerable),
+                configurable: Some(true),
+                ..Default::default()
+            }
+        }
+        PropertyKind::Setter => {
+            let name = get_or_intern_string(&format!("set {key_string}"));
+            PropertyDescriptor {
+                set: Some(evaluate_named(&definition.value, name, interpreter)?),
+                enumerable: Some(enumerable),
+                configurable: Some(true),
+                ..Default::default()
+            }
+        }
+    };
+    define_own_property(interpreter, object_id, key, &descriptor)?;
+    Ok(())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-runtime-semantics-classdefinitionevaluation
+pub fn evaluate_class(
+    definition: &ClassDefinition,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    // the class body sees its own name as an immutable binding
+    interpreter.enter_scope(None);
+    let result = evaluate_class_body(definition, interpreter);
+    interpreter.leave_scope();
+    result
+}
+
+fn evaluate_class_body(
+    definition: &ClassDefinition,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let empty_body = Stmt::new_block(vec![]);
+    let (arguments, body) = match &definition.constructor {
+        Some(Expr::FunctionDecl {
+            arguments, body, ..
+        }) => (arguments.as_slice(), &**body),
+        _ => (&[][..], &empty_body),
+    };
+    let constructor = evaluate_function(
+        None,
+        definition.identifier,
+        arguments,
+        body,
+        &FunctionKind::ClassConstructor,
+        BodyKind::Normal,
+        interpreter,
+    )?;
+    let constructor_id = constructor.get_object_id()?;
+
+    let prototype_key = get_or_intern_string(PROTOTYPE_NAME);
+    let prototype = get_property_value(interpreter, &constructor, prototype_key)?;
+    let prototype_id = prototype.get_object_id()?;
+    let read_only = PropertyDescriptor {
+        writable: Some(false),
+        ..Default::default()
+    };
+    define_own_property(interpreter, constructor_id, prototype_key, &read_only)?;
+
+    let mut cursor = OperandCursor::new(interpreter);
+    for member in &definition.members {
+        let target = match member.is_static {
+            true => constructor_id,
+            false => prototype_id,
+        };
+        define_from_definition(target, &member.definition, false, &mut cursor, interpreter)?;
+    }
+
+    if let Some(name) = definition.identifier {
+        interpreter.new_variable(name, false, constructor.clone());
+    }
+    Ok(constructor)
+}
+
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -401,6 +940,11 @@ impl fmt::Display for Expr {
             Expr::Literal { value } => {
                 write!(f, "Literal({:?})", value)
             }
+            Expr::RegExpLiteral { pattern, flags } => {
+                let pattern = get_string_from_pool(pattern).unwrap_or_default();
+                let flags = get_string_from_pool(flags).unwrap_or_default();
+                write!(f, "RegExp(/{pattern}/{flags})")
+            }
             Expr::Unary { operator, right } => {
                 write!(f, "Unary({:?} {})", operator, right)
             }
@@ -432,6 +976,8 @@ impl fmt::Display for Expr {
                 identifier,
                 arguments,
                 body,
+                kind: _,
+                body_kind,
             } => {
                 let args = arguments
                     .iter()
@@ -440,15 +986,54 @@ impl fmt::Display for Expr {
                     .join(", ");
                 write!(
                     f,
-                    "FunctionDecl {}({args}) {{ {body} }}",
+                    "{body_kind}FunctionDecl {}({args}) {{ {body} }}",
                     identifier
                         .clone()
                         .unwrap_or(Box::new(Expr::new_literal(JSValue::Undefined)))
                 )
             }
-            Expr::PrintExpr { kind } => {
-                write!(f, "Console.{kind:?}",)
+            Expr::This => write!(f, "This"),
+            Expr::New { callee, arguments } => {
+                let args = arguments
+                    .iter()
+                    .map(|arg| format!("{arg}"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "New {callee}({args})")
+            }
+            Expr::ObjectLiteral { properties } => {
+                let props = properties
+                    .iter()
+                    .map(|prop| format!("{:?} {}: {}", prop.kind, prop.key, prop.value))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "ObjectLiteral {{ {props} }}")
+            }
+            Expr::ArrayLiteral { elements } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| format!("{element}"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "ArrayLiteral [{elements}]")
+            }
+            Expr::Class { definition } => {
+                let name = definition
+                    .identifier
+                    .and_then(|id| get_string_from_pool(&id))
+                    .unwrap_or_default();
+                write!(f, "Class {name} ({} members)", definition.members.len())
+            }
+            Expr::Yield { argument, delegate } => {
+                let star = if *delegate { "*" } else { "" };
+                match argument {
+                    Some(argument) => write!(f, "Yield{star}({argument})"),
+                    None => write!(f, "Yield{star}"),
+                }
             }
+            Expr::Await { argument } => write!(f, "Await({argument})"),
+            Expr::Import { specifier, .. } => write!(f, "Import({specifier})"),
+            Expr::Spread { expr } => write!(f, "Spread({expr})"),
         }
     }
 }
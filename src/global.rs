@@ -1,20 +1,80 @@
-use std::sync::Mutex;
-use std::sync::OnceLock;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
 use string_interner::{StringInterner, backend::StringBackend, symbol::SymbolU32};
 
-static STRING_INTERNER: OnceLock<Mutex<StringInterner<StringBackend>>> = OnceLock::new();
+use crate::values::WellKnownSymbol;
 
-fn get_interner() -> &'static Mutex<StringInterner<StringBackend>> {
-    STRING_INTERNER.get_or_init(|| Mutex::new(StringInterner::new()))
+/// The state interpreters don't share: the strings they interned and the ids of the symbols
+/// they created. Strings and symbols are only meaningful in the isolate they came from, so
+/// every interpreter gets one of its own and enters it on the thread it runs on.
+#[derive(Clone)]
+pub(crate) struct Isolate(Arc<Mutex<IsolateState>>);
+
+struct IsolateState {
+    strings: StringInterner<StringBackend>,
+    /// Ids for new symbols, after the well-known ones.
+    next_symbol_id: usize,
+}
+
+thread_local! {
+    static CURRENT_ISOLATE: RefCell<Option<Isolate>> = const { RefCell::new(None) };
+}
+
+impl Isolate {
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Mutex::new(IsolateState {
+            strings: StringInterner::new(),
+            next_symbol_id: WellKnownSymbol::ALL.len(),
+        })))
+    }
+
+    /// Makes this the isolate strings and symbols come from on the current thread until the
+    /// guard is dropped. Entering nests, so the host can call back into another interpreter
+    /// from a host function. Only one thread runs an interpreter at a time, which keeps the
+    /// lock uncontended.
+    pub(crate) fn enter(&self) -> EnteredIsolate {
+        let previous = CURRENT_ISOLATE.with(|current| current.replace(Some(self.clone())));
+        EnteredIsolate { previous }
+    }
+}
+
+/// Restores the isolate that was current before `Isolate::enter`.
+pub(crate) struct EnteredIsolate {
+    previous: Option<Isolate>,
+}
+
+impl Drop for EnteredIsolate {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_ISOLATE.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+fn with_current_isolate<T>(f: impl FnOnce(&mut IsolateState) -> T) -> T {
+    CURRENT_ISOLATE.with(|current| {
+        let current = current.borrow();
+        let isolate = current
+            .as_ref()
+            .expect("no interpreter has been entered on this thread");
+        let mut state = isolate.0.lock().unwrap();
+        f(&mut state)
+    })
 }
 
 pub fn get_or_intern_string(s: &str) -> SymbolU32 {
-    get_interner().lock().unwrap().get_or_intern(s)
+    with_current_isolate(|isolate| isolate.strings.get_or_intern(s))
 }
 
 pub fn get_string_from_pool(sym: &SymbolU32) -> Option<String> {
-    let interner = get_interner().lock().unwrap();
-    interner.resolve(*sym).map(|s| s.to_owned())
+    with_current_isolate(|isolate| isolate.strings.resolve(*sym).map(|s| s.to_owned()))
+}
+
+pub fn next_symbol_id() -> usize {
+    with_current_isolate(|isolate| {
+        let id = isolate.next_symbol_id;
+        isolate.next_symbol_id += 1;
+        id
+    })
 }
 
 #[allow(dead_code)]
@@ -22,6 +82,7 @@ pub fn get_string_from_pool(sym: &SymbolU32) -> Option<String> {
 pub fn dump_pool() {
     use log::debug;
 
-    let interner = get_interner().lock().unwrap();
-    interner.iter().for_each(|item| debug!("{:?}", item));
+    with_current_isolate(|isolate| {
+        isolate.strings.iter().for_each(|item| debug!("{:?}", item));
+    });
 }
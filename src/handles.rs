@@ -0,0 +1,421 @@
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Weak},
+};
+
+use crate::{
+    Interpreter,
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    heap::{Trace, Tracer},
+    host::{self, HostFunction},
+    inspect::{self, InspectOptions},
+    values::{JSResult, JSValue, ObjectKind, PropertyKey, get_property_value, set_property_value},
+};
+
+/// What a handle refers to. Casting a handle with `Local::cast` checks the value against it.
+pub trait HandleKind: private::Sealed {}
+
+mod private {
+    use crate::values::JSValue;
+
+    pub trait Sealed {
+        fn accepts(value: &JSValue) -> bool;
+    }
+}
+
+/// Any value, primitives included.
+pub struct Value;
+
+/// Any object, functions and arrays included.
+pub struct Object;
+
+pub struct Function;
+
+pub struct Array;
+
+impl HandleKind for Value {}
+impl HandleKind for Object {}
+impl HandleKind for Function {}
+impl HandleKind for Array {}
+
+impl private::Sealed for Value {
+    fn accepts(_value: &JSValue) -> bool {
+        true
+    }
+}
+
+impl private::Sealed for Object {
+    fn accepts(value: &JSValue) -> bool {
+        value.is_object()
+    }
+}
+
+impl private::Sealed for Function {
+    fn accepts(value: &JSValue) -> bool {
+        matches!(
+            value,
+            JSValue::Object {
+                kind: ObjectKind::Function,
+                ..
+            }
+        )
+    }
+}
+
+impl private::Sealed for Array {
+    fn accepts(value: &JSValue) -> bool {
+        matches!(
+            value,
+            JSValue::Object {
+                kind: ObjectKind::Array,
+                ..
+            }
+        )
+    }
+}
+
+/// Opens handles into an interpreter's heap for the host. Everything a `Local` of the scope
+/// refers to stays alive until the scope is dropped, and the borrow of the interpreter keeps
+/// its locals from being used once it is.
+pub struct HandleScope<'i> {
+    pub(crate) interpreter: &'i mut Interpreter,
+    mark: usize,
+}
+
+impl<'i> HandleScope<'i> {
+    pub fn new(interpreter: &'i mut Interpreter) -> Self {
+        let mark = interpreter.heap.temporaries_mark();
+        Self { interpreter, mark }
+    }
+
+    pub fn global_object(&mut self) -> Local<'i, Object> {
+        let _isolate = self.interpreter.isolate.enter();
+        let global = self
+            .interpreter
+            .get_global_object()
+            .expect("the interpreter has been set up");
+        self.local(global)
+    }
+
+    /// Runs `source` as a script, returning the value of its last statement or what it threw.
+    /// The jobs and due timers it queued run before this returns, like with `interpret`.
+    pub fn eval(&mut self, source: &str) -> Result<Local<'i, Value>, Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        let completion = self.interpreter.evaluate_script(source);
+        self.interpreter.run_until_idle();
+        match completion {
+            Ok(value) => Ok(self.local(value)),
+            Err(error) => Err(self.thrown(error)),
+        }
+    }
+
+    /// The global variable or property `name`, declared with `let`, `const` or otherwise.
+    pub fn get_global(&mut self, name: &str) -> Result<Local<'i, Value>, Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        let value = self
+            .interpreter
+            .get_value_from_environment(get_or_intern_string(name))
+            .cloned();
+        self.completion(value)
+    }
+
+    /// Stores `value` in the global property `name`.
+    pub fn set_global<T: HandleKind>(
+        &mut self,
+        name: &str,
+        value: &Local<'i, T>,
+    ) -> Result<(), Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        let global = self.global_object();
+        self.set(&global, name, value)
+    }
+
+    pub fn undefined(&mut self) -> Local<'i, Value> {
+        self.local(JSValue::Undefined)
+    }
+
+    pub fn number(&mut self, value: f64) -> Local<'i, Value> {
+        self.local(JSValue::new_number(&value))
+    }
+
+    pub fn boolean(&mut self, value: bool) -> Local<'i, Value> {
+        self.local(JSValue::new_boolean(value))
+    }
+
+    pub fn string(&mut self, value: &str) -> Local<'i, Value> {
+        let _isolate = self.interpreter.isolate.enter();
+        self.local(JSValue::new_string(&get_or_intern_string(value)))
+    }
+
+    pub fn new_object(&mut self) -> Local<'i, Object> {
+        let _isolate = self.interpreter.isolate.enter();
+        let object = JSValue::new_object(vec![], self.interpreter);
+        self.local(object)
+    }
+
+    /// A function that runs `function` when called, see `Interpreter::register_fn`.
+    pub fn new_function(&mut self, name: &str, function: impl HostFunction) -> Local<'i, Function> {
+        let _isolate = self.interpreter.isolate.enter();
+        let function = host::new_host_function(self.interpreter, name, Arc::new(function));
+        self.local(function)
+    }
+
+    /// An error object with `message`, for host functions to throw.
+    pub fn error(&mut self, message: &str) -> Local<'i, Value> {
+        let _isolate = self.interpreter.isolate.enter();
+        self.thrown(JSError::new(message))
+    }
+
+    /// Reads the property `key` of `target`, running getters and proxy traps like a script
+    /// would.
+    pub fn get<T: HandleKind>(
+        &mut self,
+        target: &Local<'i, T>,
+        key: &str,
+    ) -> Result<Local<'i, Value>, Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        self.check(target);
+        let key = PropertyKey::from_name(key);
+        let value = get_property_value(self.interpreter, &target.value, key);
+        self.completion(value)
+    }
+
+    /// Writes the property `key` of `target`, running setters and proxy traps like a script
+    /// would.
+    pub fn set<T: HandleKind, U: HandleKind>(
+        &mut self,
+        target: &Local<'i, T>,
+        key: &str,
+        value: &Local<'i, U>,
+    ) -> Result<(), Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        self.check(target);
+        self.check(value);
+        let key = PropertyKey::from_name(key);
+        match set_property_value(self.interpreter, &target.value, key, value.value.clone()) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(self.thrown(error)),
+        }
+    }
+
+    pub fn length(&mut self, array: &Local<'i, Array>) -> Result<usize, Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        let length = self.get(array, "length")?;
+        Ok(length.as_number().unwrap_or(0.0) as usize)
+    }
+
+    pub fn get_index(
+        &mut self,
+        array: &Local<'i, Array>,
+        index: usize,
+    ) -> Result<Local<'i, Value>, Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        self.get(array, &index.to_string())
+    }
+
+    /// Calls `function` with `this` and `arguments`, returning what it returned or threw.
+    pub fn call<T: HandleKind>(
+        &mut self,
+        function: &Local<'i, Function>,
+        this: &Local<'i, T>,
+        arguments: &[Local<'i, Value>],
+    ) -> Result<Local<'i, Value>, Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        self.check(function);
+        self.check(this);
+        arguments.iter().for_each(|argument| self.check(argument));
+        let arguments: Vec<JSValue> = arguments.iter().map(|local| local.value.clone()).collect();
+        let result = self
+            .interpreter
+            .call_function(&function.value, &this.value, &arguments);
+        self.completion(result)
+    }
+
+    /// Converts `value` to a string the way `String(value)` does.
+    pub fn to_rust_string<T: HandleKind>(
+        &mut self,
+        value: &Local<'i, T>,
+    ) -> Result<String, Local<'i, Value>> {
+        let _isolate = self.interpreter.isolate.enter();
+        self.check(value);
+        match value.value.to_string(self.interpreter) {
+            Ok(string) => Ok(get_string_from_pool(&string).unwrap_or_default()),
+            Err(error) => Err(self.thrown(error)),
+        }
+    }
+
+    /// `value` the way Node's `util.inspect` shows it, e.g. for a REPL to echo results.
+    pub fn inspect<T: HandleKind>(
+        &mut self,
+        value: &Local<'i, T>,
+        options: &InspectOptions,
+    ) -> String {
+        let _isolate = self.interpreter.isolate.enter();
+        self.check(value);
+        inspect::inspect(self.interpreter, &value.value, options)
+    }
+
+    fn completion(
+        &mut self,
+        result: JSResult<JSValue>,
+    ) -> Result<Local<'i, Value>, Local<'i, Value>> {
+        match result {
+            Ok(value) => Ok(self.local(value)),
+            Err(error) => Err(self.thrown(error)),
+        }
+    }
+
+    /// The value `error` throws, an error object for the ones the interpreter raises itself.
+    fn thrown(&mut self, error: JSError) -> Local<'i, Value> {
+        let value = self
+            .interpreter
+            .error_value(&error)
+            .unwrap_or(JSValue::Undefined);
+        self.local(value)
+    }
+
+    pub(crate) fn local<T: HandleKind>(&mut self, value: JSValue) -> Local<'i, T> {
+        self.interpreter.root_value(&value);
+        Local {
+            value,
+            interpreter_id: self.interpreter.id,
+            kind: PhantomData,
+        }
+    }
+
+    /// Handles only work with the interpreter they came from, anything else would read
+    /// whatever happens to have the same id in another heap.
+    pub(crate) fn check<T>(&self, local: &Local<'i, T>) {
+        assert_eq!(
+            local.interpreter_id, self.interpreter.id,
+            "handle used with an interpreter it doesn't belong to"
+        );
+    }
+}
+
+impl Drop for HandleScope<'_> {
+    fn drop(&mut self) {
+        self.interpreter.heap.release_temporaries(self.mark);
+    }
+}
+
+/// A handle that lives as long as the `HandleScope` it came from, see `Global` for one that
+/// outlives it.
+pub struct Local<'i, T> {
+    pub(crate) value: JSValue,
+    interpreter_id: usize,
+    kind: PhantomData<(&'i (), T)>,
+}
+
+/// The name older versions of V8 used for `Local`.
+pub type Handle<'i, T> = Local<'i, T>;
+
+impl<T> std::fmt::Debug for Local<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Local").field(&self.value).finish()
+    }
+}
+
+impl<T> Clone for Local<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            interpreter_id: self.interpreter_id,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<'i, T: HandleKind> Local<'i, T> {
+    /// Opens `global` in `scope`, panicking when it belongs to another interpreter.
+    pub fn new(scope: &mut HandleScope<'i>, global: &Global<T>) -> Self {
+        assert_eq!(
+            global.slot.interpreter_id, scope.interpreter.id,
+            "handle used with an interpreter it doesn't belong to"
+        );
+        scope.local(global.slot.value.clone())
+    }
+
+    /// The same value as a handle of another kind, `None` when it isn't one, e.g. when
+    /// casting a `Local<Value>` holding a number to `Local<Function>`.
+    pub fn cast<U: HandleKind>(self) -> Option<Local<'i, U>> {
+        U::accepts(&self.value).then_some(Local {
+            value: self.value,
+            interpreter_id: self.interpreter_id,
+            kind: PhantomData,
+        })
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        self.value.is_undefined()
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self.value {
+            JSValue::Number { data } => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            JSValue::Boolean { data } => Some(data),
+            _ => None,
+        }
+    }
+}
+
+/// A handle that keeps its value alive until it and all its clones are dropped, independent
+/// of any `HandleScope`.
+pub struct Global<T> {
+    slot: Arc<GlobalSlot>,
+    kind: PhantomData<T>,
+}
+
+/// The name older versions of V8 used for `Global`.
+pub type Persistent<T> = Global<T>;
+
+/// What a `Global` and its clones share. The interpreter only holds on to it weakly and roots
+/// its value for as long as it's still around.
+pub(crate) struct GlobalSlot {
+    value: JSValue,
+    interpreter_id: usize,
+}
+
+impl Trace for Weak<GlobalSlot> {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(slot) = self.upgrade() {
+            slot.value.trace(tracer);
+        }
+    }
+}
+
+impl<T: HandleKind> Global<T> {
+    pub fn new(scope: &mut HandleScope<'_>, local: &Local<'_, T>) -> Self {
+        assert_eq!(
+            local.interpreter_id, scope.interpreter.id,
+            "handle used with an interpreter it doesn't belong to"
+        );
+        let slot = Arc::new(GlobalSlot {
+            value: local.value.clone(),
+            interpreter_id: local.interpreter_id,
+        });
+        let handles = &mut scope.interpreter.global_handles;
+        handles.retain(|handle| handle.strong_count() > 0);
+        handles.push(Arc::downgrade(&slot));
+        Self {
+            slot,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Global<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+            kind: PhantomData,
+        }
+    }
+}
@@ -1,17 +1,365 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    mem::size_of,
+    ops::Range,
+    time::{Duration, Instant},
+};
 
 use crate::{
+    builtins::collection::WeakKey,
     environment::Environment,
     errors::JSError,
-    values::{JSObject, JSResult, JSValue},
+    values::{JSObject, JSResult, JSValue, ObjectData},
     variable::Variable,
 };
 
 pub type HeapId = usize;
 
+/// How many entries the old space takes in between major collections before it has grown to
+/// hold more than that many live entries, see `Heap::collect`.
+pub const DEFAULT_GC_THRESHOLD: usize = 10_000;
+
+/// Anything holding heap ids. The collector calls `trace` to find out what an entry keeps alive.
+pub trait Trace {
+    fn trace(&self, tracer: &mut Tracer);
+}
+
+/// How many consecutive ids share a segment of an `IdMap`.
+const SEGMENT_SIZE: HeapId = 1024;
+
+/// A map keyed by heap id, split into segments of consecutive ids. Ids are handed out in
+/// order, so growing it only ever rehashes the segment the new id falls into, never the whole
+/// map the way a single `HashMap` the size of the heap would in the middle of a collection.
+#[derive(Debug)]
+struct IdMap<V> {
+    segments: HashMap<HeapId, HashMap<HeapId, V>>,
+    len: usize,
+}
+
+impl<V> Default for IdMap<V> {
+    fn default() -> Self {
+        Self {
+            segments: HashMap::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<V> IdMap<V> {
+    fn get(&self, id: &HeapId) -> Option<&V> {
+        self.segments.get(&(id / SEGMENT_SIZE))?.get(id)
+    }
+
+    fn get_mut(&mut self, id: &HeapId) -> Option<&mut V> {
+        self.segments.get_mut(&(id / SEGMENT_SIZE))?.get_mut(id)
+    }
+
+    fn contains_key(&self, id: &HeapId) -> bool {
+        self.get(id).is_some()
+    }
+
+    fn insert(&mut self, id: HeapId, value: V) -> Option<V> {
+        let previous = self
+            .segments
+            .entry(id / SEGMENT_SIZE)
+            .or_default()
+            .insert(id, value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    fn remove(&mut self, id: &HeapId) -> Option<V> {
+        let segment = id / SEGMENT_SIZE;
+        let entries = self.segments.get_mut(&segment)?;
+        let value = entries.remove(id)?;
+        if entries.is_empty() {
+            self.segments.remove(&segment);
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&HeapId, &V)> {
+        self.segments.values().flatten()
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &HeapId> {
+        self.segments.values().flat_map(HashMap::keys)
+    }
+
+    fn values(&self) -> impl Iterator<Item = &V> {
+        self.segments.values().flat_map(HashMap::values)
+    }
+
+    /// The segments currently in use, for going through the map a segment at a time.
+    fn segments(&self) -> Vec<HeapId> {
+        self.segments.keys().copied().collect()
+    }
+
+    /// Keeps the entries of one segment `keep` returns true for. Returns how many it checked.
+    fn retain_segment(&mut self, segment: HeapId, mut keep: impl FnMut(HeapId) -> bool) -> usize {
+        let Some(entries) = self.segments.get_mut(&segment) else {
+            return 0;
+        };
+        let checked = entries.len();
+        entries.retain(|id, _| keep(*id));
+        self.len -= checked - entries.len();
+        if entries.is_empty() {
+            self.segments.remove(&segment);
+        }
+        checked
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(HeapId) -> bool) {
+        for segment in self.segments() {
+            self.retain_segment(segment, &mut keep);
+        }
+    }
+}
+
+/// A set of heap ids, segmented like an `IdMap`.
+#[derive(Debug, Default)]
+struct IdSet(IdMap<()>);
+
+impl IdSet {
+    fn insert(&mut self, id: HeapId) -> bool {
+        self.0.insert(id, ()).is_none()
+    }
+
+    fn contains(&self, id: &HeapId) -> bool {
+        self.0.contains_key(id)
+    }
+
+    fn remove(&mut self, id: &HeapId) -> bool {
+        self.0.remove(id).is_some()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &HeapId> {
+        self.0.keys()
+    }
+
+    fn retain(&mut self, keep: impl FnMut(HeapId) -> bool) {
+        self.0.retain(keep);
+    }
+}
+
+impl FromIterator<HeapId> for IdSet {
+    fn from_iter<I: IntoIterator<Item = HeapId>>(ids: I) -> Self {
+        let mut set = Self::default();
+        ids.into_iter().for_each(|id| {
+            set.insert(id);
+        });
+        set
+    }
+}
+
+/// The mark state of a collection: the ids found reachable so far and those of them whose own
+/// references haven't been followed yet.
+#[derive(Debug, Default)]
+pub struct Tracer {
+    marked: IdSet,
+    pending: Vec<HeapId>,
+}
+
+impl Tracer {
+    pub fn mark(&mut self, id: HeapId) {
+        if self.marked.insert(id) {
+            self.pending.push(id);
+        }
+    }
+
+    pub fn is_marked(&self, id: HeapId) -> bool {
+        self.marked.contains(&id)
+    }
+}
+
+/// How many consecutive slots of a container a write flags as changed together.
+const CARD_SIZE: usize = 32;
+
+/// A set of cards, runs of `CARD_SIZE` slots, as a bitmap.
+#[derive(Clone, Debug, Default)]
+struct Cards {
+    /// The first 64 cards, kept apart so that small containers never allocate.
+    first: u64,
+    rest: Vec<u64>,
+}
+
+impl Cards {
+    fn insert(&mut self, card: usize) {
+        if card < 64 {
+            self.first |= 1 << card;
+            return;
+        }
+        let word = card / 64 - 1;
+        if word >= self.rest.len() {
+            self.rest.resize(word + 1, 0);
+        }
+        self.rest[word] |= 1 << (card % 64);
+    }
+
+    fn take(&mut self) -> Vec<usize> {
+        let words =
+            std::iter::once(std::mem::take(&mut self.first)).chain(std::mem::take(&mut self.rest));
+        let mut cards = vec![];
+        for (word, mut bits) in words.enumerate() {
+            while bits != 0 {
+                cards.push(word * 64 + bits.trailing_zeros() as usize);
+                bits &= bits - 1;
+            }
+        }
+        cards
+    }
+}
+
+/// Which cards of a container were written since the collector last looked, kept twice over:
+/// minor collections only trace the written cards of an old container to find what in the
+/// nursery it refers to, and incremental marking traces them again instead of the whole
+/// container.
+#[derive(Clone, Debug, Default)]
+pub struct WriteLog {
+    remembered: Cards,
+    marking: Cards,
+}
+
+impl WriteLog {
+    pub fn record(&mut self, slot: usize) {
+        self.remembered.insert(slot / CARD_SIZE);
+        self.marking.insert(slot / CARD_SIZE);
+    }
+
+    /// Records every slot from `slot` up to `len`, for when they've all moved.
+    pub fn record_from(&mut self, slot: usize, len: usize) {
+        for card in slot / CARD_SIZE..len.div_ceil(CARD_SIZE) {
+            self.remembered.insert(card);
+            self.marking.insert(card);
+        }
+    }
+}
+
+/// A container with room for a great many references, like a property map or the entries of
+/// a Map, which the collector traces a card at a time. Every write to a slot has to be
+/// recorded in the write log.
+pub trait Slots {
+    fn slot_count(&self) -> usize;
+    fn trace_slots(&self, slots: Range<usize>, tracer: &mut Tracer);
+    fn write_log(&mut self) -> &mut WriteLog;
+}
+
+/// The slots `card` covers in a container of `count` slots.
+fn card_slots(card: usize, count: usize) -> Range<usize> {
+    (card * CARD_SIZE).min(count)..((card + 1) * CARD_SIZE).min(count)
+}
+
+impl Trace for JSValue {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let JSValue::Object { object_id, .. } = self {
+            tracer.mark(*object_id);
+        }
+    }
+}
+
+impl<T: Trace> Trace for Option<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(item) = self {
+            item.trace(tracer);
+        }
+    }
+}
+
+impl<T: Trace> Trace for Vec<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.iter().for_each(|item| item.trace(tracer));
+    }
+}
+
+impl Trace for () {
+    fn trace(&self, _tracer: &mut Tracer) {}
+}
+
+/// How the collector divides its work, see `Interpreter::with_gc_options`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GcOptions {
+    /// Allocates into a nursery that is collected on its own, so short-lived objects are freed
+    /// without tracing the whole heap. Without it everything goes straight to the old space.
+    pub generational: bool,
+    /// How many entries the nursery holds before a minor collection empties it.
+    pub nursery_size: usize,
+    /// How many minor collections an entry has to survive before it's moved to the old space.
+    pub promotion_age: u8,
+    /// How many entries the old space takes in before it's collected, or more once it holds
+    /// more than that many live entries.
+    pub major_threshold: usize,
+    /// Marks and sweeps the old space a few entries at a time between statements instead of
+    /// all at once.
+    pub incremental: bool,
+    /// How many entries, or slots of a large one, a single step of incremental marking traces
+    /// or of sweeping checks, on top of one for every entry allocated since the previous step.
+    pub step_budget: usize,
+}
+
+impl Default for GcOptions {
+    fn default() -> Self {
+        Self {
+            generational: true,
+            nursery_size: 4096,
+            promotion_age: 2,
+            major_threshold: DEFAULT_GC_THRESHOLD,
+            incremental: true,
+            step_budget: 1000,
+        }
+    }
+}
+
+/// What the heap reports about itself and the collections it has run.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HeapStats {
+    /// JavaScript objects on the heap.
+    pub live_objects: usize,
+    /// Every entry on the heap: objects, scopes, variables and completion values.
+    pub live_entries: usize,
+    /// Entries in the nursery that haven't been promoted to the old space yet.
+    pub young_entries: usize,
+    /// A rough estimate of how much memory the entries take up.
+    pub live_bytes: usize,
+    /// Collections of either kind that have freed memory.
+    pub collections: usize,
+    pub minor_collections: usize,
+    /// Slices of incremental marking and sweeping run between statements.
+    pub incremental_steps: usize,
+    /// Entries freed by all collections so far.
+    pub freed_entries: usize,
+    pub last_pause: Duration,
+    /// The longest the interpreter was stopped by a collection or an incremental step.
+    pub max_pause: Duration,
+    pub total_pause: Duration,
+}
+
+/// The collector work that's due at a safe point, see `Heap::pending_work`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GcWork {
+    Minor,
+    Major,
+    MarkingStep,
+    SweepStep,
+}
+
 #[derive(Debug)]
 enum HeapValue {
     Environment(Environment),
@@ -49,10 +397,168 @@ impl std::fmt::Display for HeapValue {
     }
 }
 
-#[derive(Debug, Default)]
+impl Trace for HeapValue {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            HeapValue::Environment(environment) => environment.trace(tracer),
+            HeapValue::Variable(variable) => variable.trace(tracer),
+            HeapValue::Value(value) => value.trace(tracer),
+            HeapValue::Object(object) => object.trace(tracer),
+        }
+    }
+}
+
+impl HeapValue {
+    fn estimated_size(&self) -> usize {
+        let extra = match self {
+            HeapValue::Environment(environment) => environment.estimated_size(),
+            HeapValue::Object(object) => object.estimated_size(),
+            HeapValue::Variable(_) | HeapValue::Value(_) => 0,
+        };
+        size_of::<HeapValue>() + extra
+    }
+
+    /// What the entry refers to outside of its slots, all of it unless it's an object.
+    fn trace_header(&self, tracer: &mut Tracer) {
+        match self {
+            HeapValue::Object(object) => object.trace_header(tracer),
+            value => value.trace(tracer),
+        }
+    }
+
+    fn slots(&self) -> [Option<&dyn Slots>; 2] {
+        match self {
+            HeapValue::Object(object) => object.slots(),
+            _ => [None, None],
+        }
+    }
+
+    fn slots_mut(&mut self) -> [Option<&mut dyn Slots>; 2] {
+        match self {
+            HeapValue::Object(object) => object.slots_mut(),
+            _ => [None, None],
+        }
+    }
+
+    /// Takes the cards of each of the entry's slots one of their write logs has recorded.
+    fn take_written(&mut self, log: fn(&mut WriteLog) -> &mut Cards) -> [Vec<usize>; 2] {
+        let mut written = [vec![], vec![]];
+        for (slots, cards) in self.slots_mut().into_iter().zip(&mut written) {
+            if let Some(slots) = slots {
+                *cards = log(slots.write_log()).take();
+            }
+        }
+        written
+    }
+
+    fn trace_cards(&self, cards: &[Vec<usize>; 2], tracer: &mut Tracer) {
+        for (slots, cards) in self.slots().into_iter().zip(cards) {
+            if let Some(slots) = slots {
+                let count = slots.slot_count();
+                for card in cards {
+                    slots.trace_slots(card_slots(*card, count), tracer);
+                }
+            }
+        }
+    }
+
+    /// WeakMaps and WeakSets, whose entries the collector handles itself.
+    fn is_weak_table(&self) -> bool {
+        matches!(
+            self,
+            HeapValue::Object(object)
+                if matches!(object.get_data(), Some(ObjectData::WeakMap(_) | ObjectData::WeakSet(_)))
+        )
+    }
+}
+
+/// A major collection whose marking is spread over several safe points.
+#[derive(Debug)]
+struct Marking {
+    tracer: Tracer,
+    weak_tables: Vec<HeapId>,
+    /// Slots left to trace: the entry, which of its containers and the slot to go on from.
+    partial: Vec<(HeapId, usize, usize)>,
+    /// Marked entries changed since they were traced, whose written cards the next step
+    /// traces again.
+    dirty: IdSet,
+}
+
+/// A major collection whose marking is done, freeing the unmarked part of the old space a few
+/// entries at a time.
+#[derive(Debug)]
+struct Sweeping {
+    marked: IdSet,
+    /// The segments of the old space left to check.
+    segments: Vec<HeapId>,
+}
+
+/// Everything the interpreter allocates, collected by a generational tracing collector.
+///
+/// New entries go to the nursery, which minor collections empty by copying the survivors into
+/// a fresh one, or into the old space once they've survived `promotion_age` of them. The old
+/// space is only collected by major collections, which mark and sweep it incrementally if so
+/// configured, with minor collections carrying on in between. Both depend on the write barrier
+/// in `get_mut`: old entries handed out for mutation are remembered as possibly referring to
+/// the nursery, and entries already marked are traced again. Objects log which parts of their
+/// properties and entries were written, see `Slots`, so that neither has to go through all of
+/// a large one again, and marking traces them a piece at a time.
+///
+/// The collector can't see the Rust stack, so values native code and the tree walk are holding
+/// on to have to be rooted as temporaries. Every allocation is, and statements release what was
+/// rooted while they ran once they complete, see `Stmt::evaluate`.
+#[derive(Debug)]
 pub struct Heap {
-    map: HashMap<HeapId, HeapValue>,
+    young: HashMap<HeapId, HeapValue>,
+    /// How many minor collections each entry of the nursery has survived.
+    ages: HashMap<HeapId, u8>,
+    old: IdMap<HeapValue>,
+    /// Old entries that may refer to the nursery.
+    remembered: IdSet,
+    marking: Option<Marking>,
+    sweeping: Option<Sweeping>,
+    options: GcOptions,
     counter: HeapId,
+    temporaries: Vec<HeapId>,
+    /// Entries allocated since the last incremental step, which the next one makes up for.
+    allocated_since_step: usize,
+    /// Entries added to the old space since the last major collection.
+    allocated: usize,
+    /// How many of those trigger the next major collection.
+    threshold: usize,
+    collections: usize,
+    minor_collections: usize,
+    incremental_steps: usize,
+    freed_entries: usize,
+    last_pause: Duration,
+    max_pause: Duration,
+    total_pause: Duration,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self {
+            young: HashMap::new(),
+            ages: HashMap::new(),
+            old: IdMap::default(),
+            remembered: IdSet::default(),
+            marking: None,
+            sweeping: None,
+            options: GcOptions::default(),
+            counter: 0,
+            temporaries: vec![],
+            allocated_since_step: 0,
+            allocated: 0,
+            threshold: DEFAULT_GC_THRESHOLD,
+            collections: 0,
+            minor_collections: 0,
+            incremental_steps: 0,
+            freed_entries: 0,
+            last_pause: Duration::ZERO,
+            max_pause: Duration::ZERO,
+            total_pause: Duration::ZERO,
+        }
+    }
 }
 
 impl Heap {
@@ -60,6 +566,533 @@ impl Heap {
         Self::default()
     }
 
+    pub fn set_options(&mut self, options: GcOptions) {
+        self.options = options;
+        self.threshold = options.major_threshold.max(self.old.len());
+        if !options.generational {
+            self.tenure();
+        }
+    }
+
+    /// Sets how many entries the old space takes in before a major collection. The heap may
+    /// go longer between them once it holds more than that many live entries.
+    pub fn set_threshold(&mut self, allocations: usize) {
+        self.set_options(GcOptions {
+            major_threshold: allocations,
+            ..self.options
+        });
+    }
+
+    /// Moves the whole nursery to the old space, for entries known to live as long as the
+    /// interpreter does.
+    pub fn tenure(&mut self) {
+        for (id, value) in std::mem::take(&mut self.young) {
+            self.insert_old(id, value);
+        }
+        self.ages.clear();
+        self.remembered = IdSet::default();
+        self.threshold = self.options.major_threshold.max(self.old.len());
+    }
+
+    /// The most urgent work due. A full nursery comes first, even while a major collection is
+    /// under way, so that it doesn't grow for as long as the major collection takes.
+    pub fn pending_work(&self) -> Option<GcWork> {
+        if self.options.generational && self.young.len() >= self.options.nursery_size {
+            Some(GcWork::Minor)
+        } else if self.marking.is_some() {
+            Some(GcWork::MarkingStep)
+        } else if self.sweeping.is_some() {
+            Some(GcWork::SweepStep)
+        } else if self.allocated >= self.threshold {
+            Some(GcWork::Major)
+        } else {
+            None
+        }
+    }
+
+    /// Keeps the object `value` refers to alive until the temporaries are released.
+    pub fn root_value(&mut self, value: &JSValue) {
+        if let JSValue::Object { object_id, .. } = value {
+            self.temporaries.push(*object_id);
+        }
+    }
+
+    /// Keeps whatever `item` refers to alive until the temporaries are released.
+    pub fn root(&mut self, item: &impl Trace) {
+        let mut tracer = Tracer::default();
+        item.trace(&mut tracer);
+        self.temporaries.append(&mut tracer.pending);
+    }
+
+    /// Where the temporaries currently end, to hand to `release_temporaries` later.
+    pub fn temporaries_mark(&self) -> usize {
+        self.temporaries.len()
+    }
+
+    pub fn release_temporaries(&mut self, mark: usize) {
+        self.temporaries.truncate(mark);
+    }
+
+    /// Frees the unreachable part of the nursery. Only the nursery is traced: old entries are
+    /// assumed to be alive, so whatever of them refers to the nursery, the cards written since
+    /// the last minor collection of the entries in the remembered set, is a root as well.
+    /// Survivors are copied into a new nursery, or promoted to the old space once old enough,
+    /// and the old nursery is dropped as a whole.
+    pub fn collect_young(&mut self, mut roots: Tracer) {
+        let start = Instant::now();
+        for id in &self.temporaries {
+            roots.mark(*id);
+        }
+        let mut written = vec![];
+        for id in self.remembered.iter() {
+            if let Some(value) = self.old.get_mut(id) {
+                written.push((*id, value.take_written(|log| &mut log.remembered)));
+            }
+        }
+        let mut weak_tables = vec![];
+        for (id, cards) in &written {
+            let value = &self.old.get(id).expect("remembered entries are old");
+            if value.is_weak_table() {
+                weak_tables.push(*id);
+            }
+            value.trace_header(&mut roots);
+            value.trace_cards(cards, &mut roots);
+        }
+        loop {
+            while let Some(id) = roots.pending.pop() {
+                if self.young.contains_key(&id) {
+                    self.trace_entry(id, &mut roots, &mut weak_tables);
+                }
+            }
+            self.trace_ephemerons(&mut roots, &weak_tables, true);
+            if roots.pending.is_empty() {
+                break;
+            }
+        }
+
+        let nursery = std::mem::take(&mut self.young);
+        let mut ages = std::mem::take(&mut self.ages);
+        let mut freed = HashSet::new();
+        let mut promoted = vec![];
+        for (id, value) in nursery {
+            if !roots.is_marked(id) {
+                freed.insert(id);
+                continue;
+            }
+            let age = ages.get(&id).copied().unwrap_or(0) + 1;
+            if age >= self.options.promotion_age {
+                self.insert_old(id, value);
+                promoted.push(id);
+            } else {
+                self.young.insert(id, value);
+                self.ages.insert(id, age);
+            }
+        }
+        ages.clear();
+        self.allocated += promoted.len();
+        self.retain_live_keys(&weak_tables, |key| !freed.contains(&key));
+
+        self.remembered = IdSet::default();
+        for (id, cards) in written {
+            self.remember_young_refs(id, Some(cards));
+        }
+        for id in promoted {
+            self.remember_young_refs(id, None);
+        }
+
+        self.freed_entries += freed.len();
+        self.collections += 1;
+        self.minor_collections += 1;
+        self.record_pause(start);
+    }
+
+    /// Starts a major collection, marking only the roots when it's going to be incremental
+    /// and running all of it otherwise.
+    pub fn begin_major(&mut self, mut roots: Tracer) {
+        if !self.options.incremental {
+            self.collect(roots);
+            return;
+        }
+        let start = Instant::now();
+        for id in &self.temporaries {
+            roots.mark(*id);
+        }
+        self.marking = Some(Marking {
+            tracer: roots,
+            weak_tables: vec![],
+            partial: vec![],
+            dirty: IdSet::default(),
+        });
+        self.allocated_since_step = 0;
+        self.incremental_steps += 1;
+        self.record_pause(start);
+    }
+
+    /// Traces the cards written since the last step and up to `step_budget` more slots, plus
+    /// one for every entry allocated in the meantime, so that marking keeps ahead of the
+    /// program. Large containers are traced a piece at a time. Returns whether there's nothing
+    /// left to trace, at which point `finish_marking` checks the roots once more.
+    pub fn mark_step(&mut self) -> bool {
+        let Some(mut marking) = self.marking.take() else {
+            return true;
+        };
+        let start = Instant::now();
+        for id in std::mem::take(&mut marking.dirty).iter() {
+            self.retrace_written(*id, &mut marking);
+        }
+        let budget = self.options.step_budget.max(1) + self.allocated_since_step;
+        let mut traced = 0;
+        while traced < budget {
+            if let Some((id, index, from)) = marking.partial.pop() {
+                traced += self.scan_slots(id, index, from, budget - traced, &mut marking);
+            } else if let Some(id) = marking.tracer.pending.pop() {
+                self.scan_entry(id, &mut marking);
+                traced += 1;
+            } else {
+                break;
+            }
+        }
+        if marking.tracer.pending.is_empty() && marking.partial.is_empty() {
+            self.trace_ephemerons(&mut marking.tracer, &marking.weak_tables, false);
+        }
+        let done = marking.tracer.pending.is_empty()
+            && marking.partial.is_empty()
+            && marking.dirty.is_empty();
+        self.marking = Some(marking);
+        self.allocated_since_step = 0;
+        self.incremental_steps += 1;
+        self.record_pause(start);
+        done
+    }
+
+    /// Traces what the entry refers to besides its slots, and queues those to be traced in
+    /// pieces.
+    fn scan_entry(&self, id: HeapId, marking: &mut Marking) {
+        let Some(value) = self.get(id) else {
+            return;
+        };
+        if value.is_weak_table() && !marking.weak_tables.contains(&id) {
+            marking.weak_tables.push(id);
+        }
+        value.trace_header(&mut marking.tracer);
+        for (index, slots) in value.slots().into_iter().enumerate() {
+            if slots.is_some() {
+                marking.partial.push((id, index, 0));
+            }
+        }
+    }
+
+    /// Traces up to `budget` slots, or a card's worth, of one of the entry's containers from
+    /// `from` on, queueing the rest. Returns how many it traced.
+    fn scan_slots(
+        &self,
+        id: HeapId,
+        index: usize,
+        from: usize,
+        budget: usize,
+        marking: &mut Marking,
+    ) -> usize {
+        let Some(slots) = self.get(id).and_then(|value| value.slots()[index]) else {
+            return 1;
+        };
+        let count = slots.slot_count();
+        let to = count.min(from + budget.max(CARD_SIZE));
+        if from < to {
+            slots.trace_slots(from..to, &mut marking.tracer);
+        }
+        if to < count {
+            marking.partial.push((id, index, to));
+        }
+        to.saturating_sub(from).max(1)
+    }
+
+    /// Traces what a marked entry changed since it was last traced refers to: everything
+    /// outside its slots and the cards it wrote.
+    fn retrace_written(&mut self, id: HeapId, marking: &mut Marking) {
+        let Some(value) = self.young.get_mut(&id).or_else(|| self.old.get_mut(&id)) else {
+            return;
+        };
+        let cards = value.take_written(|log| &mut log.marking);
+        let value = self.get(id).expect("the entry was just found");
+        if value.is_weak_table() && !marking.weak_tables.contains(&id) {
+            marking.weak_tables.push(id);
+        }
+        value.trace_header(&mut marking.tracer);
+        value.trace_cards(&cards, &mut marking.tracer);
+    }
+
+    /// Marks the roots and temporaries again, as they aren't covered by the write barrier.
+    /// Entries allocated since marking started are marked already, so this can only turn up
+    /// entries that existed before it did, and marking ends once it doesn't turn up any.
+    /// Otherwise later steps trace what it found. Returns whether marking is over, at which
+    /// point the old space is swept a few entries at a time by `sweep_step`.
+    pub fn finish_marking(&mut self, roots: Tracer) -> bool {
+        let Some(mut marking) = self.marking.take() else {
+            return true;
+        };
+        let start = Instant::now();
+        for id in roots.marked.iter() {
+            marking.tracer.mark(*id);
+        }
+        for id in &self.temporaries {
+            marking.tracer.mark(*id);
+        }
+        if !marking.tracer.pending.is_empty() {
+            self.marking = Some(marking);
+            self.record_pause(start);
+            return false;
+        }
+        let Marking {
+            tracer,
+            weak_tables,
+            ..
+        } = marking;
+        self.retain_live_keys(&weak_tables, |key| tracer.is_marked(key));
+        self.sweeping = Some(Sweeping {
+            marked: tracer.marked,
+            segments: self.old.segments(),
+        });
+        self.record_pause(start);
+        true
+    }
+
+    /// Checks segments of the old space until it has gone through `step_budget` entries, plus
+    /// one for every entry allocated since the last step, freeing those that weren't marked.
+    /// The nursery is left to minor collections. Returns whether the major collection is
+    /// complete.
+    pub fn sweep_step(&mut self) -> bool {
+        let Some(mut sweeping) = self.sweeping.take() else {
+            return true;
+        };
+        let start = Instant::now();
+        let budget = self.options.step_budget.max(1) + self.allocated_since_step;
+        let mut checked = 0;
+        while checked < budget
+            && let Some(segment) = sweeping.segments.pop()
+        {
+            let before = self.old.len();
+            checked += self.old.retain_segment(segment, |id| {
+                let live = sweeping.marked.contains(&id);
+                if !live {
+                    self.remembered.remove(&id);
+                }
+                live
+            });
+            self.freed_entries += before - self.old.len();
+        }
+        self.allocated_since_step = 0;
+        self.incremental_steps += 1;
+        let done = sweeping.segments.is_empty();
+        if done {
+            self.collections += 1;
+            self.allocated = 0;
+            self.threshold = self.options.major_threshold.max(self.old.len());
+        } else {
+            self.sweeping = Some(sweeping);
+        }
+        self.record_pause(start);
+        done
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-weakref-processing-model
+    /// Marks everything reachable from `roots` and the temporaries in both spaces and frees
+    /// the rest, abandoning any incremental collection in progress. The values of a WeakMap are
+    /// only reachable through it while their key is, so those are traced once the rest of the
+    /// marking is done, repeating until nothing new turns up. Entries of weak tables whose key
+    /// didn't survive are dropped afterwards.
+    pub fn collect(&mut self, mut roots: Tracer) {
+        let start = Instant::now();
+        self.marking = None;
+        self.sweeping = None;
+        for id in &self.temporaries {
+            roots.mark(*id);
+        }
+        let mut weak_tables = vec![];
+        self.mark_all(&mut roots, &mut weak_tables);
+        self.sweep(&roots, &weak_tables);
+        self.record_pause(start);
+    }
+
+    pub fn stats(&self) -> HeapStats {
+        let entries = || self.old.values().chain(self.young.values());
+        HeapStats {
+            live_objects: entries()
+                .filter(|value| matches!(value, HeapValue::Object(_)))
+                .count(),
+            live_entries: self.old.len() + self.young.len(),
+            young_entries: self.young.len(),
+            live_bytes: entries().map(HeapValue::estimated_size).sum(),
+            collections: self.collections,
+            minor_collections: self.minor_collections,
+            incremental_steps: self.incremental_steps,
+            freed_entries: self.freed_entries,
+            last_pause: self.last_pause,
+            max_pause: self.max_pause,
+            total_pause: self.total_pause,
+        }
+    }
+
+    fn mark_all(&self, tracer: &mut Tracer, weak_tables: &mut Vec<HeapId>) {
+        loop {
+            while let Some(id) = tracer.pending.pop() {
+                self.trace_entry(id, tracer, weak_tables);
+            }
+            self.trace_ephemerons(tracer, weak_tables, false);
+            if tracer.pending.is_empty() {
+                break;
+            }
+        }
+    }
+
+    fn trace_entry(&self, id: HeapId, tracer: &mut Tracer, weak_tables: &mut Vec<HeapId>) {
+        let Some(value) = self.get(id) else {
+            return;
+        };
+        if value.is_weak_table() && !weak_tables.contains(&id) {
+            weak_tables.push(id);
+        }
+        value.trace(tracer);
+    }
+
+    /// Traces the values of WeakMap entries whose key is marked, or in the old space for a
+    /// minor collection, which takes all of that to be alive.
+    fn trace_ephemerons(&self, tracer: &mut Tracer, weak_tables: &[HeapId], minor: bool) {
+        for id in weak_tables {
+            let Some(HeapValue::Object(object)) = self.get(*id) else {
+                continue;
+            };
+            if let Some(ObjectData::WeakMap(table)) = object.get_data() {
+                for (key, value) in table.entries() {
+                    let live = match key {
+                        WeakKey::Object(key_id) => {
+                            tracer.is_marked(*key_id) || (minor && self.old.contains_key(key_id))
+                        }
+                        WeakKey::Symbol(_) => true,
+                    };
+                    if live {
+                        value.trace(tracer);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Frees the unmarked entries of both spaces once a full collection's marking is done.
+    /// Surviving nursery entries age as they would in a minor collection.
+    fn sweep(&mut self, tracer: &Tracer, weak_tables: &[HeapId]) {
+        let before = self.old.len() + self.young.len();
+        self.old.retain(|id| tracer.is_marked(id));
+        self.remembered.retain(|id| tracer.is_marked(id));
+        let nursery = std::mem::take(&mut self.young);
+        let ages = std::mem::take(&mut self.ages);
+        for (id, value) in nursery {
+            if !tracer.is_marked(id) {
+                continue;
+            }
+            let age = ages.get(&id).copied().unwrap_or(0) + 1;
+            if age >= self.options.promotion_age {
+                self.insert_old(id, value);
+                self.remember_young_refs(id, None);
+            } else {
+                self.young.insert(id, value);
+                self.ages.insert(id, age);
+            }
+        }
+        self.retain_live_keys(weak_tables, |key| tracer.is_marked(key));
+
+        self.freed_entries += before - self.old.len() - self.young.len();
+        self.collections += 1;
+        self.allocated = 0;
+        self.threshold = self.options.major_threshold.max(self.old.len());
+    }
+
+    fn retain_live_keys(&mut self, weak_tables: &[HeapId], is_live: impl Fn(HeapId) -> bool) {
+        for id in weak_tables {
+            let Some(HeapValue::Object(object)) = self.young.get_mut(id).or(self.old.get_mut(id))
+            else {
+                continue;
+            };
+            match object.get_data_mut() {
+                Some(ObjectData::WeakMap(table)) => table.retain_live(&is_live),
+                Some(ObjectData::WeakSet(table)) => table.retain_live(&is_live),
+                _ => (),
+            }
+        }
+    }
+
+    /// Adds an old entry to the remembered set if it still refers to the nursery, with the
+    /// cards that do so in its write log. Only `cards` are checked if given, the ones the last
+    /// minor collection took from the log, otherwise all of them.
+    fn remember_young_refs(&mut self, id: HeapId, cards: Option<[Vec<usize>; 2]>) {
+        let Some(value) = self.old.get(&id) else {
+            return;
+        };
+        let refers_to_young = |trace: &dyn Fn(&mut Tracer)| {
+            let mut tracer = Tracer::default();
+            trace(&mut tracer);
+            tracer.marked.iter().any(|id| self.young.contains_key(id))
+        };
+        let mut young_cards = [vec![], vec![]];
+        for (index, slots) in value.slots().into_iter().enumerate() {
+            let Some(slots) = slots else {
+                continue;
+            };
+            let count = slots.slot_count();
+            let candidates = match &cards {
+                Some(cards) => cards[index].clone(),
+                None => (0..count.div_ceil(CARD_SIZE)).collect(),
+            };
+            young_cards[index] = candidates
+                .into_iter()
+                .filter(|card| {
+                    refers_to_young(&|tracer| slots.trace_slots(card_slots(*card, count), tracer))
+                })
+                .collect();
+        }
+        let remembered = refers_to_young(&|tracer| Self::trace_header_weakly(value, tracer))
+            || young_cards.iter().any(|cards| !cards.is_empty());
+
+        let value = self.old.get_mut(&id).expect("the entry was just found");
+        for (slots, cards) in value.slots_mut().into_iter().zip(young_cards) {
+            if let Some(slots) = slots {
+                let log = &mut slots.write_log().remembered;
+                *log = Cards::default();
+                cards.into_iter().for_each(|card| log.insert(card));
+            }
+        }
+        if remembered {
+            self.remembered.insert(id);
+        }
+    }
+
+    /// Like `HeapValue::trace_header`, but following the keys and values of weak tables too.
+    fn trace_header_weakly(value: &HeapValue, tracer: &mut Tracer) {
+        value.trace_header(tracer);
+        if let HeapValue::Object(object) = value {
+            match object.get_data() {
+                Some(ObjectData::WeakMap(table)) => table.entries().for_each(|(key, value)| {
+                    if let WeakKey::Object(key_id) = key {
+                        tracer.mark(*key_id);
+                    }
+                    value.trace(tracer);
+                }),
+                Some(ObjectData::WeakSet(table)) => table.entries().for_each(|(key, _)| {
+                    if let WeakKey::Object(key_id) = key {
+                        tracer.mark(*key_id);
+                    }
+                }),
+                _ => (),
+            }
+        }
+    }
+
+    #[track_caller]
+    fn record_pause(&mut self, start: Instant) {
+        self.last_pause = start.elapsed();
+        self.max_pause = self.max_pause.max(self.last_pause);
+        self.total_pause += self.last_pause;
+    }
+
     pub fn add_environment(&mut self, env: Environment) -> HeapId {
         let value = HeapValue::new_environment(env);
         self.add_to_map(value)
@@ -169,19 +1202,69 @@ impl Heap {
     }
 
     fn get(&self, id: HeapId) -> Option<&HeapValue> {
-        self.map.get(&id)
+        self.young.get(&id).or_else(|| self.old.get(&id))
     }
 
+    /// The write barrier: every mutation of an entry goes through here, so this is where an
+    /// old entry is remembered as possibly referring to the nursery from now on, and where an
+    /// entry incremental marking has already traced is queued to be traced again, once. What
+    /// either of them logged of the entry's writes before then is out of date: an old entry
+    /// outside the remembered set doesn't refer to the nursery, and a marked entry outside the
+    /// dirty set has been traced as it is now, so the log starts over.
     fn get_mut(&mut self, id: HeapId) -> Option<&mut HeapValue> {
-        self.map.get_mut(&id)
+        let newly_remembered =
+            self.options.generational && self.old.contains_key(&id) && self.remembered.insert(id);
+        let newly_dirty = match &mut self.marking {
+            Some(marking) if marking.tracer.is_marked(id) => marking.dirty.insert(id),
+            _ => false,
+        };
+        let value = self.young.get_mut(&id).or_else(|| self.old.get_mut(&id))?;
+        if newly_remembered || newly_dirty {
+            for slots in value.slots_mut().into_iter().flatten() {
+                let log = slots.write_log();
+                if newly_remembered {
+                    log.remembered = Cards::default();
+                }
+                if newly_dirty {
+                    log.marking = Cards::default();
+                }
+            }
+        }
+        Some(value)
     }
 
+    /// New entries start out as temporaries, nothing else refers to them yet. While
+    /// incremental marking is under way they're marked straight away, so that it only ever
+    /// has the entries that existed when it started left to find, and the next step traces
+    /// them on top of its budget.
     fn add_to_map(&mut self, value: HeapValue) -> HeapId {
         let id = self.get_next_id();
-        self.map.insert(id, value);
+        if self.options.generational {
+            self.young.insert(id, value);
+            if let Some(marking) = &mut self.marking {
+                marking.tracer.mark(id);
+            }
+        } else {
+            self.insert_old(id, value);
+            self.allocated += 1;
+        }
+        self.allocated_since_step += 1;
+        self.temporaries.push(id);
         id
     }
 
+    /// Moves an entry into the old space, marked if a major collection is under way as
+    /// whatever moves there is alive.
+    fn insert_old(&mut self, id: HeapId, value: HeapValue) {
+        self.old.insert(id, value);
+        if let Some(marking) = &mut self.marking {
+            marking.tracer.mark(id);
+        }
+        if let Some(sweeping) = &mut self.sweeping {
+            sweeping.marked.insert(id);
+        }
+    }
+
     fn get_next_id(&mut self) -> HeapId {
         let id = self.counter;
         self.counter += 1;
@@ -192,7 +1275,7 @@ impl Heap {
 impl std::fmt::Display for Heap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Heap: {{")?;
-        for (id, value) in &self.map {
+        for (id, value) in self.old.iter().chain(&self.young) {
             writeln!(f, "{id}: {value}")?;
         }
         writeln!(f, "}}")
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use crate::{
+    Interpreter,
+    global::get_or_intern_string,
+    handles::{HandleScope, Local, Value},
+    values::{JSObject, JSResult, JSValue, ObjectKind, set_property_value},
+};
+
+/// A Rust closure scripts call like any other function. It gets the `this` value and the
+/// arguments of the call, and returns what the call evaluates to or the value it throws. Host
+/// functions are `Send` and `Sync` so the interpreters holding them can move between threads.
+pub trait HostFunction:
+    for<'i> Fn(
+        &mut HandleScope<'i>,
+        Local<'i, Value>,
+        &[Local<'i, Value>],
+    ) -> Result<Local<'i, Value>, Local<'i, Value>>
+    + Send
+    + Sync
+    + 'static
+{
+}
+
+impl<F> HostFunction for F where
+    F: for<'i> Fn(
+            &mut HandleScope<'i>,
+            Local<'i, Value>,
+            &[Local<'i, Value>],
+        ) -> Result<Local<'i, Value>, Local<'i, Value>>
+        + Send
+        + Sync
+        + 'static
+{
+}
+
+/// A global object the host fills with methods, see `Interpreter::register_object`.
+pub struct NativeObject<'a> {
+    interpreter: &'a mut Interpreter,
+    object: JSValue,
+}
+
+impl<'a> NativeObject<'a> {
+    pub(crate) fn new(interpreter: &'a mut Interpreter, name: &str) -> JSResult<Self> {
+        let mark = interpreter.heap.temporaries_mark();
+        let object = JSValue::new_object(vec![], interpreter);
+        let global = interpreter.get_global_object()?;
+        let result = set_property_value(
+            interpreter,
+            &global,
+            get_or_intern_string(name),
+            object.clone(),
+        );
+        interpreter.heap.release_temporaries(mark);
+        result?;
+        Ok(Self {
+            interpreter,
+            object,
+        })
+    }
+
+    /// Adds a method that runs `function` when called.
+    pub fn method(self, name: &str, function: impl HostFunction) -> Self {
+        let _isolate = self.interpreter.isolate.enter();
+        let mark = self.interpreter.heap.temporaries_mark();
+        let function = new_host_function(self.interpreter, name, Arc::new(function));
+        // the object is one of our own, so this can't run a setter or fail
+        let _ = set_property_value(
+            self.interpreter,
+            &self.object,
+            get_or_intern_string(name),
+            function,
+        );
+        self.interpreter.heap.release_temporaries(mark);
+        self
+    }
+}
+
+/// The closure behind a host function, held by its function object so it's dropped together
+/// with it once the collector frees the object.
+#[derive(Clone)]
+pub(crate) struct HostCallback(Arc<dyn HostFunction>);
+
+impl HostCallback {
+    pub(crate) fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        this: &JSValue,
+        args: &[JSValue],
+    ) -> JSResult<JSValue> {
+        let mut scope = HandleScope::new(interpreter);
+        let this = scope.local(this.clone());
+        let args: Vec<Local<Value>> = args.iter().map(|arg| scope.local(arg.clone())).collect();
+        match (self.0)(&mut scope, this, &args) {
+            Ok(result) => Ok(result.value),
+            Err(thrown) => Err(scope.interpreter.throw_value(thrown.value)),
+        }
+    }
+}
+
+impl std::fmt::Debug for HostCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HostCallback")
+    }
+}
+
+/// Wraps `function` in a function object.
+pub(crate) fn new_host_function(
+    interpreter: &mut Interpreter,
+    name: &str,
+    function: Arc<dyn HostFunction>,
+) -> JSValue {
+    let object_id = JSObject::new_host_function(name, HostCallback(function), interpreter);
+    JSValue::Object {
+        object_id,
+        kind: ObjectKind::Function,
+    }
+}
@@ -0,0 +1,1058 @@
+//! Node's `util.inspect`, how `console.log` and the REPL show values: nested objects up to a
+//! depth, references back to an object being shown marked as circular, and long output broken
+//! over several lines. Objects are read straight from the heap, so inspecting a value never
+//! runs getters, proxy traps or any other script.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        array_buffer::array_buffer_state,
+        collection::OrderedTable,
+        promise::PromiseState,
+        typed_array::{TypedArrayState, typed_array_get_element, typed_array_length},
+    },
+    constants::{CONSTRUCTOR_NAME, NAME_NAME, PROTOTYPE_NAME},
+    date::iso_string,
+    global::get_string_from_pool,
+    values::{
+        self, BodyKind, FunctionKind, JSObject, JSValue, ObjectData, ObjectId, ObjectProperty,
+        PropertyKey, WellKnownSymbol, array_index, bigint,
+    },
+};
+
+/// How `inspect` lays values out, Node's options of the same names.
+#[derive(Clone, Debug)]
+pub struct InspectOptions {
+    /// How many levels of nested objects to show, `None` for all of them.
+    pub depth: Option<usize>,
+    /// Whether to style the output with ANSI escape codes, for terminals.
+    pub colors: bool,
+    /// How long a line may get before the entries of an object go on lines of their own.
+    pub break_length: usize,
+    /// How many of the innermost levels of nesting may still be put on a single line.
+    pub compact: usize,
+    /// How many elements of arrays, typed arrays, maps and sets to show.
+    pub max_array_length: usize,
+}
+
+impl Default for InspectOptions {
+    fn default() -> Self {
+        Self {
+            depth: Some(2),
+            colors: false,
+            break_length: 80,
+            compact: 3,
+            max_array_length: 100,
+        }
+    }
+}
+
+/// Strings shorter than this are never split at their line breaks.
+const MIN_LINE_LENGTH: usize = 16;
+
+/// What a piece of output is, for coloring it. Names go unstyled.
+#[derive(Clone, Copy)]
+enum Style {
+    Special,
+    Number,
+    BigInt,
+    Boolean,
+    Undefined,
+    Null,
+    String,
+    Symbol,
+    Date,
+    RegExp,
+}
+
+impl Style {
+    /// The SGR codes that turn the style's color on and off again.
+    fn codes(self) -> (u8, u8) {
+        match self {
+            Style::Special => (36, 39),
+            Style::Number | Style::BigInt | Style::Boolean => (33, 39),
+            Style::Undefined => (90, 39),
+            Style::Null => (1, 22),
+            Style::String | Style::Symbol => (32, 39),
+            Style::Date => (35, 39),
+            Style::RegExp => (31, 39),
+        }
+    }
+}
+
+/// Whether the entries of an object are its properties or the elements of an array, which may
+/// be grouped into columns.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Extras {
+    Object,
+    Array,
+}
+
+/// Where the entries shown before an object's properties come from.
+enum Entries<'a> {
+    None,
+    Array(&'a JSObject, usize),
+    TypedArray(TypedArrayState, usize),
+    Set(&'a OrderedTable<()>),
+    Map(&'a OrderedTable<JSValue>),
+    ArrayBuffer(ObjectId),
+    Promise(&'a PromiseState),
+    WeakCollection,
+}
+
+/// `value` the way `util.inspect(value, options)` shows it.
+pub fn inspect(interpreter: &Interpreter, value: &JSValue, options: &InspectOptions) -> String {
+    let mut context = Context {
+        interpreter,
+        options,
+        seen: vec![],
+        circular: vec![],
+        indentation: 0,
+        current_depth: 0,
+    };
+    context.format_value(value, 0)
+}
+
+struct Context<'a> {
+    interpreter: &'a Interpreter,
+    options: &'a InspectOptions,
+    /// The objects being formatted, outermost first.
+    seen: Vec<ObjectId>,
+    /// The objects found to be referred to from inside themselves, numbered from one.
+    circular: Vec<ObjectId>,
+    indentation: usize,
+    /// The depth of the object formatted last, which decides whether the objects around it
+    /// may still go on a single line.
+    current_depth: usize,
+}
+
+impl<'a> Context<'a> {
+    fn object(&self, object_id: ObjectId) -> Option<&'a JSObject> {
+        self.interpreter.get_object(object_id).ok()
+    }
+
+    fn stylize(&self, text: &str, style: Style) -> String {
+        if !self.options.colors {
+            return text.to_string();
+        }
+        let (start, end) = style.codes();
+        format!("\x1b[{start}m{text}\x1b[{end}m")
+    }
+
+    /// How many columns `text` takes up on a terminal, without its escape codes.
+    fn width(&self, text: &str) -> usize {
+        if !self.options.colors {
+            return text.chars().count();
+        }
+        let mut width = 0;
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+                continue;
+            }
+            width += 1;
+        }
+        width
+    }
+
+    fn format_value(&mut self, value: &JSValue, recurse_times: usize) -> String {
+        let JSValue::Object { object_id, .. } = value else {
+            return self.format_primitive(value);
+        };
+        // a proxy is shown as its target, without running any of its traps
+        let mut object_id = *object_id;
+        let object = loop {
+            match self.object(object_id) {
+                Some(JSObject::Proxy(proxy)) => match proxy.target() {
+                    Some(target) => object_id = target,
+                    None => return self.stylize("<Revoked Proxy>", Style::Special),
+                },
+                Some(object) => break object,
+                None => return self.stylize("undefined", Style::Undefined),
+            }
+        };
+        if self.seen.contains(&object_id) {
+            let index = match self.circular.iter().position(|id| *id == object_id) {
+                Some(position) => position + 1,
+                None => {
+                    self.circular.push(object_id);
+                    self.circular.len()
+                }
+            };
+            return self.stylize(&format!("[Circular *{index}]"), Style::Special);
+        }
+        self.format_raw(object_id, object, recurse_times)
+    }
+
+    fn format_raw(
+        &mut self,
+        object_id: ObjectId,
+        object: &'a JSObject,
+        recurse_times: usize,
+    ) -> String {
+        let constructor = self.constructor_name(object_id);
+        let constructor = constructor.as_deref();
+        let tag = self.to_string_tag(object_id);
+        let mut keys = self.enumerable_keys(object);
+        let mut base = String::new();
+        let mut braces = (String::from("{"), "}");
+        let mut entries = Entries::None;
+        let mut extras = Extras::Object;
+        let beyond_depth = self
+            .options
+            .depth
+            .is_some_and(|depth| recurse_times > depth);
+
+        match (object, object.get_data()) {
+            (JSObject::Array(array), _) => {
+                let length = array.len();
+                let prefix = match constructor != Some("Array") || !tag.is_empty() {
+                    true => prefix(constructor, &tag, "Array", &format!("({length})")),
+                    false => String::new(),
+                };
+                keys.retain(|key| array_index(key).is_none());
+                braces = (format!("{prefix}["), "]");
+                if length == 0 && keys.is_empty() {
+                    return format!("{}]", braces.0);
+                }
+                extras = Extras::Array;
+                entries = Entries::Array(object, length);
+            }
+            (_, Some(ObjectData::Set(table))) => {
+                let prefix = prefix(constructor, &tag, "Set", &format!("({})", table.len()));
+                if table.len() == 0 && keys.is_empty() {
+                    return format!("{prefix}{{}}");
+                }
+                braces = (format!("{prefix}{{"), "}");
+                entries = Entries::Set(table);
+            }
+            (_, Some(ObjectData::Map(table))) => {
+                let prefix = prefix(constructor, &tag, "Map", &format!("({})", table.len()));
+                if table.len() == 0 && keys.is_empty() {
+                    return format!("{prefix}{{}}");
+                }
+                braces = (format!("{prefix}{{"), "}");
+                entries = Entries::Map(table);
+            }
+            (_, Some(ObjectData::TypedArray(state))) => {
+                let length = typed_array_length(self.interpreter, state).unwrap_or(0);
+                let fallback = match constructor {
+                    Some(_) => "",
+                    None => state.kind.name(),
+                };
+                let prefix = prefix(constructor, &tag, fallback, &format!("({length})"));
+                braces = (format!("{prefix}["), "]");
+                if length == 0 && keys.is_empty() {
+                    return format!("{}]", braces.0);
+                }
+                extras = Extras::Array;
+                entries = Entries::TypedArray(**state, length);
+            }
+            (JSObject::Function(function), _) => {
+                let is_class = matches!(function.get_kind(), FunctionKind::ClassConstructor);
+                base = self.function_base(
+                    object_id,
+                    is_class,
+                    function.body_kind(),
+                    constructor,
+                    &tag,
+                );
+                if keys.is_empty() {
+                    return self.stylize(&base, Style::Special);
+                }
+            }
+            _ if constructor == Some("Object") => {
+                if !tag.is_empty() {
+                    braces.0 = format!("{}{{", prefix(constructor, &tag, "Object", ""));
+                }
+                if keys.is_empty() {
+                    return format!("{}}}", braces.0);
+                }
+            }
+            (_, Some(ObjectData::RegExp(state))) => {
+                base = state.literal();
+                let prefix = prefix(constructor, &tag, "RegExp", "");
+                if prefix != "RegExp " {
+                    base = format!("{prefix}{base}");
+                }
+                if keys.is_empty() || beyond_depth {
+                    return self.stylize(&base, Style::RegExp);
+                }
+            }
+            (_, Some(ObjectData::Date(time))) => {
+                base = match time.is_nan() {
+                    true => "Invalid Date".to_string(),
+                    false => iso_string(*time),
+                };
+                let prefix = prefix(constructor, &tag, "Date", "");
+                if prefix != "Date " {
+                    base = format!("{prefix}{base}");
+                }
+                if keys.is_empty() {
+                    return self.stylize(&base, Style::Date);
+                }
+            }
+            (_, Some(ObjectData::ArrayBuffer(_))) => {
+                braces.0 = format!("{}{{", prefix(constructor, &tag, "ArrayBuffer", ""));
+                entries = Entries::ArrayBuffer(object_id);
+            }
+            (_, Some(ObjectData::Promise(state))) => {
+                braces.0 = format!("{}{{", prefix(constructor, &tag, "Promise", ""));
+                entries = Entries::Promise(state);
+            }
+            (_, Some(ObjectData::WeakSet(_) | ObjectData::WeakMap(_))) => {
+                let fallback = internal_name(object);
+                braces.0 = format!("{}{{", prefix(constructor, &tag, fallback, ""));
+                entries = Entries::WeakCollection;
+            }
+            (
+                _,
+                Some(
+                    data @ (ObjectData::Number(_)
+                    | ObjectData::Boolean(_)
+                    | ObjectData::String(_)
+                    | ObjectData::Symbol(_)
+                    | ObjectData::BigInt(_)),
+                ),
+            ) => {
+                let (name, primitive, style) = match data {
+                    ObjectData::Number(number) => ("Number", format_number(*number), Style::Number),
+                    ObjectData::Boolean(data) => ("Boolean", data.to_string(), Style::Boolean),
+                    ObjectData::String(data) => {
+                        keys.retain(|key| array_index(key).is_none());
+                        let string = get_string_from_pool(data).unwrap_or_default();
+                        ("String", quote_string(&string), Style::String)
+                    }
+                    ObjectData::Symbol(symbol) => ("Symbol", symbol.to_string(), Style::Symbol),
+                    ObjectData::BigInt(data) => {
+                        let bigint = format!("{}n", bigint::to_string(data, 10));
+                        ("BigInt", bigint, Style::BigInt)
+                    }
+                    _ => unreachable!("only primitive wrappers get here"),
+                };
+                base = format!("[{name}");
+                match constructor {
+                    Some(constructor) if constructor == name => {}
+                    Some(constructor) => base.push_str(&format!(" ({constructor})")),
+                    None => base.push_str(" (null prototype)"),
+                }
+                base.push_str(&format!(": {primitive}]"));
+                if !tag.is_empty() && Some(tag.as_str()) != constructor {
+                    base.push_str(&format!(" [{tag}]"));
+                }
+                if keys.is_empty() {
+                    return self.stylize(&base, style);
+                }
+            }
+            _ => {
+                let style = self.context_style(object, constructor, &tag);
+                if keys.is_empty() {
+                    return format!("{style}{{}}");
+                }
+                braces.0 = format!("{style}{{");
+            }
+        }
+
+        if beyond_depth {
+            let mut name = self.context_style(object, constructor, &tag);
+            name.pop();
+            if constructor.is_some() {
+                name = format!("[{name}]");
+            }
+            return self.stylize(&name, Style::Special);
+        }
+        let recurse_times = recurse_times + 1;
+
+        self.seen.push(object_id);
+        self.current_depth = recurse_times;
+        let mut output = self.format_entries(&entries, recurse_times);
+        for key in &keys {
+            output.push(self.format_property(object, key, recurse_times));
+        }
+        if let Some(position) = self.circular.iter().position(|id| *id == object_id) {
+            let reference = self.stylize(&format!("<ref *{}>", position + 1), Style::Special);
+            base = match base.is_empty() {
+                true => reference,
+                false => format!("{reference} {base}"),
+            };
+        }
+        self.seen.pop();
+
+        // numbers line up on the right when grouped into columns, anything else on the left
+        let numeric = match entries {
+            Entries::Array(array, _) => (0..output.len()).all(|index| {
+                let key = PropertyKey::from_name(&index.to_string());
+                matches!(
+                    array.get_property(&key),
+                    Some(ObjectProperty::Data {
+                        value: JSValue::Number { .. } | JSValue::BigInt { .. },
+                        ..
+                    })
+                )
+            }),
+            _ => true,
+        };
+        self.reduce_to_single_string(output, &base, &braces, extras, recurse_times, numeric)
+    }
+
+    fn format_entries(&mut self, entries: &Entries<'a>, recurse_times: usize) -> Vec<String> {
+        let max_length = self.options.max_array_length;
+        let mut output = vec![];
+        match entries {
+            Entries::None => {}
+            Entries::Array(array, length) => {
+                self.format_array(array, *length, recurse_times, &mut output);
+            }
+            Entries::TypedArray(state, length) => {
+                for index in 0..max_length.min(*length) {
+                    let element = typed_array_get_element(self.interpreter, state, index as f64);
+                    output.push(self.format_primitive(&element));
+                }
+                if *length > max_length {
+                    output.push(remaining_text(length - max_length));
+                }
+            }
+            Entries::Set(table) => {
+                self.indentation += 2;
+                for (value, _) in table.iter().take(max_length) {
+                    output.push(self.format_value(value, recurse_times));
+                }
+                self.indentation -= 2;
+                if table.len() > max_length {
+                    output.push(remaining_text(table.len() - max_length));
+                }
+            }
+            Entries::Map(table) => {
+                self.indentation += 2;
+                for (key, value) in table.iter().take(max_length) {
+                    let key = self.format_value(key, recurse_times);
+                    let value = self.format_value(value, recurse_times);
+                    output.push(format!("{key} => {value}"));
+                }
+                self.indentation -= 2;
+                if table.len() > max_length {
+                    output.push(remaining_text(table.len() - max_length));
+                }
+            }
+            Entries::ArrayBuffer(object_id) => {
+                let state = array_buffer_state(self.interpreter, *object_id);
+                match state.filter(|state| !state.is_detached()) {
+                    Some(state) => {
+                        let bytes = state.bytes();
+                        let mut contents: Vec<String> = bytes
+                            .iter()
+                            .take(max_length)
+                            .map(|byte| format!("{byte:02x}"))
+                            .collect();
+                        if bytes.len() > max_length {
+                            let remaining = bytes.len() - max_length;
+                            let ending = if remaining > 1 { "s" } else { "" };
+                            contents.push(format!("... {remaining} more byte{ending}"));
+                        }
+                        let label = self.stylize("[Uint8Contents]", Style::Special);
+                        output.push(format!("{label}: <{}>", contents.join(" ")));
+                    }
+                    None => output.push(self.stylize("(detached)", Style::Special)),
+                }
+                let byte_length = state.map_or(0, |state| state.byte_length());
+                let byte_length = self.stylize(&byte_length.to_string(), Style::Number);
+                output.push(format!("byteLength: {byte_length}"));
+            }
+            Entries::Promise(state) => match state.result() {
+                None => output.push(self.stylize("<pending>", Style::Special)),
+                Some(result) => {
+                    self.indentation += 2;
+                    let value =
+                        self.format_value(result.unwrap_or_else(|reason| reason), recurse_times);
+                    self.indentation -= 2;
+                    match result {
+                        Ok(_) => output.push(value),
+                        Err(_) => {
+                            let label = self.stylize("<rejected>", Style::Special);
+                            output.push(format!("{label} {value}"));
+                        }
+                    }
+                }
+            },
+            Entries::WeakCollection => {
+                output.push(self.stylize("<items unknown>", Style::Special));
+            }
+        }
+        output
+    }
+
+    /// The elements of an array, with runs of holes shown as `<n empty items>`.
+    fn format_array(
+        &mut self,
+        array: &'a JSObject,
+        length: usize,
+        recurse_times: usize,
+        output: &mut Vec<String>,
+    ) {
+        let max_length = self.options.max_array_length.min(length);
+        let mut indices: Vec<usize> = array
+            .property_keys()
+            .iter()
+            .filter_map(array_index)
+            .filter(|index| *index < length)
+            .collect();
+        indices.sort_unstable();
+        let mut next = 0;
+        for index in indices {
+            if output.len() >= max_length {
+                break;
+            }
+            if index != next {
+                output.push(self.empty_items(index - next));
+                next = index;
+                if output.len() == max_length {
+                    break;
+                }
+            }
+            let key = PropertyKey::from_name(&index.to_string());
+            let element = self.format_property_value(array, &key, recurse_times);
+            output.push(element);
+            next += 1;
+        }
+        let remaining = length - next;
+        if output.len() != max_length {
+            if remaining > 0 {
+                output.push(self.empty_items(remaining));
+            }
+        } else if remaining > 0 {
+            output.push(remaining_text(remaining));
+        }
+    }
+
+    fn empty_items(&self, count: usize) -> String {
+        let ending = if count > 1 { "s" } else { "" };
+        self.stylize(&format!("<{count} empty item{ending}>"), Style::Undefined)
+    }
+
+    fn format_property_value(
+        &mut self,
+        object: &'a JSObject,
+        key: &PropertyKey,
+        recurse_times: usize,
+    ) -> String {
+        match object.get_property(key) {
+            Some(ObjectProperty::Data { value, .. }) => {
+                self.indentation += 2;
+                let value = self.format_value(value, recurse_times);
+                self.indentation -= 2;
+                value
+            }
+            Some(accessor @ ObjectProperty::Accessor { get, set, .. }) => {
+                let style = match get.is_some() || set.is_some() {
+                    true => Style::Special,
+                    false => Style::Undefined,
+                };
+                self.stylize(accessor.accessor_label(), style)
+            }
+            None => self.stylize("undefined", Style::Undefined),
+        }
+    }
+
+    fn format_property(
+        &mut self,
+        object: &'a JSObject,
+        key: &PropertyKey,
+        recurse_times: usize,
+    ) -> String {
+        let value = self.format_property_value(object, key, recurse_times);
+        let name = match key {
+            PropertyKey::Symbol { .. } => {
+                format!("[{}]", self.stylize(&key.to_string(), Style::Symbol))
+            }
+            PropertyKey::String(_) => {
+                let name = key.to_string();
+                if name == "__proto__" {
+                    "['__proto__']".to_string()
+                } else if is_identifier(&name) {
+                    name
+                } else {
+                    self.stylize(&quote_string(&name), Style::String)
+                }
+            }
+        };
+        format!("{name}: {value}")
+    }
+
+    fn format_primitive(&self, value: &JSValue) -> String {
+        match value {
+            JSValue::String { data } => {
+                let string = get_string_from_pool(data).unwrap_or_default();
+                let length = string.encode_utf16().count();
+                let limit = self
+                    .options
+                    .break_length
+                    .saturating_sub(self.indentation + 4);
+                if length > MIN_LINE_LENGTH && length > limit {
+                    let separator = format!(" +\n{}", " ".repeat(self.indentation + 2));
+                    let lines: Vec<String> = string
+                        .split_inclusive('\n')
+                        .map(|line| self.stylize(&quote_string(line), Style::String))
+                        .collect();
+                    return lines.join(&separator);
+                }
+                self.stylize(&quote_string(&string), Style::String)
+            }
+            JSValue::Number { data } => self.stylize(&format_number(*data), Style::Number),
+            JSValue::BigInt { data } => {
+                self.stylize(&format!("{}n", bigint::to_string(data, 10)), Style::BigInt)
+            }
+            JSValue::Boolean { data } => self.stylize(&data.to_string(), Style::Boolean),
+            JSValue::Undefined => self.stylize("undefined", Style::Undefined),
+            JSValue::Null => self.stylize("null", Style::Null),
+            JSValue::Symbol { id, description } => {
+                let symbol = PropertyKey::Symbol {
+                    id: *id,
+                    description: *description,
+                };
+                self.stylize(&symbol.to_string(), Style::Symbol)
+            }
+            JSValue::Object { .. } => unreachable!("objects aren't primitives"),
+        }
+    }
+
+    /// `[Function: name]`, `[AsyncFunction (anonymous)]` or `[class A extends B]`.
+    fn function_base(
+        &self,
+        object_id: ObjectId,
+        is_class: bool,
+        body_kind: BodyKind,
+        constructor: Option<&str>,
+        tag: &str,
+    ) -> String {
+        if is_class {
+            let name = self.own_name(object_id).filter(|name| !name.is_empty());
+            let mut base = format!("class {}", name.as_deref().unwrap_or("(anonymous)"));
+            if let Some(constructor) = constructor.filter(|name| *name != "Function") {
+                base.push_str(&format!(" [{constructor}]"));
+            }
+            if !tag.is_empty() && Some(tag) != constructor {
+                base.push_str(&format!(" [{tag}]"));
+            }
+            match constructor {
+                Some(_) => {
+                    let parent = self
+                        .object(object_id)
+                        .and_then(|object| object.get_prototype());
+                    let parent = parent.map(|parent| self.name(parent)).unwrap_or_default();
+                    if !parent.is_empty() {
+                        base.push_str(&format!(" extends {parent}"));
+                    }
+                }
+                None => base.push_str(" extends [null prototype]"),
+            }
+            return format!("[{base}]");
+        }
+
+        let kind = match body_kind {
+            BodyKind::Normal => "Function",
+            BodyKind::Generator => "GeneratorFunction",
+            BodyKind::Async => "AsyncFunction",
+        };
+        let mut base = format!("[{kind}");
+        if constructor.is_none() {
+            base.push_str(" (null prototype)");
+        }
+        match self.name(object_id) {
+            name if name.is_empty() => base.push_str(" (anonymous)"),
+            name => base.push_str(&format!(": {name}")),
+        }
+        base.push(']');
+        if let Some(constructor) = constructor.filter(|name| *name != kind) {
+            base.push_str(&format!(" {constructor}"));
+        }
+        if !tag.is_empty() && Some(tag) != constructor {
+            base.push_str(&format!(" [{tag}]"));
+        }
+        base
+    }
+
+    /// The data property `key` of the object or the first of its prototypes that has it.
+    fn lookup(
+        &self,
+        object_id: ObjectId,
+        key: &PropertyKey,
+    ) -> Option<(ObjectId, &'a ObjectProperty)> {
+        let mut current = Some(object_id);
+        while let Some(object_id) = current {
+            let object = self.object(object_id)?;
+            if let Some(property) = object.get_property(key) {
+                return Some((object_id, property));
+            }
+            current = object.get_prototype();
+        }
+        None
+    }
+
+    fn own_name(&self, object_id: ObjectId) -> Option<String> {
+        let key = PropertyKey::from_name(NAME_NAME);
+        match self.object(object_id)?.get_property(&key)? {
+            ObjectProperty::Data {
+                value: JSValue::String { data },
+                ..
+            } => get_string_from_pool(data),
+            _ => None,
+        }
+    }
+
+    /// The `name` of a function, own or inherited, or nothing when it isn't a string.
+    fn name(&self, object_id: ObjectId) -> String {
+        let key = PropertyKey::from_name(NAME_NAME);
+        match self.lookup(object_id, &key) {
+            Some((
+                _,
+                ObjectProperty::Data {
+                    value: JSValue::String { data },
+                    ..
+                },
+            )) => get_string_from_pool(data).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    /// The name of the closest `constructor` up the prototype chain that the object is an
+    /// instance of, `None` for objects without a prototype.
+    fn constructor_name(&self, object_id: ObjectId) -> Option<String> {
+        let key = PropertyKey::from_name(CONSTRUCTOR_NAME);
+        let mut current = Some(object_id);
+        let mut first_prototype = None;
+        while let Some(id) = current {
+            let object = self.object(id)?;
+            // arrays and functions inherit from `Object.prototype` directly, there being no
+            // `Array` or `Function` to construct them
+            let kind = self.object(object_id).map_or("Object", internal_name);
+            if id == self.interpreter.intrinsics.object_proto_id && kind != "Object" {
+                return Some(kind.to_string());
+            }
+            if let Some(ObjectProperty::Data {
+                value:
+                    JSValue::Object {
+                        object_id: constructor,
+                        ..
+                    },
+                ..
+            }) = object.get_property(&key)
+            {
+                let is_function = matches!(self.object(*constructor), Some(JSObject::Function(_)));
+                let name = self.name(*constructor);
+                if is_function && !name.is_empty() && self.is_instance(object_id, *constructor) {
+                    return Some(name);
+                }
+            }
+            current = object.get_prototype();
+            first_prototype.get_or_insert(current);
+        }
+        match first_prototype {
+            Some(None) => None,
+            _ => self
+                .object(object_id)
+                .map(|object| internal_name(object).to_string()),
+        }
+    }
+
+    fn is_instance(&self, object_id: ObjectId, constructor: ObjectId) -> bool {
+        let key = PropertyKey::from_name(PROTOTYPE_NAME);
+        let prototype = match self
+            .object(constructor)
+            .and_then(|object| object.get_property(&key))
+        {
+            Some(ObjectProperty::Data {
+                value: JSValue::Object { object_id, .. },
+                ..
+            }) => *object_id,
+            _ => return false,
+        };
+        let mut current = self
+            .object(object_id)
+            .and_then(|object| object.get_prototype());
+        while let Some(id) = current {
+            if id == prototype {
+                return true;
+            }
+            current = self.object(id).and_then(|object| object.get_prototype());
+        }
+        false
+    }
+
+    /// `Symbol.toStringTag`, unless it is an own enumerable property the keys already show.
+    fn to_string_tag(&self, object_id: ObjectId) -> String {
+        let key = PropertyKey::well_known(WellKnownSymbol::ToStringTag);
+        match self.lookup(object_id, &key) {
+            Some((owner, property)) if owner == object_id && property.is_enumerable() => {
+                String::new()
+            }
+            Some((
+                _,
+                ObjectProperty::Data {
+                    value: JSValue::String { data },
+                    ..
+                },
+            )) => get_string_from_pool(data).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    fn enumerable_keys(&self, object: &JSObject) -> Vec<PropertyKey> {
+        object
+            .property_keys()
+            .into_iter()
+            .filter(|key| object.get_property(key).is_some_and(|p| p.is_enumerable()))
+            .collect()
+    }
+
+    /// What goes in front of the braces of an object that isn't special in any other way,
+    /// with a trailing space.
+    fn context_style(&self, object: &JSObject, constructor: Option<&str>, tag: &str) -> String {
+        let fallback = match constructor {
+            Some(_) => "",
+            None => match internal_name(object) {
+                name if name == tag => "Object",
+                name => name,
+            },
+        };
+        prefix(constructor, tag, fallback, "")
+    }
+
+    fn reduce_to_single_string(
+        &self,
+        mut output: Vec<String>,
+        base: &str,
+        braces: &(String, &str),
+        extras: Extras,
+        recurse_times: usize,
+        numeric: bool,
+    ) -> String {
+        let base = match base.is_empty() {
+            true => String::new(),
+            false => format!("{base} "),
+        };
+        if self.options.compact >= 1 {
+            let entries = output.len();
+            if extras == Extras::Array && entries > 6 {
+                output = self.group_array_elements(output, numeric);
+            }
+            // only the innermost levels of nesting go on a single line
+            let depth = self.current_depth.saturating_sub(recurse_times);
+            if depth < self.options.compact && entries == output.len() {
+                let start = output.len()
+                    + self.indentation
+                    + braces.0.chars().count()
+                    + base.chars().count()
+                    + 10;
+                if self.is_below_break_length(&output, start, &base) {
+                    let joined = output.join(", ");
+                    if !joined.contains('\n') {
+                        return format!("{base}{} {joined} {}", braces.0, braces.1);
+                    }
+                }
+            }
+        }
+        let indentation = format!("\n{}", " ".repeat(self.indentation));
+        let joined = output.join(&format!(",{indentation}  "));
+        format!(
+            "{base}{}{indentation}  {joined}{indentation}{}",
+            braces.0, braces.1
+        )
+    }
+
+    fn is_below_break_length(&self, output: &[String], start: usize, base: &str) -> bool {
+        let break_length = self.options.break_length;
+        let mut total_length = output.len() + start;
+        if total_length + output.len() > break_length {
+            return false;
+        }
+        for entry in output {
+            total_length += self.width(entry);
+            if total_length > break_length {
+                return false;
+            }
+        }
+        !base.contains('\n')
+    }
+
+    /// Puts the elements of longer arrays into columns, as many as fit a roughly square block.
+    fn group_array_elements(&self, output: Vec<String>, numeric: bool) -> Vec<String> {
+        let separator_space = 2;
+        let mut output_length = output.len();
+        // the "... more items" entry isn't part of any column
+        if self.options.max_array_length < output.len() {
+            output_length -= 1;
+        }
+        let data_length: Vec<usize> = output[..output_length]
+            .iter()
+            .map(|entry| self.width(entry))
+            .collect();
+        let total_length: usize = data_length.iter().map(|len| len + separator_space).sum();
+        let max_length = data_length.iter().copied().max().unwrap_or(0);
+        let actual_max = max_length + separator_space;
+        let break_length = self.options.break_length;
+        if actual_max * 3 + self.indentation >= break_length
+            || (total_length as f64 / actual_max as f64 <= 5.0 && max_length > 6)
+        {
+            return output;
+        }
+
+        let approx_char_heights = 2.5;
+        let average_bias = (actual_max as f64 - total_length as f64 / output.len() as f64).sqrt();
+        let biased_max = (actual_max as f64 - 3.0 - average_bias).max(1.0);
+        let columns = [
+            ((approx_char_heights * biased_max * output_length as f64).sqrt() / biased_max).round(),
+            ((break_length - self.indentation) as f64 / actual_max as f64).floor(),
+            (self.options.compact * 4) as f64,
+            15.0,
+        ]
+        .into_iter()
+        .fold(f64::INFINITY, f64::min) as usize;
+        if columns <= 1 {
+            return output;
+        }
+        let max_line_length: Vec<usize> = (0..columns)
+            .map(|column| {
+                let widest = data_length
+                    .iter()
+                    .skip(column)
+                    .step_by(columns)
+                    .copied()
+                    .max()
+                    .unwrap_or(0);
+                widest + separator_space
+            })
+            .collect();
+        let mut grouped = vec![];
+        for start in (0..output_length).step_by(columns) {
+            let end = (start + columns).min(output_length);
+            let mut line = String::new();
+            for index in start..end {
+                let column = index - start;
+                // the escape codes take up no room but count towards the padding
+                let padding =
+                    max_line_length[column] + output[index].chars().count() - data_length[index];
+                let entry = match index == end - 1 {
+                    true if !numeric => {
+                        line.push_str(&output[index]);
+                        continue;
+                    }
+                    true => {
+                        let padding = padding - separator_space;
+                        pad(&output[index], padding, numeric)
+                    }
+                    false => pad(&format!("{}, ", output[index]), padding, numeric),
+                };
+                line.push_str(&entry);
+            }
+            grouped.push(line);
+        }
+        if output_length < output.len() {
+            grouped.push(output[output_length].clone());
+        }
+        grouped
+    }
+}
+
+/// `Constructor(size) [tag] `, or `[fallback(size): null prototype] ` without a constructor.
+fn prefix(constructor: Option<&str>, tag: &str, fallback: &str, size: &str) -> String {
+    match constructor {
+        None if !tag.is_empty() && fallback != tag => {
+            format!("[{fallback}{size}: null prototype] [{tag}] ")
+        }
+        None => format!("[{fallback}{size}: null prototype] "),
+        Some(constructor) if !tag.is_empty() && constructor != tag => {
+            format!("{constructor}{size} [{tag}] ")
+        }
+        Some(constructor) => format!("{constructor}{size} "),
+    }
+}
+
+/// The kind of object, for objects that don't have a constructor to name them.
+fn internal_name(object: &JSObject) -> &'static str {
+    match object {
+        JSObject::Array(_) => "Array",
+        JSObject::Function(function) => match function.body_kind() {
+            BodyKind::Normal => "Function",
+            BodyKind::Generator => "GeneratorFunction",
+            BodyKind::Async => "AsyncFunction",
+        },
+        _ => match object.get_data() {
+            Some(ObjectData::Map(_)) => "Map",
+            Some(ObjectData::Set(_)) => "Set",
+            Some(ObjectData::WeakMap(_)) => "WeakMap",
+            Some(ObjectData::WeakSet(_)) => "WeakSet",
+            Some(ObjectData::Promise(_)) => "Promise",
+            Some(ObjectData::Date(_)) => "Date",
+            Some(ObjectData::RegExp(_)) => "RegExp",
+            Some(ObjectData::Number(_)) => "Number",
+            Some(ObjectData::Boolean(_)) => "Boolean",
+            Some(ObjectData::String(_)) => "String",
+            Some(ObjectData::Symbol(_)) => "Symbol",
+            Some(ObjectData::BigInt(_)) => "BigInt",
+            Some(ObjectData::ArrayBuffer(_)) => "ArrayBuffer",
+            Some(ObjectData::DataView(_)) => "DataView",
+            Some(ObjectData::TypedArray(state)) => state.kind.name(),
+            _ => "Object",
+        },
+    }
+}
+
+fn remaining_text(remaining: usize) -> String {
+    let ending = if remaining > 1 { "s" } else { "" };
+    format!("... {remaining} more item{ending}")
+}
+
+fn pad(text: &str, width: usize, at_start: bool) -> String {
+    let padding = " ".repeat(width.saturating_sub(text.chars().count()));
+    match at_start {
+        true => format!("{padding}{text}"),
+        false => format!("{text}{padding}"),
+    }
+}
+
+/// `-0` keeps its sign, unlike when converted to a string.
+fn format_number(number: f64) -> String {
+    match number == 0.0 && number.is_sign_negative() {
+        true => "-0".to_string(),
+        false => values::to_string(number, 10),
+    }
+}
+
+/// Keys that can go unquoted.
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The string in single quotes, or in double quotes or backticks when that saves escaping the
+/// single quotes in it. Control characters and backslashes are escaped.
+fn quote_string(string: &str) -> String {
+    let quote = match string.contains('\'') {
+        false => '\'',
+        true if !string.contains('"') => '"',
+        true if !string.contains('`') && !string.contains("${") => '`',
+        true => '\'',
+    };
+    let mut quoted = String::from(quote);
+    for c in string.chars() {
+        match c {
+            '\'' if quote == '\'' => quoted.push_str("\\'"),
+            '\\' => quoted.push_str("\\\\"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\r' => quoted.push_str("\\r"),
+            c if c < ' ' || ('\u{7f}'..'\u{a0}').contains(&c) => {
+                quoted.push_str(&format!("\\x{:02X}", c as u32));
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push(quote);
+    quoted
+}
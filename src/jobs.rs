@@ -0,0 +1,79 @@
+//! Jobs that run once the current script has finished, i.e. microtasks. Promise reactions,
+//! `queueMicrotask` callbacks and dynamic imports are queued here and run in the order they were
+//! queued.
+
+use crate::{
+    Interpreter,
+    builtins::promise::{PromiseReaction, promise_reaction_job, resolve_thenable_job},
+    heap::{Trace, Tracer},
+    modules::dynamic_import_job,
+    values::{JSResult, JSValue},
+};
+
+#[derive(Clone, Debug)]
+pub enum Job {
+    // https://262.ecma-international.org/15.0/index.html#sec-newpromisereactionjob
+    PromiseReaction {
+        reaction: PromiseReaction,
+        argument: JSValue,
+    },
+    // https://262.ecma-international.org/15.0/index.html#sec-newpromiseresolvethenablejob
+    ResolveThenable {
+        promise: JSValue,
+        thenable: JSValue,
+        then: JSValue,
+    },
+    /// A callback passed to `queueMicrotask`.
+    Callback(JSValue),
+    /// Loads the module of an `import()` and settles its promise.
+    DynamicImport {
+        specifier: String,
+        referrer: Option<String>,
+        promise: JSValue,
+    },
+}
+
+impl Trace for Job {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            Job::PromiseReaction { reaction, argument } => {
+                reaction.trace(tracer);
+                argument.trace(tracer);
+            }
+            Job::ResolveThenable {
+                promise,
+                thenable,
+                then,
+            } => {
+                promise.trace(tracer);
+                thenable.trace(tracer);
+                then.trace(tracer);
+            }
+            Job::Callback(callback) => callback.trace(tracer),
+            Job::DynamicImport { promise, .. } => promise.trace(tracer),
+        }
+    }
+}
+
+impl Job {
+    pub fn run(self, interpreter: &mut Interpreter) -> JSResult<()> {
+        match self {
+            Job::PromiseReaction { reaction, argument } => {
+                promise_reaction_job(interpreter, reaction, argument)
+            }
+            Job::ResolveThenable {
+                promise,
+                thenable,
+                then,
+            } => resolve_thenable_job(interpreter, promise, thenable, then),
+            Job::Callback(callback) => interpreter
+                .call_function(&callback, &JSValue::Undefined, &[])
+                .map(|_| ()),
+            Job::DynamicImport {
+                specifier,
+                referrer,
+                promise,
+            } => dynamic_import_job(interpreter, &specifier, referrer.as_deref(), promise),
+        }
+    }
+}
@@ -50,15 +50,21 @@ impl<'a> Lexer<'a> {
                             _ => break,
                         }
                     }
-                    self.add_token(Kind::Number);
+                    // a trailing n makes it a BigInt literal
+                    if self.current_char == 'n' {
+                        self.next_char();
+                        self.add_token(Kind::BigInt);
+                    } else {
+                        self.add_token(Kind::Number);
+                    }
                 }
 
                 // identifier
-                'a'..='z' => {
+                'a'..='z' | '_' | '$' => {
                     let mut ident = String::new();
                     loop {
                         match self.current_char.to_ascii_lowercase() {
-                            'a'..='z' | '_' | '0'..'9' => {
+                            'a'..='z' | '_' | '$' | '0'..='9' => {
                                 ident.push(self.current_char);
                                 self.next_char();
                             }
@@ -88,7 +94,18 @@ impl<'a> Lexer<'a> {
                     self.next_char();
                 }
                 '.' => {
-                    self.add_token_and_advance(Kind::Dot);
+                    if self.check_peeked_char('.') {
+                        self.next_char();
+                        if self.check_peeked_char('.') {
+                            self.next_char();
+                            self.add_token_and_advance(Kind::Ellipsis);
+                        } else {
+                            self.report_error("Unknown token: ..");
+                            self.next_char();
+                        }
+                    } else {
+                        self.add_token_and_advance(Kind::Dot);
+                    }
                 }
                 '+' => {
                     if self.check_peeked_char('+') {
@@ -134,6 +151,8 @@ impl<'a> Lexer<'a> {
                         }
                         self.line += 1;
                         self.next_char();
+                    } else if self.regexp_allowed() {
+                        self.lex_regexp();
                     } else {
                         let is_equals = self.check_peeked_char('=');
                         if is_equals {
@@ -223,12 +242,7 @@ impl<'a> Lexer<'a> {
                             self.add_token_and_advance(Kind::BitwiseOrEquals);
                         }
                         _ => {
-                            if peeked.is_whitespace() {
-                                self.add_token_and_advance(Kind::BitwiseOr);
-                            } else {
-                                self.next_char();
-                                self.report_error(&format!("Unknown token: |{}", peeked));
-                            }
+                            self.add_token_and_advance(Kind::BitwiseOr);
                         }
                     }
                 }
@@ -254,18 +268,20 @@ impl<'a> Lexer<'a> {
                             self.add_token_and_advance(Kind::BitwiseAndEquals);
                         }
                         _ => {
-                            if peeked.is_whitespace() {
-                                self.add_token_and_advance(Kind::BitwiseAnd);
-                            } else {
-                                self.next_char();
-                                self.report_error(&format!("Unknown token: &{}", peeked));
-                            }
+                            self.add_token_and_advance(Kind::BitwiseAnd);
                         }
                     }
                 }
                 '<' => {
-                    let is_equals = self.check_peeked_char('=');
-                    if is_equals {
+                    if self.check_peeked_char('<') {
+                        self.next_char();
+                        if self.check_peeked_char('=') {
+                            self.next_char();
+                            self.add_token_and_advance(Kind::LeftShiftEquals);
+                        } else {
+                            self.add_token_and_advance(Kind::LeftShift);
+                        }
+                    } else if self.check_peeked_char('=') {
                         self.next_char();
                         self.add_token_and_advance(Kind::LessThanOrEquals);
                     } else {
@@ -273,14 +289,40 @@ impl<'a> Lexer<'a> {
                     }
                 }
                 '>' => {
-                    let is_equals = self.check_peeked_char('=');
-                    if is_equals {
+                    if self.check_peeked_char('>') {
+                        self.next_char();
+                        if self.check_peeked_char('>') {
+                            self.next_char();
+                            if self.check_peeked_char('=') {
+                                self.next_char();
+                                self.add_token_and_advance(Kind::UnsignedRightShiftEquals);
+                            } else {
+                                self.add_token_and_advance(Kind::UnsignedRightShift);
+                            }
+                        } else if self.check_peeked_char('=') {
+                            self.next_char();
+                            self.add_token_and_advance(Kind::SignedRightShiftEquals);
+                        } else {
+                            self.add_token_and_advance(Kind::SignedRightShift);
+                        }
+                    } else if self.check_peeked_char('=') {
                         self.next_char();
                         self.add_token_and_advance(Kind::GreaterThanOrEquals);
                     } else {
                         self.add_token_and_advance(Kind::GreaterThan);
                     }
                 }
+                '^' => {
+                    if self.check_peeked_char('=') {
+                        self.next_char();
+                        self.add_token_and_advance(Kind::BitwiseXorEquals);
+                    } else {
+                        self.add_token_and_advance(Kind::BitwiseXor);
+                    }
+                }
+                '~' => {
+                    self.add_token_and_advance(Kind::BitwiseNot);
+                }
                 '[' => {
                     self.add_token_and_advance(Kind::LeftSquare);
                 }
@@ -306,10 +348,8 @@ impl<'a> Lexer<'a> {
         self.errors.len() > 0
     }
 
-    pub fn replay_errors(&mut self) {
-        for error in &self.errors {
-            eprintln!("{error}");
-        }
+    pub fn errors(&self) -> &[LexerError] {
+        &self.errors
     }
 
     fn next_char(&mut self) -> char {
@@ -378,6 +418,57 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// A `/` divides after anything that ends an expression and starts a regular expression
+    /// literal everywhere else.
+    fn regexp_allowed(&self) -> bool {
+        let Some(previous) = self.tokens.last() else {
+            return true;
+        };
+        !matches!(
+            previous.get_kind(),
+            Kind::Identifier
+                | Kind::Number
+                | Kind::BigInt
+                | Kind::String
+                | Kind::RegExp
+                | Kind::RightParen
+                | Kind::RightSquare
+                | Kind::RightCurly
+                | Kind::This
+                | Kind::Super
+                | Kind::True
+                | Kind::False
+                | Kind::Null
+        )
+    }
+
+    /// Scans `/pattern/flags` as one token, the parser splits it up. A `/` inside a class or
+    /// after a backslash doesn't end the pattern.
+    fn lex_regexp(&mut self) {
+        let mut in_class = false;
+        loop {
+            match self.next_char() {
+                '\0' | '\n' => {
+                    self.report_error("Invalid regular expression: missing /");
+                    return;
+                }
+                '\\' => {
+                    if matches!(self.next_char(), '\0' | '\n') {
+                        self.report_error("Invalid regular expression: missing /");
+                        return;
+                    }
+                }
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => break,
+                _ => {}
+            }
+        }
+        // the flags
+        while self.next_char().is_alphanumeric() {}
+        self.add_token(Kind::RegExp);
+    }
+
     fn check_peeked_char(&mut self, check_char: char) -> bool {
         let peeked = self.peek_next_char();
         if let Some(&ch) = peeked {
@@ -1,78 +1,354 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    sync::{
+        Weak,
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+    },
+};
+
 use log::{debug, info, trace};
 use string_interner::{Symbol, symbol::SymbolU32};
 
 use crate::{
-    constants::GLOBAL_THIS_NAME,
+    constants::{GLOBAL_THIS_NAME, PROTOTYPE_NAME},
     environment::Environment,
-    errors::JSError,
-    global::{get_or_intern_string, get_string_from_pool},
-    heap::{Heap, HeapId},
+    errors::{ErrorKind, JSError},
+    global::{Isolate, get_or_intern_string, get_string_from_pool},
+    handles::GlobalSlot,
+    heap::{GcWork, Heap, HeapId, Trace, Tracer},
+    jobs::Job,
     lexer::Lexer,
+    modules::ModuleRecord,
     parser::Parser,
+    random::Random,
+    realm::{Intrinsics, RealmRecord},
+    resume::ResumeRecord,
     span::Span,
+    timers::{Clock, TimerQueue},
     token::Token,
-    values::{JSObject, JSResult, JSValue, equal, same_value},
+    values::{
+        JSObject, JSResult, JSValue, ObjectKind, PropertyKey, WellKnownSymbol, bigint, equal,
+        get_property_value, internal, same_value,
+    },
     variable::Variable,
 };
 
+mod builtins;
+mod commonjs;
+mod console;
 mod constants;
+mod date;
 mod environment;
 mod errors;
 mod expr;
 mod global;
+mod handles;
 mod heap;
+mod host;
+mod inspect;
+mod jobs;
 mod lexer;
+mod modules;
 mod parser;
+mod random;
+mod realm;
+mod regexp;
+mod resume;
+#[cfg(feature = "serde")]
+mod serde_support;
 mod span;
 mod stmt;
+mod timers;
 mod token;
 mod utils;
 mod values;
 mod variable;
 
+pub use console::{BufferConsole, Console, ConsoleMessage, LogLevel, StdoutConsole};
+pub use date::timezone::TimeZone;
+pub use handles::{
+    Array, Function, Global, Handle, HandleKind, HandleScope, Local, Object, Persistent, Value,
+};
+pub use heap::{GcOptions, HeapStats};
+pub use host::{HostFunction, NativeObject};
+pub use inspect::InspectOptions;
+pub use modules::{FileSystemLoader, ModuleLoader};
+pub use realm::Realm;
+#[cfg(feature = "serde")]
+pub use serde_support::SerdeError;
+
+static NEXT_INTERPRETER_ID: AtomicUsize = AtomicUsize::new(0);
+
 pub struct Interpreter {
+    /// Tells the handles of this interpreter from those of others.
+    id: usize,
     environment_stack: Vec<usize>,
     heap: Heap,
-    object_proto_id: usize,
-    function_proto_id: usize,
-    output_buffer: String,
-    error_buffer: String,
+    /// The strings and symbol ids of this interpreter, entered by everything the host calls.
+    isolate: Isolate,
+    /// The intrinsics of the current realm.
+    intrinsics: Intrinsics,
+    /// Every realm set up so far, `realm` is the index of the current one.
+    realms: Vec<RealmRecord>,
+    realm: usize,
+    /// The symbols `Symbol.for` handed out, by key.
+    symbol_registry: HashMap<SymbolU32, JSValue>,
+    /// Microtasks waiting to run once the current script is done.
+    job_queue: VecDeque<Job>,
+    /// Promises rejected while nothing handled them, reported if that is still true once the
+    /// job queue is empty.
+    pending_rejections: Vec<JSValue>,
+    /// Timers and immediates, the macrotasks of the event loop.
+    timers: TimerQueue,
+    /// The generator behind `Math.random`.
+    random: Random,
+    /// Where `Date` finds local time, see `with_time_zone`.
+    time_zone: TimeZone,
+    /// Milliseconds since the epoch when the timers' clock read 0, see `with_time_origin`.
+    time_origin: f64,
+    module_loader: Box<dyn ModuleLoader>,
+    /// Every module loaded so far, in all realms. `module_map` finds those of the current realm by
+    /// the key their loader resolved.
+    modules: Vec<ModuleRecord>,
+    module_map: HashMap<String, usize>,
+    /// Whether scripts get a global `require`, see `with_commonjs`.
+    commonjs: bool,
+    /// CommonJS `module` objects of the current realm by key, so each file only runs once.
+    commonjs_cache: HashMap<String, JSValue>,
+    /// Records of the generator currently being resumed, see `resume`.
+    resume_stack: Vec<ResumeRecord>,
+    /// What the host's `Global` handles refer to, rooted until they're dropped.
+    global_handles: Vec<Weak<GlobalSlot>>,
+    /// Where console output and uncaught errors go, see `with_console`. Missing while it is
+    /// handling a message.
+    console: Option<Box<dyn Console>>,
+    /// The default console, which the event loop methods return the output of.
+    captured: BufferConsole,
+    console_state: builtins::console::ConsoleState,
+    /// How many uncaught exceptions and unhandled rejections have been reported so far.
+    uncaught_errors: usize,
     source: String,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
+        let isolate = Isolate::new();
+        let _isolate = isolate.enter();
         let mut heap = Heap::new();
-        let object_proto = JSObject::create_object_proto(); // should always be 0. store anyways
-        let proto_id = heap.add_object(object_proto);
-        let env_id = heap.add_environment(Environment::new());
-        let function_proto = JSObject::create_function_proto(env_id, proto_id);
-        let function_proto_id = heap.add_object(function_proto);
+        let (env_id, intrinsics) = Self::create_realm_objects(&mut heap);
         let environment_stack = vec![env_id];
+        let captured = BufferConsole::new();
         Self {
+            id: NEXT_INTERPRETER_ID.fetch_add(1, AtomicOrdering::Relaxed),
             environment_stack,
             heap,
-            object_proto_id: proto_id,
-            function_proto_id,
-            output_buffer: String::new(),
-            error_buffer: String::new(),
+            isolate: isolate.clone(),
+            intrinsics: intrinsics.clone(),
+            realms: vec![RealmRecord {
+                global_environment: env_id,
+                intrinsics,
+                module_map: HashMap::new(),
+                commonjs_cache: HashMap::new(),
+            }],
+            realm: 0,
+            symbol_registry: HashMap::new(),
+            job_queue: VecDeque::new(),
+            pending_rejections: vec![],
+            timers: TimerQueue::new(Clock::new_real()),
+            random: Random::from_time(),
+            time_zone: TimeZone::system(),
+            time_origin: date::system_time(),
+            module_loader: Box::new(FileSystemLoader::default()),
+            modules: vec![],
+            module_map: HashMap::new(),
+            commonjs: false,
+            commonjs_cache: HashMap::new(),
+            resume_stack: vec![],
+            global_handles: vec![],
+            console: Some(Box::new(captured.clone())),
+            captured,
+            console_state: Default::default(),
+            uncaught_errors: 0,
             source: "".to_owned(), // lil hack
         }
     }
 
+    /// Switches timers to a virtual clock that only moves through `advance_time`, so scripts
+    /// that depend on time run instantly and deterministically.
+    pub fn with_virtual_clock(mut self) -> Self {
+        self.timers = TimerQueue::new(Clock::new_virtual());
+        self
+    }
+
+    /// Seeds `Math.random`, so scripts see the same sequence of numbers on every run.
+    pub fn with_random_seed(mut self, seed: u64) -> Self {
+        self.set_random_seed(seed);
+        self
+    }
+
+    /// Restarts `Math.random` from `seed`.
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+    }
+
+    /// Sets the time zone `Date` uses for local time instead of the system's, e.g.
+    /// `TimeZone::Utc` or `TimeZone::Fixed(60)` for one hour east of UTC.
+    pub fn with_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    /// Sets what `Date.now` returns while the clock reads 0, in milliseconds since the epoch.
+    /// Together with `with_virtual_clock` this pins "now" for scripts, and `advance_time`
+    /// moves it forward.
+    pub fn with_time_origin(mut self, epoch_ms: f64) -> Self {
+        self.time_origin = epoch_ms - self.timers.clock().now();
+        self
+    }
+
+    /// Sends console output and uncaught errors to `console` instead of the buffers whose
+    /// contents `interpret` and the event loop methods return, which stay empty from then on.
+    pub fn with_console(mut self, console: impl Console + 'static) -> Self {
+        self.console = Some(Box::new(console));
+        self
+    }
+
+    /// Replaces the default `FileSystemLoader` used to find the modules imported by
+    /// `run_module`, `import` declarations and `import()`.
+    pub fn with_module_loader(mut self, loader: impl ModuleLoader + 'static) -> Self {
+        self.module_loader = Box::new(loader);
+        self
+    }
+
+    /// Gives scripts a global `require` that loads CommonJS files relative to the module
+    /// loader's root. Has to come before `setup`.
+    pub fn with_commonjs(mut self) -> Self {
+        self.commonjs = true;
+        self
+    }
+
+    /// Runs a major collection once `allocations` entries have made it to the old space since
+    /// the last one, or more once the heap holds more than that many live entries.
+    pub fn with_gc_threshold(mut self, allocations: usize) -> Self {
+        self.heap.set_threshold(allocations);
+        self
+    }
+
+    /// Configures the collector, e.g. turning off the nursery or incremental marking, or
+    /// bounding how much marking a single step does.
+    pub fn with_gc_options(mut self, options: GcOptions) -> Self {
+        self.heap.set_options(options);
+        self
+    }
+
     pub fn setup(mut self) -> Self {
-        JSObject::create_global_object(&mut self);
+        let _isolate = self.isolate.enter();
+        self.set_up_realm();
         trace!("{}", self.heap);
+        // everything created so far is reachable from the global object or a prototype
+        self.heap.release_temporaries(0);
+        self.heap.tenure();
         self
     }
 
+    /// Sets up another realm with a global object and intrinsics of its own. Scripts only
+    /// reach it once `enter_realm` makes it the current one.
+    pub fn create_realm(&mut self) -> Realm {
+        let _isolate = self.isolate.enter();
+        let mark = self.heap.temporaries_mark();
+        let (global_environment, intrinsics) = Self::create_realm_objects(&mut self.heap);
+        self.realms.push(RealmRecord {
+            global_environment,
+            intrinsics,
+            module_map: HashMap::new(),
+            commonjs_cache: HashMap::new(),
+        });
+        let realm = Realm {
+            index: self.realms.len() - 1,
+            interpreter_id: self.id,
+        };
+        let previous = self.enter_realm(realm);
+        self.set_up_realm();
+        self.enter_realm(previous);
+        self.heap.release_temporaries(mark);
+        realm
+    }
+
+    /// The realm scripts run in, the host's handles are opened in and globals are set in.
+    pub fn current_realm(&self) -> Realm {
+        Realm {
+            index: self.realm,
+            interpreter_id: self.id,
+        }
+    }
+
+    /// Makes `realm` the current one, returning the realm that was current before. Functions
+    /// keep the global variables of the realm they were defined in, but the objects they
+    /// create get the prototypes of the current one.
+    pub fn enter_realm(&mut self, realm: Realm) -> Realm {
+        assert_eq!(
+            realm.interpreter_id, self.id,
+            "realm used with an interpreter it doesn't belong to"
+        );
+        let previous = self.current_realm();
+        let record = &mut self.realms[self.realm];
+        record.global_environment = self.environment_stack[0];
+        record.intrinsics = self.intrinsics.clone();
+        record.module_map = std::mem::take(&mut self.module_map);
+        record.commonjs_cache = std::mem::take(&mut self.commonjs_cache);
+
+        let record = &mut self.realms[realm.index];
+        self.environment_stack[0] = record.global_environment;
+        self.intrinsics = record.intrinsics.clone();
+        self.module_map = std::mem::take(&mut record.module_map);
+        self.commonjs_cache = std::mem::take(&mut record.commonjs_cache);
+        self.realm = realm.index;
+        previous
+    }
+
+    /// The global environment of a new realm and the two prototypes everything else is
+    /// created from.
+    fn create_realm_objects(heap: &mut Heap) -> (HeapId, Intrinsics) {
+        let object_proto = JSObject::create_object_proto();
+        let proto_id = heap.add_object(object_proto);
+        let env_id = heap.add_environment(Environment::new(None));
+        let function_proto = JSObject::create_function_proto(env_id, proto_id);
+        let function_proto_id = heap.add_object(function_proto);
+        (env_id, Intrinsics::new(proto_id, function_proto_id))
+    }
+
+    /// Fills the global object of the current realm and creates its intrinsics.
+    fn set_up_realm(&mut self) {
+        JSObject::create_global_object(self);
+        if self.commonjs {
+            let require = commonjs::create_require(self, None);
+            if let Ok(JSValue::Object { object_id, .. }) = self.get_global_object()
+                && let Ok(global) = self.get_object_mut(object_id)
+            {
+                global.add_property(get_or_intern_string("require"), require);
+            }
+        }
+    }
+
     pub fn interpret(&mut self, source: &str) -> Result<(String, String), String> {
+        let _isolate = self.isolate.enter();
         self.source = source.to_owned();
-        let tokens = self.lex()?;
+        let tokens = match self.lex() {
+            Ok(tokens) => tokens,
+            Err(message) => {
+                self.report_error(&message);
+                return Err(String::from("Lexer failure. Aborting"));
+            }
+        };
 
         let mut parser = Parser::new(tokens, self);
         let statements = parser.parse();
+        for error in parser.errors().to_vec() {
+            self.report_error(&error.message);
+        }
 
+        let mark = self.heap.temporaries_mark();
         for statement in statements {
             debug!("raw_statement: {statement}");
             let res = statement.evaluate(self);
@@ -80,16 +356,310 @@ impl Interpreter {
                 Ok(value) => {
                     debug!("debug_value: {}", debug_value(self, &value));
                 }
-                Err(e) => {
-                    eprintln!("{}", e.message);
+                Err(e) => self.report_error(&e.message),
+            }
+        }
+        self.heap.release_temporaries(mark);
+
+        Ok(self.run_until_idle())
+    }
+
+    /// Defines a global function `name` that runs `function`, e.g.
+    /// `register_fn("add", |scope, _this, args| ...)`. Arguments the script left out are
+    /// missing from `args` rather than undefined.
+    pub fn register_fn(&mut self, name: &str, function: impl HostFunction) {
+        let _isolate = self.isolate.enter();
+        let mut scope = HandleScope::new(self);
+        let function = scope.new_function(name, function);
+        // the global object is an ordinary object without setters of ours
+        let _ = scope.set_global(name, &function);
+    }
+
+    /// Defines an empty global object `name` for the host to add methods to with
+    /// `NativeObject::method`.
+    pub fn register_object(&mut self, name: &str) -> Result<NativeObject<'_>, String> {
+        let _isolate = self.isolate.enter();
+        NativeObject::new(self, name).map_err(|e| e.message)
+    }
+
+    /// Runs `specifier` as a module: the loader resolves and fetches it and everything it imports,
+    /// then each module is evaluated once, dependencies first.
+    pub fn run_module(&mut self, specifier: &str) -> (String, String) {
+        let _isolate = self.isolate.enter();
+        let mark = self.heap.temporaries_mark();
+        if let Err(e) = modules::import_module(self, specifier, None) {
+            self.report_error(&e.message);
+        }
+        self.heap.release_temporaries(mark);
+        self.run_until_idle()
+    }
+
+    /// Runs `specifier` as a CommonJS module, the entry point of a program made of files that
+    /// `require` each other.
+    pub fn run_commonjs(&mut self, specifier: &str) -> (String, String) {
+        let _isolate = self.isolate.enter();
+        let mark = self.heap.temporaries_mark();
+        if let Err(e) = commonjs::require(self, specifier, None) {
+            self.report_error(&e.message);
+        }
+        self.heap.release_temporaries(mark);
+        self.run_until_idle()
+    }
+
+    /// Parses `json` and stores the result in the global property `name`, so hosts can hand
+    /// scripts configuration without building source text.
+    pub fn set_global_json(&mut self, name: &str, json: &str) -> Result<(), String> {
+        let _isolate = self.isolate.enter();
+        let value = builtins::json::parse_json(self, json).map_err(|e| e.message)?;
+        let global = self.get_global_object().map_err(|e| e.message)?;
+        values::set_property_value(self, &global, get_or_intern_string(name), value)
+            .map(|_| ())
+            .map_err(|e| e.message)
+    }
+
+    /// The global variable or property `name` serialized like `JSON.stringify` would, `None`
+    /// when it has no JSON form, e.g. when it is `undefined`.
+    pub fn get_global_json(&mut self, name: &str) -> Result<Option<String>, String> {
+        let _isolate = self.isolate.enter();
+        let value = self
+            .get_value_from_environment(get_or_intern_string(name))
+            .cloned()
+            .map_err(|e| e.message)?;
+        let undefined = JSValue::Undefined;
+        builtins::json::stringify_json(self, &value, &undefined, &undefined).map_err(|e| e.message)
+    }
+
+    /// Stores `bytes` in the global property `name` as an ArrayBuffer. The buffer takes
+    /// ownership of the vector instead of copying it.
+    pub fn set_global_array_buffer(&mut self, name: &str, bytes: Vec<u8>) -> Result<(), String> {
+        let _isolate = self.isolate.enter();
+        let object_id = builtins::array_buffer::new_array_buffer(self, bytes);
+        let buffer = JSValue::Object {
+            object_id,
+            kind: ObjectKind::Object,
+        };
+        let global = self.get_global_object().map_err(|e| e.message)?;
+        values::set_property_value(self, &global, get_or_intern_string(name), buffer)
+            .map(|_| ())
+            .map_err(|e| e.message)
+    }
+
+    /// Takes the bytes of the ArrayBuffer in the global variable or property `name` without
+    /// copying them, detaching the buffer the way `transfer` does. `None` when it holds
+    /// anything else or is already detached.
+    pub fn take_global_array_buffer(&mut self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let _isolate = self.isolate.enter();
+        let value = self
+            .get_value_from_environment(get_or_intern_string(name))
+            .cloned()
+            .map_err(|e| e.message)?;
+        let Some(object_id) = builtins::array_buffer::as_array_buffer(self, &value) else {
+            return Ok(None);
+        };
+        Ok(
+            builtins::array_buffer::array_buffer_state_mut(self, object_id)
+                .and_then(|state| state.detach()),
+        )
+    }
+
+    /// Runs queued jobs, including any they queue in turn, and every timer that is already
+    /// due, until nothing is left to do right now. `interpret` does this before returning, so
+    /// hosts only need it after queueing work themselves.
+    pub fn run_until_idle(&mut self) -> (String, String) {
+        let _isolate = self.isolate.enter();
+        self.run_microtasks();
+        let now = self.timers.clock().now();
+        self.run_timers_due_by(now);
+        self.buffers()
+    }
+
+    /// Advances the virtual clock by `ms` milliseconds, running every timer that comes due on
+    /// the way at the time it was scheduled for. A real clock can't be advanced, so this only
+    /// runs the timers that are due already.
+    pub fn advance_time(&mut self, ms: f64) -> (String, String) {
+        let _isolate = self.isolate.enter();
+        if !self.timers.clock().is_virtual() {
+            return self.run_until_idle();
+        }
+        self.run_microtasks();
+        let target = self.timers.clock().now() + ms.max(0.0);
+        self.run_timers_due_by(target);
+        self.timers.clock_mut().advance_to(target);
+        self.buffers()
+    }
+
+    /// Keeps running until no timers are left, waiting for each one to come due. A virtual
+    /// clock jumps straight to the next timer instead of waiting.
+    pub fn run_event_loop(&mut self) -> (String, String) {
+        let _isolate = self.isolate.enter();
+        self.run_until_idle();
+        while let Some(due) = self.timers.next_due() {
+            let now = self.timers.clock().now();
+            if due > now {
+                match self.timers.clock().is_virtual() {
+                    true => self.timers.clock_mut().advance_to(due),
+                    false => {
+                        std::thread::sleep(std::time::Duration::from_secs_f64((due - now) / 1000.0))
+                    }
+                }
+            }
+            self.run_until_idle();
+        }
+        self.buffers()
+    }
+
+    /// Runs a full collection of both spaces now, whatever the thresholds say.
+    pub fn collect_garbage(&mut self) {
+        let _isolate = self.isolate.enter();
+        let mut roots = Tracer::default();
+        self.trace_roots(&mut roots);
+        self.heap.collect(roots);
+        debug!("garbage collected: {:?}", self.heap.stats());
+    }
+
+    /// Live objects, their estimated size and how much time collections have taken so far.
+    pub fn heap_stats(&self) -> HeapStats {
+        let _isolate = self.isolate.enter();
+        self.heap.stats()
+    }
+
+    /// How many errors nothing caught have been reported so far: exceptions thrown by scripts,
+    /// modules and timers, syntax errors and promises rejected without a handler. Hosts exit
+    /// with a failure status when this isn't 0.
+    pub fn uncaught_errors(&self) -> usize {
+        self.uncaught_errors
+    }
+
+    pub fn has_pending_timers(&self) -> bool {
+        !self.timers.is_empty()
+    }
+
+    /// The current time of the event loop's clock in milliseconds.
+    pub fn now(&self) -> f64 {
+        self.timers.clock().now()
+    }
+
+    fn buffers(&self) -> (String, String) {
+        (self.captured.output(), self.captured.errors())
+    }
+
+    fn run_microtasks(&mut self) {
+        while let Some(job) = self.job_queue.pop_front() {
+            let mark = self.heap.temporaries_mark();
+            self.heap.root(&job);
+            let result = job.run(self);
+            self.heap.release_temporaries(mark);
+            if let Err(e) = result {
+                self.report_error(&e.message);
+            }
+            self.collect_garbage_if_due();
+        }
+        builtins::promise::report_unhandled_rejections(self);
+    }
+
+    /// Runs timers in the order they come due, each followed by the microtasks it queued.
+    fn run_timers_due_by(&mut self, time: f64) {
+        while let Some(timer) = self.timers.pop_due(time) {
+            self.timers.clock_mut().advance_to(timer.due);
+            let mark = self.heap.temporaries_mark();
+            self.heap.root(&timer);
+            let result = self.call_function(&timer.callback, &JSValue::Undefined, &timer.arguments);
+            self.heap.release_temporaries(mark);
+            if let Err(e) = result {
+                self.report_error(&e.message);
+            }
+            self.run_microtasks();
+        }
+    }
+
+    /// What the collector starts marking from, besides the temporaries: the scopes of every
+    /// active call, which include the global object, the intrinsics and globals of every realm,
+    /// whatever is waiting to run or be resumed and what the host holds on to.
+    fn trace_roots(&self, tracer: &mut Tracer) {
+        for id in &self.environment_stack {
+            tracer.mark(*id);
+        }
+        self.intrinsics.trace(tracer);
+        self.realms.trace(tracer);
+        self.symbol_registry
+            .values()
+            .for_each(|symbol| symbol.trace(tracer));
+        self.job_queue.iter().for_each(|job| job.trace(tracer));
+        self.pending_rejections.trace(tracer);
+        self.timers.trace(tracer);
+        self.modules.trace(tracer);
+        self.commonjs_cache
+            .values()
+            .for_each(|module| module.trace(tracer));
+        self.resume_stack.trace(tracer);
+        self.global_handles.trace(tracer);
+    }
+
+    /// A safe point: empties the nursery once it's full, starts a major collection when
+    /// enough has been promoted since the last one, or continues the one under way, which
+    /// a full nursery doesn't hold up. Only called where everything the Rust stack holds on
+    /// to is rooted.
+    fn collect_garbage_if_due(&mut self) {
+        if self.heap.pending_work() == Some(GcWork::Minor) {
+            let mut roots = Tracer::default();
+            self.trace_roots(&mut roots);
+            self.heap.collect_young(roots);
+        }
+        let Some(work) = self.heap.pending_work() else {
+            return;
+        };
+        let mut roots = Tracer::default();
+        match work {
+            GcWork::Minor => (),
+            GcWork::Major => {
+                self.trace_roots(&mut roots);
+                self.heap.begin_major(roots);
+            }
+            GcWork::MarkingStep => {
+                if self.heap.mark_step() {
+                    self.trace_roots(&mut roots);
+                    self.heap.finish_marking(roots);
+                }
+            }
+            GcWork::SweepStep => {
+                if self.heap.sweep_step() {
+                    debug!("garbage collected: {:?}", self.heap.stats());
                 }
             }
         }
+    }
 
-        let out = self.output_buffer.clone();
-        let err = self.error_buffer.clone();
+    /// Keeps the object `value` refers to alive until the enclosing statement completes.
+    fn root_value(&mut self, value: &JSValue) {
+        self.heap.root_value(value);
+    }
+
+    fn enqueue_job(&mut self, job: Job) {
+        self.job_queue.push_back(job);
+    }
 
-        Ok((out, err))
+    fn report_error(&mut self, message: &str) {
+        self.uncaught_errors += 1;
+        builtins::console::write_text(self, LogLevel::Error, message);
+    }
+
+    /// Runs `source` as a script up to the first statement that throws, see
+    /// `HandleScope::eval`. Unlike `interpret`, syntax errors mean nothing runs at all.
+    fn evaluate_script(&mut self, source: &str) -> JSResult<JSValue> {
+        self.source = source.to_owned();
+        let tokens = self
+            .lex()
+            .map_err(|message| JSError::new_syntax_error(&message))?;
+        let mut parser = Parser::new(tokens, self);
+        let statements = parser.parse();
+        if let Some(error) = parser.errors().first() {
+            return Err(error.clone());
+        }
+        let mut completion = JSValue::Undefined;
+        for statement in statements {
+            completion = statement.evaluate(self)?;
+        }
+        Ok(completion)
     }
 
     fn lex(&mut self) -> Result<Vec<Token>, String> {
@@ -103,8 +673,8 @@ impl Interpreter {
         debug!("=========== END LEXER OUTPUT ===========\n");
 
         if lexer.had_errors() {
-            lexer.replay_errors();
-            return Err(String::from("Lexer failure. Aborting"));
+            let errors: Vec<String> = lexer.errors().iter().map(|e| e.to_string()).collect();
+            return Err(errors.join("\n"));
         }
         Ok(tokens)
     }
@@ -123,6 +693,19 @@ impl Interpreter {
     }
 
     fn new_variable(&mut self, ident_id: SymbolU32, is_mutable: bool, value: JSValue) {
+        // the declaration of a binding created ahead of time, e.g. a module export
+        let current = self.get_current_environment_handle();
+        let existing = self
+            .get_environment(current)
+            .ok()
+            .and_then(|environment| environment.get_variable(ident_id));
+        if let Some(var_id) = existing
+            && let Ok(var) = self.get_var(var_id)
+            && !var.is_initialized()
+        {
+            var.initialize(is_mutable, value);
+            return;
+        }
         let var = Variable::new(is_mutable, value);
         let var_id = self.add_var(var);
         self.add_variable_to_current_environment(ident_id, var_id);
@@ -152,15 +735,40 @@ impl Interpreter {
         );
     }
 
+    /// Walks the lexical environment chain starting at the innermost scope.
+    fn resolve_variable_id(&self, str_id: SymbolU32) -> JSResult<Option<usize>> {
+        Ok(self.resolve_binding(str_id)?.map(|(_, var_id)| var_id))
+    }
+
+    /// Like `resolve_variable_id`, also returning the environment the name was found in.
+    fn resolve_binding(&self, str_id: SymbolU32) -> JSResult<Option<(HeapId, usize)>> {
+        let mut current = Some(self.get_current_environment_handle());
+        while let Some(env_id) = current {
+            let environment = self.get_environment(env_id)?;
+            if let Some(var_id) = environment.get_variable(str_id) {
+                return Ok(Some((env_id, var_id)));
+            }
+            current = environment.get_parent();
+        }
+        Ok(None)
+    }
+
+    /// Imported bindings are read-only views of another module's variables.
+    fn is_import_binding(&self, str_id: SymbolU32) -> JSResult<bool> {
+        match self.resolve_binding(str_id)? {
+            Some((env_id, _)) => Ok(self.get_environment(env_id)?.is_import(&str_id)),
+            None => Ok(false),
+        }
+    }
+
     fn get_value_from_environment(&mut self, str_id: SymbolU32) -> JSResult<&JSValue> {
-        for id in self.environment_stack.iter().rev() {
-            let environment = self.get_environment(*id)?;
-            let var_result = environment.get_variable(str_id);
-            if let Some(var_id) = var_result {
-                let var = self.get_var(var_id)?;
-                let val = var.get_value();
-                return Ok(val);
+        if let Some(var_id) = self.resolve_variable_id(str_id)? {
+            let var = self.get_var(var_id)?;
+            if !var.is_initialized() {
+                let name = get_string_from_pool(&str_id).unwrap_or_default();
+                return Err(JSError::new_uninitialized_error(&name));
             }
+            return Ok(var.get_value());
         }
 
         // we didn't find the variable - so check the global object since it wasn't invoked directly
@@ -175,15 +783,16 @@ impl Interpreter {
         let environment = self
             .get_environment(*environment_handle)
             .expect("Environment ID not found in heap");
-        environment.has_variable(string_id)
+        // bindings created ahead of their declaration don't count until it has run
+        environment
+            .get_variable(*string_id)
+            .and_then(|var_id| self.heap.get_variable(var_id).ok())
+            .is_some_and(|var| var.is_initialized())
     }
 
     fn get_value_from_global_this(&mut self, str_id: SymbolU32) -> JSResult<&JSValue> {
-        let global_environment_id = self
-            .environment_stack
-            .get(0)
-            .expect("Why did you delete the global environment?"); // should always exist
-        let global_environment = self.get_environment(*global_environment_id)?;
+        let global_environment_id = self.global_environment()?;
+        let global_environment = self.get_environment(global_environment_id)?;
         let global_this = get_or_intern_string(GLOBAL_THIS_NAME);
         let var_result = global_environment.get_variable(global_this);
         if let Some(var_id) = var_result {
@@ -192,7 +801,7 @@ impl Interpreter {
             // always true
             if let JSValue::Object { object_id, kind: _ } = val {
                 let object = self.get_object(object_id)?;
-                let prop = object.get_property(&str_id);
+                let prop = object.get_property(&str_id.into());
                 if let Some(prop) = prop {
                     let value = prop.get_value()?;
                     return Ok(value);
@@ -202,19 +811,34 @@ impl Interpreter {
         Err(JSError::new("Variable not found in environment"))
     }
 
+    fn get_global_object(&mut self) -> JSResult<JSValue> {
+        let global_environment_id = self.global_environment()?;
+        let global_this = get_or_intern_string(GLOBAL_THIS_NAME);
+        let var_id = self
+            .get_environment(global_environment_id)?
+            .get_variable(global_this)
+            .ok_or_else(|| JSError::new("globalThis is missing"))?;
+        Ok(self.get_var(var_id)?.get_value_cloned())
+    }
+
+    /// The global environment of the realm the running code comes from, where its scope chain
+    /// ends. That is the current realm's unless a function of another realm is running.
+    fn global_environment(&self) -> JSResult<HeapId> {
+        let mut id = self.get_current_environment_handle();
+        while let Some(parent) = self.get_environment(id)?.get_parent() {
+            id = parent;
+        }
+        Ok(id)
+    }
+
     fn get_variable_from_current_environment(
         &mut self,
         string_id: SymbolU32,
     ) -> JSResult<&mut Variable> {
-        for id in self.environment_stack.iter().rev() {
-            let environment = self.get_environment(*id)?;
-            let var_result = environment.get_variable(string_id);
-            if let Some(var_id) = var_result {
-                let var = self.get_var(var_id)?;
-                return Ok(var);
-            }
+        match self.resolve_variable_id(string_id)? {
+            Some(var_id) => self.get_var(var_id),
+            None => Err(JSError::new("Variable not found")),
         }
-        Err(JSError::new("Variable not found"))
     }
 
     fn get_environment(&self, id: HeapId) -> JSResult<&Environment> {
@@ -246,7 +870,12 @@ impl Interpreter {
     }
 
     fn new_scope(&mut self) -> usize {
-        let new_env = Environment::new();
+        let parent = self.get_current_environment_handle();
+        self.new_scope_with_parent(parent)
+    }
+
+    fn new_scope_with_parent(&mut self, parent: usize) -> usize {
+        let new_env = Environment::new(Some(parent));
         self.heap.add_environment(new_env)
     }
 
@@ -265,17 +894,208 @@ impl Interpreter {
         self.environment_stack.pop();
     }
 
-    fn bind_variable(&mut self, param_id: SymbolU32, value: &JSValue) -> JSResult<JSValue> {
-        let var = self
-            .get_variable_from_current_environment(param_id)
-            .unwrap();
-        var.update_value(value.clone())?;
-        debug!("var: {var:?}");
-        Ok(JSValue::Undefined)
+    fn is_callable(&self, value: &JSValue) -> bool {
+        match value {
+            JSValue::Object { object_id, kind: _ } => self
+                .get_object(*object_id)
+                .is_ok_and(|object| object.is_function()),
+            _ => false,
+        }
+    }
+
+    /// [[Call]] on an arbitrary value, throwing a TypeError for anything that isn't a function.
+    fn call_function(
+        &mut self,
+        callee: &JSValue,
+        this: &JSValue,
+        arguments: &[JSValue],
+    ) -> JSResult<JSValue> {
+        if !self.is_callable(callee) {
+            return Err(JSError::new_type_error("value is not a function"));
+        }
+        self.root_value(callee);
+        let object = callee.get_object(self)?.clone();
+        object.call(this, arguments, self)
+    }
+
+    /// [[Construct]]. Script constructors receive an ordinary object inheriting from
+    /// `callee.prototype`, native ones create their own.
+    fn construct(&mut self, callee: &JSValue, arguments: &[JSValue]) -> JSResult<JSValue> {
+        self.construct_with_new_target(callee, arguments, callee)
+    }
+
+    /// [[Construct]] with a `new.target` other than the callee, as `Reflect.construct` and
+    /// proxies without a `construct` trap pass along. The new object inherits from
+    /// `new_target.prototype`.
+    fn construct_with_new_target(
+        &mut self,
+        callee: &JSValue,
+        arguments: &[JSValue],
+        new_target: &JSValue,
+    ) -> JSResult<JSValue> {
+        let object = match callee {
+            JSValue::Object { object_id, kind: _ } => self.get_object(*object_id)?.clone(),
+            _ => return Err(JSError::new_type_error("value is not a constructor")),
+        };
+        if !object.is_constructor() {
+            return Err(JSError::new_type_error("value is not a constructor"));
+        }
+        self.root_value(callee);
+        self.root_value(new_target);
+        if let JSObject::Proxy(_) = object {
+            return object.construct(&JSValue::Undefined, new_target, arguments, self);
+        }
+        let prototype_key = get_or_intern_string(PROTOTYPE_NAME);
+        let proto_id = match get_property_value(self, new_target, prototype_key)? {
+            JSValue::Object { object_id, kind: _ } => object_id,
+            _ => self.intrinsics.object_proto_id,
+        };
+        let this_id = JSObject::new_ordinary_object(vec![], true, Some(proto_id), self);
+        let this = JSValue::Object {
+            object_id: this_id,
+            kind: ObjectKind::Object,
+        };
+        object.construct(&this, new_target, arguments, self)
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-instanceofoperator
+    fn instance_of(&mut self, value: &JSValue, target: &JSValue) -> JSResult<JSValue> {
+        if !target.is_object() {
+            return Err(JSError::new_type_error(
+                "Right-hand side of 'instanceof' is not an object",
+            ));
+        }
+        let key = PropertyKey::well_known(WellKnownSymbol::HasInstance);
+        let handler = get_property_value(self, target, key)?;
+        if !handler.is_undefined() && !handler.is_null() {
+            let result = self.call_function(&handler, target, std::slice::from_ref(value))?;
+            return Ok(JSValue::new_boolean(result.to_boolean()));
+        }
+        if !self.is_callable(target) {
+            return Err(JSError::new_type_error(
+                "Right-hand side of 'instanceof' is not callable",
+            ));
+        }
+        self.ordinary_has_instance(target, value)
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-ordinaryhasinstance
+    /// Whether `constructor.prototype` is on the prototype chain of `value`.
+    fn ordinary_has_instance(
+        &mut self,
+        constructor: &JSValue,
+        value: &JSValue,
+    ) -> JSResult<JSValue> {
+        if !self.is_callable(constructor) {
+            return Ok(JSValue::new_boolean(false));
+        }
+        let JSValue::Object { object_id, .. } = value else {
+            return Ok(JSValue::new_boolean(false));
+        };
+        let prototype_key = get_or_intern_string(PROTOTYPE_NAME);
+        let prototype = get_property_value(self, constructor, prototype_key)?;
+        let JSValue::Object {
+            object_id: prototype_id,
+            ..
+        } = prototype
+        else {
+            let prototype = debug_value(self, &prototype);
+            return Err(JSError::new_type_error(&format!(
+                "Function has non-object prototype '{prototype}' in instanceof check"
+            )));
+        };
+        let mut current = internal::get_prototype_of(self, *object_id)?;
+        while let Some(id) = current {
+            if id == prototype_id {
+                return Ok(JSValue::new_boolean(true));
+            }
+            current = internal::get_prototype_of(self, id)?;
+        }
+        Ok(JSValue::new_boolean(false))
     }
 
     fn get_object_proto_id(&self) -> usize {
-        self.object_proto_id
+        self.intrinsics.object_proto_id
+    }
+
+    /// Records taken off the stack are rooted, they live on the Rust stack from now on.
+    fn take_resume_record(&mut self) -> Option<ResumeRecord> {
+        let record = self.resume_stack.pop();
+        self.heap.root(&record);
+        record
+    }
+
+    fn save_resume_record(&mut self, record: ResumeRecord) {
+        self.resume_stack.push(record);
+    }
+
+    fn take_resume_stack(&mut self) -> Vec<ResumeRecord> {
+        let records = std::mem::take(&mut self.resume_stack);
+        self.heap.root(&records);
+        records
+    }
+
+    fn restore_resume_stack(&mut self, records: Vec<ResumeRecord>) {
+        self.resume_stack = records;
+    }
+
+    /// Builds the completion for `throw value`. The message is what gets reported when nothing
+    /// catches it.
+    fn throw_value(&mut self, value: JSValue) -> JSError {
+        let message = format!("Uncaught {}", self.describe_thrown(&value));
+        let id = self.add_value(value);
+        JSError::new_throw(id, &message)
+    }
+
+    /// How an uncaught value is reported: `name: message` for error-like objects.
+    fn describe_thrown(&mut self, value: &JSValue) -> String {
+        let name_key = get_or_intern_string("name");
+        let message_key = get_or_intern_string("message");
+        match value {
+            JSValue::Object { .. } => {
+                let name = get_property_value(self, value, name_key).unwrap_or_default();
+                let message = get_property_value(self, value, message_key).unwrap_or_default();
+                match (name, message) {
+                    (JSValue::String { data: name }, JSValue::String { data: message }) => {
+                        let name = get_string_from_pool(&name).unwrap_or_default();
+                        let message = get_string_from_pool(&message).unwrap_or_default();
+                        format!("{name}: {message}")
+                    }
+                    _ => "[object Object]".to_string(),
+                }
+            }
+            other => other
+                .to_string(self)
+                .ok()
+                .and_then(|s| get_string_from_pool(&s))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The value a `catch` clause binds. Errors raised by the engine itself only carry a message,
+    /// so they are turned into an object with `name` and `message`.
+    fn error_value(&mut self, error: &JSError) -> JSResult<JSValue> {
+        if let ErrorKind::Throw(id) = error.kind {
+            return Ok(self.get_value(id)?.clone());
+        }
+        let description = error
+            .message
+            .strip_prefix("Uncaught ")
+            .unwrap_or(&error.message);
+        let (name, message) = description
+            .split_once(": ")
+            .unwrap_or(("Error", description));
+        let properties = vec![
+            (
+                get_or_intern_string("name"),
+                JSValue::new_string(&get_or_intern_string(name)),
+            ),
+            (
+                get_or_intern_string("message"),
+                JSValue::new_string(&get_or_intern_string(message)),
+            ),
+        ];
+        Ok(JSValue::new_object(properties, self))
     }
 
     fn same_type(&self, left: &JSValue, right: &JSValue) -> JSResult<JSValue> {
@@ -310,10 +1130,7 @@ impl Interpreter {
                 JSValue::Number { data: _ } => true,
                 _ => false,
             },
-            JSValue::BigInt => match right {
-                JSValue::BigInt => true,
-                _ => false,
-            },
+            JSValue::BigInt { .. } => matches!(right, JSValue::BigInt { .. }),
             JSValue::Object {
                 object_id: _,
                 kind: _,
@@ -349,6 +1166,7 @@ impl Interpreter {
                 let right = right.to_string(self)?;
                 *data == right
             }
+            JSValue::BigInt { .. } | JSValue::Symbol { .. } => left == right,
             _ => true,
         }))
     }
@@ -374,14 +1192,23 @@ impl Interpreter {
             return self.is_loosely_equal(&left, right);
         }
 
-        if left.is_big_int() && right.is_string() {
-            todo!()
+        if let (JSValue::BigInt { data }, JSValue::String { data: string }) = (left, right) {
+            let string = get_string_from_pool(string).unwrap_or_default();
+            let equal = bigint::string_to_big_int(&string).is_some_and(|n| n == **data);
+            return Ok(JSValue::new_boolean(equal));
         }
 
         if left.is_string() && right.is_big_int() {
             return self.is_loosely_equal(right, left);
         }
 
+        if let (JSValue::BigInt { data }, JSValue::Number { data: number })
+        | (JSValue::Number { data: number }, JSValue::BigInt { data }) = (left, right)
+        {
+            let equal = bigint::compare_to_number(data, *number) == Some(Ordering::Equal);
+            return Ok(JSValue::new_boolean(equal));
+        }
+
         if left.is_boolean() {
             let left = left.to_number(self)?;
             return self.is_loosely_equal(&left, right);
@@ -402,7 +1229,7 @@ impl Interpreter {
         if (right.is_string() || right.is_number() || right.is_big_int() || right.is_symbol())
             && left.is_object()
         {
-            let left = right.to_primitive(None, self)?;
+            let left = left.to_primitive(None, self)?;
             return self.is_loosely_equal(&left, right);
         }
 
@@ -422,26 +1249,7 @@ impl Interpreter {
     }
 }
 
+/// A short description of `value` for messages, like `util.inspect` with its default options.
 pub fn debug_value(interpreter: &mut Interpreter, value: &JSValue) -> String {
-    let out = match value {
-        JSValue::Null => "null".to_string(),
-        JSValue::Undefined => "undefined".to_string(),
-        JSValue::Boolean { data } => data.to_string(),
-        JSValue::String { data } => {
-            let s = get_string_from_pool(data).unwrap_or("UNKNOWN STRING".to_string());
-            format!("'{s}'")
-        }
-        JSValue::Symbol {
-            id: _,
-            description: _,
-        } => todo!(),
-        JSValue::Number { data } => data.to_string(),
-        JSValue::BigInt => todo!(),
-        JSValue::Object { object_id, kind: _ } => {
-            let obj = interpreter.get_object(*object_id).unwrap().clone();
-            obj.debug(interpreter)
-        }
-    };
-
-    out
+    inspect::inspect(interpreter, value, &InspectOptions::default())
 }
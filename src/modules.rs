@@ -0,0 +1,794 @@
+//! ES modules. The host finds and fetches module source through a `ModuleLoader`; the
+//! interpreter loads the whole graph, links every import to the variable it names and then
+//! evaluates dependencies before the modules importing them.
+
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::promise::{create_promise, reject_promise, resolve_promise},
+    constants::{DEFAULT_EXPORT_BINDING, DEFAULT_NAME},
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    heap::{Trace, Tracer},
+    jobs::Job,
+    parser::Parser,
+    stmt::{ExportDeclaration, ExportSpecifier, ImportName, Stmt},
+    values::{JSObject, JSResult, JSValue, ObjectKind, ObjectProperty},
+    variable::Variable,
+};
+
+/// How the interpreter finds modules. `resolve` turns the specifier in an import into a key that
+/// identifies one module, `load` fetches the source for a key. Every key is loaded at most once.
+/// Loaders are `Send` so the interpreters owning them can move between threads.
+pub trait ModuleLoader: Send {
+    /// `referrer` is the key of the importing module, `None` for entry points and scripts.
+    fn resolve(&self, specifier: &str, referrer: Option<&str>) -> Result<String, String>;
+
+    fn load(&self, key: &str) -> Result<String, String>;
+}
+
+/// Resolves relative specifiers against the importing module's directory, or `root` for entry
+/// points, and reads modules from disk. Keys are absolute paths.
+#[derive(Clone, Debug)]
+pub struct FileSystemLoader {
+    root: PathBuf,
+}
+
+impl FileSystemLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Default for FileSystemLoader {
+    fn default() -> Self {
+        Self::new(std::env::current_dir().unwrap_or_default())
+    }
+}
+
+impl ModuleLoader for FileSystemLoader {
+    fn resolve(&self, specifier: &str, referrer: Option<&str>) -> Result<String, String> {
+        let is_path = specifier.starts_with("./")
+            || specifier.starts_with("../")
+            || Path::new(specifier).is_absolute();
+        if !is_path {
+            return Err(format!("Cannot find package '{specifier}'"));
+        }
+        let base = match referrer {
+            Some(referrer) => Path::new(referrer)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            None => self.root.clone(),
+        };
+        let path = normalize(&base.join(specifier));
+        if !path.is_file() {
+            return Err(format!("Cannot find module '{}'", path.display()));
+        }
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    fn load(&self, key: &str) -> Result<String, String> {
+        std::fs::read_to_string(key).map_err(|e| format!("Cannot load module '{key}': {e}"))
+    }
+}
+
+/// Folds `.` and `..` without touching the filesystem, so one module always gets one key.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[derive(Clone, Debug)]
+pub enum ModuleStatus {
+    Unlinked,
+    Linking,
+    Linked,
+    Evaluating,
+    Evaluated,
+    /// Linking or evaluation failed. Importing the module again throws the same error.
+    Errored(JSError),
+}
+
+#[derive(Clone, Debug)]
+struct ImportEntry {
+    from: SymbolU32,
+    imported: ImportName,
+    local: SymbolU32,
+}
+
+/// `export { a as b } from "./x.js"` or `export * as b from "./x.js"`
+#[derive(Clone, Debug)]
+struct IndirectExport {
+    exported: SymbolU32,
+    from: SymbolU32,
+    imported: ImportName,
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-source-text-module-records
+#[derive(Clone, Debug)]
+pub struct ModuleRecord {
+    /// Holds the module's top level bindings. Its parent is the global environment.
+    environment: usize,
+    status: ModuleStatus,
+    body: Arc<Vec<Stmt>>,
+    /// Specifiers in the order they appear in the source.
+    requested: Vec<SymbolU32>,
+    /// The module each specifier resolved to.
+    dependencies: HashMap<SymbolU32, usize>,
+    imports: Vec<ImportEntry>,
+    local_exports: Vec<ExportSpecifier>,
+    indirect_exports: Vec<IndirectExport>,
+    star_exports: Vec<SymbolU32>,
+    namespace: Option<JSValue>,
+}
+
+impl Trace for ModuleRecord {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.mark(self.environment);
+        if let ModuleStatus::Errored(error) = &self.status {
+            error.trace(tracer);
+        }
+        self.namespace.trace(tracer);
+    }
+}
+
+impl ModuleRecord {
+    fn new(environment: usize, body: Vec<Stmt>) -> Self {
+        let mut record = Self {
+            environment,
+            status: ModuleStatus::Unlinked,
+            body: Arc::new(vec![]),
+            requested: vec![],
+            dependencies: HashMap::new(),
+            imports: vec![],
+            local_exports: vec![],
+            indirect_exports: vec![],
+            star_exports: vec![],
+            namespace: None,
+        };
+        for stmt in body.iter() {
+            record.add_entries(stmt);
+        }
+        // re-exporting an imported name forwards to the module it came from
+        let local_exports = std::mem::take(&mut record.local_exports);
+        for export in local_exports {
+            let import = record
+                .imports
+                .iter()
+                .find(|import| import.local == export.local);
+            match import {
+                Some(import) if import.imported != ImportName::Namespace => {
+                    record.indirect_exports.push(IndirectExport {
+                        exported: export.exported,
+                        from: import.from,
+                        imported: import.imported,
+                    });
+                }
+                _ => record.local_exports.push(export),
+            }
+        }
+        record.body = Arc::new(body);
+        record
+    }
+
+    fn request(&mut self, specifier: SymbolU32) {
+        if !self.requested.contains(&specifier) {
+            self.requested.push(specifier);
+        }
+    }
+
+    fn add_entries(&mut self, stmt: &Stmt) {
+        let default_name = get_or_intern_string(DEFAULT_NAME);
+        match stmt {
+            Stmt::Import {
+                specifier,
+                bindings,
+            } => {
+                self.request(*specifier);
+                for binding in bindings {
+                    self.imports.push(ImportEntry {
+                        from: *specifier,
+                        imported: binding.imported,
+                        local: binding.local,
+                    });
+                }
+            }
+            Stmt::Export(declaration) => match &**declaration {
+                ExportDeclaration::Declaration(stmt) => {
+                    if let Some(name) = declared_name(stmt) {
+                        self.local_exports.push(ExportSpecifier {
+                            local: name,
+                            exported: name,
+                        });
+                    }
+                }
+                ExportDeclaration::Default(_) => self.local_exports.push(ExportSpecifier {
+                    local: get_or_intern_string(DEFAULT_EXPORT_BINDING),
+                    exported: default_name,
+                }),
+                ExportDeclaration::DefaultDeclaration(stmt) => {
+                    if let Some(name) = declared_name(stmt) {
+                        self.local_exports.push(ExportSpecifier {
+                            local: name,
+                            exported: default_name,
+                        });
+                    }
+                }
+                ExportDeclaration::List {
+                    specifiers,
+                    from: None,
+                } => self.local_exports.extend(specifiers.iter().cloned()),
+                ExportDeclaration::List {
+                    specifiers,
+                    from: Some(from),
+                } => {
+                    self.request(*from);
+                    for specifier in specifiers {
+                        self.indirect_exports.push(IndirectExport {
+                            exported: specifier.exported,
+                            from: *from,
+                            imported: ImportName::Name(specifier.local),
+                        });
+                    }
+                }
+                ExportDeclaration::All { from, alias } => {
+                    self.request(*from);
+                    match alias {
+                        Some(alias) => self.indirect_exports.push(IndirectExport {
+                            exported: *alias,
+                            from: *from,
+                            imported: ImportName::Namespace,
+                        }),
+                        None => self.star_exports.push(*from),
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+/// The name a top level declaration binds.
+fn declared_name(stmt: &Stmt) -> Option<SymbolU32> {
+    match stmt {
+        Stmt::VariableDecl { identifier, .. } | Stmt::FunctionDecl { identifier, .. } => {
+            match &**identifier {
+                crate::expr::Expr::Identifier { string_index } => Some(*string_index),
+                _ => None,
+            }
+        }
+        Stmt::ClassDecl(definition) => definition.identifier,
+        _ => None,
+    }
+}
+
+/// The declaration under an `export`, if the statement is one.
+fn unwrap_export(stmt: &Stmt) -> &Stmt {
+    match stmt {
+        Stmt::Export(declaration) => match &**declaration {
+            ExportDeclaration::Declaration(stmt) | ExportDeclaration::DefaultDeclaration(stmt) => {
+                stmt
+            }
+            _ => stmt,
+        },
+        stmt => stmt,
+    }
+}
+
+/// Loads, links and evaluates a module and everything it imports. Modules already evaluated are
+/// not run again.
+pub fn import_module(
+    interpreter: &mut Interpreter,
+    specifier: &str,
+    referrer: Option<&str>,
+) -> JSResult<usize> {
+    let index = load_module(interpreter, specifier, referrer)?;
+    link_module(interpreter, index)?;
+    evaluate_module(interpreter, index)?;
+    Ok(index)
+}
+
+pub fn loader_error(message: &str) -> JSError {
+    JSError::new(&format!("Uncaught Error: {message}"))
+}
+
+/// Fetches and parses a module and, recursively, the modules it requests. A module is added to
+/// the module map before its dependencies are loaded, so import cycles end there.
+fn load_module(
+    interpreter: &mut Interpreter,
+    specifier: &str,
+    referrer: Option<&str>,
+) -> JSResult<usize> {
+    let key = interpreter
+        .module_loader
+        .resolve(specifier, referrer)
+        .map_err(|message| loader_error(&message))?;
+    if let Some(index) = interpreter.module_map.get(&key) {
+        return Ok(*index);
+    }
+    let source = interpreter
+        .module_loader
+        .load(&key)
+        .map_err(|message| loader_error(&message))?;
+    let body = parse_source(interpreter, &source, Some(&key))?;
+    let global_environment = interpreter.environment_stack[0];
+    let environment = interpreter.new_scope_with_parent(global_environment);
+    let record = ModuleRecord::new(environment, body);
+    let requested = record.requested.clone();
+    let index = interpreter.modules.len();
+    interpreter.modules.push(record);
+    interpreter.module_map.insert(key.clone(), index);
+
+    for specifier in requested {
+        let name = get_string_from_pool(&specifier).unwrap_or_default();
+        match load_module(interpreter, &name, Some(&key)) {
+            Ok(dependency) => {
+                interpreter.modules[index]
+                    .dependencies
+                    .insert(specifier, dependency);
+            }
+            Err(e) => {
+                interpreter.modules[index].status = ModuleStatus::Errored(e.clone());
+                return Err(e);
+            }
+        }
+    }
+    Ok(index)
+}
+
+/// Parses a whole file, as module code when `module` holds its key. Anything that fails to
+/// parse is reported as a SyntaxError.
+pub fn parse_source(
+    interpreter: &mut Interpreter,
+    source: &str,
+    module: Option<&str>,
+) -> JSResult<Vec<Stmt>> {
+    // the parser reads token text from the interpreter's source
+    let script_source = std::mem::replace(&mut interpreter.source, source.to_owned());
+    let result = match interpreter.lex() {
+        Ok(tokens) => {
+            let mut parser = match module {
+                Some(key) => Parser::new_module(tokens, interpreter, get_or_intern_string(key)),
+                None => Parser::new(tokens, interpreter),
+            };
+            let body = parser.parse();
+            match parser.errors().first() {
+                Some(error) => Err(error.clone()),
+                None => Ok(body),
+            }
+        }
+        Err(message) => Err(JSError::new(&message)),
+    };
+    interpreter.source = script_source;
+    result.map_err(|error| match error.message.starts_with("Uncaught ") {
+        true => error,
+        false => JSError::new_syntax_error(&error.message),
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-moduledeclarationlinking
+/// Creates the bindings of every module in the graph not linked yet, then points each import
+/// at the variable it resolves to. Bindings are created for all modules first so imports
+/// within a cycle find them.
+fn link_module(interpreter: &mut Interpreter, index: usize) -> JSResult<()> {
+    let mut unlinked = vec![];
+    collect_unlinked(interpreter, index, &mut unlinked)?;
+    let result = unlinked
+        .iter()
+        .try_for_each(|module| declare_bindings(interpreter, *module))
+        .and_then(|_| {
+            unlinked
+                .iter()
+                .try_for_each(|module| bind_imports(interpreter, *module))
+        });
+    let status = match &result {
+        Ok(_) => ModuleStatus::Linked,
+        Err(e) => ModuleStatus::Errored(e.clone()),
+    };
+    for module in unlinked {
+        interpreter.modules[module].status = status.clone();
+    }
+    result
+}
+
+fn collect_unlinked(
+    interpreter: &mut Interpreter,
+    index: usize,
+    unlinked: &mut Vec<usize>,
+) -> JSResult<()> {
+    match &interpreter.modules[index].status {
+        ModuleStatus::Errored(e) => return Err(e.clone()),
+        ModuleStatus::Unlinked => (),
+        _ => return Ok(()),
+    }
+    interpreter.modules[index].status = ModuleStatus::Linking;
+    unlinked.push(index);
+    let record = &interpreter.modules[index];
+    let dependencies: Vec<usize> = record
+        .requested
+        .iter()
+        .filter_map(|specifier| record.dependencies.get(specifier).copied())
+        .collect();
+    for dependency in dependencies {
+        collect_unlinked(interpreter, dependency, unlinked)?;
+    }
+    Ok(())
+}
+
+/// Top level `let`, `const` and `class` bindings start out uninitialized, functions are
+/// created right away so other modules can call them before this one has run.
+fn declare_bindings(interpreter: &mut Interpreter, index: usize) -> JSResult<()> {
+    let record = &interpreter.modules[index];
+    let body = Arc::clone(&record.body);
+    let environment = record.environment;
+    interpreter.enter_scope(Some(environment));
+    let result = body.iter().try_for_each(|stmt| {
+        let declaration = unwrap_export(stmt);
+        if let Stmt::FunctionDecl { .. } = declaration {
+            return declaration.evaluate(interpreter).map(|_| ());
+        }
+        let name = match stmt {
+            Stmt::Export(export) if matches!(&**export, ExportDeclaration::Default(_)) => {
+                Some(get_or_intern_string(DEFAULT_EXPORT_BINDING))
+            }
+            _ => declared_name(declaration),
+        };
+        if let Some(name) = name {
+            let var_id = interpreter.add_var(Variable::new_uninitialized(true));
+            interpreter.add_variable_to_current_environment(name, var_id);
+        }
+        Ok(())
+    });
+    interpreter.leave_scope();
+    result
+}
+
+fn bind_imports(interpreter: &mut Interpreter, index: usize) -> JSResult<()> {
+    let record = &interpreter.modules[index];
+    let imports = record.imports.clone();
+    let dependencies = record.dependencies.clone();
+    let environment = record.environment;
+    for import in imports {
+        let dependency = dependencies[&import.from];
+        let binding = match import.imported {
+            ImportName::Namespace => ResolvedBinding::Namespace(dependency),
+            ImportName::Name(name) => {
+                match resolve_export(interpreter, dependency, name, &mut vec![])? {
+                    Resolution::Found(binding) => binding,
+                    resolution => {
+                        return Err(unresolvable_import(import.from, name, resolution));
+                    }
+                }
+            }
+        };
+        match binding {
+            ResolvedBinding::Local { module, binding } => {
+                let var_id = interpreter
+                    .get_environment(interpreter.modules[module].environment)?
+                    .get_variable(binding);
+                let Some(var_id) = var_id else {
+                    let name = match import.imported {
+                        ImportName::Name(name) => name,
+                        ImportName::Namespace => binding,
+                    };
+                    return Err(unresolvable_import(import.from, name, Resolution::NotFound));
+                };
+                interpreter
+                    .get_environment_mut(environment)?
+                    .add_import(import.local, var_id);
+            }
+            ResolvedBinding::Namespace(module) => {
+                let namespace = get_namespace(interpreter, module)?;
+                let var_id = interpreter.add_var(Variable::new(false, namespace));
+                interpreter
+                    .get_environment_mut(environment)?
+                    .add_variable(import.local, var_id);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unresolvable_import(from: SymbolU32, name: SymbolU32, resolution: Resolution) -> JSError {
+    let from = get_string_from_pool(&from).unwrap_or_default();
+    let name = get_string_from_pool(&name).unwrap_or_default();
+    match resolution {
+        Resolution::Ambiguous => JSError::new_syntax_error(&format!(
+            "The requested module '{from}' contains conflicting star exports for name '{name}'"
+        )),
+        _ => JSError::new_syntax_error(&format!(
+            "The requested module '{from}' does not provide an export named '{name}'"
+        )),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResolvedBinding {
+    /// A variable in the top level environment of `module`.
+    Local {
+        module: usize,
+        binding: SymbolU32,
+    },
+    Namespace(usize),
+}
+
+enum Resolution {
+    Found(ResolvedBinding),
+    NotFound,
+    /// Two `export *` provide the name from different modules.
+    Ambiguous,
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-resolveexport
+/// Follows re-exports to the module that declares `name`. `resolve_set` holds the lookups in
+/// progress, a lookup that comes back around is a cycle and resolves to nothing.
+fn resolve_export(
+    interpreter: &Interpreter,
+    index: usize,
+    name: SymbolU32,
+    resolve_set: &mut Vec<(usize, SymbolU32)>,
+) -> JSResult<Resolution> {
+    if resolve_set.contains(&(index, name)) {
+        return Ok(Resolution::NotFound);
+    }
+    resolve_set.push((index, name));
+    let record = &interpreter.modules[index];
+    if let Some(export) = record
+        .local_exports
+        .iter()
+        .find(|export| export.exported == name)
+    {
+        return Ok(Resolution::Found(ResolvedBinding::Local {
+            module: index,
+            binding: export.local,
+        }));
+    }
+    if let Some(export) = record
+        .indirect_exports
+        .iter()
+        .find(|export| export.exported == name)
+    {
+        let dependency = record.dependencies[&export.from];
+        return match export.imported {
+            ImportName::Namespace => Ok(Resolution::Found(ResolvedBinding::Namespace(dependency))),
+            ImportName::Name(imported) => {
+                resolve_export(interpreter, dependency, imported, resolve_set)
+            }
+        };
+    }
+    // `export *` never re-exports a default
+    if name == get_or_intern_string(DEFAULT_NAME) {
+        return Ok(Resolution::NotFound);
+    }
+    let stars: Vec<usize> = record
+        .star_exports
+        .iter()
+        .map(|from| record.dependencies[from])
+        .collect();
+    let mut found = None;
+    for dependency in stars {
+        match resolve_export(interpreter, dependency, name, resolve_set)? {
+            Resolution::Ambiguous => return Ok(Resolution::Ambiguous),
+            Resolution::NotFound => (),
+            Resolution::Found(binding) => match found {
+                Some(previous) if previous != binding => return Ok(Resolution::Ambiguous),
+                _ => found = Some(binding),
+            },
+        }
+    }
+    Ok(found.map_or(Resolution::NotFound, Resolution::Found))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getexportednames
+fn exported_names(
+    interpreter: &Interpreter,
+    index: usize,
+    visited: &mut Vec<usize>,
+) -> Vec<SymbolU32> {
+    if visited.contains(&index) {
+        return vec![];
+    }
+    visited.push(index);
+    let record = &interpreter.modules[index];
+    let mut names: Vec<SymbolU32> = record
+        .local_exports
+        .iter()
+        .map(|export| export.exported)
+        .chain(record.indirect_exports.iter().map(|export| export.exported))
+        .collect();
+    let default_name = get_or_intern_string(DEFAULT_NAME);
+    for from in record.star_exports.iter() {
+        let dependency = record.dependencies[from];
+        for name in exported_names(interpreter, dependency, visited) {
+            if name != default_name && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-modulenamespacecreate
+/// The object `import * as ns` binds: a frozen, prototype-less view of the module's exports,
+/// sorted by name. Each property reads the exported variable when accessed, so it stays live.
+pub fn get_namespace(interpreter: &mut Interpreter, index: usize) -> JSResult<JSValue> {
+    if let Some(namespace) = &interpreter.modules[index].namespace {
+        return Ok(namespace.clone());
+    }
+    let object_id = JSObject::new_ordinary_object(vec![], true, None, interpreter);
+    let namespace = JSValue::Object {
+        object_id,
+        kind: ObjectKind::Object,
+    };
+    // cached before the exports are resolved, modules in a cycle may export each other
+    interpreter.modules[index].namespace = Some(namespace.clone());
+
+    let mut names = exported_names(interpreter, index, &mut vec![]);
+    names.sort_by_key(|name| get_string_from_pool(name).unwrap_or_default());
+    for name in names {
+        let getter = match resolve_export(interpreter, index, name, &mut vec![])? {
+            Resolution::Found(ResolvedBinding::Local { module, binding }) => {
+                let environment = interpreter.modules[module].environment;
+                let Some(var_id) = interpreter
+                    .get_environment(environment)?
+                    .get_variable(binding)
+                else {
+                    continue;
+                };
+                let captures = vec![
+                    JSValue::new_number(&(var_id as f64)),
+                    JSValue::new_string(&name),
+                ];
+                JSObject::new_native_closure("", 0, read_binding, captures, interpreter)
+            }
+            Resolution::Found(ResolvedBinding::Namespace(module)) => {
+                let captures = vec![JSValue::new_number(&(module as f64))];
+                JSObject::new_native_closure("", 0, read_namespace, captures, interpreter)
+            }
+            _ => continue,
+        };
+        let getter = JSValue::Object {
+            object_id: getter,
+            kind: ObjectKind::Function,
+        };
+        let property = ObjectProperty::Accessor {
+            get: Some(getter),
+            set: None,
+            enumerable: true,
+            configurable: false,
+        };
+        interpreter
+            .get_object_mut(object_id)?
+            .define_property(name, property);
+    }
+    interpreter.get_object_mut(object_id)?.prevent_extensions();
+    Ok(namespace)
+}
+
+/// Namespace getter for an exported variable, captures the variable id and the export name.
+fn read_binding(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    let var_id = captures[0].get_number() as usize;
+    let variable = interpreter.get_var(var_id)?;
+    if !variable.is_initialized() {
+        let name = match &captures[1] {
+            JSValue::String { data } => get_string_from_pool(data).unwrap_or_default(),
+            _ => String::new(),
+        };
+        return Err(JSError::new_uninitialized_error(&name));
+    }
+    Ok(variable.get_value_cloned())
+}
+
+/// Namespace getter for `export * as ns`, captures the index of the re-exported module.
+fn read_namespace(
+    interpreter: &mut Interpreter,
+    captures: &[JSValue],
+    _this: &JSValue,
+    _args: &[JSValue],
+) -> JSResult<JSValue> {
+    get_namespace(interpreter, captures[0].get_number() as usize)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-innermoduleevaluation
+/// Evaluates dependencies first, in the order they were requested. A module already being
+/// evaluated is part of a cycle and is skipped, its bindings may still be uninitialized.
+fn evaluate_module(interpreter: &mut Interpreter, index: usize) -> JSResult<()> {
+    match &interpreter.modules[index].status {
+        ModuleStatus::Errored(e) => return Err(e.clone()),
+        ModuleStatus::Evaluating | ModuleStatus::Evaluated => return Ok(()),
+        ModuleStatus::Unlinked | ModuleStatus::Linking => {
+            return Err(JSError::new("Module has not been linked"));
+        }
+        ModuleStatus::Linked => (),
+    }
+    interpreter.modules[index].status = ModuleStatus::Evaluating;
+    let record = &interpreter.modules[index];
+    let dependencies: Vec<usize> = record
+        .requested
+        .iter()
+        .map(|specifier| record.dependencies[specifier])
+        .collect();
+    let body = Arc::clone(&record.body);
+    let environment = record.environment;
+
+    let result = dependencies
+        .into_iter()
+        .try_for_each(|dependency| evaluate_module(interpreter, dependency))
+        .and_then(|_| {
+            interpreter.enter_scope(Some(environment));
+            let result = body
+                .iter()
+                .filter(|stmt| !stmt.is_hoisted_function())
+                .try_for_each(|stmt| stmt.evaluate(interpreter).map(|_| ()));
+            interpreter.leave_scope();
+            result
+        });
+    interpreter.modules[index].status = match &result {
+        Ok(_) => ModuleStatus::Evaluated,
+        Err(e) => ModuleStatus::Errored(e.clone()),
+    };
+    result
+}
+
+/// `import(specifier)`: the module is loaded in a job once the current script is done, the
+/// returned promise settles with its namespace or with the error that stopped it.
+pub fn dynamic_import(
+    interpreter: &mut Interpreter,
+    specifier: &JSValue,
+    referrer: Option<SymbolU32>,
+) -> JSResult<JSValue> {
+    let promise = create_promise(interpreter, None);
+    match specifier.to_string(interpreter) {
+        Ok(specifier) => interpreter.enqueue_job(Job::DynamicImport {
+            specifier: get_string_from_pool(&specifier).unwrap_or_default(),
+            referrer: referrer.and_then(|referrer| get_string_from_pool(&referrer)),
+            promise: promise.clone(),
+        }),
+        Err(e) => {
+            let reason = interpreter.error_value(&e)?;
+            reject_promise(interpreter, &promise, reason)?;
+        }
+    }
+    Ok(promise)
+}
+
+pub fn dynamic_import_job(
+    interpreter: &mut Interpreter,
+    specifier: &str,
+    referrer: Option<&str>,
+    promise: JSValue,
+) -> JSResult<()> {
+    match import_module(interpreter, specifier, referrer) {
+        Ok(index) => {
+            let namespace = get_namespace(interpreter, index)?;
+            resolve_promise(interpreter, &promise, namespace)
+        }
+        Err(e) if e.is_exception() => {
+            let reason = interpreter.error_value(&e)?;
+            reject_promise(interpreter, &promise, reason)
+        }
+        Err(e) => Err(e),
+    }
+}
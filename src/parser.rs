@@ -7,13 +7,15 @@ use string_interner::symbol::SymbolU32;
 
 use crate::{
     Interpreter,
+    builtins::regexp::validate_regexp_literal,
+    constants::DEFAULT_NAME,
     errors::JSError,
-    expr::{Expr, ObjectCallKind},
+    expr::{ClassDefinition, ClassMember, Expr, ObjectCallKind, PropertyDefinition, PropertyKind},
     global::get_or_intern_string,
-    stmt::Stmt,
+    stmt::{ExportDeclaration, ExportSpecifier, ImportBinding, ImportName, Stmt},
     token::{Kind, Token},
     utils::check_identifier,
-    values::{JSResult, JSValue},
+    values::{BodyKind, FunctionKind, JSResult, JSValue, bigint::string_to_big_int},
 };
 
 pub struct Parser<'a> {
@@ -22,6 +24,10 @@ pub struct Parser<'a> {
     had_error: bool,
     tokens: Peekable<IntoIter<Token>>,
     interpreter: &'a mut Interpreter,
+    // one entry per enclosing function body, true for generators
+    function_context: Vec<BodyKind>,
+    // key of the module being parsed, None for scripts
+    module: Option<SymbolU32>,
 }
 
 impl<'a> Parser<'a> {
@@ -34,65 +40,257 @@ impl<'a> Parser<'a> {
             had_error: false,
             tokens: iter,
             interpreter,
+            function_context: vec![],
+            module: None,
         }
     }
 
+    /// A parser for module code, which may contain `import` and `export` declarations.
+    pub fn new_module(
+        token_list: Vec<Token>,
+        interpreter: &'a mut Interpreter,
+        module: SymbolU32,
+    ) -> Self {
+        let mut parser = Self::new(token_list, interpreter);
+        parser.module = Some(module);
+        parser
+    }
+
+    pub fn errors(&self) -> &[JSError] {
+        &self.errors
+    }
+
     pub fn parse(&mut self) -> Vec<Stmt> {
         let mut program: Vec<Stmt> = vec![];
         while !self.current_token.is_kind(&Kind::Eof) {
-            let stmt_res = self.handle_statements();
+            let start = self.current_token.get_span().start;
+            let stmt_res = self.handle_module_item();
             match stmt_res {
                 Ok(stmt) => {
                     program.push(stmt);
                 }
                 Err(e) => {
-                    eprintln!("{}", e.message);
                     self.errors.push(e);
                     self.had_error = true;
+                    // skip the offending token so a failed statement can't stall the loop
+                    if self.current_token.get_span().start == start {
+                        self.next_token();
+                    }
                 }
             }
         }
         program
     }
 
+    // import and export declarations may only appear at the top level
+    fn handle_module_item(&mut self) -> JSResult<Stmt> {
+        let temp_eof = Token::new_eof();
+        let is_dynamic_import = self.peek().unwrap_or(&temp_eof).is_kind(&Kind::LeftParen);
+        match self.current_token.get_kind() {
+            Kind::Import if !is_dynamic_import => {
+                if self.module.is_none() {
+                    return Err(JSError::new(
+                        "Uncaught SyntaxError: Cannot use import statement outside a module",
+                    ));
+                }
+                self.next_token();
+                self.handle_import_declaration()
+            }
+            Kind::Export if self.module.is_some() => {
+                self.next_token();
+                self.handle_export_declaration()
+            }
+            _ => self.handle_statements(),
+        }
+    }
+
+    // `import` has been consumed
+    fn handle_import_declaration(&mut self) -> JSResult<Stmt> {
+        let mut bindings = vec![];
+        // import "./x.js"; only runs the module
+        if !self.current_token.is_kind(&Kind::String) {
+            if self.current_token.is_kind(&Kind::Identifier) {
+                let local = self.get_binding_name()?;
+                bindings.push(ImportBinding {
+                    imported: ImportName::Name(get_or_intern_string(DEFAULT_NAME)),
+                    local,
+                });
+                if self.current_token.is_kind(&Kind::Comma) {
+                    self.next_token();
+                }
+            }
+            if self.current_token.is_kind(&Kind::Star) {
+                self.next_token();
+                self.expect_contextual_keyword("as", "ImportDeclaration")?;
+                let local = self.get_binding_name()?;
+                bindings.push(ImportBinding {
+                    imported: ImportName::Namespace,
+                    local,
+                });
+            } else if self.current_token.is_kind(&Kind::LeftCurly) {
+                self.next_token();
+                while !self.current_token.is_kind(&Kind::RightCurly) {
+                    let is_identifier = self.current_token.is_kind(&Kind::Identifier);
+                    let name = self.get_property_name()?;
+                    let local = if self.is_contextual_keyword("as") {
+                        self.next_token();
+                        self.get_binding_name()?
+                    } else if is_identifier {
+                        check_identifier(&name)?;
+                        get_or_intern_string(&name)
+                    } else {
+                        return Err(JSError::new(&format!(
+                            "Uncaught SyntaxError: Unexpected reserved word '{name}'"
+                        )));
+                    };
+                    bindings.push(ImportBinding {
+                        imported: ImportName::Name(get_or_intern_string(&name)),
+                        local,
+                    });
+                    if !self.current_token.is_kind(&Kind::Comma) {
+                        break;
+                    }
+                    self.next_token();
+                }
+                self.expect_and_consume(&Kind::RightCurly, "ImportDeclaration")?;
+            }
+            self.expect_contextual_keyword("from", "ImportDeclaration")?;
+        }
+        let specifier = self.get_module_specifier()?;
+        self.expect_and_consume(&Kind::Semicolon, "ImportDeclaration")?;
+        Ok(Stmt::new_import(specifier, bindings))
+    }
+
+    // `export` has been consumed
+    fn handle_export_declaration(&mut self) -> JSResult<Stmt> {
+        let is_async_function = self.is_async_function_start();
+        let declaration = match self.current_token.get_kind() {
+            Kind::Default => {
+                self.next_token();
+                self.handle_export_default()?
+            }
+            Kind::Star => {
+                self.next_token();
+                let alias = if self.is_contextual_keyword("as") {
+                    self.next_token();
+                    Some(get_or_intern_string(&self.get_property_name()?))
+                } else {
+                    None
+                };
+                self.expect_contextual_keyword("from", "ExportDeclaration")?;
+                let from = self.get_module_specifier()?;
+                self.expect_and_consume(&Kind::Semicolon, "ExportDeclaration")?;
+                ExportDeclaration::All { from, alias }
+            }
+            Kind::LeftCurly => {
+                self.next_token();
+                let mut specifiers = vec![];
+                while !self.current_token.is_kind(&Kind::RightCurly) {
+                    let local = get_or_intern_string(&self.get_property_name()?);
+                    let exported = if self.is_contextual_keyword("as") {
+                        self.next_token();
+                        get_or_intern_string(&self.get_property_name()?)
+                    } else {
+                        local
+                    };
+                    specifiers.push(ExportSpecifier { local, exported });
+                    if !self.current_token.is_kind(&Kind::Comma) {
+                        break;
+                    }
+                    self.next_token();
+                }
+                self.expect_and_consume(&Kind::RightCurly, "ExportDeclaration")?;
+                let from = if self.is_contextual_keyword("from") {
+                    self.next_token();
+                    Some(self.get_module_specifier()?)
+                } else {
+                    None
+                };
+                self.expect_and_consume(&Kind::Semicolon, "ExportDeclaration")?;
+                ExportDeclaration::List { specifiers, from }
+            }
+            Kind::Let | Kind::Const | Kind::Var | Kind::Function | Kind::Class => {
+                ExportDeclaration::Declaration(self.handle_statements()?)
+            }
+            Kind::Identifier if is_async_function => {
+                ExportDeclaration::Declaration(self.handle_statements()?)
+            }
+            token => {
+                return Err(JSError::new(&format!(
+                    "Uncaught SyntaxError: Unexpected token '{token:?}' after export"
+                )));
+            }
+        };
+        Ok(Stmt::new_export(declaration))
+    }
+
+    // `export default` has been consumed. named functions and classes are declarations,
+    // anything else is an expression
+    fn handle_export_default(&mut self) -> JSResult<ExportDeclaration> {
+        let is_async = self.is_async_function_start();
+        if is_async || self.current_token.is_kind(&Kind::Function) {
+            if is_async {
+                self.next_token();
+            }
+            self.next_token();
+            let body_kind = self.handle_body_kind(is_async)?;
+            if !self.current_token.is_kind(&Kind::LeftParen) {
+                let declaration = self.handle_function_declaration(body_kind)?;
+                return Ok(ExportDeclaration::DefaultDeclaration(declaration));
+            }
+            let parameters = self.handle_parameters("FunctionExpression")?;
+            let body = self.handle_function_body(body_kind)?;
+            let function =
+                Expr::new_function_decl(None, parameters, body, FunctionKind::Normal, body_kind);
+            return Ok(ExportDeclaration::Default(function));
+        }
+        if self.current_token.is_kind(&Kind::Class) {
+            self.next_token();
+            let is_named = self.current_token.is_kind(&Kind::Identifier);
+            let definition = self.handle_class()?;
+            return match is_named {
+                true => Ok(ExportDeclaration::DefaultDeclaration(Stmt::new_class(
+                    definition,
+                ))),
+                false => Ok(ExportDeclaration::Default(Expr::new_class(definition))),
+            };
+        }
+        let expr = self.handle_expressions()?;
+        self.expect_and_consume(&Kind::Semicolon, "ExportDefaultDeclaration")?;
+        Ok(ExportDeclaration::Default(expr))
+    }
+
     fn handle_statements(&mut self) -> JSResult<Stmt> {
+        let is_async_function = self.is_async_function_start();
         match self.current_token.get_kind() {
             Kind::Let | Kind::Var | Kind::Const => {
                 let is_mutable = self.current_token.is_kinds(vec![Kind::Let, Kind::Var]);
                 self.next_token();
 
                 let ident = self.get_identifier()?;
-                let expr = if self.current_token.is_kind(&Kind::Equals) {
-                    self.next_token(); // consume equals
-                    Some(self.handle_expressions()?)
-                } else {
-                    None
-                };
-                self.expect_and_consume(&Kind::Semicolon, "VariableDecl")?;
-                Ok(Stmt::new_variable(is_mutable, ident, expr))
+                self.handle_variable_initializer(is_mutable, ident)
             }
 
             Kind::Function => {
                 self.next_token();
-                let ident = self.get_identifier()?;
-                self.expect_and_consume(&Kind::LeftParen, "FunctionDecl")?;
+                let body_kind = self.handle_body_kind(false)?;
+                self.handle_function_declaration(body_kind)
+            }
 
-                let parameters = if self.current_token.is_kind(&Kind::RightParen) {
-                    vec![]
-                } else {
-                    let mut params = vec![];
-                    let first_param = self.get_identifier()?;
-                    params.push(first_param);
-                    while self.current_token.is_kind(&Kind::Comma) {
-                        self.next_token();
-                        let param = self.get_identifier()?;
-                        params.push(param);
-                    }
-                    params
-                };
-                self.expect_and_consume(&Kind::RightParen, "FunctionDecl")?;
-                let body = self.handle_statements()?;
-                Ok(Stmt::new_function(ident, parameters, body))
+            Kind::Identifier if is_async_function => {
+                self.next_token();
+                self.next_token();
+                let body_kind = self.handle_body_kind(true)?;
+                self.handle_function_declaration(body_kind)
+            }
+
+            Kind::Class => {
+                self.next_token();
+                if !self.current_token.is_kind(&Kind::Identifier) {
+                    return Err(JSError::new("Uncaught SyntaxError: class name required"));
+                }
+                let definition = self.handle_class()?;
+                Ok(Stmt::new_class(definition))
             }
 
             Kind::LeftCurly => {
@@ -117,6 +315,42 @@ impl<'a> Parser<'a> {
                 Ok(Stmt::new_return(Some(expr)))
             }
 
+            Kind::Throw => {
+                self.next_token();
+                let expr = self.handle_expressions()?;
+                self.expect_and_consume(&Kind::Semicolon, "ThrowStatement")?;
+                Ok(Stmt::new_throw(expr))
+            }
+
+            Kind::Try => {
+                self.next_token();
+                let block = self.handle_block("TryStatement")?;
+                let mut parameter = None;
+                let mut handler = None;
+                if self.current_token.is_kind(&Kind::Catch) {
+                    self.next_token();
+                    // the binding is optional: catch { ... }
+                    if self.current_token.is_kind(&Kind::LeftParen) {
+                        self.next_token();
+                        parameter = Some(self.get_identifier()?);
+                        self.expect_and_consume(&Kind::RightParen, "CatchClause")?;
+                    }
+                    handler = Some(self.handle_block("CatchClause")?);
+                }
+                let finalizer = if self.current_token.is_kind(&Kind::Finally) {
+                    self.next_token();
+                    Some(self.handle_block("FinallyClause")?)
+                } else {
+                    None
+                };
+                if handler.is_none() && finalizer.is_none() {
+                    return Err(JSError::new(
+                        "Uncaught SyntaxError: Missing catch or finally after try",
+                    ));
+                }
+                Ok(Stmt::new_try(block, parameter, handler, finalizer))
+            }
+
             Kind::Break => {
                 self.next_token();
                 self.expect_and_consume(&Kind::Semicolon, "BreakStatement")?;
@@ -160,6 +394,22 @@ impl<'a> Parser<'a> {
                 let initializer = if self.current_token.is_kind(&Kind::Semicolon) {
                     self.next_token(); // statements consume semis
                     None
+                } else if self
+                    .current_token
+                    .is_kinds(vec![Kind::Let, Kind::Var, Kind::Const])
+                {
+                    let is_mutable = self.current_token.is_kinds(vec![Kind::Let, Kind::Var]);
+                    self.next_token();
+                    let ident = self.get_identifier()?;
+                    // `of` isn't a keyword, so for-of is only recognized here
+                    if self.is_contextual_keyword("of") {
+                        self.next_token();
+                        let iterable = self.handle_expressions()?;
+                        self.expect_and_consume(&Kind::RightParen, "ForOfStatement")?;
+                        let body = self.handle_statements()?;
+                        return Ok(Stmt::new_for_of(is_mutable, ident, iterable, body));
+                    }
+                    Some(self.handle_variable_initializer(is_mutable, ident)?)
                 } else {
                     Some(self.handle_statements()?)
                 };
@@ -196,7 +446,29 @@ impl<'a> Parser<'a> {
         self.handle_assignment()
     }
 
+    // the rest of a let/const/var declaration after its name
+    fn handle_variable_initializer(&mut self, is_mutable: bool, ident: Expr) -> JSResult<Stmt> {
+        let expr = if self.current_token.is_kind(&Kind::Equals) {
+            self.next_token(); // consume equals
+            Some(self.handle_expressions()?)
+        } else {
+            None
+        };
+        self.expect_and_consume(&Kind::Semicolon, "VariableDecl")?;
+        Ok(Stmt::new_variable(is_mutable, ident, expr))
+    }
+
+    fn handle_block(&mut self, caller: &str) -> JSResult<Stmt> {
+        if !self.current_token.is_kind(&Kind::LeftCurly) {
+            return Err(JSError::new(&format!("Expected '{{' after {caller}")));
+        }
+        self.handle_statements()
+    }
+
     fn handle_assignment(&mut self) -> JSResult<Expr> {
+        if self.current_token.is_kind(&Kind::Yield) {
+            return self.handle_yield();
+        }
         let mut left = self.handle_logical_or()?;
         if self.current_token.is_kinds(vec![
             Kind::Equals,
@@ -208,6 +480,10 @@ impl<'a> Parser<'a> {
             Kind::LogicalAndEquals,
             Kind::BitwiseOrEquals,
             Kind::BitwiseAndEquals,
+            Kind::BitwiseXorEquals,
+            Kind::LeftShiftEquals,
+            Kind::SignedRightShiftEquals,
+            Kind::UnsignedRightShiftEquals,
         ]) {
             let op_token = self.current_token.clone();
             self.next_token();
@@ -226,6 +502,10 @@ impl<'a> Parser<'a> {
                     Kind::LogicalAndEquals => Kind::LogicalAnd,
                     Kind::BitwiseOrEquals => Kind::BitwiseOr,
                     Kind::BitwiseAndEquals => Kind::BitwiseAnd,
+                    Kind::BitwiseXorEquals => Kind::BitwiseXor,
+                    Kind::LeftShiftEquals => Kind::LeftShift,
+                    Kind::SignedRightShiftEquals => Kind::SignedRightShift,
+                    Kind::UnsignedRightShiftEquals => Kind::UnsignedRightShift,
                     _ => panic!("add the kind to the if list, dork"),
                 };
                 let right = Expr::new_binary(op, left.clone(), right);
@@ -235,6 +515,36 @@ impl<'a> Parser<'a> {
         Ok(left)
     }
 
+    // yield binds looser than everything but the comma, and its operand is optional
+    fn handle_yield(&mut self) -> JSResult<Expr> {
+        if self.function_context.last() != Some(&BodyKind::Generator) {
+            return Err(JSError::new(
+                "Uncaught SyntaxError: yield is only valid in generator functions",
+            ));
+        }
+        self.next_token();
+        let delegate = self.current_token.is_kind(&Kind::Star);
+        if delegate {
+            self.next_token();
+        }
+        let has_argument = delegate
+            || !self.current_token.is_kinds(vec![
+                Kind::RightParen,
+                Kind::RightSquare,
+                Kind::RightCurly,
+                Kind::Comma,
+                Kind::Semicolon,
+                Kind::Colon,
+                Kind::Eof,
+            ]);
+        let argument = if has_argument {
+            Some(self.handle_assignment()?)
+        } else {
+            None
+        };
+        Ok(Expr::new_yield(argument, delegate))
+    }
+
     fn handle_logical_or(&mut self) -> JSResult<Expr> {
         let mut left = self.handle_logical_and()?;
         while self.current_token.is_kinds(vec![Kind::LogicalOr]) {
@@ -247,8 +557,41 @@ impl<'a> Parser<'a> {
     }
 
     fn handle_logical_and(&mut self) -> JSResult<Expr> {
-        let mut left = self.handle_equality()?;
+        let mut left = self.handle_bitwise_or()?;
         while self.current_token.is_kinds(vec![Kind::LogicalAnd]) {
+            let operator = self.current_token.get_kind().clone();
+            self.next_token();
+            let right = self.handle_bitwise_or()?;
+            left = Expr::new_binary(operator, left, right);
+        }
+        Ok(left)
+    }
+
+    fn handle_bitwise_or(&mut self) -> JSResult<Expr> {
+        let mut left = self.handle_bitwise_xor()?;
+        while self.current_token.is_kinds(vec![Kind::BitwiseOr]) {
+            let operator = self.current_token.get_kind().clone();
+            self.next_token();
+            let right = self.handle_bitwise_xor()?;
+            left = Expr::new_binary(operator, left, right);
+        }
+        Ok(left)
+    }
+
+    fn handle_bitwise_xor(&mut self) -> JSResult<Expr> {
+        let mut left = self.handle_bitwise_and()?;
+        while self.current_token.is_kinds(vec![Kind::BitwiseXor]) {
+            let operator = self.current_token.get_kind().clone();
+            self.next_token();
+            let right = self.handle_bitwise_and()?;
+            left = Expr::new_binary(operator, left, right);
+        }
+        Ok(left)
+    }
+
+    fn handle_bitwise_and(&mut self) -> JSResult<Expr> {
+        let mut left = self.handle_equality()?;
+        while self.current_token.is_kinds(vec![Kind::BitwiseAnd]) {
             let operator = self.current_token.get_kind().clone();
             self.next_token();
             let right = self.handle_equality()?;
@@ -274,12 +617,28 @@ impl<'a> Parser<'a> {
     }
 
     fn handle_comparisons(&mut self) -> JSResult<Expr> {
-        let mut left = self.handle_terms()?;
+        let mut left = self.handle_shifts()?;
         while self.current_token.is_kinds(vec![
             Kind::LessThan,
             Kind::GreaterThan,
             Kind::LessThanOrEquals,
             Kind::GreaterThanOrEquals,
+            Kind::Instanceof,
+        ]) {
+            let operator = self.current_token.get_kind().clone();
+            self.next_token();
+            let right = self.handle_shifts()?;
+            left = Expr::new_binary(operator, left, right);
+        }
+        Ok(left)
+    }
+
+    fn handle_shifts(&mut self) -> JSResult<Expr> {
+        let mut left = self.handle_terms()?;
+        while self.current_token.is_kinds(vec![
+            Kind::LeftShift,
+            Kind::SignedRightShift,
+            Kind::UnsignedRightShift,
         ]) {
             let operator = self.current_token.get_kind().clone();
             self.next_token();
@@ -315,6 +674,16 @@ impl<'a> Parser<'a> {
     }
 
     fn handle_unaries(&mut self) -> JSResult<Expr> {
+        if self.current_token.is_kind(&Kind::Await) {
+            if self.function_context.last() != Some(&BodyKind::Async) {
+                return Err(JSError::new(
+                    "Uncaught SyntaxError: await is only valid in async functions",
+                ));
+            }
+            self.next_token();
+            let argument = self.handle_unaries()?;
+            return Ok(Expr::new_await(argument));
+        }
         if self.current_token.is_unary_operator() {
             let operator = self.current_token.clone();
             self.next_token();
@@ -336,44 +705,380 @@ impl<'a> Parser<'a> {
     }
 
     fn handle_call(&mut self) -> JSResult<Expr> {
-        let mut left = self.handle_primaries()?;
+        let mut left = if self.current_token.is_kind(&Kind::New) {
+            self.handle_new()?
+        } else {
+            self.handle_primaries()?
+        };
         while self
             .current_token
             .is_kinds(vec![Kind::Dot, Kind::LeftParen, Kind::LeftSquare])
         {
-            let prev = self.current_token.clone();
+            if self.current_token.is_kind(&Kind::LeftParen) {
+                self.next_token();
+                let args = self.handle_arguments()?;
+                left = Expr::new_function_call(left, args);
+            } else {
+                left = self.handle_member(left)?;
+            }
+        }
+        Ok(left)
+    }
+
+    // new Foo.Bar(args) - the callee is a member expression, the argument list is optional
+    fn handle_new(&mut self) -> JSResult<Expr> {
+        self.expect_and_consume(&Kind::New, "NewExpression")?;
+        let mut callee = if self.current_token.is_kind(&Kind::New) {
+            self.handle_new()?
+        } else {
+            self.handle_primaries()?
+        };
+        while self
+            .current_token
+            .is_kinds(vec![Kind::Dot, Kind::LeftSquare])
+        {
+            callee = self.handle_member(callee)?;
+        }
+        let args = if self.current_token.is_kind(&Kind::LeftParen) {
             self.next_token();
-            match prev.get_kind() {
-                Kind::Dot => {
-                    let ident = self.get_identifier()?;
-                    left = Expr::new_object_call(left, ident, ObjectCallKind::Dot);
-                }
-                Kind::LeftParen => {
-                    let args = if self.current_token.is_kind(&Kind::RightParen) {
-                        vec![]
-                    } else {
-                        let mut args = Vec::with_capacity(6);
-                        let arg = self.handle_expressions()?;
-                        args.push(arg);
-                        while self.current_token.is_kind(&Kind::Comma) {
-                            self.next_token();
-                            let param = self.handle_expressions()?;
-                            args.push(param);
-                        }
-                        args
-                    };
-                    self.expect_and_consume(&Kind::RightParen, "CallExpr")?;
-                    left = Expr::new_function_call(left, args);
-                }
-                Kind::LeftSquare => {
-                    let expr = self.handle_expressions()?;
-                    self.expect_and_consume(&Kind::RightSquare, "SquareCallExpr")?;
-                    left = Expr::new_object_call(left, expr, ObjectCallKind::Square);
+            self.handle_arguments()?
+        } else {
+            vec![]
+        };
+        Ok(Expr::new_new(callee, args))
+    }
+
+    fn handle_member(&mut self, object: Expr) -> JSResult<Expr> {
+        if self.current_token.is_kind(&Kind::Dot) {
+            self.next_token();
+            let name = self.get_property_name()?;
+            let key = Expr::new_literal(JSValue::new_string(&get_or_intern_string(&name)));
+            return Ok(Expr::new_object_call(object, key, ObjectCallKind::Dot));
+        }
+        self.expect_and_consume(&Kind::LeftSquare, "SquareCallExpr")?;
+        let expr = self.handle_expressions()?;
+        self.expect_and_consume(&Kind::RightSquare, "SquareCallExpr")?;
+        Ok(Expr::new_object_call(object, expr, ObjectCallKind::Square))
+    }
+
+    // expects the left paren to already be consumed
+    fn handle_arguments(&mut self) -> JSResult<Vec<Expr>> {
+        let mut args = Vec::with_capacity(6);
+        while !self.current_token.is_kind(&Kind::RightParen) {
+            args.push(self.handle_spread_or_expression()?);
+            if !self.current_token.is_kind(&Kind::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+        self.expect_and_consume(&Kind::RightParen, "CallExpr")?;
+        Ok(args)
+    }
+
+    fn handle_spread_or_expression(&mut self) -> JSResult<Expr> {
+        if self.current_token.is_kind(&Kind::Ellipsis) {
+            self.next_token();
+            return Ok(Expr::new_spread(self.handle_expressions()?));
+        }
+        self.handle_expressions()
+    }
+
+    fn handle_parameters(&mut self, caller: &str) -> JSResult<Vec<Expr>> {
+        self.expect_and_consume(&Kind::LeftParen, caller)?;
+        let mut params = Vec::with_capacity(6); // that'd be a lotta args
+        while !self.current_token.is_kind(&Kind::RightParen) {
+            params.push(self.get_identifier()?);
+            if !self.current_token.is_kind(&Kind::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+        self.expect_and_consume(&Kind::RightParen, caller)?;
+        Ok(params)
+    }
+
+    // parameters and body of a method, getter or setter
+    fn handle_method(&mut self, kind: FunctionKind, body_kind: BodyKind) -> JSResult<Expr> {
+        let parameters = self.handle_parameters("MethodDefinition")?;
+        if !self.current_token.is_kind(&Kind::LeftCurly) {
+            return Err(JSError::new("Expected '{' before method body"));
+        }
+        let body = self.handle_function_body(body_kind)?;
+        Ok(Expr::new_function_decl(
+            None, parameters, body, kind, body_kind,
+        ))
+    }
+
+    // `function` and any `async` have already been consumed
+    fn handle_function_declaration(&mut self, body_kind: BodyKind) -> JSResult<Stmt> {
+        let ident = self.get_identifier()?;
+        let parameters = self.handle_parameters("FunctionDecl")?;
+        let body = self.handle_function_body(body_kind)?;
+        Ok(Stmt::new_function(ident, parameters, body, body_kind))
+    }
+
+    // tracks whether `yield` or `await` is allowed while parsing the body
+    fn handle_function_body(&mut self, body_kind: BodyKind) -> JSResult<Stmt> {
+        self.function_context.push(body_kind);
+        let body = self.handle_statements();
+        self.function_context.pop();
+        body
+    }
+
+    // the arrow token has already been consumed
+    fn handle_arrow_function(&mut self, parameters: Vec<Expr>, is_async: bool) -> JSResult<Expr> {
+        let body_kind = match is_async {
+            true => BodyKind::Async,
+            false => BodyKind::Normal,
+        };
+        self.function_context.push(body_kind);
+        let body = if self.current_token.is_kind(&Kind::LeftCurly) {
+            self.handle_statements()
+        } else {
+            self.handle_expressions().map(Stmt::new_expression)
+        };
+        self.function_context.pop();
+        Ok(Expr::new_function_decl(
+            None,
+            parameters,
+            body?,
+            FunctionKind::Arrow,
+            body_kind,
+        ))
+    }
+
+    // a `*` after `function` (or before a method name) makes a generator
+    fn handle_body_kind(&mut self, is_async: bool) -> JSResult<BodyKind> {
+        let is_generator = self.current_token.is_kind(&Kind::Star);
+        if is_generator {
+            self.next_token();
+        }
+        match (is_async, is_generator) {
+            (true, true) => Err(JSError::new(
+                "Uncaught SyntaxError: async generators are not supported",
+            )),
+            (true, false) => Ok(BodyKind::Async),
+            (false, true) => Ok(BodyKind::Generator),
+            (false, false) => Ok(BodyKind::Normal),
+        }
+    }
+
+    /// `async` is an ordinary identifier unless `function` follows it.
+    fn is_async_function_start(&mut self) -> bool {
+        let temp_eof = Token::new_eof();
+        self.is_contextual_keyword("async")
+            && self.peek().unwrap_or(&temp_eof).is_kind(&Kind::Function)
+    }
+
+    /// Like `get`/`set`, `async` only starts a method when a property name (or `*`) follows.
+    fn is_async_method_start(&mut self) -> bool {
+        if !self.is_contextual_keyword("async") {
+            return false;
+        }
+        let temp_eof = Token::new_eof();
+        !self.peek().unwrap_or(&temp_eof).is_kinds(vec![
+            Kind::Colon,
+            Kind::LeftParen,
+            Kind::Comma,
+            Kind::RightCurly,
+            Kind::Semicolon,
+            Kind::Equals,
+            Kind::Eof,
+        ])
+    }
+
+    fn is_contextual_keyword(&self, keyword: &str) -> bool {
+        self.current_token.is_kind(&Kind::Identifier)
+            && self
+                .interpreter
+                .get_source_at_span(&self.current_token.get_span())
+                == keyword
+    }
+
+    // identifiers, keywords, strings, numbers or [computed] keys
+    fn handle_property_key(&mut self) -> JSResult<Expr> {
+        if self.current_token.is_kind(&Kind::LeftSquare) {
+            self.next_token();
+            let expr = self.handle_expressions()?;
+            self.expect_and_consume(&Kind::RightSquare, "ComputedPropertyName")?;
+            return Ok(expr);
+        }
+        if self.current_token.is_kind(&Kind::Number) {
+            let source = self
+                .interpreter
+                .get_source_at_span(&self.current_token.get_span());
+            self.next_token();
+            let num = source
+                .parse::<f64>()
+                .map_err(|_| JSError::new("Invalid number"))?;
+            return Ok(Expr::new_literal(JSValue::new_number(&num)));
+        }
+        let name = if self.current_token.is_kind(&Kind::String) {
+            let source = self
+                .interpreter
+                .get_source_at_span(&self.current_token.get_span());
+            self.next_token();
+            source
+        } else {
+            self.get_property_name()?
+        };
+        Ok(Expr::new_literal(JSValue::new_string(
+            &get_or_intern_string(&name),
+        )))
+    }
+
+    /// `get`/`set` only start an accessor when another property name follows them.
+    fn is_accessor_start(&mut self) -> Option<PropertyKind> {
+        if !self.current_token.is_kind(&Kind::Identifier) {
+            return None;
+        }
+        let source = self
+            .interpreter
+            .get_source_at_span(&self.current_token.get_span());
+        let kind = match source.as_str() {
+            "get" => PropertyKind::Getter,
+            "set" => PropertyKind::Setter,
+            _ => return None,
+        };
+        let temp_eof = Token::new_eof();
+        let peeked = self.peek().unwrap_or(&temp_eof);
+        if peeked.is_kinds(vec![
+            Kind::Colon,
+            Kind::LeftParen,
+            Kind::Comma,
+            Kind::RightCurly,
+            Kind::Semicolon,
+            Kind::Equals,
+            Kind::Eof,
+        ]) {
+            return None;
+        }
+        Some(kind)
+    }
+
+    fn handle_property_definition(&mut self) -> JSResult<PropertyDefinition> {
+        if let Some(kind) = self.is_accessor_start() {
+            self.next_token();
+            let key = self.handle_property_key()?;
+            let value = self.handle_method(FunctionKind::Method, BodyKind::Normal)?;
+            return Ok(PropertyDefinition { kind, key, value });
+        }
+        let is_async = self.is_async_method_start();
+        if is_async {
+            self.next_token();
+        }
+        let body_kind = self.handle_body_kind(is_async)?;
+        if body_kind != BodyKind::Normal {
+            let key = self.handle_property_key()?;
+            let value = self.handle_method(FunctionKind::Method, body_kind)?;
+            return Ok(PropertyDefinition {
+                kind: PropertyKind::Value,
+                key,
+                value,
+            });
+        }
+        let is_identifier = self.current_token.is_kind(&Kind::Identifier);
+        let shorthand_source = self
+            .interpreter
+            .get_source_at_span(&self.current_token.get_span());
+        let key = self.handle_property_key()?;
+        let value = match self.current_token.get_kind() {
+            Kind::Colon => {
+                self.next_token();
+                self.handle_expressions()?
+            }
+            Kind::LeftParen => self.handle_method(FunctionKind::Method, BodyKind::Normal)?,
+            // shorthand { x } reads the variable x
+            Kind::Comma | Kind::RightCurly if is_identifier => {
+                Expr::new_identifier(&get_or_intern_string(&shorthand_source))
+            }
+            _ => return Err(JSError::new("Expected ':' after property name")),
+        };
+        Ok(PropertyDefinition {
+            kind: PropertyKind::Value,
+            key,
+            value,
+        })
+    }
+
+    // the class keyword has already been consumed
+    fn handle_class(&mut self) -> JSResult<ClassDefinition> {
+        let identifier = if self.current_token.is_kind(&Kind::Identifier) {
+            match self.get_identifier()? {
+                Expr::Identifier { string_index } => Some(string_index),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        self.expect_and_consume(&Kind::LeftCurly, "ClassDeclaration")?;
+        let mut constructor = None;
+        let mut members = vec![];
+        while !self.current_token.is_kind(&Kind::RightCurly) {
+            if self.current_token.is_kind(&Kind::Semicolon) {
+                self.next_token();
+                continue;
+            }
+            if self.current_token.is_kind(&Kind::Eof) {
+                return Err(JSError::new("Expected '}' after class body"));
+            }
+            let temp_eof = Token::new_eof();
+            let is_static = self.current_token.is_kind(&Kind::Static)
+                && !self
+                    .peek()
+                    .unwrap_or(&temp_eof)
+                    .is_kinds(vec![Kind::LeftParen]);
+            if is_static {
+                self.next_token();
+            }
+            let is_async = self.is_async_method_start();
+            if is_async {
+                self.next_token();
+            }
+            let body_kind = self.handle_body_kind(is_async)?;
+            let accessor = match body_kind {
+                BodyKind::Normal => self.is_accessor_start(),
+                _ => None,
+            };
+            if accessor.is_some() {
+                self.next_token();
+            }
+            let is_constructor_name = !is_static
+                && body_kind == BodyKind::Normal
+                && accessor.is_none()
+                && self.current_token.is_kind(&Kind::Identifier)
+                && self
+                    .interpreter
+                    .get_source_at_span(&self.current_token.get_span())
+                    == "constructor";
+            let key = self.handle_property_key()?;
+            if is_constructor_name {
+                if constructor.is_some() {
+                    return Err(JSError::new(
+                        "Uncaught SyntaxError: A class may only have one constructor",
+                    ));
                 }
-                _ => (),
+                constructor =
+                    Some(self.handle_method(FunctionKind::ClassConstructor, BodyKind::Normal)?);
+                continue;
             }
+            let value = self.handle_method(FunctionKind::Method, body_kind)?;
+            let definition = PropertyDefinition {
+                kind: accessor.unwrap_or(PropertyKind::Value),
+                key,
+                value,
+            };
+            members.push(ClassMember {
+                is_static,
+                definition,
+            });
         }
-        Ok(left)
+        self.expect_and_consume(&Kind::RightCurly, "ClassDeclaration")?;
+        Ok(ClassDefinition {
+            identifier,
+            constructor,
+            members,
+        })
     }
 
     fn handle_primaries(&mut self) -> JSResult<Expr> {
@@ -391,193 +1096,165 @@ impl<'a> Parser<'a> {
                     .map_err(|_| JSError::new("Invalid number"))?;
                 return Ok(Expr::new_literal(JSValue::new_number(&num)));
             }
+            Kind::BigInt => {
+                let digits = source_value.trim_end_matches('n').replace('_', "");
+                let value =
+                    string_to_big_int(&digits).ok_or_else(|| JSError::new("Invalid BigInt"))?;
+                Ok(Expr::new_literal(JSValue::new_big_int(value)))
+            }
             Kind::String => {
                 let idx = get_or_intern_string(&source_value);
                 Ok(Expr::new_literal(JSValue::new_string(&idx)))
             }
+            Kind::RegExp => {
+                // the token runs from the opening slash to the end of the flags
+                let end = source_value.rfind('/').unwrap_or_default();
+                let (pattern, flags) = (&source_value[1..end], &source_value[end + 1..]);
+                validate_regexp_literal(pattern, flags)?;
+                Ok(Expr::new_regexp_literal(
+                    get_or_intern_string(pattern),
+                    get_or_intern_string(flags),
+                ))
+            }
             Kind::Identifier => {
                 check_identifier(&source_value)?;
+                if source_value == "async"
+                    && let Some(expr) = self.handle_async_expression()?
+                {
+                    return Ok(expr);
+                }
                 let idx = get_or_intern_string(&source_value);
                 let expr = Expr::new_identifier(&idx);
                 if self.current_token.is_kind(&Kind::Arrow) {
                     // we're in an arrow function!
                     self.next_token();
-                    let body = if self.current_token.is_kind(&Kind::LeftCurly) {
-                        self.handle_statements()?
-                    } else {
-                        let expr = self.handle_expressions()?;
-                        Stmt::new_expression(expr)
-                    };
-                    return Ok(Expr::new_function_decl(None, vec![expr], body));
+                    return self.handle_arrow_function(vec![expr], false);
                 }
                 Ok(expr)
             }
             Kind::True => Ok(Expr::new_literal(JSValue::new_boolean(true))),
             Kind::False => Ok(Expr::new_literal(JSValue::new_boolean(false))),
             Kind::Null => Ok(Expr::new_literal(JSValue::new_null())),
-            Kind::Undefined => Ok(Expr::new_literal(JSValue::new_undefined())),
-            Kind::LeftParen => {
-                // immediate right paren - we're in arrow land. grouping needs inner content
-                if self.current_token.is_kind(&Kind::RightParen) {
-                    self.next_token();
-                    self.expect_and_consume(&Kind::Arrow, "ArrowFunction")?;
-
-                    let body = if self.current_token.is_kind(&Kind::LeftCurly) {
-                        self.handle_statements()?
-                    } else {
-                        let expr = self.handle_expressions()?;
-                        Stmt::new_expression(expr)
-                    };
-                    return Ok(Expr::new_function_decl(None, vec![], body));
-                }
-
-                let expr = self.handle_expressions()?;
-                // comma separator means arrow land
-                if self.current_token.is_kind(&Kind::Comma) {
-                    let mut args = Vec::with_capacity(8);
-                    args.push(expr);
-                    while self.current_token.is_kind(&Kind::Comma) {
-                        self.next_token();
-                        let param = self.handle_expressions()?;
-                        args.push(param);
-                    }
-                    self.expect_and_consume(&Kind::RightParen, "ArrowFunction")?;
-                    self.expect_and_consume(&Kind::Arrow, "ArrowFunction")?;
-
-                    let body = if self.current_token.is_kind(&Kind::LeftCurly) {
-                        self.handle_statements()?
-                    } else {
-                        let expr = self.handle_expressions()?;
-                        Stmt::new_expression(expr)
-                    };
-
-                    return Ok(Expr::new_function_decl(None, args, body));
-                } else {
-                    self.expect_and_consume(&Kind::RightParen, "Expression")?;
-                    // if next token is an arrow we're in arrow land
-                    if self.current_token.is_kind(&Kind::Arrow) {
-                        self.next_token();
-                        let body = if self.current_token.is_kind(&Kind::LeftCurly) {
-                            self.handle_statements()?
-                        } else {
-                            let expr = self.handle_expressions()?;
-                            Stmt::new_expression(expr)
-                        };
-                        return Ok(Expr::new_function_decl(None, vec![expr], body));
-                    }
-                    // otherwise its just a parenthetical
-                    Ok(Expr::new_grouping(expr))
-                }
-            }
+            Kind::LeftParen => self.handle_parenthesized(false),
             Kind::LeftSquare => {
-                if self.current_token.is_kind(&Kind::RightSquare) {
-                    self.next_token();
-                    return Ok(Expr::new_literal(JSValue::new_array(
-                        vec![],
-                        self.interpreter,
-                    )));
-                }
-                let mut expressions = Vec::with_capacity(10);
-                loop {
-                    let expr = self.handle_expressions()?;
-                    expressions.push(expr);
+                let mut elements = Vec::with_capacity(10);
+                while !self.current_token.is_kind(&Kind::RightSquare) {
+                    elements.push(self.handle_spread_or_expression()?);
                     if !self.current_token.is_kind(&Kind::Comma) {
                         break;
                     }
                     self.next_token();
                 }
-
                 self.expect_and_consume(&Kind::RightSquare, "ArrayExpression")?;
-
-                let properties = expressions
-                    .into_iter()
-                    .enumerate()
-                    .map(|(idx, expr)| {
-                        let val = expr.evaluate(self.interpreter)?;
-                        let idx_val = idx.to_string();
-                        let idx_sym = get_or_intern_string(&idx_val);
-                        Ok((idx_sym, val))
-                    })
-                    .collect::<JSResult<Vec<(SymbolU32, JSValue)>>>()?;
-                Ok(Expr::new_literal(JSValue::new_array(
-                    properties,
-                    self.interpreter,
-                )))
+                Ok(Expr::new_array_literal(elements))
             }
             Kind::LeftCurly => {
-                if self.current_token.is_kind(&Kind::RightCurly) {
-                    self.next_token();
-                    return Ok(Expr::new_literal(JSValue::new_object(
-                        vec![],
-                        self.interpreter,
-                    )));
-                }
-
                 let mut properties = Vec::with_capacity(8);
-
-                let key_error = JSError::new("Object literal key must be a string");
-                loop {
-                    let key = match self.current_token.get_kind() {
-                        Kind::Identifier | Kind::String => self
-                            .interpreter
-                            .get_source_at_span(&self.current_token.get_span()),
-                        _ => return Err(key_error),
-                    };
-                    self.next_token();
-                    let key_index = get_or_intern_string(&key);
-                    self.expect_and_consume(&Kind::Colon, "ObjectExpression")?;
-                    let value_expr = self.handle_expressions()?;
-                    let value = value_expr.evaluate(self.interpreter)?;
-                    properties.push((key_index, value));
-
-                    // if we have a comma, check if it is danglng. if no comma or the peek value is not an ident, break
-                    let temp_eof = Token::new_eof();
-                    let peeked = self.peek().unwrap_or(&temp_eof).clone();
+                // a trailing comma is fine, so check for the closing curly on every pass
+                while !self.current_token.is_kind(&Kind::RightCurly) {
+                    properties.push(self.handle_property_definition()?);
                     if !self.current_token.is_kind(&Kind::Comma) {
                         break;
-                    } else if peeked.is_kind(&Kind::RightCurly) {
-                        self.next_token();
-                        break;
                     }
                     self.next_token();
                 }
-
                 self.expect_and_consume(&Kind::RightCurly, "ObjectExpression")?;
-                let object = JSValue::new_object(properties, self.interpreter);
-                return Ok(Expr::new_literal(object));
+                Ok(Expr::new_object_literal(properties))
             }
-            Kind::Function => {
-                // Left Paren - no identifier found
-                let ident = if self.current_token.is_kind(&Kind::LeftParen) {
-                    None
-                } else {
-                    // we have an identifier, consume it and move on
-                    let expr = self.get_identifier()?;
-                    Some(Box::new(expr))
-                };
+            Kind::Function => self.handle_function_expression(false),
+            Kind::Class => {
+                let definition = self.handle_class()?;
+                Ok(Expr::new_class(definition))
+            }
+            Kind::This => Ok(Expr::This),
+            Kind::Import => {
+                if !self.current_token.is_kind(&Kind::LeftParen) {
+                    return Err(JSError::new(
+                        "Uncaught SyntaxError: Cannot use import statement outside a module",
+                    ));
+                }
+                self.next_token();
+                let specifier = self.handle_expressions()?;
+                self.expect_and_consume(&Kind::RightParen, "ImportCall")?;
+                Ok(Expr::new_import(specifier, self.module))
+            }
+            token => Err(JSError::new(&format!("Unexpected token: {:?}", token))),
+        }
+    }
 
-                // we should have the start of the parameters here
-                self.expect_and_consume(&Kind::LeftParen, "FunctionExpression")?;
+    // the left paren has already been consumed
+    fn handle_parenthesized(&mut self, is_async: bool) -> JSResult<Expr> {
+        // immediate right paren - we're in arrow land. grouping needs inner content
+        if self.current_token.is_kind(&Kind::RightParen) {
+            self.next_token();
+            self.expect_and_consume(&Kind::Arrow, "ArrowFunction")?;
+            return self.handle_arrow_function(vec![], is_async);
+        }
 
-                let parameters = if self.current_token.is_kind(&Kind::RightParen) {
-                    vec![]
-                } else {
-                    let mut params = Vec::with_capacity(6); // that'd be a lotta args
-                    let first_param = self.get_identifier()?;
-                    params.push(first_param);
-                    while self.current_token.is_kind(&Kind::Comma) {
-                        self.next_token();
-                        let param = self.get_identifier()?;
-                        params.push(param);
-                    }
-                    params
-                };
-                self.expect_and_consume(&Kind::RightParen, "FunctionExpression")?;
-                let body = self.handle_statements()?;
-                let expr = Expr::new_function_decl(ident, parameters, body);
-                Ok(expr)
+        let expr = self.handle_expressions()?;
+        // comma separator means arrow land
+        if self.current_token.is_kind(&Kind::Comma) {
+            let mut args = Vec::with_capacity(8);
+            args.push(expr);
+            while self.current_token.is_kind(&Kind::Comma) {
+                self.next_token();
+                let param = self.handle_expressions()?;
+                args.push(param);
             }
-            token => Err(JSError::new(&format!("Unexpected token: {:?}", token))),
+            self.expect_and_consume(&Kind::RightParen, "ArrowFunction")?;
+            self.expect_and_consume(&Kind::Arrow, "ArrowFunction")?;
+            return self.handle_arrow_function(args, is_async);
+        }
+        self.expect_and_consume(&Kind::RightParen, "Expression")?;
+        // if next token is an arrow we're in arrow land
+        if is_async || self.current_token.is_kind(&Kind::Arrow) {
+            self.expect_and_consume(&Kind::Arrow, "ArrowFunction")?;
+            return self.handle_arrow_function(vec![expr], is_async);
+        }
+        // otherwise its just a parenthetical
+        Ok(Expr::new_grouping(expr))
+    }
+
+    // the function keyword has already been consumed
+    fn handle_function_expression(&mut self, is_async: bool) -> JSResult<Expr> {
+        let body_kind = self.handle_body_kind(is_async)?;
+        // Left Paren - no identifier found
+        let ident = if self.current_token.is_kind(&Kind::LeftParen) {
+            None
+        } else {
+            // we have an identifier, consume it and move on
+            let expr = self.get_identifier()?;
+            Some(Box::new(expr))
+        };
+        let parameters = self.handle_parameters("FunctionExpression")?;
+        let body = self.handle_function_body(body_kind)?;
+        Ok(Expr::new_function_decl(
+            ident,
+            parameters,
+            body,
+            FunctionKind::Normal,
+            body_kind,
+        ))
+    }
+
+    // `async` has been consumed. returns None when it was just an identifier named async
+    fn handle_async_expression(&mut self) -> JSResult<Option<Expr>> {
+        let arrow_follows = self.peek().is_some_and(|token| token.is_kind(&Kind::Arrow));
+        match self.current_token.get_kind() {
+            Kind::Function => {
+                self.next_token();
+                self.handle_function_expression(true).map(Some)
+            }
+            Kind::LeftParen => {
+                self.next_token();
+                self.handle_parenthesized(true).map(Some)
+            }
+            Kind::Identifier if arrow_follows => {
+                let parameter = self.get_identifier()?;
+                self.next_token();
+                self.handle_arrow_function(vec![parameter], true).map(Some)
+            }
+            _ => Ok(None),
         }
     }
 
@@ -600,6 +1277,48 @@ impl<'a> Parser<'a> {
         Err(error)
     }
 
+    /// Property names after a dot or in a literal may be any identifier name, keywords included.
+    fn get_property_name(&mut self) -> JSResult<String> {
+        let source_value = self
+            .interpreter
+            .get_source_at_span(&self.current_token.get_span());
+        let is_name = !source_value.is_empty()
+            && source_value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+        if self.current_token.is_kind(&Kind::String) || !is_name {
+            return Err(JSError::new("Identifier expected"));
+        }
+        self.next_token();
+        Ok(source_value)
+    }
+
+    fn expect_contextual_keyword(&mut self, keyword: &str, caller: &str) -> JSResult<()> {
+        if self.is_contextual_keyword(keyword) {
+            self.next_token();
+            return Ok(());
+        }
+        Err(JSError::new(&format!("Expected '{keyword}' in {caller}")))
+    }
+
+    fn get_module_specifier(&mut self) -> JSResult<SymbolU32> {
+        if !self.current_token.is_kind(&Kind::String) {
+            return Err(JSError::new("Expected a module specifier string"));
+        }
+        let source = self
+            .interpreter
+            .get_source_at_span(&self.current_token.get_span());
+        self.next_token();
+        Ok(get_or_intern_string(&source))
+    }
+
+    fn get_binding_name(&mut self) -> JSResult<SymbolU32> {
+        match self.get_identifier()? {
+            Expr::Identifier { string_index } => Ok(string_index),
+            _ => Err(JSError::new("Identifier expected")),
+        }
+    }
+
     fn get_identifier(&mut self) -> JSResult<Expr> {
         let current_span = self.current_token.get_span();
         let source_value = self
@@ -0,0 +1,51 @@
+//! The pseudo-random number generator behind `Math.random`: xorshift128+, like V8, seeded
+//! through splitmix64. Hosts can pick the seed to make runs reproducible.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: [u64; 2],
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 spreads any seed, 0 included, over a state that is never all zeroes
+        let mut seed = seed;
+        let mut next = || {
+            seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        };
+        Self {
+            state: [next(), next()],
+        }
+    }
+
+    /// Seeded from the system time, for when the host doesn't care about reproducibility.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::new(nanos)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let [mut s1, s0] = self.state;
+        let result = s0.wrapping_add(s1);
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+        self.state = [s0, s1];
+        result
+    }
+
+    /// A number in [0, 1) built from the top 53 bits, every one of them equally likely.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
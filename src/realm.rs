@@ -0,0 +1,158 @@
+//! Realms: a global object and environment with the intrinsics that go with them, the
+//! prototypes and constructors builtins create objects from. Every interpreter starts out with
+//! one, and `Interpreter::create_realm` sets up more that share its heap, job queue and symbol
+//! registry but none of its globals.
+
+use std::collections::HashMap;
+
+use crate::{
+    heap::{HeapId, Trace, Tracer},
+    values::JSValue,
+};
+
+/// A realm of an interpreter, see `Interpreter::create_realm` and `Interpreter::enter_realm`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Realm {
+    pub(crate) index: usize,
+    pub(crate) interpreter_id: usize,
+}
+
+/// The objects of a realm the interpreter needs to find without looking them up on the global
+/// object, where scripts could have replaced them.
+#[derive(Clone, Debug)]
+pub(crate) struct Intrinsics {
+    pub(crate) object_proto_id: usize,
+    pub(crate) function_proto_id: usize,
+    pub(crate) iterator_proto_id: usize,
+    pub(crate) generator_proto_id: usize,
+    pub(crate) promise_proto_id: usize,
+    /// The intrinsic `Promise`, what `then` falls back to without a `Symbol.species`.
+    pub(crate) promise_constructor_id: usize,
+    /// Where property lookups on number primitives start.
+    pub(crate) number_proto_id: usize,
+    /// Where property lookups on BigInt primitives start.
+    pub(crate) bigint_proto_id: usize,
+    /// Where property lookups on symbols start.
+    pub(crate) symbol_proto_id: usize,
+    /// Where property lookups on strings start.
+    pub(crate) string_proto_id: usize,
+    pub(crate) regexp_proto_id: usize,
+    /// The intrinsic `RegExp`, what `split` and `matchAll` fall back to without a
+    /// `Symbol.species`.
+    pub(crate) regexp_constructor_id: usize,
+    pub(crate) regexp_string_iterator_proto_id: usize,
+    pub(crate) map_proto_id: usize,
+    pub(crate) map_iterator_proto_id: usize,
+    pub(crate) set_proto_id: usize,
+    pub(crate) set_iterator_proto_id: usize,
+    pub(crate) weak_map_proto_id: usize,
+    pub(crate) weak_set_proto_id: usize,
+    pub(crate) date_proto_id: usize,
+    pub(crate) array_buffer_proto_id: usize,
+    /// The intrinsic `ArrayBuffer`, what `slice` falls back to without a `Symbol.species`.
+    pub(crate) array_buffer_constructor_id: usize,
+    /// `%TypedArray%.prototype`, shared by every kind of typed array.
+    pub(crate) typed_array_proto_id: usize,
+    /// `Int8Array` through `BigUint64Array` and their prototypes, indexed by
+    /// `TypedArrayKind`.
+    pub(crate) typed_array_constructor_ids: [usize; 11],
+    pub(crate) typed_array_proto_ids: [usize; 11],
+    pub(crate) array_iterator_proto_id: usize,
+    pub(crate) data_view_proto_id: usize,
+}
+
+impl Intrinsics {
+    /// Everything but the two prototypes that exist before any builtin does starts out as
+    /// `Object.prototype`, until setting up the global object creates the real ones.
+    pub(crate) fn new(object_proto_id: usize, function_proto_id: usize) -> Self {
+        let proto_id = object_proto_id;
+        Self {
+            object_proto_id,
+            function_proto_id,
+            iterator_proto_id: proto_id,
+            generator_proto_id: proto_id,
+            promise_proto_id: proto_id,
+            promise_constructor_id: proto_id,
+            number_proto_id: proto_id,
+            bigint_proto_id: proto_id,
+            symbol_proto_id: proto_id,
+            string_proto_id: proto_id,
+            regexp_proto_id: proto_id,
+            regexp_constructor_id: proto_id,
+            regexp_string_iterator_proto_id: proto_id,
+            map_proto_id: proto_id,
+            map_iterator_proto_id: proto_id,
+            set_proto_id: proto_id,
+            set_iterator_proto_id: proto_id,
+            weak_map_proto_id: proto_id,
+            weak_set_proto_id: proto_id,
+            date_proto_id: proto_id,
+            array_buffer_proto_id: proto_id,
+            array_buffer_constructor_id: proto_id,
+            typed_array_proto_id: proto_id,
+            typed_array_constructor_ids: [proto_id; 11],
+            typed_array_proto_ids: [proto_id; 11],
+            array_iterator_proto_id: proto_id,
+            data_view_proto_id: proto_id,
+        }
+    }
+}
+
+impl Trace for Intrinsics {
+    fn trace(&self, tracer: &mut Tracer) {
+        let ids = [
+            self.object_proto_id,
+            self.function_proto_id,
+            self.iterator_proto_id,
+            self.generator_proto_id,
+            self.promise_proto_id,
+            self.promise_constructor_id,
+            self.number_proto_id,
+            self.bigint_proto_id,
+            self.symbol_proto_id,
+            self.string_proto_id,
+            self.regexp_proto_id,
+            self.regexp_constructor_id,
+            self.regexp_string_iterator_proto_id,
+            self.map_proto_id,
+            self.map_iterator_proto_id,
+            self.set_proto_id,
+            self.set_iterator_proto_id,
+            self.weak_map_proto_id,
+            self.weak_set_proto_id,
+            self.date_proto_id,
+            self.array_buffer_proto_id,
+            self.array_buffer_constructor_id,
+            self.typed_array_proto_id,
+            self.array_iterator_proto_id,
+            self.data_view_proto_id,
+        ];
+        let typed_arrays = self
+            .typed_array_constructor_ids
+            .iter()
+            .chain(&self.typed_array_proto_ids);
+        for id in ids.iter().chain(typed_arrays) {
+            tracer.mark(*id);
+        }
+    }
+}
+
+/// What the interpreter keeps of the realms that aren't current. The current realm's state
+/// lives in the interpreter itself and is only moved back here when another one is entered.
+pub(crate) struct RealmRecord {
+    pub(crate) global_environment: HeapId,
+    pub(crate) intrinsics: Intrinsics,
+    /// Modules by key and CommonJS `module` objects, so realms load their own copies.
+    pub(crate) module_map: HashMap<String, usize>,
+    pub(crate) commonjs_cache: HashMap<String, JSValue>,
+}
+
+impl Trace for RealmRecord {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.mark(self.global_environment);
+        self.intrinsics.trace(tracer);
+        self.commonjs_cache
+            .values()
+            .for_each(|module| module.trace(tracer));
+    }
+}
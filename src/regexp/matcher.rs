@@ -0,0 +1,422 @@
+//! A backtracking matcher in the continuation-passing shape of the spec's pattern semantics:
+//! every node gets the rest of the match as a continuation and reports whether the whole match
+//! succeeded. Captures are mutated in place and put back whenever a path fails.
+//! https://262.ecma-international.org/15.0/index.html#sec-pattern-semantics
+
+use super::{
+    Flags,
+    parser::{CharClass, ClassItem, Node},
+};
+
+pub type Captures = Vec<Option<(usize, usize)>>;
+
+type Continuation<'a> = &'a dyn Fn(&mut State, usize) -> bool;
+
+pub struct State<'a> {
+    input: &'a [u16],
+    flags: &'a Flags,
+    pub captures: Captures,
+    /// Every step of a match is a call on the native stack, so long inputs to repeated groups
+    /// could overflow it. Past `max_depth` every path fails instead.
+    depth: usize,
+    max_depth: usize,
+    pub overflowed: bool,
+}
+
+impl<'a> State<'a> {
+    pub fn new(input: &'a [u16], flags: &'a Flags, group_count: usize, max_depth: usize) -> Self {
+        Self {
+            input,
+            flags,
+            captures: vec![None; group_count + 1],
+            depth: 0,
+            max_depth,
+            overflowed: false,
+        }
+    }
+
+    /// The character starting at `position`, and where the next one starts.
+    fn read_forward(&self, position: usize) -> Option<(u32, usize)> {
+        let unit = *self.input.get(position)?;
+        if self.flags.unicode
+            && (0xd800..0xdc00).contains(&unit)
+            && let Some(&low) = self.input.get(position + 1)
+            && (0xdc00..0xe000).contains(&low)
+        {
+            let c = 0x10000 + ((u32::from(unit) - 0xd800) << 10) + (u32::from(low) - 0xdc00);
+            return Some((c, position + 2));
+        }
+        Some((u32::from(unit), position + 1))
+    }
+
+    /// The character ending at `position`, and where it starts.
+    fn read_backward(&self, position: usize) -> Option<(u32, usize)> {
+        let unit = *self.input.get(position.checked_sub(1)?)?;
+        if self.flags.unicode
+            && (0xdc00..0xe000).contains(&unit)
+            && position >= 2
+            && (0xd800..0xdc00).contains(&self.input[position - 2])
+        {
+            let high = u32::from(self.input[position - 2]);
+            let c = 0x10000 + ((high - 0xd800) << 10) + (u32::from(unit) - 0xdc00);
+            return Some((c, position - 2));
+        }
+        Some((u32::from(unit), position - 1))
+    }
+
+    fn read(&self, position: usize, backward: bool) -> Option<(u32, usize)> {
+        match backward {
+            true => self.read_backward(position),
+            false => self.read_forward(position),
+        }
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-runtime-semantics-canonicalize-ch
+    fn canonicalize(&self, c: u32) -> u32 {
+        if !self.flags.ignore_case {
+            return c;
+        }
+        let Some(ch) = char::from_u32(c) else {
+            return c;
+        };
+        if self.flags.unicode {
+            // close enough to simple case folding for everything but a few special cases
+            let mut upper = ch.to_uppercase();
+            let folded = match (upper.next(), upper.next()) {
+                (Some(upper), None) => single(upper.to_lowercase()).unwrap_or(ch),
+                _ => single(ch.to_lowercase()).unwrap_or(ch),
+            };
+            return folded as u32;
+        }
+        match single(ch.to_uppercase()) {
+            // non-ASCII characters never turn into ASCII ones
+            Some(upper) if !(c >= 128 && (upper as u32) < 128) => upper as u32,
+            _ => c,
+        }
+    }
+
+    fn is_word_character(&self, position: Option<usize>) -> bool {
+        let Some(unit) = position.and_then(|position| self.input.get(position)) else {
+            return false;
+        };
+        is_word(u32::from(*unit))
+    }
+
+    fn class_matches(&self, class: &CharClass, c: u32) -> bool {
+        let found = class.items.iter().any(|item| match item {
+            ClassItem::Range(low, high) => {
+                let in_range = |c: u32| *low <= c && c <= *high;
+                in_range(c)
+                    || (self.flags.ignore_case
+                        && case_variants(c).into_iter().any(|variant| {
+                            in_range(variant) && self.canonicalize(variant) == self.canonicalize(c)
+                        }))
+            }
+            ClassItem::Digit { negated } => negated ^ ('0' as u32..='9' as u32).contains(&c),
+            ClassItem::Word { negated } => negated ^ is_word(c),
+            ClassItem::Space { negated } => negated ^ is_space(c),
+        });
+        found != class.negated
+    }
+
+    fn is_line_terminator_at(&self, position: usize) -> bool {
+        self.input
+            .get(position)
+            .is_some_and(|unit| is_line_terminator(u32::from(*unit)))
+    }
+}
+
+fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// The characters that could canonicalize the same way as `c`.
+fn case_variants(c: u32) -> Vec<u32> {
+    let Some(ch) = char::from_u32(c) else {
+        return vec![];
+    };
+    let mut variants = vec![];
+    variants.extend(single(ch.to_lowercase()).map(|c| c as u32));
+    variants.extend(single(ch.to_uppercase()).map(|c| c as u32));
+    if let Some(upper) = single(ch.to_uppercase()) {
+        variants.extend(single(upper.to_lowercase()).map(|c| c as u32));
+    }
+    variants
+}
+
+fn is_word(c: u32) -> bool {
+    char::from_u32(c).is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_line_terminator(c: u32) -> bool {
+    matches!(c, 0x0a | 0x0d | 0x2028 | 0x2029)
+}
+
+// WhiteSpace and LineTerminator
+fn is_space(c: u32) -> bool {
+    matches!(
+        c,
+        0x09..=0x0d | 0x20 | 0xa0 | 0x1680 | 0x2000..=0x200a | 0x2028 | 0x2029 | 0x202f | 0x205f
+            | 0x3000 | 0xfeff
+    )
+}
+
+/// Matches `node` at `position`, reading right to left inside lookbehinds, and hands the end
+/// position to `next`.
+pub fn match_node(
+    node: &Node,
+    state: &mut State,
+    position: usize,
+    backward: bool,
+    next: Continuation,
+) -> bool {
+    if state.depth >= state.max_depth {
+        state.overflowed = true;
+        return false;
+    }
+    state.depth += 1;
+    let matched = match_nested(node, state, position, backward, next);
+    state.depth -= 1;
+    matched
+}
+
+fn match_nested(
+    node: &Node,
+    state: &mut State,
+    position: usize,
+    backward: bool,
+    next: Continuation,
+) -> bool {
+    match node {
+        Node::Empty => next(state, position),
+        Node::Char(_) | Node::Any | Node::Class(_) => {
+            match match_single(node, state, position, backward) {
+                Some(position) => next(state, position),
+                None => false,
+            }
+        }
+        Node::LineStart => {
+            let at_start = position == 0
+                || (state.flags.multiline && state.is_line_terminator_at(position - 1));
+            at_start && next(state, position)
+        }
+        Node::LineEnd => {
+            let at_end = position == state.input.len()
+                || (state.flags.multiline && state.is_line_terminator_at(position));
+            at_end && next(state, position)
+        }
+        Node::WordBoundary { negated } => {
+            let before = state.is_word_character(position.checked_sub(1));
+            let after = state.is_word_character(Some(position));
+            (negated ^ (before != after)) && next(state, position)
+        }
+        Node::Group { index: None, node } => match_node(node, state, position, backward, next),
+        Node::Group {
+            index: Some(index),
+            node,
+        } => {
+            let index = *index;
+            match_node(node, state, position, backward, &|state, end| {
+                let previous = state.captures[index];
+                state.captures[index] = match backward {
+                    true => Some((end, position)),
+                    false => Some((position, end)),
+                };
+                if next(state, end) {
+                    return true;
+                }
+                state.captures[index] = previous;
+                false
+            })
+        }
+        Node::BackReference(index) => {
+            let Some((start, end)) = state.captures.get(*index).copied().flatten() else {
+                return next(state, position);
+            };
+            let length = end - start;
+            let from = match backward {
+                true => match position.checked_sub(length) {
+                    Some(from) => from,
+                    None => return false,
+                },
+                false => position,
+            };
+            if from + length > state.input.len() {
+                return false;
+            }
+            let matches = (0..length).all(|offset| {
+                let expected = u32::from(state.input[start + offset]);
+                let actual = u32::from(state.input[from + offset]);
+                state.canonicalize(expected) == state.canonicalize(actual)
+            });
+            let end = match backward {
+                true => from,
+                false => from + length,
+            };
+            matches && next(state, end)
+        }
+        Node::Look {
+            behind,
+            negated,
+            node,
+        } => {
+            let saved = state.captures.clone();
+            let matched = match_node(node, state, position, *behind, &|_, _| true);
+            if matched == *negated {
+                state.captures = saved;
+                return false;
+            }
+            if *negated {
+                // nothing a negative lookaround captured is visible outside it
+                state.captures = saved.clone();
+            }
+            if next(state, position) {
+                return true;
+            }
+            state.captures = saved;
+            false
+        }
+        Node::Sequence(nodes) => match_sequence(nodes, state, position, backward, next),
+        Node::Alternation(alternatives) => alternatives
+            .iter()
+            .any(|alternative| match_node(alternative, state, position, backward, next)),
+        Node::Repeat {
+            node,
+            min,
+            max,
+            greedy,
+            groups,
+        } => {
+            if groups.is_empty() && matches!(**node, Node::Char(_) | Node::Any | Node::Class(_)) {
+                return match_single_repeat(
+                    node, *min, *max, *greedy, state, position, backward, next,
+                );
+            }
+            let repeat = Repeat {
+                node,
+                greedy: *greedy,
+                groups: groups.clone(),
+                backward,
+            };
+            repeat.match_from(state, position, *min, *max, next)
+        }
+    }
+}
+
+fn match_sequence(
+    nodes: &[Node],
+    state: &mut State,
+    position: usize,
+    backward: bool,
+    next: Continuation,
+) -> bool {
+    // lookbehinds match their terms right to left
+    let split = match backward {
+        true => nodes.split_last(),
+        false => nodes.split_first(),
+    };
+    let Some((first, rest)) = split else {
+        return next(state, position);
+    };
+    match_node(first, state, position, backward, &|state, position| {
+        match_sequence(rest, state, position, backward, next)
+    })
+}
+
+/// Where a one-character node ends when it matches at `position`.
+fn match_single(node: &Node, state: &State, position: usize, backward: bool) -> Option<usize> {
+    let (c, end) = state.read(position, backward)?;
+    let matches = match node {
+        Node::Char(expected) => state.canonicalize(*expected) == state.canonicalize(c),
+        Node::Any => state.flags.dot_all || !is_line_terminator(c),
+        Node::Class(class) => state.class_matches(class, c),
+        _ => false,
+    };
+    matches.then_some(end)
+}
+
+/// Repeats of a single character need no backtracking into the atom, so the positions are
+/// collected up front instead of recursing once per character.
+#[allow(clippy::too_many_arguments)]
+fn match_single_repeat(
+    node: &Node,
+    min: u32,
+    max: Option<u32>,
+    greedy: bool,
+    state: &mut State,
+    position: usize,
+    backward: bool,
+    next: Continuation,
+) -> bool {
+    let mut positions = vec![position];
+    let mut current = position;
+    while max.is_none_or(|max| positions.len() <= max as usize) {
+        match match_single(node, state, current, backward) {
+            Some(end) => {
+                current = end;
+                positions.push(end);
+            }
+            None => break,
+        }
+    }
+    let min = min as usize;
+    if positions.len() <= min {
+        return false;
+    }
+    let candidates = &positions[min..];
+    match greedy {
+        true => candidates.iter().rev().any(|&end| next(state, end)),
+        false => candidates.iter().any(|&end| next(state, end)),
+    }
+}
+
+struct Repeat<'a> {
+    node: &'a Node,
+    greedy: bool,
+    groups: std::ops::Range<usize>,
+    backward: bool,
+}
+
+impl Repeat<'_> {
+    // https://262.ecma-international.org/15.0/index.html#sec-runtime-semantics-repeatmatcher-abstract-operation
+    fn match_from(
+        &self,
+        state: &mut State,
+        position: usize,
+        min: u32,
+        max: Option<u32>,
+        next: Continuation,
+    ) -> bool {
+        if max == Some(0) {
+            return next(state, position);
+        }
+        let iterate = |state: &mut State| {
+            let saved = state.captures[self.groups.clone()].to_vec();
+            for capture in &mut state.captures[self.groups.clone()] {
+                *capture = None;
+            }
+            let matched = match_node(self.node, state, position, self.backward, &|state, end| {
+                // an empty iteration past the minimum could go on forever
+                if min == 0 && end == position {
+                    return false;
+                }
+                let max = max.map(|max| max - 1);
+                self.match_from(state, end, min.saturating_sub(1), max, next)
+            });
+            if !matched {
+                state.captures[self.groups.clone()].copy_from_slice(&saved);
+            }
+            matched
+        };
+        if min > 0 {
+            return iterate(state);
+        }
+        match self.greedy {
+            true => iterate(state) || next(state, position),
+            false => next(state, position) || iterate(state),
+        }
+    }
+}
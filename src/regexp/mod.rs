@@ -0,0 +1,140 @@
+//! The regular expression engine behind `RegExp`. Patterns are parsed once into a tree and
+//! matched against UTF-16 strings by backtracking.
+
+mod matcher;
+mod parser;
+
+pub use matcher::Captures;
+
+use matcher::{State, match_node};
+use parser::Pattern;
+
+/// The flags a `RegExp` was created with, in the order `flags` lists them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Flags {
+    pub has_indices: bool,
+    pub global: bool,
+    pub ignore_case: bool,
+    pub multiline: bool,
+    pub dot_all: bool,
+    pub unicode: bool,
+    pub sticky: bool,
+}
+
+impl Flags {
+    /// `None` when a flag is unknown or repeated.
+    pub fn parse(flags: &str) -> Option<Self> {
+        let mut parsed = Self::default();
+        for flag in flags.chars() {
+            let slot = match flag {
+                'd' => &mut parsed.has_indices,
+                'g' => &mut parsed.global,
+                'i' => &mut parsed.ignore_case,
+                'm' => &mut parsed.multiline,
+                's' => &mut parsed.dot_all,
+                'u' => &mut parsed.unicode,
+                'y' => &mut parsed.sticky,
+                _ => return None,
+            };
+            if *slot {
+                return None;
+            }
+            *slot = true;
+        }
+        Some(parsed)
+    }
+}
+
+/// A match ran out of stack, which scripts see as a RangeError.
+#[derive(Debug)]
+pub struct StackOverflow;
+
+#[derive(Debug)]
+pub struct Regex {
+    pattern: Pattern,
+    pub flags: Flags,
+}
+
+impl Regex {
+    /// Compiles `source`. The error is V8's wording without the
+    /// `Invalid regular expression: /source/flags: ` prefix.
+    pub fn new(source: &str, flags: Flags) -> Result<Self, String> {
+        let pattern = parser::parse(source, flags.unicode)?;
+        Ok(Self { pattern, flags })
+    }
+
+    /// The named groups with their capture indices, in the order they appear.
+    pub fn group_names(&self) -> &[(String, usize)] {
+        &self.pattern.group_names
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-regexpbuiltinexec
+    /// Finds the first match at `start` or after it, or only at `start` with the `y` flag.
+    /// Index 0 of the captures is the whole match.
+    ///
+    /// Deep matches need a lot of native stack, so when one doesn't fit in the calling thread's
+    /// the search is run again on a thread with a much bigger one.
+    pub fn find(&self, input: &[u16], start: usize) -> Result<Option<Captures>, StackOverflow> {
+        match self.find_within(input, start, SHALLOW_DEPTH) {
+            Err(StackOverflow) => std::thread::scope(|scope| {
+                std::thread::Builder::new()
+                    .stack_size(DEEP_STACK_SIZE)
+                    .spawn_scoped(scope, || self.find_within(input, start, DEEP_DEPTH))
+                    .map_err(|_| StackOverflow)?
+                    .join()
+                    .map_err(|_| StackOverflow)?
+            }),
+            result => result,
+        }
+    }
+
+    fn find_within(
+        &self,
+        input: &[u16],
+        start: usize,
+        max_depth: usize,
+    ) -> Result<Option<Captures>, StackOverflow> {
+        let mut index = start;
+        while index <= input.len() {
+            let mut state = State::new(input, &self.flags, self.pattern.group_count, max_depth);
+            let matched = match_node(
+                &self.pattern.node,
+                &mut state,
+                index,
+                false,
+                &|state, end| {
+                    state.captures[0] = Some((index, end));
+                    true
+                },
+            );
+            if state.overflowed {
+                return Err(StackOverflow);
+            }
+            if matched {
+                return Ok(Some(state.captures));
+            }
+            if self.flags.sticky {
+                break;
+            }
+            index = advance_string_index(input, index, self.flags.unicode);
+        }
+        Ok(None)
+    }
+}
+
+/// How deep a match may nest on the calling thread, and on the thread `find` falls back to.
+const SHALLOW_DEPTH: usize = 1_000;
+const DEEP_DEPTH: usize = 200_000;
+const DEEP_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// https://262.ecma-international.org/15.0/index.html#sec-advancestringindex
+pub fn advance_string_index(string: &[u16], index: usize, unicode: bool) -> usize {
+    if !unicode
+        || index + 1 >= string.len()
+        || !(0xd800..0xdc00).contains(&string[index])
+        || !(0xdc00..0xe000).contains(&string[index + 1])
+    {
+        return index + 1;
+    }
+    index + 2
+}
@@ -0,0 +1,709 @@
+//! Parses a pattern into the tree `matcher` walks, reporting early errors the way V8 words
+//! them. Without the `u` flag the pattern is read as UTF-16 code units and the Annex B
+//! leniencies apply, e.g. a lone `{` or `]` is just a character.
+
+use std::ops::Range;
+
+#[derive(Clone, Debug)]
+pub enum Node {
+    Empty,
+    /// A code unit, or a code point with the `u` flag.
+    Char(u32),
+    /// `.`
+    Any,
+    Class(CharClass),
+    /// `^`
+    LineStart,
+    /// `$`
+    LineEnd,
+    /// `\b`, or `\B` when negated.
+    WordBoundary {
+        negated: bool,
+    },
+    Group {
+        /// The capture index, `None` for `(?:...)`.
+        index: Option<usize>,
+        node: Box<Node>,
+    },
+    BackReference(usize),
+    Look {
+        behind: bool,
+        negated: bool,
+        node: Box<Node>,
+    },
+    Sequence(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+        greedy: bool,
+        /// The captures inside, which every iteration starts out without.
+        groups: Range<usize>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct CharClass {
+    pub negated: bool,
+    pub items: Vec<ClassItem>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ClassItem {
+    Range(u32, u32),
+    /// `\d`, or `\D` when negated.
+    Digit {
+        negated: bool,
+    },
+    /// `\w`, or `\W` when negated.
+    Word {
+        negated: bool,
+    },
+    /// `\s`, or `\S` when negated.
+    Space {
+        negated: bool,
+    },
+}
+
+/// The parsed pattern and what the matcher needs to know about its groups.
+#[derive(Debug)]
+pub struct Pattern {
+    pub node: Node,
+    pub group_count: usize,
+    pub group_names: Vec<(String, usize)>,
+}
+
+pub fn parse(source: &str, unicode: bool) -> Result<Pattern, String> {
+    let chars = match unicode {
+        true => source.chars().map(|c| c as u32).collect(),
+        false => source.encode_utf16().map(u32::from).collect(),
+    };
+    let mut parser = PatternParser {
+        chars,
+        position: 0,
+        unicode,
+        group_count: 0,
+        group_names: vec![],
+        named_references: vec![],
+        total_groups: 0,
+        has_named_groups: false,
+    };
+    parser.scan_groups();
+    let mut node = parser.parse_disjunction()?;
+    if parser.position < parser.chars.len() {
+        // the only way the disjunction stops early
+        return Err("Unmatched ')'".to_string());
+    }
+    for name in &parser.named_references {
+        if !parser.group_names.iter().any(|(group, _)| group == name) {
+            return Err("Invalid named capture referenced".to_string());
+        }
+    }
+    resolve_named_references(&mut node, &parser.group_names);
+    Ok(Pattern {
+        node,
+        group_count: parser.group_count,
+        group_names: parser.group_names,
+    })
+}
+
+/// Named references can come before the group they name, so the parser leaves placeholder
+/// indices from `NAMED_REFERENCE_BASE` up that are swapped for the real ones here.
+fn resolve_named_references(node: &mut Node, names: &[(String, usize)]) {
+    match node {
+        Node::Group { node, .. } | Node::Look { node, .. } | Node::Repeat { node, .. } => {
+            resolve_named_references(node, names)
+        }
+        Node::Sequence(nodes) | Node::Alternation(nodes) => {
+            for node in nodes {
+                resolve_named_references(node, names);
+            }
+        }
+        Node::BackReference(index) if *index >= NAMED_REFERENCE_BASE => {
+            let name_index = *index - NAMED_REFERENCE_BASE;
+            *index = names[name_index].1;
+        }
+        _ => {}
+    }
+}
+
+/// Placeholder back references to named groups count from here, indexing the parser's
+/// `named_references`.
+const NAMED_REFERENCE_BASE: usize = usize::MAX / 2;
+
+struct PatternParser {
+    chars: Vec<u32>,
+    position: usize,
+    unicode: bool,
+    group_count: usize,
+    group_names: Vec<(String, usize)>,
+    named_references: Vec<String>,
+    /// Capture groups in the whole pattern, which decides what `\N` means.
+    total_groups: usize,
+    has_named_groups: bool,
+}
+
+impl PatternParser {
+    fn peek(&self) -> Option<u32> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_is(&self, c: char) -> bool {
+        self.peek() == Some(c as u32)
+    }
+
+    fn peek_at_is(&self, offset: usize, c: char) -> bool {
+        self.chars.get(self.position + offset) == Some(&(c as u32))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek_is(c) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn next(&mut self) -> Option<u32> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    /// Counts the capture groups up front, skipping escapes and classes.
+    fn scan_groups(&mut self) {
+        let mut index = 0;
+        let mut in_class = false;
+        while index < self.chars.len() {
+            let c = self.chars[index];
+            let next = self.chars.get(index + 1).copied();
+            match char::from_u32(c) {
+                Some('\\') => index += 1,
+                Some('[') => in_class = true,
+                Some(']') => in_class = false,
+                Some('(') if !in_class => {
+                    if next != Some('?' as u32) {
+                        self.total_groups += 1;
+                    } else if self.chars.get(index + 2) == Some(&('<' as u32))
+                        && !matches!(
+                            self.chars.get(index + 3).and_then(|c| char::from_u32(*c)),
+                            Some('=' | '!')
+                        )
+                    {
+                        self.total_groups += 1;
+                        self.has_named_groups = true;
+                    }
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+    }
+
+    fn parse_disjunction(&mut self) -> Result<Node, String> {
+        let mut alternatives = vec![self.parse_alternative()?];
+        while self.eat('|') {
+            alternatives.push(self.parse_alternative()?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.pop().unwrap_or(Node::Empty),
+            _ => Node::Alternation(alternatives),
+        })
+    }
+
+    fn parse_alternative(&mut self) -> Result<Node, String> {
+        let mut terms = vec![];
+        while let Some(c) = self.peek() {
+            if c == '|' as u32 || c == ')' as u32 {
+                break;
+            }
+            terms.push(self.parse_term()?);
+        }
+        Ok(match terms.len() {
+            0 => Node::Empty,
+            1 => terms.pop().unwrap_or(Node::Empty),
+            _ => Node::Sequence(terms),
+        })
+    }
+
+    fn parse_term(&mut self) -> Result<Node, String> {
+        let groups_before = self.group_count;
+        let Some(c) = self.peek() else {
+            return Ok(Node::Empty);
+        };
+        let atom = match char::from_u32(c) {
+            Some('^') => {
+                self.position += 1;
+                return Ok(Node::LineStart);
+            }
+            Some('$') => {
+                self.position += 1;
+                return Ok(Node::LineEnd);
+            }
+            Some('\\') if self.peek_at_is(1, 'b') || self.peek_at_is(1, 'B') => {
+                let negated = self.peek_at_is(1, 'B');
+                self.position += 2;
+                return Ok(Node::WordBoundary { negated });
+            }
+            Some('(') if self.peek_at_is(1, '?') => {
+                let look = match (
+                    self.chars.get(self.position + 2),
+                    self.chars.get(self.position + 3),
+                ) {
+                    (Some(&c), _) if c == '=' as u32 => Some((false, false, 3)),
+                    (Some(&c), _) if c == '!' as u32 => Some((false, true, 3)),
+                    (Some(&l), Some(&c)) if l == '<' as u32 && c == '=' as u32 => {
+                        Some((true, false, 4))
+                    }
+                    (Some(&l), Some(&c)) if l == '<' as u32 && c == '!' as u32 => {
+                        Some((true, true, 4))
+                    }
+                    _ => None,
+                };
+                match look {
+                    Some((behind, negated, length)) => {
+                        self.position += length;
+                        let node = self.parse_disjunction()?;
+                        if !self.eat(')') {
+                            return Err("Unterminated group".to_string());
+                        }
+                        let look = Node::Look {
+                            behind,
+                            negated,
+                            node: Box::new(node),
+                        };
+                        // only lookaheads can be quantified, and only without the u flag
+                        if behind || self.unicode {
+                            if self.at_quantifier() {
+                                return Err("Invalid quantifier".to_string());
+                            }
+                            return Ok(look);
+                        }
+                        look
+                    }
+                    None => self.parse_atom()?,
+                }
+            }
+            _ => self.parse_atom()?,
+        };
+        self.parse_quantifier(atom, groups_before)
+    }
+
+    fn at_quantifier(&mut self) -> bool {
+        let start = self.position;
+        let result = match self.peek().and_then(char::from_u32) {
+            Some('*' | '+' | '?') => true,
+            Some('{') => self.parse_braces().is_some(),
+            _ => false,
+        };
+        self.position = start;
+        result
+    }
+
+    /// Reads `{n}`, `{n,}` or `{n,m}`. Leaves the position alone when there is none.
+    fn parse_braces(&mut self) -> Option<(u32, Option<u32>)> {
+        let start = self.position;
+        let result = (|| {
+            if !self.eat('{') {
+                return None;
+            }
+            let min = self.parse_decimal()?;
+            let max = match self.eat(',') {
+                true => match self.peek_is('}') {
+                    true => None,
+                    false => Some(self.parse_decimal()?),
+                },
+                false => Some(min),
+            };
+            if !self.eat('}') {
+                return None;
+            }
+            Some((min, max))
+        })();
+        if result.is_none() {
+            self.position = start;
+        }
+        result
+    }
+
+    fn parse_decimal(&mut self) -> Option<u32> {
+        let start = self.position;
+        let mut value: u32 = 0;
+        while let Some(digit) = self
+            .peek()
+            .and_then(char::from_u32)
+            .and_then(|c| c.to_digit(10))
+        {
+            // large counts saturate, they can't be told apart from infinity in practice
+            value = value.saturating_mul(10).saturating_add(digit);
+            self.position += 1;
+        }
+        match self.position > start {
+            true => Some(value),
+            false => None,
+        }
+    }
+
+    fn parse_quantifier(&mut self, atom: Node, groups_before: usize) -> Result<Node, String> {
+        let (min, max) = match self.peek().and_then(char::from_u32) {
+            Some('*') => {
+                self.position += 1;
+                (0, None)
+            }
+            Some('+') => {
+                self.position += 1;
+                (1, None)
+            }
+            Some('?') => {
+                self.position += 1;
+                (0, Some(1))
+            }
+            Some('{') => match self.parse_braces() {
+                Some(bounds) => bounds,
+                None if self.unicode => return Err("Incomplete quantifier".to_string()),
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if let Some(max) = max
+            && max < min
+        {
+            return Err("numbers out of order in {} quantifier".to_string());
+        }
+        let greedy = !self.eat('?');
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+            groups: groups_before + 1..self.group_count + 1,
+        })
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, String> {
+        let c = self.next().ok_or("Unexpected end of pattern")?;
+        match char::from_u32(c) {
+            Some('.') => Ok(Node::Any),
+            Some('(') => self.parse_group(),
+            Some('[') => Ok(Node::Class(self.parse_class()?)),
+            Some('\\') => self.parse_atom_escape(),
+            Some('*' | '+' | '?') => Err("Nothing to repeat".to_string()),
+            Some('{') => {
+                self.position -= 1;
+                if self.parse_braces().is_some() {
+                    return Err("Nothing to repeat".to_string());
+                }
+                self.position += 1;
+                match self.unicode {
+                    true => Err("Lone quantifier brackets".to_string()),
+                    false => Ok(Node::Char(c)),
+                }
+            }
+            Some('}' | ']') if self.unicode => Err("Lone quantifier brackets".to_string()),
+            _ => Ok(Node::Char(c)),
+        }
+    }
+
+    // the opening paren has been consumed
+    fn parse_group(&mut self) -> Result<Node, String> {
+        let index = if self.eat('?') {
+            if self.eat(':') {
+                None
+            } else if self.eat('<') {
+                let name = self.parse_group_name()?;
+                if self
+                    .group_names
+                    .iter()
+                    .any(|(existing, _)| *existing == name)
+                {
+                    return Err("Duplicate capture group name".to_string());
+                }
+                self.group_count += 1;
+                self.group_names.push((name, self.group_count));
+                Some(self.group_count)
+            } else {
+                return Err("Invalid group".to_string());
+            }
+        } else {
+            self.group_count += 1;
+            Some(self.group_count)
+        };
+        let node = self.parse_disjunction()?;
+        if !self.eat(')') {
+            return Err("Unterminated group".to_string());
+        }
+        Ok(Node::Group {
+            index,
+            node: Box::new(node),
+        })
+    }
+
+    // the opening angle bracket has been consumed
+    fn parse_group_name(&mut self) -> Result<String, String> {
+        let mut name = String::new();
+        loop {
+            let c = self.next().and_then(char::from_u32);
+            match c {
+                Some('>') if !name.is_empty() => return Ok(name),
+                Some(c)
+                    if c == '$'
+                        || c == '_'
+                        || c.is_alphabetic()
+                        || (!name.is_empty()
+                            && (c.is_alphanumeric() || c == '\u{200c}' || c == '\u{200d}')) =>
+                {
+                    name.push(c)
+                }
+                _ => return Err("Invalid capture group name".to_string()),
+            }
+        }
+    }
+
+    // the backslash has been consumed
+    fn parse_atom_escape(&mut self) -> Result<Node, String> {
+        let c = self.peek().ok_or("\\ at end of pattern")?;
+        match char::from_u32(c) {
+            Some('1'..='9') => {
+                let start = self.position;
+                let number = self.parse_decimal().unwrap_or_default() as usize;
+                if number <= self.total_groups {
+                    return Ok(Node::BackReference(number));
+                }
+                if self.unicode {
+                    return Err("Invalid escape".to_string());
+                }
+                // not a back reference, so a legacy octal escape or the digit itself
+                self.position = start;
+                Ok(Node::Char(self.parse_legacy_octal()))
+            }
+            Some('k') if self.unicode || self.has_named_groups => {
+                self.position += 1;
+                self.parse_named_reference()
+            }
+            Some('d' | 'D' | 's' | 'S' | 'w' | 'W') => {
+                self.position += 1;
+                Ok(Node::Class(CharClass {
+                    negated: false,
+                    items: vec![class_escape(c)],
+                }))
+            }
+            _ => Ok(Node::Char(self.parse_character_escape(false)?)),
+        }
+    }
+
+    // the `k` has been consumed
+    fn parse_named_reference(&mut self) -> Result<Node, String> {
+        if !self.eat('<') {
+            return Err("Invalid named reference".to_string());
+        }
+        let name = self
+            .parse_group_name()
+            .map_err(|_| "Invalid named reference".to_string())?;
+        let index = match self.named_references.iter().position(|n| *n == name) {
+            Some(index) => index,
+            None => {
+                self.named_references.push(name);
+                self.named_references.len() - 1
+            }
+        };
+        Ok(Node::BackReference(NAMED_REFERENCE_BASE + index))
+    }
+
+    /// Up to three octal digits worth at most 0o377, or a single `8` or `9` as itself.
+    fn parse_legacy_octal(&mut self) -> u32 {
+        let first = self.next().unwrap_or_default();
+        let Some(mut value) = char::from_u32(first).and_then(|c| c.to_digit(8)) else {
+            return first;
+        };
+        for _ in 0..2 {
+            match self
+                .peek()
+                .and_then(char::from_u32)
+                .and_then(|c| c.to_digit(8))
+            {
+                Some(digit) if value * 8 + digit <= 0o377 => {
+                    value = value * 8 + digit;
+                    self.position += 1;
+                }
+                _ => break,
+            }
+        }
+        value
+    }
+
+    /// The escapes that stand for a single character, after the backslash. `in_class` allows
+    /// `\b` for backspace and `\-`.
+    fn parse_character_escape(&mut self, in_class: bool) -> Result<u32, String> {
+        let c = self.next().ok_or("\\ at end of pattern")?;
+        let value = match char::from_u32(c) {
+            Some('t') => 0x09,
+            Some('n') => 0x0a,
+            Some('v') => 0x0b,
+            Some('f') => 0x0c,
+            Some('r') => 0x0d,
+            Some('b') if in_class => 0x08,
+            Some('-') if in_class => c,
+            Some('c') => match self.peek().and_then(char::from_u32) {
+                Some(letter) if letter.is_ascii_alphabetic() => {
+                    self.position += 1;
+                    letter as u32 % 32
+                }
+                _ if self.unicode => return Err("Invalid unicode escape".to_string()),
+                // the backslash is a character of its own, the `c` comes next
+                _ => {
+                    self.position -= 1;
+                    '\\' as u32
+                }
+            },
+            Some('0')
+                if !self
+                    .peek()
+                    .and_then(char::from_u32)
+                    .is_some_and(|c| c.is_ascii_digit()) =>
+            {
+                0
+            }
+            Some('0'..='7') if !self.unicode => {
+                self.position -= 1;
+                self.parse_legacy_octal()
+            }
+            Some('x') => match self.parse_hex_digits(2) {
+                Some(value) => value,
+                None if self.unicode => return Err("Invalid escape".to_string()),
+                None => c,
+            },
+            Some('u') => match self.parse_unicode_escape() {
+                Some(value) => value,
+                None if self.unicode => return Err("Invalid Unicode escape".to_string()),
+                None => c,
+            },
+            Some(
+                '^' | '$' | '\\' | '.' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|'
+                | '/',
+            ) => c,
+            // `\p{...}` property escapes aren't supported
+            _ if self.unicode => return Err("Invalid escape".to_string()),
+            _ => c,
+        };
+        Ok(value)
+    }
+
+    fn parse_hex_digits(&mut self, count: usize) -> Option<u32> {
+        let digits = self.chars.get(self.position..self.position + count)?;
+        let mut value = 0;
+        for digit in digits {
+            value = value * 16 + char::from_u32(*digit)?.to_digit(16)?;
+        }
+        self.position += count;
+        Some(value)
+    }
+
+    // the `u` has been consumed
+    fn parse_unicode_escape(&mut self) -> Option<u32> {
+        if self.unicode && self.peek_is('{') {
+            let start = self.position;
+            self.position += 1;
+            let mut value: u32 = 0;
+            let mut digits = 0;
+            while let Some(digit) = self
+                .peek()
+                .and_then(char::from_u32)
+                .and_then(|c| c.to_digit(16))
+            {
+                value = value.saturating_mul(16).saturating_add(digit);
+                digits += 1;
+                self.position += 1;
+            }
+            if digits == 0 || value > 0x10ffff || !self.eat('}') {
+                self.position = start;
+                return None;
+            }
+            return Some(value);
+        }
+        let value = self.parse_hex_digits(4)?;
+        // with the u flag an escaped surrogate pair is one code point
+        if self.unicode
+            && (0xd800..0xdc00).contains(&value)
+            && self.peek_is('\\')
+            && self.peek_at_is(1, 'u')
+        {
+            let start = self.position;
+            self.position += 2;
+            match self.parse_hex_digits(4) {
+                Some(low) if (0xdc00..0xe000).contains(&low) => {
+                    return Some(0x10000 + ((value - 0xd800) << 10) + (low - 0xdc00));
+                }
+                _ => self.position = start,
+            }
+        }
+        Some(value)
+    }
+
+    // the opening bracket has been consumed
+    fn parse_class(&mut self) -> Result<CharClass, String> {
+        let negated = self.eat('^');
+        let mut items = vec![];
+        loop {
+            if self.eat(']') {
+                return Ok(CharClass { negated, items });
+            }
+            let first = self.parse_class_atom()?;
+            if self.peek_is('-') && !self.peek_at_is(1, ']') && self.position + 1 < self.chars.len()
+            {
+                self.position += 1;
+                let second = self.parse_class_atom()?;
+                match (&first, &second) {
+                    (ClassItem::Range(low, _), ClassItem::Range(high, _)) => {
+                        if low > high {
+                            return Err("Range out of order in character class".to_string());
+                        }
+                        items.push(ClassItem::Range(*low, *high));
+                    }
+                    _ if self.unicode => return Err("Invalid character class".to_string()),
+                    // a class escape can't bound a range, so the dash is a character
+                    _ => {
+                        items.push(first);
+                        items.push(ClassItem::Range('-' as u32, '-' as u32));
+                        items.push(second);
+                    }
+                }
+                continue;
+            }
+            items.push(first);
+        }
+    }
+
+    fn parse_class_atom(&mut self) -> Result<ClassItem, String> {
+        let c = self.next().ok_or("Unterminated character class")?;
+        if c != '\\' as u32 {
+            return Ok(ClassItem::Range(c, c));
+        }
+        let escaped = self.peek().ok_or("\\ at end of pattern")?;
+        match char::from_u32(escaped) {
+            Some('d' | 'D' | 's' | 'S' | 'w' | 'W') => {
+                self.position += 1;
+                Ok(class_escape(escaped))
+            }
+            Some('B') if self.unicode => Err("Invalid class escape".to_string()),
+            Some('1'..='9') if !self.unicode => {
+                let value = self.parse_legacy_octal();
+                Ok(ClassItem::Range(value, value))
+            }
+            _ => {
+                let value = self.parse_character_escape(true)?;
+                Ok(ClassItem::Range(value, value))
+            }
+        }
+    }
+}
+
+fn class_escape(c: u32) -> ClassItem {
+    let c = char::from_u32(c).unwrap_or_default();
+    let negated = c.is_ascii_uppercase();
+    match c.to_ascii_lowercase() {
+        'd' => ClassItem::Digit { negated },
+        's' => ClassItem::Space { negated },
+        _ => ClassItem::Word { negated },
+    }
+}
@@ -0,0 +1,180 @@
+//! Bookkeeping that lets a generator suspend in the middle of the recursive tree walk.
+//!
+//! A `yield` unwinds the Rust stack like any other abrupt completion. Every node it passes
+//! through that has already done part of its work pushes a `ResumeRecord` describing where it
+//! was. Resuming evaluates the generator body again with those records restored; each node on the
+//! path pops its own record and skips straight to the child that suspended. Records are only
+//! present while walking back down that path, so nodes evaluated normally never see one.
+
+use crate::{
+    Interpreter,
+    builtins::iterator::IteratorRecord,
+    errors::JSError,
+    expr::Expr,
+    heap::{Trace, Tracer},
+    values::{JSResult, JSValue},
+};
+
+/// How a suspended generator is being resumed: `next(v)`, `throw(v)` or `return(v)`.
+#[derive(Clone, Debug)]
+pub enum ResumeAction {
+    Next(JSValue),
+    Throw(JSValue),
+    Return(JSValue),
+}
+
+#[derive(Clone, Debug)]
+pub enum LoopPhase {
+    Initializer,
+    Condition,
+    Body,
+    Update,
+}
+
+#[derive(Clone, Debug)]
+pub enum TryPhase {
+    Block,
+    Catch {
+        environment: usize,
+    },
+    Finally {
+        completion: Result<JSValue, JSError>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum ResumeRecord {
+    /// The `yield` that suspended. The action is filled in when the generator is resumed.
+    Yield(Option<ResumeAction>),
+    /// A suspended `yield*` and the iterator it delegates to.
+    Delegate {
+        iterator: IteratorRecord,
+        action: Option<ResumeAction>,
+    },
+    /// Results of the operands an expression had already evaluated.
+    Operands(Vec<JSValue>),
+    Block {
+        index: usize,
+        environment: usize,
+    },
+    /// The scope of the branch taken and which one it was, `None` while the condition itself
+    /// was suspended.
+    Branch(Option<(usize, bool)>),
+    Loop {
+        environment: Option<usize>,
+        phase: LoopPhase,
+    },
+    /// The loop's iterator and the scope of the current iteration, `None` while the iterable
+    /// expression was suspended.
+    ForOf(Option<(IteratorRecord, usize)>),
+    Try(TryPhase),
+}
+
+impl ResumeRecord {
+    /// Hands the resume action to the record of the suspended `yield`.
+    pub fn set_action(&mut self, resume_action: ResumeAction) {
+        match self {
+            ResumeRecord::Yield(action) | ResumeRecord::Delegate { action, .. } => {
+                *action = Some(resume_action);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Trace for ResumeAction {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            ResumeAction::Next(value)
+            | ResumeAction::Throw(value)
+            | ResumeAction::Return(value) => value.trace(tracer),
+        }
+    }
+}
+
+impl Trace for ResumeRecord {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            ResumeRecord::Yield(action) => action.trace(tracer),
+            ResumeRecord::Delegate { iterator, action } => {
+                iterator.trace(tracer);
+                action.trace(tracer);
+            }
+            ResumeRecord::Operands(values) => values.trace(tracer),
+            ResumeRecord::Block { environment, .. }
+            | ResumeRecord::Branch(Some((environment, _)))
+            | ResumeRecord::Loop {
+                environment: Some(environment),
+                ..
+            }
+            | ResumeRecord::Try(TryPhase::Catch { environment }) => tracer.mark(*environment),
+            ResumeRecord::ForOf(Some((iterator, environment))) => {
+                iterator.trace(tracer);
+                tracer.mark(*environment);
+            }
+            ResumeRecord::Try(TryPhase::Finally { completion }) => match completion {
+                Ok(value) => value.trace(tracer),
+                Err(error) => error.trace(tracer),
+            },
+            ResumeRecord::Branch(None)
+            | ResumeRecord::Loop {
+                environment: None, ..
+            }
+            | ResumeRecord::ForOf(None)
+            | ResumeRecord::Try(TryPhase::Block) => (),
+        }
+    }
+}
+
+/// Evaluates the operands of an expression left to right, caching each result so a resumed
+/// generator doesn't evaluate them (and their side effects) a second time.
+pub struct OperandCursor {
+    values: Vec<JSValue>,
+    position: usize,
+}
+
+impl OperandCursor {
+    pub fn new(interpreter: &mut Interpreter) -> Self {
+        let values = match interpreter.take_resume_record() {
+            Some(ResumeRecord::Operands(values)) => values,
+            Some(record) => {
+                interpreter.save_resume_record(record);
+                vec![]
+            }
+            None => vec![],
+        };
+        Self {
+            values,
+            position: 0,
+        }
+    }
+
+    pub fn evaluate(&mut self, expr: &Expr, interpreter: &mut Interpreter) -> JSResult<JSValue> {
+        self.compute(interpreter, |interpreter| expr.evaluate(interpreter))
+    }
+
+    /// Like `evaluate` for intermediate results that aren't a single sub-expression, such as
+    /// the method looked up for a call.
+    pub fn compute<F>(&mut self, interpreter: &mut Interpreter, f: F) -> JSResult<JSValue>
+    where
+        F: FnOnce(&mut Interpreter) -> JSResult<JSValue>,
+    {
+        if let Some(value) = self.values.get(self.position) {
+            self.position += 1;
+            return Ok(value.clone());
+        }
+        match f(interpreter) {
+            Ok(value) => {
+                self.values.push(value.clone());
+                self.position += 1;
+                Ok(value)
+            }
+            Err(e) => {
+                if e.is_yield() {
+                    interpreter.save_resume_record(ResumeRecord::Operands(self.values.clone()));
+                }
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,1037 @@
+use std::fmt::{self, Display};
+
+use num_bigint::BigInt;
+use serde::{
+    Serialize,
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, VariantAccess, Visitor,
+    },
+    ser::{
+        self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+};
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    Interpreter,
+    builtins::{
+        create_array, index_key,
+        object::{own_keys, string_keys},
+    },
+    errors::JSError,
+    global::{get_or_intern_string, get_string_from_pool},
+    handles::{HandleKind, HandleScope, Local, Value},
+    values::{self, JSValue, ObjectKind, get_property_value, set_property_value},
+};
+
+/// The largest integer a number holds exactly along with all the ones below it.
+const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+/// Why a Rust value couldn't be converted to a JS value or back, and where in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SerdeError {
+    message: String,
+    /// Where the conversion failed, e.g. `.items[3].price`. Empty for the value itself, and
+    /// `None` until the innermost value the error passes through fills it in.
+    path: Option<String>,
+}
+
+impl SerdeError {
+    fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            path: None,
+        }
+    }
+
+    fn expected(what: &str, path: &str) -> Self {
+        Self::new(&format!("expected {what}")).at(path)
+    }
+
+    fn at(mut self, path: &str) -> Self {
+        self.path.get_or_insert_with(|| path.to_string());
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or_default()
+    }
+}
+
+impl Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path() {
+            "" => write!(f, "{}", self.message),
+            path => write!(f, "{} at {}", self.message, path),
+        }
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(&msg.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(&msg.to_string())
+    }
+}
+
+impl From<JSError> for SerdeError {
+    fn from(error: JSError) -> Self {
+        Self::new(&error.message)
+    }
+}
+
+impl<'i> HandleScope<'i> {
+    /// Builds the JS counterpart of `value`: objects for structs and maps, arrays for
+    /// sequences and tuples, `null` for `None` and `()`. Enum variants with data become
+    /// objects with the variant's name as their only key, like `serde_json` does. Integers a
+    /// number can't hold exactly, beyond `Number.MAX_SAFE_INTEGER` either way, become BigInts.
+    pub fn to_js<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<Local<'i, Value>, SerdeError> {
+        let _isolate = self.interpreter.isolate.enter();
+        let value = value.serialize(ValueSerializer {
+            interpreter: self.interpreter,
+        })?;
+        Ok(self.local(value))
+    }
+
+    /// Reads a `T` back from `value`, the other way around from `to_js`.
+    pub fn from_js<T: DeserializeOwned>(
+        &mut self,
+        value: &Local<'i, impl HandleKind>,
+    ) -> Result<T, SerdeError> {
+        self.check(value);
+        let _isolate = self.interpreter.isolate.enter();
+        T::deserialize(ValueDeserializer {
+            interpreter: self.interpreter,
+            value: value.value.clone(),
+            path: String::new(),
+        })
+    }
+}
+
+impl Interpreter {
+    /// Stores the JS counterpart of `value` in the global property `name`, see
+    /// `HandleScope::to_js`.
+    pub fn set_global_value<T: Serialize + ?Sized>(
+        &mut self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let _isolate = self.isolate.enter();
+        let mark = self.heap.temporaries_mark();
+        let result = value
+            .serialize(ValueSerializer { interpreter: self })
+            .and_then(|value| {
+                let global = self.get_global_object()?;
+                set_property_value(self, &global, get_or_intern_string(name), value)?;
+                Ok(())
+            });
+        self.heap.release_temporaries(mark);
+        result
+    }
+
+    /// Reads a `T` from the global variable or property `name`, see `HandleScope::from_js`.
+    pub fn get_global_value<T: DeserializeOwned>(&mut self, name: &str) -> Result<T, SerdeError> {
+        let _isolate = self.isolate.enter();
+        let mark = self.heap.temporaries_mark();
+        let result = self
+            .get_value_from_environment(get_or_intern_string(name))
+            .cloned()
+            .map_err(SerdeError::from)
+            .and_then(|value| {
+                T::deserialize(ValueDeserializer {
+                    interpreter: self,
+                    value,
+                    path: String::new(),
+                })
+            });
+        self.heap.release_temporaries(mark);
+        result
+    }
+}
+
+/// Allocates as it goes. Nothing it creates is reachable before it's done, so this relies
+/// on every allocation being rooted as a temporary.
+struct ValueSerializer<'a> {
+    interpreter: &'a mut Interpreter,
+}
+
+impl<'a> ValueSerializer<'a> {
+    fn number(self, value: f64) -> Result<JSValue, SerdeError> {
+        Ok(JSValue::new_number(&value))
+    }
+
+    fn integer(self, value: i128) -> Result<JSValue, SerdeError> {
+        if value.unsigned_abs() <= MAX_SAFE_INTEGER {
+            return self.number(value as f64);
+        }
+        Ok(JSValue::BigInt {
+            data: Box::new(BigInt::from(value)),
+        })
+    }
+
+    fn string(self, value: &str) -> Result<JSValue, SerdeError> {
+        Ok(JSValue::new_string(&get_or_intern_string(value)))
+    }
+}
+
+/// `{ variant: value }`, how enum variants with data are represented.
+fn wrap_variant(interpreter: &mut Interpreter, variant: &str, value: JSValue) -> JSValue {
+    JSValue::new_object(vec![(get_or_intern_string(variant), value)], interpreter)
+}
+
+impl<'a> ser::Serializer for ValueSerializer<'a> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = SeqSerializer<'a>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = MapSerializer<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<JSValue, SerdeError> {
+        Ok(JSValue::new_boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<JSValue, SerdeError> {
+        self.number(v as f64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<JSValue, SerdeError> {
+        self.number(v as f64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<JSValue, SerdeError> {
+        self.number(v as f64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<JSValue, SerdeError> {
+        self.integer(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<JSValue, SerdeError> {
+        self.integer(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<JSValue, SerdeError> {
+        self.number(v as f64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<JSValue, SerdeError> {
+        self.number(v as f64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<JSValue, SerdeError> {
+        self.number(v as f64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<JSValue, SerdeError> {
+        self.integer(v.into())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<JSValue, SerdeError> {
+        match i128::try_from(v) {
+            Ok(v) => self.integer(v),
+            Err(_) => Ok(JSValue::BigInt {
+                data: Box::new(BigInt::from(v)),
+            }),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<JSValue, SerdeError> {
+        self.number(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<JSValue, SerdeError> {
+        self.number(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<JSValue, SerdeError> {
+        self.string(&v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<JSValue, SerdeError> {
+        self.string(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<JSValue, SerdeError> {
+        let bytes = v.iter().map(|byte| JSValue::new_number(&(*byte as f64)));
+        Ok(create_array(bytes.collect(), self.interpreter))
+    }
+
+    fn serialize_none(self) -> Result<JSValue, SerdeError> {
+        Ok(JSValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<JSValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JSValue, SerdeError> {
+        Ok(JSValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JSValue, SerdeError> {
+        Ok(JSValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<JSValue, SerdeError> {
+        self.string(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<JSValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JSValue, SerdeError> {
+        let value = value.serialize(ValueSerializer {
+            interpreter: self.interpreter,
+        })?;
+        Ok(wrap_variant(self.interpreter, variant, value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<'a>, SerdeError> {
+        Ok(SeqSerializer {
+            interpreter: self.interpreter,
+            values: Vec::with_capacity(len.unwrap_or_default()),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'a>, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'a>, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'a>, SerdeError> {
+        let mut serializer = self.serialize_seq(Some(len))?;
+        serializer.variant = Some(variant);
+        Ok(serializer)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'a>, SerdeError> {
+        Ok(MapSerializer {
+            interpreter: self.interpreter,
+            properties: vec![],
+            next_key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer<'a>, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer<'a>, SerdeError> {
+        let mut serializer = self.serialize_map(Some(len))?;
+        serializer.variant = Some(variant);
+        Ok(serializer)
+    }
+}
+
+struct SeqSerializer<'a> {
+    interpreter: &'a mut Interpreter,
+    values: Vec<JSValue>,
+    /// The variant a tuple variant's array gets wrapped in.
+    variant: Option<&'static str>,
+}
+
+impl SeqSerializer<'_> {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let value = value.serialize(ValueSerializer {
+            interpreter: self.interpreter,
+        })?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<JSValue, SerdeError> {
+        let array = create_array(self.values, self.interpreter);
+        Ok(match self.variant {
+            Some(variant) => wrap_variant(self.interpreter, variant, array),
+            None => array,
+        })
+    }
+}
+
+impl SerializeSeq for SeqSerializer<'_> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JSValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl SerializeTuple for SeqSerializer<'_> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JSValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl SerializeTupleStruct for SeqSerializer<'_> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JSValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl SerializeTupleVariant for SeqSerializer<'_> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JSValue, SerdeError> {
+        self.finish()
+    }
+}
+
+struct MapSerializer<'a> {
+    interpreter: &'a mut Interpreter,
+    properties: Vec<(SymbolU32, JSValue)>,
+    next_key: Option<SymbolU32>,
+    /// The variant a struct variant's object gets wrapped in.
+    variant: Option<&'static str>,
+}
+
+impl MapSerializer<'_> {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        key: SymbolU32,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let value = value.serialize(ValueSerializer {
+            interpreter: self.interpreter,
+        })?;
+        self.properties.push((key, value));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<JSValue, SerdeError> {
+        let object = JSValue::new_object(self.properties, self.interpreter);
+        Ok(match self.variant {
+            Some(variant) => wrap_variant(self.interpreter, variant, object),
+            None => object,
+        })
+    }
+}
+
+impl SerializeMap for MapSerializer<'_> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+
+    /// Property keys are strings, so only keys that serialize to a string, a number or a
+    /// boolean are supported.
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        let key = key.serialize(ValueSerializer {
+            interpreter: self.interpreter,
+        })?;
+        let key = match key {
+            JSValue::String { data } => data,
+            JSValue::Number { data } => get_or_intern_string(&values::to_string(data, 10)),
+            JSValue::Boolean { data } => get_or_intern_string(&data.to_string()),
+            _ => return Err(SerdeError::new("map keys must be strings or numbers")),
+        };
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| SerdeError::new("map value without a key"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<JSValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl SerializeStruct for MapSerializer<'_> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert(get_or_intern_string(key), value)
+    }
+
+    fn end(self) -> Result<JSValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl SerializeStructVariant for MapSerializer<'_> {
+    type Ok = JSValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert(get_or_intern_string(key), value)
+    }
+
+    fn end(self) -> Result<JSValue, SerdeError> {
+        self.finish()
+    }
+}
+
+/// Reads `value` through the same property lookups a script would do, getters included.
+/// `path` is where `value` sits in the value being read, for error messages.
+struct ValueDeserializer<'a> {
+    interpreter: &'a mut Interpreter,
+    value: JSValue,
+    path: String,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn expected(&self, what: &str) -> SerdeError {
+        SerdeError::expected(what, &self.path)
+    }
+
+    fn visit_number<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let number = match &self.value {
+            JSValue::Number { data } => *data,
+            JSValue::BigInt { data } => {
+                let data = data.as_ref();
+                let result = if let Ok(number) = i64::try_from(data) {
+                    visitor.visit_i64(number)
+                } else if let Ok(number) = u64::try_from(data) {
+                    visitor.visit_u64(number)
+                } else if let Ok(number) = i128::try_from(data) {
+                    visitor.visit_i128(number)
+                } else if let Ok(number) = u128::try_from(data) {
+                    visitor.visit_u128(number)
+                } else {
+                    return Err(self.expected("number"));
+                };
+                return result.map_err(|e: SerdeError| e.at(&self.path));
+            }
+            _ => return Err(self.expected("number")),
+        };
+        // integral numbers are read as integers, so they fit whatever integer type is asked for
+        // that has room for them, up to u64::MAX and down to i64::MIN
+        let result = match number.fract() == 0.0 {
+            true if (0.0..18_446_744_073_709_551_616.0).contains(&number) => {
+                visitor.visit_u64(number as u64)
+            }
+            true if (-9_223_372_036_854_775_808.0..0.0).contains(&number) => {
+                visitor.visit_i64(number as i64)
+            }
+            _ => visitor.visit_f64(number),
+        };
+        result.map_err(|e: SerdeError| e.at(&self.path))
+    }
+
+    fn visit_array<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if !matches!(
+            self.value,
+            JSValue::Object {
+                kind: ObjectKind::Array,
+                ..
+            }
+        ) {
+            return Err(self.expected("array"));
+        }
+        self.interpreter.root_value(&self.value);
+        let length = get_property_value(
+            self.interpreter,
+            &self.value,
+            get_or_intern_string("length"),
+        )
+        .map_err(|e| SerdeError::from(e).at(&self.path))?;
+        let length = match length {
+            JSValue::Number { data } => data as usize,
+            _ => 0,
+        };
+        let path = self.path.clone();
+        visitor
+            .visit_seq(ArrayAccess {
+                interpreter: self.interpreter,
+                array: self.value,
+                index: 0,
+                length,
+                path: self.path,
+            })
+            .map_err(|e: SerdeError| e.at(&path))
+    }
+
+    fn visit_object<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if !matches!(
+            self.value,
+            JSValue::Object {
+                kind: ObjectKind::Object,
+                ..
+            }
+        ) {
+            return Err(self.expected("object"));
+        }
+        self.interpreter.root_value(&self.value);
+        let keys = own_keys(self.interpreter, &self.value, true)
+            .map_err(|e| SerdeError::from(e).at(&self.path))?;
+        let path = self.path.clone();
+        visitor
+            .visit_map(ObjectAccess {
+                interpreter: self.interpreter,
+                object: self.value,
+                keys: string_keys(keys).into_iter(),
+                key: None,
+                path: self.path,
+            })
+            .map_err(|e: SerdeError| e.at(&path))
+    }
+
+    fn string(&self) -> Option<String> {
+        match &self.value {
+            JSValue::String { data } => get_string_from_pool(data),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let path = self.path.clone();
+        match &self.value {
+            JSValue::Null | JSValue::Undefined => visitor.visit_unit(),
+            JSValue::Boolean { data } => visitor.visit_bool(*data),
+            JSValue::Number { .. } | JSValue::BigInt { .. } => return self.visit_number(visitor),
+            JSValue::String { .. } => visitor.visit_string(self.string().unwrap_or_default()),
+            JSValue::Symbol { .. } => return Err(self.expected("a value other than a symbol")),
+            JSValue::Object {
+                kind: ObjectKind::Array,
+                ..
+            } => return self.visit_array(visitor),
+            JSValue::Object {
+                kind: ObjectKind::Object,
+                ..
+            } => return self.visit_object(visitor),
+            JSValue::Object {
+                kind: ObjectKind::Function,
+                ..
+            } => return Err(self.expected("a value other than a function")),
+        }
+        .map_err(|e: SerdeError| e.at(&path))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JSValue::Boolean { data } => visitor
+                .visit_bool(data)
+                .map_err(|e: SerdeError| e.at(&self.path)),
+            _ => Err(self.expected("boolean")),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_number(visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.string() {
+            Some(string) => visitor
+                .visit_string(string)
+                .map_err(|e: SerdeError| e.at(&self.path)),
+            None => Err(self.expected("string")),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JSValue::Null | JSValue::Undefined => visitor
+                .visit_none()
+                .map_err(|e: SerdeError| e.at(&self.path)),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JSValue::Null | JSValue::Undefined => visitor
+                .visit_unit()
+                .map_err(|e: SerdeError| e.at(&self.path)),
+            _ => Err(self.expected("null")),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.visit_object(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.visit_object(visitor)
+    }
+
+    /// Unit variants are read from their name, the others from an object with the variant's
+    /// name as its only key, the way `to_js` writes them.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        if let Some(variant) = self.string() {
+            let variant: de::value::StringDeserializer<SerdeError> = variant.into_deserializer();
+            return visitor
+                .visit_enum(variant)
+                .map_err(|e: SerdeError| e.at(&self.path));
+        }
+        let keys = match &self.value {
+            JSValue::Object {
+                kind: ObjectKind::Object,
+                ..
+            } => own_keys(self.interpreter, &self.value, true)
+                .map_err(|e| SerdeError::from(e).at(&self.path))?,
+            _ => vec![],
+        };
+        let [key] = string_keys(keys)[..] else {
+            return Err(self.expected("string or object with a single key"));
+        };
+        let data = get_property_value(self.interpreter, &self.value, key)
+            .map_err(|e| SerdeError::from(e).at(&self.path))?;
+        let variant = get_string_from_pool(&key).unwrap_or_default();
+        let path = self.path.clone();
+        visitor
+            .visit_enum(VariantData {
+                data: ValueDeserializer {
+                    interpreter: self.interpreter,
+                    value: data,
+                    path: format!("{}.{variant}", self.path),
+                },
+                variant,
+            })
+            .map_err(|e: SerdeError| e.at(&path))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+}
+
+struct ArrayAccess<'a> {
+    interpreter: &'a mut Interpreter,
+    array: JSValue,
+    index: usize,
+    length: usize,
+    path: String,
+}
+
+impl<'de> SeqAccess<'de> for ArrayAccess<'_> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        if self.index >= self.length {
+            return Ok(None);
+        }
+        let path = format!("{}[{}]", self.path, self.index);
+        let value = get_property_value(self.interpreter, &self.array, index_key(self.index))
+            .map_err(|e| SerdeError::from(e).at(&path))?;
+        self.index += 1;
+        seed.deserialize(ValueDeserializer {
+            interpreter: self.interpreter,
+            value,
+            path,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.length - self.index)
+    }
+}
+
+struct ObjectAccess<'a> {
+    interpreter: &'a mut Interpreter,
+    object: JSValue,
+    keys: std::vec::IntoIter<SymbolU32>,
+    /// The key whose value is read next.
+    key: Option<SymbolU32>,
+    path: String,
+}
+
+impl<'de> MapAccess<'de> for ObjectAccess<'_> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        let Some(key) = self.keys.next() else {
+            return Ok(None);
+        };
+        self.key = Some(key);
+        let name: de::value::StringDeserializer<SerdeError> = get_string_from_pool(&key)
+            .unwrap_or_default()
+            .into_deserializer();
+        seed.deserialize(name).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerdeError::new("map value without a key"))?;
+        let path = format!(
+            "{}.{}",
+            self.path,
+            get_string_from_pool(&key).unwrap_or_default()
+        );
+        let value = get_property_value(self.interpreter, &self.object, key)
+            .map_err(|e| SerdeError::from(e).at(&path))?;
+        seed.deserialize(ValueDeserializer {
+            interpreter: self.interpreter,
+            value,
+            path,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.keys.len())
+    }
+}
+
+/// An enum variant with data, read from `{ variant: data }`.
+struct VariantData<'a> {
+    data: ValueDeserializer<'a>,
+    variant: String,
+}
+
+impl<'de, 'a> EnumAccess<'de> for VariantData<'a> {
+    type Error = SerdeError;
+    type Variant = ValueDeserializer<'a>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, ValueDeserializer<'a>), SerdeError> {
+        let variant: de::value::StringDeserializer<SerdeError> = self.variant.into_deserializer();
+        Ok((seed.deserialize(variant)?, self.data))
+    }
+}
+
+impl<'de> VariantAccess<'de> for ValueDeserializer<'_> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.visit_array(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.visit_object(visitor)
+    }
+}
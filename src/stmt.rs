@@ -1,21 +1,68 @@
 use std::fmt;
 
 use log::info;
+use string_interner::symbol::SymbolU32;
 
 use crate::{
     Interpreter,
+    builtins::iterator::{IteratorRecord, get_iterator, iterator_close, iterator_step},
+    constants::{DEFAULT_EXPORT_BINDING, DEFAULT_NAME},
     errors::{ErrorKind, JSError},
-    expr::Expr,
-    global::get_string_from_pool,
-    utils::get_function_params,
-    values::{JSObject, JSResult, JSValue, ObjectKind},
+    expr::{ClassDefinition, Expr, evaluate_class, evaluate_function, evaluate_named},
+    global::{get_or_intern_string, get_string_from_pool},
+    resume::{LoopPhase, ResumeRecord, TryPhase},
+    values::{BodyKind, FunctionKind, JSResult, JSValue},
 };
 
+/// What an import or re-export takes from the other module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportName {
+    Name(SymbolU32),
+    /// `* as ns`, the module namespace object.
+    Namespace,
+}
+
+/// One binding of an import declaration, e.g. `a as b` imports `a` under the local name `b`.
+#[derive(Clone, Debug)]
+pub struct ImportBinding {
+    pub imported: ImportName,
+    pub local: SymbolU32,
+}
+
+/// `export a as b` exports the local `a` under the name `b`.
+#[derive(Clone, Debug)]
+pub struct ExportSpecifier {
+    pub local: SymbolU32,
+    pub exported: SymbolU32,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExportDeclaration {
+    /// `export let x = 1;`, `export function f() {}` or `export class C {}`
+    Declaration(Stmt),
+    /// `export default <expression>;`, held in the hidden `*default*` binding.
+    Default(Expr),
+    /// `export default function f() {}` or `export default class C {}`
+    DefaultDeclaration(Stmt),
+    /// `export { a, b as c };`, or a re-export when `from` is set.
+    List {
+        specifiers: Vec<ExportSpecifier>,
+        from: Option<SymbolU32>,
+    },
+    /// `export * from "./x.js";` or `export * as ns from "./x.js";`
+    All {
+        from: SymbolU32,
+        alias: Option<SymbolU32>,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub enum Stmt {
     Block(Vec<Stmt>),
     Break,
+    ClassDecl(Box<ClassDefinition>),
     Continue,
+    Export(Box<ExportDeclaration>),
     Expression(Box<Expr>),
     For {
         initializer: Option<Box<Stmt>>,
@@ -23,17 +70,36 @@ pub enum Stmt {
         state: Option<Expr>,
         body: Box<Stmt>,
     },
+    ForOf {
+        is_mutable: bool,
+        identifier: Box<Expr>,
+        iterable: Box<Expr>,
+        body: Box<Stmt>,
+    },
     FunctionDecl {
         identifier: Box<Expr>,
         arguments: Vec<Expr>,
         body: Box<Stmt>,
+        body_kind: BodyKind,
     },
     If {
         condition: Box<Expr>,
         branch_true: Box<Stmt>,
         branch_false: Option<Box<Stmt>>,
     },
+    /// Bindings are created when the module is linked, so evaluating this does nothing.
+    Import {
+        specifier: SymbolU32,
+        bindings: Vec<ImportBinding>,
+    },
     Return(Option<Expr>),
+    Throw(Box<Expr>),
+    Try {
+        block: Box<Stmt>,
+        parameter: Option<Box<Expr>>,
+        handler: Option<Box<Stmt>>,
+        finalizer: Option<Box<Stmt>>,
+    },
     VariableDecl {
         is_mutable: bool,
         identifier: Box<Expr>,
@@ -81,14 +147,44 @@ impl Stmt {
         }
     }
 
-    pub fn new_function(ident: Expr, args: Vec<Expr>, body: Stmt) -> Self {
+    pub fn new_function(ident: Expr, args: Vec<Expr>, body: Stmt, body_kind: BodyKind) -> Self {
         Self::FunctionDecl {
             identifier: Box::new(ident),
             arguments: args,
             body: Box::new(body),
+            body_kind,
+        }
+    }
+
+    pub fn new_import(specifier: SymbolU32, bindings: Vec<ImportBinding>) -> Self {
+        Self::Import {
+            specifier,
+            bindings,
+        }
+    }
+
+    pub fn new_export(declaration: ExportDeclaration) -> Self {
+        Self::Export(Box::new(declaration))
+    }
+
+    /// Function declarations at the top level of a module are created while linking, before
+    /// any module code runs.
+    pub fn is_hoisted_function(&self) -> bool {
+        match self {
+            Stmt::FunctionDecl { .. } => true,
+            Stmt::Export(declaration) => matches!(
+                &**declaration,
+                ExportDeclaration::Declaration(Stmt::FunctionDecl { .. })
+                    | ExportDeclaration::DefaultDeclaration(Stmt::FunctionDecl { .. })
+            ),
+            _ => false,
         }
     }
 
+    pub fn new_class(definition: ClassDefinition) -> Self {
+        Self::ClassDecl(Box::new(definition))
+    }
+
     pub fn new_for(
         init: Option<Stmt>,
         cond: Option<Expr>,
@@ -103,19 +199,79 @@ impl Stmt {
         }
     }
 
+    pub fn new_for_of(is_mutable: bool, ident: Expr, iterable: Expr, body: Stmt) -> Self {
+        Self::ForOf {
+            is_mutable,
+            identifier: Box::new(ident),
+            iterable: Box::new(iterable),
+            body: Box::new(body),
+        }
+    }
+
+    pub fn new_throw(expr: Expr) -> Self {
+        Self::Throw(Box::new(expr))
+    }
+
+    pub fn new_try(
+        block: Stmt,
+        parameter: Option<Expr>,
+        handler: Option<Stmt>,
+        finalizer: Option<Stmt>,
+    ) -> Self {
+        Self::Try {
+            block: Box::new(block),
+            parameter: parameter.map(Box::new),
+            handler: handler.map(Box::new),
+            finalizer: finalizer.map(Box::new),
+        }
+    }
+
+    /// Statement boundaries are where garbage gets collected. Whatever was rooted while the
+    /// statement ran is released once it completes, apart from its completion value.
     pub fn evaluate(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
+        interpreter.collect_garbage_if_due();
+        let mark = interpreter.heap.temporaries_mark();
+        let result = self.evaluate_statement(interpreter);
+        interpreter.heap.release_temporaries(mark);
+        match &result {
+            Ok(value) => interpreter.root_value(value),
+            Err(error) => interpreter.heap.root(error),
+        }
+        result
+    }
+
+    /// Statements that contain other statements push a `ResumeRecord` when a `yield` unwinds
+    /// through them and pick it back up when the generator is resumed.
+    fn evaluate_statement(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
         match self {
             Stmt::Block(stmts) => {
-                interpreter.enter_scope(None);
-                for stmt in stmts {
-                    let res = stmt.evaluate(interpreter)?;
-                    info!("statement result: {res:?}");
-                }
+                let (start, environment) = match interpreter.take_resume_record() {
+                    Some(ResumeRecord::Block { index, environment }) => (index, Some(environment)),
+                    record => {
+                        restore_record(record, interpreter);
+                        (0, None)
+                    }
+                };
+                let environment = interpreter.enter_scope(environment);
+                let result = evaluate_statements(stmts, start, interpreter);
                 interpreter.leave_scope();
-                Ok(JSValue::Undefined)
+                result.map_err(|(index, e)| {
+                    if e.is_yield() {
+                        interpreter.save_resume_record(ResumeRecord::Block { index, environment });
+                    }
+                    e
+                })
             }
             Stmt::Break => Err(JSError::new_break()),
+            Stmt::ClassDecl(definition) => {
+                let class = evaluate_class(definition, interpreter)?;
+                if let Some(name) = definition.identifier {
+                    interpreter.new_variable(name, true, class);
+                }
+                Ok(JSValue::Undefined)
+            }
             Stmt::Continue => Err(JSError::new_continue()),
+            Stmt::Export(declaration) => evaluate_export(declaration, interpreter),
             Self::Expression(expr) => expr.evaluate(interpreter),
             Stmt::For {
                 initializer,
@@ -123,60 +279,55 @@ impl Stmt {
                 state,
                 body,
             } => {
-                interpreter.enter_scope(None);
-                if let Some(stmt) = initializer {
-                    stmt.evaluate(interpreter)?;
-                }
-                let mut abort_count = 0;
-                'forst: loop {
-                    if abort_count > 100 {
-                        panic!("infinite loop")
-                    }
-                    if let Some(expr) = condition {
-                        let value = expr.evaluate(interpreter)?;
-                        if !value.to_boolean() {
-                            break 'forst;
-                        }
-                    }
-                    let body_res = body.evaluate(interpreter);
-                    if let Some(expr) = state {
-                        expr.evaluate(interpreter)?;
+                let (environment, mut phase) = match interpreter.take_resume_record() {
+                    Some(ResumeRecord::Loop {
+                        environment: Some(environment),
+                        phase,
+                    }) => (Some(environment), phase),
+                    record => {
+                        restore_record(record, interpreter);
+                        (None, LoopPhase::Initializer)
                     }
-
-                    if let Err(e) = body_res {
-                        if e.kind == ErrorKind::Break {
-                            break;
-                        } else if e.kind == ErrorKind::Continue {
-                            continue;
-                        }
-                    }
-                    abort_count += 1;
-                }
-
+                };
+                let environment = interpreter.enter_scope(environment);
+                let result =
+                    evaluate_for(initializer, condition, state, body, &mut phase, interpreter);
                 interpreter.leave_scope();
-                Ok(JSValue::Undefined)
+                suspend_on_yield(
+                    result,
+                    ResumeRecord::Loop {
+                        environment: Some(environment),
+                        phase,
+                    },
+                    interpreter,
+                )
             }
+            Stmt::ForOf {
+                is_mutable,
+                identifier,
+                iterable,
+                body,
+            } => evaluate_for_of(*is_mutable, identifier, iterable, body, interpreter),
             Stmt::FunctionDecl {
                 identifier,
                 arguments,
                 body,
+                body_kind,
             } => {
-                let ident = identifier.evaluate(interpreter)?;
-                let ident_id = ident.to_string(interpreter)?;
-                let scope_id = interpreter.enter_scope(None);
-                let parameters = get_function_params(arguments, interpreter)?;
-                for param in &parameters {
-                    interpreter.new_variable(*param, true, JSValue::Undefined);
-                }
-                interpreter.leave_scope();
-                let object_id =
-                    JSObject::new_function_object(body.clone(), parameters, scope_id, interpreter);
-
-                let value = JSValue::Object {
-                    object_id,
-                    kind: ObjectKind::Function,
+                let name = match &**identifier {
+                    Expr::Identifier { string_index } => *string_index,
+                    _ => return Err(JSError::new("Identifier expected")),
                 };
-                interpreter.new_variable(ident_id, false, value);
+                let function = evaluate_function(
+                    None,
+                    Some(name),
+                    arguments,
+                    body,
+                    &FunctionKind::Normal,
+                    *body_kind,
+                    interpreter,
+                )?;
+                interpreter.new_variable(name, true, function);
 
                 Ok(JSValue::Undefined)
             }
@@ -185,18 +336,36 @@ impl Stmt {
                 branch_true,
                 branch_false,
             } => {
-                let evaluated_condition = condition.evaluate(interpreter)?;
-                interpreter.enter_scope(None);
-                if evaluated_condition.to_boolean() {
-                    let b_true = branch_true.evaluate(interpreter)?;
-                    return Ok(b_true);
+                let (environment, taken) = match interpreter.take_resume_record() {
+                    Some(ResumeRecord::Branch(Some((environment, taken)))) => {
+                        (Some(environment), taken)
+                    }
+                    record => {
+                        if !matches!(record, Some(ResumeRecord::Branch(None))) {
+                            restore_record(record, interpreter);
+                        }
+                        let value = condition.evaluate(interpreter);
+                        let value =
+                            suspend_on_yield(value, ResumeRecord::Branch(None), interpreter)?;
+                        (None, value.to_boolean())
+                    }
+                };
+                let environment = interpreter.enter_scope(environment);
+                let result = if taken {
+                    branch_true.evaluate(interpreter)
                 } else if let Some(branch_false) = branch_false {
-                    let b_false = branch_false.evaluate(interpreter)?;
-                    return Ok(b_false);
-                }
+                    branch_false.evaluate(interpreter)
+                } else {
+                    Ok(JSValue::Undefined)
+                };
                 interpreter.leave_scope();
-                Ok(JSValue::Undefined)
+                suspend_on_yield(
+                    result,
+                    ResumeRecord::Branch(Some((environment, taken))),
+                    interpreter,
+                )
             }
+            Stmt::Import { .. } => Ok(JSValue::Undefined),
             Stmt::Return(expr) => {
                 if let Some(expr) = expr {
                     let res = expr.evaluate(interpreter)?;
@@ -208,6 +377,16 @@ impl Stmt {
                 let id = interpreter.add_value(JSValue::new_undefined());
                 return Err(JSError::new_return(id));
             }
+            Stmt::Throw(expr) => {
+                let value = expr.evaluate(interpreter)?;
+                Err(interpreter.throw_value(value))
+            }
+            Stmt::Try {
+                block,
+                parameter,
+                handler,
+                finalizer,
+            } => evaluate_try(block, parameter, handler, finalizer, interpreter),
             Stmt::VariableDecl {
                 is_mutable,
                 identifier,
@@ -251,23 +430,332 @@ impl Stmt {
                 condition: raw_condition,
                 body,
             } => {
-                'whilst: loop {
-                    let condition = raw_condition.evaluate(interpreter)?;
-                    if !condition.to_boolean() {
-                        break 'whilst;
+                let mut phase = match interpreter.take_resume_record() {
+                    Some(ResumeRecord::Loop {
+                        environment: None,
+                        phase,
+                    }) => phase,
+                    record => {
+                        restore_record(record, interpreter);
+                        LoopPhase::Condition
                     }
-                    let body_res = body.evaluate(interpreter);
-                    if let Err(e) = body_res {
-                        if e.kind == ErrorKind::Break {
-                            break;
-                        } else if e.kind == ErrorKind::Continue {
-                            continue;
-                        }
+                };
+                let result = evaluate_while(raw_condition, body, &mut phase, interpreter);
+                suspend_on_yield(
+                    result,
+                    ResumeRecord::Loop {
+                        environment: None,
+                        phase,
+                    },
+                    interpreter,
+                )
+            }
+        }
+    }
+}
+
+/// Puts back a record that belongs to somebody else.
+fn restore_record(record: Option<ResumeRecord>, interpreter: &mut Interpreter) {
+    if let Some(record) = record {
+        interpreter.save_resume_record(record);
+    }
+}
+
+/// Pushes `record` when a `yield` unwinds through the statement.
+fn suspend_on_yield(
+    result: JSResult<JSValue>,
+    record: ResumeRecord,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    if let Err(e) = &result
+        && e.is_yield()
+    {
+        interpreter.save_resume_record(record);
+    }
+    result
+}
+
+/// Runs `stmts` from `start`, reporting which statement an abrupt completion came from.
+/// Runs the declaration part of an export. What gets exported was already worked out when the
+/// module was linked.
+fn evaluate_export(
+    declaration: &ExportDeclaration,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    match declaration {
+        ExportDeclaration::Default(expr) => {
+            let value = evaluate_named(expr, get_or_intern_string(DEFAULT_NAME), interpreter)?;
+            let default_binding = get_or_intern_string(DEFAULT_EXPORT_BINDING);
+            interpreter.new_variable(default_binding, false, value);
+            Ok(JSValue::Undefined)
+        }
+        // the declared name is what gets exported as `default`
+        ExportDeclaration::Declaration(stmt) | ExportDeclaration::DefaultDeclaration(stmt) => {
+            stmt.evaluate(interpreter)
+        }
+        ExportDeclaration::List { .. } | ExportDeclaration::All { .. } => Ok(JSValue::Undefined),
+    }
+}
+
+fn evaluate_statements(
+    stmts: &[Stmt],
+    start: usize,
+    interpreter: &mut Interpreter,
+) -> Result<JSValue, (usize, JSError)> {
+    for (index, stmt) in stmts.iter().enumerate().skip(start) {
+        let res = stmt.evaluate(interpreter).map_err(|e| (index, e))?;
+        info!("statement result: {res:?}");
+    }
+    Ok(JSValue::Undefined)
+}
+
+fn evaluate_while(
+    condition: &Expr,
+    body: &Stmt,
+    phase: &mut LoopPhase,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let mark = interpreter.heap.temporaries_mark();
+    loop {
+        interpreter.heap.release_temporaries(mark);
+        match phase {
+            LoopPhase::Initializer | LoopPhase::Condition | LoopPhase::Update => {
+                if !condition.evaluate(interpreter)?.to_boolean() {
+                    break;
+                }
+                *phase = LoopPhase::Body;
+            }
+            LoopPhase::Body => {
+                if let Err(e) = body.evaluate(interpreter) {
+                    match e.kind {
+                        ErrorKind::Break => break,
+                        ErrorKind::Continue => (),
+                        _ => return Err(e),
                     }
                 }
-                Ok(JSValue::Undefined)
+                *phase = LoopPhase::Condition;
+            }
+        }
+    }
+    Ok(JSValue::Undefined)
+}
+
+/// `phase` tracks where the loop is so a suspended generator resumes at the same step.
+fn evaluate_for(
+    initializer: &Option<Box<Stmt>>,
+    condition: &Option<Expr>,
+    state: &Option<Expr>,
+    body: &Stmt,
+    phase: &mut LoopPhase,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let mark = interpreter.heap.temporaries_mark();
+    loop {
+        interpreter.heap.release_temporaries(mark);
+        match phase {
+            LoopPhase::Initializer => {
+                if let Some(stmt) = initializer {
+                    stmt.evaluate(interpreter)?;
+                }
+                *phase = LoopPhase::Condition;
+            }
+            LoopPhase::Condition => {
+                if let Some(expr) = condition {
+                    let value = expr.evaluate(interpreter)?;
+                    if !value.to_boolean() {
+                        break;
+                    }
+                }
+                *phase = LoopPhase::Body;
+            }
+            LoopPhase::Body => {
+                if let Err(e) = body.evaluate(interpreter) {
+                    match e.kind {
+                        ErrorKind::Break => break,
+                        ErrorKind::Continue => (),
+                        _ => return Err(e),
+                    }
+                }
+                *phase = LoopPhase::Update;
+            }
+            LoopPhase::Update => {
+                if let Some(expr) = state {
+                    expr.evaluate(interpreter)?;
+                }
+                *phase = LoopPhase::Condition;
+            }
+        }
+    }
+    Ok(JSValue::Undefined)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-runtime-semantics-forinofloopevaluation
+/// Every iteration gets a fresh scope for the loop variable so closures capture that
+/// iteration's value. Leaving the loop early closes the iterator.
+fn evaluate_for_of(
+    is_mutable: bool,
+    identifier: &Expr,
+    iterable: &Expr,
+    body: &Stmt,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let Expr::Identifier { string_index } = identifier else {
+        return Err(JSError::new("Identifier expected"));
+    };
+    let (mut iterator, mut resumed) = match interpreter.take_resume_record() {
+        Some(ResumeRecord::ForOf(Some((iterator, environment)))) => (iterator, Some(environment)),
+        record => {
+            if !matches!(record, Some(ResumeRecord::ForOf(None))) {
+                restore_record(record, interpreter);
+            }
+            let value = iterable.evaluate(interpreter);
+            let value = suspend_on_yield(value, ResumeRecord::ForOf(None), interpreter)?;
+            (get_iterator(interpreter, &value)?, None)
+        }
+    };
+    let mark = interpreter.heap.temporaries_mark();
+    loop {
+        interpreter.heap.release_temporaries(mark);
+        let environment = match resumed.take() {
+            Some(environment) => environment,
+            None => {
+                let Some(value) = iterator_step(interpreter, &mut iterator)? else {
+                    break;
+                };
+                let environment = interpreter.enter_scope(None);
+                interpreter.new_variable(*string_index, is_mutable, value);
+                interpreter.leave_scope();
+                environment
+            }
+        };
+        interpreter.enter_scope(Some(environment));
+        let result = body.evaluate(interpreter);
+        interpreter.leave_scope();
+        let Err(e) = result else {
+            continue;
+        };
+        match e.kind {
+            ErrorKind::Continue => (),
+            ErrorKind::Yield(_) => {
+                let record = ResumeRecord::ForOf(Some((iterator, environment)));
+                interpreter.save_resume_record(record);
+                return Err(e);
+            }
+            ErrorKind::Break => {
+                iterator_close(interpreter, &iterator)?;
+                break;
+            }
+            _ => {
+                return close_after_abrupt(&iterator, e, interpreter);
+            }
+        }
+    }
+    Ok(JSValue::Undefined)
+}
+
+/// Closes the iterator for a `return` or exception leaving the loop. A throw keeps precedence
+/// over anything the iterator's `return` method throws.
+fn close_after_abrupt(
+    iterator: &IteratorRecord,
+    error: JSError,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let closed = iterator_close(interpreter, iterator);
+    if error.is_exception() {
+        return Err(error);
+    }
+    closed?;
+    Err(error)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-try-statement-runtime-semantics-evaluation
+fn evaluate_try(
+    block: &Stmt,
+    parameter: &Option<Box<Expr>>,
+    handler: &Option<Box<Stmt>>,
+    finalizer: &Option<Box<Stmt>>,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSValue> {
+    let phase = match interpreter.take_resume_record() {
+        Some(ResumeRecord::Try(phase)) => phase,
+        record => {
+            restore_record(record, interpreter);
+            TryPhase::Block
+        }
+    };
+    let completion = match phase {
+        TryPhase::Block => {
+            let result = block.evaluate(interpreter);
+            match result {
+                Err(e) if e.is_yield() => {
+                    interpreter.save_resume_record(ResumeRecord::Try(TryPhase::Block));
+                    return Err(e);
+                }
+                Err(e) if e.is_exception() && handler.is_some() => {
+                    let environment = interpreter.enter_scope(None);
+                    let bound = bind_catch_parameter(parameter, &e, interpreter);
+                    interpreter.leave_scope();
+                    match bound {
+                        Ok(()) => evaluate_catch(handler, environment, interpreter)?,
+                        Err(e) => Err(e),
+                    }
+                }
+                result => result,
             }
         }
+        TryPhase::Catch { environment } => evaluate_catch(handler, environment, interpreter)?,
+        TryPhase::Finally { completion } => completion,
+    };
+    let Some(finalizer) = finalizer else {
+        return completion;
+    };
+    match finalizer.evaluate(interpreter) {
+        Err(e) if e.is_yield() => {
+            let record = ResumeRecord::Try(TryPhase::Finally { completion });
+            interpreter.save_resume_record(record);
+            Err(e)
+        }
+        // an abrupt finally block replaces whatever the try or catch block did
+        Err(e) => Err(e),
+        Ok(_) => completion,
+    }
+}
+
+fn bind_catch_parameter(
+    parameter: &Option<Box<Expr>>,
+    error: &JSError,
+    interpreter: &mut Interpreter,
+) -> JSResult<()> {
+    if let Some(parameter) = parameter {
+        let Expr::Identifier { string_index } = &**parameter else {
+            return Err(JSError::new("Identifier expected"));
+        };
+        let value = interpreter.error_value(error)?;
+        interpreter.new_variable(*string_index, true, value);
+    }
+    Ok(())
+}
+
+/// Runs the catch block in the scope holding its parameter. The outer `Err` is a suspension
+/// the caller has to pass on untouched.
+fn evaluate_catch(
+    handler: &Option<Box<Stmt>>,
+    environment: usize,
+    interpreter: &mut Interpreter,
+) -> JSResult<JSResult<JSValue>> {
+    let Some(handler) = handler else {
+        return Ok(Ok(JSValue::Undefined));
+    };
+    interpreter.enter_scope(Some(environment));
+    let result = handler.evaluate(interpreter);
+    interpreter.leave_scope();
+    match result {
+        Err(e) if e.is_yield() => {
+            let record = ResumeRecord::Try(TryPhase::Catch { environment });
+            interpreter.save_resume_record(record);
+            Err(e)
+        }
+        result => Ok(result),
     }
 }
 
@@ -295,10 +783,60 @@ impl Stmt {
                 writeln!(f, "{}Break", indent_str)
             }
 
+            Stmt::ClassDecl(definition) => {
+                let name = definition
+                    .identifier
+                    .and_then(|id| get_string_from_pool(&id))
+                    .unwrap_or_default();
+                writeln!(f, "{}ClassDecl {}", indent_str, name)
+            }
+
             Stmt::Continue => {
                 writeln!(f, "{}Continue", indent_str)
             }
 
+            Stmt::Export(declaration) => match &**declaration {
+                ExportDeclaration::Declaration(stmt) => {
+                    writeln!(f, "{}Export", indent_str)?;
+                    stmt.fmt_indented(f, indent + 1)
+                }
+                ExportDeclaration::Default(expr) => {
+                    writeln!(f, "{}ExportDefault({})", indent_str, expr)
+                }
+                ExportDeclaration::DefaultDeclaration(stmt) => {
+                    writeln!(f, "{}ExportDefault", indent_str)?;
+                    stmt.fmt_indented(f, indent + 1)
+                }
+                ExportDeclaration::List { specifiers, from } => {
+                    let names: Vec<String> = specifiers
+                        .iter()
+                        .map(|specifier| {
+                            let local = get_string_from_pool(&specifier.local).unwrap_or_default();
+                            let exported =
+                                get_string_from_pool(&specifier.exported).unwrap_or_default();
+                            format!("{local} as {exported}")
+                        })
+                        .collect();
+                    write!(f, "{}Export {{ {} }}", indent_str, names.join(", "))?;
+                    match from {
+                        Some(from) => {
+                            let from = get_string_from_pool(from).unwrap_or_default();
+                            writeln!(f, " from {:?}", from)
+                        }
+                        None => writeln!(f),
+                    }
+                }
+                ExportDeclaration::All { from, alias } => {
+                    let from = get_string_from_pool(from).unwrap_or_default();
+                    match alias.and_then(|alias| get_string_from_pool(&alias)) {
+                        Some(alias) => {
+                            writeln!(f, "{}Export * as {} from {:?}", indent_str, alias, from)
+                        }
+                        None => writeln!(f, "{}Export * from {:?}", indent_str, from),
+                    }
+                }
+            },
+
             Stmt::Expression(expr) => {
                 writeln!(f, "{}Expression({})", indent_str, expr)
             }
@@ -337,12 +875,28 @@ impl Stmt {
                 writeln!(f, "{}}}", indent_str)
             }
 
+            Stmt::ForOf {
+                is_mutable,
+                identifier,
+                iterable,
+                body,
+            } => {
+                writeln!(f, "{}ForOf {{", indent_str)?;
+                writeln!(f, "{}  is_mutable: {}", indent_str, is_mutable)?;
+                writeln!(f, "{}  identifier: {}", indent_str, identifier)?;
+                writeln!(f, "{}  iterable: {}", indent_str, iterable)?;
+                body.fmt_indented(f, indent + 2)?;
+                writeln!(f, "{}}}", indent_str)
+            }
+
             Stmt::FunctionDecl {
                 identifier,
                 arguments,
                 body,
+                body_kind,
             } => {
                 writeln!(f, "{}FunctionDecl {{", indent_str)?;
+                writeln!(f, "{}  body_kind: {:?}", indent_str, body_kind)?;
                 writeln!(f, "{}  identifier: {}", indent_str, identifier)?;
                 writeln!(f, "{}  arguments: [", indent_str)?;
                 for arg in arguments {
@@ -376,6 +930,33 @@ impl Stmt {
                 writeln!(f, "{}}}", indent_str)
             }
 
+            Stmt::Import {
+                specifier,
+                bindings,
+            } => {
+                let names: Vec<String> = bindings
+                    .iter()
+                    .map(|binding| {
+                        let local = get_string_from_pool(&binding.local).unwrap_or_default();
+                        match binding.imported {
+                            ImportName::Name(name) => {
+                                let name = get_string_from_pool(&name).unwrap_or_default();
+                                format!("{name} as {local}")
+                            }
+                            ImportName::Namespace => format!("* as {local}"),
+                        }
+                    })
+                    .collect();
+                let specifier = get_string_from_pool(specifier).unwrap_or_default();
+                writeln!(
+                    f,
+                    "{}Import {{ {} }} from {:?}",
+                    indent_str,
+                    names.join(", "),
+                    specifier
+                )
+            }
+
             Stmt::Return(expr) => {
                 write!(f, "{}Return(", indent_str)?;
                 match expr {
@@ -385,6 +966,32 @@ impl Stmt {
                 writeln!(f, ")")
             }
 
+            Stmt::Throw(expr) => {
+                writeln!(f, "{}Throw({})", indent_str, expr)
+            }
+
+            Stmt::Try {
+                block,
+                parameter,
+                handler,
+                finalizer,
+            } => {
+                writeln!(f, "{}Try {{", indent_str)?;
+                block.fmt_indented(f, indent + 2)?;
+                if let Some(handler) = handler {
+                    match parameter {
+                        Some(parameter) => writeln!(f, "{}  catch ({}):", indent_str, parameter)?,
+                        None => writeln!(f, "{}  catch:", indent_str)?,
+                    }
+                    handler.fmt_indented(f, indent + 2)?;
+                }
+                if let Some(finalizer) = finalizer {
+                    writeln!(f, "{}  finally:", indent_str)?;
+                    finalizer.fmt_indented(f, indent + 2)?;
+                }
+                writeln!(f, "{}}}", indent_str)
+            }
+
             Stmt::VariableDecl {
                 is_mutable,
                 identifier,
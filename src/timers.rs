@@ -0,0 +1,163 @@
+//! The macrotask side of the event loop: timers and immediates, ordered by when they come due.
+//! Time comes from a `Clock`, either the real one or a virtual one the host moves forward.
+
+use std::time::Instant;
+
+use crate::{
+    heap::{Trace, Tracer},
+    values::JSValue,
+};
+
+/// Where the event loop reads the current time from, in milliseconds.
+#[derive(Clone, Debug)]
+pub enum Clock {
+    Real {
+        start: Instant,
+    },
+    /// Only moves when the host advances it, which makes timers deterministic.
+    Virtual {
+        now: f64,
+    },
+}
+
+impl Clock {
+    pub fn new_real() -> Self {
+        Clock::Real {
+            start: Instant::now(),
+        }
+    }
+
+    pub fn new_virtual() -> Self {
+        Clock::Virtual { now: 0.0 }
+    }
+
+    pub fn now(&self) -> f64 {
+        match self {
+            Clock::Real { start } => start.elapsed().as_secs_f64() * 1000.0,
+            Clock::Virtual { now } => *now,
+        }
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        matches!(self, Clock::Virtual { .. })
+    }
+
+    /// Moves a virtual clock forward to `time`. Real clocks can't be moved.
+    pub fn advance_to(&mut self, time: f64) {
+        if let Clock::Virtual { now } = self {
+            *now = now.max(time);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Timer {
+    pub id: u32,
+    pub callback: JSValue,
+    pub arguments: Vec<JSValue>,
+    pub due: f64,
+    /// Set for `setInterval`, which reschedules itself every time it runs.
+    pub interval: Option<f64>,
+    /// Breaks ties between timers due at the same time, first scheduled runs first.
+    sequence: u64,
+}
+
+impl Trace for Timer {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.callback.trace(tracer);
+        self.arguments.trace(tracer);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TimerQueue {
+    clock: Clock,
+    timers: Vec<Timer>,
+    next_id: u32,
+    next_sequence: u64,
+}
+
+impl TimerQueue {
+    pub fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            timers: vec![],
+            next_id: 1,
+            next_sequence: 0,
+        }
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    /// Queues `callback` to run `delay` milliseconds from now and returns the timer's id.
+    pub fn schedule(
+        &mut self,
+        callback: JSValue,
+        arguments: Vec<JSValue>,
+        delay: f64,
+        interval: Option<f64>,
+    ) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let due = self.clock.now() + delay;
+        self.insert(Timer {
+            id,
+            callback,
+            arguments,
+            due,
+            interval,
+            sequence: 0,
+        });
+        id
+    }
+
+    fn insert(&mut self, mut timer: Timer) {
+        timer.sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.timers.push(timer);
+    }
+
+    pub fn clear(&mut self, id: u32) {
+        self.timers.retain(|timer| timer.id != id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// When the earliest pending timer comes due.
+    pub fn next_due(&self) -> Option<f64> {
+        self.timers.iter().map(|timer| timer.due).reduce(f64::min)
+    }
+
+    /// Removes and returns the earliest timer that is due by `time`. An interval is put back
+    /// for its next run straight away, so clearing it from its own callback still works.
+    pub fn pop_due(&mut self, time: f64) -> Option<Timer> {
+        let index = self
+            .timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.due <= time)
+            .min_by(|(_, a), (_, b)| a.due.total_cmp(&b.due).then(a.sequence.cmp(&b.sequence)))
+            .map(|(index, _)| index)?;
+        let timer = self.timers.remove(index);
+        if let Some(interval) = timer.interval {
+            let mut next = timer.clone();
+            next.due = timer.due + interval;
+            self.insert(next);
+        }
+        Some(timer)
+    }
+}
+
+impl Trace for TimerQueue {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.timers.trace(tracer);
+    }
+}
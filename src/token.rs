@@ -1,7 +1,4 @@
-use std::{
-    collections::HashMap,
-    sync::{Mutex, OnceLock},
-};
+use std::{collections::HashMap, sync::OnceLock};
 
 use crate::span::Span;
 
@@ -58,10 +55,12 @@ pub enum Kind {
     True,
     False,
     Null,
-    Undefined,
     Number,
+    BigInt,
     Identifier,
     String,
+    /// `/pattern/flags`
+    RegExp,
 
     // operators
     Plus,
@@ -96,6 +95,7 @@ pub enum Kind {
     GreaterThanOrEquals,
     Percent,
     Dot,
+    Ellipsis,
     BitwiseOr,
     LogicalOr,
     BitwiseOrEquals, // |=
@@ -104,6 +104,15 @@ pub enum Kind {
     LogicalAnd,
     BitwiseAndEquals, // &=
     LogicalAndEquals, // &&=
+    BitwiseXor,
+    BitwiseXorEquals, // ^=
+    BitwiseNot,
+    LeftShift,
+    LeftShiftEquals, // <<=
+    SignedRightShift,
+    SignedRightShiftEquals, // >>=
+    UnsignedRightShift,
+    UnsignedRightShiftEquals, // >>>=
 
     // special
     Eof,
@@ -120,11 +129,24 @@ impl Kind {
             | Kind::LessThanOrEquals
             | Kind::GreaterThan
             | Kind::GreaterThanOrEquals
-            | Kind::Percent => true,
+            | Kind::Percent
+            | Kind::LeftShift
+            | Kind::SignedRightShift
+            | Kind::UnsignedRightShift
+            | Kind::BitwiseAnd
+            | Kind::BitwiseXor
+            | Kind::BitwiseOr => true,
             _ => false,
         }
     }
 
+    pub fn is_relational_operator(&self) -> bool {
+        matches!(
+            self,
+            Kind::LessThan | Kind::LessThanOrEquals | Kind::GreaterThan | Kind::GreaterThanOrEquals
+        )
+    }
+
     pub fn is_equality_operator(&self) -> bool {
         match self {
             Kind::EqualEqual | Kind::EqualEqualEqual | Kind::NotEqual | Kind::NotEqualEqual => true,
@@ -133,9 +155,13 @@ impl Kind {
     }
 }
 
-static KEYWORDS: OnceLock<Mutex<HashMap<&'static str, Kind>>> = OnceLock::new();
+static KEYWORDS: OnceLock<HashMap<&'static str, Kind>> = OnceLock::new();
+
+fn get_keywords() -> &'static HashMap<&'static str, Kind> {
+    KEYWORDS.get_or_init(create_keywords)
+}
 
-fn get_keywords() -> &'static Mutex<HashMap<&'static str, Kind>> {
+fn create_keywords() -> HashMap<&'static str, Kind> {
     let mut m = HashMap::new();
 
     // Control flow
@@ -199,9 +225,7 @@ fn get_keywords() -> &'static Mutex<HashMap<&'static str, Kind>> {
     m.insert("true", Kind::True);
     m.insert("false", Kind::False);
     m.insert("null", Kind::Null);
-    m.insert("undefined", Kind::Undefined);
-    m.insert("infinity", Kind::Number);
-    KEYWORDS.get_or_init(|| Mutex::new(m))
+    m
 }
 
 #[derive(Clone, Debug)]
@@ -256,6 +280,7 @@ impl Token {
             | Kind::Typeof
             | Kind::Plus
             | Kind::PlusPlus
+            | Kind::BitwiseNot
             | Kind::Void => true,
             _ => false,
         }
@@ -270,6 +295,6 @@ impl Token {
 }
 
 pub fn get_keyword(word: &str) -> Option<Kind> {
-    let map = get_keywords().lock().unwrap();
+    let map = get_keywords();
     map.get(word).map(|w| w.clone())
 }
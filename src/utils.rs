@@ -4,7 +4,7 @@ use log::trace;
 use regex::Regex;
 use string_interner::symbol::SymbolU32;
 
-use crate::{Interpreter, errors::JSError, expr::Expr, values::JSResult};
+use crate::{errors::JSError, expr::Expr, values::JSResult};
 
 static IDENTIFIER_REGEX: OnceLock<Regex> = OnceLock::new();
 
@@ -21,27 +21,12 @@ pub fn check_identifier(source: &str) -> JSResult<()> {
     Err(JSError::new("Identifier expected"))
 }
 
-pub fn get_function_params(
-    args: &Vec<Expr>,
-    interpreter: &mut Interpreter,
-) -> JSResult<Vec<SymbolU32>> {
-    let parameters = args
-        .iter()
-        .map(|arg| {
-            let evaluated = arg.evaluate(interpreter)?;
-            evaluated.to_string(interpreter)
+/// Parameters are parsed as identifier expressions, pull the names back out of them.
+pub fn get_function_params(args: &[Expr]) -> JSResult<Vec<SymbolU32>> {
+    args.iter()
+        .map(|arg| match arg {
+            Expr::Identifier { string_index } => Ok(*string_index),
+            _ => Err(JSError::new("Identifier expected")),
         })
-        .collect::<JSResult<Vec<SymbolU32>>>()?;
-    Ok(parameters)
-}
-
-pub fn remove_quotes_from_string(string: &str) -> String {
-    let single_quote = '\'';
-    let double_quote = '"';
-    string.chars().fold(String::new(), |mut acc, c| {
-        if c != single_quote && c != double_quote {
-            acc.push(c)
-        }
-        acc
-    })
+        .collect()
 }
@@ -0,0 +1,205 @@
+//! The BigInt numeric type operations, the arbitrary-precision counterparts of `number.rs`.
+
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Num, One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    errors::JSError,
+    values::{JSResult, number},
+};
+
+/// The most bits a BigInt may have, V8's limit.
+pub const MAX_BITS: u64 = 1 << 30;
+
+fn size_exceeded() -> JSError {
+    JSError::new_range_error("Maximum BigInt size exceeded")
+}
+
+pub fn unary_minus(x: &BigInt) -> BigInt {
+    -x
+}
+
+pub fn bitwise_not(x: &BigInt) -> BigInt {
+    !x
+}
+
+pub fn multiply(x: &BigInt, y: &BigInt) -> BigInt {
+    x * y
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-numeric-types-bigint-divide
+pub fn divide(x: &BigInt, y: &BigInt) -> JSResult<BigInt> {
+    if y.is_zero() {
+        return Err(JSError::new_range_error("Division by zero"));
+    }
+    // rounds towards zero, like Rust's integer division
+    Ok(x / y)
+}
+
+pub fn remainder(x: &BigInt, y: &BigInt) -> JSResult<BigInt> {
+    if y.is_zero() {
+        return Err(JSError::new_range_error("Division by zero"));
+    }
+    // the sign follows the dividend
+    Ok(x % y)
+}
+
+pub fn add(x: &BigInt, y: &BigInt) -> BigInt {
+    x + y
+}
+
+pub fn subtract(x: &BigInt, y: &BigInt) -> BigInt {
+    x - y
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-numeric-types-bigint-leftShift
+pub fn left_shift(x: &BigInt, y: &BigInt) -> JSResult<BigInt> {
+    if y.is_negative() {
+        return signed_right_shift(x, &-y);
+    }
+    if x.is_zero() {
+        return Ok(BigInt::zero());
+    }
+    match y.to_u64() {
+        Some(count) if x.bits() + count <= MAX_BITS => Ok(x << count),
+        _ => Err(size_exceeded()),
+    }
+}
+
+pub fn signed_right_shift(x: &BigInt, y: &BigInt) -> JSResult<BigInt> {
+    if y.is_negative() {
+        return left_shift(x, &-y);
+    }
+    // shifting every bit out leaves the sign, rounding towards negative infinity
+    match y.to_u64() {
+        Some(count) if count < x.bits() => Ok(x >> count),
+        _ if x.is_negative() => Ok(-BigInt::one()),
+        _ => Ok(BigInt::zero()),
+    }
+}
+
+pub fn unsigned_right_shift(_x: &BigInt, _y: &BigInt) -> JSResult<BigInt> {
+    Err(JSError::new_type_error(
+        "BigInts have no unsigned right shift, use >> instead",
+    ))
+}
+
+// the bitwise operators act as if both operands were infinitely sign-extended two's complement
+pub fn bitwise_and(x: &BigInt, y: &BigInt) -> BigInt {
+    x & y
+}
+
+pub fn bitwise_xor(x: &BigInt, y: &BigInt) -> BigInt {
+    x ^ y
+}
+
+pub fn bitwise_or(x: &BigInt, y: &BigInt) -> BigInt {
+    x | y
+}
+
+pub fn to_string(x: &BigInt, radix: u8) -> String {
+    x.to_str_radix(radix as u32)
+}
+
+/// Compares a BigInt with a Number exactly, without rounding either one. NaN compares to
+/// nothing.
+pub fn compare_to_number(x: &BigInt, y: f64) -> Option<Ordering> {
+    if y.is_nan() {
+        return None;
+    }
+    if y.is_infinite() {
+        return Some(match y > 0.0 {
+            true => Ordering::Less,
+            false => Ordering::Greater,
+        });
+    }
+    let floor = y.floor();
+    let integer = BigInt::from_f64(floor)?;
+    match x.cmp(&integer) {
+        // x is at most floor(y), so it's less than y unless y is an integer
+        Ordering::Equal if floor != y => Some(Ordering::Less),
+        ordering => Some(ordering),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-numbertobigint
+pub fn number_to_big_int(x: f64) -> JSResult<BigInt> {
+    match x.is_finite() && x.trunc() == x {
+        true => Ok(BigInt::from_f64(x).unwrap_or_default()),
+        false => Err(JSError::new_range_error(&format!(
+            "The number {} cannot be converted to a BigInt because it is not an integer",
+            number::to_string(x, 10)
+        ))),
+    }
+}
+
+/// The Number closest to `x`, ties going to even.
+pub fn to_number(x: &BigInt) -> f64 {
+    x.to_f64().unwrap_or(f64::NAN)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-stringtobigint
+/// Parses a StringIntegerLiteral: decimal digits with an optional sign, or hex, octal or binary
+/// digits behind their prefix, surrounded by whitespace. An empty string is 0.
+pub fn string_to_big_int(string: &str) -> Option<BigInt> {
+    let string = string.trim_matches(number::is_js_whitespace);
+    if string.is_empty() {
+        return Some(BigInt::zero());
+    }
+    let prefixed = [
+        ("0x", 16),
+        ("0X", 16),
+        ("0o", 8),
+        ("0O", 8),
+        ("0b", 2),
+        ("0B", 2),
+    ];
+    for (prefix, radix) in prefixed {
+        if let Some(digits) = string.strip_prefix(prefix) {
+            return parse_digits(digits, radix);
+        }
+    }
+    let (negative, digits) = match string.as_bytes()[0] {
+        b'-' => (true, &string[1..]),
+        b'+' => (false, &string[1..]),
+        _ => (false, string),
+    };
+    let value = parse_digits(digits, 10)?;
+    Some(if negative { -value } else { value })
+}
+
+fn parse_digits(digits: &str, radix: u32) -> Option<BigInt> {
+    // from_str_radix would also take a sign and underscores
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    BigInt::from_str_radix(digits, radix).ok()
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-bigint.asintn
+/// `x` modulo 2^bits, as a signed two's complement integer when `signed` is set.
+pub fn as_n_bits(x: &BigInt, bits: u64, signed: bool) -> JSResult<BigInt> {
+    if bits == 0 {
+        return Ok(BigInt::zero());
+    }
+    // values that already fit come back as they are, however large bits is
+    let fits = match signed {
+        true => x.bits() < bits,
+        false => !x.is_negative() && x.bits() <= bits,
+    };
+    if fits {
+        return Ok(x.clone());
+    }
+    if bits > MAX_BITS {
+        return Err(size_exceeded());
+    }
+    let modulus = BigInt::one() << bits;
+    let result = x.mod_floor(&modulus);
+    if signed && result.bits() == bits {
+        return Ok(result - modulus);
+    }
+    Ok(result)
+}
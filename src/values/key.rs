@@ -0,0 +1,174 @@
+//! Property keys: a string, interned like every other string, or a symbol.
+
+use std::hash::{Hash, Hasher};
+
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    global::{get_or_intern_string, get_string_from_pool},
+    values::JSValue,
+};
+
+// https://262.ecma-international.org/15.0/index.html#sec-object-type
+#[derive(Clone, Copy, Debug)]
+pub enum PropertyKey {
+    String(SymbolU32),
+    /// Symbols are told apart by id alone, the description only comes along for display.
+    Symbol {
+        id: usize,
+        description: Option<SymbolU32>,
+    },
+}
+
+impl PropertyKey {
+    pub fn from_name(name: &str) -> Self {
+        Self::String(get_or_intern_string(name))
+    }
+
+    pub fn well_known(symbol: WellKnownSymbol) -> Self {
+        Self::Symbol {
+            id: symbol as usize,
+            description: Some(get_or_intern_string(&symbol.description())),
+        }
+    }
+
+    pub fn is_symbol(&self) -> bool {
+        matches!(self, Self::Symbol { .. })
+    }
+
+    /// The string, for keys that are strings.
+    pub fn as_string(&self) -> Option<SymbolU32> {
+        match self {
+            Self::String(string) => Some(*string),
+            Self::Symbol { .. } => None,
+        }
+    }
+
+    /// The key as a value again, e.g. for `Object.getOwnPropertySymbols`.
+    pub fn to_value(self) -> JSValue {
+        match self {
+            Self::String(string) => JSValue::new_string(&string),
+            Self::Symbol { id, description } => JSValue::Symbol { id, description },
+        }
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-setfunctionname
+    /// The name a function defined under this key gets: the string itself, or the symbol's
+    /// description in brackets.
+    pub fn function_name(&self) -> String {
+        match self {
+            Self::String(string) => get_string_from_pool(string).unwrap_or_default(),
+            Self::Symbol { description, .. } => match description {
+                Some(description) => {
+                    format!(
+                        "[{}]",
+                        get_string_from_pool(description).unwrap_or_default()
+                    )
+                }
+                None => String::new(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for PropertyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(string) => {
+                write!(f, "{}", get_string_from_pool(string).unwrap_or_default())
+            }
+            Self::Symbol { description, .. } => {
+                let description = description
+                    .and_then(|description| get_string_from_pool(&description))
+                    .unwrap_or_default();
+                write!(f, "Symbol({description})")
+            }
+        }
+    }
+}
+
+impl PartialEq for PropertyKey {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::String(left), Self::String(right)) => left == right,
+            (Self::Symbol { id: left, .. }, Self::Symbol { id: right, .. }) => left == right,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for PropertyKey {}
+
+impl Hash for PropertyKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Self::String(string) => (0u8, *string).hash(state),
+            Self::Symbol { id, .. } => (1u8, *id).hash(state),
+        }
+    }
+}
+
+impl From<SymbolU32> for PropertyKey {
+    fn from(string: SymbolU32) -> Self {
+        Self::String(string)
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-well-known-symbols
+/// The symbols the spec's algorithms look up. Their ids come first, before any symbol a script
+/// creates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WellKnownSymbol {
+    AsyncIterator,
+    HasInstance,
+    Iterator,
+    Match,
+    MatchAll,
+    Replace,
+    Search,
+    Species,
+    Split,
+    ToPrimitive,
+    ToStringTag,
+}
+
+impl WellKnownSymbol {
+    pub const ALL: [WellKnownSymbol; 11] = [
+        Self::AsyncIterator,
+        Self::HasInstance,
+        Self::Iterator,
+        Self::Match,
+        Self::MatchAll,
+        Self::Replace,
+        Self::Search,
+        Self::Species,
+        Self::Split,
+        Self::ToPrimitive,
+        Self::ToStringTag,
+    ];
+
+    /// The name of the `Symbol` property that holds it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AsyncIterator => "asyncIterator",
+            Self::HasInstance => "hasInstance",
+            Self::Iterator => "iterator",
+            Self::Match => "match",
+            Self::MatchAll => "matchAll",
+            Self::Replace => "replace",
+            Self::Search => "search",
+            Self::Species => "species",
+            Self::Split => "split",
+            Self::ToPrimitive => "toPrimitive",
+            Self::ToStringTag => "toStringTag",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        format!("Symbol.{}", self.name())
+    }
+
+    pub fn to_value(self) -> JSValue {
+        PropertyKey::well_known(self).to_value()
+    }
+}
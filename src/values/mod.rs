@@ -1,10 +1,18 @@
+pub mod bigint;
+mod key;
 mod number;
 mod objects;
 mod string;
 mod value;
 
+pub use key::{PropertyKey, WellKnownSymbol};
 pub use number::*;
-pub use objects::{JSObject, get_object_property, get_object_property_mut};
+pub use objects::{
+    BodyKind, FunctionKind, JSObject, NativeClosure, NativeFunction, ObjectData, ObjectId,
+    ObjectProperty, ObjectPropertyBuilder, Property, PropertyDescriptor, ProxyObject, array_index,
+    define_own_property, get_property_value, internal, is_same_value,
+    link_constructor_and_prototype, ordinary_to_primitive, set_property_value,
+};
 pub use value::*;
 
 use crate::errors::JSError;
@@ -17,7 +25,7 @@ pub enum PreferredType {
 
 pub type JSResult<T> = Result<T, JSError>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ObjectKind {
     Object,
     Function,
@@ -214,20 +214,14 @@ pub fn divide(x: f64, y: f64) -> f64 {
         return -x;
     }
 
+    // -0.0 == 0.0, so the sign of a zero divisor has to be read from its sign bit
     if y == 0.0 {
-        if x > 0.0 {
+        if (x > 0.0) == y.is_sign_positive() {
             return f64::INFINITY;
         }
         return f64::NEG_INFINITY;
     }
 
-    if y == -0.0 {
-        if x > 0.0 {
-            return f64::NEG_INFINITY;
-        }
-        return f64::INFINITY;
-    }
-
     x / y
 }
 
@@ -375,11 +369,8 @@ pub fn same_value(x: f64, y: f64) -> bool {
     if x.is_nan() && y.is_nan() {
         return true;
     }
-    if x == 0.0 && y == -0.0 {
-        return false;
-    }
-    if x == -0.0 && y == 0.0 {
-        return false;
+    if x == 0.0 && y == 0.0 {
+        return x.is_sign_negative() == y.is_sign_negative();
     }
     if x == y {
         return true;
@@ -439,6 +430,309 @@ pub fn bitwise_or(x: f64, y: f64, interpreter: &mut Interpreter) -> i32 {
     return bitwise_op(BitwiseOp::Or, x, y, interpreter);
 }
 
+const RADIX_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// https://262.ecma-international.org/15.0/index.html#sec-numeric-types-number-tostring
 pub fn to_string(x: f64, radix: u8) -> String {
-    x.to_string()
+    if x.is_nan() {
+        return "NaN".to_string();
+    }
+    if x == 0.0 {
+        return "0".to_string();
+    }
+    if x < 0.0 {
+        return format!("-{}", to_string(-x, radix));
+    }
+    if x.is_infinite() {
+        return "Infinity".to_string();
+    }
+    if radix != 10 {
+        return to_radix_string(x, radix as u32);
+    }
+    // integers that fit in 53 bits print as they are, and they are by far the most common
+    if x < 2f64.powi(53) && x.fract() == 0.0 {
+        return (x as u64).to_string();
+    }
+    let (digits, exponent) = shortest_digits(x);
+    let k = digits.len() as i32;
+    // the decimal point goes after the first n digits
+    let n = exponent + 1;
+    if k <= n && n <= 21 {
+        return format!("{digits}{}", "0".repeat((n - k) as usize));
+    }
+    if 0 < n && n <= 21 {
+        let (integer, fraction) = digits.split_at(n as usize);
+        return format!("{integer}.{fraction}");
+    }
+    if -6 < n && n <= 0 {
+        return format!("0.{}{digits}", "0".repeat(-n as usize));
+    }
+    let sign = if n - 1 < 0 { '-' } else { '+' };
+    let (first, rest) = digits.split_at(1);
+    match rest.is_empty() {
+        true => format!("{first}e{sign}{}", (n - 1).abs()),
+        false => format!("{first}.{rest}e{sign}{}", (n - 1).abs()),
+    }
+}
+
+/// The fewest decimal digits that still read back as `x`, with the exponent of the first one.
+/// `x` has to be finite and positive.
+pub fn shortest_digits(x: f64) -> (String, i32) {
+    // Rust's `{:e}` is already the shortest round trip, e.g. "1.2345e3"
+    let formatted = format!("{x:e}");
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let digits = mantissa.replace('.', "");
+    let exponent = exponent.parse().unwrap_or_default();
+    match even_tie_digits(x, digits.len()) {
+        Some(even) => even,
+        None => (digits, exponent),
+    }
+}
+
+/// When `x` lies exactly halfway between two decimals of `count` digits that both read back as
+/// `x`, the even one, which is the one the spec picks and Rust doesn't necessarily.
+fn even_tie_digits(x: f64, count: usize) -> Option<(String, i32)> {
+    // a tie needs an exact expansion of count + 1 digits, which long fractions can't be
+    if fraction_digits(x) as f64 + x.log10() > 20.0 {
+        return None;
+    }
+    let (exact, point) = exact_digits(x);
+    if exact.len() != count + 1 || exact.last() != Some(&5) {
+        return None;
+    }
+    let mut candidate = exact[..count].to_vec();
+    let mut exponent = point - 1;
+    if candidate.last().is_some_and(|digit| digit % 2 == 1) {
+        // round up to the even neighbour instead
+        let mut index = count;
+        loop {
+            if index == 0 {
+                candidate.insert(0, 1);
+                candidate.pop();
+                exponent += 1;
+                break;
+            }
+            index -= 1;
+            if candidate[index] < 9 {
+                candidate[index] += 1;
+                break;
+            }
+            candidate[index] = 0;
+        }
+    }
+    let digits: String = candidate
+        .iter()
+        .map(|digit| (b'0' + digit) as char)
+        .collect();
+    let digits = digits.trim_end_matches('0').to_string();
+    let digits = if digits.is_empty() {
+        "0".to_string()
+    } else {
+        digits
+    };
+    let reads_back = format!("{digits}e{}", exponent - digits.len() as i32 + 1).parse() == Ok(x);
+    reads_back.then_some((digits, exponent))
+}
+
+/// How many decimal fraction digits the exact value of `x` has: a double is an integer times
+/// a power of two, and 2^-n takes exactly n of them.
+fn fraction_digits(x: f64) -> u32 {
+    let bits = x.to_bits();
+    let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mantissa, exponent) = match biased_exponent {
+        0 => (fraction, -1074),
+        _ => (fraction | (1 << 52), biased_exponent - 1075),
+    };
+    if mantissa == 0 {
+        return 0;
+    }
+    let exponent = exponent + mantissa.trailing_zeros() as i32;
+    (-exponent).max(0) as u32
+}
+
+/// The exact decimal expansion of a finite, positive `x` as digits without leading or
+/// trailing zeros, and the position of the decimal point relative to the first of them.
+pub fn exact_digits(x: f64) -> (Vec<u8>, i32) {
+    let formatted = format!("{x:.*}", fraction_digits(x) as usize);
+    let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+    let integer = integer.trim_start_matches('0');
+    let mut point = integer.len() as i32;
+    let mut digits: Vec<u8> = integer
+        .bytes()
+        .chain(fraction.bytes())
+        .map(|b| b - b'0')
+        .collect();
+    if integer.is_empty() {
+        let zeros = digits.iter().take_while(|digit| **digit == 0).count();
+        digits.drain(..zeros);
+        point -= zeros as i32;
+    }
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    (digits, point)
+}
+
+/// `x` in a radix other than 10, with as many fraction digits as it takes to tell it apart from
+/// its neighbours. Follows V8's DoubleToRadixCString, `x` has to be finite and positive.
+fn to_radix_string(x: f64, radix: u32) -> String {
+    let radix_f64 = radix as f64;
+    let mut integer = x.floor();
+    let mut fraction = x - integer;
+    // half the distance to the next double, digits past this precision are noise
+    let mut delta = (0.5 * (f64::from_bits(x.to_bits() + 1) - x)).max(f64::from_bits(1));
+    let mut fraction_digits: Vec<u32> = vec![];
+    if fraction >= delta {
+        loop {
+            fraction *= radix_f64;
+            delta *= radix_f64;
+            let digit = fraction as u32;
+            fraction_digits.push(digit);
+            fraction -= digit as f64;
+            // round half to even, carrying into the digits already written
+            if (fraction > 0.5 || (fraction == 0.5 && digit & 1 == 1)) && fraction + delta > 1.0 {
+                loop {
+                    match fraction_digits.pop() {
+                        Some(digit) if digit + 1 < radix => {
+                            fraction_digits.push(digit + 1);
+                            break;
+                        }
+                        Some(_) => continue,
+                        None => {
+                            integer += 1.0;
+                            break;
+                        }
+                    }
+                }
+                break;
+            }
+            if fraction < delta {
+                break;
+            }
+        }
+    }
+
+    // digits below the precision of a double are unknown, and written as zeros
+    let mut integer_digits = vec![];
+    while integer / radix_f64 >= 2f64.powi(53) {
+        integer /= radix_f64;
+        integer_digits.push(0);
+    }
+    loop {
+        let remainder = integer % radix_f64;
+        integer_digits.push(remainder as u32);
+        integer = (integer - remainder) / radix_f64;
+        if integer <= 0.0 {
+            break;
+        }
+    }
+    let mut string: String = integer_digits
+        .iter()
+        .rev()
+        .map(|digit| RADIX_DIGITS[*digit as usize] as char)
+        .collect();
+    if !fraction_digits.is_empty() {
+        string.push('.');
+        string.extend(
+            fraction_digits
+                .iter()
+                .map(|digit| RADIX_DIGITS[*digit as usize] as char),
+        );
+    }
+    string
+}
+
+/// Whitespace and line terminators that surround numbers in strings.
+pub fn is_js_whitespace(c: char) -> bool {
+    matches!(
+        c,
+        '\t' | '\n'
+            | '\u{b}'
+            | '\u{c}'
+            | '\r'
+            | ' '
+            | '\u{a0}'
+            | '\u{2028}'
+            | '\u{2029}'
+            | '\u{feff}'
+    ) || c.is_whitespace()
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-stringtonumber
+pub fn string_to_number(string: &str) -> f64 {
+    let string = string.trim_matches(is_js_whitespace);
+    if string.is_empty() {
+        return 0.0;
+    }
+    let lower = string.to_ascii_lowercase();
+    let radix = match lower.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        let digits = &string[2..];
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return f64::NAN;
+        }
+        return digits.chars().fold(0.0, |number, c| {
+            number * radix as f64 + c.to_digit(radix).unwrap_or_default() as f64
+        });
+    }
+    match decimal_prefix_length(string) {
+        length if length == string.len() => parse_decimal(string),
+        _ => f64::NAN,
+    }
+}
+
+/// Length of the longest prefix of `string` that is a StrDecimalLiteral, 0 if there is none.
+pub fn decimal_prefix_length(string: &str) -> usize {
+    let bytes = string.as_bytes();
+    let mut position = 0;
+    if let Some(b'+' | b'-') = bytes.first() {
+        position += 1;
+    }
+    if string[position..].starts_with("Infinity") {
+        return position + "Infinity".len();
+    }
+    let count_digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count()
+    };
+    let integer_digits = count_digits(position);
+    position += integer_digits;
+    let mut fraction_digits = 0;
+    if bytes.get(position) == Some(&b'.') {
+        fraction_digits = count_digits(position + 1);
+        if integer_digits + fraction_digits > 0 {
+            position += 1 + fraction_digits;
+        }
+    }
+    if integer_digits + fraction_digits == 0 {
+        return 0;
+    }
+    if let Some(b'e' | b'E') = bytes.get(position) {
+        let mut exponent = position + 1;
+        if let Some(b'+' | b'-') = bytes.get(exponent) {
+            exponent += 1;
+        }
+        let exponent_digits = count_digits(exponent);
+        if exponent_digits > 0 {
+            position = exponent + exponent_digits;
+        }
+    }
+    position
+}
+
+/// Reads a StrDecimalLiteral, which Rust's own parser accepts too apart from `Infinity`.
+pub fn parse_decimal(string: &str) -> f64 {
+    match string.trim_start_matches('+') {
+        "Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        string => string.parse().unwrap_or(f64::NAN),
+    }
 }
@@ -1,14 +1,14 @@
-use std::collections::HashMap;
-
-use string_interner::symbol::SymbolU32;
-
 use crate::{
     Interpreter,
-    errors::JSError,
-    global::{get_or_intern_string, get_string_from_pool},
+    constants::LENGTH_NAME,
+    global::get_string_from_pool,
+    heap::Tracer,
     values::{
-        JSResult, JSValue, PreferredType,
-        objects::{ObjectProperty, Properties, TO_PRIMITIVE_SYM},
+        JSResult, JSValue, PropertyKey,
+        objects::{
+            ObjectProperty, ObjectPropertyBuilder, Properties, PropertyMap,
+            ordinary_own_property_keys,
+        },
     },
 };
 
@@ -17,104 +17,137 @@ use crate::{
 pub struct Array {
     extensible: bool,
     prototype: Option<usize>,
-    properties: HashMap<SymbolU32, ObjectProperty>,
+    properties: PropertyMap,
 }
 
 impl Array {
     pub fn new(properties: Properties, interpreter: &mut Interpreter) -> Self {
-        let map = HashMap::from_iter(
-            properties
-                .into_iter()
-                .map(|(k, v)| (k, ObjectProperty::new_from_value(v))),
-        );
+        let mut map: PropertyMap = properties
+            .into_iter()
+            .map(|(k, v)| (k.into(), ObjectProperty::new_from_value(v)))
+            .collect();
+        let length = map.len() as f64;
+        let length_prop = ObjectPropertyBuilder::new(JSValue::new_number(&length))
+            .writable(true)
+            .build();
+        map.insert(PropertyKey::from_name(LENGTH_NAME), length_prop);
         Self {
             extensible: true,
-            prototype: None,
+            prototype: Some(interpreter.get_object_proto_id()),
             properties: map,
         }
     }
 
-    pub fn get_property(&self, key: &SymbolU32) -> Option<&ObjectProperty> {
+    /// What the array refers to besides its properties.
+    pub fn trace_header(&self, tracer: &mut Tracer) {
+        if let Some(prototype) = self.prototype {
+            tracer.mark(prototype);
+        }
+    }
+
+    pub fn properties(&self) -> &PropertyMap {
+        &self.properties
+    }
+
+    pub fn properties_mut(&mut self) -> &mut PropertyMap {
+        &mut self.properties
+    }
+
+    pub fn get_property(&self, key: &PropertyKey) -> Option<&ObjectProperty> {
         self.properties.get(key)
     }
 
-    pub fn get_property_mut(&mut self, key: &SymbolU32) -> Option<&mut ObjectProperty> {
+    pub fn get_property_mut(&mut self, key: &PropertyKey) -> Option<&mut ObjectProperty> {
         self.properties.get_mut(key)
     }
 
+    /// Inserts the property and keeps `length` in sync when an index at or past the end is added.
+    pub fn add_property(&mut self, key: PropertyKey, value: ObjectProperty) {
+        if let Some(index) = array_index(&key)
+            && index >= self.len()
+        {
+            self.set_len(index + 1);
+        }
+        self.properties.insert(key, value);
+    }
+
+    pub fn remove_property(&mut self, key: &PropertyKey) -> Option<ObjectProperty> {
+        self.properties.shift_remove(key)
+    }
+
+    pub fn property_keys(&self) -> Vec<PropertyKey> {
+        ordinary_own_property_keys(self.properties.keys())
+    }
+
+    pub fn get_prototype(&self) -> Option<usize> {
+        self.prototype
+    }
+
+    pub fn set_prototype(&mut self, proto: Option<usize>) {
+        self.prototype = proto;
+    }
+
+    pub fn is_extensible(&self) -> bool {
+        self.extensible
+    }
+
+    pub fn prevent_extensions(&mut self) {
+        self.extensible = false;
+    }
+
+    pub fn len(&self) -> usize {
+        let length_key = PropertyKey::from_name(LENGTH_NAME);
+        match self.properties.get(&length_key).map(|p| p.get_value()) {
+            Some(Ok(JSValue::Number { data })) => *data as usize,
+            _ => 0,
+        }
+    }
+
+    /// Updates `length`, deleting any elements that no longer fit.
+    pub fn set_len(&mut self, new_len: usize) {
+        let old_len = self.len();
+        for index in new_len..old_len {
+            let key = PropertyKey::from_name(&index.to_string());
+            self.properties.shift_remove(&key);
+        }
+        let length_key = PropertyKey::from_name(LENGTH_NAME);
+        if let Some(prop) = self.properties.get_mut(&length_key) {
+            prop.set_value(JSValue::new_number(&(new_len as f64)));
+        }
+    }
+
     pub fn push(&mut self, value: JSValue) -> JSResult<JSValue> {
-        let next_id = self.properties.len().to_string();
-        let id = get_or_intern_string(&next_id);
+        let next_id = self.len().to_string();
+        let id = PropertyKey::from_name(&next_id);
         let property = ObjectProperty::new_from_value(value);
-        self.properties.insert(id, property);
-        let new_len = self.properties.len() as f64;
+        self.add_property(id, property);
+        let new_len = self.len() as f64;
         let val = JSValue::new_number(&new_len);
         Ok(val)
     }
 
     pub fn pop(&mut self) -> JSResult<JSValue> {
-        if self.properties.len() == 0 {
+        let len = self.len();
+        if len == 0 {
             return Ok(JSValue::Undefined);
         }
-        let prev_id_str = (self.properties.len() - 1).to_string();
-        let id = get_or_intern_string(&prev_id_str);
-        let res = self.properties.remove(&id).expect("Something catastrophic"); // safe - we know there's at least 1 ID
-        let value = res.get_value()?;
-        Ok(value.clone())
-    }
-
-    pub fn to_primitive(
-        &self,
-        hint: PreferredType,
-        interpreter: &mut Interpreter,
-    ) -> JSResult<JSValue> {
-        let prim_sym = get_or_intern_string(TO_PRIMITIVE_SYM);
-        let maybe_property = self.properties.get(&prim_sym);
-        match maybe_property {
-            Some(_property) => {
-                todo!();
-            }
-            None => {
-                let method_names = match hint {
-                    PreferredType::Number => vec!["value_of", "to_string"],
-                    PreferredType::String => vec!["to_string", "value_of"],
-                };
-                for method in method_names {
-                    if method == "value_of" {
-                        let result = self.value_of()?;
-                        if !result.is_object() {
-                            return Ok(result);
-                        }
-                    }
-                    if method == "to_string" {
-                        return self.to_string(interpreter);
-                    }
-                }
-                let error = JSError::new_function_type_error("unknown");
-                return Err(error);
-            }
-        }
-    }
-
-    pub fn value_of(&self) -> JSResult<JSValue> {
-        // Ok(JSValue::object_shallow_copy(self.id))
-        todo!()
+        let prev_id_str = (len - 1).to_string();
+        let id = PropertyKey::from_name(&prev_id_str);
+        let value = match self.properties.get(&id) {
+            Some(prop) if !prop.is_accessor() => prop.get_value()?.clone(),
+            _ => JSValue::Undefined,
+        };
+        self.set_len(len - 1);
+        Ok(value)
     }
+}
 
-    pub fn to_string(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
-        let values = self
-            .properties
-            .values()
-            .map(|prop| {
-                let val = prop.get_value()?;
-                let res = val.to_string(interpreter)?;
-                let string = get_string_from_pool(&res)
-                    .expect("An array has a value that doesn't exist in the string pool?");
-                Ok(string)
-            })
-            .collect::<JSResult<Vec<String>>>()?
-            .join(",");
-        let sym = get_or_intern_string(&format!("[{values}]"));
-        Ok(JSValue::new_string(&sym))
+/// Returns the numeric index when `key` is a canonical array index ("0", "1", ... but not "01").
+pub fn array_index(key: &PropertyKey) -> Option<usize> {
+    let string = get_string_from_pool(&key.as_string()?)?;
+    let index = string.parse::<u32>().ok()?;
+    if index.to_string() != string || index == u32::MAX {
+        return None;
     }
+    Some(index as usize)
 }
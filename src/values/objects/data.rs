@@ -0,0 +1,101 @@
+use num_bigint::BigInt;
+use string_interner::symbol::SymbolU32;
+
+use crate::{
+    builtins::{
+        array_buffer::ArrayBufferState,
+        collection::{CollectionIteratorState, OrderedTable, WeakTable},
+        data_view::DataViewState,
+        generator::GeneratorState,
+        promise::PromiseState,
+        regexp::{RegExpState, RegExpStringIteratorState},
+        typed_array::{ArrayIteratorState, TypedArrayState},
+    },
+    heap::{Slots, Trace, Tracer},
+    values::{JSValue, PropertyKey},
+};
+
+/// Internal slots of built-in objects that live outside the property map.
+#[derive(Clone, Debug, Default)]
+pub enum ObjectData {
+    #[default]
+    None,
+    Generator(Box<GeneratorState>),
+    Promise(Box<PromiseState>),
+    /// [[NumberData]] of the objects `new Number(x)` creates.
+    Number(f64),
+    /// [[BooleanData]] of the objects `Object(true)` creates. There's no `Boolean` global, so
+    /// they inherit from `Object.prototype`.
+    Boolean(bool),
+    /// [[StringData]] of the objects `Object('abc')` creates.
+    String(SymbolU32),
+    /// [[SymbolData]] of the objects `Object(symbol)` creates.
+    Symbol(PropertyKey),
+    /// [[BigIntData]] of the objects `Object(1n)` creates.
+    BigInt(Box<BigInt>),
+    /// [[DateValue]], a time value or NaN for an invalid date.
+    Date(f64),
+    RegExp(Box<RegExpState>),
+    RegExpStringIterator(Box<RegExpStringIteratorState>),
+    /// [[MapData]], keys to values.
+    Map(Box<OrderedTable<JSValue>>),
+    MapIterator(Box<CollectionIteratorState>),
+    /// [[SetData]], a Set's values are the table's keys.
+    Set(Box<OrderedTable<()>>),
+    SetIterator(Box<CollectionIteratorState>),
+    WeakMap(Box<WeakTable<JSValue>>),
+    WeakSet(Box<WeakTable<()>>),
+    ArrayBuffer(Box<ArrayBufferState>),
+    TypedArray(Box<TypedArrayState>),
+    ArrayIterator(Box<ArrayIteratorState>),
+    /// [[ViewedArrayBuffer]], [[ByteOffset]] and [[ByteLength]] of a DataView.
+    DataView(Box<DataViewState>),
+}
+
+impl ObjectData {
+    /// The entries of a Map or Set, which the collector traces as slots.
+    pub fn slots(&self) -> Option<&dyn Slots> {
+        match self {
+            ObjectData::Map(table) => Some(table.as_ref()),
+            ObjectData::Set(table) => Some(table.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub fn slots_mut(&mut self) -> Option<&mut dyn Slots> {
+        match self {
+            ObjectData::Map(table) => Some(table.as_mut()),
+            ObjectData::Set(table) => Some(table.as_mut()),
+            _ => None,
+        }
+    }
+}
+
+/// The weak collections are left out: what their entries keep alive depends on their keys,
+/// which the collector works out itself.
+impl Trace for ObjectData {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            ObjectData::Generator(state) => state.trace(tracer),
+            ObjectData::Promise(state) => state.trace(tracer),
+            ObjectData::RegExpStringIterator(state) => state.trace(tracer),
+            ObjectData::Map(table) => table.trace(tracer),
+            ObjectData::MapIterator(state) | ObjectData::SetIterator(state) => state.trace(tracer),
+            ObjectData::Set(table) => table.trace(tracer),
+            ObjectData::TypedArray(state) => state.trace(tracer),
+            ObjectData::ArrayIterator(state) => state.trace(tracer),
+            ObjectData::DataView(state) => state.trace(tracer),
+            ObjectData::None
+            | ObjectData::Number(_)
+            | ObjectData::Boolean(_)
+            | ObjectData::String(_)
+            | ObjectData::Symbol(_)
+            | ObjectData::BigInt(_)
+            | ObjectData::Date(_)
+            | ObjectData::RegExp(_)
+            | ObjectData::WeakMap(_)
+            | ObjectData::WeakSet(_)
+            | ObjectData::ArrayBuffer(_) => (),
+        }
+    }
+}
@@ -1,44 +1,103 @@
-use core::f64;
-use std::collections::HashMap;
-
 use log::debug;
 use string_interner::symbol::SymbolU32;
 
 use crate::{
     Interpreter,
-    errors::ErrorKind,
-    expr::{Expr, LogKind},
-    global::{get_or_intern_string, get_string_from_pool},
+    builtins::{generator::create_generator, promise::start_async_function},
+    constants::{PROTOTYPE_NAME, THIS_NAME},
+    errors::{ErrorKind, JSError},
+    global::get_or_intern_string,
+    heap::{Trace, Tracer},
+    host::HostCallback,
     stmt::Stmt,
-    token::Kind,
-    values::{JSResult, JSValue, PreferredType, objects::ObjectProperty},
+    values::{
+        JSResult, JSValue, PropertyKey,
+        objects::{ObjectProperty, PropertyMap, ordinary_own_property_keys},
+    },
 };
 
+/// Signature shared by every built-in implemented in Rust. Receives the `this` value and the
+/// already evaluated arguments.
+pub type NativeFunction = fn(&mut Interpreter, &JSValue, &[JSValue]) -> JSResult<JSValue>;
+
+/// A built-in that closes over some values, like the resolving functions handed to a promise
+/// executor. Receives the captured values ahead of `this` and the arguments.
+pub type NativeClosure =
+    fn(&mut Interpreter, &[JSValue], &JSValue, &[JSValue]) -> JSResult<JSValue>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FunctionKind {
+    Normal,
+    Arrow,
+    Method,
+    ClassConstructor,
+}
+
+impl FunctionKind {
+    pub fn is_constructor(&self) -> bool {
+        matches!(self, FunctionKind::Normal | FunctionKind::ClassConstructor)
+    }
+}
+
+/// How calling the function runs its body: straight away, or as a coroutine driven by the
+/// generator object or promise the call returns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BodyKind {
+    #[default]
+    Normal,
+    Generator,
+    Async,
+}
+
+impl std::fmt::Display for BodyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyKind::Normal => write!(f, ""),
+            BodyKind::Generator => write!(f, "*"),
+            BodyKind::Async => write!(f, "async "),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FunctionObject {
+    extensible: bool,
     prototype: Option<usize>,
-    properties: HashMap<SymbolU32, ObjectProperty>,
+    properties: PropertyMap,
+    kind: FunctionKind,
     call: Box<Stmt>,
     environment_id: usize,
     formal_parameters: Vec<SymbolU32>,
+    body_kind: BodyKind,
+    native: Option<NativeFunction>,
+    native_construct: Option<NativeFunction>, // receives new.target as `this`
+    closure: Option<(NativeClosure, Vec<JSValue>)>,
+    host: Option<HostCallback>,
 }
 
 impl FunctionObject {
     pub fn new_proto(env_id: usize, proto_id: usize) -> Self {
-        let mut properties = HashMap::new();
+        let mut properties = PropertyMap::new();
         let length_id = get_or_intern_string("length");
         let name_id = get_or_intern_string("name");
         let length_val = ObjectProperty::new_from_value(JSValue::new_number(&0.0));
-        properties.insert(length_id, length_val);
+        properties.insert(length_id.into(), length_val);
         let name_string_id = get_or_intern_string("");
         let name_val = ObjectProperty::new_from_value(JSValue::new_string(&name_string_id));
-        properties.insert(name_id, name_val);
+        properties.insert(name_id.into(), name_val);
         Self {
+            extensible: true,
             prototype: Some(proto_id),
             environment_id: env_id,
+            kind: FunctionKind::Normal,
             call: Box::new(Stmt::Break),
             formal_parameters: vec![],
+            body_kind: BodyKind::Normal,
             properties,
+            native: None,
+            native_construct: None,
+            closure: None,
+            host: None,
         }
     }
 
@@ -47,88 +106,287 @@ impl FunctionObject {
         environment_id: usize,
         proto_id: usize,
         parameters: Vec<SymbolU32>,
+        kind: FunctionKind,
+        body_kind: BodyKind,
     ) -> Self {
         Self {
+            extensible: true,
             prototype: Some(proto_id),
-            properties: HashMap::new(),
+            properties: PropertyMap::new(),
+            kind,
             call,
             environment_id,
             formal_parameters: parameters,
+            body_kind,
+            native: None,
+            native_construct: None,
+            closure: None,
+            host: None,
+        }
+    }
+
+    pub fn new_native(
+        native: NativeFunction,
+        native_construct: Option<NativeFunction>,
+        environment_id: usize,
+        proto_id: usize,
+    ) -> Self {
+        Self {
+            extensible: true,
+            prototype: Some(proto_id),
+            properties: PropertyMap::new(),
+            kind: FunctionKind::Method,
+            call: Box::new(Stmt::Break),
+            environment_id,
+            formal_parameters: vec![],
+            body_kind: BodyKind::Normal,
+            native: Some(native),
+            native_construct,
+            closure: None,
+            host: None,
         }
     }
 
-    pub fn call(&self, arguments: &Vec<Expr>, interpreter: &mut Interpreter) -> JSResult<JSValue> {
-        interpreter.enter_scope(Some(self.environment_id));
+    pub fn new_native_closure(
+        closure: NativeClosure,
+        captures: Vec<JSValue>,
+        environment_id: usize,
+        proto_id: usize,
+    ) -> Self {
+        Self {
+            extensible: true,
+            prototype: Some(proto_id),
+            properties: PropertyMap::new(),
+            kind: FunctionKind::Method,
+            call: Box::new(Stmt::Break),
+            environment_id,
+            formal_parameters: vec![],
+            body_kind: BodyKind::Normal,
+            native: None,
+            native_construct: None,
+            closure: Some((closure, captures)),
+            host: None,
+        }
+    }
+
+    pub(crate) fn new_host(host: HostCallback, environment_id: usize, proto_id: usize) -> Self {
+        Self {
+            extensible: true,
+            prototype: Some(proto_id),
+            properties: PropertyMap::new(),
+            kind: FunctionKind::Method,
+            call: Box::new(Stmt::Break),
+            environment_id,
+            formal_parameters: vec![],
+            body_kind: BodyKind::Normal,
+            native: None,
+            native_construct: None,
+            closure: None,
+            host: Some(host),
+        }
+    }
+
+    pub fn call(
+        &self,
+        this: &JSValue,
+        arguments: &[JSValue],
+        interpreter: &mut Interpreter,
+    ) -> JSResult<JSValue> {
+        if let Some(native) = self.native {
+            return native(interpreter, this, arguments);
+        }
+        if let Some((closure, captures)) = &self.closure {
+            return closure(interpreter, captures, this, arguments);
+        }
+        if let Some(host) = &self.host {
+            return host.call(interpreter, this, arguments);
+        }
+        if self.kind == FunctionKind::ClassConstructor {
+            return Err(JSError::new_type_error(
+                "Class constructor cannot be invoked without 'new'",
+            ));
+        }
+        match self.body_kind {
+            BodyKind::Normal => self.evaluate_body(this, arguments, interpreter),
+            BodyKind::Generator => self.start_generator(this, arguments, interpreter),
+            BodyKind::Async => self.start_async(this, arguments, interpreter),
+        }
+    }
+
+    /// [[Construct]]. `this_value` is the freshly allocated object for script functions and is
+    /// ignored by native constructors, which build their own object from `new_target`.
+    pub fn construct(
+        &self,
+        this_value: &JSValue,
+        new_target: &JSValue,
+        arguments: &[JSValue],
+        interpreter: &mut Interpreter,
+    ) -> JSResult<JSValue> {
+        if let Some(native_construct) = self.native_construct {
+            return native_construct(interpreter, new_target, arguments);
+        }
+        let result = self.evaluate_body(this_value, arguments, interpreter)?;
+        if result.is_object() {
+            return Ok(result);
+        }
+        Ok(this_value.clone())
+    }
+
+    /// Creates the function's scope with `this` and the parameters bound, and enters it.
+    fn enter_function_scope(
+        &self,
+        this: &JSValue,
+        arguments: &[JSValue],
+        interpreter: &mut Interpreter,
+    ) -> usize {
+        let scope_id = interpreter.new_scope_with_parent(self.environment_id);
+        interpreter.enter_scope(Some(scope_id));
         debug!("function arguments: {:?}", arguments);
-        for (param, arg) in self.formal_parameters.iter().zip(arguments.iter()) {
-            let argument = arg.evaluate(interpreter)?;
-            interpreter.bind_variable(*param, &argument)?;
+        // arrow functions resolve `this` lexically so they never bind it
+        if self.kind != FunctionKind::Arrow {
+            let this_id = get_or_intern_string(THIS_NAME);
+            interpreter.new_variable(this_id, false, this.clone());
+        }
+        for (index, param) in self.formal_parameters.iter().enumerate() {
+            let argument = arguments.get(index).cloned().unwrap_or(JSValue::Undefined);
+            interpreter.new_variable(*param, true, argument);
         }
+        scope_id
+    }
+
+    fn evaluate_body(
+        &self,
+        this: &JSValue,
+        arguments: &[JSValue],
+        interpreter: &mut Interpreter,
+    ) -> JSResult<JSValue> {
+        self.enter_function_scope(this, arguments, interpreter);
+        // a call made while a generator is being resumed must not see its resume records
+        let resume_stack = interpreter.take_resume_stack();
         let result = self.call.evaluate(interpreter);
-        let result = match result {
-            Ok(v) => v,
+        interpreter.restore_resume_stack(resume_stack);
+        interpreter.leave_scope();
+        match result {
+            Ok(v) => Ok(v),
             Err(e) => match e.kind {
                 ErrorKind::Return(id) => {
                     let value = interpreter.get_value(id)?;
-                    value.clone()
-                }
-                _ => {
-                    return Err(e);
+                    Ok(value.clone())
                 }
+                _ => Err(e),
             },
-        };
+        }
+    }
+
+    /// Calling a generator function only binds the arguments. The body runs as the returned
+    /// generator object is resumed.
+    fn start_generator(
+        &self,
+        this: &JSValue,
+        arguments: &[JSValue],
+        interpreter: &mut Interpreter,
+    ) -> JSResult<JSValue> {
+        let scope_id = self.enter_function_scope(this, arguments, interpreter);
         interpreter.leave_scope();
-        Ok(result)
+        let prototype = self
+            .get_property(&PropertyKey::from_name(PROTOTYPE_NAME))
+            .and_then(|prop| prop.get_value().ok())
+            .and_then(|value| value.get_object_id().ok());
+        Ok(create_generator(
+            scope_id,
+            self.call.clone(),
+            prototype,
+            interpreter,
+        ))
     }
 
-    pub fn to_primitive(&self, hint: PreferredType) -> JSResult<JSValue> {
-        todo!()
+    /// Calling an async function runs its body up to the first `await` and returns a promise
+    /// for its completion.
+    fn start_async(
+        &self,
+        this: &JSValue,
+        arguments: &[JSValue],
+        interpreter: &mut Interpreter,
+    ) -> JSResult<JSValue> {
+        let scope_id = self.enter_function_scope(this, arguments, interpreter);
+        interpreter.leave_scope();
+        let coroutine = create_generator(scope_id, self.call.clone(), None, interpreter);
+        start_async_function(interpreter, coroutine)
     }
 
-    pub fn debug(&self, interpreter: &mut Interpreter) -> String {
-        format!("Function! idk whats in it")
+    pub fn get_kind(&self) -> &FunctionKind {
+        &self.kind
     }
 
-    pub fn create_is_finite(interpreter: &mut Interpreter) -> Self {
-        let is_finite_arg_id = get_or_intern_string(&format!("is_finite_arg"));
-        let left = Expr::new_identifier(&is_finite_arg_id);
-        let right = Expr::new_literal(JSValue::Number {
-            data: f64::INFINITY,
-        });
-        let binary_expr = Expr::new_binary(Kind::NotEqual, left, right);
-        let stmt = Stmt::new_expression(binary_expr);
-        Self::new_built_in(is_finite_arg_id, stmt, interpreter)
+    pub fn is_constructor(&self) -> bool {
+        if self.native.is_some() {
+            return self.native_construct.is_some();
+        }
+        if self.closure.is_some() {
+            return false;
+        }
+        self.body_kind == BodyKind::Normal && self.kind.is_constructor()
     }
 
-    pub fn create_log(interpreter: &mut Interpreter) -> Self {
-        Self::create_generic_logger(LogKind::Log, interpreter)
+    pub fn body_kind(&self) -> BodyKind {
+        self.body_kind
     }
 
-    pub fn create_error(interpreter: &mut Interpreter) -> Self {
-        Self::create_generic_logger(LogKind::Error, interpreter)
+    pub fn get_prototype(&self) -> Option<usize> {
+        self.prototype
     }
 
-    fn create_generic_logger(kind: LogKind, interpreter: &mut Interpreter) -> Self {
-        let log_id = get_or_intern_string("data");
-        let print_expr = Expr::new_print_expr(kind);
-        let stmt = Stmt::new_expression(print_expr);
-        Self::new_built_in(log_id, stmt, interpreter)
+    pub fn set_prototype(&mut self, proto: Option<usize>) {
+        self.prototype = proto;
     }
 
-    pub fn new_built_in(arg_id: SymbolU32, stmt: Stmt, interpreter: &mut Interpreter) -> Self {
-        let scope_id = interpreter.enter_scope(None);
-        let parameters = vec![arg_id];
-        for param in &parameters {
-            interpreter.new_variable(arg_id, true, JSValue::Undefined);
-        }
-        interpreter.leave_scope();
+    pub fn is_extensible(&self) -> bool {
+        self.extensible
+    }
 
-        Self {
-            call: Box::new(stmt),
-            prototype: None,
-            properties: HashMap::new(),
-            environment_id: scope_id,
-            formal_parameters: parameters,
+    pub fn prevent_extensions(&mut self) {
+        self.extensible = false;
+    }
+
+    pub fn properties(&self) -> &PropertyMap {
+        &self.properties
+    }
+
+    pub fn properties_mut(&mut self) -> &mut PropertyMap {
+        &mut self.properties
+    }
+
+    pub fn get_property(&self, key: &PropertyKey) -> Option<&ObjectProperty> {
+        self.properties.get(key)
+    }
+
+    pub fn get_property_mut(&mut self, key: &PropertyKey) -> Option<&mut ObjectProperty> {
+        self.properties.get_mut(key)
+    }
+
+    pub fn add_property(&mut self, key: impl Into<PropertyKey>, value: ObjectProperty) {
+        self.properties.insert(key.into(), value);
+    }
+
+    pub fn remove_property(&mut self, key: &PropertyKey) -> Option<ObjectProperty> {
+        self.properties.shift_remove(key)
+    }
+
+    pub fn property_keys(&self) -> Vec<PropertyKey> {
+        ordinary_own_property_keys(self.properties.keys())
+    }
+}
+
+impl FunctionObject {
+    /// What the function refers to besides its properties: a script function keeps the scope
+    /// it was created in alive, a native closure its captures.
+    pub fn trace_header(&self, tracer: &mut Tracer) {
+        if let Some(prototype) = self.prototype {
+            tracer.mark(prototype);
+        }
+        tracer.mark(self.environment_id);
+        if let Some((_, captures)) = &self.closure {
+            captures.trace(tracer);
         }
     }
 }
@@ -137,8 +395,7 @@ impl std::fmt::Display for FunctionObject {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Function: {{")?;
         for (key, value) in &self.properties {
-            let string = get_string_from_pool(&key).unwrap();
-            writeln!(f, "\t {string}: {:?}", value.get_value())?;
+            writeln!(f, "\t {key}: {:?}", value.get_value())?;
         }
         writeln!(f, "}}")
     }
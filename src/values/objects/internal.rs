@@ -0,0 +1,335 @@
+//! The essential internal methods every object has, [[GetPrototypeOf]] through
+//! [[OwnPropertyKeys]]. Ordinary objects, arrays and functions share the ordinary algorithms,
+//! typed arrays hook their integer-indexed elements in front of them and proxies hand every
+//! operation to their handler. Code outside this module should go through these instead of the
+//! raw property storage on `JSObject` whenever the object may come from script.
+
+use crate::{
+    Interpreter,
+    builtins::typed_array::{
+        is_valid_integer_index, typed_array_get_element, typed_array_index, typed_array_own_keys,
+        typed_array_set_element,
+    },
+    values::{
+        JSResult, JSValue, PropertyKey,
+        objects::{
+            JSObject, ObjectId, ObjectProperty, ObjectPropertyBuilder, PropertyDescriptor,
+            ordinary_define_own_property, proxy,
+        },
+    },
+};
+
+/// `object_id` as a value, tagged with the kind of object it is.
+pub fn object_value(interpreter: &Interpreter, object_id: ObjectId) -> JSResult<JSValue> {
+    let kind = interpreter.get_object(object_id)?.kind();
+    Ok(JSValue::Object { object_id, kind })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinary-object-internal-methods-and-internal-slots-getprototypeof
+pub fn get_prototype_of(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+) -> JSResult<Option<ObjectId>> {
+    match interpreter.get_object(object_id)? {
+        JSObject::Proxy(proxy) => proxy::get_prototype_of(interpreter, *proxy),
+        object => Ok(object.get_prototype()),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinarysetprototypeof
+/// Returns `false` when the object isn't extensible or the new prototype would create a cycle.
+pub fn set_prototype_of(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    prototype: Option<ObjectId>,
+) -> JSResult<bool> {
+    let object = interpreter.get_object(object_id)?;
+    if let JSObject::Proxy(proxy) = object {
+        return proxy::set_prototype_of(interpreter, *proxy, prototype);
+    }
+    if object.get_prototype() == prototype {
+        return Ok(true);
+    }
+    if !object.is_extensible() {
+        return Ok(false);
+    }
+    // a proxy ends the cycle check, its [[GetPrototypeOf]] could be anything
+    let mut current = prototype;
+    while let Some(id) = current {
+        if id == object_id {
+            return Ok(false);
+        }
+        current = match interpreter.get_object(id)? {
+            JSObject::Proxy(_) => None,
+            object => object.get_prototype(),
+        };
+    }
+    interpreter
+        .get_object_mut(object_id)?
+        .set_prototype(prototype);
+    Ok(true)
+}
+
+pub fn is_extensible(interpreter: &mut Interpreter, object_id: ObjectId) -> JSResult<bool> {
+    match interpreter.get_object(object_id)? {
+        JSObject::Proxy(proxy) => proxy::is_extensible(interpreter, *proxy),
+        object => Ok(object.is_extensible()),
+    }
+}
+
+pub fn prevent_extensions(interpreter: &mut Interpreter, object_id: ObjectId) -> JSResult<bool> {
+    match interpreter.get_object_mut(object_id)? {
+        JSObject::Proxy(proxy) => {
+            let proxy = *proxy;
+            proxy::prevent_extensions(interpreter, proxy)
+        }
+        object => {
+            object.prevent_extensions();
+            Ok(true)
+        }
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-typedarray-getownproperty
+/// [[GetOwnProperty]]. The elements of a typed array are writable, enumerable and configurable
+/// data properties read from its buffer.
+pub fn get_own_property(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: &PropertyKey,
+) -> JSResult<Option<ObjectProperty>> {
+    if let JSObject::Proxy(proxy) = interpreter.get_object(object_id)? {
+        return proxy::get_own_property(interpreter, *proxy, key);
+    }
+    if let Some((state, index)) = typed_array_index(interpreter, object_id, key) {
+        if !is_valid_integer_index(interpreter, &state, index) {
+            return Ok(None);
+        }
+        let value = typed_array_get_element(interpreter, &state, index);
+        let prop = ObjectPropertyBuilder::new(value)
+            .writable(true)
+            .enumerable(true)
+            .configurable(true)
+            .build();
+        return Ok(Some(prop));
+    }
+    Ok(interpreter
+        .get_object(object_id)?
+        .get_property(key)
+        .cloned())
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-typedarray-defineownproperty
+/// [[DefineOwnProperty]]. Returns `false` when the descriptor is incompatible with the current
+/// property.
+pub fn define_own_property(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: impl Into<PropertyKey>,
+    desc: &PropertyDescriptor,
+) -> JSResult<bool> {
+    let key = key.into();
+    if let JSObject::Proxy(proxy) = interpreter.get_object(object_id)? {
+        return proxy::define_own_property(interpreter, *proxy, &key, desc);
+    }
+    if let Some((state, index)) = typed_array_index(interpreter, object_id, &key) {
+        let rejected = !is_valid_integer_index(interpreter, &state, index)
+            || desc.configurable == Some(false)
+            || desc.enumerable == Some(false)
+            || desc.is_accessor_descriptor()
+            || desc.writable == Some(false);
+        if rejected {
+            return Ok(false);
+        }
+        if let Some(value) = &desc.value {
+            typed_array_set_element(interpreter, &state, index, value)?;
+        }
+        return Ok(true);
+    }
+    ordinary_define_own_property(interpreter, object_id, key, desc)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinaryhasproperty
+pub fn has_property(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: &PropertyKey,
+) -> JSResult<bool> {
+    if let JSObject::Proxy(proxy) = interpreter.get_object(object_id)? {
+        return proxy::has_property(interpreter, *proxy, key);
+    }
+    if let Some((state, index)) = typed_array_index(interpreter, object_id, key) {
+        return Ok(is_valid_integer_index(interpreter, &state, index));
+    }
+    if get_own_property(interpreter, object_id, key)?.is_some() {
+        return Ok(true);
+    }
+    match get_prototype_of(interpreter, object_id)? {
+        Some(parent) => has_property(interpreter, parent, key),
+        None => Ok(false),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinaryget
+/// [[Get]]. Getters run with `receiver` as `this`, which is the object the lookup started on
+/// rather than the one holding the property.
+pub fn get(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: &PropertyKey,
+    receiver: &JSValue,
+) -> JSResult<JSValue> {
+    if let JSObject::Proxy(proxy) = interpreter.get_object(object_id)? {
+        return proxy::get(interpreter, *proxy, key, receiver);
+    }
+    // https://262.ecma-international.org/15.0/index.html#sec-typedarray-get
+    if let Some((state, index)) = typed_array_index(interpreter, object_id, key) {
+        return Ok(typed_array_get_element(interpreter, &state, index));
+    }
+    let property = match get_own_property(interpreter, object_id, key)? {
+        Some(property) => property,
+        None => {
+            return match get_prototype_of(interpreter, object_id)? {
+                Some(parent) => get(interpreter, parent, key, receiver),
+                None => Ok(JSValue::Undefined),
+            };
+        }
+    };
+    // the caller may be the only one left holding the value once it is overwritten
+    match property {
+        ObjectProperty::Data { value, .. } => {
+            interpreter.root_value(&value);
+            Ok(value)
+        }
+        ObjectProperty::Accessor { get, .. } => match get {
+            Some(getter) => interpreter.call_function(&getter, receiver, &[]),
+            None => Ok(JSValue::Undefined),
+        },
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinaryset
+/// [[Set]]. Returns `false` when the write was rejected by a non-writable property, a missing
+/// setter or a non-extensible receiver.
+pub fn set(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: &PropertyKey,
+    value: JSValue,
+    receiver: &JSValue,
+) -> JSResult<bool> {
+    if let JSObject::Proxy(proxy) = interpreter.get_object(object_id)? {
+        return proxy::set(interpreter, *proxy, key, value, receiver);
+    }
+    // https://262.ecma-international.org/15.0/index.html#sec-typedarray-set
+    if let Some((state, index)) = typed_array_index(interpreter, object_id, key) {
+        let is_receiver =
+            matches!(receiver, JSValue::Object { object_id: id, .. } if *id == object_id);
+        if is_receiver {
+            typed_array_set_element(interpreter, &state, index, &value)?;
+            return Ok(true);
+        }
+        if !is_valid_integer_index(interpreter, &state, index) {
+            return Ok(true);
+        }
+    }
+    let own = match get_own_property(interpreter, object_id, key)? {
+        Some(own) => own,
+        None => match get_prototype_of(interpreter, object_id)? {
+            Some(parent) => return set(interpreter, parent, key, value, receiver),
+            None => ObjectPropertyBuilder::new(JSValue::Undefined)
+                .writable(true)
+                .enumerable(true)
+                .configurable(true)
+                .build(),
+        },
+    };
+    match own {
+        ObjectProperty::Data {
+            writable: false, ..
+        } => Ok(false),
+        ObjectProperty::Data { .. } => {
+            let JSValue::Object {
+                object_id: receiver_id,
+                ..
+            } = receiver
+            else {
+                return Ok(false);
+            };
+            let receiver_id = *receiver_id;
+            match get_own_property(interpreter, receiver_id, key)? {
+                Some(existing) if existing.is_accessor() || !existing.is_writable() => Ok(false),
+                Some(_) => {
+                    let desc = PropertyDescriptor {
+                        value: Some(value),
+                        ..Default::default()
+                    };
+                    define_own_property(interpreter, receiver_id, *key, &desc)
+                }
+                None => create_data_property(interpreter, receiver_id, *key, value),
+            }
+        }
+        ObjectProperty::Accessor { set, .. } => match set {
+            Some(setter) => {
+                interpreter.call_function(&setter, receiver, &[value])?;
+                Ok(true)
+            }
+            None => Ok(false),
+        },
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-createdataproperty
+/// Defines a writable, enumerable and configurable data property, the kind assignment creates.
+pub fn create_data_property(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: impl Into<PropertyKey>,
+    value: JSValue,
+) -> JSResult<bool> {
+    let desc = PropertyDescriptor {
+        value: Some(value),
+        writable: Some(true),
+        enumerable: Some(true),
+        configurable: Some(true),
+        ..Default::default()
+    };
+    define_own_property(interpreter, object_id, key, &desc)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinarydelete
+/// [[Delete]]. Returns `false` when the property exists but isn't configurable.
+pub fn delete(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: &PropertyKey,
+) -> JSResult<bool> {
+    if let JSObject::Proxy(proxy) = interpreter.get_object(object_id)? {
+        return proxy::delete(interpreter, *proxy, key);
+    }
+    if let Some((state, index)) = typed_array_index(interpreter, object_id, key) {
+        return Ok(!is_valid_integer_index(interpreter, &state, index));
+    }
+    match get_own_property(interpreter, object_id, key)? {
+        None => Ok(true),
+        Some(property) if property.is_configurable() => {
+            interpreter.get_object_mut(object_id)?.remove_property(key);
+            Ok(true)
+        }
+        Some(_) => Ok(false),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinaryownpropertykeys
+/// [[OwnPropertyKeys]]. The elements of a typed array come ahead of its other keys.
+pub fn own_property_keys(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+) -> JSResult<Vec<PropertyKey>> {
+    if let JSObject::Proxy(proxy) = interpreter.get_object(object_id)? {
+        return proxy::own_property_keys(interpreter, *proxy);
+    }
+    let mut keys = typed_array_own_keys(interpreter, object_id);
+    keys.extend(interpreter.get_object(object_id)?.property_keys());
+    Ok(keys)
+}
@@ -2,39 +2,53 @@
 #![allow(unused_variables)]
 
 mod array;
+mod data;
 mod function;
+pub mod internal;
 mod ordinary;
+mod property_map;
+mod proxy;
 
 use core::f64;
 
+pub use array::array_index;
+pub use data::ObjectData;
+pub use function::{BodyKind, FunctionKind, NativeClosure, NativeFunction};
+pub use internal::define_own_property;
+pub use property_map::PropertyMap;
+pub use proxy::ProxyObject;
+
 use function::*;
-use log::debug;
 use ordinary::*;
 use string_interner::symbol::SymbolU32;
 
 use crate::{
-    Interpreter,
+    Interpreter, builtins,
     constants::{
-        CONSOLE_NAME, ERROR_NAME, GLOBAL_THIS_NAME, INFINITY_NAME, IS_FINITE_NAME, LOG_NAME,
-        NAN_NAME, UNDEFINED_NAME,
+        CONSTRUCTOR_NAME, GLOBAL_THIS_NAME, INFINITY_NAME, LENGTH_NAME, NAME_NAME, NAN_NAME,
+        PROTOTYPE_NAME, UNDEFINED_NAME,
     },
     errors::JSError,
-    expr::Expr,
     global::{get_or_intern_string, get_string_from_pool},
+    heap::{Slots, Trace, Tracer},
+    host::HostCallback,
     stmt::Stmt,
-    values::{JSResult, JSValue, ObjectKind, PreferredType, objects::array::Array},
+    values::{
+        JSResult, JSValue, ObjectKind, PreferredType, PropertyKey, objects::array::Array,
+        same_value,
+    },
 };
 
 pub type ObjectId = usize;
 pub type Property = (SymbolU32, JSValue);
 pub type Properties = Vec<Property>;
-pub const TO_PRIMITIVE_SYM: &'static str = "@@toPrimitive";
 
 #[derive(Clone, Debug)]
 pub enum JSObject {
     Ordinary(OrdinaryObject),
     Function(FunctionObject),
     Array(Array),
+    Proxy(ProxyObject),
 }
 
 impl JSObject {
@@ -49,16 +63,98 @@ impl JSObject {
         interpreter.add_object(object)
     }
 
+    /// An ordinary object carrying internal slots, e.g. a generator object.
+    pub fn new_object_with_data(
+        data: ObjectData,
+        proto: Option<usize>,
+        interpreter: &mut Interpreter,
+    ) -> usize {
+        let ordinary = OrdinaryObject::new_with_data(data, proto);
+        interpreter.add_object(JSObject::Ordinary(ordinary))
+    }
+
+    /// Creates a script function. Constructors also get a fresh `prototype` object whose
+    /// `constructor` points back at the function. Generator functions aren't constructors but
+    /// still get a `prototype`, which the generator objects they return inherit from.
     pub fn new_function_object(
         call: Box<Stmt>,
         params: Vec<SymbolU32>,
         environment_id: usize,
+        kind: FunctionKind,
+        body_kind: BodyKind,
+        name: SymbolU32,
         interpreter: &mut Interpreter,
     ) -> usize {
-        let proto_id = interpreter.function_proto_id;
-        let object =
-            JSObject::Function(FunctionObject::new(call, environment_id, proto_id, params));
-        interpreter.add_object(object)
+        let proto_id = interpreter.intrinsics.function_proto_id;
+        let length = params.len() as f64;
+        let has_prototype = kind.is_constructor() && body_kind == BodyKind::Normal;
+        let mut function =
+            FunctionObject::new(call, environment_id, proto_id, params, kind, body_kind);
+        add_function_name_and_length(&mut function, name, length);
+        let object = JSObject::Function(function);
+        let function_id = interpreter.add_object(object);
+        if body_kind == BodyKind::Generator {
+            let generator_proto_id = interpreter.intrinsics.generator_proto_id;
+            let prototype_id =
+                JSObject::new_ordinary_object(vec![], true, Some(generator_proto_id), interpreter);
+            let prototype_value = JSValue::Object {
+                object_id: prototype_id,
+                kind: ObjectKind::Object,
+            };
+            let prototype_prop = ObjectPropertyBuilder::new(prototype_value)
+                .writable(true)
+                .build();
+            if let Ok(function) = interpreter.get_object_mut(function_id) {
+                function.define_property(get_or_intern_string(PROTOTYPE_NAME), prototype_prop);
+            }
+        }
+        if has_prototype {
+            let object_proto_id = interpreter.get_object_proto_id();
+            let prototype_id =
+                JSObject::new_ordinary_object(vec![], true, Some(object_proto_id), interpreter);
+            link_constructor_and_prototype(function_id, prototype_id, interpreter);
+        }
+        function_id
+    }
+
+    pub fn new_native_function(
+        name: &str,
+        length: usize,
+        native: NativeFunction,
+        native_construct: Option<NativeFunction>,
+        interpreter: &mut Interpreter,
+    ) -> usize {
+        let proto_id = interpreter.intrinsics.function_proto_id;
+        let env_id = interpreter.get_current_environment_handle();
+        let mut function = FunctionObject::new_native(native, native_construct, env_id, proto_id);
+        add_function_name_and_length(&mut function, get_or_intern_string(name), length as f64);
+        interpreter.add_object(JSObject::Function(function))
+    }
+
+    pub fn new_native_closure(
+        name: &str,
+        length: usize,
+        closure: NativeClosure,
+        captures: Vec<JSValue>,
+        interpreter: &mut Interpreter,
+    ) -> usize {
+        let proto_id = interpreter.intrinsics.function_proto_id;
+        let env_id = interpreter.get_current_environment_handle();
+        let mut function = FunctionObject::new_native_closure(closure, captures, env_id, proto_id);
+        add_function_name_and_length(&mut function, get_or_intern_string(name), length as f64);
+        interpreter.add_object(JSObject::Function(function))
+    }
+
+    pub(crate) fn new_host_function(
+        name: &str,
+        callback: HostCallback,
+        interpreter: &mut Interpreter,
+    ) -> usize {
+        let proto_id = interpreter.intrinsics.function_proto_id;
+        let env_id = interpreter.get_current_environment_handle();
+        let mut function = FunctionObject::new_host(callback, env_id, proto_id);
+        add_function_name_and_length(&mut function, get_or_intern_string(name), 0.0);
+        interpreter.add_object(JSObject::Function(function))
     }
 
     pub fn new_array_object(properties: Properties, interpreter: &mut Interpreter) -> usize {
@@ -67,6 +163,10 @@ impl JSObject {
         interpreter.add_object(object)
     }
 
+    pub fn new_proxy_object(proxy: ProxyObject, interpreter: &mut Interpreter) -> usize {
+        interpreter.add_object(JSObject::Proxy(proxy))
+    }
+
     pub fn create_object_proto() -> Self {
         let ordinary = OrdinaryObject::new(vec![], true, None);
         JSObject::Ordinary(ordinary)
@@ -81,34 +181,228 @@ impl JSObject {
         let proto_id = interpreter.get_object_proto_id();
         let mut global_object = OrdinaryObject::new(vec![], true, Some(proto_id));
 
-        let inf_str_id = get_or_intern_string(INFINITY_NAME);
-        let infinity = JSValue::new_number(&f64::INFINITY);
-        global_object.add_property_from_value(inf_str_id, infinity);
+        // the value properties of the global object can be neither written nor redefined
+        let constants = [
+            (INFINITY_NAME, JSValue::new_number(&f64::INFINITY)),
+            (NAN_NAME, JSValue::new_number(&f64::NAN)),
+            (UNDEFINED_NAME, JSValue::new_undefined()),
+        ];
+        for (name, value) in constants {
+            global_object.add_property(
+                get_or_intern_string(name),
+                ObjectPropertyBuilder::new(value)
+                    .writable(false)
+                    .configurable(false)
+                    .build(),
+            );
+        }
 
-        let nan_str_id = get_or_intern_string(NAN_NAME);
-        let nan = JSValue::new_number(&f64::NAN);
-        global_object.add_property_from_value(nan_str_id, nan);
+        let (console_id, console) = builtins::console::create_console_object(interpreter);
+        global_object.add_property(
+            console_id,
+            ObjectPropertyBuilder::new(console)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
 
-        let undef_str_id = get_or_intern_string(UNDEFINED_NAME);
-        let undefined = JSValue::new_undefined();
-        global_object.add_property_from_value(undef_str_id, undefined);
+        interpreter.intrinsics.iterator_proto_id =
+            builtins::iterator::create_iterator_prototype(interpreter);
+        interpreter.intrinsics.generator_proto_id =
+            builtins::generator::create_generator_prototype(interpreter);
 
-        let (is_finite_id, is_finite) = JSObject::new_built_in_fn(
-            IS_FINITE_NAME,
-            FunctionObject::create_is_finite,
-            interpreter,
+        let (object_id, object_ctor) = builtins::object::create_object_constructor(interpreter);
+        global_object.add_property(
+            object_id,
+            ObjectPropertyBuilder::new(object_ctor)
+                .writable(true)
+                .configurable(true)
+                .build(),
         );
-        global_object.add_property(is_finite_id, ObjectPropertyBuilder::new(is_finite).build());
 
-        let (console_id, console_obj) = JSObject::new_built_in_obj(
-            CONSOLE_NAME,
-            vec![
-                JSObject::new_built_in_fn(LOG_NAME, FunctionObject::create_log, interpreter),
-                JSObject::new_built_in_fn(ERROR_NAME, FunctionObject::create_error, interpreter),
-            ],
+        let (promise_id, promise_ctor) = builtins::promise::create_promise_constructor(interpreter);
+        global_object.add_property(
+            promise_id,
+            ObjectPropertyBuilder::new(promise_ctor)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        for (number_id, number) in builtins::number::create_number_constructor(interpreter) {
+            global_object.add_property(
+                number_id,
+                ObjectPropertyBuilder::new(number)
+                    .writable(true)
+                    .configurable(true)
+                    .build(),
+            );
+        }
+
+        let (bigint_id, bigint) = builtins::bigint::create_bigint_constructor(interpreter);
+        global_object.add_property(
+            bigint_id,
+            ObjectPropertyBuilder::new(bigint)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (symbol_id, symbol) = builtins::symbol::create_symbol_constructor(interpreter);
+        global_object.add_property(
+            symbol_id,
+            ObjectPropertyBuilder::new(symbol)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (string_id, string) = builtins::string::create_string_constructor(interpreter);
+        global_object.add_property(
+            string_id,
+            ObjectPropertyBuilder::new(string)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (regexp_id, regexp) = builtins::regexp::create_regexp_constructor(interpreter);
+        global_object.add_property(
+            regexp_id,
+            ObjectPropertyBuilder::new(regexp)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (map_id, map) = builtins::map::create_map_constructor(interpreter);
+        global_object.add_property(
+            map_id,
+            ObjectPropertyBuilder::new(map)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (set_id, set) = builtins::set::create_set_constructor(interpreter);
+        global_object.add_property(
+            set_id,
+            ObjectPropertyBuilder::new(set)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        for (weak_id, weak) in builtins::weak::create_weak_constructors(interpreter) {
+            global_object.add_property(
+                weak_id,
+                ObjectPropertyBuilder::new(weak)
+                    .writable(true)
+                    .configurable(true)
+                    .build(),
+            );
+        }
+
+        let (date_id, date) = builtins::date::create_date_constructor(interpreter);
+        global_object.add_property(
+            date_id,
+            ObjectPropertyBuilder::new(date)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (array_buffer_id, array_buffer) =
+            builtins::array_buffer::create_array_buffer_constructor(interpreter);
+        global_object.add_property(
+            array_buffer_id,
+            ObjectPropertyBuilder::new(array_buffer)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        for (typed_array_id, typed_array) in
+            builtins::typed_array::create_typed_array_constructors(interpreter)
+        {
+            global_object.add_property(
+                typed_array_id,
+                ObjectPropertyBuilder::new(typed_array)
+                    .writable(true)
+                    .configurable(true)
+                    .build(),
+            );
+        }
+
+        let (data_view_id, data_view) =
+            builtins::data_view::create_data_view_constructor(interpreter);
+        global_object.add_property(
+            data_view_id,
+            ObjectPropertyBuilder::new(data_view)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (math_id, math) = builtins::math::create_math_object(interpreter);
+        global_object.add_property(
+            math_id,
+            ObjectPropertyBuilder::new(math)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (proxy_id, proxy) = builtins::proxy::create_proxy_constructor(interpreter);
+        global_object.add_property(
+            proxy_id,
+            ObjectPropertyBuilder::new(proxy)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (reflect_id, reflect) = builtins::reflect::create_reflect_object(interpreter);
+        global_object.add_property(
+            reflect_id,
+            ObjectPropertyBuilder::new(reflect)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        let (json_id, json) = builtins::json::create_json_object(interpreter);
+        global_object.add_property(
+            json_id,
+            ObjectPropertyBuilder::new(json)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
+
+        for (timer_id, timer) in builtins::timers::create_timer_functions(interpreter) {
+            global_object.add_property(
+                timer_id,
+                ObjectPropertyBuilder::new(timer)
+                    .writable(true)
+                    .configurable(true)
+                    .build(),
+            );
+        }
+
+        let (queue_microtask_id, queue_microtask) = JSObject::new_native_fn(
+            "queueMicrotask",
+            1,
+            builtins::promise::queue_microtask,
             interpreter,
         );
-        global_object.add_property(console_id, ObjectPropertyBuilder::new(console_obj).build());
+        global_object.add_property(
+            queue_microtask_id,
+            ObjectPropertyBuilder::new(queue_microtask)
+                .writable(true)
+                .configurable(true)
+                .build(),
+        );
 
         let global_object = JSObject::Ordinary(global_object);
         let obj_id = interpreter.add_object(global_object);
@@ -120,145 +414,600 @@ impl JSObject {
         interpreter.new_variable(global_this_id, false, value);
     }
 
-    pub fn to_primitive(
-        &self,
-        hint: PreferredType,
-        interpreter: &mut Interpreter,
-    ) -> JSResult<JSValue> {
+    pub fn get_data(&self) -> Option<&ObjectData> {
+        match self {
+            JSObject::Ordinary(ordinary_object) => Some(ordinary_object.get_data()),
+            _ => None,
+        }
+    }
+
+    pub fn get_data_mut(&mut self) -> Option<&mut ObjectData> {
         match self {
-            JSObject::Ordinary(ordinary_object) => ordinary_object.to_primitive(hint, interpreter),
-            JSObject::Function(function_object) => function_object.to_primitive(hint),
-            JSObject::Array(array) => array.to_primitive(hint, interpreter),
+            JSObject::Ordinary(ordinary_object) => Some(ordinary_object.get_data_mut()),
+            _ => None,
         }
     }
 
     pub fn is_function(&self) -> bool {
         match self {
             JSObject::Function(_) => true,
+            JSObject::Proxy(proxy) => proxy.is_callable(),
             _ => false,
         }
     }
 
-    pub fn value_of(&self) -> JSResult<JSValue> {
+    pub fn is_constructor(&self) -> bool {
         match self {
-            JSObject::Ordinary(ordinary) => ordinary.value_of(),
-            JSObject::Function(function) => todo!(),
-            JSObject::Array(array) => todo!(),
+            JSObject::Function(function) => function.is_constructor(),
+            JSObject::Proxy(proxy) => proxy.is_constructor(),
+            _ => false,
         }
     }
 
+    pub fn kind(&self) -> ObjectKind {
+        match self {
+            JSObject::Ordinary(_) => ObjectKind::Object,
+            JSObject::Function(_) => ObjectKind::Function,
+            JSObject::Array(_) => ObjectKind::Array,
+            JSObject::Proxy(proxy) => proxy.kind(),
+        }
+    }
+
+    /// `this`, the arguments and the result are rooted for the caller, native functions hold
+    /// on to them while running script.
     pub fn call(
         &self,
-        args: &Vec<Expr>,
-        name: Option<&SymbolU32>,
+        this: &JSValue,
+        args: &[JSValue],
         interpreter: &mut Interpreter,
     ) -> JSResult<JSValue> {
-        match self {
-            JSObject::Ordinary(ordinary_object) => ordinary_object.call(name.unwrap()),
-            JSObject::Function(object) => object.call(args, interpreter),
-            JSObject::Array(array) => todo!(),
-        }
+        interpreter.root_value(this);
+        args.iter().for_each(|arg| interpreter.root_value(arg));
+        let result = match self {
+            JSObject::Function(object) => object.call(this, args, interpreter),
+            JSObject::Proxy(proxy) if proxy.is_callable() => {
+                proxy::call(interpreter, *proxy, this, args)
+            }
+            _ => Err(JSError::new_type_error("object is not a function")),
+        }?;
+        interpreter.root_value(&result);
+        Ok(result)
     }
 
-    pub fn get_property(&self, key: &SymbolU32) -> Option<&ObjectProperty> {
+    pub fn construct(
+        &self,
+        this: &JSValue,
+        new_target: &JSValue,
+        args: &[JSValue],
+        interpreter: &mut Interpreter,
+    ) -> JSResult<JSValue> {
+        args.iter().for_each(|arg| interpreter.root_value(arg));
+        let result = match self {
+            JSObject::Function(object) if object.is_constructor() => {
+                object.construct(this, new_target, args, interpreter)
+            }
+            // a proxy's [[Construct]] never sees the `this` made for ordinary constructors
+            JSObject::Proxy(proxy) if proxy.is_constructor() => {
+                proxy::construct(interpreter, *proxy, args, new_target)
+            }
+            _ => Err(JSError::new_type_error("object is not a constructor")),
+        }?;
+        interpreter.root_value(&result);
+        Ok(result)
+    }
+
+    /// The raw own property storage, which proxies don't have. Anything a script can observe
+    /// should go through the internal methods in `internal` instead.
+    pub fn get_property(&self, key: &PropertyKey) -> Option<&ObjectProperty> {
         match self {
             JSObject::Ordinary(ordinary_object) => ordinary_object.get_property(key),
-            JSObject::Function(function_object) => todo!(),
+            JSObject::Function(function_object) => function_object.get_property(key),
             JSObject::Array(array) => array.get_property(key),
+            JSObject::Proxy(_) => None,
         }
     }
 
-    pub fn get_property_mut(&mut self, key: &SymbolU32) -> Option<&mut ObjectProperty> {
+    pub fn get_property_mut(&mut self, key: &PropertyKey) -> Option<&mut ObjectProperty> {
         match self {
             JSObject::Ordinary(ordinary_object) => ordinary_object.get_property_mut(key),
-            JSObject::Function(function_object) => todo!(),
+            JSObject::Function(function_object) => function_object.get_property_mut(key),
             JSObject::Array(array) => array.get_property_mut(key),
+            JSObject::Proxy(_) => None,
         }
     }
 
-    pub fn add_property(&mut self, key: SymbolU32, value: JSValue) {
+    pub fn add_property(&mut self, key: impl Into<PropertyKey>, value: JSValue) {
         let prop = ObjectProperty::new_from_value(value);
+        self.define_property(key.into(), prop);
+    }
+
+    /// Inserts or replaces the property without any attribute validation.
+    pub fn define_property(&mut self, key: impl Into<PropertyKey>, prop: ObjectProperty) {
+        let key = key.into();
         match self {
             JSObject::Ordinary(ordinary_object) => ordinary_object.add_property(key, prop),
-            JSObject::Function(function_object) => todo!(),
-            JSObject::Array(array) => todo!(),
+            JSObject::Function(function_object) => function_object.add_property(key, prop),
+            JSObject::Array(array) => array.add_property(key, prop),
+            JSObject::Proxy(_) => {}
         }
     }
 
-    pub fn debug(&self, interpreter: &mut Interpreter) -> String {
+    pub fn remove_property(&mut self, key: &PropertyKey) -> Option<ObjectProperty> {
         match self {
-            JSObject::Ordinary(ordinary_object) => ordinary_object.debug(interpreter),
-            JSObject::Function(function_object) => function_object.debug(interpreter),
-            JSObject::Array(array) => todo!(),
+            JSObject::Ordinary(ordinary_object) => ordinary_object.remove_property(key),
+            JSObject::Function(function_object) => function_object.remove_property(key),
+            JSObject::Array(array) => array.remove_property(key),
+            JSObject::Proxy(_) => None,
         }
     }
 
-    pub fn new_built_in_fn<F>(name: &str, f: F, interpreter: &mut Interpreter) -> Property
-    where
-        F: Fn(&mut Interpreter) -> FunctionObject,
-    {
-        let str_id = get_or_intern_string(name);
-        debug!("{name} has id {str_id:?}");
-        let fn_object = f(interpreter);
-        let js_object = JSObject::Function(fn_object);
-        let object_id = interpreter.add_object(js_object);
-        let js_value = JSValue::Object {
-            object_id,
-            kind: ObjectKind::Function,
+    pub fn property_keys(&self) -> Vec<PropertyKey> {
+        match self {
+            JSObject::Ordinary(ordinary_object) => ordinary_object.property_keys(),
+            JSObject::Function(function_object) => function_object.property_keys(),
+            JSObject::Array(array) => array.property_keys(),
+            JSObject::Proxy(_) => vec![],
+        }
+    }
+
+    pub fn get_prototype(&self) -> Option<usize> {
+        match self {
+            JSObject::Ordinary(ordinary_object) => ordinary_object.get_prototype(),
+            JSObject::Function(function_object) => function_object.get_prototype(),
+            JSObject::Array(array) => array.get_prototype(),
+            JSObject::Proxy(_) => None,
+        }
+    }
+
+    pub fn set_prototype(&mut self, proto: Option<usize>) {
+        match self {
+            JSObject::Ordinary(ordinary_object) => ordinary_object.set_prototype(proto),
+            JSObject::Function(function_object) => function_object.set_prototype(proto),
+            JSObject::Array(array) => array.set_prototype(proto),
+            JSObject::Proxy(_) => {}
+        }
+    }
+
+    pub fn is_extensible(&self) -> bool {
+        match self {
+            JSObject::Ordinary(ordinary_object) => ordinary_object.is_extensible(),
+            JSObject::Function(function_object) => function_object.is_extensible(),
+            JSObject::Array(array) => array.is_extensible(),
+            JSObject::Proxy(_) => false,
+        }
+    }
+
+    pub fn prevent_extensions(&mut self) {
+        match self {
+            JSObject::Ordinary(ordinary_object) => ordinary_object.prevent_extensions(),
+            JSObject::Function(function_object) => function_object.prevent_extensions(),
+            JSObject::Array(array) => array.prevent_extensions(),
+            JSObject::Proxy(_) => {}
+        }
+    }
+
+    /// Roughly what the properties and internal slots take up beyond the object itself.
+    pub fn estimated_size(&self) -> usize {
+        let properties = self.property_keys().len() * size_of::<(PropertyKey, ObjectProperty)>();
+        let data = match self.get_data() {
+            Some(ObjectData::ArrayBuffer(state)) => state.byte_length(),
+            Some(ObjectData::Map(table)) => table.len() * size_of::<(JSValue, JSValue)>(),
+            Some(ObjectData::Set(table)) => table.len() * size_of::<JSValue>(),
+            _ => 0,
         };
-        (str_id, js_value)
+        properties + data
     }
 
-    fn new_built_in_obj(
+    /// A function implemented directly in Rust, with the key to store it under.
+    pub fn new_native_fn(
         name: &str,
-        properties: Properties,
+        length: usize,
+        native: NativeFunction,
         interpreter: &mut Interpreter,
     ) -> Property {
         let str_id = get_or_intern_string(name);
-        let object_id = JSObject::new_ordinary_object(properties, true, None, interpreter);
+        let object_id = JSObject::new_native_function(name, length, native, None, interpreter);
         let js_value = JSValue::Object {
             object_id,
-            kind: ObjectKind::Object,
+            kind: ObjectKind::Function,
         };
         (str_id, js_value)
     }
 }
 
-pub fn get_object_property<'a>(
-    interpreter: &'a mut Interpreter,
+fn add_function_name_and_length(function: &mut FunctionObject, name: SymbolU32, length: f64) {
+    let length_prop = ObjectPropertyBuilder::new(JSValue::new_number(&length))
+        .configurable(true)
+        .build();
+    function.add_property(get_or_intern_string(LENGTH_NAME), length_prop);
+    let name_prop = ObjectPropertyBuilder::new(JSValue::new_string(&name))
+        .configurable(true)
+        .build();
+    function.add_property(get_or_intern_string(NAME_NAME), name_prop);
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-ordinaryownpropertykeys
+/// Orders own keys the way the spec enumerates them: integer indices ascending, then string keys
+/// in insertion order, then symbols in insertion order.
+pub fn ordinary_own_property_keys<'a>(
+    keys: impl Iterator<Item = &'a PropertyKey>,
+) -> Vec<PropertyKey> {
+    let mut indices = vec![];
+    let mut strings = vec![];
+    let mut symbols = vec![];
+    for key in keys {
+        match array::array_index(key) {
+            Some(index) => indices.push((index, *key)),
+            None if key.is_symbol() => symbols.push(*key),
+            None => strings.push(*key),
+        }
+    }
+    indices.sort_by_key(|(index, _)| *index);
+    indices
+        .into_iter()
+        .map(|(_, key)| key)
+        .chain(strings)
+        .chain(symbols)
+        .collect()
+}
+
+/// Sets `F.prototype = P` and `P.constructor = F` with the attributes ordinary functions use.
+pub fn link_constructor_and_prototype(
+    function_id: ObjectId,
+    prototype_id: ObjectId,
+    interpreter: &mut Interpreter,
+) {
+    let function_value = JSValue::Object {
+        object_id: function_id,
+        kind: ObjectKind::Function,
+    };
+    let prototype_value = JSValue::Object {
+        object_id: prototype_id,
+        kind: ObjectKind::Object,
+    };
+    let constructor_prop = ObjectPropertyBuilder::new(function_value)
+        .writable(true)
+        .configurable(true)
+        .build();
+    if let Ok(prototype) = interpreter.get_object_mut(prototype_id) {
+        prototype.define_property(get_or_intern_string(CONSTRUCTOR_NAME), constructor_prop);
+    }
+    let prototype_prop = ObjectPropertyBuilder::new(prototype_value)
+        .writable(true)
+        .build();
+    if let Ok(function) = interpreter.get_object_mut(function_id) {
+        function.define_property(get_or_intern_string(PROTOTYPE_NAME), prototype_prop);
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getv
+/// [[Get]] on any value. Primitives look their properties up on the prototype of their wrapper
+/// objects.
+pub fn get_property_value(
+    interpreter: &mut Interpreter,
     object_value: &JSValue,
-    key: SymbolU32,
-) -> JSResult<&'a ObjectProperty> {
-    if let JSValue::Object { object_id, kind: _ } = object_value {
-        let object = interpreter.get_object(*object_id)?;
-        let key_string = get_string_from_pool(&key).unwrap();
+    key: impl Into<PropertyKey>,
+) -> JSResult<JSValue> {
+    let key = key.into();
+    if let JSValue::String { data } = object_value
+        && let Some(value) = string_own_property(*data, &key)
+    {
+        return Ok(value);
+    }
+    let start = match object_value {
+        JSValue::Object { object_id, kind: _ } => *object_id,
+        JSValue::Number { .. } => interpreter.intrinsics.number_proto_id,
+        JSValue::BigInt { .. } => interpreter.intrinsics.bigint_proto_id,
+        JSValue::Symbol { .. } => interpreter.intrinsics.symbol_proto_id,
+        JSValue::String { .. } => interpreter.intrinsics.string_proto_id,
+        _ => return Ok(JSValue::Undefined),
+    };
+    internal::get(interpreter, start, &key, object_value)
+}
 
-        let property = object.get_property(&key);
-        if let Some(prop) = property {
-            return Ok(prop);
+// https://262.ecma-international.org/15.0/index.html#sec-ordinarytoprimitive
+/// Calls `valueOf` and `toString`, in the order `hint` asks for, until one returns a primitive.
+pub fn ordinary_to_primitive(
+    interpreter: &mut Interpreter,
+    object: &JSValue,
+    hint: PreferredType,
+) -> JSResult<JSValue> {
+    let method_names = match hint {
+        PreferredType::String => ["toString", "valueOf"],
+        PreferredType::Number => ["valueOf", "toString"],
+    };
+    for name in method_names {
+        let key = get_or_intern_string(name);
+        let method = get_property_value(interpreter, object, key)?;
+        if !interpreter.is_callable(&method) {
+            continue;
+        }
+        let result =
+            if name == "toString" && is_default_array_to_string(interpreter, object, &method)? {
+                array_to_string(interpreter, object)?
+            } else {
+                interpreter.call_function(&method, object, &[])?
+            };
+        if !result.is_object() {
+            return Ok(result);
         }
     }
+    Err(JSError::new_type_error(
+        "Cannot convert object to primitive value",
+    ))
+}
 
-    Err(JSError::new("Could not get object property"))
+/// Arrays find `Object.prototype.toString` for lack of an `Array.prototype`, unless script gave
+/// them one of their own they're joined the way `Array.prototype.toString` would.
+fn is_default_array_to_string(
+    interpreter: &mut Interpreter,
+    object: &JSValue,
+    method: &JSValue,
+) -> JSResult<bool> {
+    if !matches!(
+        object,
+        JSValue::Object {
+            kind: ObjectKind::Array,
+            ..
+        }
+    ) {
+        return Ok(false);
+    }
+    let object_proto = JSValue::Object {
+        object_id: interpreter.intrinsics.object_proto_id,
+        kind: ObjectKind::Object,
+    };
+    let key = get_or_intern_string("toString");
+    let default = get_property_value(interpreter, &object_proto, key)?;
+    Ok(is_same_value(method, &default))
 }
 
-pub fn get_object_property_mut<'a>(
-    interpreter: &'a mut Interpreter,
+// https://262.ecma-international.org/15.0/index.html#sec-array.prototype.join
+/// The elements joined with commas, with holes, `undefined` and `null` left empty.
+fn array_to_string(interpreter: &mut Interpreter, array: &JSValue) -> JSResult<JSValue> {
+    let length_key = get_or_intern_string(LENGTH_NAME);
+    let length = get_property_value(interpreter, array, length_key)?
+        .to_length(interpreter)?
+        .get_number() as usize;
+    let mut joined = String::new();
+    for index in 0..length {
+        if index > 0 {
+            joined.push(',');
+        }
+        let key = get_or_intern_string(&index.to_string());
+        let element = get_property_value(interpreter, array, key)?;
+        if element.is_undefined() || element.is_null() {
+            continue;
+        }
+        let string = element.to_string(interpreter)?;
+        joined.push_str(&get_string_from_pool(&string).unwrap_or_default());
+    }
+    Ok(JSValue::new_string(&get_or_intern_string(&joined)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-string-exotic-objects-getownproperty-p
+/// `length` and the indices of a string primitive, counted in UTF-16 code units.
+fn string_own_property(string: SymbolU32, key: &PropertyKey) -> Option<JSValue> {
+    let name = get_string_from_pool(&key.as_string()?)?;
+    let units = get_string_from_pool(&string)?
+        .encode_utf16()
+        .collect::<Vec<_>>();
+    if name == LENGTH_NAME {
+        return Some(JSValue::new_number(&(units.len() as f64)));
+    }
+    // canonical numeric strings only, "01" or "+1" are ordinary keys
+    let index = name
+        .parse::<usize>()
+        .ok()
+        .filter(|index| index.to_string() == name)?;
+    let unit = *units.get(index)?;
+    let string = String::from_utf16_lossy(&[unit]);
+    Some(JSValue::new_string(&get_or_intern_string(&string)))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-set-o-p-v-throw
+/// Returns `false` when the write was rejected by a non-writable property, a missing setter or a
+/// non-extensible object. Sloppy mode callers ignore the result.
+pub fn set_property_value(
+    interpreter: &mut Interpreter,
     object_value: &JSValue,
-    key: SymbolU32,
-) -> JSResult<&'a mut ObjectProperty> {
-    if let JSValue::Object { object_id, kind: _ } = object_value {
-        let object = interpreter.get_object_mut(*object_id)?;
-        let property_opt = object.get_property_mut(&key);
-        if let Some(property) = property_opt {
-            return Ok(property);
+    key: impl Into<PropertyKey>,
+    value: JSValue,
+) -> JSResult<bool> {
+    let object_id = object_value.get_object_id()?;
+    internal::set(interpreter, object_id, &key.into(), value, object_value)
+}
+
+/// A partially populated property descriptor, as produced by `ToPropertyDescriptor`.
+#[derive(Clone, Debug, Default)]
+pub struct PropertyDescriptor {
+    pub value: Option<JSValue>,
+    pub writable: Option<bool>,
+    pub get: Option<JSValue>,
+    pub set: Option<JSValue>,
+    pub enumerable: Option<bool>,
+    pub configurable: Option<bool>,
+}
+
+impl PropertyDescriptor {
+    pub fn is_accessor_descriptor(&self) -> bool {
+        self.get.is_some() || self.set.is_some()
+    }
+
+    pub fn is_data_descriptor(&self) -> bool {
+        self.value.is_some() || self.writable.is_some()
+    }
+
+    pub fn is_generic_descriptor(&self) -> bool {
+        !self.is_accessor_descriptor() && !self.is_data_descriptor()
+    }
+
+    /// Builds the property a missing key gets; unspecified attributes default to false.
+    pub fn to_property(&self) -> ObjectProperty {
+        if self.is_accessor_descriptor() {
+            return ObjectProperty::Accessor {
+                get: self.get.clone().filter(|g| !g.is_undefined()),
+                set: self.set.clone().filter(|s| !s.is_undefined()),
+                enumerable: self.enumerable.unwrap_or_default(),
+                configurable: self.configurable.unwrap_or_default(),
+            };
+        }
+        let builder = ObjectPropertyBuilder::new(self.value.clone().unwrap_or_default())
+            .writable(self.writable.unwrap_or_default())
+            .enumerable(self.enumerable.unwrap_or_default())
+            .configurable(self.configurable.unwrap_or_default());
+        builder.build()
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-iscompatiblepropertydescriptor
+/// Whether `desc` could be applied on top of `current`, or added to an object that is
+/// `extensible` when there is no current property.
+pub fn is_compatible_property_descriptor(
+    extensible: bool,
+    desc: &PropertyDescriptor,
+    current: Option<&ObjectProperty>,
+) -> bool {
+    let Some(current) = current else {
+        return extensible;
+    };
+    if current.is_configurable() {
+        return true;
+    }
+    if desc.configurable == Some(true) {
+        return false;
+    }
+    if desc
+        .enumerable
+        .is_some_and(|enumerable| enumerable != current.is_enumerable())
+    {
+        return false;
+    }
+    if !desc.is_generic_descriptor() && desc.is_accessor_descriptor() != current.is_accessor() {
+        return false;
+    }
+    match current {
+        ObjectProperty::Accessor { get, set, .. } => {
+            let changes_getter = desc
+                .get
+                .as_ref()
+                .is_some_and(|g| !same_accessor(g, get.as_ref().unwrap_or(&JSValue::Undefined)));
+            let changes_setter = desc
+                .set
+                .as_ref()
+                .is_some_and(|s| !same_accessor(s, set.as_ref().unwrap_or(&JSValue::Undefined)));
+            !changes_getter && !changes_setter
+        }
+        ObjectProperty::Data {
+            value, writable, ..
+        } => {
+            *writable
+                || (desc.writable != Some(true)
+                    && desc.value.as_ref().is_none_or(|v| is_same_value(v, value)))
         }
     }
+}
 
-    Err(JSError::new("Could not get object property"))
+// https://262.ecma-international.org/15.0/index.html#sec-validateandapplypropertydescriptor
+/// The ordinary [[DefineOwnProperty]]. Returns `false` when the descriptor is incompatible with
+/// the current property.
+fn ordinary_define_own_property(
+    interpreter: &mut Interpreter,
+    object_id: ObjectId,
+    key: PropertyKey,
+    desc: &PropertyDescriptor,
+) -> JSResult<bool> {
+    let object = interpreter.get_object(object_id)?;
+    // https://262.ecma-international.org/15.0/index.html#sec-array-exotic-objects-defineownproperty-p-desc
+    // an array whose length can't change can't grow either
+    if let JSObject::Array(array) = object
+        && let Some(index) = array_index(&key)
+        && index >= array.len()
+        && array
+            .get_property(&PropertyKey::from_name(LENGTH_NAME))
+            .is_some_and(|length| !length.is_writable())
+    {
+        return Ok(false);
+    }
+    let extensible = object.is_extensible();
+    let current = object.get_property(&key).cloned();
+    if !is_compatible_property_descriptor(extensible, desc, current.as_ref()) {
+        return Ok(false);
+    }
+    let Some(current) = current else {
+        let object = interpreter.get_object_mut(object_id)?;
+        object.define_property(key, desc.to_property());
+        return Ok(true);
+    };
+
+    let enumerable = desc.enumerable.unwrap_or(current.is_enumerable());
+    let configurable = desc.configurable.unwrap_or(current.is_configurable());
+    let updated = match current {
+        ObjectProperty::Data {
+            value, writable, ..
+        } if !desc.is_accessor_descriptor() => ObjectProperty::Data {
+            value: desc.value.clone().unwrap_or(value),
+            writable: desc.writable.unwrap_or(writable),
+            enumerable,
+            configurable,
+        },
+        ObjectProperty::Accessor { get, set, .. } if !desc.is_data_descriptor() => {
+            ObjectProperty::Accessor {
+                get: match &desc.get {
+                    Some(g) => Some(g.clone()).filter(|g| !g.is_undefined()),
+                    None => get,
+                },
+                set: match &desc.set {
+                    Some(s) => Some(s.clone()).filter(|s| !s.is_undefined()),
+                    None => set,
+                },
+                enumerable,
+                configurable,
+            }
+        }
+        // switching between data and accessor keeps only the shared attributes
+        _ => {
+            let mut converted = desc.clone();
+            converted.enumerable = Some(enumerable);
+            converted.configurable = Some(configurable);
+            converted.to_property()
+        }
+    };
+    let mut updated = updated;
+    let is_array = matches!(interpreter.get_object(object_id)?, JSObject::Array(_));
+    if is_array
+        && key == PropertyKey::from_name(LENGTH_NAME)
+        && let (Some(len), ObjectProperty::Data { value, .. }) = (&desc.value, &mut updated)
+    {
+        let new_len = len.to_length(interpreter)?.get_number();
+        if let JSObject::Array(array) = interpreter.get_object_mut(object_id)? {
+            array.set_len(new_len as usize);
+        }
+        *value = JSValue::new_number(&new_len);
+    }
+    let object = interpreter.get_object_mut(object_id)?;
+    object.define_property(key, updated);
+    Ok(true)
+}
+
+fn same_accessor(left: &JSValue, right: &JSValue) -> bool {
+    match (left, right) {
+        (JSValue::Undefined, JSValue::Undefined) => true,
+        (JSValue::Object { object_id: l, .. }, JSValue::Object { object_id: r, .. }) => l == r,
+        _ => false,
+    }
 }
 
-struct ObjectPropertyBuilder {
+// https://262.ecma-international.org/15.0/index.html#sec-samevalue
+pub fn is_same_value(left: &JSValue, right: &JSValue) -> bool {
+    match (left, right) {
+        (JSValue::Number { data: l }, JSValue::Number { data: r }) => same_value(*l, *r),
+        (JSValue::Object { object_id: l, .. }, JSValue::Object { object_id: r, .. }) => l == r,
+        _ => left == right,
+    }
+}
+
+pub struct ObjectPropertyBuilder {
     value: JSValue,
     writable: Option<bool>,
     enumerable: Option<bool>,
@@ -266,7 +1015,7 @@ struct ObjectPropertyBuilder {
 }
 
 impl ObjectPropertyBuilder {
-    fn new(value: JSValue) -> Self {
+    pub fn new(value: JSValue) -> Self {
         Self {
             value,
             writable: None,
@@ -275,22 +1024,22 @@ impl ObjectPropertyBuilder {
         }
     }
 
-    fn writable(mut self, w: bool) -> Self {
+    pub fn writable(mut self, w: bool) -> Self {
         self.writable = Some(w);
         self
     }
 
-    fn enumerable(mut self, w: bool) -> Self {
+    pub fn enumerable(mut self, w: bool) -> Self {
         self.enumerable = Some(w);
         self
     }
 
-    fn configurable(mut self, w: bool) -> Self {
+    pub fn configurable(mut self, w: bool) -> Self {
         self.configurable = Some(w);
         self
     }
 
-    fn build(self) -> ObjectProperty {
+    pub fn build(self) -> ObjectProperty {
         ObjectProperty::Data {
             value: self.value,
             writable: self.writable.unwrap_or_default(),
@@ -300,6 +1049,53 @@ impl ObjectPropertyBuilder {
     }
 }
 
+impl JSObject {
+    /// What the object refers to outside of its `slots`.
+    pub fn trace_header(&self, tracer: &mut Tracer) {
+        match self {
+            JSObject::Ordinary(ordinary_object) => ordinary_object.trace_header(tracer),
+            JSObject::Function(function_object) => function_object.trace_header(tracer),
+            JSObject::Array(array) => array.trace_header(tracer),
+            JSObject::Proxy(proxy) => proxy.trace(tracer),
+        }
+    }
+
+    /// The containers of the object the collector traces a card at a time: its properties,
+    /// and the entries of a Map or Set.
+    pub fn slots(&self) -> [Option<&dyn Slots>; 2] {
+        match self {
+            JSObject::Ordinary(ordinary_object) => [
+                Some(ordinary_object.properties()),
+                ordinary_object.get_data().slots(),
+            ],
+            JSObject::Function(function_object) => [Some(function_object.properties()), None],
+            JSObject::Array(array) => [Some(array.properties()), None],
+            JSObject::Proxy(_) => [None, None],
+        }
+    }
+
+    pub fn slots_mut(&mut self) -> [Option<&mut dyn Slots>; 2] {
+        match self {
+            JSObject::Ordinary(ordinary_object) => {
+                let (properties, data) = ordinary_object.parts_mut();
+                [Some(properties), data.slots_mut()]
+            }
+            JSObject::Function(function_object) => [Some(function_object.properties_mut()), None],
+            JSObject::Array(array) => [Some(array.properties_mut()), None],
+            JSObject::Proxy(_) => [None, None],
+        }
+    }
+}
+
+impl Trace for JSObject {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.trace_header(tracer);
+        for slots in self.slots().into_iter().flatten() {
+            slots.trace_slots(0..slots.slot_count(), tracer);
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ObjectProperty {
     Data {
@@ -308,9 +1104,9 @@ pub enum ObjectProperty {
         enumerable: bool,
         configurable: bool,
     },
-    Attribute {
-        get: Option<JSObject>,
-        set: Option<JSObject>,
+    Accessor {
+        get: Option<JSValue>,
+        set: Option<JSValue>,
         enumerable: bool,
         configurable: bool,
     },
@@ -326,6 +1122,15 @@ impl ObjectProperty {
         }
     }
 
+    pub fn new_accessor(get: Option<JSValue>, set: Option<JSValue>) -> Self {
+        Self::Accessor {
+            get,
+            set,
+            enumerable: true,
+            configurable: true,
+        }
+    }
+
     pub fn is_configurable(&self) -> bool {
         match self {
             ObjectProperty::Data {
@@ -334,7 +1139,7 @@ impl ObjectProperty {
                 enumerable: _,
                 configurable,
             } => *configurable,
-            ObjectProperty::Attribute {
+            ObjectProperty::Accessor {
                 get: _,
                 set: _,
                 enumerable: _,
@@ -343,6 +1148,38 @@ impl ObjectProperty {
         }
     }
 
+    pub fn is_enumerable(&self) -> bool {
+        match self {
+            ObjectProperty::Data { enumerable, .. } => *enumerable,
+            ObjectProperty::Accessor { enumerable, .. } => *enumerable,
+        }
+    }
+
+    pub fn is_writable(&self) -> bool {
+        match self {
+            ObjectProperty::Data { writable, .. } => *writable,
+            ObjectProperty::Accessor { .. } => false,
+        }
+    }
+
+    pub fn is_accessor(&self) -> bool {
+        matches!(self, ObjectProperty::Accessor { .. })
+    }
+
+    /// How accessors are shown when printing, matching node's `[Getter/Setter]` notation.
+    pub fn accessor_label(&self) -> &'static str {
+        match self {
+            ObjectProperty::Accessor {
+                get: Some(_),
+                set: Some(_),
+                ..
+            } => "[Getter/Setter]",
+            ObjectProperty::Accessor { get: Some(_), .. } => "[Getter]",
+            ObjectProperty::Accessor { set: Some(_), .. } => "[Setter]",
+            _ => "undefined",
+        }
+    }
+
     pub fn get_value(&self) -> JSResult<&JSValue> {
         match self {
             Self::Data {
@@ -351,21 +1188,60 @@ impl ObjectProperty {
                 enumerable: _,
                 configurable: _,
             } => return Ok(value),
-            _ => unimplemented!(),
+            _ => Err(JSError::new(
+                "Accessor properties must be read through their getter",
+            )),
         }
     }
 
-    pub fn set_value(&mut self, value: JSValue) {
-        let old_value = match self {
+    /// Overwrites a data property's value. Returns `false` when the property is read-only or an
+    /// accessor, in which case nothing changes. The owning object was handed out through
+    /// `Heap::get_object_mut`, which already ran the collector's write barrier for it.
+    pub fn set_value(&mut self, value: JSValue) -> bool {
+        match self {
             Self::Data {
-                value,
+                value: old_value,
+                writable: true,
+                ..
+            } => {
+                *old_value = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn freeze(&mut self) {
+        match self {
+            ObjectProperty::Data {
                 writable,
-                enumerable,
                 configurable,
-            } => value,
-            _ => unimplemented!(),
-        };
-        *old_value = value;
+                ..
+            } => {
+                *writable = false;
+                *configurable = false;
+            }
+            ObjectProperty::Accessor { configurable, .. } => *configurable = false,
+        }
+    }
+
+    pub fn seal(&mut self) {
+        match self {
+            ObjectProperty::Data { configurable, .. } => *configurable = false,
+            ObjectProperty::Accessor { configurable, .. } => *configurable = false,
+        }
+    }
+}
+
+impl Trace for ObjectProperty {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            ObjectProperty::Data { value, .. } => value.trace(tracer),
+            ObjectProperty::Accessor { get, set, .. } => {
+                get.trace(tracer);
+                set.trace(tracer);
+            }
+        }
     }
 }
 
@@ -375,6 +1251,7 @@ impl std::fmt::Display for JSObject {
             JSObject::Ordinary(ordinary_object) => write!(f, "{ordinary_object}"),
             JSObject::Function(function_object) => write!(f, "{function_object}"),
             JSObject::Array(array) => todo!(),
+            JSObject::Proxy(proxy) => write!(f, "Proxy: {proxy:?}"),
         }
     }
 }
@@ -1,14 +1,15 @@
-use std::collections::HashMap;
-
-use string_interner::{Symbol, symbol::SymbolU32};
+use string_interner::symbol::SymbolU32;
 
 use crate::{
-    Interpreter, debug_value,
+    Interpreter,
     errors::JSError,
-    global::{get_or_intern_string, get_string_from_pool},
+    global::get_string_from_pool,
+    heap::{Trace, Tracer},
     values::{
-        JSResult, JSValue, PreferredType,
-        objects::{ObjectProperty, Properties, TO_PRIMITIVE_SYM},
+        JSResult, JSValue, PropertyKey,
+        objects::{
+            ObjectProperty, Properties, PropertyMap, data::ObjectData, ordinary_own_property_keys,
+        },
     },
 };
 
@@ -16,20 +17,30 @@ use crate::{
 pub struct OrdinaryObject {
     extensible: bool,
     prototype: Option<usize>,
-    properties: HashMap<SymbolU32, ObjectProperty>,
+    properties: PropertyMap,
+    data: ObjectData,
 }
 
 impl OrdinaryObject {
     pub fn new(properties: Properties, extensible: bool, proto: Option<usize>) -> Self {
-        let map = HashMap::from_iter(
-            properties
-                .into_iter()
-                .map(|(k, v)| (k, ObjectProperty::new_from_value(v))),
-        );
+        let map = properties
+            .into_iter()
+            .map(|(k, v)| (k.into(), ObjectProperty::new_from_value(v)))
+            .collect();
         Self {
             extensible,
             prototype: proto,
             properties: map,
+            data: ObjectData::None,
+        }
+    }
+
+    pub fn new_with_data(data: ObjectData, proto: Option<usize>) -> Self {
+        Self {
+            extensible: true,
+            prototype: proto,
+            properties: PropertyMap::new(),
+            data,
         }
     }
 
@@ -42,98 +53,71 @@ impl OrdinaryObject {
         Self {
             extensible,
             prototype: proto,
-            properties: HashMap::from_iter(properties),
+            properties: properties.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+            data: ObjectData::None,
         }
     }
 
-    pub fn to_primitive(
-        &self,
-        hint: PreferredType,
-        interpreter: &mut Interpreter,
-    ) -> JSResult<JSValue> {
-        let prim_sym = get_or_intern_string(TO_PRIMITIVE_SYM);
-        let maybe_property = self.properties.get(&prim_sym);
-        match maybe_property {
-            Some(_property) => {
-                todo!();
-            }
-            None => {
-                let method_names = match hint {
-                    PreferredType::Number => vec!["value_of", "to_string"],
-                    PreferredType::String => vec!["to_string", "value_of"],
-                };
-                for method in method_names {
-                    if method == "value_of" {
-                        let result = self.value_of()?;
-                        if !result.is_object() {
-                            return Ok(result);
-                        }
-                    }
-                    if method == "to_string" {
-                        return self.to_string(interpreter);
-                    }
-                }
-                let error = JSError::new_function_type_error("unknown");
-                return Err(error);
-            }
-        }
+    pub fn get_data(&self) -> &ObjectData {
+        &self.data
+    }
+
+    pub fn get_data_mut(&mut self) -> &mut ObjectData {
+        &mut self.data
+    }
+
+    pub fn properties(&self) -> &PropertyMap {
+        &self.properties
+    }
+
+    pub fn properties_mut(&mut self) -> &mut PropertyMap {
+        &mut self.properties
+    }
+
+    /// The properties and the internal slots at once, for the collector.
+    pub fn parts_mut(&mut self) -> (&mut PropertyMap, &mut ObjectData) {
+        (&mut self.properties, &mut self.data)
     }
 
-    pub fn to_string(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
-        let values = self
-            .properties
-            .iter()
-            .map(|(key, prop)| {
-                let key = get_string_from_pool(key).unwrap();
-                let val = prop.get_value()?;
-                let res = val.to_string(interpreter)?;
-                let string = get_string_from_pool(&res)
-                    .expect("An object has a value that doesn't exist in the string pool?");
-                Ok(format!("{key}: {string}"))
-            })
-            .collect::<JSResult<Vec<String>>>()?
-            .join(", ");
-        let values = if values.len() > 70 {
-            let mut new_values = String::new();
-            let mut found = false;
-            for c in values.chars() {
-                if found {
-                    new_values.push('\n');
-                    found = false;
-                }
-                if c == ',' || c == '{' || c == '}' {
-                    found = true;
-                }
-                new_values.push(c);
-            }
-            new_values
-        } else {
-            values
-        };
-        let sym = get_or_intern_string(&format!("{{{values}}}"));
-        Ok(JSValue::new_string(&sym))
-    }
-
-    pub fn value_of(&self) -> JSResult<JSValue> {
-        // Ok(JSValue::object_shallow_copy(self.id))
-        todo!()
-    }
-
-    pub fn get_property(&self, key: &SymbolU32) -> Option<&ObjectProperty> {
+    pub fn get_property(&self, key: &PropertyKey) -> Option<&ObjectProperty> {
         self.properties.get(key)
     }
 
-    pub fn get_property_mut(&mut self, key: &SymbolU32) -> Option<&mut ObjectProperty> {
+    pub fn get_property_mut(&mut self, key: &PropertyKey) -> Option<&mut ObjectProperty> {
         self.properties.get_mut(key)
     }
 
-    pub fn add_property(&mut self, key: SymbolU32, value: ObjectProperty) {
-        self.properties.insert(key, value);
+    pub fn add_property(&mut self, key: impl Into<PropertyKey>, value: ObjectProperty) {
+        self.properties.insert(key.into(), value);
+    }
+
+    pub fn remove_property(&mut self, key: &PropertyKey) -> Option<ObjectProperty> {
+        self.properties.shift_remove(key)
+    }
+
+    pub fn property_keys(&self) -> Vec<PropertyKey> {
+        ordinary_own_property_keys(self.properties.keys())
+    }
+
+    pub fn get_prototype(&self) -> Option<usize> {
+        self.prototype
+    }
+
+    pub fn set_prototype(&mut self, proto: Option<usize>) {
+        self.prototype = proto;
+    }
+
+    pub fn is_extensible(&self) -> bool {
+        self.extensible
+    }
+
+    pub fn prevent_extensions(&mut self) {
+        self.extensible = false;
     }
 
     pub fn add_property_from_value(&mut self, key: SymbolU32, value: JSValue) {
         self.properties
-            .insert(key, ObjectProperty::new_from_value(value));
+            .insert(key.into(), ObjectProperty::new_from_value(value));
     }
 
     pub fn call(&self, name: &SymbolU32) -> JSResult<JSValue> {
@@ -141,21 +125,18 @@ impl OrdinaryObject {
         let error = JSError::new(&format!("Uncaught TypeError: {s} is not a function"));
         Err(error)
     }
+}
 
-    pub fn debug(&self, interpreter: &mut Interpreter) -> String {
-        let mut out = String::new();
-        out.push_str("{");
-        let mut props = vec![];
-        for (string_id, object_property) in &self.properties {
-            let string = get_string_from_pool(&string_id).unwrap_or("UNKNOWN".to_string());
-            let value = object_property.get_value().unwrap().clone();
-            let prop = format!(" {string}: {}", debug_value(interpreter, &value));
-            props.push(prop);
+impl OrdinaryObject {
+    /// What the object refers to besides its properties and the entries of its Map or Set
+    /// data, which the collector traces as slots.
+    pub fn trace_header(&self, tracer: &mut Tracer) {
+        if let Some(prototype) = self.prototype {
+            tracer.mark(prototype);
+        }
+        if self.data.slots().is_none() {
+            self.data.trace(tracer);
         }
-        let prop_string = props.join(",");
-        out.push_str(&prop_string);
-        out.push_str(" }");
-        out
     }
 }
 
@@ -163,9 +144,7 @@ impl std::fmt::Display for OrdinaryObject {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Object: {{")?;
         for (key, value) in &self.properties {
-            let string = get_string_from_pool(&key).unwrap();
-            let key_index = key.to_usize();
-            writeln!(f, "\t ({key_index}){string}: {:?}", value.get_value())?;
+            writeln!(f, "\t {key}: {:?}", value.get_value())?;
         }
         writeln!(f, "}}")
     }
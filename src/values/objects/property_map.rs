@@ -0,0 +1,90 @@
+use std::ops::Range;
+
+use indexmap::{IndexMap, map};
+
+use crate::{
+    heap::{Slots, Trace, Tracer, WriteLog},
+    values::{PropertyKey, objects::ObjectProperty},
+};
+
+/// An object's own properties in insertion order. Handing out a property for writing logs its
+/// position, so that the collector can trace just the part of a large object that changed.
+#[derive(Clone, Debug, Default)]
+pub struct PropertyMap {
+    properties: IndexMap<PropertyKey, ObjectProperty>,
+    write_log: WriteLog,
+}
+
+impl PropertyMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    pub fn get(&self, key: &PropertyKey) -> Option<&ObjectProperty> {
+        self.properties.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &PropertyKey) -> Option<&mut ObjectProperty> {
+        let (index, _, property) = self.properties.get_full_mut(key)?;
+        self.write_log.record(index);
+        Some(property)
+    }
+
+    pub fn insert(&mut self, key: PropertyKey, value: ObjectProperty) {
+        let (index, _) = self.properties.insert_full(key, value);
+        self.write_log.record(index);
+    }
+
+    /// Removes the property, moving the ones after it up a place like `IndexMap::shift_remove`.
+    pub fn shift_remove(&mut self, key: &PropertyKey) -> Option<ObjectProperty> {
+        let (index, _, property) = self.properties.shift_remove_full(key)?;
+        self.write_log.record_from(index, self.properties.len());
+        Some(property)
+    }
+
+    pub fn keys(&self) -> map::Keys<'_, PropertyKey, ObjectProperty> {
+        self.properties.keys()
+    }
+
+    pub fn values(&self) -> map::Values<'_, PropertyKey, ObjectProperty> {
+        self.properties.values()
+    }
+}
+
+impl FromIterator<(PropertyKey, ObjectProperty)> for PropertyMap {
+    fn from_iter<I: IntoIterator<Item = (PropertyKey, ObjectProperty)>>(properties: I) -> Self {
+        Self {
+            properties: IndexMap::from_iter(properties),
+            write_log: WriteLog::default(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a PropertyMap {
+    type Item = (&'a PropertyKey, &'a ObjectProperty);
+    type IntoIter = map::Iter<'a, PropertyKey, ObjectProperty>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.properties.iter()
+    }
+}
+
+impl Slots for PropertyMap {
+    fn slot_count(&self) -> usize {
+        self.properties.len()
+    }
+
+    fn trace_slots(&self, slots: Range<usize>, tracer: &mut Tracer) {
+        if let Some(properties) = self.properties.get_range(slots) {
+            properties.values().for_each(|prop| prop.trace(tracer));
+        }
+    }
+
+    fn write_log(&mut self) -> &mut WriteLog {
+        &mut self.write_log
+    }
+}
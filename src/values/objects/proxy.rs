@@ -0,0 +1,667 @@
+//! Proxy exotic objects: every internal method looks up the matching trap on the handler and
+//! falls through to the target when there is none. Trap results are checked against the target
+//! so a proxy can't report anything the target itself would contradict.
+
+use crate::{
+    Interpreter,
+    builtins::{
+        create_array, list_from_array_like,
+        object::{from_property_descriptor, to_property_descriptor},
+    },
+    debug_value,
+    errors::JSError,
+    global::get_or_intern_string,
+    heap::{Trace, Tracer},
+    values::{
+        JSResult, JSValue, ObjectKind, PropertyKey, get_property_value,
+        objects::{
+            ObjectId, ObjectProperty, PropertyDescriptor, internal,
+            is_compatible_property_descriptor, is_same_value,
+        },
+    },
+};
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots
+#[derive(Clone, Copy, Debug)]
+pub struct ProxyObject {
+    target: Option<ObjectId>,
+    handler: Option<ObjectId>,
+    kind: ObjectKind,
+    constructor: bool,
+}
+
+impl Trace for ProxyObject {
+    fn trace(&self, tracer: &mut Tracer) {
+        for id in [self.target, self.handler].into_iter().flatten() {
+            tracer.mark(id);
+        }
+    }
+}
+
+impl ProxyObject {
+    /// `kind` and `constructor` mirror the target, so a proxy for a function is callable and a
+    /// proxy for an array is still an array.
+    pub fn new(target: ObjectId, handler: ObjectId, kind: ObjectKind, constructor: bool) -> Self {
+        Self {
+            target: Some(target),
+            handler: Some(handler),
+            kind,
+            constructor,
+        }
+    }
+
+    pub fn kind(&self) -> ObjectKind {
+        self.kind
+    }
+
+    pub fn is_callable(&self) -> bool {
+        self.kind == ObjectKind::Function
+    }
+
+    pub fn is_constructor(&self) -> bool {
+        self.constructor
+    }
+
+    pub fn target(&self) -> Option<ObjectId> {
+        self.target
+    }
+
+    pub fn handler(&self) -> Option<ObjectId> {
+        self.handler
+    }
+
+    pub fn revoke(&mut self) {
+        self.target = None;
+        self.handler = None;
+    }
+}
+
+/// The target and handler of a live proxy.
+struct Parts {
+    target_id: ObjectId,
+    target: JSValue,
+    handler: JSValue,
+}
+
+/// The target and handler of `proxy`, or the TypeError every trap throws once it is revoked.
+fn parts(interpreter: &Interpreter, proxy: ProxyObject, trap: &str) -> JSResult<Parts> {
+    let (Some(target_id), Some(handler_id)) = (proxy.target, proxy.handler) else {
+        return Err(JSError::new_type_error(&format!(
+            "Cannot perform '{trap}' on a proxy that has been revoked"
+        )));
+    };
+    Ok(Parts {
+        target_id,
+        target: internal::object_value(interpreter, target_id)?,
+        handler: internal::object_value(interpreter, handler_id)?,
+    })
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-getmethod
+/// The handler's trap, or `None` when it is undefined or null and the operation should go to
+/// the target.
+fn trap(interpreter: &mut Interpreter, parts: &Parts, name: &str) -> JSResult<Option<JSValue>> {
+    let trap = get_property_value(interpreter, &parts.handler, get_or_intern_string(name))?;
+    if trap.is_undefined() || trap.is_null() {
+        return Ok(None);
+    }
+    if !interpreter.is_callable(&trap) {
+        let trap = debug_value(interpreter, &trap);
+        return Err(JSError::new_type_error(&format!(
+            "'{name}' on proxy: trap {trap} is not a function"
+        )));
+    }
+    Ok(Some(trap))
+}
+
+fn call_trap(
+    interpreter: &mut Interpreter,
+    parts: &Parts,
+    trap: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    interpreter.call_function(trap, &parts.handler, args)
+}
+
+fn proxy_error(trap: &str, message: &str) -> JSError {
+    JSError::new_type_error(&format!("'{trap}' on proxy: {message}"))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-getprototypeof
+pub fn get_prototype_of(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+) -> JSResult<Option<ObjectId>> {
+    let parts = parts(interpreter, proxy, "getPrototypeOf")?;
+    let Some(trap) = trap(interpreter, &parts, "getPrototypeOf")? else {
+        return internal::get_prototype_of(interpreter, parts.target_id);
+    };
+    let prototype = match call_trap(
+        interpreter,
+        &parts,
+        &trap,
+        std::slice::from_ref(&parts.target),
+    )? {
+        JSValue::Object { object_id, .. } => Some(object_id),
+        JSValue::Null => None,
+        _ => {
+            return Err(proxy_error(
+                "getPrototypeOf",
+                "trap returned neither object nor null",
+            ));
+        }
+    };
+    if internal::is_extensible(interpreter, parts.target_id)? {
+        return Ok(prototype);
+    }
+    if internal::get_prototype_of(interpreter, parts.target_id)? != prototype {
+        return Err(proxy_error(
+            "getPrototypeOf",
+            "proxy target is non-extensible but the trap did not return its actual prototype",
+        ));
+    }
+    Ok(prototype)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-setprototypeof-v
+pub fn set_prototype_of(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    prototype: Option<ObjectId>,
+) -> JSResult<bool> {
+    let parts = parts(interpreter, proxy, "setPrototypeOf")?;
+    let Some(trap) = trap(interpreter, &parts, "setPrototypeOf")? else {
+        return internal::set_prototype_of(interpreter, parts.target_id, prototype);
+    };
+    let prototype_value = match prototype {
+        Some(id) => internal::object_value(interpreter, id)?,
+        None => JSValue::Null,
+    };
+    let args = [parts.target.clone(), prototype_value];
+    if !call_trap(interpreter, &parts, &trap, &args)?.to_boolean() {
+        return Ok(false);
+    }
+    if internal::is_extensible(interpreter, parts.target_id)? {
+        return Ok(true);
+    }
+    if internal::get_prototype_of(interpreter, parts.target_id)? != prototype {
+        return Err(proxy_error(
+            "setPrototypeOf",
+            "trap returned truish for setting a new prototype on the non-extensible proxy target",
+        ));
+    }
+    Ok(true)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-isextensible
+pub fn is_extensible(interpreter: &mut Interpreter, proxy: ProxyObject) -> JSResult<bool> {
+    let parts = parts(interpreter, proxy, "isExtensible")?;
+    let Some(trap) = trap(interpreter, &parts, "isExtensible")? else {
+        return internal::is_extensible(interpreter, parts.target_id);
+    };
+    let result = call_trap(
+        interpreter,
+        &parts,
+        &trap,
+        std::slice::from_ref(&parts.target),
+    )?
+    .to_boolean();
+    let target_result = internal::is_extensible(interpreter, parts.target_id)?;
+    if result != target_result {
+        return Err(proxy_error(
+            "isExtensible",
+            &format!(
+                "trap result does not reflect extensibility of proxy target (which is '{target_result}')"
+            ),
+        ));
+    }
+    Ok(result)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-preventextensions
+pub fn prevent_extensions(interpreter: &mut Interpreter, proxy: ProxyObject) -> JSResult<bool> {
+    let parts = parts(interpreter, proxy, "preventExtensions")?;
+    let Some(trap) = trap(interpreter, &parts, "preventExtensions")? else {
+        return internal::prevent_extensions(interpreter, parts.target_id);
+    };
+    let result = call_trap(
+        interpreter,
+        &parts,
+        &trap,
+        std::slice::from_ref(&parts.target),
+    )?
+    .to_boolean();
+    if result && internal::is_extensible(interpreter, parts.target_id)? {
+        return Err(proxy_error(
+            "preventExtensions",
+            "trap returned truish but the proxy target is extensible",
+        ));
+    }
+    Ok(result)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-getownproperty-p
+pub fn get_own_property(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    key: &PropertyKey,
+) -> JSResult<Option<ObjectProperty>> {
+    const TRAP: &str = "getOwnPropertyDescriptor";
+    let parts = parts(interpreter, proxy, TRAP)?;
+    let Some(trap) = trap(interpreter, &parts, TRAP)? else {
+        return internal::get_own_property(interpreter, parts.target_id, key);
+    };
+    let args = [parts.target.clone(), key.to_value()];
+    let result = call_trap(interpreter, &parts, &trap, &args)?;
+    if !result.is_object() && !result.is_undefined() {
+        return Err(proxy_error(
+            TRAP,
+            &format!("trap returned neither object nor undefined for property '{key}'"),
+        ));
+    }
+    let target_property = internal::get_own_property(interpreter, parts.target_id, key)?;
+    if result.is_undefined() {
+        let Some(target_property) = target_property else {
+            return Ok(None);
+        };
+        if !target_property.is_configurable() {
+            return Err(proxy_error(
+                TRAP,
+                &format!(
+                    "trap returned undefined for property '{key}' which is non-configurable in the proxy target"
+                ),
+            ));
+        }
+        if !internal::is_extensible(interpreter, parts.target_id)? {
+            return Err(proxy_error(
+                TRAP,
+                &format!(
+                    "trap returned undefined for property '{key}' which exists in the non-extensible proxy target"
+                ),
+            ));
+        }
+        return Ok(None);
+    }
+    let extensible = internal::is_extensible(interpreter, parts.target_id)?;
+    let desc = to_property_descriptor(interpreter, &result)?;
+    let property = desc.to_property();
+    if !is_compatible_property_descriptor(extensible, &desc, target_property.as_ref()) {
+        return Err(proxy_error(
+            TRAP,
+            &format!(
+                "trap returned descriptor for property '{key}' that is incompatible with the existing property in the proxy target"
+            ),
+        ));
+    }
+    if !property.is_configurable() {
+        match &target_property {
+            Some(target_property) if !target_property.is_configurable() => {
+                if desc.writable == Some(false) && target_property.is_writable() {
+                    return Err(proxy_error(
+                        TRAP,
+                        &format!(
+                            "trap reported non-configurable and writable for property '{key}' which is non-configurable, non-writable in the proxy target"
+                        ),
+                    ));
+                }
+            }
+            _ => {
+                return Err(proxy_error(
+                    TRAP,
+                    &format!(
+                        "trap reported non-configurability for property '{key}' which is either non-existent or configurable in the proxy target"
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(Some(property))
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-defineownproperty-p-desc
+pub fn define_own_property(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    key: &PropertyKey,
+    desc: &PropertyDescriptor,
+) -> JSResult<bool> {
+    const TRAP: &str = "defineProperty";
+    let parts = parts(interpreter, proxy, TRAP)?;
+    let Some(trap) = trap(interpreter, &parts, TRAP)? else {
+        return internal::define_own_property(interpreter, parts.target_id, *key, desc);
+    };
+    let desc_object = from_property_descriptor(interpreter, desc);
+    let args = [parts.target.clone(), key.to_value(), desc_object];
+    if !call_trap(interpreter, &parts, &trap, &args)?.to_boolean() {
+        return Ok(false);
+    }
+    let target_property = internal::get_own_property(interpreter, parts.target_id, key)?;
+    let extensible = internal::is_extensible(interpreter, parts.target_id)?;
+    let setting_non_configurable = desc.configurable == Some(false);
+    let non_configurable_error = || {
+        proxy_error(
+            TRAP,
+            &format!(
+                "trap returned truish for defining non-configurable property '{key}' which is either non-existent or configurable in the proxy target"
+            ),
+        )
+    };
+    let Some(target_property) = target_property else {
+        if !extensible {
+            return Err(proxy_error(
+                TRAP,
+                &format!(
+                    "trap returned truish for adding property '{key}' to the non-extensible proxy target"
+                ),
+            ));
+        }
+        if setting_non_configurable {
+            return Err(non_configurable_error());
+        }
+        return Ok(true);
+    };
+    if !is_compatible_property_descriptor(extensible, desc, Some(&target_property)) {
+        return Err(proxy_error(
+            TRAP,
+            &format!(
+                "trap returned truish for adding property '{key}' that is incompatible with the existing property in the proxy target"
+            ),
+        ));
+    }
+    if setting_non_configurable && target_property.is_configurable() {
+        return Err(non_configurable_error());
+    }
+    if let ObjectProperty::Data {
+        writable: true,
+        configurable: false,
+        ..
+    } = target_property
+        && desc.writable == Some(false)
+    {
+        return Err(proxy_error(
+            TRAP,
+            &format!(
+                "trap returned truish for defining non-configurable property '{key}' which cannot be non-writable, unless there exists a corresponding non-configurable, non-writable own property of the target object"
+            ),
+        ));
+    }
+    Ok(true)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-hasproperty-p
+pub fn has_property(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    key: &PropertyKey,
+) -> JSResult<bool> {
+    let parts = parts(interpreter, proxy, "has")?;
+    let Some(trap) = trap(interpreter, &parts, "has")? else {
+        return internal::has_property(interpreter, parts.target_id, key);
+    };
+    let args = [parts.target.clone(), key.to_value()];
+    let result = call_trap(interpreter, &parts, &trap, &args)?.to_boolean();
+    if result {
+        return Ok(true);
+    }
+    if let Some(target_property) = internal::get_own_property(interpreter, parts.target_id, key)? {
+        if !target_property.is_configurable() {
+            return Err(proxy_error(
+                "has",
+                &format!(
+                    "trap returned falsish for property '{key}' which exists in the proxy target as non-configurable"
+                ),
+            ));
+        }
+        if !internal::is_extensible(interpreter, parts.target_id)? {
+            return Err(proxy_error(
+                "has",
+                &format!(
+                    "trap returned falsish for property '{key}' but the proxy target is not extensible"
+                ),
+            ));
+        }
+    }
+    Ok(false)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-get-p-receiver
+pub fn get(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    key: &PropertyKey,
+    receiver: &JSValue,
+) -> JSResult<JSValue> {
+    let parts = parts(interpreter, proxy, "get")?;
+    let Some(trap) = trap(interpreter, &parts, "get")? else {
+        return internal::get(interpreter, parts.target_id, key, receiver);
+    };
+    let args = [parts.target.clone(), key.to_value(), receiver.clone()];
+    let result = call_trap(interpreter, &parts, &trap, &args)?;
+    match internal::get_own_property(interpreter, parts.target_id, key)? {
+        Some(ObjectProperty::Data {
+            value,
+            writable: false,
+            configurable: false,
+            ..
+        }) if !is_same_value(&result, &value) => {
+            let expected = debug_value(interpreter, &value);
+            let actual = debug_value(interpreter, &result);
+            Err(proxy_error(
+                "get",
+                &format!(
+                    "property '{key}' is a read-only and non-configurable data property on the proxy target but the proxy did not return its actual value (expected '{expected}' but got '{actual}')"
+                ),
+            ))
+        }
+        Some(ObjectProperty::Accessor {
+            get: None,
+            configurable: false,
+            ..
+        }) if !result.is_undefined() => {
+            let actual = debug_value(interpreter, &result);
+            Err(proxy_error(
+                "get",
+                &format!(
+                    "property '{key}' is a non-configurable accessor property on the proxy target and does not have a getter function, but the trap did not return 'undefined' (got '{actual}')"
+                ),
+            ))
+        }
+        _ => Ok(result),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-set-p-v-receiver
+pub fn set(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    key: &PropertyKey,
+    value: JSValue,
+    receiver: &JSValue,
+) -> JSResult<bool> {
+    let parts = parts(interpreter, proxy, "set")?;
+    let Some(trap) = trap(interpreter, &parts, "set")? else {
+        return internal::set(interpreter, parts.target_id, key, value, receiver);
+    };
+    let args = [
+        parts.target.clone(),
+        key.to_value(),
+        value.clone(),
+        receiver.clone(),
+    ];
+    if !call_trap(interpreter, &parts, &trap, &args)?.to_boolean() {
+        return Ok(false);
+    }
+    match internal::get_own_property(interpreter, parts.target_id, key)? {
+        Some(ObjectProperty::Data {
+            value: target_value,
+            writable: false,
+            configurable: false,
+            ..
+        }) if !is_same_value(&value, &target_value) => Err(proxy_error(
+            "set",
+            &format!(
+                "trap returned truish for property '{key}' which exists in the proxy target as a non-configurable and non-writable data property with a different value"
+            ),
+        )),
+        Some(ObjectProperty::Accessor {
+            set: None,
+            configurable: false,
+            ..
+        }) => Err(proxy_error(
+            "set",
+            &format!(
+                "trap returned truish for property '{key}' which exists in the proxy target as a non-configurable and non-writable accessor property without a setter"
+            ),
+        )),
+        _ => Ok(true),
+    }
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-delete-p
+pub fn delete(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    key: &PropertyKey,
+) -> JSResult<bool> {
+    const TRAP: &str = "deleteProperty";
+    let parts = parts(interpreter, proxy, TRAP)?;
+    let Some(trap) = trap(interpreter, &parts, TRAP)? else {
+        return internal::delete(interpreter, parts.target_id, key);
+    };
+    let args = [parts.target.clone(), key.to_value()];
+    if !call_trap(interpreter, &parts, &trap, &args)?.to_boolean() {
+        return Ok(false);
+    }
+    let Some(target_property) = internal::get_own_property(interpreter, parts.target_id, key)?
+    else {
+        return Ok(true);
+    };
+    if !target_property.is_configurable() {
+        return Err(proxy_error(
+            TRAP,
+            &format!(
+                "trap returned truish for property '{key}' which is non-configurable in the proxy target"
+            ),
+        ));
+    }
+    if !internal::is_extensible(interpreter, parts.target_id)? {
+        return Err(proxy_error(
+            TRAP,
+            &format!(
+                "trap returned truish for property '{key}' but the proxy target is non-extensible"
+            ),
+        ));
+    }
+    Ok(true)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-ownpropertykeys
+pub fn own_property_keys(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+) -> JSResult<Vec<PropertyKey>> {
+    let parts = parts(interpreter, proxy, "ownKeys")?;
+    let Some(trap) = trap(interpreter, &parts, "ownKeys")? else {
+        return internal::own_property_keys(interpreter, parts.target_id);
+    };
+    let result = call_trap(
+        interpreter,
+        &parts,
+        &trap,
+        std::slice::from_ref(&parts.target),
+    )?;
+    if !result.is_object() {
+        return Err(JSError::new_type_error(
+            "CreateListFromArrayLike called on non-object",
+        ));
+    }
+    let mut keys: Vec<PropertyKey> = vec![];
+    for element in list_from_array_like(interpreter, &result)? {
+        if !matches!(element, JSValue::String { .. } | JSValue::Symbol { .. }) {
+            let element = debug_value(interpreter, &element);
+            return Err(JSError::new_type_error(&format!(
+                "{element} is not a valid property name"
+            )));
+        }
+        let key = element.to_property_key(interpreter)?;
+        if keys.contains(&key) {
+            return Err(proxy_error("ownKeys", "trap returned duplicate entries"));
+        }
+        keys.push(key);
+    }
+    let extensible = internal::is_extensible(interpreter, parts.target_id)?;
+    let mut configurable_keys = vec![];
+    let mut non_configurable_keys = vec![];
+    for key in internal::own_property_keys(interpreter, parts.target_id)? {
+        match internal::get_own_property(interpreter, parts.target_id, &key)? {
+            Some(property) if !property.is_configurable() => non_configurable_keys.push(key),
+            _ => configurable_keys.push(key),
+        }
+    }
+    if extensible && non_configurable_keys.is_empty() {
+        return Ok(keys);
+    }
+    let mut unchecked = keys.clone();
+    let mut check = |key: &PropertyKey| match unchecked.iter().position(|k| k == key) {
+        Some(position) => {
+            unchecked.remove(position);
+            Ok(())
+        }
+        None => Err(proxy_error(
+            "ownKeys",
+            &format!("trap result did not include '{key}'"),
+        )),
+    };
+    for key in &non_configurable_keys {
+        check(key)?;
+    }
+    if extensible {
+        return Ok(keys);
+    }
+    for key in &configurable_keys {
+        check(key)?;
+    }
+    if !unchecked.is_empty() {
+        return Err(proxy_error(
+            "ownKeys",
+            "trap returned extra keys but proxy target is non-extensible",
+        ));
+    }
+    Ok(keys)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-call-thisargument-argumentslist
+pub fn call(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    this: &JSValue,
+    args: &[JSValue],
+) -> JSResult<JSValue> {
+    let parts = parts(interpreter, proxy, "apply")?;
+    let Some(trap) = trap(interpreter, &parts, "apply")? else {
+        return interpreter.call_function(&parts.target, this, args);
+    };
+    let args = create_array(args.to_vec(), interpreter);
+    let trap_args = [parts.target.clone(), this.clone(), args];
+    call_trap(interpreter, &parts, &trap, &trap_args)
+}
+
+// https://262.ecma-international.org/15.0/index.html#sec-proxy-object-internal-methods-and-internal-slots-construct-argumentslist-newtarget
+pub fn construct(
+    interpreter: &mut Interpreter,
+    proxy: ProxyObject,
+    args: &[JSValue],
+    new_target: &JSValue,
+) -> JSResult<JSValue> {
+    let parts = parts(interpreter, proxy, "construct")?;
+    let Some(trap) = trap(interpreter, &parts, "construct")? else {
+        return interpreter.construct_with_new_target(&parts.target, args, new_target);
+    };
+    let args = create_array(args.to_vec(), interpreter);
+    let trap_args = [parts.target.clone(), args, new_target.clone()];
+    let object = call_trap(interpreter, &parts, &trap, &trap_args)?;
+    if !object.is_object() {
+        return Err(proxy_error("construct", "trap returned non-object"));
+    }
+    Ok(object)
+}
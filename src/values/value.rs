@@ -1,64 +1,79 @@
 use core::f64;
-use std::{
-    mem::discriminant,
-    sync::{Mutex, OnceLock},
-};
+use std::{cmp::Ordering, mem::discriminant};
 
 use log::{debug, trace};
+use num_bigint::BigInt;
+use num_traits::Zero;
 use string_interner::symbol::SymbolU32;
 
 use crate::{
     Interpreter,
     errors::JSError,
-    expr::Expr,
-    global::{get_or_intern_string, get_string_from_pool},
-    stmt::Stmt,
+    global::{get_or_intern_string, get_string_from_pool, next_symbol_id},
     token::Kind,
-    utils::{get_function_params, remove_quotes_from_string},
     values::{
-        JSResult, ObjectKind, PreferredType, add, bitwise_or, divide, equal, less_than, multiply,
-        objects::{JSObject, ObjectId, Properties},
-        remainder, subtract,
+        JSResult, ObjectKind, PreferredType, PropertyKey, WellKnownSymbol, add, bigint,
+        bitwise_and, bitwise_or, bitwise_xor, divide, left_shift, multiply,
+        objects::{JSObject, ObjectId, Properties, get_property_value, ordinary_to_primitive},
+        remainder, signed_right_shift, string_to_number, subtract, to_string, unsigned_right_shift,
     },
 };
 
-static SYMBOL_COUNTER: OnceLock<Mutex<usize>> = OnceLock::new();
-
-fn get_symbol_counter() -> &'static Mutex<usize> {
-    SYMBOL_COUNTER.get_or_init(|| Mutex::new(0))
-}
-
-fn get_new_symbol_id() -> usize {
-    let mut counter = get_symbol_counter().lock().unwrap();
-    let value = counter.clone();
-    *counter += 1;
-    value
-}
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum JSValue {
     Null,
+    #[default]
     Undefined,
-    Boolean { data: bool },
-    String { data: SymbolU32 },
-    Symbol { id: usize, description: SymbolU32 },
-    Number { data: f64 },
-    BigInt,
-    Object { object_id: usize, kind: ObjectKind },
+    Boolean {
+        data: bool,
+    },
+    String {
+        data: SymbolU32,
+    },
+    Symbol {
+        id: usize,
+        description: Option<SymbolU32>,
+    },
+    Number {
+        data: f64,
+    },
+    BigInt {
+        data: Box<BigInt>,
+    },
+    Object {
+        object_id: usize,
+        kind: ObjectKind,
+    },
 }
 
 impl JSValue {
+    // https://262.ecma-international.org/15.0/index.html#sec-toprimitive
     pub fn to_primitive(
         &self,
         preferred_type: Option<PreferredType>,
         interpreter: &mut Interpreter,
     ) -> JSResult<JSValue> {
         match self {
-            JSValue::Object { object_id, kind: _ } => {
-                let obj = interpreter.get_object(*object_id)?.clone();
-                let res =
-                    obj.to_primitive(preferred_type.unwrap_or(PreferredType::Number), interpreter)?;
-                Ok(res)
+            JSValue::Object { .. } => {
+                let key = PropertyKey::well_known(WellKnownSymbol::ToPrimitive);
+                let exotic_to_prim = get_property_value(interpreter, self, key)?;
+                if !exotic_to_prim.is_undefined() && !exotic_to_prim.is_null() {
+                    let hint = match preferred_type {
+                        None => "default",
+                        Some(PreferredType::Number) => "number",
+                        Some(PreferredType::String) => "string",
+                    };
+                    let hint = JSValue::new_string(&get_or_intern_string(hint));
+                    let result = interpreter.call_function(&exotic_to_prim, self, &[hint])?;
+                    if result.is_object() {
+                        return Err(JSError::new_type_error(
+                            "Cannot convert object to primitive value",
+                        ));
+                    }
+                    return Ok(result);
+                }
+                let hint = preferred_type.unwrap_or(PreferredType::Number);
+                ordinary_to_primitive(interpreter, self, hint)
             }
             _ => Ok(self.clone()),
         }
@@ -69,6 +84,7 @@ impl JSValue {
             JSValue::Boolean { data } => *data,
             JSValue::Undefined | JSValue::Null => false,
             JSValue::Number { data } => !(*data == 0.0 || *data == -0.0 || f64::is_nan(*data)),
+            JSValue::BigInt { data } => !data.is_zero(),
             JSValue::String { data } => {
                 let string_opt = get_string_from_pool(data);
                 if let Some(string) = string_opt {
@@ -83,8 +99,8 @@ impl JSValue {
     pub fn to_numeric(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
         let prim_value = self.to_primitive(Some(PreferredType::Number), interpreter)?;
         match prim_value {
-            JSValue::BigInt => todo!(),
-            _ => self.to_number(interpreter),
+            JSValue::BigInt { .. } => Ok(prim_value),
+            _ => prim_value.to_number(interpreter),
         }
     }
 
@@ -97,23 +113,19 @@ impl JSValue {
                 false => &0.0,
             }),
             JSValue::String { data } => JSValue::new_number(&JSValue::string_to_number(data)),
-            JSValue::Symbol {
-                id: _,
-                description: _,
-            } => {
-                return Err(JSError::new_function_type_error(
+            JSValue::Symbol { .. } => {
+                return Err(JSError::new_type_error(
                     "Cannot convert a Symbol value to a number",
                 ));
             }
-            JSValue::BigInt => {
-                return Err(JSError::new_function_type_error(
+            JSValue::BigInt { .. } => {
+                return Err(JSError::new_type_error(
                     "Cannot convert a BigInt value to a number",
                 ));
             }
             JSValue::Number { data: _ } => self.clone(),
-            JSValue::Object { object_id, kind: _ } => {
-                let object = interpreter.get_object_mut(*object_id)?.clone();
-                let prim_value = object.to_primitive(PreferredType::Number, interpreter)?;
+            JSValue::Object { .. } => {
+                let prim_value = self.to_primitive(Some(PreferredType::Number), interpreter)?;
                 prim_value.to_number(interpreter)?
             }
         };
@@ -122,11 +134,7 @@ impl JSValue {
 
     pub fn string_to_number(value: &SymbolU32) -> f64 {
         let string = get_string_from_pool(value).expect("Prevented by spec");
-        let number = string.parse::<f64>();
-        match number {
-            Ok(n) => n,
-            Err(_) => f64::NAN,
-        }
+        string_to_number(&string)
     }
 
     pub fn to_integer_or_infinity(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
@@ -137,8 +145,7 @@ impl JSValue {
         if number.is_infinite() {
             return Ok(JSValue::new_number(&number));
         }
-        let floored = f64::floor(number);
-        Ok(JSValue::new_number(&floored))
+        Ok(JSValue::new_number(&number.trunc()))
     }
 
     pub fn is_finite(&self, interpreter: &mut Interpreter) -> JSResult<bool> {
@@ -146,113 +153,103 @@ impl JSValue {
         Ok(number.is_finite())
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-toint32
     pub fn to_int_32(&self, interpreter: &mut Interpreter) -> JSResult<i32> {
-        let number = self.to_number(interpreter)?.get_number();
-        let is_finite = self.is_finite(interpreter)?;
-        if !is_finite || number == 0.0 || number == -0.0 {
-            return Ok(0);
-        }
-        let int = number.trunc() as i32;
-        let rhs_mod = 2i32.pow(32);
-        let int32bit = int % rhs_mod;
-        if int32bit >= 2i32.pow(31) {
-            return Ok(int32bit - rhs_mod);
-        }
-
-        Ok(int32bit)
+        Ok(self.to_uint_32(interpreter)? as i32)
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-touint32
     pub fn to_uint_32(&self, interpreter: &mut Interpreter) -> JSResult<u32> {
         let number = self.to_number(interpreter)?.get_number();
-        let is_finite = self.is_finite(interpreter)?;
-        if !is_finite || number == 0.0 || number == -0.0 {
+        if !number.is_finite() || number == 0.0 {
             return Ok(0);
         }
-        let int = number.trunc() as u32;
-        let rhs_mod = 2u32.pow(32);
-        let int32bit = int % rhs_mod;
-        Ok(int32bit)
+        let int32bit = number.trunc().rem_euclid(2f64.powi(32));
+        Ok(int32bit as u32)
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-toint16
+    /// Like the other narrow conversions, the low bits of `ToUint32`, since 2^16 divides 2^32.
     pub fn to_int_16(&self, interpreter: &mut Interpreter) -> JSResult<i16> {
-        let number = self.to_number(interpreter)?.get_number();
-        let is_finite = self.is_finite(interpreter)?;
-        if !is_finite || number == 0.0 || number == -0.0 {
-            return Ok(0);
-        }
-        let int = number.trunc() as i16;
-        let rhs_mod = 2i16.pow(16);
-        let int16bit = int % rhs_mod;
-        if int16bit >= 2i16.pow(15) {
-            return Ok(int16bit - rhs_mod);
-        }
-
-        Ok(int16bit)
+        Ok(self.to_uint_32(interpreter)? as i16)
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-touint16
     pub fn to_uint_16(&self, interpreter: &mut Interpreter) -> JSResult<u16> {
-        let number = self.to_number(interpreter)?.get_number();
-        let is_finite = self.is_finite(interpreter)?;
-        if !is_finite || number == 0.0 || number == -0.0 {
-            return Ok(0);
-        }
-        let int = number.trunc() as u16;
-        let rhs_mod = 2u16.pow(16);
-        let int16bit = int % rhs_mod;
-        Ok(int16bit)
+        Ok(self.to_uint_32(interpreter)? as u16)
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-toint8
     pub fn to_int_8(&self, interpreter: &mut Interpreter) -> JSResult<i8> {
-        let number = self.to_number(interpreter)?.get_number();
-
-        let is_finite = self.is_finite(interpreter)?;
-        if !is_finite || number == 0.0 || number == -0.0 {
-            return Ok(0);
-        }
-        let int = number.trunc() as i8;
-        let rhs_mod = 2i8.pow(8);
-        let int8bit = int % rhs_mod;
-        if int8bit >= 2i8.pow(7) {
-            return Ok(int8bit - rhs_mod);
-        }
-
-        Ok(int8bit)
+        Ok(self.to_uint_32(interpreter)? as i8)
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-touint8
     pub fn to_uint_8(&self, interpreter: &mut Interpreter) -> JSResult<u8> {
-        let number = self.to_number(interpreter)?.get_number();
-        let is_finite = self.is_finite(interpreter)?;
-        if !is_finite || number == 0.0 || number == -0.0 {
-            return Ok(0);
-        }
-        let int = number.trunc() as u8;
-        let rhs_mod = 2u8.pow(8);
-        let int8bit = int % rhs_mod;
-        Ok(int8bit)
+        Ok(self.to_uint_32(interpreter)? as u8)
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-touint8clamp
+    /// Clamps to 0..=255 and rounds half to even.
     pub fn to_uint_8_clamped(&self, interpreter: &mut Interpreter) -> JSResult<u8> {
         let number = self.to_number(interpreter)?.get_number();
-        if number.is_nan() {
+        if number.is_nan() || number <= 0.0 {
             return Ok(0);
         }
-        let clamped = number.clamp(0.0, 255.0);
-        let floor = number.floor();
-        if clamped < floor + 0.5 {
-            return Ok(floor as u8);
-        }
-        if clamped > floor + 0.5 {
-            return Ok(floor as u8 + 1);
-        }
-        let floor = floor as u8;
-        if floor % 2 == 0 {
-            return Ok(floor);
+        if number >= 255.0 {
+            return Ok(255);
         }
-        Ok(floor + 1)
+        let floor = number.floor();
+        let rounded = match number - floor {
+            fraction if fraction > 0.5 => floor + 1.0,
+            fraction if fraction < 0.5 => floor,
+            _ if floor % 2.0 == 0.0 => floor,
+            _ => floor + 1.0,
+        };
+        Ok(rounded as u8)
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-tobigint64
+    pub fn to_big_int_64(&self, interpreter: &mut Interpreter) -> JSResult<i64> {
+        Ok(self.to_big_uint_64(interpreter)? as i64)
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-tobiguint64
+    pub fn to_big_uint_64(&self, interpreter: &mut Interpreter) -> JSResult<u64> {
+        let big_int = self.to_big_int(interpreter)?;
+        let (_, digits) = bigint::as_n_bits(&big_int, 64, false)?.to_u64_digits();
+        Ok(digits.first().copied().unwrap_or_default())
     }
 
-    pub fn to_big_int(&self) -> JSResult<JSValue> {
-        todo!()
+    // https://262.ecma-international.org/15.0/index.html#sec-tobigint
+    pub fn to_big_int(&self, interpreter: &mut Interpreter) -> JSResult<BigInt> {
+        let prim_value = self.to_primitive(Some(PreferredType::Number), interpreter)?;
+        match prim_value {
+            JSValue::Boolean { data } => Ok(BigInt::from(data as u8)),
+            JSValue::BigInt { data } => Ok(*data),
+            JSValue::String { data } => {
+                let string = get_string_from_pool(&data).unwrap_or_default();
+                bigint::string_to_big_int(&string).ok_or_else(|| {
+                    JSError::new_syntax_error(&format!("Cannot convert {string} to a BigInt"))
+                })
+            }
+            JSValue::Symbol { id, description } => {
+                let symbol = PropertyKey::Symbol { id, description };
+                Err(JSError::new_type_error(&format!(
+                    "Cannot convert {symbol} to a BigInt"
+                )))
+            }
+            JSValue::Null | JSValue::Undefined | JSValue::Number { .. } => {
+                let string = prim_value.to_string(interpreter)?;
+                let string = get_string_from_pool(&string).unwrap_or_default();
+                Err(JSError::new_type_error(&format!(
+                    "Cannot convert {string} to a BigInt"
+                )))
+            }
+            JSValue::Object { .. } => {
+                Err(JSError::new_type_error("Cannot convert object to a BigInt"))
+            }
+        }
     }
 
     pub fn to_object<'a>(&'a self, interpreter: &'a mut Interpreter) -> JSResult<&'a JSObject> {
@@ -280,17 +277,29 @@ impl JSValue {
             JSValue::Undefined => get_or_intern_string("undefined"),
             JSValue::Boolean { data } => get_or_intern_string(&data.to_string()),
             JSValue::String { data } => *data,
-            JSValue::Symbol { id: _, description } => *description,
-            JSValue::Number { data } => get_or_intern_string(&data.to_string()),
-            JSValue::BigInt => todo!(),
-            JSValue::Object { object_id, kind: _ } => {
-                let object = interpreter.get_object(*object_id)?.clone();
-                let prim_value = object.to_primitive(PreferredType::String, interpreter)?;
+            JSValue::Symbol { .. } => {
+                return Err(JSError::new_type_error(
+                    "Cannot convert a Symbol value to a string",
+                ));
+            }
+            JSValue::Number { data } => get_or_intern_string(&to_string(*data, 10)),
+            JSValue::BigInt { data } => get_or_intern_string(&bigint::to_string(data, 10)),
+            JSValue::Object { .. } => {
+                let prim_value = self.to_primitive(Some(PreferredType::String), interpreter)?;
                 prim_value.to_string(interpreter)?
             }
         })
     }
 
+    // https://262.ecma-international.org/15.0/index.html#sec-topropertykey
+    pub fn to_property_key(&self, interpreter: &mut Interpreter) -> JSResult<PropertyKey> {
+        let key = self.to_primitive(Some(PreferredType::String), interpreter)?;
+        match key {
+            JSValue::Symbol { id, description } => Ok(PropertyKey::Symbol { id, description }),
+            _ => Ok(PropertyKey::String(key.to_string(interpreter)?)),
+        }
+    }
+
     pub fn to_length(&self, interpreter: &mut Interpreter) -> JSResult<JSValue> {
         let len = self.to_integer_or_infinity(interpreter)?;
         if let JSValue::Number { data } = len {
@@ -323,13 +332,7 @@ impl JSValue {
     }
 
     pub fn is_symbol(&self) -> bool {
-        match self {
-            Self::Symbol {
-                id: _,
-                description: _,
-            } => true,
-            _ => false,
-        }
+        matches!(self, Self::Symbol { .. })
     }
 
     pub fn is_number(&self) -> bool {
@@ -345,13 +348,25 @@ impl JSValue {
     }
 
     pub fn is_big_int(&self) -> bool {
-        discriminant(self) == discriminant(&JSValue::BigInt)
+        matches!(self, Self::BigInt { .. })
     }
 
     pub fn new_number(v: &f64) -> Self {
         Self::Number { data: *v }
     }
 
+    pub fn new_big_int(v: BigInt) -> Self {
+        Self::BigInt { data: Box::new(v) }
+    }
+
+    /// A new symbol, unlike every other.
+    pub fn new_symbol(description: Option<SymbolU32>) -> Self {
+        Self::Symbol {
+            id: next_symbol_id(),
+            description,
+        }
+    }
+
     pub fn new_boolean(v: bool) -> Self {
         Self::Boolean { data: v }
     }
@@ -365,7 +380,9 @@ impl JSValue {
     }
 
     pub fn new_object(properties: Properties, interpreter: &mut Interpreter) -> Self {
-        let object_id = JSObject::new_ordinary_object(properties, true, None, interpreter);
+        let proto_id = interpreter.get_object_proto_id();
+        let object_id =
+            JSObject::new_ordinary_object(properties, true, Some(proto_id), interpreter);
         Self::Object {
             object_id,
             kind: ObjectKind::Object,
@@ -406,38 +423,6 @@ impl JSValue {
         }
     }
 
-    pub fn new_function(
-        ident: Option<Expr>,
-        args: Vec<Expr>,
-        body: Stmt,
-        interpreter: &mut Interpreter,
-    ) -> JSResult<Self> {
-        let identifier = match ident {
-            Some(i) => i.evaluate(interpreter)?,
-            None => {
-                let sym_id = get_new_symbol_id();
-                let description = format!("unknown-function-{sym_id}");
-                let desc = get_or_intern_string(&description);
-                JSValue::Symbol {
-                    id: get_new_symbol_id(),
-                    description: desc,
-                }
-            }
-        };
-        let ident_id = identifier.to_string(interpreter)?;
-        let scope_id = interpreter.enter_scope(None);
-        let parameters = get_function_params(&args, interpreter)?;
-        let object_id =
-            JSObject::new_function_object(Box::new(body), parameters, scope_id, interpreter);
-        let value = JSValue::Object {
-            object_id,
-            kind: ObjectKind::Function,
-        };
-        interpreter.new_variable(ident_id, false, value);
-
-        Ok(JSValue::Undefined)
-    }
-
     pub fn apply_string_or_numeric_binary_operator(
         &self,
         op: &Kind,
@@ -455,62 +440,117 @@ impl JSValue {
                 let right_str_sym = right_prim.to_string(interpreter)?;
                 let left_str = get_string_from_pool(&left_str_sym).unwrap(); // panic should be fine here, programmer error not JS error
                 let right_str = get_string_from_pool(&right_str_sym).unwrap();
-                // we store strings with quote marks to distinguish from identifiers
-                // concatenation means stripping the quotes (if present, it is valid to concatenate an identifier too)
-                // then adding them back in at the end as we know we have a string
-                let left_str = remove_quotes_from_string(&left_str);
-                let right_str = remove_quotes_from_string(&right_str);
-                let concatenated = format!("'{left_str}{right_str}'");
+                let concatenated = format!("{left_str}{right_str}");
                 let id = get_or_intern_string(&concatenated);
                 return Ok(JSValue::new_string(&id));
             }
             l_val = left_prim;
             r_val = right_prim;
         };
-        // must be numbers at this point
-        let l_num = l_val.to_numeric(interpreter)?.get_number();
-        let r_num = r_val.to_numeric(interpreter)?.get_number();
-        debug!("Checking: {} {:?} {}", l_num, op, r_num);
-        // assert these are the same type when doing bigints
-        let result = match op {
-            Kind::Plus => add(l_num, r_num),
-            Kind::Minus => subtract(l_num, r_num),
-            Kind::Star => multiply(l_num, r_num),
-            Kind::Slash => divide(l_num, r_num),
-            Kind::Percent => remainder(l_num, r_num),
-            Kind::LessThan => {
-                let res = less_than(l_num, r_num);
-                return Ok(JSValue::new_boolean(res));
+        if op.is_relational_operator() {
+            let left_prim = l_val.to_primitive(Some(PreferredType::Number), interpreter)?;
+            let right_prim = r_val.to_primitive(Some(PreferredType::Number), interpreter)?;
+            let ordering = JSValue::compare(&left_prim, &right_prim, interpreter)?;
+            let result = match op {
+                Kind::LessThan => ordering == Some(Ordering::Less),
+                Kind::LessThanOrEquals => {
+                    matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+                }
+                Kind::GreaterThan => ordering == Some(Ordering::Greater),
+                _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            };
+            return Ok(JSValue::new_boolean(result));
+        }
+        let l_num = l_val.to_numeric(interpreter)?;
+        let r_num = r_val.to_numeric(interpreter)?;
+        debug!("Checking: {:?} {:?} {:?}", l_num, op, r_num);
+        match (l_num, r_num) {
+            (JSValue::Number { data: l_num }, JSValue::Number { data: r_num }) => {
+                let result = match op {
+                    Kind::Plus => add(l_num, r_num),
+                    Kind::Minus => subtract(l_num, r_num),
+                    Kind::Star => multiply(l_num, r_num),
+                    Kind::Slash => divide(l_num, r_num),
+                    Kind::Percent => remainder(l_num, r_num),
+                    Kind::LeftShift => left_shift(l_num, r_num, interpreter) as f64,
+                    Kind::SignedRightShift => signed_right_shift(l_num, r_num, interpreter) as f64,
+                    Kind::UnsignedRightShift => {
+                        unsigned_right_shift(l_num, r_num, interpreter) as f64
+                    }
+                    Kind::BitwiseAnd => bitwise_and(l_num, r_num, interpreter) as f64,
+                    Kind::BitwiseXor => bitwise_xor(l_num, r_num, interpreter) as f64,
+                    Kind::BitwiseOr => bitwise_or(l_num, r_num, interpreter) as f64,
+                    _ => panic!("Invalid numeric operation: {:?}", op),
+                };
+                Ok(JSValue::new_number(&result))
+            }
+            (JSValue::BigInt { data: l_num }, JSValue::BigInt { data: r_num }) => {
+                let result = match op {
+                    Kind::Plus => bigint::add(&l_num, &r_num),
+                    Kind::Minus => bigint::subtract(&l_num, &r_num),
+                    Kind::Star => bigint::multiply(&l_num, &r_num),
+                    Kind::Slash => bigint::divide(&l_num, &r_num)?,
+                    Kind::Percent => bigint::remainder(&l_num, &r_num)?,
+                    Kind::LeftShift => bigint::left_shift(&l_num, &r_num)?,
+                    Kind::SignedRightShift => bigint::signed_right_shift(&l_num, &r_num)?,
+                    Kind::UnsignedRightShift => bigint::unsigned_right_shift(&l_num, &r_num)?,
+                    Kind::BitwiseAnd => bigint::bitwise_and(&l_num, &r_num),
+                    Kind::BitwiseXor => bigint::bitwise_xor(&l_num, &r_num),
+                    Kind::BitwiseOr => bigint::bitwise_or(&l_num, &r_num),
+                    _ => panic!("Invalid numeric operation: {:?}", op),
+                };
+                Ok(JSValue::new_big_int(result))
             }
-            Kind::LessThanOrEquals => {
-                let lt = less_than(l_num, r_num);
-                let eq = equal(l_num, r_num);
-                let value = lt || eq;
-                return Ok(JSValue::new_boolean(value));
+            _ => Err(JSError::new_type_error(
+                "Cannot mix BigInt and other types, use explicit conversions",
+            )),
+        }
+    }
+
+    // https://262.ecma-international.org/15.0/index.html#sec-islessthan
+    /// Orders two primitives the way the relational operators do. `None` is the spec's
+    /// undefined, when NaN or a string that isn't an integer is involved.
+    fn compare(
+        left: &JSValue,
+        right: &JSValue,
+        interpreter: &mut Interpreter,
+    ) -> JSResult<Option<Ordering>> {
+        match (left, right) {
+            (JSValue::String { data: l_str }, JSValue::String { data: r_str }) => {
+                // strings compare by UTF-16 code units
+                let l_str = get_string_from_pool(l_str).unwrap_or_default();
+                let r_str = get_string_from_pool(r_str).unwrap_or_default();
+                return Ok(Some(l_str.encode_utf16().cmp(r_str.encode_utf16())));
             }
-            Kind::GreaterThan => {
-                let res = less_than(r_num, l_num);
-                return Ok(JSValue::new_boolean(res));
+            (JSValue::BigInt { data }, JSValue::String { data: string }) => {
+                let string = get_string_from_pool(string).unwrap_or_default();
+                let other = bigint::string_to_big_int(&string);
+                return Ok(other.map(|other| data.as_ref().cmp(&other)));
             }
-            Kind::GreaterThanOrEquals => {
-                let gt = less_than(r_num, l_num);
-                let eq = equal(l_num, r_num);
-                let value = gt || eq;
-                return Ok(JSValue::new_boolean(value));
+            (JSValue::String { data: string }, JSValue::BigInt { data }) => {
+                let string = get_string_from_pool(string).unwrap_or_default();
+                let other = bigint::string_to_big_int(&string);
+                return Ok(other.map(|other| other.cmp(data)));
             }
-            Kind::EqualEqual => return Ok(JSValue::new_boolean(equal(l_num, r_num))),
-            Kind::EqualEqualEqual => return Ok(JSValue::new_boolean(equal(l_num, r_num))),
-            Kind::NotEqual => {
-                let result = equal(l_num, r_num);
-                return Ok(JSValue::new_boolean(!result));
+            _ => {}
+        }
+        let l_num = left.to_numeric(interpreter)?;
+        let r_num = right.to_numeric(interpreter)?;
+        Ok(match (&l_num, &r_num) {
+            (JSValue::Number { data: l_num }, JSValue::Number { data: r_num }) => {
+                l_num.partial_cmp(r_num)
             }
-            Kind::BitwiseOr => {
-                let result = bitwise_or(l_num, r_num, interpreter);
-                result as f64
+            (JSValue::BigInt { data: l_num }, JSValue::BigInt { data: r_num }) => {
+                Some(l_num.cmp(r_num))
             }
-            _ => panic!("the disco"),
-        };
-        Ok(JSValue::new_number(&result))
+            (JSValue::BigInt { data }, JSValue::Number { data: number }) => {
+                bigint::compare_to_number(data, *number)
+            }
+            (JSValue::Number { data: number }, JSValue::BigInt { data }) => {
+                bigint::compare_to_number(data, *number).map(Ordering::reverse)
+            }
+            _ => None,
+        })
     }
 
     pub fn compute_equality(
@@ -583,6 +623,7 @@ impl PartialEq for JSValue {
                 },
             ) => l_id == r_id,
             (Self::Number { data: left }, Self::Number { data: right }) => left == right,
+            (Self::BigInt { data: left }, Self::BigInt { data: right }) => left == right,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
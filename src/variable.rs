@@ -3,6 +3,7 @@
 
 use crate::{
     errors::JSError,
+    heap::{Trace, Tracer},
     values::{JSResult, JSValue},
 };
 
@@ -27,6 +28,27 @@ impl Variable {
         }
     }
 
+    /// A binding that exists but can't be used until its declaration runs, like the exports of
+    /// a module that hasn't been evaluated yet.
+    pub fn new_uninitialized(mutable: bool) -> Self {
+        Self {
+            is_initialized: false,
+            is_expired: false,
+            is_mutable: mutable,
+            value: JSValue::Undefined,
+        }
+    }
+
+    pub fn initialize(&mut self, mutable: bool, value: JSValue) {
+        self.is_initialized = true;
+        self.is_mutable = mutable;
+        self.value = value;
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
     pub fn expire_variable(&mut self) {
         self.is_expired = true;
     }
@@ -35,6 +57,8 @@ impl Variable {
         self.is_expired
     }
 
+    /// Only reachable through `Heap::get_variable_mut`, which runs the collector's write
+    /// barrier for the variable.
     pub fn update_value(&mut self, value: JSValue) -> JSResult<JSValue> {
         if self.is_mutable {
             self.value = value;
@@ -56,6 +80,12 @@ impl Variable {
     }
 }
 
+impl Trace for Variable {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.value.trace(tracer);
+    }
+}
+
 impl std::fmt::Display for Variable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Variable: {:?}", self.value)
@@ -1,7 +1,18 @@
 #[cfg(test)]
 mod tests {
 
-    use v8::Interpreter;
+    use std::{
+        collections::HashMap,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    use v8::{
+        Array, BufferConsole, Console, ConsoleMessage, Function, GcOptions, Global, HandleScope,
+        InspectOptions, Interpreter, Local, LogLevel, ModuleLoader, Object, TimeZone, Value,
+    };
 
     // Helper function to run source and capture output
     fn run_and_capture(source: &str) -> (String, String) {
@@ -749,4 +760,2956 @@ mod tests {
     //     let (_stdout, stderr) = run_and_capture("let x = 5; x();");
     //     assert!(stderr.contains("TypeError") || stderr.contains("not a function"));
     // }
+
+    // ==========================================================================
+    // ACCESSORS AND PROPERTY DESCRIPTORS
+    // ==========================================================================
+
+    #[test]
+    fn test_object_literal_accessors() {
+        let source = r#"
+            let temp = {
+                _celsius: 10,
+                get fahrenheit() { return this._celsius * 2 + 30; },
+                set fahrenheit(f) { this._celsius = (f - 30) / 2; }
+            };
+            console.log(temp.fahrenheit);
+            temp.fahrenheit = 70;
+            console.log(temp._celsius);
+        "#;
+        assert_eq!(run(source), "50\n20\n");
+    }
+
+    #[test]
+    fn test_class_accessors_and_methods() {
+        let source = r#"
+            class Rect {
+                constructor(w, h) { this.w = w; this.h = h; }
+                get area() { return this.w * this.h; }
+                set side(s) { this.w = s; this.h = s; }
+                scale(k) { return new Rect(this.w * k, this.h * k); }
+                static unit() { return new Rect(1, 1); }
+            }
+            let r = new Rect(2, 3);
+            console.log(r.area);
+            r.side = 4;
+            console.log(r.area);
+            console.log(r.scale(2).area);
+            console.log(Rect.unit().area);
+            console.log(r);
+        "#;
+        assert_eq!(run(source), "6\n16\n64\n1\nRect { w: 4, h: 4 }\n");
+    }
+
+    #[test]
+    fn test_class_constructor_requires_new() {
+        let (_stdout, stderr) = run_and_capture("class A {} A();");
+        assert!(stderr.contains("TypeError"));
+    }
+
+    #[test]
+    fn test_define_property_attributes() {
+        let source = r#"
+            let obj = {};
+            Object.defineProperty(obj, 'fixed', { value: 1 });
+            obj.fixed = 2;
+            console.log(obj.fixed);
+            let desc = Object.getOwnPropertyDescriptor(obj, 'fixed');
+            console.log(desc.writable);
+            console.log(desc.enumerable);
+            console.log(desc.configurable);
+            Object.defineProperty(obj, 'computed', { get: function() { return 'hi'; } });
+            console.log(obj.computed);
+        "#;
+        assert_eq!(run(source), "1\nfalse\nfalse\nfalse\nhi\n");
+    }
+
+    #[test]
+    fn test_redefine_non_configurable_throws() {
+        let source = r#"
+            let obj = {};
+            Object.defineProperty(obj, 'x', { value: 1 });
+            Object.defineProperty(obj, 'x', { value: 2 });
+        "#;
+        let (_stdout, stderr) = run_and_capture(source);
+        assert!(stderr.contains("Cannot redefine property: x"));
+    }
+
+    #[test]
+    fn test_freeze_seal_prevent_extensions() {
+        let source = r#"
+            let frozen = Object.freeze({ a: 1 });
+            frozen.a = 2;
+            frozen.b = 3;
+            console.log(frozen.a);
+            console.log(frozen.b);
+            console.log(Object.isFrozen(frozen));
+            let sealed = Object.seal({ a: 1 });
+            sealed.a = 2;
+            console.log(sealed.a);
+            console.log(Object.isSealed(sealed));
+            console.log(Object.isFrozen(sealed));
+            let closed = Object.preventExtensions({});
+            console.log(Object.isExtensible(closed));
+        "#;
+        assert_eq!(run(source), "1\nundefined\ntrue\n2\ntrue\nfalse\nfalse\n");
+    }
+
+    #[test]
+    fn test_array_with_non_writable_length_cannot_grow() {
+        let source = r#"
+            let a = [1, 2, 3, 4, 5];
+            Object.defineProperty(a, 'length', { writable: false });
+            a[10] = 1;
+            a[2] = 9;
+            console.log(a.length + ' ' + a[10] + ' ' + a[2]);
+            console.log(Reflect.defineProperty(a, '5', { value: 1 }) + ' ' + Reflect.set(a, 7, 1));
+            try { Object.defineProperty(a, '6', { value: 1 }); } catch (e) { console.log(e.name); }
+        "#;
+        assert_eq!(run(source), "5 undefined 9\nfalse false\nTypeError\n");
+    }
+
+    #[test]
+    fn test_global_value_properties_are_read_only() {
+        let source = r#"
+            NaN = 5;
+            Infinity = 1;
+            undefined = 1;
+            console.log(NaN + ' ' + Infinity + ' ' + undefined);
+            const desc = Object.getOwnPropertyDescriptor(globalThis, 'undefined');
+            console.log(desc.writable + ' ' + desc.enumerable + ' ' + desc.configurable);
+            console.log(Reflect.deleteProperty(globalThis, 'NaN') + ' ' + Reflect.set(globalThis, 'Infinity', 0));
+            function shadow(undefined) { return undefined; }
+            console.log(shadow(3));
+        "#;
+        assert_eq!(
+            run(source),
+            "NaN Infinity undefined\nfalse false false\nfalse false\n3\n"
+        );
+    }
+
+    // ==========================================================================
+    // OBJECT STATIC METHODS
+    // ==========================================================================
+
+    #[test]
+    fn test_object_keys_enumeration_order() {
+        let source = r#"
+            let obj = { b: 1, a: 2, 10: 'ten', 2: 'two' };
+            console.log(Object.keys(obj));
+            console.log(Object.values(obj));
+            console.log(Object.entries({ x: 1, y: 2 }));
+        "#;
+        assert_eq!(
+            run(source),
+            "[ '2', '10', 'b', 'a' ]\n[ 'two', 'ten', 1, 2 ]\n[ [ 'x', 1 ], [ 'y', 2 ] ]\n"
+        );
+    }
+
+    #[test]
+    fn test_object_assign_and_from_entries() {
+        let source = r#"
+            let target = Object.assign({ a: 1 }, { b: 2 }, null, { a: 3 });
+            console.log(target);
+            console.log(Object.fromEntries([['k', 1], ['j', 2]]));
+        "#;
+        assert_eq!(run(source), "{ a: 3, b: 2 }\n{ k: 1, j: 2 }\n");
+    }
+
+    #[test]
+    fn test_object_own_property_names() {
+        let source = r#"
+            let obj = {};
+            Object.defineProperty(obj, 'hidden', { value: 1 });
+            obj.shown = 2;
+            console.log(Object.getOwnPropertyNames(obj));
+            console.log(Object.keys(obj));
+            console.log(Object.getOwnPropertySymbols(obj).length);
+        "#;
+        assert_eq!(run(source), "[ 'hidden', 'shown' ]\n[ 'shown' ]\n0\n");
+    }
+
+    #[test]
+    fn test_object_is() {
+        let source = r#"
+            console.log(Object.is(NaN, NaN));
+            console.log(Object.is(0, -0));
+            console.log(Object.is('a', 'a'));
+        "#;
+        assert_eq!(run(source), "true\nfalse\ntrue\n");
+    }
+
+    #[test]
+    fn test_object_group_by() {
+        let source = r#"
+            let groups = Object.groupBy([1, 2, 3, 4, 5], (n) => {
+                if (n % 2 === 0) {
+                    return 'even';
+                }
+                return 'odd';
+            });
+            console.log(Object.keys(groups));
+            console.log(groups.odd);
+            console.log(groups.even);
+        "#;
+        assert_eq!(run(source), "[ 'odd', 'even' ]\n[ 1, 3, 5 ]\n[ 2, 4 ]\n");
+    }
+
+    #[test]
+    fn test_object_callable() {
+        let source = r#"
+            let obj = { a: 1 };
+            console.log(Object(obj) === obj);
+            console.log(new Object());
+            console.log(typeof Object);
+        "#;
+        assert_eq!(run(source), "true\n{}\nfunction\n");
+    }
+
+    #[test]
+    fn test_object_wraps_primitives() {
+        let source = r#"
+            console.log(Object(1), Object(true), Object('ab'), Object(Symbol('s')), Object(10n));
+            console.log(JSON.stringify(Object(true)), JSON.stringify([Object(1), Object('x')]));
+            console.log(Object(1) + 1, Object('ab') + '!', Object('ab').length, Object('ab')[1]);
+            console.log(Object(true).toString(), Object('a').toString(), Object(2n).toString());
+            console.log(Object(1) === 1, typeof Object('a'), Object.keys(Object('ab')));
+            console.log(Object(null), Object(undefined), Object(Symbol('q')).toString());
+        "#;
+        assert_eq!(
+            run(source),
+            "[Number: 1] [Boolean: true] [String: 'ab'] [Symbol: Symbol(s)] [BigInt: 10n]\n\
+             true [1,\"x\"]\n\
+             2 ab! 2 b\n\
+             [object Boolean] a 2\n\
+             false object [ '0', '1' ]\n\
+             {} {} Symbol(q)\n"
+        );
+    }
+
+    // ==========================================================================
+    // GENERATORS AND ITERATION
+    // ==========================================================================
+
+    #[test]
+    fn test_generator_next() {
+        let source = r#"
+            function* gen() {
+                let x = yield 1;
+                yield x + 1;
+                return 10;
+            }
+            const g = gen();
+            console.log(g.next().value);
+            console.log(g.next(5).value);
+            let last = g.next();
+            console.log(last.value);
+            console.log(last.done);
+            console.log(g.next().done);
+        "#;
+        assert_eq!(run(source), "1\n6\n10\ntrue\ntrue\n");
+    }
+
+    #[test]
+    fn test_for_of_and_spread() {
+        let source = r#"
+            function* count(n) {
+                for (let i = 0; i < n; i = i + 1) {
+                    yield i;
+                }
+            }
+            for (const v of count(2)) {
+                console.log(v);
+            }
+            console.log([...count(3)]);
+            function add(a, b, c) {
+                return a + b + c;
+            }
+            console.log(add(...[1, 2], 3));
+            console.log([...'ab']);
+        "#;
+        assert_eq!(run(source), "0\n1\n[ 0, 1, 2 ]\n6\n[ 'a', 'b' ]\n");
+    }
+
+    #[test]
+    fn test_string_iterator() {
+        let source = r#"
+            console.log(typeof ''[Symbol.iterator]);
+            const iterator = 'ab'[Symbol.iterator]();
+            console.log(iterator.next(), iterator.next().value, iterator.next().done);
+            console.log(iterator[Symbol.iterator]() === iterator);
+            String.prototype[Symbol.iterator] = function () {
+                let done = false;
+                const string = this;
+                return {
+                    next() {
+                        const result = { value: 'whole ' + string, done: done };
+                        done = true;
+                        return result;
+                    },
+                };
+            };
+            for (const c of 'xy') {
+                console.log(c);
+            }
+            console.log([...'ab']);
+        "#;
+        assert_eq!(
+            run(source),
+            "function\n{ value: 'a', done: false } b true\ntrue\nwhole xy\n[ 'whole ab' ]\n"
+        );
+    }
+
+    #[test]
+    fn test_yield_delegation() {
+        let source = r#"
+            function* inner() {
+                yield 1;
+                yield 2;
+                return 'inner done';
+            }
+            function* outer() {
+                const result = yield* inner();
+                yield result;
+                yield* [3, 4];
+            }
+            console.log([...outer()]);
+        "#;
+        assert_eq!(run(source), "[ 1, 2, 'inner done', 3, 4 ]\n");
+    }
+
+    #[test]
+    fn test_generator_return_runs_finally() {
+        let source = r#"
+            function* gen() {
+                try {
+                    yield 1;
+                    yield 2;
+                } finally {
+                    console.log('cleanup');
+                }
+            }
+            const g = gen();
+            g.next();
+            let result = g.return(7);
+            console.log(result.value);
+            console.log(result.done);
+            for (const v of gen()) {
+                break;
+            }
+        "#;
+        assert_eq!(run(source), "cleanup\n7\ntrue\ncleanup\n");
+    }
+
+    #[test]
+    fn test_generator_throw() {
+        let source = r#"
+            function* gen() {
+                while (true) {
+                    try {
+                        yield 1;
+                    } catch (e) {
+                        console.log('caught ' + e);
+                    }
+                }
+            }
+            const g = gen();
+            g.next();
+            console.log(g.throw('boom').value);
+        "#;
+        assert_eq!(run(source), "caught boom\n1\n");
+    }
+
+    #[test]
+    fn test_generator_methods_and_state() {
+        let source = r#"
+            const obj = {
+                *letters() {
+                    yield 'a';
+                    yield 'b';
+                },
+            };
+            console.log([...obj.letters()]);
+            let g;
+            function* reentrant() {
+                g.next();
+            }
+            g = reentrant();
+            try {
+                g.next();
+            } catch (e) {
+                console.log(e.message);
+            }
+        "#;
+        assert_eq!(run(source), "[ 'a', 'b' ]\nGenerator is already running\n");
+    }
+
+    #[test]
+    fn test_try_catch_finally() {
+        let source = r#"
+            try {
+                throw { name: 'MyError', message: 'bad' };
+            } catch (e) {
+                console.log(e.message);
+            }
+            try {
+                missing();
+            } catch (e) {
+                console.log(e.name);
+            } finally {
+                console.log('finally');
+            }
+        "#;
+        assert_eq!(run(source), "bad\nReferenceError\nfinally\n");
+        let (_, err) = run_and_capture("throw 'oops';");
+        assert_eq!(err, "Uncaught oops\n");
+    }
+
+    // ==========================================================================
+    // PROMISES AND ASYNC
+    // ==========================================================================
+
+    #[test]
+    fn test_promise_then_runs_after_script() {
+        let source = r#"
+            console.log('start');
+            new Promise((resolve) => resolve(1))
+                .then(v => v + 1)
+                .then(v => console.log('then ' + v));
+            queueMicrotask(() => console.log('microtask'));
+            console.log('end');
+        "#;
+        assert_eq!(run(source), "start\nend\nmicrotask\nthen 2\n");
+    }
+
+    #[test]
+    fn test_promise_catch_and_finally() {
+        let source = r#"
+            Promise.reject('bad')
+                .catch(e => { console.log('caught ' + e); return 'recovered'; })
+                .finally(() => console.log('finally'))
+                .then(v => console.log(v));
+            new Promise(() => { throw { name: 'Error', message: 'in executor' }; })
+                .catch(e => console.log(e.message));
+            new Promise(resolve => resolve(Promise.resolve('adopted')))
+                .then(v => console.log(v));
+        "#;
+        assert_eq!(
+            run(source),
+            "caught bad\nin executor\nfinally\nadopted\nrecovered\n"
+        );
+    }
+
+    #[test]
+    fn test_promise_combinators() {
+        let source = r#"
+            Promise.all([1, Promise.resolve(2), new Promise(r => r(3))])
+                .then(v => console.log(v));
+            Promise.all([Promise.resolve(1), Promise.reject('no')])
+                .catch(e => console.log('all ' + e));
+            Promise.allSettled([Promise.resolve(1), Promise.reject(2)])
+                .then(v => console.log(v[0].status + ' ' + v[1].reason));
+            Promise.race([Promise.resolve('a'), Promise.resolve('b')])
+                .then(v => console.log('race ' + v));
+            Promise.any([Promise.reject(1), Promise.resolve(2)])
+                .then(v => console.log('any ' + v));
+            Promise.any([]).catch(e => console.log(e.name));
+        "#;
+        assert_eq!(
+            run(source),
+            "AggregateError\n[ 1, 2, 3 ]\nall no\nfulfilled 2\nrace a\nany 2\n"
+        );
+    }
+
+    #[test]
+    fn test_async_await() {
+        let source = r#"
+            async function add(x) {
+                let a = await x;
+                let b = await Promise.resolve(a * 2);
+                return a + b;
+            }
+            add(5).then(v => console.log('add ' + v));
+            const fail = async () => { throw 'oops'; };
+            fail().catch(e => console.log('caught ' + e));
+            const recover = async x => {
+                try {
+                    await Promise.reject(x);
+                } catch (e) {
+                    return 'recovered ' + e;
+                }
+            };
+            recover('r').then(v => console.log(v));
+            let obj = { async value() { return await { then(resolve) { resolve(7); } }; } };
+            obj.value().then(v => console.log('thenable ' + v));
+            console.log('sync');
+        "#;
+        assert_eq!(
+            run(source),
+            "sync\ncaught oops\nrecovered r\nadd 15\nthenable 7\n"
+        );
+    }
+
+    #[test]
+    fn test_unhandled_rejection() {
+        let source = r#"
+            let late = Promise.reject('late');
+            queueMicrotask(() => late.catch(() => console.log('handled')));
+            Promise.reject({ name: 'Error', message: 'nobody' });
+        "#;
+        let (out, err) = run_and_capture(source);
+        assert_eq!(out, "handled\n");
+        assert_eq!(err, "Uncaught (in promise) Error: nobody\n");
+    }
+
+    // ==========================================================================
+    // TIMERS
+    // ==========================================================================
+
+    #[test]
+    fn test_timers_with_virtual_clock() {
+        let mut interpreter = Interpreter::new().with_virtual_clock().setup();
+        let source = r#"
+            setTimeout(() => console.log('late'), 100);
+            setTimeout((a, b) => console.log('soon ' + a + b), 0, 'x', 'y');
+            setImmediate(() => console.log('immediate'));
+            let cancelled = setTimeout(() => console.log('never'), 10);
+            clearTimeout(cancelled);
+            console.log('sync');
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "sync\nimmediate\n");
+        let (out, _) = interpreter.advance_time(50.0);
+        assert_eq!(out, "sync\nimmediate\nsoon xy\n");
+        assert!(interpreter.has_pending_timers());
+        let (out, _) = interpreter.advance_time(50.0);
+        assert_eq!(out, "sync\nimmediate\nsoon xy\nlate\n");
+        assert!(!interpreter.has_pending_timers());
+        assert_eq!(interpreter.now(), 100.0);
+    }
+
+    #[test]
+    fn test_set_interval_and_microtasks() {
+        let mut interpreter = Interpreter::new().with_virtual_clock().setup();
+        let source = r#"
+            let count = 0;
+            let id = setInterval(() => {
+                count = count + 1;
+                console.log('tick ' + count);
+                Promise.resolve(count).then(c => console.log('micro ' + c));
+                if (count === 3) {
+                    clearInterval(id);
+                }
+            }, 10);
+            setTimeout(() => console.log('between'), 15);
+        "#;
+        interpreter.interpret(source).unwrap();
+        let (out, _) = interpreter.advance_time(1000.0);
+        assert_eq!(
+            out,
+            "tick 1\nmicro 1\nbetween\ntick 2\nmicro 2\ntick 3\nmicro 3\n"
+        );
+    }
+
+    #[test]
+    fn test_event_loop_runs_until_timers_done() {
+        let mut interpreter = Interpreter::new().with_virtual_clock().setup();
+        let source = r#"
+            setTimeout(() => {
+                console.log('first');
+                setTimeout(() => console.log('second'), 1000);
+            }, 1000);
+        "#;
+        interpreter.interpret(source).unwrap();
+        let (out, _) = interpreter.run_event_loop();
+        assert_eq!(out, "first\nsecond\n");
+        assert_eq!(interpreter.now(), 2000.0);
+    }
+
+    #[test]
+    fn test_uncaught_errors_are_counted() {
+        let mut interpreter = Interpreter::new().with_virtual_clock().setup();
+        interpreter
+            .interpret("try { missing(); } catch (e) {} console.log('fine');")
+            .unwrap();
+        assert_eq!(interpreter.uncaught_errors(), 0);
+        let source = r#"
+            setTimeout(() => { throw 'late'; }, 10);
+            Promise.reject(1);
+        "#;
+        interpreter.interpret(source).unwrap();
+        assert_eq!(interpreter.uncaught_errors(), 1);
+        let (_, err) = interpreter.run_event_loop();
+        assert_eq!(err, "Uncaught (in promise) 1\nUncaught late\n");
+        assert_eq!(interpreter.uncaught_errors(), 2);
+    }
+
+    // ==========================================================================
+    // MODULES
+    // ==========================================================================
+
+    /// Serves modules from memory, keyed by their specifier with any leading `./` dropped.
+    struct MemoryLoader {
+        modules: HashMap<String, String>,
+    }
+
+    impl ModuleLoader for MemoryLoader {
+        fn resolve(&self, specifier: &str, _referrer: Option<&str>) -> Result<String, String> {
+            let key = specifier.trim_start_matches("./").to_string();
+            match self.modules.contains_key(&key) {
+                true => Ok(key),
+                false => Err(format!("Cannot find module '{specifier}'")),
+            }
+        }
+
+        fn load(&self, key: &str) -> Result<String, String> {
+            Ok(self.modules[key].clone())
+        }
+    }
+
+    fn run_modules(entry: &str, modules: &[(&str, &str)]) -> (String, String) {
+        let modules = modules
+            .iter()
+            .map(|(key, source)| (key.to_string(), source.to_string()))
+            .collect();
+        let mut interpreter = Interpreter::new()
+            .with_module_loader(MemoryLoader { modules })
+            .setup();
+        interpreter.run_module(entry)
+    }
+
+    #[test]
+    fn test_named_default_and_namespace_imports() {
+        let lib = r#"
+            export const a = 1;
+            export function double(x) { return x * 2; }
+            export default 'fallback';
+            const hidden = 3;
+            export { hidden as shown };
+        "#;
+        let main = r#"
+            import fallback, { a, double as twice, shown } from './lib.js';
+            import * as lib from './lib.js';
+            console.log(fallback);
+            console.log(twice(a) + shown);
+            console.log(Object.keys(lib).length);
+            console.log(lib.default + ' ' + lib.shown);
+            console.log(Object.isExtensible(lib));
+        "#;
+        let (out, _) = run_modules("main.js", &[("main.js", main), ("lib.js", lib)]);
+        assert_eq!(out, "fallback\n5\n4\nfallback 3\nfalse\n");
+    }
+
+    #[test]
+    fn test_live_bindings_are_read_only() {
+        let counter = r#"
+            export let count = 0;
+            export function increment() { count += 1; }
+        "#;
+        let main = r#"
+            import { count, increment } from './counter.js';
+            import * as counter from './counter.js';
+            increment();
+            increment();
+            console.log(count + ' ' + counter.count);
+            try { count = 10; } catch (e) { console.log(e.message); }
+        "#;
+        let (out, _) = run_modules("main.js", &[("main.js", main), ("counter.js", counter)]);
+        assert_eq!(out, "2 2\nAssignment to constant variable.\n");
+    }
+
+    #[test]
+    fn test_re_exports() {
+        let a = "export const one = 1; export default 'a default';";
+        let b = "export const two = 2;";
+        let index = r#"
+            export * from './a.js';
+            export { two as deux } from './b.js';
+            export * as bee from './b.js';
+            import { one } from './a.js';
+            export { one as uno };
+        "#;
+        let main = r#"
+            import { one, uno, deux, bee } from './index.js';
+            import * as index from './index.js';
+            console.log(one + uno + deux + bee.two);
+            console.log(index.default);
+        "#;
+        let modules = [
+            ("main.js", main),
+            ("index.js", index),
+            ("a.js", a),
+            ("b.js", b),
+        ];
+        let (out, _) = run_modules("main.js", &modules);
+        assert_eq!(out, "6\nundefined\n");
+    }
+
+    #[test]
+    fn test_import_cycles() {
+        let a = r#"
+            import { b } from './b.js';
+            console.log('a runs');
+            export function a() { return 'a'; }
+            export const late = 'late';
+            console.log(b());
+        "#;
+        let b = r#"
+            import { a, late } from './a.js';
+            console.log('b runs ' + a());
+            try { late; } catch (e) { console.log(e.message); }
+            export function b() { return 'b sees ' + late; }
+        "#;
+        let (out, _) = run_modules("a.js", &[("a.js", a), ("b.js", b)]);
+        assert_eq!(
+            out,
+            "b runs a\nCannot access 'late' before initialization\na runs\nb sees late\n"
+        );
+    }
+
+    #[test]
+    fn test_modules_run_once_and_errors() {
+        let shared = "console.log('shared runs'); export const value = 1;";
+        let main = r#"
+            import './shared.js';
+            import { value } from './shared.js';
+            import { missing } from './shared.js';
+        "#;
+        let (out, err) = run_modules("main.js", &[("main.js", main), ("shared.js", shared)]);
+        assert_eq!(out, "");
+        assert!(err.contains("does not provide an export named 'missing'"));
+
+        let main = "import './shared.js'; import { value } from './shared.js'; console.log(value);";
+        let (out, _) = run_modules("main.js", &[("main.js", main), ("shared.js", shared)]);
+        assert_eq!(out, "shared runs\n1\n");
+
+        let (_, err) = run_modules("main.js", &[("main.js", "import './nowhere.js';")]);
+        assert!(err.contains("Cannot find module './nowhere.js'"));
+    }
+
+    #[test]
+    fn test_dynamic_import() {
+        let lib = "export default 42; export const name = 'lib';";
+        let main = r#"
+            import('./lib.js').then(lib => console.log(lib.default + ' ' + lib.name));
+            import('./missing.js').catch(e => console.log(e.message));
+            const load = async () => {
+                const lib = await import('./lib.js');
+                console.log('awaited ' + lib.name);
+            };
+            load();
+            console.log('sync');
+        "#;
+        let (out, _) = run_modules("main.js", &[("main.js", main), ("lib.js", lib)]);
+        assert_eq!(
+            out,
+            "sync\n42 lib\nCannot find module './missing.js'\nawaited lib\n"
+        );
+    }
+
+    // ==========================================================================
+    // COMMONJS
+    // ==========================================================================
+
+    fn memory_loader(modules: &[(&str, &str)]) -> MemoryLoader {
+        let modules = modules
+            .iter()
+            .map(|(key, source)| (key.to_string(), source.to_string()))
+            .collect();
+        MemoryLoader { modules }
+    }
+
+    #[test]
+    fn test_require_and_module_exports() {
+        let files = [
+            (
+                "main.js",
+                r#"
+                const math = require('./math');
+                const square = require('./square.js');
+                const config = require('./config.json');
+                console.log(math.add(1, 2) + square(3));
+                console.log(config.name + ' ' + config.values[1]);
+                console.log(require('./math') === math);
+                console.log(this === module.exports);
+                console.log(__filename + ' ' + __dirname);
+                console.log(require.resolve('./util'));
+            "#,
+            ),
+            (
+                "math.js",
+                "console.log('math loads'); exports.add = (a, b) => a + b;",
+            ),
+            (
+                "square.js",
+                "module.exports = function (x) { return x * x; };",
+            ),
+            (
+                "config.json",
+                r#"{ "name": "config", "values": [1, 2, 3] }"#,
+            ),
+            ("util/index.js", "module.exports = {};"),
+        ];
+        let mut interpreter = Interpreter::new()
+            .with_module_loader(memory_loader(&files))
+            .setup();
+        let (out, _) = interpreter.run_commonjs("main.js");
+        assert_eq!(
+            out,
+            "math loads\n12\nconfig 2\ntrue\ntrue\nmain.js .\nutil/index.js\n"
+        );
+    }
+
+    #[test]
+    fn test_circular_require() {
+        let files = [
+            (
+                "a.js",
+                r#"
+                exports.done = false;
+                const b = require('./b.js');
+                console.log('in a, b.done = ' + b.done);
+                exports.done = true;
+            "#,
+            ),
+            (
+                "b.js",
+                r#"
+                const a = require('./a.js');
+                console.log('in b, a.done = ' + a.done);
+                exports.done = true;
+            "#,
+            ),
+        ];
+        let mut interpreter = Interpreter::new()
+            .with_module_loader(memory_loader(&files))
+            .setup();
+        let (out, _) = interpreter.run_commonjs("a.js");
+        assert_eq!(out, "in b, a.done = false\nin a, b.done = true\n");
+    }
+
+    #[test]
+    fn test_global_require_and_errors() {
+        let files = [
+            (
+                "thrower.js",
+                "console.log('runs'); throw { message: 'broken' };",
+            ),
+            ("ok.js", "module.exports = 'ok';"),
+        ];
+        let mut interpreter = Interpreter::new()
+            .with_module_loader(memory_loader(&files))
+            .with_commonjs()
+            .setup();
+        let source = r#"
+            console.log(require('./ok'));
+            try { require('./missing'); } catch (e) { console.log(e.message); }
+            try { require('./thrower'); } catch (e) { console.log(e.message); }
+            try { require('./thrower'); } catch (e) { console.log(e.message); }
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(
+            out,
+            "ok\nCannot find module './missing'\nruns\nbroken\nruns\nbroken\n"
+        );
+        assert_eq!(run("console.log(typeof require);"), "undefined\n");
+    }
+
+    // ==========================================================================
+    // JSON
+    // ==========================================================================
+
+    #[test]
+    fn test_json_parse_and_stringify() {
+        let source = r#"
+            const value = JSON.parse(' {"a": [1, 2.5e1, -0.5, true, null, "xy"], "b": {}, "a": 3} ');
+            console.log(value.a);
+            console.log(JSON.stringify(value));
+            console.log(JSON.stringify({ u: undefined, f: function () {}, n: NaN, s: 'q"' }));
+            console.log(JSON.stringify([undefined, function () {}]));
+            console.log(JSON.stringify(undefined));
+            console.log(JSON.stringify({ d: { toJSON: function (key) { return 'key ' + key; } } }));
+        "#;
+        assert_eq!(
+            run(source),
+            "3\n{\"a\":3,\"b\":{}}\n{\"n\":null,\"s\":\"q\\\"\"}\n[null,null]\nundefined\n{\"d\":\"key d\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_json_reviver_replacer_and_space() {
+        let source = r#"
+            const revived = JSON.parse('{"a": 1, "b": {"c": 2}, "d": 0}', function (key, value) {
+                if (value === 0) { return undefined; }
+                if (typeof value === 'number') { return value + 1; }
+                return value;
+            });
+            console.log(JSON.stringify(revived));
+            console.log(JSON.stringify({ a: 1, b: 2, c: { a: 3, d: 4 } }, ['a', 'c', 'a']));
+            console.log(JSON.stringify({ a: 1, b: 'x' }, function (key, value) {
+                if (typeof value === 'number') { return value * 10; }
+                return value;
+            }));
+            console.log(JSON.stringify({ a: [1, {}], e: [] }, null, '--'));
+            console.log(JSON.stringify([1], null, 20));
+        "#;
+        assert_eq!(
+            run(source),
+            "{\"a\":2,\"b\":{\"c\":3}}\n{\"a\":1,\"c\":{\"a\":3}}\n{\"a\":10,\"b\":\"x\"}\n{\n--\"a\": [\n----1,\n----{}\n--],\n--\"e\": []\n}\n[\n          1\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let source = r#"
+            const cycle = {};
+            cycle.self = cycle;
+            try { JSON.stringify(cycle); } catch (e) { console.log(e.message); }
+            try { JSON.parse('{"a" 1}'); } catch (e) { console.log(e.message); }
+            try { JSON.parse('[1, 2'); } catch (e) { console.log(e.message); }
+            try { JSON.parse('[1 2]'); } catch (e) { console.log(e.message); }
+            try { JSON.parse('{"a": 1,}'); } catch (e) { console.log(e.message); }
+            try { JSON.parse('01'); } catch (e) { console.log(e.message); }
+            try { JSON.parse('nul'); } catch (e) { console.log(e.message); }
+            try { JSON.parse('"abc'); } catch (e) { console.log(e.message); }
+            JSON.parse('{');
+        "#;
+        let (out, err) = run_and_capture(source);
+        assert_eq!(
+            out,
+            "Converting circular structure to JSON\n\
+             Expected ':' after property name in JSON at position 5\n\
+             Unexpected end of JSON input\n\
+             Expected ',' or ']' after array element in JSON at position 3\n\
+             Expected property name or '}' in JSON at position 8\n\
+             Unexpected non-whitespace character after JSON at position 1\n\
+             Unexpected end of JSON input\n\
+             Unterminated string in JSON at position 4\n"
+        );
+        assert_eq!(err, "Uncaught SyntaxError: Unexpected end of JSON input\n");
+    }
+
+    #[test]
+    fn test_json_from_the_host() {
+        let mut interpreter = Interpreter::new().setup();
+        interpreter
+            .set_global_json(
+                "config",
+                r#"{"name": "app", "retries": 3, "tags": ["a", "b"]}"#,
+            )
+            .unwrap();
+        let source = r#"
+            console.log(config.name);
+            var result = { total: config.retries * 2, tags: config.tags, skipped: undefined };
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "app\n");
+        assert_eq!(
+            interpreter.get_global_json("result").unwrap().as_deref(),
+            Some(r#"{"total":6,"tags":["a","b"]}"#)
+        );
+        assert_eq!(interpreter.get_global_json("undefined").unwrap(), None);
+        assert!(interpreter.get_global_json("missing").is_err());
+        assert_eq!(
+            interpreter.set_global_json("bad", "[1,]").unwrap_err(),
+            "Uncaught SyntaxError: Unexpected token ']' in JSON at position 3"
+        );
+    }
+
+    // ==========================================================================
+    // MATH
+    // ==========================================================================
+
+    #[test]
+    fn test_math_functions() {
+        let source = r#"
+            console.log(Math.abs(-2) + Math.floor(1.7) + Math.ceil(1.2) + Math.trunc(-4.7));
+            console.log(Math.round(2.5) + ' ' + Math.round(-2.5) + ' ' + Math.round(0.49999999999999994));
+            console.log(Object.is(Math.round(-0.4), -0));
+            console.log(Math.sign(-3) + Math.sqrt(16) + Math.cbrt(27) + Math.pow(2, 10));
+            console.log(Math.hypot(3, 4));
+            console.log(Math.hypot(NaN, Infinity) === Infinity);
+            console.log(Math.clz32(1) + ' ' + Math.imul(4294967295, 5));
+            console.log(Math.fround(5.05));
+            console.log(Math.log2(8) + Math.log10(1000) + Math.exp(0) + Math.expm1(0));
+            console.log(Math.cos(0) + Math.tanh(0) + Math.atan2(0, 1));
+        "#;
+        assert_eq!(
+            run(source),
+            "1\n3 -2 0\ntrue\n1030\n5\ntrue\n31 -5\n5.050000190734863\n7\n1\n"
+        );
+    }
+
+    #[test]
+    fn test_math_min_max_and_constants() {
+        let source = r#"
+            console.log(Math.max(1, 3, 2) + ' ' + Math.min(1, 3, 2));
+            console.log(Math.min(1, NaN, 3));
+            console.log(Math.max() === -Infinity);
+            console.log(Object.is(Math.max(-0, 0), 0));
+            console.log(Object.is(Math.min(0, -0), -0));
+            console.log(Math.PI);
+            Math.PI = 3;
+            console.log(Math.PI === 3.141592653589793);
+            console.log(Object.keys(Math).length);
+        "#;
+        assert_eq!(
+            run(source),
+            "3 1\nNaN\ntrue\ntrue\ntrue\n3.141592653589793\ntrue\n0\n"
+        );
+    }
+
+    #[test]
+    fn test_math_random_is_seedable() {
+        let source = r#"
+            var first = Math.random();
+            var second = Math.random();
+            console.log(first >= 0 && first < 1 && first !== second);
+        "#;
+        let draw = |seed: u64| {
+            let mut interpreter = Interpreter::new().with_random_seed(seed).setup();
+            let (out, _) = interpreter.interpret(source).unwrap();
+            assert_eq!(out, "true\n");
+            interpreter.get_global_json("first").unwrap()
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(7));
+
+        let mut interpreter = Interpreter::new().setup();
+        interpreter.set_random_seed(42);
+        interpreter.interpret(source).unwrap();
+        assert_eq!(interpreter.get_global_json("first").unwrap(), draw(42));
+    }
+
+    // ==========================================================================
+    // NUMBER
+    // ==========================================================================
+
+    #[test]
+    fn test_number_to_string() {
+        let source = r#"
+            console.log(Math.pow(10, 21));
+            console.log(Math.pow(10, 20));
+            console.log(0.0000001);
+            console.log(-0);
+            console.log(0.1 + 0.2);
+            console.log('' + Number('779539845543410.25'));
+            console.log((255).toString(16) + ' ' + (255).toString(2) + ' ' + (-255.5).toString(16));
+            console.log((0.5).toString(2) + ' ' + (25).toString(36));
+        "#;
+        assert_eq!(
+            run(source),
+            "1e+21\n100000000000000000000\n1e-7\n-0\n0.30000000000000004\n779539845543410.2\nff 11111111 -ff.8\n0.1 p\n"
+        );
+    }
+
+    #[test]
+    fn test_number_formatting_methods() {
+        let source = r#"
+            console.log((1.005).toFixed(2) + ' ' + (2.5).toFixed(0) + ' ' + (-1.5).toFixed(0));
+            console.log((0).toFixed(2) + ' ' + (0.000001).toFixed(7) + ' ' + Math.pow(10, 21).toFixed(2));
+            console.log((123.456).toExponential(2) + ' ' + (123.456).toExponential() + ' ' + (0).toExponential());
+            console.log((123.456).toPrecision(4) + ' ' + (0.00000123).toPrecision(2));
+            console.log((123456).toPrecision(2) + ' ' + (99.99).toPrecision(3));
+            try { (1).toFixed(101); } catch (e) { console.log(e.message); }
+            try { (1).toString(1); } catch (e) { console.log(e.message); }
+            try { (1).toPrecision(0); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "1.00 3 -2\n0.00 0.0000010 1e+21\n1.23e+2 1.23456e+2 0e+0\n123.5 0.0000012\n1.2e+5 100\n\
+             toFixed() digits argument must be between 0 and 100\n\
+             toString() radix argument must be between 2 and 36\n\
+             toPrecision() argument must be between 1 and 100\n"
+        );
+    }
+
+    #[test]
+    fn test_number_constructor_and_statics() {
+        let source = r#"
+            console.log(Number('  12  ') + Number('') + Number('0x10'));
+            console.log(Number('12px') + ' ' + Number(null) + ' ' + Number() + ' ' + Number('-Infinity'));
+            console.log(Number.isInteger(5) + ' ' + Number.isInteger(5.5) + ' ' + Number.isSafeInteger(Math.pow(2, 53)));
+            console.log(Number.isNaN('abc') + ' ' + isNaN('abc') + ' ' + Number.isFinite('12') + ' ' + isFinite('12'));
+            console.log(Number.MAX_SAFE_INTEGER + ' ' + Number.EPSILON + ' ' + Number.MIN_VALUE);
+            const boxed = new Number(5);
+            console.log(typeof boxed + ' ' + (boxed + 1) + ' ' + boxed.toFixed(1));
+            Number.MAX_VALUE = 1;
+            console.log(Number.MAX_VALUE);
+        "#;
+        assert_eq!(
+            run(source),
+            "28\nNaN 0 0 -Infinity\ntrue false false\nfalse true false true\n\
+             9007199254740991 2.220446049250313e-16 5e-324\nobject 6 5.0\n1.7976931348623157e+308\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_int_and_parse_float() {
+        let source = r#"
+            console.log(parseInt('  42px') + ' ' + parseInt('0x1F') + ' ' + parseInt('101', 2) + ' ' + parseInt('-z', 36));
+            console.log(parseInt('abc') + ' ' + parseInt('12', 1) + ' ' + parseInt('0x1F', 10));
+            console.log(parseFloat('3.14abc') + ' ' + parseFloat('.5') + ' ' + parseFloat('-Infinityx') + ' ' + parseFloat('e5'));
+            console.log(Number.parseInt === parseInt && Number.parseFloat === parseFloat);
+        "#;
+        assert_eq!(
+            run(source),
+            "42 31 5 -35\nNaN NaN 0\n3.14 0.5 -Infinity NaN\ntrue\n"
+        );
+    }
+
+    // ==========================================================================
+    // BIGINT
+    // ==========================================================================
+
+    #[test]
+    fn test_bigint_arithmetic() {
+        let source = r#"
+            const id = 18446744073709551615n;
+            console.log(typeof id + ' ' + (id + 1n) + ' ' + id * id);
+            console.log(-7n / 2n + ' ' + -7n % 2n + ' ' + (10n - 20n) + ' ' + -id);
+            console.log((5n & -3n) + ' ' + (5n | -3n) + ' ' + (5n ^ -3n) + ' ' + ~5n);
+            console.log((1n << 64n) + ' ' + (-9n >> 2n) + ' ' + (1n >> 100n) + ' ' + (8n << -2n));
+            let shifted = 3n;
+            shifted <<= 2n;
+            console.log(shifted + ' ' + !0n + ' ' + !!1n);
+        "#;
+        assert_eq!(
+            run(source),
+            "bigint 18446744073709551616 340282366920938463426481119284349108225\n\
+             -3 -1 -10 -18446744073709551615\n\
+             5 -3 -8 -6\n\
+             18446744073709551616 -3 0 2\n\
+             12 true true\n"
+        );
+    }
+
+    #[test]
+    fn test_bigint_comparisons_and_equality() {
+        let source = r#"
+            console.log((1n < 2) + ' ' + (2n > 1.5) + ' ' + (2n <= 2) + ' ' + (1n < NaN));
+            console.log((9007199254740993n > 9007199254740992) + ' ' + (10n > '9') + ' ' + (1n < 'x'));
+            console.log((1n == 1) + ' ' + (1n == '1') + ' ' + (1n == 1.5) + ' ' + (0n == false));
+            console.log((1n === 1) + ' ' + (5n === 5n) + ' ' + (5n !== 6n) + ' ' + (1n == 'x'));
+        "#;
+        assert_eq!(
+            run(source),
+            "true true true false\ntrue true false\ntrue true false true\nfalse true true false\n"
+        );
+    }
+
+    #[test]
+    fn test_bigint_conversions() {
+        let source = r#"
+            console.log(BigInt(42) + ' ' + BigInt('  0x1f ') + ' ' + BigInt('-12') + ' ' + BigInt(true));
+            console.log(BigInt(9007199254740993) + ' ' + Number(12345678901234567890n));
+            console.log(BigInt.asIntN(64, 18446744073709551615n) + ' ' + BigInt.asUintN(64, -1n));
+            console.log(BigInt.asIntN(8, 255n) + ' ' + BigInt.asUintN(8, 257n) + ' ' + BigInt.asIntN(0, 5n));
+            console.log((255n).toString(16) + ' ' + (-255n).toString(2) + ' ' + 10n.toString());
+            console.log(BigInt.prototype.valueOf === (1n).valueOf);
+        "#;
+        assert_eq!(
+            run(source),
+            "42 31 -12 1\n9007199254740992 12345678901234567000\n-1 18446744073709551615\n\
+             -1 1 0\nff -11111111 10\ntrue\n"
+        );
+    }
+
+    #[test]
+    fn test_bigint_errors() {
+        let source = r#"
+            try { 1n + 1; } catch (e) { console.log(e.message); }
+            try { 1n / 0n; } catch (e) { console.log(e.message); }
+            try { 1n >>> 1n; } catch (e) { console.log(e.message); }
+            try { +1n; } catch (e) { console.log(e.message); }
+            try { BigInt(1.5); } catch (e) { console.log(e.message); }
+            try { BigInt('1.5'); } catch (e) { console.log(e.message); }
+            try { BigInt(undefined); } catch (e) { console.log(e.message); }
+            try { new BigInt(1); } catch (e) { console.log(e.message); }
+            try { BigInt.asIntN(64, 5); } catch (e) { console.log(e.message); }
+            try { (1n).toString(37); } catch (e) { console.log(e.message); }
+            try { JSON.stringify({ id: 1n }); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "Cannot mix BigInt and other types, use explicit conversions\n\
+             Division by zero\n\
+             BigInts have no unsigned right shift, use >> instead\n\
+             Cannot convert a BigInt value to a number\n\
+             The number 1.5 cannot be converted to a BigInt because it is not an integer\n\
+             Cannot convert 1.5 to a BigInt\n\
+             Cannot convert undefined to a BigInt\n\
+             BigInt is not a constructor\n\
+             Cannot convert 5 to a BigInt\n\
+             toString() radix argument must be between 2 and 36\n\
+             Do not know how to serialize a BigInt\n"
+        );
+    }
+
+    // ============================================================================
+    // SYMBOLS
+    // ============================================================================
+
+    #[test]
+    fn test_symbols_as_property_keys() {
+        let source = r#"
+            const s = Symbol('k');
+            const o = { a: 1, [s]: 2 };
+            o[Symbol.iterator] = 3;
+            console.log(typeof s + ' ' + s.description + ' ' + s.toString() + ' ' + Symbol().description);
+            console.log((Symbol('k') === s) + ' ' + (o[s] === 2) + ' ' + Object.keys(o).length);
+            console.log(Object.getOwnPropertySymbols(o).length + ' ' + (Object.getOwnPropertySymbols(o)[0] === s));
+            console.log(JSON.stringify(o) + ' ' + JSON.stringify(Object.assign({}, o)[s]));
+            console.log(o);
+            const m = { [s]() {} };
+            console.log(m[s].name);
+        "#;
+        assert_eq!(
+            run(source),
+            "symbol k Symbol(k) undefined\nfalse true 1\n2 true\n{\"a\":1} 2\n\
+             { a: 1, [Symbol(k)]: 2, [Symbol(Symbol.iterator)]: 3 }\n[k]\n"
+        );
+    }
+
+    #[test]
+    fn test_symbol_registry_and_errors() {
+        let source = r#"
+            console.log((Symbol.for('app') === Symbol.for('app')) + ' ' + Symbol.keyFor(Symbol.for('app')));
+            console.log(Symbol.keyFor(Symbol('app')) + ' ' + Symbol.keyFor(Symbol.iterator));
+            try { Symbol('x') + ''; } catch (e) { console.log(e.message); }
+            try { Symbol('x') * 1; } catch (e) { console.log(e.message); }
+            try { new Symbol(); } catch (e) { console.log(e.message); }
+            try { Symbol.keyFor('app'); } catch (e) { console.log(e.message); }
+            try { undefined[Symbol('x')]; } catch (e) { console.log(e.message); }
+            try { Object.defineProperty(Object.freeze({}), Symbol('y'), { value: 1 }); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "true app\nundefined undefined\n\
+             Cannot convert a Symbol value to a string\n\
+             Cannot convert a Symbol value to a number\n\
+             Symbol is not a constructor\n\
+             app is not a symbol\n\
+             Cannot read properties of undefined (reading 'Symbol(x)')\n\
+             Cannot define property Symbol(y), object is not extensible\n"
+        );
+    }
+
+    #[test]
+    fn test_well_known_symbols() {
+        let source = r#"
+            const range = { [Symbol.iterator]() {
+                let i = 0;
+                return { next() { i = i + 1; return { value: i, done: i > 3 }; } };
+            } };
+            for (const n of range) { console.log(n); }
+            const money = { [Symbol.toPrimitive](hint) { console.log(hint); return 42; } };
+            console.log(money + 1);
+            console.log(money * 2);
+            console.log(({ [Symbol.toStringTag]: 'Custom' }).toString() + ' ' + Math.toString() + ' ' + ({}).toString());
+            const Even = { [Symbol.hasInstance](n) { return n % 2 === 0; } };
+            function F() {}
+            console.log((2 instanceof Even) + ' ' + (3 instanceof Even) + ' ' + (new F() instanceof F) + ' ' + ({} instanceof F));
+            try { ({}) instanceof {}; } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "1\n2\n3\ndefault\n43\nnumber\n84\n[object Custom] [object Math] [object Object]\n\
+             true false true false\nRight-hand side of 'instanceof' is not callable\n"
+        );
+    }
+
+    #[test]
+    fn test_ordinary_to_primitive() {
+        let source = r#"
+            console.log('' + { valueOf: function () { return 7; } });
+            console.log(String({ toString: function () { return 'TS'; } }));
+            console.log({ valueOf: function () { return 2; } } * 3);
+            const both = { valueOf() { return 1; }, toString() { return 'two'; } };
+            console.log((both + 1) + ' ' + String(both));
+            try { '' + { toString() { return {}; }, valueOf() { return {}; } }; } catch (e) { console.log(e.name + ': ' + e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "7\nTS\n6\n2 two\nTypeError: Cannot convert object to primitive value\n"
+        );
+    }
+
+    #[test]
+    fn test_string_of_plain_objects() {
+        let source = r#"
+            const tagged = { [Symbol.toStringTag]: 'Thing' };
+            console.log(String({ a: 1 }) + ' ' + String(tagged) + ' ' + ('' + tagged));
+            console.log(String(/a\/b/gi) + ' ' + String([1, null, [2, 3], undefined]) + ' ' + (1 + [1, 2]));
+            const traps = { get(target, key) { if (key === 'valueOf') { return function () { return 42; }; } return undefined; } };
+            console.log(new Proxy({}, traps) + 1);
+        "#;
+        assert_eq!(
+            run(source),
+            "[object Object] [object Thing] [object Thing]\n/a\\/b/gi 1,,2,3, 11,2\n43\n"
+        );
+    }
+
+    #[test]
+    fn test_promise_species() {
+        let source = r#"
+            let made = 0;
+            function Tracked(executor) { made = made + 1; return new Promise(executor); }
+            const p = Promise.resolve(1);
+            p.constructor = { [Symbol.species]: Tracked };
+            p.then(function (x) { console.log(x + ' ' + made); });
+            const q = Promise.resolve(2);
+            q.constructor = { [Symbol.species]: 5 };
+            try { q.then(); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "object.constructor[Symbol.species] is not a constructor\n1 1\n"
+        );
+    }
+
+    // ============================================================================
+    // REGEXP
+    // ============================================================================
+
+    #[test]
+    fn test_regexp_exec_and_flags() {
+        let source = r#"
+            const m = /(?<year>\d{4})-(?<month>\d\d)/d.exec('on 2024-05!');
+            console.log(m[0] + ' ' + m.index + ' ' + m.groups.year + ' ' + m.groups.month);
+            console.log(m.indices[0][0] + ' ' + m.indices[0][1] + ' ' + m.indices.groups.month[0]);
+            const g = /o/g;
+            console.log(g.test('foo') + ' ' + g.lastIndex + ' ' + g.test('foo') + ' ' + g.lastIndex + ' ' + g.test('foo') + ' ' + g.lastIndex);
+            const y = /a/y;
+            y.lastIndex = 1;
+            console.log(y.test('ba') + ' ' + y.test('ba') + ' ' + y.lastIndex);
+            console.log(new RegExp('a/b', 'gimsuyd').flags + ' ' + /a\/b/i.source + ' ' + new RegExp('').toString());
+            console.log(RegExp.prototype.source + ' ' + RegExp.prototype.global + ' ' + /x/s.dotAll + ' ' + /x/.unicode);
+        "#;
+        assert_eq!(
+            run(source),
+            "2024-05 3 2024 05\n3 10 8\ntrue 2 true 3 false 0\ntrue false 0\n\
+             dgimsuy a\\/b /(?:)/\n(?:) undefined true false\n"
+        );
+    }
+
+    #[test]
+    fn test_regexp_engine() {
+        let source = r#"
+            console.log(/(?<=\$)\d+/.exec('cost $42')[0] + ' ' + /(?<!\$)\b\d+/.exec('$42 or 17')[0]);
+            console.log(/(\w)\1/.exec('hello')[0] + ' ' + /(?<c>l)\k<c>/.exec('hello').index);
+            console.log(/a{2,3}?/.exec('aaaa')[0] + ' ' + /(z)((a+)?(b+)?(c))*/.exec('zaacbbbcac')[3]);
+            console.log(/(a)|b/.exec('b')[1] + ' ' + /[^abc]+/.exec('abcdefabc')[0] + ' ' + /[\d-z]+/.exec('1-z')[0]);
+            console.log(/(?=(a+))a*b\1/.exec('baaabac')[0] + ' ' + /[a-z]+/i.exec('12ABc3')[0] + ' ' + /a{/.test('a{'));
+            const a = 8;
+            const b = 2;
+            console.log(a / b / 2 + ' ' + [/a/, /b/g].length + ' ' + /[/]/.test('/'));
+        "#;
+        assert_eq!(
+            run(source),
+            "42 17\nll 2\naa a\nundefined def 1-z\naba ABc true\n2 2 true\n"
+        );
+    }
+
+    #[test]
+    fn test_string_regexp_methods() {
+        let source = r#"
+            console.log('a1b22c333'.match(/\d+/g)[2] + ' ' + 'abc'.match(/z/) + ' ' + 'abc'.match(/b/).index);
+            for (const r of 'a1b2'.matchAll(/[a-z](\d)/g)) { console.log(r[0] + ':' + r[1] + '@' + r.index); }
+            console.log('John Smith'.replace(/(\w+)\s(\w+)/, '$2, $1') + ' ' + 'abc'.replace(/(?<x>b)/, '[$<x>$&$`$$]'));
+            console.log('aaa'.replace(/a/g, (m, offset) => offset) + ' ' + 'abc'.replace('b', (m) => m + m));
+            console.log('abc'.search(/c/) + ' ' + 'abc'.search('z'));
+            const parts = 'a,b;c'.split(/([,;])/);
+            console.log(parts.length + ' ' + parts[1] + ' ' + 'x-y-z'.split('-', 2).length + ' ' + 'test'.split(/(?:)/)[3]);
+            console.log('abc'.length + ' ' + 'abc'[1] + ' ' + String(12) + String());
+        "#;
+        assert_eq!(
+            run(source),
+            "333 null 1\na1:1@0\nb2:2@2\nSmith, John a[bba$]c\n012 abbc\n2 -1\n5 , 2 t\n3 b 12\n"
+        );
+    }
+
+    #[test]
+    fn test_regexp_errors() {
+        let source = r#"
+            try { new RegExp('(a'); } catch (e) { console.log(e.message); }
+            try { new RegExp('a', 'gg'); } catch (e) { console.log(e.message); }
+            try { new RegExp('a{2,1}'); } catch (e) { console.log(e.message); }
+            try { new RegExp('{', 'u'); } catch (e) { console.log(e.message); }
+            try { 'a'.matchAll(/a/); } catch (e) { console.log(e.message); }
+            const r = /a/g;
+            console.log((RegExp(r) === r) + ' ' + new RegExp(r, 'i').flags);
+        "#;
+        assert_eq!(
+            run(source),
+            "Invalid regular expression: /(a/: Unterminated group\n\
+             Invalid flags supplied to RegExp constructor 'gg'\n\
+             Invalid regular expression: /a{2,1}/: numbers out of order in {} quantifier\n\
+             Invalid regular expression: /{/u: Lone quantifier brackets\n\
+             String.prototype.matchAll called with a non-global RegExp argument\n\
+             true i\n"
+        );
+    }
+
+    // ============================================================================
+    // KEYED COLLECTIONS
+    // ============================================================================
+
+    #[test]
+    fn test_map_same_value_zero_and_order() {
+        let source = r#"
+            const m = new Map([[1, 'a'], [2, 'b']]);
+            m.set(NaN, 'nan');
+            m.set(-0, 'zero');
+            const key = {};
+            m.set(key, 'obj');
+            console.log(m.size + ' ' + m.get(NaN) + ' ' + m.get(0) + ' ' + m.get(key) + ' ' + m.get({}));
+            console.log(m.delete(2) + ' ' + m.delete(2) + ' ' + m.delete(key) + ' ' + Object.is([...m.keys()][2], 0));
+            let order = '';
+            for (const entry of m) { order = order + entry[0] + '=' + entry[1] + ';'; }
+            console.log(order);
+            console.log((m.set(1, 'c') === m) + ' ' + m.get(1) + ' ' + m.size);
+        "#;
+        assert_eq!(
+            run(source),
+            "5 nan zero obj undefined\ntrue false true true\n1=a;NaN=nan;0=zero;\ntrue c 3\n"
+        );
+    }
+
+    #[test]
+    fn test_set_and_iteration_during_mutation() {
+        let source = r#"
+            const s = new Set([1, 2, 3, 1, 1n, BigInt(1)]);
+            let seen = '';
+            s.forEach(function (v) { seen = seen + v + ','; if (v === 1) { s.delete(2); s.add(4); } });
+            console.log(s.size + ' ' + seen);
+            const entry = s.entries().next().value;
+            console.log(entry[0] + ':' + entry[1] + ' ' + (Set.prototype.keys === Set.prototype.values));
+            const big = new Map();
+            for (let i = 0; i < 100; i = i + 1) { big.set(i, i); }
+            const live = big.values();
+            for (let i = 0; i < 98; i = i + 1) { big.delete(i); }
+            console.log(live.next().value + ' ' + live.next().value + ' ' + live.next().done);
+            const iter = big.keys();
+            big.clear();
+            big.set('x', 1);
+            console.log(iter.next().value + ' ' + iter.next().done + ' ' + big.size);
+        "#;
+        assert_eq!(run(source), "4 1,3,1,4,\n1:1 true\n98 99 true\nx true 1\n");
+    }
+
+    #[test]
+    fn test_weak_collections() {
+        let source = r#"
+            const wm = new WeakMap();
+            const key = {};
+            const sym = Symbol('s');
+            wm.set(key, 5).set(sym, 6);
+            console.log(wm.get(key) + ' ' + wm.get(sym) + ' ' + wm.has({}) + ' ' + wm.get(1));
+            console.log(wm.delete(key) + ' ' + wm.has(key) + ' ' + wm.delete(1));
+            const ws = new WeakSet([key]);
+            console.log(ws.has(key) + ' ' + ws.delete(key) + ' ' + ws.has(key));
+            try { wm.set(1, 2); } catch (e) { console.log(e.message); }
+            try { ws.add(Symbol.for('x')); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "5 6 false undefined\ntrue false false\ntrue true false\n\
+             Invalid value used as weak map key\nInvalid value used in weak set\n"
+        );
+    }
+
+    #[test]
+    fn test_collection_errors_and_group_by() {
+        let source = r#"
+            try { Map(); } catch (e) { console.log(e.message); }
+            try { new Map([1]); } catch (e) { console.log(e.message); }
+            try { new Set(5); } catch (e) { console.log(e.message); }
+            const size = Object.getOwnPropertyDescriptor(Map.prototype, 'size').get;
+            try { size(); } catch (e) { console.log(e.message); }
+            const groups = Map.groupBy([1, 2, 3, 4], (x) => x % 2);
+            console.log(groups.size + ' ' + groups.get(1)[1] + ' ' + groups.get(0).length);
+        "#;
+        assert_eq!(
+            run(source),
+            "Constructor Map requires 'new'\nIterator value 1 is not an entry object\n5 is not iterable\n\
+             Method get Map.prototype.size called on incompatible receiver undefined\n2 3 2\n"
+        );
+    }
+
+    // ==========================================================================
+    // DATE
+    // ==========================================================================
+
+    /// Runs `source` at 2023-11-14T22:13:20Z on a virtual clock, with `time_zone` as local time.
+    fn run_at_fixed_time(source: &str, time_zone: TimeZone) -> String {
+        let mut interpreter = Interpreter::new()
+            .with_virtual_clock()
+            .with_time_origin(1_700_000_000_000.0)
+            .with_time_zone(time_zone)
+            .setup();
+        interpreter.interpret(source).unwrap().0
+    }
+
+    #[test]
+    fn test_date_now_and_local_time() {
+        let source = r#"
+            const d = new Date();
+            console.log(Date.now());
+            console.log(d.toString());
+            console.log(d.getHours() + ':' + d.getUTCHours() + ' ' + d.getTimezoneOffset());
+            console.log(d.toISOString());
+            console.log(d.toLocaleString());
+        "#;
+        assert_eq!(
+            run_at_fixed_time(source, TimeZone::Fixed(60)),
+            "1700000000000\nTue Nov 14 2023 23:13:20 GMT+0100 (GMT+01:00)\n23:22 -60\n\
+             2023-11-14T22:13:20.000Z\n11/14/2023, 11:13:20 PM\n"
+        );
+        let mut interpreter = Interpreter::new()
+            .with_virtual_clock()
+            .with_time_origin(0.0)
+            .with_time_zone(TimeZone::Utc)
+            .setup();
+        interpreter.interpret("").unwrap();
+        interpreter.advance_time(1500.0);
+        let (out, _) = interpreter
+            .interpret("console.log(Date.now() + ' ' + new Date().toUTCString());")
+            .unwrap();
+        assert_eq!(out, "1500 Thu, 01 Jan 1970 00:00:01 GMT\n");
+    }
+
+    #[test]
+    fn test_date_parse() {
+        let source = r#"
+            console.log(Date.parse('2023-11-14'));
+            console.log(Date.parse('2023-11-14T22:13:20'));
+            console.log(Date.parse('2023-11-14T22:13:20.5+01:00'));
+            console.log(Date.parse('Tue, 14 Nov 2023 22:13:20 GMT'));
+            console.log(Date.parse('November 14, 2023 10:13 PM'));
+            console.log(Date.parse('11/14/2023'));
+            console.log(Date.parse('2023-13-01') + ' ' + Date.parse('not a date'));
+            console.log(new Date('2023-11-14T10').getTime());
+        "#;
+        assert_eq!(
+            run_at_fixed_time(source, TimeZone::Fixed(-300)),
+            "1699920000000\n1700018000000\n1699996400500\n1700000000000\n\
+             1700017980000\n1699938000000\nNaN NaN\nNaN\n"
+        );
+    }
+
+    #[test]
+    fn test_date_components_and_setters() {
+        let source = r#"
+            const d = new Date(2020, 0, 31, 12);
+            console.log(d.getTime() + ' ' + Date.UTC(2020, 0, 31, 12) + ' ' + Date.UTC());
+            d.setMonth(1);
+            console.log(d.getMonth() + ' ' + d.getDate());
+            d.setUTCHours(25, 30);
+            console.log(d.toISOString());
+            console.log(new Date(99, 0).getFullYear() + ' ' + d.getDay() + ' ' + d.getYear());
+            const invalid = new Date(NaN);
+            console.log(invalid.setDate(1) + ' ' + invalid.toString());
+            invalid.setFullYear(2000);
+            console.log(invalid.toISOString());
+        "#;
+        assert_eq!(
+            run_at_fixed_time(source, TimeZone::Utc),
+            "1580472000000 1580472000000 NaN\n2 2\n2020-03-03T01:30:00.000Z\n\
+             1999 2 120\nNaN Invalid Date\n2000-01-01T00:00:00.000Z\n"
+        );
+    }
+
+    #[test]
+    fn test_date_conversions() {
+        let source = r#"
+            const d = new Date(Date.UTC(2000, 0, 1));
+            console.log(d - 0);
+            console.log(d + 1);
+            console.log(JSON.stringify({ when: d, never: new Date(NaN) }));
+            console.log(typeof Date() + ' ' + (new Date(d).getTime() === d.getTime()));
+            try { new Date(NaN).toISOString(); } catch (e) { console.log(e.message); }
+            try { d[Symbol.toPrimitive]('bogus'); } catch (e) { console.log(e.message); }
+            const getTime = Date.prototype.getTime;
+            try { getTime(); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run_at_fixed_time(source, TimeZone::Utc),
+            "946684800000\nSat Jan 01 2000 00:00:00 GMT+0000 (Coordinated Universal Time)1\n\
+             {\"when\":\"2000-01-01T00:00:00.000Z\",\"never\":null}\nstring true\n\
+             Invalid time value\nInvalid hint: 'bogus'\nthis is not a Date object.\n"
+        );
+    }
+
+    // ==========================================================================
+    // TYPED ARRAYS
+    // ==========================================================================
+
+    #[test]
+    fn test_array_buffers() {
+        let source = r#"
+            const buffer = new ArrayBuffer(4, { maxByteLength: 8 });
+            const fixed = new Uint8Array(buffer, 0, 4);
+            const tracking = new Uint8Array(buffer);
+            fixed[0] = 7;
+            buffer.resize(6);
+            console.log(buffer.byteLength + ' ' + fixed.length + ' ' + tracking.length);
+            buffer.resize(2);
+            console.log(fixed.length + ' ' + tracking.length + ' ' + tracking[0]);
+            const moved = buffer.transfer(3);
+            console.log(buffer.detached + ' ' + tracking.length + ' ' + new Uint8Array(moved)[0]);
+            try { tracking.fill(1); } catch (e) { console.log(e.message); }
+            try { moved.resize(9); } catch (e) { console.log(e.message); }
+            console.log(new ArrayBuffer(10).slice(2, -2).byteLength + ' ' + ArrayBuffer.isView(fixed));
+        "#;
+        assert_eq!(
+            run(source),
+            "6 4 6\n0 2 7\ntrue 0 7\n\
+             Cannot perform %TypedArray%.prototype.fill on a detached ArrayBuffer\n\
+             ArrayBuffer.prototype.resize: Invalid length parameter\n6 true\n"
+        );
+    }
+
+    #[test]
+    fn test_typed_array_element_conversions() {
+        let source = r#"
+            const bytes = new Uint8Array(3);
+            bytes[0] = 257; bytes[1] = -1; bytes[2] = 3.7;
+            console.log(bytes.join() + ' ' + bytes[3] + ' ' + bytes['-0']);
+            console.log(new Uint8ClampedArray([300, -5, 1.5, 2.5, 254.5]).join());
+            console.log(Int8Array.of(127, 128, 255, -129).join());
+            const big = new BigInt64Array([9223372036854775808n, -1n]);
+            console.log(big[0] + ' ' + new BigUint64Array(big.buffer)[1]);
+            console.log(new Float32Array([1.1])[0] + ' ' + new Float64Array(new Int8Array([1, -2])).join());
+            try { new BigInt64Array(bytes); } catch (e) { console.log(e.message); }
+            try { new Uint16Array(new ArrayBuffer(4), 1); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "1,255,3 undefined undefined\n255,0,2,2,254\n127,-128,-1,127\n\
+             -9223372036854775808 18446744073709551615\n1.100000023841858 1,-2\n\
+             Cannot mix BigInt and other types, use explicit conversions\n\
+             start offset of Uint16Array should be a multiple of 2\n"
+        );
+    }
+
+    #[test]
+    fn test_typed_array_methods() {
+        let source = r#"
+            const a = new Int16Array([5, 1, 4, 2, 3]);
+            console.log(a.sort().join() + ' ' + a.map(x => x * 2).join() + ' ' + a.filter(x => x % 2).join());
+            console.log(a.subarray(1, 3).join() + ' ' + a.slice(-2).join() + ' ' + a.indexOf(4) + ' ' + a.at(-1));
+            console.log(a.with(0, 9).join() + ' ' + a.toReversed().join() + ' ' + a.reduce((x, y) => x + y));
+            let keys = 0;
+            for (const key of a.keys()) keys += key;
+            console.log(keys + ' ' + Object.keys(a).length + ' ' + Object.getOwnPropertyDescriptor(a, '1').value);
+            const target = new Uint8Array(6);
+            target.set([1, 2], 1);
+            target.set(new Uint8Array([9, 8]), 4);
+            console.log(target.join() + ' ' + Uint8Array.from('123', x => x * 2).join());
+            try { Object.freeze(a); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "1,2,3,4,5 2,4,6,8,10 1,3,5\n2,3 4,5 3 5\n9,2,3,4,5 5,4,3,2,1 15\n\
+             10 5 2\n0,1,2,0,9,8 2,4,6\nCannot freeze array buffer views with elements\n"
+        );
+    }
+
+    #[test]
+    fn test_data_view_endianness() {
+        let source = r#"
+            const view = new DataView(new ArrayBuffer(8), 0);
+            view.setUint16(0, 4660);
+            view.setUint16(2, 4660, true);
+            console.log(view.getUint8(0) + ' ' + view.getUint8(1) + ' ' + view.getUint8(2) + ' ' + view.getUint8(3));
+            console.log(view.getUint16(0, true) + ' ' + view.getInt32(0));
+            view.setFloat64(0, Math.PI, true);
+            console.log(view.getFloat64(0, true) + ' ' + new Float64Array(view.buffer)[0]);
+            view.setBigInt64(0, -2n);
+            console.log(view.getBigUint64(0) + ' ' + view.getInt8(7));
+            try { view.getInt32(6); } catch (e) { console.log(e.name + ': ' + e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "18 52 52 18\n13330 305411090\n3.141592653589793 3.141592653589793\n\
+             18446744073709551614 -2\nRangeError: Offset is outside the bounds of the DataView\n"
+        );
+    }
+
+    #[test]
+    fn test_array_buffers_from_the_host() {
+        let mut interpreter = Interpreter::new().setup();
+        interpreter
+            .set_global_array_buffer("input", vec![1, 2, 3, 4])
+            .unwrap();
+        let source = r#"
+            const words = new Uint16Array(input);
+            console.log(words.length + ' ' + words[0]);
+            const output = new Uint8Array(input).map(x => x * 10).buffer;
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "2 513\n");
+        assert_eq!(
+            interpreter.take_global_array_buffer("output").unwrap(),
+            Some(vec![10, 20, 30, 40])
+        );
+        // taking the bytes detaches the buffer
+        assert_eq!(
+            interpreter.take_global_array_buffer("output").unwrap(),
+            None
+        );
+        assert_eq!(interpreter.take_global_array_buffer("words").unwrap(), None);
+    }
+
+    // ==========================================================================
+    // PROXY AND REFLECT
+    // ==========================================================================
+
+    #[test]
+    fn test_proxy_traps() {
+        let source = r#"
+            let trapped = '';
+            const target = { a: 1, b: 2 };
+            const proxy = new Proxy(target, {
+                get: (t, key, receiver) => {
+                    trapped = trapped + 'get:' + String(key) + ' ';
+                    return Reflect.get(t, key, receiver);
+                },
+                set: (t, key, value, receiver) => Reflect.set(t, key, value * 2, receiver),
+                has: (t, key) => {
+                    if (key === 'hidden') { return false; }
+                    return Reflect.has(t, key);
+                },
+                deleteProperty: (t, key) => {
+                    if (key === 'a') { return false; }
+                    return Reflect.deleteProperty(t, key);
+                },
+                ownKeys: t => ['b', 'a'],
+            });
+            proxy.c = 5;
+            console.log(proxy.a + ' ' + target.c + ' ' + trapped);
+            target.hidden = 1;
+            console.log(Reflect.has(proxy, 'a') + ' ' + Reflect.has(proxy, 'hidden'));
+            console.log(Reflect.deleteProperty(proxy, 'a') + ' ' + Reflect.deleteProperty(proxy, 'b'));
+            const keys = Object.keys(proxy);
+            console.log(keys.length + ' ' + keys[0] + ' ' + JSON.stringify(proxy));
+        "#;
+        assert_eq!(
+            run(source),
+            "1 10 get:a \ntrue false\nfalse true\n1 a {\"a\":1}\n"
+        );
+    }
+
+    #[test]
+    fn test_proxy_call_and_construct() {
+        let source = r#"
+            const add = new Proxy((x, y) => x + y, {
+                apply: (target, thisArg, args) => target(args[0], args[1]) * 10,
+            });
+            console.log(add(1, 2) + ' ' + typeof add);
+            function Point(x) { this.x = x; }
+            const Logged = new Proxy(Point, {
+                construct: (target, args, newTarget) => ({ x: args[0] + 100 }),
+            });
+            const Plain = new Proxy(Point, {});
+            const point = new Plain(7);
+            console.log(new Logged(1).x + ' ' + point.x + ' ' + (point instanceof Point));
+            try { Proxy({}, {}); } catch (e) { console.log(e.message); }
+            try { new Proxy(1, {}); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "30 function\n101 7 true\nConstructor Proxy requires 'new'\n\
+             Cannot create proxy with a non-object as target or handler\n"
+        );
+    }
+
+    #[test]
+    fn test_proxy_invariants_and_revocation() {
+        let source = r#"
+            const fixed = {};
+            Object.defineProperty(fixed, 'k', { value: 1 });
+            const liar = new Proxy(fixed, { get: () => 2, ownKeys: () => [] });
+            try { liar.k; } catch (e) { console.log(e.message); }
+            try { Object.getOwnPropertyNames(liar); } catch (e) { console.log(e.message); }
+            const stubborn = new Proxy({}, { preventExtensions: () => false });
+            console.log(Reflect.preventExtensions(stubborn));
+            try { Object.freeze(stubborn); } catch (e) { console.log(e.message); }
+            const revocable = Proxy.revocable({ x: 1 }, {});
+            const proxy = revocable.proxy;
+            console.log(proxy.x);
+            revocable.revoke();
+            revocable.revoke();
+            try { proxy.x; } catch (e) { console.log(e.name + ': ' + e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "'get' on proxy: property 'k' is a read-only and non-configurable data property on \
+             the proxy target but the proxy did not return its actual value (expected '1' but got '2')\n\
+             'ownKeys' on proxy: trap result did not include 'k'\n\
+             false\n'preventExtensions' on proxy: trap returned falsish\n1\n\
+             TypeError: Cannot perform 'get' on a proxy that has been revoked\n"
+        );
+    }
+
+    #[test]
+    fn test_reflect() {
+        let source = r#"
+            const base = { get who() { return this.name; } };
+            const holder = { name: 'holder' };
+            console.log(Reflect.setPrototypeOf(holder, base) + ' ' + holder.who);
+            console.log(Reflect.setPrototypeOf(base, holder) + ' ' + (Reflect.getPrototypeOf(holder) === base));
+            console.log(Reflect.get(base, 'who', { name: 'receiver' }));
+            console.log(Reflect.apply(Math.max, undefined, [1, 5, 3]));
+            function F() { this.made = true; }
+            function G() {}
+            const made = Reflect.construct(F, [], G);
+            console.log((made instanceof G) + ' ' + made.made);
+            const keys = Reflect.ownKeys({ z: 1, 1: 2, y: 3 });
+            console.log(keys[0] + ',' + keys[1] + ',' + keys[2]);
+            console.log(Reflect.defineProperty(Object.freeze({}), 'x', { value: 1 }));
+            const desc = Reflect.getOwnPropertyDescriptor({ q: 4 }, 'q');
+            console.log(desc.value + ' ' + desc.writable + ' ' + Reflect.isExtensible(Object.seal({})));
+            try { Reflect.get(1, 'a'); } catch (e) { console.log(e.message); }
+        "#;
+        assert_eq!(
+            run(source),
+            "true holder\nfalse true\nreceiver\n5\ntrue true\n1,z,y\nfalse\n4 true false\n\
+             Reflect.get called on non-object\n"
+        );
+    }
+
+    // ==========================================================================
+    // GARBAGE COLLECTION
+    // ==========================================================================
+
+    #[test]
+    fn test_gc_frees_unreachable_objects() {
+        let mut interpreter = Interpreter::new().setup();
+        interpreter.collect_garbage();
+        let baseline = interpreter.heap_stats();
+        let source = r#"
+            let total = 0;
+            for (let i = 0; i < 20000; i = i + 1) {
+                const garbage = { index: i, list: [i, i + 1] };
+                total = total + garbage.list[1];
+            }
+            console.log(total);
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "200010000\n");
+        let during = interpreter.heap_stats();
+        assert!(during.collections > baseline.collections);
+        interpreter.collect_garbage();
+        let after = interpreter.heap_stats();
+        assert!(after.freed_entries > 40000);
+        assert!(after.live_objects < baseline.live_objects + 50);
+    }
+
+    #[test]
+    fn test_gc_keeps_reachable_values() {
+        let mut interpreter = Interpreter::new()
+            .with_virtual_clock()
+            .with_gc_threshold(100)
+            .setup();
+        let source = r#"
+            let list = null;
+            for (let i = 0; i < 3000; i = i + 1) {
+                list = { value: i, next: list };
+            }
+            function counter() {
+                let count = 0;
+                return () => { count = count + 1; return count; };
+            }
+            const next = counter();
+            function* numbers() {
+                const seen = [];
+                while (true) {
+                    seen[seen.length] = { n: seen.length };
+                    yield seen;
+                }
+            }
+            const gen = numbers();
+            gen.next();
+            const map = new Map();
+            map.set({ key: 1 }, { value: 'mapped' });
+            let settled = new Promise(resolve => setTimeout(() => resolve({ done: 'late' }), 10));
+            for (let i = 0; i < 3000; i = i + 1) {
+                const garbage = [i];
+                next();
+            }
+        "#;
+        interpreter.interpret(source).unwrap();
+        interpreter.collect_garbage();
+        let source = r#"
+            let length = 0;
+            let node = list;
+            while (node !== null) {
+                length = length + 1;
+                node = node.next;
+            }
+            console.log(length + ' ' + list.value + ' ' + next());
+            console.log(gen.next().value[1].n);
+            map.forEach((value, key) => console.log(key.key + ' ' + value.value));
+            settled.then(result => console.log(result.done));
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "3000 2999 3001\n1\n1 mapped\n");
+        interpreter.collect_garbage();
+        let (out, _) = interpreter.advance_time(10.0);
+        assert_eq!(out, "3000 2999 3001\n1\n1 mapped\nlate\n");
+    }
+
+    #[test]
+    fn test_gc_drops_weak_map_entries() {
+        let mut interpreter = Interpreter::new().setup();
+        let source = r#"
+            const weak = new WeakMap();
+            const kept = {};
+            weak.set(kept, 'kept');
+            let key = {};
+            weak.set(key, { back: key, nested: {} });
+            const marks = new WeakSet();
+            marks.add(key);
+        "#;
+        interpreter.interpret(source).unwrap();
+        interpreter.collect_garbage();
+        let before = interpreter.heap_stats();
+        interpreter.interpret("key = null;").unwrap();
+        interpreter.collect_garbage();
+        let after = interpreter.heap_stats();
+        // the key, the value it mapped to and the object nested in that
+        assert_eq!(before.live_objects - after.live_objects, 3);
+        let (out, _) = interpreter
+            .interpret("console.log(weak.get(kept));")
+            .unwrap();
+        assert_eq!(out, "kept\n");
+    }
+
+    #[test]
+    fn test_gc_stats() {
+        let mut interpreter = Interpreter::new().with_gc_threshold(500).setup();
+        let initial = interpreter.heap_stats();
+        assert_eq!(initial.collections, 0);
+        assert!(initial.live_objects > 0);
+        assert!(initial.live_bytes > initial.live_entries);
+        let source = r#"
+            for (let i = 0; i < 10000; i = i + 1) {
+                const garbage = { i: i };
+            }
+        "#;
+        interpreter.interpret(source).unwrap();
+        let stats = interpreter.heap_stats();
+        assert!(stats.collections > 1);
+        assert!(stats.freed_entries > 0);
+        assert!(stats.total_pause >= stats.last_pause);
+        assert!(stats.total_pause > std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn test_gc_minor_collections_promote_survivors() {
+        let options = GcOptions {
+            nursery_size: 64,
+            ..GcOptions::default()
+        };
+        let mut interpreter = Interpreter::new().with_gc_options(options).setup();
+        let source = r#"
+            const kept = [];
+            for (let i = 0; i < 2000; i = i + 1) {
+                const garbage = { i: i };
+                if (i % 100 === 0) {
+                    kept[kept.length] = { i: i };
+                }
+            }
+            let total = 0;
+            for (let i = 0; i < kept.length; i = i + 1) {
+                total = total + kept[i].i;
+            }
+            console.log(total);
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "19000\n");
+        let stats = interpreter.heap_stats();
+        assert!(stats.minor_collections > 10);
+        assert!(stats.young_entries < 64 * 2);
+        assert!(stats.freed_entries > 2000);
+    }
+
+    #[test]
+    fn test_gc_write_barrier_keeps_young_objects() {
+        let options = GcOptions {
+            nursery_size: 16,
+            promotion_age: 1,
+            ..GcOptions::default()
+        };
+        let mut interpreter = Interpreter::new().with_gc_options(options).setup();
+        interpreter
+            .interpret("const holder = { items: [] }; let x = 0;")
+            .unwrap();
+        for _ in 0..20 {
+            interpreter.interpret("x = x + 1;").unwrap();
+        }
+        let source = r#"
+            for (let i = 0; i < 500; i = i + 1) {
+                holder.items[i] = { value: i, nested: { value: i } };
+                const garbage = [i, i];
+            }
+            let total = 0;
+            for (let i = 0; i < 500; i = i + 1) {
+                total = total + holder.items[i].nested.value;
+            }
+            console.log(total);
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "124750\n");
+        assert!(interpreter.heap_stats().minor_collections > 10);
+    }
+
+    #[test]
+    fn test_gc_incremental_marking() {
+        let options = GcOptions {
+            nursery_size: 32,
+            major_threshold: 50,
+            step_budget: 10,
+            ..GcOptions::default()
+        };
+        let mut interpreter = Interpreter::new().with_gc_options(options).setup();
+        let source = r#"
+            const table = new Map();
+            let list = null;
+            for (let i = 0; i < 5000; i = i + 1) {
+                list = { value: i, next: list };
+                table.set(i % 40, { latest: i });
+            }
+            let length = 0;
+            let node = list;
+            while (node !== null) {
+                length = length + 1;
+                node = node.next;
+            }
+            console.log(length + ' ' + table.get(39).latest);
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "5000 4999\n");
+        let stats = interpreter.heap_stats();
+        assert!(stats.collections > stats.minor_collections);
+        assert!(stats.incremental_steps > stats.collections - stats.minor_collections);
+        assert!(stats.max_pause >= stats.last_pause);
+    }
+
+    #[test]
+    fn test_gc_incremental_marking_keeps_up_with_allocation() {
+        let options = GcOptions {
+            generational: true,
+            nursery_size: 1,
+            promotion_age: 1,
+            major_threshold: 1,
+            incremental: true,
+            step_budget: 1,
+        };
+        let mut interpreter = Interpreter::new().with_gc_options(options).setup();
+        let source = r#"
+            let list = null;
+            let total = 0;
+            for (let i = 0; i < 20000; i = i + 1) {
+                const garbage = { index: i, list: [i, i + 1] };
+                total = total + garbage.list[1];
+                if (i % 10 === 0) { list = { value: i, next: list }; }
+            }
+            let length = 0;
+            while (list !== null) {
+                length = length + 1;
+                list = list.next;
+            }
+            console.log(total + ' ' + length);
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "200010000 2000\n");
+        let stats = interpreter.heap_stats();
+        assert!(stats.collections > stats.minor_collections);
+        assert!(stats.young_entries <= 1);
+        assert!(stats.freed_entries > 50_000);
+    }
+
+    #[test]
+    fn test_gc_pauses_do_not_grow_with_the_heap() {
+        let max_pause = |size: usize, array: bool| {
+            let mut interpreter = Interpreter::new().setup();
+            let fill = if array {
+                format!(
+                    "const keep = []; for (let i = 0; i < {size}; i = i + 1) {{ keep[i] = {{ index: i }}; }}"
+                )
+            } else {
+                format!(
+                    "let keep = null; for (let i = 0; i < {size}; i = i + 1) {{ keep = {{ index: i, next: keep }}; }}"
+                )
+            };
+            let churn = "for (let i = 0; i < 20000; i = i + 1) { const garbage = { index: i }; }";
+            interpreter.interpret(&fill).unwrap();
+            interpreter.interpret(churn).unwrap();
+            let stats = interpreter.heap_stats();
+            assert!(stats.live_entries > size);
+            stats.max_pause
+        };
+        for array in [false, true] {
+            let small = max_pause(4_000, array);
+            let large = max_pause(40_000, array);
+            assert!(
+                large < small * 4,
+                "{small:?} with a small heap, {large:?} with a large one"
+            );
+        }
+    }
+
+    #[test]
+    fn test_gc_options() {
+        let options = GcOptions {
+            generational: false,
+            incremental: false,
+            major_threshold: 500,
+            ..GcOptions::default()
+        };
+        let mut interpreter = Interpreter::new().with_gc_options(options).setup();
+        let source = r#"
+            for (let i = 0; i < 10000; i = i + 1) {
+                const garbage = { i: i };
+            }
+        "#;
+        interpreter.interpret(source).unwrap();
+        let stats = interpreter.heap_stats();
+        assert!(stats.collections > 1);
+        assert_eq!(stats.minor_collections, 0);
+        assert_eq!(stats.incremental_steps, 0);
+        assert_eq!(stats.young_entries, 0);
+    }
+
+    // ==========================================================================
+    // HANDLES
+    // ==========================================================================
+
+    #[test]
+    fn test_handles_read_and_call() {
+        let mut interpreter = Interpreter::new().setup();
+        let source = r#"
+            const config = { name: 'demo', list: [10, 20, 30] };
+            function add(a, b) { return a + b + this.offset; }
+        "#;
+        interpreter.interpret(source).unwrap();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let config = scope.get_global("config").unwrap();
+        let name = scope.get(&config, "name").unwrap();
+        assert_eq!(scope.to_rust_string(&name).unwrap(), "demo");
+        let list = scope.get(&config, "list").unwrap();
+        let list = list.cast::<Array>().unwrap();
+        assert_eq!(scope.length(&list).unwrap(), 3);
+        let second = scope.get_index(&list, 1).unwrap();
+        assert_eq!(second.as_number(), Some(20.0));
+
+        let add = scope.get_global("add").unwrap();
+        let add = add.cast::<Function>().unwrap();
+        let this = scope.new_object();
+        let offset = scope.number(0.5);
+        scope.set(&this, "offset", &offset).unwrap();
+        let arguments = [scope.number(1.0), scope.number(2.0)];
+        let sum = scope.call(&add, &this, &arguments).unwrap();
+        assert_eq!(sum.as_number(), Some(3.5));
+    }
+
+    #[test]
+    fn test_handle_casts() {
+        let mut interpreter = Interpreter::new().setup();
+        interpreter
+            .interpret("const items = [1]; function f() {}")
+            .unwrap();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let global = scope.global_object();
+        let number = scope.number(1.0);
+        assert!(number.clone().cast::<Object>().is_none());
+        assert!(number.cast::<Value>().is_some());
+        let items = scope.get_global("items").unwrap();
+        assert!(items.clone().cast::<Function>().is_none());
+        assert!(items.cast::<Object>().unwrap().cast::<Array>().is_some());
+        let f = scope.get_global("f").unwrap();
+        assert!(f.cast::<Function>().is_some());
+        let missing = scope.get(&global, "missing").unwrap();
+        assert!(missing.is_undefined());
+    }
+
+    #[test]
+    fn test_local_handles_are_rooted() {
+        let options = GcOptions {
+            nursery_size: 32,
+            major_threshold: 100,
+            ..GcOptions::default()
+        };
+        let mut interpreter = Interpreter::new().with_gc_options(options).setup();
+        let source = r#"
+            let held = { label: 'still here' };
+            function churn() {
+                held = null;
+                for (let i = 0; i < 2000; i = i + 1) {
+                    const garbage = { i: i };
+                }
+            }
+        "#;
+        interpreter.interpret(source).unwrap();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let held = scope.get_global("held").unwrap();
+        let churn = scope.get_global("churn").unwrap();
+        let churn = churn.cast::<Function>().unwrap();
+        let undefined = scope.undefined();
+        scope.call(&churn, &undefined, &[]).unwrap();
+        let label = scope.get(&held, "label").unwrap();
+        assert_eq!(scope.to_rust_string(&label).unwrap(), "still here");
+    }
+
+    #[test]
+    fn test_global_handles_outlive_scopes() {
+        let mut interpreter = Interpreter::new().setup();
+        interpreter
+            .interpret("let temp = { value: 42, nested: {} };")
+            .unwrap();
+        let handle = {
+            let mut scope = HandleScope::new(&mut interpreter);
+            let temp = scope.get_global("temp").unwrap();
+            Global::new(&mut scope, &temp.cast::<Object>().unwrap())
+        };
+        interpreter.interpret("temp = null;").unwrap();
+        interpreter.collect_garbage();
+        let held = interpreter.heap_stats();
+        {
+            let mut scope = HandleScope::new(&mut interpreter);
+            let temp = Local::new(&mut scope, &handle);
+            let value = scope.get(&temp, "value").unwrap();
+            assert_eq!(value.as_number(), Some(42.0));
+        }
+        drop(handle);
+        interpreter.collect_garbage();
+        assert_eq!(held.live_objects - interpreter.heap_stats().live_objects, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't belong to")]
+    fn test_handles_are_tied_to_their_interpreter() {
+        let mut first = Interpreter::new().setup();
+        let mut second = Interpreter::new().setup();
+        let handle = {
+            let mut scope = HandleScope::new(&mut first);
+            let global = scope.global_object();
+            Global::new(&mut scope, &global)
+        };
+        let mut scope = HandleScope::new(&mut second);
+        Local::new(&mut scope, &handle);
+    }
+
+    // ==========================================================================
+    // EMBEDDING
+    // ==========================================================================
+
+    #[test]
+    fn test_register_fn() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let mut interpreter = Interpreter::new().setup();
+        interpreter.register_fn("sum", move |scope, _this, args| {
+            counted.fetch_add(1, Ordering::Relaxed);
+            let total = args.iter().filter_map(|arg| arg.as_number()).sum();
+            Ok(scope.number(total))
+        });
+        let (out, _) = interpreter
+            .interpret("console.log(sum(1, 2, 3)); console.log(sum() + sum(4));")
+            .unwrap();
+        assert_eq!(out, "6\n4\n");
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_host_functions_throw_and_call_back() {
+        let mut interpreter = Interpreter::new().setup();
+        interpreter.register_fn("check", |scope, _this, args| match args.first() {
+            Some(arg) if arg.as_number().is_some() => Ok(arg.clone()),
+            _ => Err(scope.error("expected a number")),
+        });
+        interpreter.register_fn("twice", |scope, this, args| {
+            let callback = args.first().cloned().and_then(|arg| arg.cast::<Function>());
+            let Some(callback) = callback else {
+                return Err(scope.error("expected a function"));
+            };
+            let first = scope.call(&callback, &this, &[])?;
+            scope.call(&callback, &this, &[first])
+        });
+        let source = r#"
+            try {
+                check('nope');
+            } catch (e) {
+                console.log(e.message);
+            }
+            let count = 0;
+            console.log(twice(() => { count = count + 1; return count; }));
+            try {
+                twice(() => { throw { code: 7 }; });
+            } catch (e) {
+                console.log(e.code);
+            }
+            check();
+        "#;
+        let (out, err) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "expected a number\n2\n7\n");
+        assert_eq!(err, "Uncaught Error: expected a number\n");
+    }
+
+    #[test]
+    fn test_host_functions_are_freed_with_their_function() {
+        let state = Arc::new(AtomicUsize::new(0));
+        let mut interpreter = Interpreter::new().setup();
+        for _ in 0..100 {
+            let captured = state.clone();
+            let mut scope = HandleScope::new(&mut interpreter);
+            let function = scope.new_function("bump", move |scope, _this, _args| {
+                captured.fetch_add(1, Ordering::Relaxed);
+                Ok(scope.undefined())
+            });
+            let this = scope.undefined();
+            scope.call(&function, &this, &[]).unwrap();
+        }
+        assert_eq!(state.load(Ordering::Relaxed), 100);
+        interpreter.collect_garbage();
+        assert_eq!(Arc::strong_count(&state), 1);
+    }
+
+    #[test]
+    fn test_register_object() {
+        let mut interpreter = Interpreter::new().setup();
+        interpreter
+            .register_object("host")
+            .unwrap()
+            .method("greet", |scope, _this, args| {
+                let name = match args.first() {
+                    Some(name) => scope.to_rust_string(name)?,
+                    None => "nobody".to_string(),
+                };
+                Ok(scope.string(&format!("hello {name}")))
+            })
+            .method("version", |scope, _this, _args| Ok(scope.number(2.0)));
+        let (out, _) = interpreter
+            .interpret(
+                "console.log(host.greet('js') + ' ' + host.version()); console.log(host.greet());",
+            )
+            .unwrap();
+        assert_eq!(out, "hello js 2\nhello nobody\n");
+    }
+
+    #[test]
+    fn test_eval_returns_values_and_errors() {
+        let mut interpreter = Interpreter::new().setup();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let value = scope.eval("const base = 40; base + 2;").unwrap();
+        assert_eq!(value.as_number(), Some(42.0));
+        let thrown = scope.eval("throw { code: 7 };").unwrap_err();
+        let code = scope.get(&thrown, "code").unwrap();
+        assert_eq!(code.as_number(), Some(7.0));
+        let thrown = scope.eval("undefinedThing;").unwrap_err();
+        let name = scope.get(&thrown, "name").unwrap();
+        assert_eq!(scope.to_rust_string(&name).unwrap(), "ReferenceError");
+        assert!(scope.eval("let = ;").is_err());
+    }
+
+    #[test]
+    fn test_globals_from_rust() {
+        let mut interpreter = Interpreter::new().setup();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let limit = scope.number(21.0);
+        scope.set_global("limit", &limit).unwrap();
+        let settings = scope.new_object();
+        let flag = scope.boolean(true);
+        scope.set(&settings, "verbose", &flag).unwrap();
+        scope.set_global("settings", &settings).unwrap();
+        scope
+            .eval("function scale(x) { if (settings.verbose) { return x * limit; } return x; }")
+            .unwrap();
+        let scale = scope.get_global("scale").unwrap();
+        let scale = scale.cast::<Function>().unwrap();
+        let undefined = scope.undefined();
+        let two = scope.number(2.0);
+        let result = scope.call(&scale, &undefined, &[two]).unwrap();
+        assert_eq!(result.as_number(), Some(42.0));
+    }
+
+    // ==========================================================================
+    // SERDE
+    // ==========================================================================
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Shape {
+        Point,
+        Circle(f64),
+        Rect { width: f64, height: f64 },
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Config {
+        name: String,
+        retries: u32,
+        ratio: f64,
+        tags: Vec<String>,
+        limits: HashMap<String, i64>,
+        shapes: Vec<Shape>,
+        pair: (bool, char),
+        missing: Option<u8>,
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let config = Config {
+            name: "demo".to_string(),
+            retries: 3,
+            ratio: 0.5,
+            tags: vec!["a".to_string(), "b".to_string()],
+            limits: HashMap::from([("low".to_string(), -1), ("high".to_string(), 10)]),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(2.0),
+                Shape::Rect {
+                    width: 1.0,
+                    height: 2.0,
+                },
+            ],
+            pair: (true, 'x'),
+            missing: None,
+        };
+        let mut interpreter = Interpreter::new().setup();
+        interpreter.set_global_value("config", &config).unwrap();
+        let source = r#"
+            console.log(config.name + ' ' + config.retries + ' ' + config.tags[1]);
+            console.log(config.limits.high + ' ' + config.shapes[0] + ' ' + config.shapes[2].Rect.height);
+            console.log(config.missing === null);
+            config.retries = config.retries + 1;
+            config.shapes[1].Circle = 4;
+            config.missing = 7;
+        "#;
+        let (out, _) = interpreter.interpret(source).unwrap();
+        assert_eq!(out, "demo 3 b\n10 Point 2\ntrue\n");
+        let read: Config = interpreter.get_global_value("config").unwrap();
+        assert_eq!(read.retries, 4);
+        assert_eq!(read.shapes[1], Shape::Circle(4.0));
+        assert_eq!(read.missing, Some(7));
+        assert_eq!(read.limits, config.limits);
+        assert_eq!(read.pair, (true, 'x'));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_error_paths() {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Item {
+            price: f64,
+        }
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Order {
+            items: Vec<Item>,
+        }
+        let mut interpreter = Interpreter::new().setup();
+        let source = r#"
+            const wrongType = { items: [{ price: 1 }, { price: 2 }, { price: 3 }, { price: 'free' }] };
+            const missingField = { items: [{ price: 1 }, { cost: 2 }] };
+        "#;
+        interpreter.interpret(source).unwrap();
+        let error = interpreter
+            .get_global_value::<Order>("wrongType")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "expected number at .items[3].price");
+        assert_eq!(error.path(), ".items[3].price");
+        let error = interpreter
+            .get_global_value::<Order>("missingField")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "missing field `price` at .items[1]");
+        let error = interpreter
+            .get_global_value::<Vec<u8>>("wrongType")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "expected array");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_with_handles() {
+        let mut interpreter = Interpreter::new().setup();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let list = scope.to_js(&vec![3, 1, 2]).unwrap();
+        let array = list.clone().cast::<Array>().unwrap();
+        assert_eq!(scope.length(&array).unwrap(), 3);
+        scope.set_global("list", &list).unwrap();
+        let sorted = scope.eval("[list[1], list[2], list[0]];").unwrap();
+        let sorted: Vec<u32> = scope.from_js(&sorted).unwrap();
+        assert_eq!(sorted, vec![1, 2, 3]);
+        let negative = scope.number(-1.0);
+        assert!(scope.from_js::<u8>(&negative).is_err());
+        assert_eq!(scope.from_js::<i8>(&negative).unwrap(), -1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_integers_beyond_safe_range() {
+        let mut interpreter = Interpreter::new().setup();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let safe = scope.to_js(&9_007_199_254_740_991_u64).unwrap();
+        let beyond = scope.to_js(&9_007_199_254_740_993_u64).unwrap();
+        let max = scope.to_js(&u64::MAX).unwrap();
+        let min = scope.to_js(&i64::MIN).unwrap();
+        scope.set_global("safe", &safe).unwrap();
+        scope.set_global("beyond", &beyond).unwrap();
+        scope.set_global("max", &max).unwrap();
+        let types = scope
+            .eval("typeof safe + ' ' + typeof beyond + ' ' + beyond + ' ' + max;")
+            .unwrap();
+        assert_eq!(
+            scope.from_js::<String>(&types).unwrap(),
+            "number bigint 9007199254740993 18446744073709551615"
+        );
+        assert_eq!(scope.from_js::<u64>(&max).unwrap(), u64::MAX);
+        assert_eq!(scope.from_js::<i64>(&min).unwrap(), i64::MIN);
+        assert_eq!(
+            scope.from_js::<u128>(&beyond).unwrap(),
+            9_007_199_254_740_993
+        );
+        assert!(scope.from_js::<u32>(&max).is_err());
+
+        let large = scope.number(2f64.powi(60));
+        assert_eq!(scope.from_js::<u64>(&large).unwrap(), 1 << 60);
+        assert_eq!(scope.from_js::<i64>(&large).unwrap(), 1 << 60);
+        assert_eq!(scope.from_js::<f64>(&large).unwrap(), 2f64.powi(60));
+        let lowest = scope.number(-(2f64.powi(63)));
+        assert_eq!(scope.from_js::<i64>(&lowest).unwrap(), i64::MIN);
+        let too_large = scope.number(2f64.powi(64));
+        assert!(scope.from_js::<u64>(&too_large).is_err());
+        let wide = scope.number(256.0);
+        assert!(scope.from_js::<u8>(&wide).is_err());
+        let fraction = scope.number(1.5);
+        assert!(scope.from_js::<u64>(&fraction).is_err());
+    }
+
+    // ==========================================================================
+    // ISOLATES AND REALMS
+    // ==========================================================================
+
+    #[test]
+    fn test_interpreters_run_on_separate_threads() {
+        fn assert_send<T: Send>(_: &T) {}
+        let mut interpreter = Interpreter::new().setup();
+        interpreter.interpret("var base = 100;").unwrap();
+        assert_send(&interpreter);
+        let moved = std::thread::spawn(move || {
+            interpreter
+                .interpret("console.log(base + Symbol('moved').description.length);")
+                .unwrap()
+                .0
+        });
+        let workers: Vec<_> = (0..4)
+            .map(|n| {
+                std::thread::spawn(move || {
+                    let mut interpreter = Interpreter::new().setup();
+                    let source = format!(
+                        "let total = 0; for (let i = 0; i < 1000; i = i + 1) {{ total = total + {n}; }} console.log('worker ' + total);"
+                    );
+                    interpreter.interpret(&source).unwrap().0
+                })
+            })
+            .collect();
+        assert_eq!(moved.join().unwrap(), "105\n");
+        for (n, worker) in workers.into_iter().enumerate() {
+            assert_eq!(worker.join().unwrap(), format!("worker {}\n", n * 1000));
+        }
+    }
+
+    #[test]
+    fn test_interpreters_have_their_own_strings() {
+        let mut first = Interpreter::new().setup();
+        let mut second = Interpreter::new().setup();
+        second
+            .interpret("var padding = ['one', 'two', 'three', 'four', 'five'];")
+            .unwrap();
+        let mut first_scope = HandleScope::new(&mut first);
+        let mut second_scope = HandleScope::new(&mut second);
+        let greeting = first_scope.string("hello from the first");
+        let other = second_scope.string("hello from the second");
+        let symbol = first_scope.eval("Symbol('first').toString();").unwrap();
+        assert_eq!(
+            first_scope.to_rust_string(&greeting).unwrap(),
+            "hello from the first"
+        );
+        assert_eq!(
+            second_scope.to_rust_string(&other).unwrap(),
+            "hello from the second"
+        );
+        assert_eq!(
+            first_scope.to_rust_string(&symbol).unwrap(),
+            "Symbol(first)"
+        );
+    }
+
+    #[test]
+    fn test_realms_have_their_own_globals() {
+        let mut interpreter = Interpreter::new().setup();
+        let main = interpreter.current_realm();
+        interpreter.interpret("var shared = 'main';").unwrap();
+        let sandbox = interpreter.create_realm();
+        assert_eq!(interpreter.enter_realm(sandbox), main);
+        let (out, err) = interpreter
+            .interpret(
+                "console.log(typeof shared); var shared = 'sandbox'; Object.prototype.extra = 1;",
+            )
+            .unwrap();
+        assert_eq!(out, "undefined\n");
+        assert_eq!(err, "");
+        interpreter.enter_realm(main);
+        let (out, _) = interpreter
+            .interpret("console.log(shared); console.log(typeof ({}).extra);")
+            .unwrap();
+        assert_eq!(out, "undefined\nmain\nundefined\n");
+        assert_eq!(interpreter.current_realm(), main);
+    }
+
+    #[test]
+    fn test_realms_share_values() {
+        let mut interpreter = Interpreter::new().setup();
+        let main = interpreter.current_realm();
+        let sandbox = interpreter.create_realm();
+        interpreter.enter_realm(sandbox);
+        interpreter
+            .interpret("var name = 'sandbox'; function whose() { return name; }")
+            .unwrap();
+        let (map, whose) = {
+            let mut scope = HandleScope::new(&mut interpreter);
+            let map = scope.eval("new Map([['key', 'value']]);").unwrap();
+            let whose = scope.get_global("whose").unwrap();
+            (
+                Global::new(&mut scope, &map),
+                Global::new(&mut scope, &whose),
+            )
+        };
+        interpreter.enter_realm(main);
+        interpreter.collect_garbage();
+        let mut scope = HandleScope::new(&mut interpreter);
+        scope.eval("var name = 'main';").unwrap();
+        let map = Local::new(&mut scope, &map);
+        let whose = Local::new(&mut scope, &whose);
+        scope.set_global("map", &map).unwrap();
+        scope.set_global("whose", &whose).unwrap();
+        let result = scope
+            .eval("(map instanceof Map) + ' ' + map.get('key') + ' ' + whose();")
+            .unwrap();
+        assert_eq!(
+            scope.to_rust_string(&result).unwrap(),
+            "false value sandbox"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "realm used with an interpreter it doesn't belong to")]
+    fn test_realms_are_tied_to_their_interpreter() {
+        let mut first = Interpreter::new().setup();
+        let mut second = Interpreter::new().setup();
+        let realm = first.create_realm();
+        second.enter_realm(realm);
+    }
+
+    // ==========================================================================
+    // CONSOLE
+    // ==========================================================================
+
+    #[test]
+    fn test_console_levels_and_format_specifiers() {
+        let source = r#"
+            console.log('%s is %d years and %i days', 'Bob', 42.5, 3.9, 'extra', 7);
+            console.log('100%% done', 1, 'two');
+            console.info('info'); console.debug('debug');
+            console.warn('careful'); console.error('broken %o', 'quoted');
+            console.trace('here'); console.trace();
+            console.assert(1 === 1, 'not shown'); console.assert(false, 'got %d', 2); console.assert(0);
+            console.dir('text');
+        "#;
+        let (out, err) = run_and_capture(source);
+        assert_eq!(
+            out,
+            "Bob is 42.5 years and 3 days extra 7\n100% done 1 two\ninfo\ndebug\n'text'\n"
+        );
+        assert_eq!(
+            err,
+            "careful\nbroken 'quoted'\nTrace: here\nTrace\nAssertion failed: got 2\nAssertion failed\n"
+        );
+    }
+
+    #[test]
+    fn test_console_count_time_and_group() {
+        let mut interpreter = Interpreter::new().with_virtual_clock().setup();
+        let source = r#"
+            console.count(); console.count(); console.count('other'); console.countReset();
+            console.count(); console.countReset('missing');
+            console.group('outer');
+            console.log('inside');
+            console.group();
+            console.warn('deeper');
+            console.groupEnd();
+            console.groupEnd();
+            console.groupEnd();
+            console.log('outside');
+            console.time('load');
+            console.time('load');
+            setTimeout(() => console.timeLog('load', 'halfway'), 250);
+            setTimeout(() => console.timeEnd('load'), 1500);
+            setTimeout(() => console.timeEnd('load'), 2000);
+        "#;
+        interpreter.interpret(source).unwrap();
+        let (out, err) = interpreter.advance_time(2000.0);
+        assert_eq!(
+            out,
+            "default: 1\ndefault: 2\nother: 1\ndefault: 1\nouter\n  inside\noutside\nload: 250ms halfway\nload: 1.5s\n"
+        );
+        assert_eq!(
+            err,
+            "Warning: Count for 'missing' does not exist\n    deeper\nWarning: Label 'load' already exists for console.time()\nWarning: No such label 'load' for console.timeEnd()\n"
+        );
+    }
+
+    #[test]
+    fn test_console_table() {
+        let source = r#"
+            console.table([{ a: 1, b: 'x' }, { a: 22, c: true }]);
+            console.table({ first: 5, second: { a: 1 } }, ['a']);
+            console.table('not tabular');
+        "#;
+        let (out, _) = run_and_capture(source);
+        let expected = [
+            "┌─────────┬────┬─────┬──────┐",
+            "│ (index) │ a  │ b   │ c    │",
+            "├─────────┼────┼─────┼──────┤",
+            "│ 0       │ 1  │ 'x' │      │",
+            "│ 1       │ 22 │     │ true │",
+            "└─────────┴────┴─────┴──────┘",
+            "┌─────────┬───┬────────┐",
+            "│ (index) │ a │ Values │",
+            "├─────────┼───┼────────┤",
+            "│ first   │   │ 5      │",
+            "│ second  │ 1 │        │",
+            "└─────────┴───┴────────┘",
+            "not tabular",
+            "",
+        ];
+        assert_eq!(out, expected.join("\n"));
+    }
+
+    /// Keeps every message with its level, group depth and the numbers among its arguments.
+    #[derive(Clone, Default)]
+    struct RecordingConsole {
+        messages: Arc<Mutex<Vec<(LogLevel, String, usize, Vec<f64>)>>>,
+    }
+
+    impl Console for RecordingConsole {
+        fn message<'i>(&mut self, _scope: &mut HandleScope<'i>, message: &ConsoleMessage<'_, 'i>) {
+            let numbers = message
+                .arguments
+                .iter()
+                .filter_map(|argument| argument.as_number())
+                .collect();
+            self.messages.lock().unwrap().push((
+                message.level,
+                message.text.to_string(),
+                message.group_depth,
+                numbers,
+            ));
+        }
+    }
+
+    #[test]
+    fn test_host_console_receives_values() {
+        let console = RecordingConsole::default();
+        let mut interpreter = Interpreter::new().with_console(console.clone()).setup();
+        let source = r#"
+            console.group('totals');
+            console.info('%d items', 3, 4.5);
+            console.groupEnd();
+            missing();
+        "#;
+        let (out, err) = interpreter.interpret(source).unwrap();
+        assert_eq!((out.as_str(), err.as_str()), ("", ""));
+        let messages = console.messages.lock().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0],
+            (LogLevel::Log, "totals".to_string(), 0, vec![])
+        );
+        assert_eq!(
+            messages[1],
+            (LogLevel::Info, "3 items 4.5".to_string(), 1, vec![3.0, 4.5])
+        );
+        assert_eq!(messages[2].0, LogLevel::Error);
+        assert!(messages[2].1.contains("missing is not defined"));
+    }
+
+    #[test]
+    fn test_buffer_console_can_be_shared() {
+        let console = BufferConsole::new();
+        let mut interpreter = Interpreter::new().with_console(console.clone()).setup();
+        interpreter
+            .interpret("console.log('one'); console.error('two');")
+            .unwrap();
+        assert_eq!(console.take(), ("one\n".to_string(), "two\n".to_string()));
+        interpreter.interpret("console.log('three');").unwrap();
+        assert_eq!(console.output(), "three\n");
+        assert_eq!(console.errors(), "");
+    }
+
+    // ==========================================================================
+    // INSPECT
+    // ==========================================================================
+
+    #[test]
+    fn test_inspect_nested_values() {
+        let source = r#"
+            var nested = { id: 7, 'first name': 'Ada', tags: ['x', "it's"], deep: { a: { b: { c: 1 } } } };
+            console.log(nested);
+            var m = new Map(); m.set('k', { v: [1, 2] }); var s = new Set(); s.add(1); s.add('two');
+            console.log(m, s, new Map());
+            var sparse = []; sparse[2] = 'c'; sparse.length = 5;
+            console.log(sparse, -0, 5n, Symbol('s'));
+            console.dir(nested, { depth: 0 });
+        "#;
+        assert_eq!(
+            run(source),
+            concat!(
+                "{\n",
+                "  id: 7,\n",
+                "  'first name': 'Ada',\n",
+                "  tags: [ 'x', \"it's\" ],\n",
+                "  deep: { a: { b: [Object] } }\n",
+                "}\n",
+                "Map(1) { 'k' => { v: [ 1, 2 ] } } Set(2) { 1, 'two' } Map(0) {}\n",
+                "[ <2 empty items>, 'c', <2 empty items> ] -0 5n Symbol(s)\n",
+                "{ id: 7, 'first name': 'Ada', tags: [Array], deep: [Object] }\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_inspect_circular_references() {
+        let source = r#"
+            var node = { name: 'root', children: [] };
+            node.children[0] = { name: 'leaf', parent: node };
+            node.self = node;
+            console.log(node);
+            var m = new Map(); m.set('me', m);
+            console.log(m);
+        "#;
+        assert_eq!(
+            run(source),
+            concat!(
+                "<ref *1> {\n",
+                "  name: 'root',\n",
+                "  children: [ { name: 'leaf', parent: [Circular *1] } ],\n",
+                "  self: [Circular *1]\n",
+                "}\n",
+                "<ref *1> Map(1) { 'me' => [Circular *1] }\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_inspect_functions_and_classes() {
+        let source = r#"
+            function named() {}
+            class Shape { constructor() { this.sides = 4; } }
+            console.log(named, function () {}, Shape, new Shape());
+            console.log({ run: async function go() {}, gen: function* steps() {} });
+            console.log({ get value() { return 1; }, set value(v) {}, get only() { return 2; } });
+        "#;
+        assert_eq!(
+            run(source),
+            concat!(
+                "[Function: named] [Function (anonymous)] [class Shape] Shape { sides: 4 }\n",
+                "{ run: [AsyncFunction: go], gen: [GeneratorFunction: steps] }\n",
+                "{ value: [Getter/Setter], only: [Getter] }\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_inspect_groups_long_arrays() {
+        let source = r#"
+            var words = []; for (var i = 0; i < 26; i = i + 1) { words[i] = 'w' + i; }
+            console.log(words);
+            var numbers = []; for (var i = 0; i < 110; i = i + 1) { numbers[i] = i + 1; }
+            console.log(numbers);
+        "#;
+        assert_eq!(
+            run(source),
+            concat!(
+                "[\n",
+                "  'w0',  'w1',  'w2',  'w3',\n",
+                "  'w4',  'w5',  'w6',  'w7',\n",
+                "  'w8',  'w9',  'w10', 'w11',\n",
+                "  'w12', 'w13', 'w14', 'w15',\n",
+                "  'w16', 'w17', 'w18', 'w19',\n",
+                "  'w20', 'w21', 'w22', 'w23',\n",
+                "  'w24', 'w25'\n",
+                "]\n",
+                "[\n",
+                "   1,  2,  3,   4,  5,  6,  7,  8,  9, 10, 11, 12,\n",
+                "  13, 14, 15,  16, 17, 18, 19, 20, 21, 22, 23, 24,\n",
+                "  25, 26, 27,  28, 29, 30, 31, 32, 33, 34, 35, 36,\n",
+                "  37, 38, 39,  40, 41, 42, 43, 44, 45, 46, 47, 48,\n",
+                "  49, 50, 51,  52, 53, 54, 55, 56, 57, 58, 59, 60,\n",
+                "  61, 62, 63,  64, 65, 66, 67, 68, 69, 70, 71, 72,\n",
+                "  73, 74, 75,  76, 77, 78, 79, 80, 81, 82, 83, 84,\n",
+                "  85, 86, 87,  88, 89, 90, 91, 92, 93, 94, 95, 96,\n",
+                "  97, 98, 99, 100,\n",
+                "  ... 10 more items\n",
+                "]\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_inspect_line_breaking_and_format_specifiers() {
+        let source = r#"
+            console.log({ description: 'a fairly long string value', other: 'another long string value', last: [1, 2, 3] });
+            console.log('%s | %o | %O', { a: { b: 1 } }, { a: { b: { c: { d: { e: 1 } } } } }, { a: { b: { c: { d: 1 } } } });
+        "#;
+        assert_eq!(
+            run(source),
+            concat!(
+                "{\n",
+                "  description: 'a fairly long string value',\n",
+                "  other: 'another long string value',\n",
+                "  last: [ 1, 2, 3 ]\n",
+                "}\n",
+                "{ a: [Object] } | {\n",
+                "  a: {\n",
+                "    b: { c: { d: { e: 1 } } }\n",
+                "  }\n",
+                "} | { a: { b: { c: [Object] } } }\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_handle_scope_inspect_with_colors() {
+        let mut interpreter = Interpreter::new().setup();
+        let mut scope = HandleScope::new(&mut interpreter);
+        let value = scope.eval("[1, 'a', null, undefined];").unwrap();
+        let plain = scope.inspect(&value, &InspectOptions::default());
+        assert_eq!(plain, "[ 1, 'a', null, undefined ]");
+        let options = InspectOptions {
+            colors: true,
+            ..InspectOptions::default()
+        };
+        assert_eq!(
+            scope.inspect(&value, &options),
+            "[ \u{1b}[33m1\u{1b}[39m, \u{1b}[32m'a'\u{1b}[39m, \u{1b}[1mnull\u{1b}[22m, \u{1b}[90mundefined\u{1b}[39m ]"
+        );
+    }
 }